@@ -0,0 +1,121 @@
+"""Thread-safety tests for the Rust kernel.
+
+Batch entry points release the GIL while Rust computes, so calling them from
+several Python threads must give the same results as calling them serially.
+"""
+
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+
+langviz_core = pytest.importorskip("langviz_core")
+
+
+FORMS = ["pater", "fater", "mater", "t͡ʃaːpʰ", "vasɐ", "wɔːtə", "akʷa", ""]
+PAIRS = [(a, b) for a in FORMS for b in FORMS]
+EDGES = [
+    ("a", "b", 0.9),
+    ("b", "c", 0.85),
+    ("c", "a", 0.8),
+    ("d", "e", 0.95),
+    ("e", "f", 0.6),
+]
+
+
+def run_concurrently(fn, workers=8):
+    with ThreadPoolExecutor(max_workers=workers) as pool:
+        return list(pool.map(lambda _: fn(), range(workers)))
+
+
+class TestGilReleasedBatches:
+    """Results computed with the GIL released match serial results."""
+
+    def test_batch_phonetic_distance(self):
+        serial = [langviz_core.py_phonetic_distance(a, b) for a, b in PAIRS]
+        for result in run_concurrently(lambda: langviz_core.py_batch_phonetic_distance(PAIRS)):
+            assert result == serial
+
+    def test_batch_feature_distance(self):
+        serial = [langviz_core.py_feature_distance(a, b) for a, b in PAIRS]
+        for result in run_concurrently(lambda: langviz_core.py_batch_feature_distance(PAIRS)):
+            assert result == serial
+
+    def test_batch_dtw_align(self):
+        serial = [langviz_core.py_dtw_align(a, b).cost for a, b in PAIRS]
+        for result in run_concurrently(lambda: langviz_core.py_batch_dtw_align(PAIRS)):
+            assert [cost for cost, _ in result] == serial
+        costs_only = langviz_core.py_batch_dtw_align(PAIRS, with_correspondences=False)
+        assert [cost for cost, _ in costs_only] == serial
+
+    def test_similarity_matrix(self):
+        expected = langviz_core.py_compute_similarity_matrix(FORMS)
+        for result in run_concurrently(lambda: langviz_core.py_compute_similarity_matrix(FORMS)):
+            assert (result == expected).all()
+
+    def test_graph_construction_and_queries(self):
+        expected_sets = sorted(
+            sorted(s.members) for s in langviz_core.py_find_cognate_sets(EDGES, 0.7)
+        )
+
+        def build_and_query():
+            graph = langviz_core.py_build_cognate_graph(EDGES, 0.7)
+            return sorted(sorted(s.members) for s in graph.find_cognate_sets())
+
+        for result in run_concurrently(build_and_query):
+            assert result == expected_sets
+
+
+class TestSharedHandles:
+    """One loaded handle serves concurrent queries and in-place updates."""
+
+    def test_concurrent_queries_on_one_graph(self):
+        graph = langviz_core.py_build_cognate_graph(EDGES, 0.7)
+        expected = sorted(sorted(s.members) for s in graph.find_cognate_sets())
+
+        def query():
+            return sorted(sorted(s.members) for s in graph.find_cognate_sets())
+
+        for result in run_concurrently(query):
+            assert result == expected
+
+    def test_updates_alongside_queries(self):
+        graph = langviz_core.py_build_cognate_graph(EDGES, 0.7)
+
+        def update_and_query():
+            graph.add_edges([("x", "y", 0.9)])
+            return graph.num_nodes()
+
+        for nodes in run_concurrently(update_and_query):
+            assert nodes >= 7
+        # Every update lands: one parallel x-y edge per worker
+        assert graph.num_nodes() == 7
+        assert graph.num_edges() == 4 + 8
+
+
+class TestBackgroundJobs:
+    """Submitted jobs give the same results as the blocking calls."""
+
+    def test_similarity_matrix_job(self):
+        job = langviz_core.py_submit_similarity_matrix(FORMS, block_size=2)
+        result = job.result(timeout=60)
+        assert job.status() == "done"
+        assert job.progress() == (28, 28)
+        assert (result == langviz_core.py_compute_similarity_matrix(FORMS)).all()
+
+    def test_pipeline_job(self):
+        ids = [f"w{i}" for i in range(len(FORMS))]
+        job = langviz_core.py_submit_pipeline(ids, FORMS, 0.7)
+        assert job.wait(timeout=60)
+        graph, cognate_sets = job.result()
+        assert graph.num_nodes() == len(FORMS)
+        assert sum(s.size for s in cognate_sets) == len(FORMS)
+
+    def test_resume_from_checkpoint(self, tmp_path):
+        path = str(tmp_path / "run.ckpt")
+        ids = [f"w{i}" for i in range(len(FORMS))]
+        job = langviz_core.py_submit_similarity_matrix_sparse(
+            ids, FORMS, 0.5, block_size=2, checkpoint=path
+        )
+        expected = job.result(timeout=60)
+        resumed = langviz_core.py_resume_job(path).result(timeout=60)
+        assert resumed.nnz() == expected.nnz()
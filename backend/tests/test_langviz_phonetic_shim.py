@@ -0,0 +1,50 @@
+"""Cross-implementation tests for the deprecated langviz_phonetic shim.
+
+Both extensions wrap the shared phonetic-core kernels, so the shim must give
+bit-identical results to langviz_core on every input, empty forms included.
+"""
+
+import subprocess
+import sys
+import warnings
+
+import pytest
+
+langviz_core = pytest.importorskip("langviz_core")
+with warnings.catch_warnings():
+    warnings.simplefilter("ignore", DeprecationWarning)
+    langviz_phonetic = pytest.importorskip("langviz_phonetic")
+
+
+FORMS = ["pater", "pitar", "fater", "t͡ʃaːpʰ", "tʃaːp", "ma˥˩", "ˈma.ma", "akʷa", ""]
+PAIRS = [(a, b) for a in FORMS for b in FORMS]
+
+
+class TestShimMatchesCore:
+    def test_phonetic_distance(self):
+        for a, b in PAIRS:
+            expected = langviz_core.py_phonetic_distance(a, b)
+            assert langviz_phonetic.phonetic_distance(a, b) == expected
+
+    def test_batch_phonetic_distance(self):
+        expected = langviz_core.py_batch_phonetic_distance(PAIRS)
+        assert langviz_phonetic.batch_phonetic_distance(PAIRS) == expected
+
+    def test_lcs_ratio(self):
+        for a, b in PAIRS:
+            assert langviz_phonetic.lcs_ratio(a, b) == langviz_core.py_lcs_ratio(a, b)
+
+    def test_empty_forms_are_identical(self):
+        assert langviz_phonetic.phonetic_distance("", "") == 1.0
+        assert langviz_core.py_phonetic_distance("", "") == 1.0
+
+
+def test_import_warns():
+    # Extension modules initialize once per process, so import in a fresh one
+    result = subprocess.run(
+        [sys.executable, "-W", "error::DeprecationWarning", "-c", "import langviz_phonetic"],
+        capture_output=True,
+        text=True,
+    )
+    assert result.returncode != 0
+    assert "langviz_core" in result.stderr
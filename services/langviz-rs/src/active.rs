@@ -0,0 +1,263 @@
+//! Active-learning query strategy: which unlabeled pairs a curator should
+//! label next.
+//!
+//! The current clustering is single linkage over the pairs scoring at or
+//! above the threshold, with labeled pairs overriding their scores
+//! (positives always link, negatives never do). A pair is informative when
+//! its score is close to the threshold, so the model is unsure of it, and
+//! when its label would change the clustering a lot. The impact of a pair
+//! is the number of item pairs whose co-membership its label flips: a
+//! rejected pair between two clusters would join them (|A|·|B| pairs), an
+//! accepted pair that is a bridge of its cluster would split it, and any
+//! other pair changes nothing. Bridges and the sizes of the sides they
+//! separate come from one depth-first search over the accepted graph.
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::error::LangVizError;
+
+/// An unlabeled pair worth labeling
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairSuggestion {
+    pub source: String,
+    pub target: String,
+    pub score: f64,
+    /// 1 at the threshold, 0 at the far end of the score range
+    pub uncertainty: f64,
+    /// Item pairs whose co-membership the pair's label would flip
+    pub changed_pairs: u64,
+    /// uncertainty · ln(1 + changed_pairs)
+    pub informativeness: f64,
+}
+
+/// The `top_k` most informative of the (source, target, score) candidates
+/// not already in the (source, target, is_cognate) labels, scores in [0, 1];
+/// most informative first, then most uncertain
+pub fn suggest_pairs(
+    candidates: &[(String, String, f64)],
+    labeled: &[(String, String, bool)],
+    threshold: f64,
+    top_k: usize,
+) -> Result<Vec<PairSuggestion>, LangVizError> {
+    if !(threshold > 0.0 && threshold < 1.0) {
+        return Err(LangVizError::InvalidParameter {
+            name: "threshold".to_string(),
+            value: threshold,
+            expected: "a value in (0, 1)".to_string(),
+        });
+    }
+    if let Some(&(_, _, score)) = candidates.iter().find(|c| !(0.0..=1.0).contains(&c.2)) {
+        return Err(LangVizError::InvalidParameter {
+            name: "score".to_string(),
+            value: score,
+            expected: "a value in [0, 1]".to_string(),
+        });
+    }
+
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let pairs = labeled.iter().map(|(a, b, _)| (a, b));
+    for (a, b) in pairs.chain(candidates.iter().map(|(a, b, _)| (a, b))) {
+        for id in [a, b] {
+            let next = index.len();
+            index.entry(id).or_insert(next);
+        }
+    }
+    let labels: HashSet<(&str, &str)> = labeled.iter().map(|(a, b, _)| key(a, b)).collect();
+
+    let mut edges = Vec::new();
+    for (a, b, _) in labeled.iter().filter(|l| l.2) {
+        edges.push((index[a.as_str()], index[b.as_str()]));
+    }
+    // Unlabeled candidates with their endpoints and accepted edge, if any
+    let mut open = Vec::new();
+    for (c, (a, b, score)) in candidates.iter().enumerate() {
+        if labels.contains(&key(a, b)) {
+            continue;
+        }
+        let (u, v) = (index[a.as_str()], index[b.as_str()]);
+        let edge = (*score >= threshold).then(|| {
+            edges.push((u, v));
+            edges.len() - 1
+        });
+        open.push((c, u, v, edge));
+    }
+    let clustering = BridgeSearch::run(index.len(), &edges);
+
+    let span = threshold.max(1.0 - threshold);
+    let mut suggestions: Vec<PairSuggestion> = open
+        .into_par_iter()
+        .map(|(c, u, v, edge)| {
+            let (source, target, score) = &candidates[c];
+            let changed_pairs = match edge {
+                Some(edge) => clustering.split_sizes.get(&edge).copied().unwrap_or(0),
+                None if clustering.component[u] != clustering.component[v] => {
+                    clustering.size_of(u) * clustering.size_of(v)
+                }
+                None => 0,
+            };
+            let uncertainty = 1.0 - (score - threshold).abs() / span;
+            PairSuggestion {
+                source: source.clone(),
+                target: target.clone(),
+                score: *score,
+                uncertainty,
+                changed_pairs,
+                informativeness: uncertainty * (changed_pairs as f64).ln_1p(),
+            }
+        })
+        .collect();
+    // Stable, so full ties keep candidate order
+    suggestions.sort_by(|a, b| {
+        b.informativeness
+            .total_cmp(&a.informativeness)
+            .then(b.uncertainty.total_cmp(&a.uncertainty))
+    });
+    suggestions.truncate(top_k);
+    Ok(suggestions)
+}
+
+/// Unordered pair key
+fn key<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Connected components and bridges of an undirected multigraph
+struct BridgeSearch {
+    component: Vec<usize>,
+    component_sizes: Vec<u64>,
+    /// Pairs a bridge's removal would separate, by edge index
+    split_sizes: HashMap<usize, u64>,
+}
+
+impl BridgeSearch {
+    /// Iterative Tarjan search: a tree edge to `child` is a bridge when
+    /// nothing below `child` reaches above it
+    fn run(n: usize, edges: &[(usize, usize)]) -> Self {
+        let mut adjacency = vec![Vec::new(); n];
+        for (e, &(u, v)) in edges.iter().enumerate() {
+            if u != v {
+                adjacency[u].push((v, e));
+                adjacency[v].push((u, e));
+            }
+        }
+        const UNSEEN: usize = usize::MAX;
+        let mut entered = vec![UNSEEN; n];
+        let mut low = vec![0; n];
+        let mut below = vec![1u64; n];
+        let mut component = vec![0; n];
+        let mut component_sizes = Vec::new();
+        // (child, edge to its parent) per bridge
+        let mut bridges = Vec::new();
+        let mut clock = 0;
+
+        for root in 0..n {
+            if entered[root] != UNSEEN {
+                continue;
+            }
+            let id = component_sizes.len();
+            // (node, edge it was entered by, next neighbor to visit)
+            let mut stack = vec![(root, UNSEEN, 0)];
+            entered[root] = clock;
+            low[root] = clock;
+            clock += 1;
+            component[root] = id;
+            while let Some(&mut (u, via, ref mut next)) = stack.last_mut() {
+                if let Some(&(v, e)) = adjacency[u].get(*next) {
+                    *next += 1;
+                    if e == via {
+                        continue;
+                    }
+                    if entered[v] == UNSEEN {
+                        entered[v] = clock;
+                        low[v] = clock;
+                        clock += 1;
+                        component[v] = id;
+                        stack.push((v, e, 0));
+                    } else {
+                        low[u] = low[u].min(entered[v]);
+                    }
+                    continue;
+                }
+                stack.pop();
+                if let Some(&(parent, _, _)) = stack.last() {
+                    low[parent] = low[parent].min(low[u]);
+                    below[parent] += below[u];
+                    if low[u] > entered[parent] {
+                        bridges.push((u, via));
+                    }
+                }
+            }
+            component_sizes.push(below[root]);
+        }
+
+        let split_sizes = bridges
+            .into_iter()
+            .map(|(child, edge)| {
+                let size = component_sizes[component[child]];
+                (edge, below[child] * (size - below[child]))
+            })
+            .collect();
+        Self {
+            component,
+            component_sizes,
+            split_sizes,
+        }
+    }
+
+    fn size_of(&self, node: usize) -> u64 {
+        self.component_sizes[self.component[node]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(a: &str, b: &str, score: f64) -> (String, String, f64) {
+        (a.to_string(), b.to_string(), score)
+    }
+
+    #[test]
+    fn test_suggest_pairs() {
+        // Clusters {a, b, c} (c hangs on a bridge) and {d, e}
+        let candidates = vec![
+            pair("a", "b", 0.9),
+            pair("b", "c", 0.55),
+            pair("d", "e", 0.8),
+            pair("c", "d", 0.45),
+            pair("a", "e", 0.1),
+            pair("b", "x", 0.48),
+        ];
+        let labeled = vec![("a".to_string(), "c".to_string(), false)];
+        let ranked = suggest_pairs(&candidates, &labeled, 0.5, 10).unwrap();
+        let order: Vec<(&str, u64)> = ranked
+            .iter()
+            .map(|s| (s.target.as_str(), s.changed_pairs))
+            .collect();
+        // Joining {a, b, c} and {d, e} flips 6 pairs; cutting c off flips 2
+        assert_eq!(
+            order,
+            [("d", 6), ("x", 3), ("c", 2), ("e", 6), ("e", 1), ("b", 2)]
+        );
+        assert!((ranked[0].uncertainty - 0.9).abs() < 1e-12);
+
+        // A positive label closes a cycle, so b-c is no longer a bridge
+        let labeled = vec![("a".to_string(), "c".to_string(), true)];
+        let ranked = suggest_pairs(&candidates, &labeled, 0.5, 10).unwrap();
+        let b_c = ranked.iter().find(|s| s.target == "c").unwrap();
+        assert_eq!(b_c.changed_pairs, 0);
+        assert_eq!(ranked.len(), 6);
+    }
+
+    #[test]
+    fn test_rejects_bad_scores() {
+        let candidates = vec![pair("a", "b", 1.5)];
+        assert!(suggest_pairs(&candidates, &[], 0.5, 1).is_err());
+        assert!(suggest_pairs(&[], &[], 1.0, 1).is_err());
+    }
+}
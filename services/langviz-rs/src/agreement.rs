@@ -0,0 +1,194 @@
+//! Inter-annotator agreement on cognate partitions.
+//!
+//! Annotators partition the same items into cognate sets, with their own
+//! arbitrary set labels, so agreement is measured on the decisions the
+//! partitions imply: for every pair of items, whether they share a set.
+//! Cohen's kappa compares two annotators on those binary decisions and
+//! Fleiss' kappa all of them at once. Every count comes from the overlaps
+//! between sets (a pair agrees "together" in as many ways as two items can
+//! be drawn from an overlap), so the pairs are never enumerated and
+//! thousands of items are cheap.
+
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Chance-corrected agreement of several annotators' partitions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Agreement {
+    pub annotators: Vec<String>,
+    pub items: usize,
+    /// Item pairs, i.e. decisions per annotator
+    pub pairs: u64,
+    /// Cohen's kappa of each annotator pair, indexed like `annotators`
+    /// (1 on the diagonal)
+    pub cohen: Vec<Vec<f64>>,
+    pub fleiss: f64,
+    /// Mean fraction of annotator pairs agreeing on an item pair
+    pub observed: f64,
+}
+
+/// Agreement of (annotator, partition) annotations, which must all cover
+/// the same items, each in exactly one set
+pub fn annotator_agreement(
+    annotations: &[(String, Vec<Vec<String>>)],
+) -> Result<Agreement, String> {
+    if annotations.len() < 2 {
+        return Err("agreement needs at least two annotators".to_string());
+    }
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for set in &annotations[0].1 {
+        for item in set {
+            let next = index.len();
+            index.entry(item).or_insert(next);
+        }
+    }
+    let n = index.len();
+    if n < 2 {
+        return Err("agreement needs at least two items".to_string());
+    }
+
+    // Set of each item, per annotator
+    let labels = annotations
+        .iter()
+        .map(|(annotator, partition)| {
+            let mut label = vec![usize::MAX; n];
+            for (s, set) in partition.iter().enumerate() {
+                for item in set {
+                    let &i = index.get(item.as_str()).ok_or_else(|| {
+                        format!(
+                            "'{}' of annotator '{}' is not annotated by '{}'",
+                            item, annotator, annotations[0].0
+                        )
+                    })?;
+                    if label[i] != usize::MAX {
+                        return Err(format!(
+                            "annotator '{}' puts '{}' in more than one set",
+                            annotator, item
+                        ));
+                    }
+                    label[i] = s;
+                }
+            }
+            if let Some(i) = label.iter().position(|&l| l == usize::MAX) {
+                let item = index.iter().find(|(_, &j)| j == i).unwrap().0;
+                return Err(format!("annotator '{}' leaves '{}' out", annotator, item));
+            }
+            Ok(label)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let m = labels.len();
+    let pairs = choose2(n as u64);
+    // Pairs each annotator (diagonal) or both annotators put together
+    let mut together = vec![vec![0u64; m]; m];
+    let counts: Vec<(usize, usize, u64)> = (0..m)
+        .flat_map(|a| (a..m).map(move |b| (a, b)))
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(a, b)| (a, b, pairs_together(&labels[a], &labels[b])))
+        .collect();
+    for (a, b, count) in counts {
+        together[a][b] = count;
+        together[b][a] = count;
+    }
+
+    let total = pairs as f64;
+    let cohen = (0..m)
+        .map(|a| {
+            (0..m)
+                .map(|b| {
+                    let (same_a, same_b) = (together[a][a] as f64, together[b][b] as f64);
+                    let both = together[a][b] as f64;
+                    let observed = (total - same_a - same_b + 2.0 * both) / total;
+                    let (pa, pb) = (same_a / total, same_b / total);
+                    kappa(observed, pa * pb + (1.0 - pa) * (1.0 - pb))
+                })
+                .collect()
+        })
+        .collect();
+
+    // Fleiss over the item pairs, with k of m annotators saying "together":
+    // Σ k = Σ_a same_a and Σ k² = Σ_a Σ_b together[a][b]
+    let m_f = m as f64;
+    let sum_k: f64 = (0..m).map(|a| together[a][a] as f64).sum();
+    let sum_k2: f64 = together.iter().flatten().map(|&c| c as f64).sum();
+    let agreeing = 2.0 * sum_k2 - 2.0 * m_f * sum_k + total * (m_f * m_f - m_f);
+    let observed = agreeing / (total * m_f * (m_f - 1.0));
+    let p = sum_k / (total * m_f);
+    let fleiss = kappa(observed, p * p + (1.0 - p) * (1.0 - p));
+
+    Ok(Agreement {
+        annotators: annotations.iter().map(|(a, _)| a.clone()).collect(),
+        items: n,
+        pairs,
+        cohen,
+        fleiss,
+        observed,
+    })
+}
+
+fn choose2(k: u64) -> u64 {
+    k * k.saturating_sub(1) / 2
+}
+
+/// Item pairs in the same set under both labelings
+fn pairs_together(a: &[usize], b: &[usize]) -> u64 {
+    let mut overlaps: HashMap<(usize, usize), u64> = HashMap::new();
+    for (&x, &y) in a.iter().zip(b) {
+        *overlaps.entry((x, y)).or_insert(0) += 1;
+    }
+    overlaps.values().map(|&c| choose2(c)).sum()
+}
+
+/// Chance-corrected agreement; when chance agreement is certain the
+/// observed agreement is too, and it counts as perfect
+fn kappa(observed: f64, expected: f64) -> f64 {
+    if expected >= 1.0 {
+        1.0
+    } else {
+        (observed - expected) / (1.0 - expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation(name: &str, sets: &[&[&str]]) -> (String, Vec<Vec<String>>) {
+        let sets = sets
+            .iter()
+            .map(|set| set.iter().map(|s| s.to_string()).collect())
+            .collect();
+        (name.to_string(), sets)
+    }
+
+    #[test]
+    fn test_annotator_agreement() {
+        let a = annotation("a", &[&["w", "x"], &["y", "z"]]);
+        let b = annotation("b", &[&["z"], &["w", "x", "y"]]);
+        let result = annotator_agreement(&[a.clone(), b]).unwrap();
+        assert_eq!((result.items, result.pairs), (4, 6));
+        // 3 of 6 pair decisions agree, as chance predicts
+        assert!(result.cohen[0][1].abs() < 1e-12);
+        assert_eq!(result.cohen[1][1], 1.0);
+        assert!((result.observed - 0.5).abs() < 1e-12);
+        assert!((result.fleiss + 1.0 / 35.0).abs() < 1e-12);
+
+        let same = annotation("c", &[&["y", "z"], &["x", "w"]]);
+        let result = annotator_agreement(&[a.clone(), same.clone(), a.clone()]).unwrap();
+        assert_eq!(result.fleiss, 1.0);
+        assert!(result.cohen.iter().flatten().all(|&k| k == 1.0));
+    }
+
+    #[test]
+    fn test_rejects_mismatched_items() {
+        let a = annotation("a", &[&["w", "x"], &["y"]]);
+        let missing = annotation("b", &[&["w", "x"]]);
+        let extra = annotation("b", &[&["w", "x", "y", "v"]]);
+        let twice = annotation("b", &[&["w", "x"], &["y", "x"]]);
+        for other in [missing, extra, twice] {
+            assert!(annotator_agreement(&[a.clone(), other]).is_err());
+        }
+        assert!(annotator_agreement(&[a]).is_err());
+    }
+}
@@ -0,0 +1,439 @@
+//! Ancestral cognate-state reconstruction: where on a tree each cognate set
+//! was gained and lost.
+//!
+//! Presence of a set at the leaves is given; its states at the inner nodes
+//! are reconstructed either by weighted (Sankoff) parsimony, with a gain
+//! costing `gain_penalty` losses and the set absent above the root, or by
+//! marginal likelihood under a two-state gain/loss model with the given
+//! rates, the root at the model's equilibrium and branch lengths as times
+//! (one unit per branch when the tree has no lengths). The likelihood
+//! posteriors come from one inside and one outside pass; an event is
+//! reported on a branch when its posterior probability reaches
+//! `min_probability`. Parsimony events have probability 1.
+
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::tree::Tree;
+
+/// Reconstruction method and its parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reconstruction {
+    Parsimony { gain_penalty: f64 },
+    Likelihood { gain_rate: f64, loss_rate: f64 },
+}
+
+/// Gains and losses on the branch above one node, as (set, probability)
+/// pairs; the root's "branch" holds the sets present in the ancestor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BranchEvents {
+    pub node: String,
+    pub parent: Option<String>,
+    pub length: f64,
+    pub gains: Vec<(usize, f64)>,
+    pub losses: Vec<(usize, f64)>,
+}
+
+/// Reconstructed states and the events on every branch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AncestralStates {
+    /// Node names in tree preorder
+    pub nodes: Vec<String>,
+    /// Probability that each set is present, per node (preorder) and set
+    pub presence: Vec<Vec<f64>>,
+    /// Per node, in preorder
+    pub branches: Vec<BranchEvents>,
+}
+
+impl AncestralStates {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("ancestral states serialize")
+    }
+}
+
+/// Reconstruct each of `sets`, given as the languages (leaf names)
+/// attesting it, in parallel
+pub fn reconstruct_ancestral_states(
+    tree: &Tree,
+    sets: &[Vec<String>],
+    method: Reconstruction,
+    min_probability: f64,
+) -> Result<AncestralStates, String> {
+    match method {
+        Reconstruction::Parsimony { gain_penalty } => {
+            if !gain_penalty.is_finite() || gain_penalty <= 0.0 {
+                return Err(format!(
+                    "gain penalty {} must be finite and > 0",
+                    gain_penalty
+                ));
+            }
+        }
+        Reconstruction::Likelihood {
+            gain_rate,
+            loss_rate,
+        } => {
+            let valid = |rate: f64| rate.is_finite() && rate > 0.0;
+            if !valid(gain_rate) || !valid(loss_rate) {
+                return Err(format!(
+                    "gain rate {} and loss rate {} must be finite and > 0",
+                    gain_rate, loss_rate
+                ));
+            }
+        }
+    }
+    if !(0.0..=1.0).contains(&min_probability) {
+        return Err(format!(
+            "min_probability {} must be in [0, 1]",
+            min_probability
+        ));
+    }
+
+    let order = tree.preorder();
+    let present = leaf_presence(tree, sets)?;
+    let has_lengths = tree.nodes.iter().any(|node| node.length > 0.0);
+    let time = |node: usize| {
+        if has_lengths {
+            tree.nodes[node].length
+        } else {
+            1.0
+        }
+    };
+
+    let per_set: Vec<SetReconstruction> = present
+        .par_iter()
+        .map(|present| match method {
+            Reconstruction::Parsimony { gain_penalty } => {
+                let state = parsimony_states(tree, &order, present, gain_penalty);
+                let events = (0..tree.nodes.len())
+                    .map(|node| {
+                        let parent = tree.nodes[node].parent.map_or(0, |p| state[p]);
+                        match (parent, state[node]) {
+                            (0, 1) => (1.0, 0.0),
+                            (1, 0) => (0.0, 1.0),
+                            _ => (0.0, 0.0),
+                        }
+                    })
+                    .collect();
+                (state.iter().map(|&s| s as f64).collect(), events)
+            }
+            Reconstruction::Likelihood {
+                gain_rate,
+                loss_rate,
+            } => {
+                let model = GainLoss::new(gain_rate, loss_rate);
+                model.posteriors(tree, &order, present, time)
+            }
+        })
+        .collect();
+
+    let nodes = order.iter().map(|&node| tree.node_name(node)).collect();
+    let presence = order
+        .iter()
+        .map(|&node| per_set.iter().map(|(p, _)| p[node]).collect())
+        .collect();
+    let branches = order
+        .iter()
+        .map(|&node| {
+            let mut gains = Vec::new();
+            let mut losses = Vec::new();
+            for (s, (_, events)) in per_set.iter().enumerate() {
+                let (gain, loss) = events[node];
+                if gain > 0.0 && gain >= min_probability {
+                    gains.push((s, gain));
+                }
+                if loss > 0.0 && loss >= min_probability {
+                    losses.push((s, loss));
+                }
+            }
+            let current = &tree.nodes[node];
+            BranchEvents {
+                node: tree.node_name(node),
+                parent: current.parent.map(|p| tree.node_name(p)),
+                length: current.length,
+                gains,
+                losses,
+            }
+        })
+        .collect();
+    Ok(AncestralStates {
+        nodes,
+        presence,
+        branches,
+    })
+}
+
+/// Presence probability and (gain, loss) probability of one set, per node
+type SetReconstruction = (Vec<f64>, Vec<(f64, f64)>);
+
+/// Presence per node (arena order) of each set; inner nodes are false
+pub(crate) fn leaf_presence(tree: &Tree, sets: &[Vec<String>]) -> Result<Vec<Vec<bool>>, String> {
+    let leaf: HashMap<String, usize> = tree
+        .leaves()
+        .into_iter()
+        .map(|node| (tree.node_name(node), node))
+        .collect();
+    sets.iter()
+        .enumerate()
+        .map(|(s, languages)| {
+            let mut present = vec![false; tree.nodes.len()];
+            for language in languages {
+                let &node = leaf.get(language).ok_or_else(|| {
+                    format!(
+                        "language '{}' of set {} is not a leaf of the tree",
+                        language, s
+                    )
+                })?;
+                present[node] = true;
+            }
+            Ok(present)
+        })
+        .collect()
+}
+
+/// Most parsimonious state (0 absent, 1 present) of every node, ties
+/// resolved towards the parent's state
+pub(crate) fn parsimony_states(
+    tree: &Tree,
+    order: &[usize],
+    present: &[bool],
+    gain_penalty: f64,
+) -> Vec<usize> {
+    let change = |from: usize, to: usize| match (from, to) {
+        (0, 1) => gain_penalty,
+        (1, 0) => 1.0,
+        _ => 0.0,
+    };
+    // Sankoff costs of the subtree below each node, per state
+    let mut cost = vec![[0.0f64; 2]; tree.nodes.len()];
+    for &node in order.iter().rev() {
+        let children = &tree.nodes[node].children;
+        if children.is_empty() {
+            let state = present[node] as usize;
+            cost[node][1 - state] = f64::INFINITY;
+            continue;
+        }
+        for state in 0..2 {
+            cost[node][state] = children
+                .iter()
+                .map(|&child| {
+                    (0..2)
+                        .map(|to| cost[child][to] + change(state, to))
+                        .fold(f64::INFINITY, f64::min)
+                })
+                .sum();
+        }
+    }
+
+    // Traceback from an absent ancestor of the root
+    let mut state = vec![0; tree.nodes.len()];
+    for &node in order {
+        let parent = tree.nodes[node].parent.map_or(0, |p| state[p]);
+        let stay = cost[node][parent] + change(parent, parent);
+        let flip = cost[node][1 - parent] + change(parent, 1 - parent);
+        state[node] = if flip < stay { 1 - parent } else { parent };
+    }
+    state
+}
+
+/// Two-state continuous-time gain/loss model
+struct GainLoss {
+    rate: f64,
+    /// Equilibrium frequency of presence
+    equilibrium: f64,
+}
+
+impl GainLoss {
+    fn new(gain_rate: f64, loss_rate: f64) -> Self {
+        Self {
+            rate: gain_rate + loss_rate,
+            equilibrium: gain_rate / (gain_rate + loss_rate),
+        }
+    }
+
+    /// P(from → to) over time `t`
+    fn transitions(&self, t: f64) -> [[f64; 2]; 2] {
+        let change = 1.0 - (-self.rate * t).exp();
+        let gain = self.equilibrium * change;
+        let loss = (1.0 - self.equilibrium) * change;
+        [[1.0 - gain, gain], [loss, 1.0 - loss]]
+    }
+
+    /// Posterior presence per node and (gain, loss) posterior per branch.
+    /// Partial likelihoods are rescaled to sum to 1 at every node, which
+    /// leaves the posteriors unchanged and avoids underflow.
+    fn posteriors(
+        &self,
+        tree: &Tree,
+        order: &[usize],
+        present: &[bool],
+        time: impl Fn(usize) -> f64,
+    ) -> SetReconstruction {
+        let n = tree.nodes.len();
+        let transitions: Vec<[[f64; 2]; 2]> =
+            (0..n).map(|node| self.transitions(time(node))).collect();
+        // Likelihood of each child's subtree seen from its parent's states
+        let from_parent = |inside: &[[f64; 2]], child: usize| -> [f64; 2] {
+            let p = &transitions[child];
+            let l = inside[child];
+            [
+                p[0][0] * l[0] + p[0][1] * l[1],
+                p[1][0] * l[0] + p[1][1] * l[1],
+            ]
+        };
+
+        let mut inside = vec![[1.0f64; 2]; n];
+        for &node in order.iter().rev() {
+            let children = &tree.nodes[node].children;
+            let mut l = if children.is_empty() {
+                if present[node] {
+                    [0.0, 1.0]
+                } else {
+                    [1.0, 0.0]
+                }
+            } else {
+                let mut l = [1.0, 1.0];
+                for &child in children {
+                    let m = from_parent(&inside, child);
+                    l = [l[0] * m[0], l[1] * m[1]];
+                }
+                l
+            };
+            normalize(&mut l);
+            inside[node] = l;
+        }
+
+        // Outside: likelihood of everything but the node's subtree
+        let mut outside = vec![[0.0f64; 2]; n];
+        outside[tree.root] = [1.0 - self.equilibrium, self.equilibrium];
+        let mut presence = vec![0.0; n];
+        let mut events = vec![(0.0, 0.0); n];
+        for &node in order {
+            let mut posterior = [
+                outside[node][0] * inside[node][0],
+                outside[node][1] * inside[node][1],
+            ];
+            normalize(&mut posterior);
+            presence[node] = posterior[1];
+            if node == tree.root {
+                events[node] = (posterior[1], 0.0);
+            }
+
+            let children = &tree.nodes[node].children;
+            for &child in children {
+                // Parent state weights excluding this child's subtree
+                let mut above = outside[node];
+                for &sibling in children.iter().filter(|&&s| s != child) {
+                    let m = from_parent(&inside, sibling);
+                    above = [above[0] * m[0], above[1] * m[1]];
+                }
+                normalize(&mut above);
+
+                let p = &transitions[child];
+                let l = inside[child];
+                let mut joint = [
+                    [above[0] * p[0][0] * l[0], above[0] * p[0][1] * l[1]],
+                    [above[1] * p[1][0] * l[0], above[1] * p[1][1] * l[1]],
+                ];
+                let total: f64 = joint.iter().flatten().sum();
+                if total > 0.0 {
+                    joint.iter_mut().flatten().for_each(|x| *x /= total);
+                }
+                events[child] = (joint[0][1], joint[1][0]);
+
+                let mut out = [
+                    above[0] * p[0][0] + above[1] * p[1][0],
+                    above[0] * p[0][1] + above[1] * p[1][1],
+                ];
+                normalize(&mut out);
+                outside[child] = out;
+            }
+        }
+        (presence, events)
+    }
+}
+
+fn normalize(v: &mut [f64; 2]) {
+    let total = v[0] + v[1];
+    if total > 0.0 {
+        v[0] /= total;
+        v[1] /= total;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sets(languages: &[&[&str]]) -> Vec<Vec<String>> {
+        languages
+            .iter()
+            .map(|set| set.iter().map(|l| l.to_string()).collect())
+            .collect()
+    }
+
+    fn tree() -> Tree {
+        Tree::from_newick("(((a:1,b:1)ab:1,c:2)abc:1,(d:1,e:1)de:2)root;").unwrap()
+    }
+
+    #[test]
+    fn test_parsimony_reconstruction() {
+        let tree = tree();
+        let cognates = sets(&[&["a", "b", "c"], &["a", "b", "c", "d"]]);
+        let method = Reconstruction::Parsimony { gain_penalty: 2.0 };
+        let states = reconstruct_ancestral_states(&tree, &cognates, method, 0.5).unwrap();
+        assert_eq!(states.nodes[0], "root");
+        let events: Vec<(&str, Vec<usize>, Vec<usize>)> = states
+            .branches
+            .iter()
+            .filter(|b| !b.gains.is_empty() || !b.losses.is_empty())
+            .map(|b| {
+                let sets = |events: &[(usize, f64)]| events.iter().map(|e| e.0).collect();
+                (b.node.as_str(), sets(&b.gains), sets(&b.losses))
+            })
+            .collect();
+        // Set 0 arises in abc; set 1 is ancestral and lost in e
+        assert_eq!(
+            events,
+            [
+                ("root", vec![1], vec![]),
+                ("abc", vec![0], vec![]),
+                ("e", vec![], vec![1]),
+            ]
+        );
+        let abc = states.nodes.iter().position(|n| n == "abc").unwrap();
+        assert_eq!(states.presence[abc], [1.0, 1.0]);
+        assert_eq!(states.branches[abc].parent.as_deref(), Some("root"));
+    }
+
+    #[test]
+    fn test_likelihood_reconstruction() {
+        let tree = tree();
+        let cognates = sets(&[&["a", "b", "c"], &["a", "b", "c", "d", "e"]]);
+        let method = Reconstruction::Likelihood {
+            gain_rate: 0.1,
+            loss_rate: 0.2,
+        };
+        let states = reconstruct_ancestral_states(&tree, &cognates, method, 0.5).unwrap();
+        let node = |name: &str| states.nodes.iter().position(|n| n == name).unwrap();
+        let (abc, de) = (node("abc"), node("de"));
+        assert!(states.presence[abc][0] > 0.9);
+        assert!(states.presence[de][0] < 0.1);
+        assert!(states.presence[abc][1] > 0.9 && states.presence[de][1] > 0.9);
+        // Leaves are certain
+        assert_eq!(states.presence[node("d")], [0.0, 1.0]);
+
+        // Set 1 is ancestral; set 0 more likely arose early and was lost in
+        // de than arose twice
+        let root_gains: Vec<usize> = states.branches[0].gains.iter().map(|g| g.0).collect();
+        assert_eq!(root_gains, [0, 1]);
+        assert!(states.branches[0].gains[1].1 > 0.9);
+        assert_eq!(states.branches[de].losses.len(), 1);
+        assert!(states.branches[abc].gains.is_empty());
+        let json: serde_json::Value = serde_json::from_str(&states.to_json()).unwrap();
+        assert_eq!(json["nodes"][0], "root");
+
+        assert!(reconstruct_ancestral_states(&tree, &cognates, method, 1.5).is_err());
+        assert!(reconstruct_ancestral_states(&tree, &sets(&[&["x"]]), method, 0.5).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! Arrow C stream import for DataFrame ingestion.
+//!
+//! pyarrow tables and polars / pandas (>= 2.2) DataFrames export their data
+//! through `__arrow_c_stream__` as an `ArrowArrayStream` of record batches.
+//! Reading the named columns straight out of the Arrow buffers skips the
+//! per-row Python tuple conversion. The stream is imported with arrow-rs,
+//! and every column read is fully validated first (offsets, view buffers,
+//! dictionary keys, UTF-8), since the buffers come from another library.
+//! Only what wordlists and edge lists need is decoded: string columns
+//! (utf8, large utf8, utf8 view, dictionary encoded) and numeric columns
+//! (integers and floats). Integer columns can also be read as text, for
+//! numeric IDs. Nulls are rejected.
+
+use arrow_array::cast::AsArray;
+use arrow_array::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+use arrow_array::types::{
+    ArrowPrimitiveType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, RecordBatchReader};
+use arrow_schema::DataType;
+
+/// Import the stream behind `ptr` (e.g. a PyCapsule's pointer), marking the
+/// original released so its owner does not free it.
+///
+/// # Safety
+/// `ptr` must point to a valid, unreleased `ArrowArrayStream`.
+pub unsafe fn import_stream(
+    ptr: *mut FFI_ArrowArrayStream,
+) -> Result<ArrowArrayStreamReader, String> {
+    ArrowArrayStreamReader::from_raw(ptr).map_err(|e| format!("Arrow stream error: {}", e))
+}
+
+/// How a column is wanted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Text,
+    Number,
+}
+
+/// A decoded column
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    Text(Vec<String>),
+    Number(Vec<f64>),
+}
+
+fn is_integer(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+    )
+}
+
+fn is_text(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+    )
+}
+
+/// Check that a column of `data_type` can be decoded at all
+fn check_supported(data_type: &DataType) -> Result<(), String> {
+    let supported = match data_type {
+        DataType::Dictionary(key, values) => is_integer(key) && is_text(values),
+        DataType::Float32 | DataType::Float64 => true,
+        other => is_integer(other) || is_text(other),
+    };
+    if supported {
+        Ok(())
+    } else {
+        Err(format!("unsupported Arrow type '{}'", data_type))
+    }
+}
+
+fn integers<T>(array: &dyn Array) -> Vec<i128>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<i128>,
+{
+    let values = array.as_primitive::<T>().values();
+    values.iter().map(|&v| v.into()).collect()
+}
+
+/// Values of an integer column, widened
+fn integer_values(array: &dyn Array) -> Option<Vec<i128>> {
+    Some(match array.data_type() {
+        DataType::Int8 => integers::<Int8Type>(array),
+        DataType::Int16 => integers::<Int16Type>(array),
+        DataType::Int32 => integers::<Int32Type>(array),
+        DataType::Int64 => integers::<Int64Type>(array),
+        DataType::UInt8 => integers::<UInt8Type>(array),
+        DataType::UInt16 => integers::<UInt16Type>(array),
+        DataType::UInt32 => integers::<UInt32Type>(array),
+        DataType::UInt64 => integers::<UInt64Type>(array),
+        _ => return None,
+    })
+}
+
+fn check_no_nulls(array: &dyn Array) -> Result<(), String> {
+    match array.logical_nulls() {
+        Some(nulls) if nulls.null_count() > 0 => Err("contains nulls".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Append the text values of `array`
+fn read_text(array: &dyn Array, out: &mut Vec<String>) -> Result<(), String> {
+    check_no_nulls(array)?;
+    match array.data_type() {
+        DataType::Utf8 => out.extend(array.as_string::<i32>().iter().flatten().map(String::from)),
+        DataType::LargeUtf8 => {
+            out.extend(array.as_string::<i64>().iter().flatten().map(String::from))
+        }
+        DataType::Utf8View => out.extend(array.as_string_view().iter().flatten().map(String::from)),
+        DataType::Dictionary(_, _) => {
+            let dictionary = array.as_any_dictionary();
+            let mut values = Vec::new();
+            read_text(dictionary.values(), &mut values)?;
+            let keys = integer_values(dictionary.keys()).ok_or("unsupported dictionary keys")?;
+            for key in keys {
+                let value = usize::try_from(key)
+                    .ok()
+                    .and_then(|index| values.get(index))
+                    .ok_or_else(|| format!("dictionary index {} out of range", key))?;
+                out.push(value.clone());
+            }
+        }
+        DataType::Float32 | DataType::Float64 => {
+            return Err("expected a text column, got floats".to_string());
+        }
+        _ => {
+            let values = integer_values(array)
+                .ok_or_else(|| format!("unsupported Arrow type '{}'", array.data_type()))?;
+            out.extend(values.iter().map(i128::to_string));
+        }
+    }
+    Ok(())
+}
+
+/// Append the numeric values of `array`
+fn read_numbers(array: &dyn Array, out: &mut Vec<f64>) -> Result<(), String> {
+    check_no_nulls(array)?;
+    match array.data_type() {
+        DataType::Float64 => out.extend(array.as_primitive::<Float64Type>().values()),
+        DataType::Float32 => out.extend(
+            array
+                .as_primitive::<Float32Type>()
+                .values()
+                .iter()
+                .map(|&v| v as f64),
+        ),
+        _ => {
+            let values = integer_values(array).ok_or("expected a numeric column, got text")?;
+            out.extend(values.into_iter().map(|v| v as f64));
+        }
+    }
+    Ok(())
+}
+
+/// Read the named columns from every record batch of `stream`
+pub fn read_columns<R: RecordBatchReader>(
+    stream: R,
+    wanted: &[(&str, ColumnKind)],
+) -> Result<Vec<Column>, String> {
+    let schema = stream.schema();
+    let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+
+    let mut targets = Vec::with_capacity(wanted.len());
+    for &(name, kind) in wanted {
+        let index = names
+            .iter()
+            .position(|&n| n == name)
+            .ok_or_else(|| format!("missing column '{}' (columns: {})", name, names.join(", ")))?;
+        check_supported(schema.field(index).data_type())
+            .map_err(|e| format!("column '{}': {}", name, e))?;
+        let column = match kind {
+            ColumnKind::Text => Column::Text(Vec::new()),
+            ColumnKind::Number => Column::Number(Vec::new()),
+        };
+        targets.push((name, index, column));
+    }
+
+    for batch in stream {
+        let batch = batch.map_err(|e| format!("Arrow stream error: {}", e))?;
+        for (name, index, column) in &mut targets {
+            let array = batch.column(*index);
+            array
+                .to_data()
+                .validate_full()
+                .map_err(|e| format!("column '{}': invalid Arrow data: {}", name, e))?;
+            match column {
+                Column::Text(values) => read_text(array, values),
+                Column::Number(values) => read_numbers(array, values),
+            }
+            .map_err(|e| format!("column '{}': {}", name, e))?;
+        }
+    }
+
+    Ok(targets.into_iter().map(|target| target.2).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{
+        DictionaryArray, Float64Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray,
+        StringViewArray,
+    };
+    use std::sync::Arc;
+
+    /// A record batch with utf8, utf8 view, float64, int64 and a
+    /// dictionary-encoded column, sliced to rows 1..3, passed through the
+    /// C stream interface
+    fn stream() -> ArrowArrayStreamReader {
+        let language: DictionaryArray<Int32Type> = vec!["lat", "eng", "lat"].into_iter().collect();
+        let batch = RecordBatch::try_from_iter([
+            (
+                "source",
+                Arc::new(StringArray::from(vec!["x", "a", "b"])) as _,
+            ),
+            (
+                "target",
+                Arc::new(StringViewArray::from(vec!["", "c", "a_very_long_target"])) as _,
+            ),
+            (
+                "weight",
+                Arc::new(Float64Array::from(vec![0.0, 0.9, 0.5])) as _,
+            ),
+            ("id", Arc::new(Int64Array::from(vec![0, 10, 11])) as _),
+            ("language", Arc::new(language) as _),
+        ])
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new([Ok(batch.slice(1, 2))], schema);
+        let mut exported = FFI_ArrowArrayStream::new(Box::new(batches));
+        unsafe { import_stream(&mut exported) }.unwrap()
+    }
+
+    #[test]
+    fn test_read_columns() {
+        let wanted = [
+            ("source", ColumnKind::Text),
+            ("target", ColumnKind::Text),
+            ("weight", ColumnKind::Number),
+            ("id", ColumnKind::Text),
+            ("language", ColumnKind::Text),
+            ("id", ColumnKind::Number),
+        ];
+        let columns = read_columns(stream(), &wanted).unwrap();
+        let text = |values: &[&str]| Column::Text(values.iter().map(|v| v.to_string()).collect());
+        assert_eq!(columns[0], text(&["a", "b"]));
+        assert_eq!(columns[1], text(&["c", "a_very_long_target"]));
+        assert_eq!(columns[2], Column::Number(vec![0.9, 0.5]));
+        assert_eq!(columns[3], text(&["10", "11"]));
+        assert_eq!(columns[4], text(&["eng", "lat"]));
+        assert_eq!(columns[5], Column::Number(vec![10.0, 11.0]));
+
+        let error = read_columns(stream(), &[("form", ColumnKind::Text)]).unwrap_err();
+        assert!(error.contains("missing column 'form'"));
+        let error = read_columns(stream(), &[("source", ColumnKind::Number)]).unwrap_err();
+        assert!(error.contains("expected a numeric column"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Stable cognate set IDs across re-runs.
+//!
+//! Newly detected clusters are matched onto the IDs of an existing cognate
+//! database so that re-running detection on an updated dataset keeps the
+//! numbering. Each existing ID goes to at most one cluster, chosen to
+//! maximize member overlap (greedy, largest overlap first). Clusters with no
+//! match get fresh IDs, and splits and merges of existing sets are reported.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// How an existing cognate set changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Members of one existing set ended up in several clusters
+    Split,
+    /// One cluster contains members of several existing sets
+    Merge,
+}
+
+impl ConflictKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictKind::Split => "split",
+            ConflictKind::Merge => "merge",
+        }
+    }
+}
+
+/// A split or merge found while assigning IDs
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdConflict {
+    pub kind: ConflictKind,
+    /// Split: the existing ID; merge: the ID given to the merged cluster
+    pub id: String,
+    /// Split: IDs of the clusters the members went to; merge: the existing
+    /// IDs absorbed into the cluster (sorted)
+    pub related: Vec<String>,
+}
+
+/// Result of mapping clusters onto existing IDs
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdAssignment {
+    /// Assigned ID per input cluster (parallel to the input)
+    pub cluster_ids: Vec<String>,
+    /// Freshly minted IDs, in creation order
+    pub new_ids: Vec<String>,
+    /// Existing IDs no longer used by any cluster (sorted)
+    pub retired_ids: Vec<String>,
+    pub conflicts: Vec<IdConflict>,
+}
+
+/// Next fresh ID: `prefix` followed by one more than the largest numeric
+/// suffix already used with that prefix
+fn fresh_id_counter<'a>(existing: impl Iterator<Item = &'a String>, prefix: &str) -> u64 {
+    existing
+        .filter_map(|id| id.strip_prefix(prefix)?.parse::<u64>().ok())
+        .max()
+        .map_or(1, |max| max + 1)
+}
+
+/// Map clusters (member lists) onto existing member → set ID assignments
+pub fn assign_cognate_set_ids(
+    clusters: &[Vec<String>],
+    existing: &HashMap<String, String>,
+    new_id_prefix: &str,
+) -> IdAssignment {
+    // Overlap between each cluster and each existing ID
+    let mut overlaps: BTreeMap<(usize, &str), usize> = BTreeMap::new();
+    for (cluster, members) in clusters.iter().enumerate() {
+        for member in members {
+            if let Some(id) = existing.get(member) {
+                *overlaps.entry((cluster, id.as_str())).or_insert(0) += 1;
+            }
+        }
+    }
+
+    // Greedy matching, largest overlap first; ties go to the lower cluster
+    // index, then the smaller ID, so results are deterministic
+    let mut candidates: Vec<((usize, &str), usize)> =
+        overlaps.iter().map(|(&k, &v)| (k, v)).collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut cluster_ids: Vec<Option<String>> = vec![None; clusters.len()];
+    let mut used: BTreeSet<&str> = BTreeSet::new();
+    for ((cluster, id), _) in candidates {
+        if cluster_ids[cluster].is_none() && !used.contains(id) {
+            cluster_ids[cluster] = Some(id.to_string());
+            used.insert(id);
+        }
+    }
+
+    let mut counter = fresh_id_counter(existing.values(), new_id_prefix);
+    let mut new_ids = Vec::new();
+    let cluster_ids: Vec<String> = cluster_ids
+        .into_iter()
+        .map(|id| {
+            id.unwrap_or_else(|| {
+                let id = format!("{}{}", new_id_prefix, counter);
+                counter += 1;
+                new_ids.push(id.clone());
+                id
+            })
+        })
+        .collect();
+
+    let all_existing: BTreeSet<&str> = existing.values().map(String::as_str).collect();
+    let retired_ids = all_existing
+        .difference(&used)
+        .map(|id| id.to_string())
+        .collect();
+
+    // Splits: one existing ID spread over several clusters; merges: one
+    // cluster drawing from several existing IDs
+    let mut spread: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut drawn: BTreeMap<usize, BTreeSet<&str>> = BTreeMap::new();
+    for &(cluster, id) in overlaps.keys() {
+        spread
+            .entry(id)
+            .or_default()
+            .insert(cluster_ids[cluster].as_str());
+        drawn.entry(cluster).or_default().insert(id);
+    }
+    let mut conflicts = Vec::new();
+    for (id, targets) in spread.into_iter().filter(|(_, t)| t.len() > 1) {
+        conflicts.push(IdConflict {
+            kind: ConflictKind::Split,
+            id: id.to_string(),
+            related: targets.into_iter().map(str::to_string).collect(),
+        });
+    }
+    for (cluster, sources) in drawn.into_iter().filter(|(_, s)| s.len() > 1) {
+        conflicts.push(IdConflict {
+            kind: ConflictKind::Merge,
+            id: cluster_ids[cluster].clone(),
+            related: sources.into_iter().map(str::to_string).collect(),
+        });
+    }
+
+    IdAssignment {
+        cluster_ids,
+        new_ids,
+        retired_ids,
+        conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(members: &[&str]) -> Vec<String> {
+        members.iter().map(|m| m.to_string()).collect()
+    }
+
+    fn existing(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(m, id)| (m.to_string(), id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let previous = existing(&[("a", "7"), ("b", "7"), ("c", "9"), ("d", "9")]);
+        // Same clusters in a different order plus a new one
+        let clusters = vec![
+            cluster(&["d", "c"]),
+            cluster(&["e", "f"]),
+            cluster(&["a", "b"]),
+        ];
+
+        let result = assign_cognate_set_ids(&clusters, &previous, "");
+        assert_eq!(result.cluster_ids, vec!["9", "10", "7"]);
+        assert_eq!(result.new_ids, vec!["10"]);
+        assert!(result.retired_ids.is_empty());
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let previous = existing(&[
+            ("a", "COG-1"),
+            ("b", "COG-1"),
+            ("c", "COG-1"),
+            ("d", "COG-2"),
+            ("e", "COG-3"),
+        ]);
+        // COG-1 splits; COG-2 and COG-3 merge
+        let clusters = vec![
+            cluster(&["a", "b"]),
+            cluster(&["c"]),
+            cluster(&["d", "e", "x"]),
+        ];
+
+        let result = assign_cognate_set_ids(&clusters, &previous, "COG-");
+        assert_eq!(result.cluster_ids, vec!["COG-1", "COG-4", "COG-2"]);
+        assert_eq!(result.retired_ids, vec!["COG-3"]);
+        assert_eq!(
+            result.conflicts,
+            vec![
+                IdConflict {
+                    kind: ConflictKind::Split,
+                    id: "COG-1".into(),
+                    related: vec!["COG-1".into(), "COG-4".into()],
+                },
+                IdConflict {
+                    kind: ConflictKind::Merge,
+                    id: "COG-2".into(),
+                    related: vec!["COG-2".into(), "COG-3".into()],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Bipartite projection of word–language (or word–cognate set) networks.
+//!
+//! The input is an edge list whose sources all belong to one side and
+//! targets to the other, e.g. (cognate set, language) rows. Projecting onto
+//! a side links two of its nodes once for every node of the other side they
+//! share: projecting onto languages gives a language–language network
+//! weighted by shared cognate sets. Repeated rows are merged by summing
+//! their weights.
+
+use ahash::AHashMap;
+use rayon::prelude::*;
+use std::str::FromStr;
+
+use crate::types::WeightedEdge;
+
+/// Side of the bipartite edge list to project onto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BipartiteSide {
+    /// Nodes in the first (source) column
+    Source,
+    /// Nodes in the second (target) column
+    Target,
+}
+
+impl FromStr for BipartiteSide {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "source" => Ok(BipartiteSide::Source),
+            "target" => Ok(BipartiteSide::Target),
+            other => Err(format!(
+                "unknown side '{}' (expected source or target)",
+                other
+            )),
+        }
+    }
+}
+
+/// Weight of a projected edge between `a` and `b`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionWeighting {
+    /// Number of shared neighbors
+    Count,
+    /// Sum over shared neighbors of the product of the two edge weights
+    Product,
+    /// Shared neighbors over the union of both neighborhoods
+    Jaccard,
+    /// Newman's collaboration weighting: each shared neighbor with degree d
+    /// contributes 1 / (d - 1), so large cognate sets count for less
+    Newman,
+}
+
+impl FromStr for ProjectionWeighting {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "count" => Ok(ProjectionWeighting::Count),
+            "product" => Ok(ProjectionWeighting::Product),
+            "jaccard" => Ok(ProjectionWeighting::Jaccard),
+            "newman" => Ok(ProjectionWeighting::Newman),
+            other => Err(format!(
+                "unknown weighting '{}' (expected count, product, jaccard or newman)",
+                other
+            )),
+        }
+    }
+}
+
+/// Project a bipartite (source, target, weight) edge list onto `side`;
+/// edges come out ordered by the first appearance of their endpoints
+pub fn project_bipartite(
+    edges: &[WeightedEdge],
+    side: BipartiteSide,
+    weighting: ProjectionWeighting,
+) -> Result<Vec<WeightedEdge>, String> {
+    let mut sources: AHashMap<&str, usize> = AHashMap::new();
+    let mut targets: AHashMap<&str, usize> = AHashMap::new();
+    for (source, target, _) in edges {
+        let next = sources.len();
+        sources.entry(source.as_str()).or_insert(next);
+        let next = targets.len();
+        targets.entry(target.as_str()).or_insert(next);
+    }
+    if let Some(shared) = sources.keys().find(|id| targets.contains_key(*id)) {
+        return Err(format!(
+            "edge list is not bipartite: '{}' is both a source and a target",
+            shared
+        ));
+    }
+
+    // Merged (projected node, weight) neighbors of every hub on the other side
+    let (nodes, hubs) = match side {
+        BipartiteSide::Source => (&sources, &targets),
+        BipartiteSide::Target => (&targets, &sources),
+    };
+    let mut merged: AHashMap<(usize, usize), f64> = AHashMap::new();
+    for (source, target, weight) in edges {
+        let (node, hub) = match side {
+            BipartiteSide::Source => (nodes[source.as_str()], hubs[target.as_str()]),
+            BipartiteSide::Target => (nodes[target.as_str()], hubs[source.as_str()]),
+        };
+        *merged.entry((hub, node)).or_insert(0.0) += weight;
+    }
+    let mut neighborhoods: Vec<Vec<(usize, f64)>> = vec![Vec::new(); hubs.len()];
+    let mut degrees = vec![0usize; nodes.len()];
+    for ((hub, node), weight) in merged {
+        neighborhoods[hub].push((node, weight));
+        degrees[node] += 1;
+    }
+
+    // Contributions per hub in parallel, summed in hub order so the
+    // weights come out identical from run to run
+    let contributions: Vec<Vec<((usize, usize), f64)>> = neighborhoods
+        .par_iter()
+        .map(|members| {
+            let share = match weighting {
+                ProjectionWeighting::Newman if members.len() > 1 => {
+                    1.0 / (members.len() - 1) as f64
+                }
+                _ => 1.0,
+            };
+            let mut pairs = Vec::new();
+            for (i, &(a, weight_a)) in members.iter().enumerate() {
+                for &(b, weight_b) in &members[i + 1..] {
+                    let value = match weighting {
+                        ProjectionWeighting::Product => weight_a * weight_b,
+                        _ => share,
+                    };
+                    pairs.push(((a.min(b), a.max(b)), value));
+                }
+            }
+            pairs
+        })
+        .collect();
+    let mut pairs: AHashMap<(usize, usize), f64> = AHashMap::new();
+    for (pair, value) in contributions.into_iter().flatten() {
+        *pairs.entry(pair).or_insert(0.0) += value;
+    }
+
+    let mut names = vec![""; nodes.len()];
+    for (&name, &index) in nodes.iter() {
+        names[index] = name;
+    }
+    let mut projected: Vec<((usize, usize), f64)> = pairs.into_iter().collect();
+    projected.sort_unstable_by_key(|&(pair, _)| pair);
+    Ok(projected
+        .into_iter()
+        .map(|((a, b), value)| {
+            let weight = match weighting {
+                ProjectionWeighting::Jaccard => value / ((degrees[a] + degrees[b]) as f64 - value),
+                _ => value,
+            };
+            (names[a].to_string(), names[b].to_string(), weight)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(source: &str, target: &str, weight: f64) -> WeightedEdge {
+        (source.to_string(), target.to_string(), weight)
+    }
+
+    #[test]
+    fn test_project_bipartite() {
+        // Cognate sets s1 {eng, deu, nld}, s2 {eng, deu}, s3 {lat}
+        let edges = vec![
+            edge("s1", "eng", 1.0),
+            edge("s1", "deu", 0.5),
+            edge("s1", "nld", 1.0),
+            edge("s2", "eng", 1.0),
+            edge("s2", "deu", 1.0),
+            edge("s3", "lat", 1.0),
+        ];
+        let project = |side, weighting| project_bipartite(&edges, side, weighting).unwrap();
+
+        let languages = project(BipartiteSide::Target, ProjectionWeighting::Count);
+        assert_eq!(
+            languages,
+            vec![
+                edge("eng", "deu", 2.0),
+                edge("eng", "nld", 1.0),
+                edge("deu", "nld", 1.0),
+            ]
+        );
+        let newman = project(BipartiteSide::Target, ProjectionWeighting::Newman);
+        assert_eq!(newman[0], edge("eng", "deu", 1.5));
+        let product = project(BipartiteSide::Target, ProjectionWeighting::Product);
+        assert_eq!(product[0], edge("eng", "deu", 1.5));
+        // eng and deu share both their sets, nld shares its only one
+        let jaccard = project(BipartiteSide::Target, ProjectionWeighting::Jaccard);
+        assert_eq!(jaccard[0].2, 1.0);
+        assert_eq!(jaccard[1].2, 0.5);
+
+        let sets = project(BipartiteSide::Source, ProjectionWeighting::Count);
+        assert_eq!(sets, vec![edge("s1", "s2", 2.0)]);
+
+        let cyclic = [edge("a", "b", 1.0), edge("b", "c", 1.0)];
+        let error = project_bipartite(&cyclic, BipartiteSide::Source, ProjectionWeighting::Count);
+        assert!(error.unwrap_err().contains("'b' is both"));
+        assert_eq!("newman".parse(), Ok(ProjectionWeighting::Newman));
+        assert!("left".parse::<BipartiteSide>().is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Borrowability: how often each concept and semantic field is borrowed.
+//!
+//! Detected borrowing candidates are given as (language, concept, borrowed)
+//! entries, one per word. Rates come per concept and per semantic field
+//! with Wilson score intervals, which stay inside [0, 1] and remain sensible
+//! for the handful of entries a single concept usually has, so rarely
+//! attested concepts can be told apart from reliably loan-prone ones.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::concepts::SemanticFields;
+
+/// Borrowing rate of one concept or semantic field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BorrowingRate {
+    /// Concept or field name
+    pub key: String,
+    pub entries: usize,
+    pub borrowed: usize,
+    /// Languages with at least one borrowed entry
+    pub borrowing_languages: usize,
+    pub rate: f64,
+    /// Wilson score interval bounds
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Borrowing rates per concept and per semantic field, each highest rate
+/// first (ties by key)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Borrowability {
+    pub confidence: f64,
+    pub concepts: Vec<BorrowingRate>,
+    /// Untagged concepts are left out; an entry counts towards every field
+    /// of its concept
+    pub fields: Vec<BorrowingRate>,
+}
+
+impl Borrowability {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("borrowability serializes")
+    }
+}
+
+/// Aggregate (language, concept, borrowed) entries with intervals at the
+/// `confidence` level (e.g. 0.95)
+pub fn borrowability(
+    entries: &[(String, String, bool)],
+    fields: &SemanticFields,
+    confidence: f64,
+) -> Result<Borrowability, String> {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(format!("confidence {} must be in (0, 1)", confidence));
+    }
+    let z = normal_quantile(0.5 + confidence / 2.0);
+
+    let mut concepts: BTreeMap<&str, Tally> = BTreeMap::new();
+    let mut field_tallies: BTreeMap<&str, Tally> = BTreeMap::new();
+    for (language, concept, borrowed) in entries {
+        concepts
+            .entry(concept)
+            .or_default()
+            .add(language, *borrowed);
+        for field in fields.fields_of(concept) {
+            field_tallies
+                .entry(field)
+                .or_default()
+                .add(language, *borrowed);
+        }
+    }
+    Ok(Borrowability {
+        confidence,
+        concepts: rates(concepts, z),
+        fields: rates(field_tallies, z),
+    })
+}
+
+#[derive(Default)]
+struct Tally<'a> {
+    entries: usize,
+    borrowed: usize,
+    languages: BTreeSet<&'a str>,
+}
+
+impl<'a> Tally<'a> {
+    fn add(&mut self, language: &'a str, borrowed: bool) {
+        self.entries += 1;
+        if borrowed {
+            self.borrowed += 1;
+            self.languages.insert(language);
+        }
+    }
+}
+
+fn rates(tallies: BTreeMap<&str, Tally>, z: f64) -> Vec<BorrowingRate> {
+    let mut rates: Vec<BorrowingRate> = tallies
+        .into_iter()
+        .map(|(key, tally)| {
+            let (lower, upper) = wilson_interval(tally.borrowed, tally.entries, z);
+            BorrowingRate {
+                key: key.to_string(),
+                entries: tally.entries,
+                borrowed: tally.borrowed,
+                borrowing_languages: tally.languages.len(),
+                rate: tally.borrowed as f64 / tally.entries as f64,
+                lower,
+                upper,
+            }
+        })
+        .collect();
+    // Stable, so ties stay in key order
+    rates.sort_by(|a, b| b.rate.total_cmp(&a.rate));
+    rates
+}
+
+/// Wilson score interval of `successes` out of `n` (n > 0)
+fn wilson_interval(successes: usize, n: usize, z: f64) -> (f64, f64) {
+    let n = n as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+/// Standard normal quantile for p in (0, 1), by Acklam's rational
+/// approximation (relative error below 1.2e-9)
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    const LOW: f64 = 0.02425;
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn flagged(entries: &[(&str, &str, bool)]) -> Vec<(String, String, bool)> {
+        entries
+            .iter()
+            .map(|(l, c, b)| (l.to_string(), c.to_string(), *b))
+            .collect()
+    }
+
+    #[test]
+    fn test_borrowability() {
+        let fields = SemanticFields::new(&HashMap::from([
+            ("hand".to_string(), vec!["body".to_string()]),
+            ("tongue".to_string(), vec!["body".to_string()]),
+        ]));
+        let entries = flagged(&[
+            ("eng", "hand", false),
+            ("deu", "hand", false),
+            ("eng", "tongue", true),
+            ("deu", "tongue", false),
+            ("eng", "coffee", true),
+            ("deu", "coffee", true),
+        ]);
+        let report = borrowability(&entries, &fields, 0.95).unwrap();
+        let concepts: Vec<(&str, usize, f64)> = report
+            .concepts
+            .iter()
+            .map(|r| (r.key.as_str(), r.borrowed, r.rate))
+            .collect();
+        assert_eq!(
+            concepts,
+            [("coffee", 2, 1.0), ("tongue", 1, 0.5), ("hand", 0, 0.0)]
+        );
+        assert_eq!(report.fields.len(), 1);
+        assert_eq!(report.fields[0].entries, 4);
+        assert_eq!(report.fields[0].borrowing_languages, 1);
+
+        // Wilson interval of 1 / 2 at 95%: 0.5 ± 0.4055
+        let tongue = &report.concepts[1];
+        assert!((tongue.lower - 0.0945).abs() < 1e-4);
+        assert!((tongue.upper - 0.9055).abs() < 1e-4);
+        assert_eq!(report.concepts[0].upper, 1.0);
+        assert_eq!(report.concepts[2].lower, 0.0);
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["fields"][0]["key"], "body");
+        assert!(borrowability(&entries, &fields, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!((normal_quantile(0.975) - 1.959963985).abs() < 1e-8);
+        assert!((normal_quantile(0.5)).abs() < 1e-12);
+        assert!((normal_quantile(0.001) + 3.090232306).abs() < 1e-8);
+    }
+}
@@ -0,0 +1,258 @@
+//! Directed networks for borrowing (donor → recipient) relations.
+//!
+//! Cognacy is symmetric and lives in the undirected `CognateGraph`;
+//! loanwords flow one way. `BorrowingGraph` keeps one weighted arc per
+//! (donor, recipient) pair, with repeated arcs accumulating weight (e.g. a
+//! loan count), and provides the directed counterparts of the cognate
+//! analytics: in/out degree, directed PageRank and strongly connected
+//! components.
+
+use ahash::AHashMap;
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+
+/// Directed, weighted donor → recipient graph keyed by node ID
+#[derive(Debug, Clone, Default)]
+pub struct BorrowingGraph {
+    graph: DiGraph<String, f64>,
+    node_map: AHashMap<String, NodeIndex>,
+}
+
+/// In/out degree and weighted strength of one node
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDegree {
+    pub id: String,
+    /// Number of donors
+    pub in_degree: usize,
+    /// Number of recipients
+    pub out_degree: usize,
+    pub in_strength: f64,
+    pub out_strength: f64,
+}
+
+/// Directed graph statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectedGraphStats {
+    pub num_nodes: usize,
+    pub num_edges: usize,
+    /// Edges over the n(n-1) possible arcs
+    pub density: f64,
+    /// Share of edges whose reverse edge also exists
+    pub reciprocity: f64,
+    pub num_strong_components: usize,
+    pub largest_strong_component_size: usize,
+}
+
+impl BorrowingGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build from (donor, recipient, weight) tuples
+    pub fn from_edges(edges: Vec<(String, String, f64)>) -> Self {
+        let mut graph = Self::new();
+        for (donor, recipient, weight) in edges {
+            graph.add_edge(donor, recipient, weight);
+        }
+        graph
+    }
+
+    fn get_or_create_node(&mut self, id: String) -> NodeIndex {
+        if let Some(&idx) = self.node_map.get(&id) {
+            return idx;
+        }
+        let idx = self.graph.add_node(id.clone());
+        self.node_map.insert(id, idx);
+        idx
+    }
+
+    /// Add a donor → recipient arc; an existing arc gains `weight`
+    pub fn add_edge(&mut self, donor: String, recipient: String, weight: f64) {
+        let from = self.get_or_create_node(donor);
+        let to = self.get_or_create_node(recipient);
+        match self.graph.find_edge(from, to) {
+            Some(edge) => self.graph[edge] += weight,
+            None => {
+                self.graph.add_edge(from, to, weight);
+            }
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Degree and strength of every node, in insertion order
+    pub fn degrees(&self) -> Vec<NodeDegree> {
+        self.graph
+            .node_indices()
+            .map(|idx| {
+                let incoming = self.graph.edges_directed(idx, Direction::Incoming);
+                let outgoing = self.graph.edges_directed(idx, Direction::Outgoing);
+                NodeDegree {
+                    id: self.graph[idx].clone(),
+                    in_degree: incoming.clone().count(),
+                    out_degree: outgoing.clone().count(),
+                    in_strength: incoming.map(|e| *e.weight()).sum(),
+                    out_strength: outgoing.map(|e| *e.weight()).sum(),
+                }
+            })
+            .collect()
+    }
+
+    /// Weighted PageRank along arc direction: rank flows from donors to
+    /// recipients in proportion to arc weight, so heavy borrowers score
+    /// high. Rank of nodes without outgoing arcs is spread uniformly.
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> Vec<(String, f64)> {
+        let n = self.graph.node_count();
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let out_strength: Vec<f64> = self
+            .graph
+            .node_indices()
+            .map(|idx| self.graph.edges(idx).map(|e| *e.weight()).sum())
+            .collect();
+        let mut ranks = vec![1.0 / n as f64; n];
+        let mut new_ranks = vec![0.0; n];
+
+        for _ in 0..iterations {
+            let dangling: f64 = (0..n)
+                .filter(|&i| out_strength[i] <= 0.0)
+                .map(|i| ranks[i])
+                .sum();
+            new_ranks.fill((1.0 - damping) / n as f64 + damping * dangling / n as f64);
+
+            for edge in self.graph.edge_references() {
+                let source = edge.source().index();
+                if out_strength[source] > 0.0 {
+                    new_ranks[edge.target().index()] +=
+                        damping * ranks[source] * edge.weight() / out_strength[source];
+                }
+            }
+
+            std::mem::swap(&mut ranks, &mut new_ranks);
+        }
+
+        self.graph
+            .node_indices()
+            .map(|idx| (self.graph[idx].clone(), ranks[idx.index()]))
+            .collect()
+    }
+
+    /// Strongly connected components (groups of languages that borrow from
+    /// each other, directly or in a cycle), largest first, members sorted
+    pub fn strongly_connected_components(&self) -> Vec<Vec<String>> {
+        let mut components: Vec<Vec<String>> = tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|component| {
+                let mut members: Vec<String> = component
+                    .into_iter()
+                    .map(|idx| self.graph[idx].clone())
+                    .collect();
+                members.sort();
+                members
+            })
+            .collect();
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        components
+    }
+
+    pub fn stats(&self) -> DirectedGraphStats {
+        let num_nodes = self.graph.node_count();
+        let num_edges = self.graph.edge_count();
+        let density = if num_nodes > 1 {
+            num_edges as f64 / (num_nodes * (num_nodes - 1)) as f64
+        } else {
+            0.0
+        };
+        let reciprocal = self
+            .graph
+            .edge_references()
+            .filter(|e| self.graph.find_edge(e.target(), e.source()).is_some())
+            .count();
+        let reciprocity = if num_edges > 0 {
+            reciprocal as f64 / num_edges as f64
+        } else {
+            0.0
+        };
+
+        let components = tarjan_scc(&self.graph);
+        DirectedGraphStats {
+            num_nodes,
+            num_edges,
+            density,
+            reciprocity,
+            num_strong_components: components.len(),
+            largest_strong_component_size: components.iter().map(Vec::len).max().unwrap_or(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loans() -> BorrowingGraph {
+        let edges = [
+            ("lat", "eng", 3.0),
+            ("fra", "eng", 5.0),
+            ("lat", "fra", 2.0),
+            ("eng", "fra", 1.0),
+            ("lat", "eng", 1.0),
+            ("non", "eng", 2.0),
+        ];
+        BorrowingGraph::from_edges(
+            edges
+                .iter()
+                .map(|(a, b, w)| (a.to_string(), b.to_string(), *w))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_degrees_and_stats() {
+        let graph = loans();
+        assert_eq!(graph.edge_count(), 5);
+
+        let degrees = graph.degrees();
+        let eng = degrees.iter().find(|d| d.id == "eng").unwrap();
+        assert_eq!((eng.in_degree, eng.out_degree), (3, 1));
+        assert_eq!((eng.in_strength, eng.out_strength), (11.0, 1.0));
+
+        let stats = graph.stats();
+        assert_eq!(stats.num_nodes, 4);
+        assert!((stats.density - 5.0 / 12.0).abs() < 1e-12);
+        assert!((stats.reciprocity - 0.4).abs() < 1e-12);
+        assert_eq!(stats.num_strong_components, 3);
+        assert_eq!(stats.largest_strong_component_size, 2);
+    }
+
+    #[test]
+    fn test_pagerank_and_components() {
+        let graph = loans();
+        let ranks = graph.pagerank(0.85, 100);
+        assert!((ranks.iter().map(|r| r.1).sum::<f64>() - 1.0).abs() < 1e-9);
+        let top = ranks.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        assert_eq!(top.0, "eng");
+        let lat = ranks.iter().find(|r| r.0 == "lat").unwrap().1;
+        assert!(lat < top.1);
+
+        let components = graph.strongly_connected_components();
+        assert_eq!(
+            components,
+            vec![
+                vec!["eng".to_string(), "fra".to_string()],
+                vec!["lat".to_string()],
+                vec!["non".to_string()],
+            ]
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Hierarchical edge bundling (Holten 2006) over a community hierarchy.
+//!
+//! The hierarchy is a list of partitions, finest first, each nesting in the
+//! next (as `louvain_hierarchy` returns them); a single root sits above the
+//! coarsest level. Every community is placed at the centroid of its member
+//! nodes in the layout. An edge is routed from its source up through the
+//! source's communities to the lowest one it shares with the target, then
+//! down to the target, and those positions become its control points (for
+//! a B-spline on the frontend). `beta` sets the bundling strength: control
+//! points are pulled towards the straight line by `1 - beta`, so 1 bundles
+//! fully and 0 draws straight edges.
+
+use ahash::AHashMap;
+use ndarray::ArrayView2;
+use rayon::prelude::*;
+
+/// Control points of one edge, source and target positions included
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundledEdge {
+    pub source: String,
+    pub target: String,
+    pub points: Vec<Vec<f64>>,
+}
+
+/// Bundle `edges` between laid-out nodes (`ids` with one `coords` row each)
+/// along `hierarchy` (levels of communities of node IDs, finest first)
+pub fn bundle_edges(
+    ids: &[String],
+    coords: ArrayView2<f64>,
+    hierarchy: &[Vec<Vec<String>>],
+    edges: &[(String, String)],
+    beta: f64,
+) -> Result<Vec<BundledEdge>, String> {
+    if !(0.0..=1.0).contains(&beta) {
+        return Err(format!("beta must be in [0, 1], got {}", beta));
+    }
+    if ids.len() != coords.nrows() {
+        return Err(format!(
+            "{} node ids for {} coordinate rows",
+            ids.len(),
+            coords.nrows()
+        ));
+    }
+    let row: AHashMap<&str, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+
+    // Community of each node per level, and each community's centroid
+    let mut membership: Vec<AHashMap<&str, usize>> = Vec::with_capacity(hierarchy.len());
+    let mut centroids: Vec<Vec<Vec<f64>>> = Vec::with_capacity(hierarchy.len());
+    for (level, communities) in hierarchy.iter().enumerate() {
+        let mut members = AHashMap::new();
+        let mut level_centroids = Vec::with_capacity(communities.len());
+        for (c, community) in communities.iter().enumerate() {
+            for node in community {
+                members.insert(node.as_str(), c);
+            }
+            level_centroids.push(centroid(
+                coords,
+                community
+                    .iter()
+                    .filter_map(|node| row.get(node.as_str()).copied()),
+            ));
+        }
+        if let Some(finer) = level.checked_sub(1).map(|finer| &hierarchy[finer]) {
+            for community in finer {
+                let mut parents = community.iter().map(|node| members.get(node.as_str()));
+                let first = parents.next().flatten();
+                if parents.any(|parent| parent != first) {
+                    return Err(format!(
+                        "level {} does not nest in level {}: a community is split",
+                        level - 1,
+                        level
+                    ));
+                }
+            }
+        }
+        membership.push(members);
+        centroids.push(level_centroids);
+    }
+    let root = centroid(coords, 0..ids.len());
+
+    edges
+        .par_iter()
+        .map(|(source, target)| {
+            let position = |node: &String| {
+                row.get(node.as_str())
+                    .map(|&r| coords.row(r).to_vec())
+                    .ok_or_else(|| format!("edge endpoint '{}' has no coordinates", node))
+            };
+            let (start, end) = (position(source)?, position(target)?);
+            // Communities of each endpoint, bottom up, to the first shared
+            let chain = |node: &String| -> Vec<Option<usize>> {
+                membership
+                    .iter()
+                    .map(|members| members.get(node.as_str()).copied())
+                    .collect()
+            };
+            let (up, down) = (chain(source), chain(target));
+            let shared = (0..hierarchy.len()).find(|&l| up[l].is_some() && up[l] == down[l]);
+
+            let mut points = vec![start];
+            let top = shared.unwrap_or(hierarchy.len());
+            for (level, community) in up.iter().enumerate().take(top) {
+                if let Some(c) = community {
+                    points.push(centroids[level][*c].clone());
+                }
+            }
+            match shared.and_then(|level| Some((level, up[level]?))) {
+                Some((level, c)) => points.push(centroids[level][c].clone()),
+                None => points.push(root.clone()),
+            }
+            for (level, community) in down.iter().enumerate().take(top).rev() {
+                if let Some(c) = community {
+                    points.push(centroids[level][*c].clone());
+                }
+            }
+            points.push(end);
+            straighten(&mut points, beta);
+            Ok(BundledEdge {
+                source: source.clone(),
+                target: target.clone(),
+                points,
+            })
+        })
+        .collect()
+}
+
+/// Mean of the given coordinate rows (the origin for none)
+fn centroid(coords: ArrayView2<f64>, rows: impl Iterator<Item = usize>) -> Vec<f64> {
+    let mut sum = vec![0.0; coords.ncols()];
+    let mut count = 0;
+    for r in rows {
+        for (total, value) in sum.iter_mut().zip(coords.row(r)) {
+            *total += value;
+        }
+        count += 1;
+    }
+    sum.iter()
+        .map(|total| total / count.max(1) as f64)
+        .collect()
+}
+
+/// Pull inner control points `1 - beta` of the way onto the straight line
+/// between the endpoints
+fn straighten(points: &mut [Vec<f64>], beta: f64) {
+    let last = points.len() - 1;
+    let (start, end) = (points[0].clone(), points[last].clone());
+    for (i, point) in points.iter_mut().enumerate().take(last).skip(1) {
+        let t = i as f64 / last as f64;
+        for (d, value) in point.iter_mut().enumerate() {
+            let straight = start[d] + t * (end[d] - start[d]);
+            *value = beta * *value + (1.0 - beta) * straight;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn names(groups: &[&[&str]]) -> Vec<Vec<String>> {
+        groups
+            .iter()
+            .map(|group| group.iter().map(|id| id.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_bundle_edges() {
+        let ids: Vec<String> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let coords = array![[0.0, 0.0], [2.0, 0.0], [10.0, 0.0], [10.0, 4.0]];
+        let hierarchy = vec![
+            names(&[&["a", "b"], &["c"], &["d"]]),
+            names(&[&["a", "b"], &["c", "d"]]),
+        ];
+        let edges = vec![
+            ("a".to_string(), "b".to_string()),
+            ("a".to_string(), "d".to_string()),
+        ];
+
+        let bundled = bundle_edges(&ids, coords.view(), &hierarchy, &edges, 1.0).unwrap();
+        // Siblings meet at their community's centroid
+        assert_eq!(bundled[0].points, [[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]]);
+        // a and d share no community: up to the root (centroid of all) and
+        // down through {c, d} and {d}
+        assert_eq!(
+            bundled[1].points,
+            [
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [1.0, 0.0],
+                [5.5, 1.0],
+                [10.0, 2.0],
+                [10.0, 4.0],
+                [10.0, 4.0]
+            ]
+        );
+
+        // beta = 0 lays the control points on the straight line
+        let straight = bundle_edges(&ids, coords.view(), &hierarchy, &edges, 0.0).unwrap();
+        assert_eq!(straight[0].points[1], [1.0, 0.0]);
+        assert!(straight[1]
+            .points
+            .iter()
+            .all(|p| (p[1] - 0.4 * p[0]).abs() < 1e-12));
+
+        let split = vec![
+            names(&[&["a", "b"], &["c", "d"]]),
+            names(&[&["a", "c"], &["b", "d"]]),
+        ];
+        assert!(bundle_edges(&ids, coords.view(), &split, &edges, 0.8).is_err());
+        assert!(bundle_edges(&ids, coords.view(), &hierarchy, &edges, 1.5).is_err());
+    }
+}
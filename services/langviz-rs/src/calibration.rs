@@ -0,0 +1,499 @@
+//! Per-language-pair similarity calibration.
+//!
+//! Closely related languages score high even on unrelated words, so a
+//! single global threshold floods the graph with their pairs. Calibration
+//! fits the distribution of unrelated-pair scores separately for every
+//! language pair and applies either a pair-specific threshold (a quantile of
+//! that distribution) or z-score normalization while the graph is built.
+//! Pairs without background scores, and nodes without a language, fall back
+//! to the distribution pooled over all pairs.
+//!
+//! `ProbabilityCalibration` instead maps raw scores to cognate
+//! probabilities learned from a small labeled sample, either with a Platt
+//! sigmoid or an isotonic (monotone step) fit, so a threshold can be chosen
+//! as "P(cognate) >= 0.9" and translated back to a score.
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use crate::graph::CognateGraph;
+use crate::types::SimilarityEdge;
+
+/// Lower bound on the standard deviation used for z-scores
+const MIN_STD_DEV: f64 = 1e-6;
+
+/// How calibrated scores decide which edges enter the graph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalibrationMode {
+    /// Keep edges at or above the pair's threshold, with their raw weight
+    Threshold,
+    /// Replace weights by their z-score against the pair's background and
+    /// keep edges with `z >= min_z`
+    ZScore { min_z: f64 },
+}
+
+impl FromStr for CalibrationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "threshold" => Ok(CalibrationMode::Threshold),
+            "zscore" => Ok(CalibrationMode::ZScore { min_z: 2.0 }),
+            other => Err(format!(
+                "unknown calibration mode '{}' (expected threshold or zscore)",
+                other
+            )),
+        }
+    }
+}
+
+/// Background score distribution of one language pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairCalibration {
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Quantile of the unrelated scores used as the similarity threshold
+    pub threshold: f64,
+    /// Number of unrelated scores fitted
+    pub count: usize,
+}
+
+impl PairCalibration {
+    fn fit(mut scores: Vec<f64>, quantile: f64) -> Self {
+        let count = scores.len();
+        if count == 0 {
+            return Self {
+                mean: 0.0,
+                std_dev: 0.0,
+                threshold: 0.0,
+                count,
+            };
+        }
+        scores.sort_by(|a, b| a.total_cmp(b));
+        let mean = scores.iter().sum::<f64>() / count as f64;
+        let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count as f64;
+
+        // Linear interpolation between the closest ranks
+        let position = quantile.clamp(0.0, 1.0) * (count - 1) as f64;
+        let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+        let fraction = position - lower as f64;
+        let threshold = scores[lower] + fraction * (scores[upper] - scores[lower]);
+
+        Self {
+            mean,
+            std_dev: variance.sqrt(),
+            threshold,
+            count,
+        }
+    }
+
+    /// Weight the edge enters the graph with, or None if it is dropped
+    pub fn apply(&self, score: f64, mode: CalibrationMode) -> Option<f64> {
+        match mode {
+            CalibrationMode::Threshold => (score >= self.threshold).then_some(score),
+            CalibrationMode::ZScore { min_z } => {
+                let z = (score - self.mean) / self.std_dev.max(MIN_STD_DEV);
+                (z >= min_z).then_some(z)
+            }
+        }
+    }
+}
+
+/// Fitted calibration for every language pair seen in the background
+#[derive(Debug, Clone)]
+pub struct PairThresholds {
+    /// Keyed by the two languages in sorted order
+    pub pairs: BTreeMap<(String, String), PairCalibration>,
+    /// Pooled over all background scores
+    pub global: PairCalibration,
+}
+
+pub(crate) fn pair_key(lang_a: &str, lang_b: &str) -> (String, String) {
+    if lang_a <= lang_b {
+        (lang_a.to_string(), lang_b.to_string())
+    } else {
+        (lang_b.to_string(), lang_a.to_string())
+    }
+}
+
+impl PairThresholds {
+    /// Fit from (language, language, score) triples of unrelated word
+    /// pairs; thresholds are the `quantile` of each pair's scores
+    pub fn fit(unrelated: &[(String, String, f64)], quantile: f64) -> Self {
+        let mut grouped: BTreeMap<(String, String), Vec<f64>> = BTreeMap::new();
+        for (lang_a, lang_b, score) in unrelated {
+            grouped
+                .entry(pair_key(lang_a, lang_b))
+                .or_default()
+                .push(*score);
+        }
+
+        let global = PairCalibration::fit(unrelated.iter().map(|u| u.2).collect(), quantile);
+        let pairs = grouped
+            .into_iter()
+            .map(|(key, scores)| (key, PairCalibration::fit(scores, quantile)))
+            .collect();
+        Self { pairs, global }
+    }
+
+    /// Calibration for a pair of languages (either order), falling back to
+    /// the global one
+    pub fn get(&self, lang_a: Option<&str>, lang_b: Option<&str>) -> &PairCalibration {
+        match (lang_a, lang_b) {
+            (Some(a), Some(b)) => self.pairs.get(&pair_key(a, b)).unwrap_or(&self.global),
+            _ => &self.global,
+        }
+    }
+}
+
+impl CognateGraph {
+    /// Build a graph whose edges pass the calibration of their endpoints'
+    /// language pair (languages keyed by node ID)
+    pub fn from_calibrated_edges(
+        edges: Vec<SimilarityEdge>,
+        languages: &HashMap<String, String>,
+        calibration: &PairThresholds,
+        mode: CalibrationMode,
+    ) -> Self {
+        let mut graph = Self::new();
+        for edge in edges {
+            let pair = calibration.get(
+                languages.get(&edge.source).map(String::as_str),
+                languages.get(&edge.target).map(String::as_str),
+            );
+            if let Some(weight) = pair.apply(edge.weight.0, mode) {
+                graph.add_edge_with_provenance(
+                    edge.source,
+                    edge.target,
+                    weight,
+                    edge.provenance.as_deref(),
+                );
+            }
+        }
+        graph
+    }
+}
+
+/// Newton iterations of the Platt fit
+const PLATT_MAX_ITERATIONS: usize = 100;
+
+/// Curve fitted from scores to probabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbabilityMethod {
+    /// Logistic sigmoid of the score: smooth, two parameters, fine for
+    /// small samples
+    Platt,
+    /// Non-decreasing piecewise-linear fit: no shape assumed, needs more
+    /// labels
+    Isotonic,
+}
+
+impl FromStr for ProbabilityMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "platt" => Ok(ProbabilityMethod::Platt),
+            "isotonic" => Ok(ProbabilityMethod::Isotonic),
+            other => Err(format!(
+                "unknown calibration method '{}' (expected platt or isotonic)",
+                other
+            )),
+        }
+    }
+}
+
+/// Fitted map from raw similarity to P(cognate)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbabilityCalibration {
+    /// `1 / (1 + exp(-(slope * score + intercept)))`
+    Platt { slope: f64, intercept: f64 },
+    /// Breakpoints (ascending scores, non-decreasing probabilities),
+    /// interpolated linearly and held constant outside their range
+    Isotonic {
+        scores: Vec<f64>,
+        probabilities: Vec<f64>,
+    },
+}
+
+fn softplus(x: f64) -> f64 {
+    x.max(0.0) + (-x.abs()).exp().ln_1p()
+}
+
+impl ProbabilityCalibration {
+    /// Fit on (score, is_cognate) samples, which need both labels
+    pub fn fit(labeled: &[(f64, bool)], method: ProbabilityMethod) -> Result<Self, String> {
+        if !labeled.iter().any(|l| l.1) || labeled.iter().all(|l| l.1) {
+            return Err("calibration needs both cognate and non-cognate samples".to_string());
+        }
+        if let Some(&(score, _)) = labeled.iter().find(|l| !l.0.is_finite()) {
+            return Err(format!("calibration score {} is not finite", score));
+        }
+        Ok(match method {
+            ProbabilityMethod::Platt => Self::fit_platt(labeled),
+            ProbabilityMethod::Isotonic => Self::fit_isotonic(labeled),
+        })
+    }
+
+    /// Platt scaling: logistic regression on the score by Newton's method
+    /// with backtracking, against Platt's smoothed targets so separable
+    /// samples still give finite parameters
+    fn fit_platt(labeled: &[(f64, bool)]) -> Self {
+        let positives = labeled.iter().filter(|l| l.1).count() as f64;
+        let negatives = labeled.len() as f64 - positives;
+        let high = (positives + 1.0) / (positives + 2.0);
+        let low = 1.0 / (negatives + 2.0);
+        let target = |label: bool| if label { high } else { low };
+        let loss = |slope: f64, intercept: f64| -> f64 {
+            labeled
+                .iter()
+                .map(|&(score, label)| {
+                    let (f, t) = (slope * score + intercept, target(label));
+                    t * softplus(-f) + (1.0 - t) * softplus(f)
+                })
+                .sum()
+        };
+
+        let (mut slope, mut intercept) = (0.0, ((positives + 1.0) / (negatives + 1.0)).ln());
+        let mut current = loss(slope, intercept);
+        for _ in 0..PLATT_MAX_ITERATIONS {
+            // Gradient and Hessian of the log loss, ridged for stability
+            let (mut g_a, mut g_b, mut h_aa, mut h_ab, mut h_bb) = (0.0, 0.0, 1e-12, 0.0, 1e-12);
+            for &(score, label) in labeled {
+                let p = sigmoid(slope * score + intercept);
+                let (error, curvature) = (p - target(label), p * (1.0 - p));
+                g_a += error * score;
+                g_b += error;
+                h_aa += curvature * score * score;
+                h_ab += curvature * score;
+                h_bb += curvature;
+            }
+            let det = h_aa * h_bb - h_ab * h_ab;
+            let step_a = (h_bb * g_a - h_ab * g_b) / det;
+            let step_b = (h_aa * g_b - h_ab * g_a) / det;
+            if !(step_a.is_finite() && step_b.is_finite()) {
+                break;
+            }
+
+            let mut scale = 1.0;
+            while scale > 1e-10 {
+                let (a, b) = (slope - scale * step_a, intercept - scale * step_b);
+                let next = loss(a, b);
+                if next <= current {
+                    (slope, intercept, current) = (a, b, next);
+                    break;
+                }
+                scale /= 2.0;
+            }
+            if scale <= 1e-10 || (scale * step_a).abs().max((scale * step_b).abs()) < 1e-10 {
+                break;
+            }
+        }
+        ProbabilityCalibration::Platt { slope, intercept }
+    }
+
+    /// Isotonic regression by pool-adjacent-violators over the distinct
+    /// scores; each pooled block keeps its first and last score as
+    /// breakpoints
+    fn fit_isotonic(labeled: &[(f64, bool)]) -> Self {
+        let mut sorted = labeled.to_vec();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // (first score, last score, positive rate, weight) per block
+        let mut blocks: Vec<(f64, f64, f64, f64)> = Vec::new();
+        for (score, label) in sorted {
+            let y = if label { 1.0 } else { 0.0 };
+            match blocks.last_mut() {
+                Some(last) if last.1 == score => {
+                    last.2 = (last.2 * last.3 + y) / (last.3 + 1.0);
+                    last.3 += 1.0;
+                }
+                _ => blocks.push((score, score, y, 1.0)),
+            }
+            while blocks.len() > 1 && blocks[blocks.len() - 2].2 > blocks[blocks.len() - 1].2 {
+                let (first, _, rate, weight) = blocks[blocks.len() - 2];
+                let last = blocks.pop().unwrap();
+                let total = weight + last.3;
+                let pooled = (rate * weight + last.2 * last.3) / total;
+                *blocks.last_mut().unwrap() = (first, last.1, pooled, total);
+            }
+        }
+
+        let (mut scores, mut probabilities) = (Vec::new(), Vec::new());
+        for (first, last, rate, _) in blocks {
+            scores.push(first);
+            probabilities.push(rate);
+            if last > first {
+                scores.push(last);
+                probabilities.push(rate);
+            }
+        }
+        ProbabilityCalibration::Isotonic {
+            scores,
+            probabilities,
+        }
+    }
+
+    /// P(cognate) of a raw score
+    pub fn probability(&self, score: f64) -> f64 {
+        match self {
+            ProbabilityCalibration::Platt { slope, intercept } => {
+                sigmoid(slope * score + intercept)
+            }
+            ProbabilityCalibration::Isotonic {
+                scores,
+                probabilities,
+            } => {
+                let k = scores.partition_point(|&s| s <= score);
+                if k == 0 {
+                    probabilities[0]
+                } else if k == scores.len() {
+                    probabilities[k - 1]
+                } else {
+                    let (x0, x1) = (scores[k - 1], scores[k]);
+                    let (y0, y1) = (probabilities[k - 1], probabilities[k]);
+                    y0 + (y1 - y0) * (score - x0) / (x1 - x0)
+                }
+            }
+        }
+    }
+
+    /// Lowest score whose probability reaches `probability`: -inf if every
+    /// score does, None if none does (or if the fit falls with the score)
+    pub fn threshold(&self, probability: f64) -> Option<f64> {
+        match self {
+            ProbabilityCalibration::Platt { slope, intercept } => {
+                if *slope <= 0.0 || probability >= 1.0 {
+                    return None;
+                }
+                if probability <= 0.0 {
+                    return Some(f64::NEG_INFINITY);
+                }
+                let logit = (probability / (1.0 - probability)).ln();
+                Some((logit - intercept) / slope)
+            }
+            ProbabilityCalibration::Isotonic {
+                scores,
+                probabilities,
+            } => {
+                let k = probabilities.partition_point(|&p| p < probability);
+                if k == 0 {
+                    Some(f64::NEG_INFINITY)
+                } else if k == scores.len() {
+                    None
+                } else {
+                    let (x0, x1) = (scores[k - 1], scores[k]);
+                    let (y0, y1) = (probabilities[k - 1], probabilities[k]);
+                    Some(x0 + (x1 - x0) * (probability - y0) / (y1 - y0))
+                }
+            }
+        }
+    }
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn background() -> Vec<(String, String, f64)> {
+        let mut unrelated = Vec::new();
+        // deu-nld unrelated words still look alike; eng-jpn do not
+        for score in [0.5, 0.55, 0.6, 0.65, 0.7] {
+            unrelated.push(("nld".to_string(), "deu".to_string(), score));
+        }
+        for score in [0.1, 0.15, 0.2, 0.25, 0.3] {
+            unrelated.push(("eng".to_string(), "jpn".to_string(), score));
+        }
+        unrelated
+    }
+
+    #[test]
+    fn test_fit_pair_thresholds() {
+        let calibration = PairThresholds::fit(&background(), 0.75);
+        assert_eq!(calibration.pairs.len(), 2);
+
+        let close = calibration.get(Some("deu"), Some("nld"));
+        assert_eq!(close.count, 5);
+        assert!((close.threshold - 0.65).abs() < 1e-12);
+        assert!((close.mean - 0.6).abs() < 1e-12);
+
+        let far = calibration.get(Some("jpn"), Some("eng"));
+        assert!((far.threshold - 0.25).abs() < 1e-12);
+        assert_eq!(calibration.get(Some("eng"), None).count, 10);
+    }
+
+    #[test]
+    fn test_probability_calibration() {
+        // Cognates mostly score high, with some overlap in the middle
+        let mut labeled = Vec::new();
+        for i in 0..50 {
+            let score = i as f64 / 50.0;
+            labeled.push((score, score > 0.5 || i % 7 == 0 && score > 0.3));
+        }
+
+        let platt = ProbabilityCalibration::fit(&labeled, ProbabilityMethod::Platt).unwrap();
+        assert!(platt.probability(0.9) > 0.9 && platt.probability(0.1) < 0.1);
+        let cut = platt.threshold(0.9).unwrap();
+        assert!((platt.probability(cut) - 0.9).abs() < 1e-9);
+
+        let isotonic = ProbabilityCalibration::fit(&labeled, "isotonic".parse().unwrap()).unwrap();
+        let ProbabilityCalibration::Isotonic { probabilities, .. } = &isotonic else {
+            unreachable!()
+        };
+        assert!(probabilities.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(isotonic.probability(0.0), 0.0);
+        assert_eq!(isotonic.probability(1.0), 1.0);
+        let cut = isotonic.threshold(0.9).unwrap();
+        assert!(cut > 0.3 && cut <= 0.52);
+        assert!(isotonic.probability(cut) >= 0.9 - 1e-12);
+        assert_eq!(isotonic.threshold(0.0), Some(f64::NEG_INFINITY));
+
+        let one_class = [(0.2, true), (0.8, true)];
+        assert!(ProbabilityCalibration::fit(&one_class, ProbabilityMethod::Platt).is_err());
+    }
+
+    #[test]
+    fn test_calibrated_graph_construction() {
+        let calibration = PairThresholds::fit(&background(), 0.75);
+        let languages: HashMap<String, String> = [
+            ("deu_hund", "deu"),
+            ("nld_hond", "nld"),
+            ("deu_katze", "deu"),
+            ("nld_huis", "nld"),
+            ("eng_mother", "eng"),
+            ("jpn_haha", "jpn"),
+        ]
+        .iter()
+        .map(|(w, l)| (w.to_string(), l.to_string()))
+        .collect();
+        let edges = vec![
+            SimilarityEdge::new("deu_hund".into(), "nld_hond".into(), 0.9),
+            // Unrelated but above a global 0.4 threshold
+            SimilarityEdge::new("deu_katze".into(), "nld_huis".into(), 0.6),
+            SimilarityEdge::new("eng_mother".into(), "jpn_haha".into(), 0.4),
+        ];
+
+        let graph = CognateGraph::from_calibrated_edges(
+            edges.clone(),
+            &languages,
+            &calibration,
+            CalibrationMode::Threshold,
+        );
+        let kept: Vec<f64> = graph.edge_list().iter().map(|e| e.2).collect();
+        assert_eq!(kept, vec![0.9, 0.4]);
+
+        let zscored = CognateGraph::from_calibrated_edges(
+            edges,
+            &languages,
+            &calibration,
+            CalibrationMode::ZScore { min_z: 2.0 },
+        );
+        let weights: Vec<f64> = zscored.edge_list().iter().map(|e| e.2).collect();
+        assert_eq!(weights.len(), 2);
+        assert!(weights.iter().all(|&z| z >= 2.0));
+    }
+}
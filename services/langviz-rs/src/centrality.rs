@@ -0,0 +1,236 @@
+//! Weighted shortest-path centralities (betweenness and closeness).
+//!
+//! Edge weights are similarities, so an edge's length is `1 / weight`:
+//! strongly similar words are close and paths prefer strong links. Edges
+//! with non-positive weight are not traversable. Both measures run one
+//! Dijkstra search per source node, parallelized with Rayon.
+
+use ordered_float::OrderedFloat;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::determinism::par_sum_vectors;
+use crate::graph::CognateGraph;
+
+/// Relative tolerance when comparing path lengths for ties
+const TIE_TOLERANCE: f64 = 1e-9;
+
+/// Element-wise sum of `per_source(s)` over all sources `s` in `0..n`,
+/// combined in a fixed order in determinism mode
+pub(crate) fn sum_over_sources<F>(n: usize, per_source: F) -> Vec<f64>
+where
+    F: Fn(usize) -> Vec<f64> + Sync,
+{
+    par_sum_vectors(n, n, per_source)
+}
+
+/// Shortest-path DAG from one source
+struct ShortestPaths {
+    /// Nodes in order of non-decreasing distance
+    order: Vec<usize>,
+    distance: Vec<f64>,
+    /// Number of shortest paths from the source
+    sigma: Vec<f64>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+fn dijkstra(adjacency: &[Vec<(usize, f64)>], source: usize) -> ShortestPaths {
+    let n = adjacency.len();
+    let mut distance = vec![f64::INFINITY; n];
+    let mut sigma = vec![0.0; n];
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut settled = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut heap = BinaryHeap::new();
+
+    distance[source] = 0.0;
+    sigma[source] = 1.0;
+    heap.push(Reverse((OrderedFloat(0.0), source)));
+
+    while let Some(Reverse((OrderedFloat(d), v))) = heap.pop() {
+        if settled[v] {
+            continue;
+        }
+        settled[v] = true;
+        order.push(v);
+
+        for &(w, length) in &adjacency[v] {
+            let candidate = d + length;
+            let tolerance = TIE_TOLERANCE * candidate.max(1.0);
+            if candidate < distance[w] - tolerance {
+                distance[w] = candidate;
+                sigma[w] = sigma[v];
+                predecessors[w].clear();
+                predecessors[w].push(v);
+                heap.push(Reverse((OrderedFloat(candidate), w)));
+            } else if (candidate - distance[w]).abs() <= tolerance && !settled[w] {
+                sigma[w] += sigma[v];
+                predecessors[w].push(v);
+            }
+        }
+    }
+
+    ShortestPaths {
+        order,
+        distance,
+        sigma,
+        predecessors,
+    }
+}
+
+/// Sort (node, score) pairs by descending score, then by node ID
+fn sorted_scores(graph: &CognateGraph, scores: Vec<f64>) -> Vec<(String, f64)> {
+    let mut result: Vec<(String, f64)> = graph
+        .graph
+        .node_indices()
+        .zip(scores)
+        .map(|(idx, score)| (graph.graph[idx].clone(), score))
+        .collect();
+    result.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    result
+}
+
+impl CognateGraph {
+    /// Neighbor lists with edge lengths `1 / weight` (shortest parallel
+    /// edge kept; self-loops and non-positive weights dropped)
+    fn length_adjacency(&self) -> Vec<Vec<(usize, f64)>> {
+        let mut adjacency: Vec<Vec<(usize, f64)>> = vec![Vec::new(); self.graph.node_count()];
+        for edge in self.graph.edge_indices() {
+            let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+            let weight = self.graph[edge];
+            if a != b && weight > 0.0 {
+                adjacency[a.index()].push((b.index(), 1.0 / weight));
+                adjacency[b.index()].push((a.index(), 1.0 / weight));
+            }
+        }
+        for neighbors in adjacency.iter_mut() {
+            neighbors.sort_by(|x, y| x.0.cmp(&y.0).then(x.1.total_cmp(&y.1)));
+            neighbors.dedup_by_key(|e| e.0);
+        }
+        adjacency
+    }
+
+    /// Weighted betweenness (Brandes), normalized to [0, 1] by the number
+    /// of node pairs not involving the node
+    pub(crate) fn weighted_betweenness_scores(&self) -> Vec<f64> {
+        let n = self.graph.node_count();
+        if n < 3 {
+            return vec![0.0; n];
+        }
+        let adjacency = self.length_adjacency();
+
+        let raw = sum_over_sources(n, |source| {
+            let paths = dijkstra(&adjacency, source);
+            let mut delta = vec![0.0; n];
+            for &w in paths.order.iter().rev() {
+                for &v in &paths.predecessors[w] {
+                    delta[v] += (paths.sigma[v] / paths.sigma[w]) * (1.0 + delta[w]);
+                }
+            }
+            delta[source] = 0.0;
+            delta
+        });
+
+        // Each undirected path is counted from both endpoints
+        let scale = 1.0 / ((n - 1) * (n - 2)) as f64;
+        raw.into_iter().map(|b| b * scale).collect()
+    }
+
+    /// Weighted closeness with Wasserman-Faust scaling for disconnected
+    /// graphs: (r / total distance) * (r / (n - 1)) over r reachable nodes
+    pub(crate) fn weighted_closeness_scores(&self) -> Vec<f64> {
+        let n = self.graph.node_count();
+        if n < 2 {
+            return vec![0.0; n];
+        }
+        let adjacency = self.length_adjacency();
+
+        (0..n)
+            .into_par_iter()
+            .map(|source| {
+                let paths = dijkstra(&adjacency, source);
+                let reachable = paths.order.len() - 1;
+                let total: f64 = paths.order.iter().map(|&v| paths.distance[v]).sum();
+                if reachable == 0 || total == 0.0 {
+                    0.0
+                } else {
+                    let r = reachable as f64;
+                    (r / total) * (r / (n - 1) as f64)
+                }
+            })
+            .collect()
+    }
+
+    /// Weighted betweenness per node, highest first
+    pub fn compute_betweenness(&self) -> Vec<(String, f64)> {
+        sorted_scores(self, self.weighted_betweenness_scores())
+    }
+
+    /// Weighted closeness per node, highest first
+    pub fn compute_closeness(&self) -> Vec<(String, f64)> {
+        sorted_scores(self, self.weighted_closeness_scores())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn edge(a: &str, b: &str, w: f64) -> SimilarityEdge {
+        SimilarityEdge::new(a.to_string(), b.to_string(), w)
+    }
+
+    #[test]
+    fn test_weighted_betweenness_prefers_strong_paths() {
+        // a-b-d is strong, a-c-d weak: only b lies on the shortest path
+        let graph = CognateGraph::from_edges(
+            vec![
+                edge("a", "b", 1.0),
+                edge("b", "d", 1.0),
+                edge("a", "c", 0.2),
+                edge("c", "d", 0.2),
+            ],
+            0.0,
+        );
+        let scores: std::collections::HashMap<String, f64> =
+            graph.compute_betweenness().into_iter().collect();
+        assert!(scores["b"] > 0.0);
+        assert_eq!(scores["c"], 0.0);
+        assert_eq!(graph.compute_betweenness()[0].0, "b");
+    }
+
+    #[test]
+    fn test_sum_over_sources() {
+        // More sources than blocks, with a partial last block
+        let n = 150;
+        let total = sum_over_sources(n, |source| vec![source as f64; n]);
+        assert_eq!(total, vec![(n * (n - 1) / 2) as f64; n]);
+        assert!(sum_over_sources(0, |_| Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_unit_weights_match_hop_centralities() {
+        let graph = CognateGraph::from_edges(
+            vec![
+                edge("hub", "a", 1.0),
+                edge("hub", "b", 1.0),
+                edge("hub", "c", 1.0),
+                edge("c", "d", 1.0),
+                edge("x", "y", 1.0),
+            ],
+            0.0,
+        );
+        let hops = graph.compute_centralities(0.85, 50);
+        let betweenness = graph.weighted_betweenness_scores();
+        let closeness = graph.weighted_closeness_scores();
+        for (i, centrality) in hops.iter().enumerate() {
+            assert!((centrality.betweenness - betweenness[i]).abs() < 1e-12);
+            assert!((centrality.closeness - closeness[i]).abs() < 1e-12);
+        }
+
+        let ranked = graph.compute_closeness();
+        assert_eq!(ranked[0].0, "hub");
+    }
+}
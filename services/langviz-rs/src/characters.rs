@@ -0,0 +1,546 @@
+//! Binary cognate character matrices for external phylogenetics tools.
+//!
+//! Every cognate class becomes one presence/absence character, grouped by
+//! concept so that each concept's characters form a contiguous charset. A
+//! language scores 1 for the classes it has, 0 for the other classes of a
+//! concept it attests and `?` for concepts it lacks, the usual coding for
+//! BEAST and MrBayes. NEXUS output carries the charsets in an ASSUMPTIONS
+//! block; PHYLIP has no place for them, so they come as a separate
+//! RAxML-style partition file.
+//!
+//! Missing concepts can be imputed before export, either from the nearest
+//! language that attests the concept (fewest disagreements over shared
+//! concepts) or from a reference tree: the closest clade above the language
+//! with attesting members lends its most common class. Imputed cells are
+//! flagged in a NEXUS NOTES block and listed by `to_imputations` for PHYLIP
+//! users, so downstream analyses can tell real data from filled-in data.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::lexicostat::CognateTable;
+use crate::tree::Tree;
+
+/// One presence/absence character
+#[derive(Debug, Clone, PartialEq)]
+pub struct Character {
+    pub concept: String,
+    pub class: String,
+}
+
+/// Where imputed concepts are drawn from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImputationMethod {
+    /// The closest attesting language by shared-concept disagreement
+    Nearest,
+    /// The closest attesting clade of a reference tree
+    Tree,
+}
+
+impl FromStr for ImputationMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(ImputationMethod::Nearest),
+            "tree" => Ok(ImputationMethod::Tree),
+            other => Err(format!(
+                "unknown imputation method '{}' (expected nearest or tree)",
+                other
+            )),
+        }
+    }
+}
+
+impl ImputationMethod {
+    pub fn name(self) -> &'static str {
+        match self {
+            ImputationMethod::Nearest => "nearest",
+            ImputationMethod::Tree => "tree",
+        }
+    }
+}
+
+/// One concept filled in for a language that lacked it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Imputation {
+    pub language: usize,
+    /// Index into the charsets
+    pub concept: usize,
+    pub method: ImputationMethod,
+    /// Languages the cell was drawn from
+    pub sources: Vec<usize>,
+}
+
+/// Languages × cognate classes presence/absence matrix
+#[derive(Debug, Clone)]
+pub struct CharacterMatrix {
+    languages: Vec<String>,
+    /// Sorted by concept, then class label
+    characters: Vec<Character>,
+    /// `rows[language]`: one of b'0', b'1', b'?' per character
+    rows: Vec<Vec<u8>>,
+    /// (concept, first, last) character positions, 1-based inclusive
+    charsets: Vec<(String, usize, usize)>,
+    imputations: Vec<Imputation>,
+}
+
+impl CharacterMatrix {
+    /// Matrix of a cognate table's languages and classes
+    pub fn from_table(table: &CognateTable) -> Self {
+        let mut by_concept: Vec<Vec<u32>> = vec![Vec::new(); table.concepts.len()];
+        for (id, (concept, _)) in table.classes.iter().enumerate() {
+            by_concept[*concept].push(id as u32);
+        }
+
+        let mut characters = Vec::new();
+        let mut charsets = Vec::new();
+        let mut rows = vec![Vec::new(); table.languages.len()];
+        for (c, ids) in by_concept.iter_mut().enumerate() {
+            ids.sort_by(|&a, &b| {
+                table.classes[a as usize]
+                    .1
+                    .cmp(&table.classes[b as usize].1)
+            });
+            let first = characters.len() + 1;
+            for &id in ids.iter() {
+                characters.push(Character {
+                    concept: table.concepts[c].clone(),
+                    class: table.classes[id as usize].1.clone(),
+                });
+            }
+            charsets.push((table.concepts[c].clone(), first, characters.len()));
+            for (row, cells) in rows.iter_mut().zip(&table.cells) {
+                let cell = &cells[c];
+                row.extend(ids.iter().map(|id| {
+                    if cell.is_empty() {
+                        b'?'
+                    } else if cell.binary_search(id).is_ok() {
+                        b'1'
+                    } else {
+                        b'0'
+                    }
+                }));
+            }
+        }
+        Self {
+            languages: table.languages.clone(),
+            characters,
+            rows,
+            charsets,
+            imputations: Vec::new(),
+        }
+    }
+
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    pub fn characters(&self) -> &[Character] {
+        &self.characters
+    }
+
+    /// (concept, first, last) character positions, 1-based inclusive
+    pub fn charsets(&self) -> &[(String, usize, usize)] {
+        &self.charsets
+    }
+
+    /// Row of a language as a 0/1/? string
+    pub fn row(&self, language: usize) -> &str {
+        std::str::from_utf8(&self.rows[language]).expect("matrix rows are ASCII")
+    }
+
+    /// Cells filled in so far, in imputation order
+    pub fn imputations(&self) -> &[Imputation] {
+        &self.imputations
+    }
+
+    /// Positions (0-based, end exclusive) of a charset's characters
+    fn block(&self, concept: usize) -> std::ops::Range<usize> {
+        let (_, first, last) = self.charsets[concept];
+        first - 1..last
+    }
+
+    fn attests(&self, language: usize, concept: usize) -> bool {
+        self.rows[language][self.block(concept)]
+            .first()
+            .is_some_and(|&state| state != b'?')
+    }
+
+    /// Missing (language, concept) cells with at least one attesting
+    /// language to draw from
+    fn missing(&self) -> Vec<(usize, usize)> {
+        (0..self.languages.len())
+            .flat_map(|l| (0..self.charsets.len()).map(move |c| (l, c)))
+            .filter(|&(l, c)| {
+                !self.attests(l, c) && (0..self.languages.len()).any(|o| self.attests(o, c))
+            })
+            .collect()
+    }
+
+    /// Share of the concepts both languages attest on which they have no
+    /// class in common; `None` when they attest nothing in common
+    fn disagreement(&self, a: usize, b: usize) -> Option<f64> {
+        let (mut shared, mut total) = (0, 0);
+        for c in 0..self.charsets.len() {
+            if self.attests(a, c) && self.attests(b, c) {
+                total += 1;
+                let block = self.block(c);
+                let (ra, rb) = (&self.rows[a][block.clone()], &self.rows[b][block]);
+                if ra.iter().zip(rb).any(|(&x, &y)| x == b'1' && y == b'1') {
+                    shared += 1;
+                }
+            }
+        }
+        (total > 0).then(|| 1.0 - shared as f64 / total as f64)
+    }
+
+    /// Fill every missing concept that some language attests from the
+    /// nearest attesting language (ties to the first, languages with no
+    /// concept in common last), copying its classes
+    pub fn impute_nearest(&mut self) {
+        let missing = self.missing();
+        let mut filled = Vec::with_capacity(missing.len());
+        for (language, concept) in missing {
+            let source = (0..self.languages.len())
+                .filter(|&other| other != language && self.attests(other, concept))
+                .min_by(|&x, &y| {
+                    let dx = self.disagreement(language, x).unwrap_or(f64::INFINITY);
+                    let dy = self.disagreement(language, y).unwrap_or(f64::INFINITY);
+                    dx.total_cmp(&dy)
+                })
+                .expect("some language attests the concept");
+            let block = self.block(concept);
+            filled.push((
+                self.rows[source][block].to_vec(),
+                language,
+                concept,
+                vec![source],
+            ));
+        }
+        self.apply(filled, ImputationMethod::Nearest);
+    }
+
+    /// Fill every missing concept from `tree` (leaves named by language):
+    /// the lowest ancestor of the language with attesting descendants lends
+    /// the class most of them have (ties to the first class)
+    pub fn impute_on_tree(&mut self, tree: &Tree) -> Result<(), String> {
+        let mut leaf_of = vec![None; self.languages.len()];
+        for leaf in tree.leaves() {
+            let name = tree.node_name(leaf);
+            if let Some(l) = self.languages.iter().position(|language| *language == name) {
+                leaf_of[l] = Some(leaf);
+            }
+        }
+        if let Some(l) = leaf_of.iter().position(Option::is_none) {
+            return Err(format!(
+                "language '{}' is not a leaf of the tree",
+                self.languages[l]
+            ));
+        }
+        let mut below: Vec<Vec<usize>> = vec![Vec::new(); tree.nodes.len()];
+        for (l, leaf) in leaf_of.iter().enumerate() {
+            below[leaf.expect("checked above")].push(l);
+        }
+        for &node in tree.preorder().iter().rev() {
+            if let Some(parent) = tree.nodes[node].parent {
+                let languages = below[node].clone();
+                below[parent].extend(languages);
+            }
+        }
+
+        let missing = self.missing();
+        let mut filled = Vec::with_capacity(missing.len());
+        for (language, concept) in missing {
+            let mut node = leaf_of[language].expect("checked above");
+            let sources = loop {
+                let attesting: Vec<usize> = below[node]
+                    .iter()
+                    .copied()
+                    .filter(|&other| self.attests(other, concept))
+                    .collect();
+                if !attesting.is_empty() {
+                    break attesting;
+                }
+                node = tree.nodes[node]
+                    .parent
+                    .expect("some language attests the concept");
+            };
+            let block = self.block(concept);
+            let counts: Vec<usize> = block
+                .clone()
+                .map(|i| sources.iter().filter(|&&s| self.rows[s][i] == b'1').count())
+                .collect();
+            let best = (0..counts.len())
+                .max_by(|&a, &b| counts[a].cmp(&counts[b]).then(b.cmp(&a)))
+                .expect("concepts have at least one class");
+            let cell = (0..counts.len())
+                .map(|i| if i == best { b'1' } else { b'0' })
+                .collect();
+            filled.push((cell, language, concept, sources));
+        }
+        self.apply(filled, ImputationMethod::Tree);
+        Ok(())
+    }
+
+    fn apply(
+        &mut self,
+        filled: Vec<(Vec<u8>, usize, usize, Vec<usize>)>,
+        method: ImputationMethod,
+    ) {
+        for (cell, language, concept, sources) in filled {
+            let block = self.block(concept);
+            self.rows[language][block].copy_from_slice(&cell);
+            self.imputations.push(Imputation {
+                language,
+                concept,
+                method,
+                sources,
+            });
+        }
+    }
+
+    /// Imputed cells as TSV (language, concept, first and last character,
+    /// method, comma-separated source languages) with a header row
+    pub fn to_imputations(&self) -> String {
+        let mut out = String::from("language\tconcept\tfirst\tlast\tmethod\tsources\n");
+        for imputation in &self.imputations {
+            let (concept, first, last) = &self.charsets[imputation.concept];
+            let sources: Vec<&str> = imputation
+                .sources
+                .iter()
+                .map(|&s| self.languages[s].as_str())
+                .collect();
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                self.languages[imputation.language],
+                concept,
+                first,
+                last,
+                imputation.method.name(),
+                sources.join(",")
+            );
+        }
+        out
+    }
+
+    /// NEXUS DATA block with character labels, plus one charset per
+    /// concept in an ASSUMPTIONS block and a NOTES block flagging imputed
+    /// cells
+    pub fn to_nexus(&self) -> String {
+        let names: Vec<String> = self.languages.iter().map(|l| nexus_word(l)).collect();
+        let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+        let mut out = String::from("#NEXUS\n\nBEGIN DATA;\n");
+        let _ = writeln!(
+            out,
+            "    DIMENSIONS NTAX={} NCHAR={};",
+            self.languages.len(),
+            self.characters.len()
+        );
+        out.push_str("    FORMAT DATATYPE=STANDARD MISSING=? GAP=- SYMBOLS=\"01\";\n");
+        if !self.characters.is_empty() {
+            out.push_str("    CHARSTATELABELS\n");
+            for (i, character) in self.characters.iter().enumerate() {
+                let label = format!("{}_{}", character.concept, character.class);
+                let end = if i + 1 == self.characters.len() {
+                    ";"
+                } else {
+                    ","
+                };
+                let _ = writeln!(out, "        {} {}{}", i + 1, nexus_word(&label), end);
+            }
+        }
+        out.push_str("    MATRIX\n");
+        for (l, name) in names.iter().enumerate() {
+            let pad = width - name.chars().count();
+            let _ = writeln!(out, "    {}{} {}", name, " ".repeat(pad), self.row(l));
+        }
+        out.push_str("    ;\nEND;\n");
+        if !self.charsets.is_empty() {
+            out.push_str("\nBEGIN ASSUMPTIONS;\n");
+            for (concept, first, last) in &self.charsets {
+                let _ = writeln!(
+                    out,
+                    "    CHARSET {} = {};",
+                    nexus_word(concept),
+                    range(*first, *last)
+                );
+            }
+            out.push_str("END;\n");
+        }
+        if !self.imputations.is_empty() {
+            out.push_str("\nBEGIN NOTES;\n");
+            for imputation in &self.imputations {
+                let (_, first, last) = &self.charsets[imputation.concept];
+                let sources: Vec<&str> = imputation
+                    .sources
+                    .iter()
+                    .map(|&s| self.languages[s].as_str())
+                    .collect();
+                let text = format!(
+                    "imputed ({}) from {}",
+                    imputation.method.name(),
+                    sources.join(", ")
+                );
+                let _ = writeln!(
+                    out,
+                    "    TEXT TAXON={} CHARACTER={} TEXT={};",
+                    imputation.language + 1,
+                    range(*first, *last),
+                    nexus_word(&text)
+                );
+            }
+            out.push_str("END;\n");
+        }
+        out
+    }
+
+    /// Relaxed PHYLIP; whitespace in language names becomes `_`
+    pub fn to_phylip(&self) -> String {
+        let names: Vec<String> = self.languages.iter().map(|l| phylip_name(l)).collect();
+        let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+        let mut out = format!("{} {}\n", self.languages.len(), self.characters.len());
+        for (l, name) in names.iter().enumerate() {
+            let pad = width - name.chars().count();
+            let _ = writeln!(out, "{}{}  {}", name, " ".repeat(pad), self.row(l));
+        }
+        out
+    }
+
+    /// RAxML-style partition file with one binary partition per concept,
+    /// to accompany `to_phylip`
+    pub fn to_partitions(&self) -> String {
+        let mut out = String::new();
+        for (concept, first, last) in &self.charsets {
+            let _ = writeln!(
+                out,
+                "BIN, {} = {}",
+                phylip_name(concept),
+                range(*first, *last)
+            );
+        }
+        out
+    }
+}
+
+fn range(first: usize, last: usize) -> String {
+    if first == last {
+        first.to_string()
+    } else {
+        format!("{}-{}", first, last)
+    }
+}
+
+/// NEXUS token, single-quoted unless it is plain letters, digits, `_`
+/// and `.`
+fn nexus_word(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+/// PHYLIP-safe name: whitespace runs become `_`
+pub(crate) fn phylip_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> CharacterMatrix {
+        let judgments: Vec<(String, String, String)> = [
+            ("eng", "hand", "1"),
+            ("deu", "hand", "1"),
+            ("lat", "hand", "2"),
+            ("eng", "to go", "b"),
+            ("eng", "to go", "a"),
+            ("deu", "to go", "a"),
+            ("Old Norse", "hand", "1"),
+        ]
+        .iter()
+        .map(|(l, c, k)| (l.to_string(), c.to_string(), k.to_string()))
+        .collect();
+        CharacterMatrix::from_table(&CognateTable::from_judgments(&judgments))
+    }
+
+    #[test]
+    fn test_character_matrix() {
+        let matrix = matrix();
+        assert_eq!(matrix.languages(), ["Old Norse", "deu", "eng", "lat"]);
+        let labels: Vec<(&str, &str)> = matrix
+            .characters()
+            .iter()
+            .map(|c| (c.concept.as_str(), c.class.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            [("hand", "1"), ("hand", "2"), ("to go", "a"), ("to go", "b")]
+        );
+        let rows: Vec<&str> = (0..4).map(|l| matrix.row(l)).collect();
+        assert_eq!(rows, ["10??", "1010", "1011", "01??"]);
+        assert_eq!(
+            matrix.charsets(),
+            [("hand".to_string(), 1, 2), ("to go".to_string(), 3, 4)]
+        );
+    }
+
+    #[test]
+    fn test_nexus_and_phylip() {
+        let matrix = matrix();
+        let nexus = matrix.to_nexus();
+        assert!(nexus.starts_with("#NEXUS\n"));
+        assert!(nexus.contains("DIMENSIONS NTAX=4 NCHAR=4;"));
+        assert!(nexus.contains("        3 'to go_a',\n"));
+        assert!(nexus.contains("    'Old Norse' 10??\n"));
+        assert!(nexus.contains("    eng         1011\n"));
+        assert!(nexus.contains("    CHARSET hand = 1-2;\n    CHARSET 'to go' = 3-4;\n"));
+
+        assert_eq!(
+            matrix.to_phylip(),
+            "4 4\nOld_Norse  10??\ndeu        1010\neng        1011\nlat        01??\n"
+        );
+        assert_eq!(
+            matrix.to_partitions(),
+            "BIN, hand = 1-2\nBIN, to_go = 3-4\n"
+        );
+    }
+
+    #[test]
+    fn test_imputation() {
+        let mut nearest = matrix();
+        nearest.impute_nearest();
+        // Old Norse agrees with deu and eng on hand, deu comes first; lat
+        // disagrees with both, so the first attesting language wins
+        let rows: Vec<&str> = (0..4).map(|l| nearest.row(l)).collect();
+        assert_eq!(rows, ["1010", "1010", "1011", "0110"]);
+        assert_eq!(nearest.imputations().len(), 2);
+        assert_eq!(nearest.imputations()[0].sources, [1]);
+        assert!(nearest.to_nexus().contains(
+            "BEGIN NOTES;\n    TEXT TAXON=1 CHARACTER=3-4 TEXT='imputed (nearest) from deu';\n"
+        ));
+        assert_eq!(
+            nearest.to_imputations().lines().nth(1),
+            Some("Old Norse\tto go\t3\t4\tnearest\tdeu")
+        );
+
+        // On the tree Old Norse sits with eng, whose commonest class (ties
+        // to the first) is a
+        let tree = Tree::from_newick("((('Old Norse',eng),deu),lat);").unwrap();
+        let mut on_tree = matrix();
+        on_tree.impute_on_tree(&tree).unwrap();
+        assert_eq!(on_tree.row(0), "1010");
+        assert_eq!(on_tree.imputations()[0].sources, [2]);
+        assert_eq!(on_tree.imputations()[1].sources, [1, 2]);
+
+        let partial = Tree::from_newick("(eng,deu);").unwrap();
+        assert!(matrix().impute_on_tree(&partial).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//! Checkpoint and resume for long all-pairs runs.
+//!
+//! An `AllPairsRun` holds everything a dense, sparse or pipeline similarity
+//! run needs (inputs, settings) together with its progress: the rows whose
+//! pairs are all computed and the pairs kept from them. Blocks cover rows
+//! in order, so the finished rows are always a prefix and a resumed run
+//! simply starts at the next one. While running, the state is saved as
+//! versioned JSON at a fixed interval, on cancellation and at the end; a
+//! save goes to a temporary file renamed over the previous checkpoint, so a
+//! crash mid-write leaves the last good one in place.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::format::JsonFormat;
+use crate::graph::CognateGraph;
+use crate::jobs::{JobError, JobProgress};
+use crate::phonetic::{similarity_blocks, SimilarityMetric};
+use crate::sparse::SparseSimilarityMatrix;
+use crate::types::SimilarityEdge;
+
+/// Current checkpoint file format version
+pub const CHECKPOINT_FORMAT_VERSION: u32 = 1;
+
+pub const CHECKPOINT_JSON: JsonFormat = JsonFormat {
+    name: "checkpoint",
+    version_key: "format_version",
+    current: CHECKPOINT_FORMAT_VERSION,
+    migrations: &[],
+};
+
+/// What a run produces once all pairs are computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTask {
+    /// Dense n × n matrix (every pair kept, no IDs)
+    Dense,
+    /// Sparse matrix of the pairs at or above the threshold
+    Sparse,
+    /// Graph of the pairs at or above the threshold, every ID a node
+    Pipeline,
+}
+
+/// Inputs, settings and progress of an all-pairs similarity run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AllPairsRun {
+    pub format_version: u32,
+    pub task: RunTask,
+    /// One per IPA string (empty for dense runs)
+    pub ids: Vec<String>,
+    pub ipa_strings: Vec<String>,
+    /// Minimum similarity kept; None keeps every pair
+    pub threshold: Option<f64>,
+    pub metric: String,
+    pub block_size: usize,
+    pub length_filter: bool,
+    /// Rows `0..rows_done` have all their pairs computed
+    pub rows_done: usize,
+    /// Kept upper-triangle (i, j, similarity) pairs of the finished rows
+    pub pairs: Vec<(usize, usize, f64)>,
+}
+
+/// Where and how often a run saves itself
+#[derive(Debug, Clone)]
+pub struct CheckpointPolicy {
+    pub path: PathBuf,
+    pub interval: Duration,
+}
+
+impl AllPairsRun {
+    /// Fresh run; fails on an unknown metric or an ID count that doesn't
+    /// match the IPA strings
+    pub fn new(
+        task: RunTask,
+        ids: Vec<String>,
+        ipa_strings: Vec<String>,
+        threshold: Option<f64>,
+        metric: &str,
+        block_size: usize,
+        length_filter: bool,
+    ) -> Result<Self, String> {
+        let run = Self {
+            format_version: CHECKPOINT_FORMAT_VERSION,
+            task,
+            ids,
+            ipa_strings,
+            threshold,
+            metric: metric.to_string(),
+            block_size,
+            length_filter,
+            rows_done: 0,
+            pairs: Vec::new(),
+        };
+        run.validate()?;
+        Ok(run)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.metric.parse::<SimilarityMetric>()?;
+        let n = self.ipa_strings.len();
+        if self.task != RunTask::Dense && self.ids.len() != n {
+            return Err(format!("{} ids for {} IPA strings", self.ids.len(), n));
+        }
+        if self.rows_done > n || self.pairs.iter().any(|&(i, j, _)| i >= n || j >= n) {
+            return Err(format!("checkpoint progress out of range for {} forms", n));
+        }
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.rows_done >= self.ipa_strings.len()
+    }
+
+    /// Write the run as JSON, replacing `path` only once fully written
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+        let error =
+            |e: std::io::Error| format!("cannot write checkpoint {}: {}", path.display(), e);
+
+        let file = File::create(&temporary).map_err(error)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self)
+            .map_err(|e| format!("cannot write checkpoint: {}", e))?;
+        writer.flush().map_err(error)?;
+        writer.get_ref().sync_all().map_err(error)?;
+        fs::rename(&temporary, path).map_err(error)
+    }
+
+    /// Read a checkpoint written by `save`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("cannot open checkpoint {}: {}", path.display(), e))?;
+        let mut document: serde_json::Value = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("invalid checkpoint file: {}", e))?;
+        CHECKPOINT_JSON.upgrade(&mut document)?;
+        let run: Self = serde_json::from_value(document)
+            .map_err(|e| format!("invalid checkpoint file: {}", e))?;
+        run.validate()?;
+        Ok(run)
+    }
+
+    /// Compute the remaining rows block by block, reporting pairs done as
+    /// progress. With `checkpoint`, the run is saved whenever `interval`
+    /// has passed since the last save, when cancelled and when complete.
+    pub fn run(
+        &mut self,
+        checkpoint: Option<&CheckpointPolicy>,
+        progress: &JobProgress,
+    ) -> Result<(), JobError> {
+        let metric: SimilarityMetric = self.metric.parse()?;
+        let threshold = self.threshold.unwrap_or(f64::NEG_INFINITY);
+        let blocks = similarity_blocks(&self.ipa_strings, self.block_size, threshold)
+            .with_metric(metric)
+            .with_length_filter(self.length_filter)
+            .starting_at(self.rows_done);
+        let mut last_save = Instant::now();
+        for block in blocks {
+            let (done, total) = (block.done, block.total);
+            self.rows_done = block.rows.end;
+            self.pairs.extend(block.pairs);
+            let status = progress.checkpoint(done, total);
+            if let Some(policy) = checkpoint {
+                let due = last_save.elapsed() >= policy.interval;
+                if due || status.is_err() || self.is_complete() {
+                    self.save(&policy.path)?;
+                    last_save = Instant::now();
+                }
+            }
+            status?;
+        }
+        Ok(())
+    }
+
+    /// Dense matrix of the computed pairs (1 on the diagonal)
+    pub fn to_matrix(&self) -> ndarray::Array2<f64> {
+        let mut matrix = ndarray::Array2::<f64>::eye(self.ipa_strings.len());
+        for &(i, j, sim) in &self.pairs {
+            matrix[[i, j]] = sim;
+            matrix[[j, i]] = sim;
+        }
+        matrix
+    }
+
+    pub fn to_sparse(&self) -> Result<SparseSimilarityMatrix, String> {
+        SparseSimilarityMatrix::from_similarity_pairs(self.ids.clone(), &self.pairs)
+    }
+
+    /// Graph with a node per ID (unmatched forms included) and an edge per
+    /// computed pair
+    pub fn to_graph(&self) -> CognateGraph {
+        let mut graph = CognateGraph::new();
+        for id in &self.ids {
+            graph.get_or_create_node(id.clone());
+        }
+        graph.add_edges(
+            self.pairs
+                .iter()
+                .map(|&(i, j, sim)| {
+                    SimilarityEdge::new(self.ids[i].clone(), self.ids[j].clone(), sim)
+                })
+                .collect(),
+        );
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(threshold: Option<f64>) -> AllPairsRun {
+        let forms = ["pater", "fater", "mater", "vasa", "voda", "akwa"];
+        AllPairsRun::new(
+            RunTask::Sparse,
+            (0..forms.len()).map(|i| format!("w{}", i)).collect(),
+            forms.iter().map(|s| s.to_string()).collect(),
+            threshold,
+            "levenshtein",
+            2,
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted_run() {
+        let mut full = run(Some(0.5));
+        full.run(None, &JobProgress::default()).unwrap();
+        assert!(full.is_complete());
+
+        // Cancel after the first block, then resume from the saved file
+        let path = std::env::temp_dir().join(format!("langviz-{}.ckpt", std::process::id()));
+        let policy = CheckpointPolicy {
+            path: path.clone(),
+            interval: Duration::from_secs(3600),
+        };
+        let progress = JobProgress::default();
+        progress.cancel();
+        let mut partial = run(Some(0.5));
+        assert_eq!(
+            partial.run(Some(&policy), &progress),
+            Err(JobError::Cancelled)
+        );
+        let mut resumed = AllPairsRun::load(&path).unwrap();
+        assert_eq!(resumed.rows_done, 2);
+        assert_eq!(resumed, partial);
+
+        let progress = JobProgress::default();
+        resumed.run(Some(&policy), &progress).unwrap();
+        assert_eq!(resumed, full);
+        assert_eq!(progress.get(), (15, 15));
+        assert_eq!(AllPairsRun::load(&path).unwrap(), full);
+        fs::remove_file(&path).unwrap();
+
+        let matrix = full.to_sparse().unwrap();
+        assert_eq!(matrix.get(0, 1), full.to_matrix().get((0, 1)).copied());
+        assert_eq!(full.to_graph().graph.node_count(), 6);
+        let error = AllPairsRun::new(
+            RunTask::Pipeline,
+            vec![],
+            vec!["a".into()],
+            None,
+            "x",
+            1,
+            true,
+        );
+        assert!(error.is_err());
+    }
+}
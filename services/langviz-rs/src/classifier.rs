@@ -0,0 +1,266 @@
+//! Trainable cognate classifier: logistic regression over alignment
+//! features, giving cognate probabilities to use as edge weights.
+//!
+//! Features per IPA pair: edit similarity, LCS ratio, feature-weighted
+//! distance, relative length difference and a correspondence score (how
+//! strongly the pair's aligned substitutions were seen among cognates in the
+//! training data).
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::features::feature_distance;
+use crate::format::CLASSIFIER_JSON;
+use crate::phonetic::{dtw_align, lcs_ratio, phonetic_distance};
+use crate::scoring::length_difference;
+
+/// Number of features per pair
+pub const NUM_PAIR_FEATURES: usize = 5;
+
+/// Feature names in vector order
+pub const PAIR_FEATURE_NAMES: [&str; NUM_PAIR_FEATURES] = [
+    "edit_similarity",
+    "lcs_ratio",
+    "feature_distance",
+    "length_difference",
+    "correspondence_score",
+];
+
+/// Gradient descent settings
+#[derive(Debug, Clone, Copy)]
+pub struct TrainOptions {
+    pub epochs: usize,
+    pub learning_rate: f64,
+    /// L2 penalty on the weights (not the bias)
+    pub l2: f64,
+}
+
+impl Default for TrainOptions {
+    fn default() -> Self {
+        Self {
+            epochs: 500,
+            learning_rate: 0.1,
+            l2: 1e-3,
+        }
+    }
+}
+
+/// Logistic regression cognate model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CognateClassifier {
+    pub weights: [f64; NUM_PAIR_FEATURES],
+    pub bias: f64,
+    /// Standardization applied before the linear model
+    means: [f64; NUM_PAIR_FEATURES],
+    stds: [f64; NUM_PAIR_FEATURES],
+    /// Sorted (segment_a, segment_b, cognate share) with segment_a <= segment_b
+    correspondences: Vec<(String, String, f64)>,
+}
+
+fn ordered(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// Order-independent substitutions of the DTW alignment
+fn substitutions(ipa_a: &str, ipa_b: &str) -> Vec<(String, String)> {
+    dtw_align(ipa_a, ipa_b)
+        .extract_correspondences()
+        .into_iter()
+        .map(|(a, b)| ordered(&a, &b))
+        .collect()
+}
+
+impl CognateClassifier {
+    /// Fit on (ipa_a, ipa_b, is_cognate) pairs
+    pub fn train(pairs: &[(String, String, bool)], options: TrainOptions) -> Result<Self, String> {
+        if !pairs.iter().any(|p| p.2) || pairs.iter().all(|p| p.2) {
+            return Err("training data needs both cognate and non-cognate pairs".to_string());
+        }
+
+        // Correspondence table: share of each substitution seen in cognates
+        let aligned: Vec<Vec<(String, String)>> = pairs
+            .par_iter()
+            .map(|(a, b, _)| substitutions(a, b))
+            .collect();
+        let mut counts: HashMap<(String, String), (f64, f64)> = HashMap::new();
+        for (subs, (_, _, label)) in aligned.iter().zip(pairs) {
+            for sub in subs {
+                let entry = counts.entry(sub.clone()).or_insert((0.0, 0.0));
+                if *label {
+                    entry.0 += 1.0;
+                } else {
+                    entry.1 += 1.0;
+                }
+            }
+        }
+        let mut correspondences: Vec<(String, String, f64)> = counts
+            .into_iter()
+            .map(|((a, b), (pos, neg))| (a, b, pos / (pos + neg + 1.0)))
+            .collect();
+        correspondences.sort_by(|x, y| (&x.0, &x.1).cmp(&(&y.0, &y.1)));
+
+        let mut model = Self {
+            weights: [0.0; NUM_PAIR_FEATURES],
+            bias: 0.0,
+            means: [0.0; NUM_PAIR_FEATURES],
+            stds: [1.0; NUM_PAIR_FEATURES],
+            correspondences,
+        };
+
+        let raw: Vec<[f64; NUM_PAIR_FEATURES]> = pairs
+            .par_iter()
+            .map(|(a, b, _)| model.features(a, b))
+            .collect();
+        let n = raw.len() as f64;
+        for k in 0..NUM_PAIR_FEATURES {
+            let mean = raw.iter().map(|x| x[k]).sum::<f64>() / n;
+            let var = raw.iter().map(|x| (x[k] - mean).powi(2)).sum::<f64>() / n;
+            model.means[k] = mean;
+            model.stds[k] = if var > 1e-12 { var.sqrt() } else { 1.0 };
+        }
+        let xs: Vec<[f64; NUM_PAIR_FEATURES]> = raw.iter().map(|x| model.standardize(x)).collect();
+        let ys: Vec<f64> = pairs.iter().map(|p| if p.2 { 1.0 } else { 0.0 }).collect();
+
+        // Full-batch gradient descent on the log loss
+        for _ in 0..options.epochs {
+            let mut grad_w = [0.0; NUM_PAIR_FEATURES];
+            let mut grad_b = 0.0;
+            for (x, &y) in xs.iter().zip(&ys) {
+                let error = model.linear_probability(x) - y;
+                for (g, xk) in grad_w.iter_mut().zip(x) {
+                    *g += error * xk;
+                }
+                grad_b += error;
+            }
+            for (w, g) in model.weights.iter_mut().zip(grad_w) {
+                *w -= options.learning_rate * (g / n + options.l2 * *w);
+            }
+            model.bias -= options.learning_rate * grad_b / n;
+        }
+
+        Ok(model)
+    }
+
+    /// Raw (unstandardized) feature vector for a pair
+    pub fn features(&self, ipa_a: &str, ipa_b: &str) -> [f64; NUM_PAIR_FEATURES] {
+        [
+            phonetic_distance(ipa_a, ipa_b),
+            lcs_ratio(ipa_a, ipa_b),
+            feature_distance(ipa_a, ipa_b),
+            length_difference(ipa_a, ipa_b),
+            self.correspondence_score(ipa_a, ipa_b),
+        ]
+    }
+
+    /// Mean cognate share of the pair's substitutions (1.0 if none; unseen
+    /// substitutions count as 0)
+    fn correspondence_score(&self, ipa_a: &str, ipa_b: &str) -> f64 {
+        let subs = substitutions(ipa_a, ipa_b);
+        if subs.is_empty() {
+            return 1.0;
+        }
+        let total: f64 = subs
+            .iter()
+            .map(|(a, b)| {
+                self.correspondences
+                    .binary_search_by(|c| (c.0.as_str(), c.1.as_str()).cmp(&(a, b)))
+                    .map_or(0.0, |i| self.correspondences[i].2)
+            })
+            .sum();
+        total / subs.len() as f64
+    }
+
+    fn standardize(&self, x: &[f64; NUM_PAIR_FEATURES]) -> [f64; NUM_PAIR_FEATURES] {
+        let mut z = [0.0; NUM_PAIR_FEATURES];
+        for k in 0..NUM_PAIR_FEATURES {
+            z[k] = (x[k] - self.means[k]) / self.stds[k];
+        }
+        z
+    }
+
+    fn linear_probability(&self, z: &[f64; NUM_PAIR_FEATURES]) -> f64 {
+        let logit: f64 = self.bias + z.iter().zip(&self.weights).map(|(x, w)| x * w).sum::<f64>();
+        sigmoid(logit)
+    }
+
+    /// Cognate probability for one pair
+    pub fn predict_proba(&self, ipa_a: &str, ipa_b: &str) -> f64 {
+        self.linear_probability(&self.standardize(&self.features(ipa_a, ipa_b)))
+    }
+
+    /// Cognate probabilities for many pairs (parallelized)
+    pub fn batch_predict(&self, pairs: &[(String, String)]) -> Vec<f64> {
+        pairs
+            .par_iter()
+            .map(|(a, b)| self.predict_proba(a, b))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_value(self)
+            .map(|model| CLASSIFIER_JSON.stamp(model).to_string())
+            .map_err(|e| format!("cannot serialize classifier: {}", e))
+    }
+
+    /// Read a model saved by `to_json` with any supported format version
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut model: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("invalid classifier JSON: {}", e))?;
+        CLASSIFIER_JSON.upgrade(&mut model)?;
+        serde_json::from_value(model).map_err(|e| format!("invalid classifier JSON: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn training_pairs() -> Vec<(String, String, bool)> {
+        [
+            ("pater", "fater", true),
+            ("pɛd", "fut", true),
+            ("piskis", "fisk", true),
+            ("tres", "θri", true),
+            ("tu", "θu", true),
+            ("pater", "mus", false),
+            ("tres", "hand", false),
+            ("piskis", "rot", false),
+            ("pɛd", "vasɐ", false),
+            ("tu", "blum", false),
+        ]
+        .iter()
+        .map(|(a, b, y)| (a.to_string(), b.to_string(), *y))
+        .collect()
+    }
+
+    #[test]
+    fn test_train_and_predict() {
+        let model = CognateClassifier::train(&training_pairs(), TrainOptions::default()).unwrap();
+
+        let cognate = model.predict_proba("pater", "fater");
+        let unrelated = model.predict_proba("pater", "mus");
+        assert!(cognate > 0.5 && unrelated < 0.5);
+        assert!(cognate <= 1.0 && unrelated >= 0.0);
+
+        let restored = CognateClassifier::from_json(&model.to_json().unwrap()).unwrap();
+        assert_eq!(
+            restored.predict_proba("tu", "θu"),
+            model.predict_proba("tu", "θu")
+        );
+    }
+
+    #[test]
+    fn test_requires_both_classes() {
+        let positives: Vec<_> = training_pairs().into_iter().filter(|p| p.2).collect();
+        assert!(CognateClassifier::train(&positives, TrainOptions::default()).is_err());
+    }
+}
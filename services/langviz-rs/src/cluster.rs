@@ -1,10 +1,24 @@
 //! Clustering primitives for cognate detection.
 
 use ahash::AHashMap;
+use ndarray::{s, Array1, Array2, ArrayView1};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
 use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::error::LangVizError;
+use crate::linalg::{symmetric_eigen, top_eigenpairs};
+use crate::sparse::SparseSimilarityMatrix;
+use crate::types::CognateSet;
+
+/// Largest matrix spectrally clustered with an exact eigendecomposition;
+/// larger ones use subspace iteration on the sparse Laplacian
+const SPECTRAL_EXACT_LIMIT: usize = 300;
 
 /// Union-Find data structure for connected components
+#[derive(Debug, Clone)]
 pub struct UnionFind {
     parent: Vec<usize>,
     rank: Vec<usize>,
@@ -19,6 +33,22 @@ impl UnionFind {
         }
     }
 
+    /// Append a new singleton element and return its index
+    pub fn push(&mut self) -> usize {
+        let index = self.parent.len();
+        self.parent.push(index);
+        self.rank.push(0);
+        index
+    }
+
+    /// Find root without path compression (usable through `&self`)
+    pub fn root(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
     /// Find root with path compression
     pub fn find(&mut self, x: usize) -> usize {
         if self.parent[x] != x {
@@ -50,17 +80,23 @@ impl UnionFind {
         }
     }
 
-    /// Get all connected components
+    /// Get all connected components, ordered by their smallest element,
+    /// members ascending
     pub fn components(&mut self) -> Vec<Vec<usize>> {
         let n = self.parent.len();
-        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut component_of = vec![usize::MAX; n];
+        let mut groups: Vec<Vec<usize>> = Vec::new();
 
         for i in 0..n {
             let root = self.find(i);
-            groups.entry(root).or_insert_with(Vec::new).push(i);
+            if component_of[root] == usize::MAX {
+                component_of[root] = groups.len();
+                groups.push(Vec::new());
+            }
+            groups[component_of[root]].push(i);
         }
 
-        groups.into_values().collect()
+        groups
     }
 }
 
@@ -122,17 +158,565 @@ pub fn threshold_clustering_with_ids(
         .collect()
 }
 
+/// Consensus partition of several clusterings of the same items. The
+/// co-association of two items is the fraction of clusterings putting them
+/// in the same cluster (an item missing from a clustering shares a cluster
+/// with nothing there); items are linked when it reaches `agreement` (pairs
+/// never clustered together stay apart), and the consensus clusters are the
+/// linked components. Items every clustering agrees on are handled as one
+/// unit, so the work grows with the number of distinct label combinations
+/// rather than items. Clusters come largest first, members in ID order.
+pub fn consensus_clustering(
+    partitions: &[Vec<Vec<String>>],
+    agreement: f64,
+) -> Vec<Vec<String>> {
+    let mut index: AHashMap<&str, usize> = AHashMap::new();
+    let mut ids: Vec<&str> = Vec::new();
+    for member in partitions.iter().flatten().flatten() {
+        index.entry(member.as_str()).or_insert_with(|| {
+            ids.push(member.as_str());
+            ids.len() - 1
+        });
+    }
+    let n = ids.len();
+
+    // Label of each item in each partition; None when it is absent
+    let mut labels: Vec<Vec<Option<usize>>> = vec![vec![None; partitions.len()]; n];
+    for (p, partition) in partitions.iter().enumerate() {
+        for (c, cluster) in partition.iter().enumerate() {
+            for member in cluster {
+                labels[index[member.as_str()]][p] = Some(c);
+            }
+        }
+    }
+
+    // Atoms: items with identical label vectors (fully present ones only;
+    // an item absent somewhere can't be grouped by its labels there)
+    let mut atom_of: AHashMap<&[Option<usize>], usize> = AHashMap::new();
+    let mut atoms: Vec<Vec<usize>> = Vec::new();
+    for (item, label) in labels.iter().enumerate() {
+        let atom = if label.iter().all(Option::is_some) {
+            *atom_of.entry(label.as_slice()).or_insert_with(|| {
+                atoms.push(Vec::new());
+                atoms.len() - 1
+            })
+        } else {
+            atoms.push(Vec::new());
+            atoms.len() - 1
+        };
+        atoms[atom].push(item);
+    }
+
+    // Co-association counts between atoms sharing some cluster
+    let mut shared: AHashMap<(usize, usize), usize> = AHashMap::new();
+    for (p, partition) in partitions.iter().enumerate() {
+        let mut by_cluster: Vec<Vec<usize>> = vec![Vec::new(); partition.len()];
+        for (a, atom) in atoms.iter().enumerate() {
+            if let Some(c) = labels[atom[0]][p] {
+                by_cluster[c].push(a);
+            }
+        }
+        for members in by_cluster {
+            for (i, &a) in members.iter().enumerate() {
+                for &b in &members[i + 1..] {
+                    *shared.entry((a, b)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let mut uf = UnionFind::new(atoms.len());
+    let needed = agreement * partitions.len() as f64;
+    for ((a, b), count) in shared {
+        if count as f64 >= needed - 1e-9 {
+            uf.union(a, b);
+        }
+    }
+
+    let mut clusters: Vec<Vec<String>> = uf
+        .components()
+        .into_iter()
+        .map(|component| {
+            let mut members: Vec<String> = component
+                .iter()
+                .flat_map(|&a| atoms[a].iter().map(|&item| ids[item].to_string()))
+                .collect();
+            members.sort();
+            members
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    clusters
+}
+
+/// Consensus of the threshold clusterings of several edge lists over the
+/// same items (one per similarity metric); see [`consensus_clustering`]
+pub fn consensus_from_edges(
+    edge_lists: Vec<Vec<(String, String, f64)>>,
+    threshold: f64,
+    agreement: f64,
+) -> Vec<Vec<String>> {
+    let partitions: Vec<Vec<Vec<String>>> = edge_lists
+        .into_par_iter()
+        .map(|edges| threshold_clustering_with_ids(edges, threshold))
+        .collect();
+    consensus_clustering(&partitions, agreement)
+}
+
+/// How `merge_cognate_sets` combines two collections of cognate sets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeStrategy {
+    /// Sets sharing a word merge, transitively
+    Union,
+    /// Every overlapping pair of sets (one from each side) yields its
+    /// common members; words on one side only are dropped
+    Intersection,
+    /// Sets from the two sides merge, transitively, when their Jaccard
+    /// overlap reaches `min_overlap`; other sets are kept as they are
+    Jaccard { min_overlap: f64 },
+}
+
+impl FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "union" => Ok(MergeStrategy::Union),
+            "intersection" => Ok(MergeStrategy::Intersection),
+            "jaccard" => Ok(MergeStrategy::Jaccard { min_overlap: 0.5 }),
+            other => Err(format!(
+                "unknown merge strategy '{}' (expected union, intersection or jaccard)",
+                other
+            )),
+        }
+    }
+}
+
+impl MergeStrategy {
+    /// Replace the Jaccard strategy's minimum overlap
+    pub fn with_min_overlap(self, value: f64) -> Result<Self, String> {
+        match self {
+            MergeStrategy::Jaccard { .. } if (0.0..=1.0).contains(&value) => {
+                Ok(MergeStrategy::Jaccard { min_overlap: value })
+            }
+            MergeStrategy::Jaccard { .. } => {
+                Err(format!("min_overlap must lie in [0, 1], got {}", value))
+            }
+            _ => Err("only the jaccard strategy takes a minimum overlap".to_string()),
+        }
+    }
+}
+
+/// Merged cognate set with a membership confidence per member
+#[derive(Debug, Clone)]
+pub struct MergedCognateSet {
+    pub set: CognateSet,
+    /// Parallel to `set.members`, in [0, 1]
+    pub confidence: Vec<f64>,
+}
+
+/// Reconcile two collections of cognate sets over the same words (e.g.
+/// from different thresholds or metrics). A member's confidence is the
+/// mean over the two sides of the Jaccard overlap between the words it
+/// shares a set with on that side and its co-members in the merged set (1
+/// when both are empty, 0 on a side that doesn't list it), so 1 means both
+/// sides group it exactly so. Under the Jaccard strategy a word left in
+/// two merged sets stays in the one where its confidence is higher. Sets
+/// come largest first, members in ID order, numbered from 0.
+pub fn merge_cognate_sets(
+    sets_a: &[Vec<String>],
+    sets_b: &[Vec<String>],
+    strategy: MergeStrategy,
+) -> Vec<MergedCognateSet> {
+    let mut index: AHashMap<&str, usize> = AHashMap::new();
+    let mut ids: Vec<&str> = Vec::new();
+    // Each side's sets as sorted, deduplicated word indices
+    let members: [Vec<Vec<usize>>; 2] = [sets_a, sets_b].map(|sets| {
+        sets.iter()
+            .map(|set| {
+                let mut words: Vec<usize> = set
+                    .iter()
+                    .map(|id| {
+                        *index.entry(id.as_str()).or_insert_with(|| {
+                            ids.push(id.as_str());
+                            ids.len() - 1
+                        })
+                    })
+                    .collect();
+                words.sort_unstable();
+                words.dedup();
+                words
+            })
+            .collect()
+    });
+    let n = ids.len();
+    // Sets listing each word, per side
+    let sets_with: [Vec<Vec<usize>>; 2] = [0, 1].map(|side| {
+        let mut sets_with = vec![Vec::new(); n];
+        for (s, words) in members[side].iter().enumerate() {
+            for &w in words {
+                sets_with[w].push(s);
+            }
+        }
+        sets_with
+    });
+
+    let confidence = |w: usize, group: &[usize]| -> f64 {
+        let in_group = group.len() - 1;
+        let agreement = |side: usize| {
+            if sets_with[side][w].is_empty() {
+                return 0.0;
+            }
+            let mut together: Vec<usize> = sets_with[side][w]
+                .iter()
+                .flat_map(|&s| members[side][s].iter().copied())
+                .filter(|&v| v != w)
+                .collect();
+            together.sort_unstable();
+            together.dedup();
+            let shared = together
+                .iter()
+                .filter(|v| group.binary_search(v).is_ok())
+                .count();
+            match together.len() + in_group - shared {
+                0 => 1.0,
+                union => shared as f64 / union as f64,
+            }
+        };
+        (agreement(0) + agreement(1)) / 2.0
+    };
+
+    let groups: Vec<Vec<usize>> = match strategy {
+        MergeStrategy::Union => {
+            let mut uf = UnionFind::new(n);
+            for words in members.iter().flatten() {
+                for pair in words.windows(2) {
+                    uf.union(pair[0], pair[1]);
+                }
+            }
+            uf.components()
+        }
+        MergeStrategy::Intersection => {
+            let mut group_of: AHashMap<(usize, usize), usize> = AHashMap::new();
+            let mut groups: Vec<Vec<usize>> = Vec::new();
+            for (w, sets) in sets_with[0].iter().enumerate() {
+                for &a in sets {
+                    for &b in &sets_with[1][w] {
+                        let g = *group_of.entry((a, b)).or_insert_with(|| {
+                            groups.push(Vec::new());
+                            groups.len() - 1
+                        });
+                        groups[g].push(w);
+                    }
+                }
+            }
+            groups
+        }
+        MergeStrategy::Jaccard { min_overlap } => {
+            let offset = members[0].len();
+            let mut uf = UnionFind::new(offset + members[1].len());
+            for (a, words) in members[0].iter().enumerate() {
+                let mut shared: AHashMap<usize, usize> = AHashMap::new();
+                for &w in words {
+                    for &b in &sets_with[1][w] {
+                        *shared.entry(b).or_insert(0) += 1;
+                    }
+                }
+                for (b, count) in shared {
+                    let union = words.len() + members[1][b].len() - count;
+                    if count as f64 >= min_overlap * union as f64 - 1e-9 {
+                        uf.union(a, offset + b);
+                    }
+                }
+            }
+            let candidates: Vec<Vec<usize>> = uf
+                .components()
+                .into_iter()
+                .map(|component| {
+                    let mut words: Vec<usize> = component
+                        .iter()
+                        .flat_map(|&s| match s.checked_sub(offset) {
+                            Some(b) => &members[1][b],
+                            None => &members[0][s],
+                        })
+                        .copied()
+                        .collect();
+                    words.sort_unstable();
+                    words.dedup();
+                    words
+                })
+                .filter(|words| !words.is_empty())
+                .collect();
+
+            // A word shared by unmerged sets stays where it fits best
+            let mut best: Vec<(f64, usize)> = vec![(f64::NEG_INFINITY, 0); n];
+            for (g, words) in candidates.iter().enumerate() {
+                for &w in words {
+                    let score = confidence(w, words);
+                    if score > best[w].0 {
+                        best[w] = (score, g);
+                    }
+                }
+            }
+            candidates
+                .iter()
+                .enumerate()
+                .map(|(g, words)| words.iter().copied().filter(|&w| best[w].1 == g).collect())
+                .collect()
+        }
+    };
+
+    let mut merged: Vec<Vec<(String, f64)>> = groups
+        .par_iter()
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            let mut scored: Vec<(String, f64)> = group
+                .iter()
+                .map(|&w| (ids[w].to_string(), confidence(w, group)))
+                .collect();
+            scored.sort_by(|a, b| a.0.cmp(&b.0));
+            scored
+        })
+        .collect();
+    merged.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].0.cmp(&b[0].0)));
+    merged
+        .into_iter()
+        .enumerate()
+        .map(|(id, scored)| {
+            let (members, confidence) = scored.into_iter().unzip();
+            MergedCognateSet {
+                set: CognateSet::new(id, members),
+                confidence,
+            }
+        })
+        .collect()
+}
+
+/// Cluster distance update for agglomerative clustering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+    /// Closest pair of members
+    Single,
+    /// Farthest pair of members
+    Complete,
+    /// Mean over all member pairs (UPGMA)
+    Average,
+}
+
+impl FromStr for Linkage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(Linkage::Single),
+            "complete" => Ok(Linkage::Complete),
+            "average" | "upgma" => Ok(Linkage::Average),
+            other => Err(format!(
+                "unknown linkage '{}' (expected single, complete or average)",
+                other
+            )),
+        }
+    }
+}
+
+/// One merge of the dendrogram. Clusters are numbered as in scipy: leaves
+/// are `0..n`, and merge `i` creates cluster `n + i`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Merge {
+    pub left: usize,
+    pub right: usize,
+    pub distance: f64,
+    /// Leaves in the merged cluster
+    pub size: usize,
+}
+
+/// Full merge tree of an agglomerative clustering
+#[derive(Debug, Clone)]
+pub struct Dendrogram {
+    /// Leaf IDs (sorted)
+    pub ids: Vec<String>,
+    /// Merges by non-decreasing distance
+    pub merges: Vec<Merge>,
+}
+
+impl Dendrogram {
+    /// Flat clusters after applying the first `count` merges, ordered by
+    /// their first leaf
+    fn clusters_after(&self, count: usize) -> Vec<Vec<String>> {
+        let n = self.ids.len();
+        let mut uf = UnionFind::new(2 * n);
+        for (i, merge) in self.merges.iter().take(count).enumerate() {
+            uf.union(merge.left, n + i);
+            uf.union(merge.right, n + i);
+        }
+
+        let mut labels: AHashMap<usize, usize> = AHashMap::new();
+        let mut clusters: Vec<Vec<String>> = Vec::new();
+        for (leaf, id) in self.ids.iter().enumerate() {
+            let root = uf.find(leaf);
+            let label = *labels.entry(root).or_insert_with(|| {
+                clusters.push(Vec::new());
+                clusters.len() - 1
+            });
+            clusters[label].push(id.clone());
+        }
+        clusters
+    }
+
+    /// Cut into (at most) `k` clusters
+    pub fn cut(&self, k: usize) -> Vec<Vec<String>> {
+        let merges = self.ids.len().saturating_sub(k.max(1));
+        self.clusters_after(merges.min(self.merges.len()))
+    }
+
+    /// Cut at a distance: keep merges at or below `height`
+    pub fn cut_height(&self, height: f64) -> Vec<Vec<String>> {
+        let count = self.merges.partition_point(|m| m.distance <= height);
+        self.clusters_after(count)
+    }
+}
+
+/// Index of pair (i, j), i < j, in a condensed distance matrix
+fn condensed_index(n: usize, i: usize, j: usize) -> usize {
+    let (i, j) = if i < j { (i, j) } else { (j, i) };
+    n * i - i * (i + 1) / 2 + j - i - 1
+}
+
+/// Agglomerative clustering over similarity edges with distance
+/// `1 - similarity` (pairs without an edge are at distance 1). Uses the
+/// nearest-neighbor chain algorithm: O(n²) time and a condensed O(n²)
+/// distance matrix.
+pub fn agglomerative_clustering(
+    similarities: Vec<(String, String, f64)>,
+    linkage: Linkage,
+) -> Dendrogram {
+    let mut ids: Vec<String> = similarities
+        .iter()
+        .flat_map(|(a, b, _)| [a.clone(), b.clone()])
+        .collect();
+    ids.sort();
+    ids.dedup();
+    let n = ids.len();
+    let id_to_idx: AHashMap<&str, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(idx, id)| (id.as_str(), idx))
+        .collect();
+
+    let mut distance = vec![1.0; n * n.saturating_sub(1) / 2];
+    for (a, b, sim) in &similarities {
+        let (i, j) = (id_to_idx[a.as_str()], id_to_idx[b.as_str()]);
+        if i != j {
+            distance[condensed_index(n, i, j)] = 1.0 - sim;
+        }
+    }
+
+    let mut active = vec![true; n];
+    let mut size = vec![1usize; n];
+    let mut chain: Vec<usize> = Vec::new();
+    // (absorbed slot, surviving slot, distance) in discovery order
+    let mut raw_merges: Vec<(usize, usize, f64)> = Vec::with_capacity(n.saturating_sub(1));
+
+    while raw_merges.len() + 1 < n {
+        if chain.is_empty() {
+            chain.push(active.iter().position(|&a| a).unwrap());
+        }
+        let (a, b) = loop {
+            let a = chain[chain.len() - 1];
+            let previous = (chain.len() > 1).then(|| chain[chain.len() - 2]);
+            // Nearest active neighbor, preferring the previous chain element
+            let mut best = previous;
+            let mut best_distance = previous.map_or(f64::INFINITY, |p| {
+                distance[condensed_index(n, a, p)]
+            });
+            for k in (0..n).filter(|&k| active[k] && k != a) {
+                let d = distance[condensed_index(n, a, k)];
+                if d < best_distance {
+                    best = Some(k);
+                    best_distance = d;
+                }
+            }
+            let b = best.unwrap();
+            if Some(b) == previous {
+                break (a, b);
+            }
+            chain.push(b);
+        };
+        chain.truncate(chain.len() - 2);
+
+        let d_ab = distance[condensed_index(n, a, b)];
+        raw_merges.push((a, b, d_ab));
+        for k in (0..n).filter(|&k| active[k] && k != a && k != b) {
+            let d_ak = distance[condensed_index(n, a, k)];
+            let d_bk = distance[condensed_index(n, b, k)];
+            distance[condensed_index(n, b, k)] = match linkage {
+                Linkage::Single => d_ak.min(d_bk),
+                Linkage::Complete => d_ak.max(d_bk),
+                Linkage::Average => {
+                    (size[a] as f64 * d_ak + size[b] as f64 * d_bk) / (size[a] + size[b]) as f64
+                }
+            };
+        }
+        active[a] = false;
+        size[b] += size[a];
+    }
+
+    // Order by distance (stable, so a merge stays after the ones it builds
+    // on) and relabel slots with scipy-style cluster numbers
+    raw_merges.sort_by(|x, y| x.2.total_cmp(&y.2));
+    let mut uf = UnionFind::new(n);
+    let mut label: Vec<usize> = (0..n).collect();
+    let mut leaves = vec![1usize; n];
+    let merges = raw_merges
+        .into_iter()
+        .enumerate()
+        .map(|(i, (a, b, distance))| {
+            let (root_a, root_b) = (uf.find(a), uf.find(b));
+            let merge = Merge {
+                left: label[root_a].min(label[root_b]),
+                right: label[root_a].max(label[root_b]),
+                distance,
+                size: leaves[root_a] + leaves[root_b],
+            };
+            uf.union(root_a, root_b);
+            let root = uf.find(a);
+            label[root] = n + i;
+            leaves[root] = merge.size;
+            merge
+        })
+        .collect();
+
+    Dendrogram { ids, merges }
+}
+
+/// Similarity per unordered (min, max) index pair
+type SimilarityLookup = HashMap<(usize, usize), f64>;
+
+/// Similarity of an item pair for the quality indices; pairs without one
+/// are left out
+type PairSimilarity<'a> = dyn Fn(usize, usize) -> Option<f64> + Sync + 'a;
+
+fn similarity_lookup(similarities: &[(usize, usize, f64)]) -> SimilarityLookup {
+    similarities
+        .iter()
+        .map(|&(i, j, sim)| ((i.min(j), i.max(j)), sim))
+        .collect()
+}
+
+/// `SimilarityLookup` as a `PairSimilarity`
+fn lookup_pairs(sim_map: &SimilarityLookup) -> impl Fn(usize, usize) -> Option<f64> + Sync + '_ {
+    |i, j| sim_map.get(&(i.min(j), i.max(j))).copied()
+}
+
 /// Compute silhouette score for clustering quality
 pub fn silhouette_score(
     similarities: &[(usize, usize, f64)],
     clusters: &[Vec<usize>],
 ) -> f64 {
-    // Build similarity lookup
-    let mut sim_map: HashMap<(usize, usize), f64> = HashMap::new();
-    for &(i, j, sim) in similarities {
-        sim_map.insert((i.min(j), i.max(j)), sim);
-    }
+    silhouette_with(&lookup_pairs(&similarity_lookup(similarities)), clusters)
+}
 
+fn silhouette_with(sim: &PairSimilarity, clusters: &[Vec<usize>]) -> f64 {
     // Find cluster assignment for each point
     let mut cluster_assignment: HashMap<usize, usize> = HashMap::new();
     for (cluster_id, cluster) in clusters.iter().enumerate() {
@@ -141,8 +725,10 @@ pub fn silhouette_score(
         }
     }
 
-    // Compute silhouette for each point
-    let points: Vec<usize> = cluster_assignment.keys().copied().collect();
+    // Compute silhouette for each point, in index order so the mean is
+    // summed the same way every run
+    let mut points: Vec<usize> = cluster_assignment.keys().copied().collect();
+    points.sort_unstable();
 
     let scores: Vec<f64> = points
         .par_iter()
@@ -159,9 +745,8 @@ pub fn silhouette_score(
             let mut intra_count = 0;
             for &other in cluster {
                 if other != point {
-                    let key = (point.min(other), point.max(other));
-                    if let Some(&sim) = sim_map.get(&key) {
-                        intra_sum += 1.0 - sim; // Convert similarity to distance
+                    if let Some(s) = sim(point, other) {
+                        intra_sum += 1.0 - s; // Convert similarity to distance
                         intra_count += 1;
                     }
                 }
@@ -179,9 +764,8 @@ pub fn silhouette_score(
                     let mut inter_sum = 0.0;
                     let mut inter_count = 0;
                     for &other in other_cluster {
-                        let key = (point.min(other), point.max(other));
-                        if let Some(&sim) = sim_map.get(&key) {
-                            inter_sum += 1.0 - sim;
+                        if let Some(s) = sim(point, other) {
+                            inter_sum += 1.0 - s;
                             inter_count += 1;
                         }
                     }
@@ -217,11 +801,10 @@ pub fn within_cluster_variance(
     similarities: &[(usize, usize, f64)],
     clusters: &[Vec<usize>],
 ) -> f64 {
-    let mut sim_map: HashMap<(usize, usize), f64> = HashMap::new();
-    for &(i, j, sim) in similarities {
-        sim_map.insert((i.min(j), i.max(j)), sim);
-    }
+    within_variance_with(&lookup_pairs(&similarity_lookup(similarities)), clusters)
+}
 
+fn within_variance_with(sim: &PairSimilarity, clusters: &[Vec<usize>]) -> f64 {
     let mut total_variance = 0.0;
     let mut total_pairs = 0;
 
@@ -235,9 +818,8 @@ pub fn within_cluster_variance(
         let mut count = 0;
         for i in 0..cluster.len() {
             for j in i + 1..cluster.len() {
-                let key = (cluster[i].min(cluster[j]), cluster[i].max(cluster[j]));
-                if let Some(&sim) = sim_map.get(&key) {
-                    sum += sim;
+                if let Some(s) = sim(cluster[i], cluster[j]) {
+                    sum += s;
                     count += 1;
                 }
             }
@@ -250,9 +832,8 @@ pub fn within_cluster_variance(
             let mut variance_sum = 0.0;
             for i in 0..cluster.len() {
                 for j in i + 1..cluster.len() {
-                    let key = (cluster[i].min(cluster[j]), cluster[i].max(cluster[j]));
-                    if let Some(&sim) = sim_map.get(&key) {
-                        variance_sum += (sim - mean).powi(2);
+                    if let Some(s) = sim(cluster[i], cluster[j]) {
+                        variance_sum += (s - mean).powi(2);
                     }
                 }
             }
@@ -269,6 +850,600 @@ pub fn within_cluster_variance(
     }
 }
 
+/// (sum, count) of known distances (1 - similarity) within `a` when `b` is
+/// None, or between `a` and `b`
+fn distance_sum(sim: &PairSimilarity, a: &[usize], b: Option<&[usize]>) -> (f64, usize) {
+    let (mut sum, mut count) = (0.0, 0);
+    for (i, &x) in a.iter().enumerate() {
+        let others = b.unwrap_or(&a[i + 1..]);
+        for &y in others {
+            if let Some(s) = sim(x, y) {
+                sum += 1.0 - s;
+                count += 1;
+            }
+        }
+    }
+    (sum, count)
+}
+
+/// Davies-Bouldin index on distances (1 - similarity), lower is better.
+///
+/// Without coordinates there are no centroids, so a cluster's scatter is
+/// its mean pairwise distance and the separation of two clusters their
+/// mean cross distance; each cluster takes its worst ratio
+/// (scatter_a + scatter_b) / separation, and the index is their mean.
+pub fn davies_bouldin_index(
+    similarities: &[(usize, usize, f64)],
+    clusters: &[Vec<usize>],
+) -> f64 {
+    davies_bouldin_with(&lookup_pairs(&similarity_lookup(similarities)), clusters)
+}
+
+fn davies_bouldin_with(sim: &PairSimilarity, clusters: &[Vec<usize>]) -> f64 {
+    let scatter: Vec<f64> = clusters
+        .par_iter()
+        .map(|cluster| match distance_sum(sim, cluster, None) {
+            (sum, count) if count > 0 => sum / count as f64,
+            _ => 0.0,
+        })
+        .collect();
+    // Worst ratio per cluster; pairs with no known (or zero) separation are
+    // skipped, as are clusters left with none
+    let worst: Vec<Option<f64>> = (0..clusters.len())
+        .into_par_iter()
+        .map(|a| {
+            (0..clusters.len())
+                .filter(|&b| b != a)
+                .filter_map(|b| match distance_sum(sim, &clusters[a], Some(&clusters[b])) {
+                    (sum, count) if count > 0 && sum > 0.0 => {
+                        Some((scatter[a] + scatter[b]) / (sum / count as f64))
+                    }
+                    _ => None,
+                })
+                .reduce(f64::max)
+        })
+        .collect();
+    let ratios: Vec<f64> = worst.into_iter().flatten().collect();
+    if ratios.is_empty() {
+        0.0
+    } else {
+        ratios.iter().sum::<f64>() / ratios.len() as f64
+    }
+}
+
+/// Mean within-cluster distance over mean between-cluster distance (1 -
+/// similarity, known pairs only), lower is better; 0 without known
+/// between-cluster pairs
+pub fn within_between_ratio(
+    similarities: &[(usize, usize, f64)],
+    clusters: &[Vec<usize>],
+) -> f64 {
+    within_between_with(&lookup_pairs(&similarity_lookup(similarities)), clusters)
+}
+
+fn within_between_with(sim: &PairSimilarity, clusters: &[Vec<usize>]) -> f64 {
+    let per_cluster: Vec<[(f64, usize); 2]> = (0..clusters.len())
+        .into_par_iter()
+        .map(|a| {
+            let within = distance_sum(sim, &clusters[a], None);
+            let between = clusters[a + 1..]
+                .iter()
+                .map(|other| distance_sum(sim, &clusters[a], Some(other)))
+                .fold((0.0, 0), |(s, c), (sum, count)| (s + sum, c + count));
+            [within, between]
+        })
+        .collect();
+    let total = |k: usize| {
+        per_cluster
+            .iter()
+            .fold((0.0, 0), |(s, c), sums| (s + sums[k].0, c + sums[k].1))
+    };
+    let ((within, within_count), (between, between_count)) = (total(0), total(1));
+    if within_count == 0 || between_count == 0 || between == 0.0 {
+        return 0.0;
+    }
+    (within / within_count as f64) / (between / between_count as f64)
+}
+
+/// Internal quality indices of one clustering
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusteringScores {
+    pub silhouette: f64,
+    pub within_cluster_variance: f64,
+    pub davies_bouldin: f64,
+    pub within_between_ratio: f64,
+    pub num_clusters: usize,
+    pub num_singletons: usize,
+}
+
+fn scores_with(sim: &PairSimilarity, clusters: &[Vec<usize>]) -> ClusteringScores {
+    ClusteringScores {
+        silhouette: silhouette_with(sim, clusters),
+        within_cluster_variance: within_variance_with(sim, clusters),
+        davies_bouldin: davies_bouldin_with(sim, clusters),
+        within_between_ratio: within_between_with(sim, clusters),
+        num_clusters: clusters.len(),
+        num_singletons: clusters.iter().filter(|c| c.len() == 1).count(),
+    }
+}
+
+/// Score many candidate clusterings of the same items against one shared
+/// similarity lookup, in parallel, e.g. to pick a threshold or k
+pub fn evaluate_clusterings(
+    similarities: &[(usize, usize, f64)],
+    clusterings: &[Vec<Vec<usize>>],
+) -> Vec<ClusteringScores> {
+    let sim_map = similarity_lookup(similarities);
+    let sim = lookup_pairs(&sim_map);
+    clusterings
+        .par_iter()
+        .map(|clusters| scores_with(&sim, clusters))
+        .collect()
+}
+
+/// Clusters of entry IDs resolved against a square sparse similarity
+/// matrix, for the quality indices without a materialized similarity list.
+/// Pairs absent from the matrix (in both directions) count as
+/// `missing_distance`, so every pair is known.
+pub struct SparseClusters<'a> {
+    matrix: &'a SparseSimilarityMatrix,
+    clusters: Vec<Vec<usize>>,
+    missing_similarity: f64,
+}
+
+impl<'a> SparseClusters<'a> {
+    pub fn new(
+        matrix: &'a SparseSimilarityMatrix,
+        clusters: &[Vec<String>],
+        missing_distance: f64,
+    ) -> Result<Self, LangVizError> {
+        if matrix.entry_ids() != matrix.col_ids() {
+            let (rows, cols) = matrix.shape();
+            return Err(LangVizError::DimensionMismatch {
+                what: "similarity matrix".to_string(),
+                expected: "square with the same row and column IDs".to_string(),
+                actual: format!("{}x{}", rows, cols),
+            });
+        }
+        if !missing_distance.is_finite() || missing_distance < 0.0 {
+            return Err(LangVizError::InvalidParameter {
+                name: "missing_distance".to_string(),
+                value: missing_distance,
+                expected: "a finite distance >= 0".to_string(),
+            });
+        }
+        let index: HashMap<&str, usize> = matrix
+            .entry_ids()
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.as_str(), i))
+            .collect();
+        let clusters = clusters
+            .iter()
+            .map(|cluster| {
+                cluster
+                    .iter()
+                    .map(|id| {
+                        index
+                            .get(id.as_str())
+                            .copied()
+                            .ok_or_else(|| LangVizError::UnknownEntryId(id.clone()))
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            matrix,
+            clusters,
+            missing_similarity: 1.0 - missing_distance,
+        })
+    }
+
+    fn similarity(&self, i: usize, j: usize) -> Option<f64> {
+        let stored = self.matrix.get(i, j).or_else(|| self.matrix.get(j, i));
+        Some(stored.unwrap_or(self.missing_similarity))
+    }
+
+    pub fn silhouette(&self) -> f64 {
+        silhouette_with(&|i, j| self.similarity(i, j), &self.clusters)
+    }
+
+    pub fn within_cluster_variance(&self) -> f64 {
+        within_variance_with(&|i, j| self.similarity(i, j), &self.clusters)
+    }
+
+    pub fn davies_bouldin(&self) -> f64 {
+        davies_bouldin_with(&|i, j| self.similarity(i, j), &self.clusters)
+    }
+
+    pub fn within_between_ratio(&self) -> f64 {
+        within_between_with(&|i, j| self.similarity(i, j), &self.clusters)
+    }
+
+    /// All four indices and the cluster counts
+    pub fn scores(&self) -> ClusteringScores {
+        scores_with(&|i, j| self.similarity(i, j), &self.clusters)
+    }
+}
+
+fn squared_distance(a: ArrayView1<f64>, b: ArrayView1<f64>) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Lloyd's k-means on the rows of `points` with k-means++ seeding, keeping
+/// the lowest-inertia of `restarts` runs. Returns a cluster label per row;
+/// a cluster that empties keeps its previous center, so fewer than `k`
+/// labels may be used.
+pub fn kmeans(points: &Array2<f64>, k: usize, restarts: usize, seed: u64) -> Vec<usize> {
+    let n = points.nrows();
+    let k = k.min(n);
+    if k == 0 {
+        return vec![0; n];
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut best: Option<(f64, Vec<usize>)> = None;
+
+    for _ in 0..restarts.max(1) {
+        // k-means++: each new center drawn proportionally to its squared
+        // distance from the nearest chosen one
+        let mut centers = Array2::<f64>::zeros((k, points.ncols()));
+        centers.row_mut(0).assign(&points.row(rng.gen_range(0..n)));
+        let mut nearest: Vec<f64> = points
+            .rows()
+            .into_iter()
+            .map(|p| squared_distance(p, centers.row(0)))
+            .collect();
+        for c in 1..k {
+            let total: f64 = nearest.iter().sum();
+            let next = if total > 0.0 {
+                let mut target = rng.gen::<f64>() * total;
+                nearest
+                    .iter()
+                    .position(|&d| {
+                        target -= d;
+                        target < 0.0
+                    })
+                    .unwrap_or(n - 1)
+            } else {
+                rng.gen_range(0..n)
+            };
+            centers.row_mut(c).assign(&points.row(next));
+            for (d, p) in nearest.iter_mut().zip(points.rows()) {
+                *d = d.min(squared_distance(p, centers.row(c)));
+            }
+        }
+
+        let mut labels = vec![usize::MAX; n];
+        for _ in 0..300 {
+            let assigned: Vec<usize> = points
+                .rows()
+                .into_iter()
+                .map(|p| {
+                    (0..k)
+                        .min_by(|&a, &b| {
+                            squared_distance(p, centers.row(a))
+                                .total_cmp(&squared_distance(p, centers.row(b)))
+                        })
+                        .unwrap_or(0)
+                })
+                .collect();
+            if assigned == labels {
+                break;
+            }
+            labels = assigned;
+
+            let mut sums = Array2::<f64>::zeros(centers.raw_dim());
+            let mut counts = vec![0usize; k];
+            for (p, &label) in points.rows().into_iter().zip(&labels) {
+                let mut sum = sums.row_mut(label);
+                sum += &p;
+                counts[label] += 1;
+            }
+            for (c, &count) in counts.iter().enumerate() {
+                if count > 0 {
+                    let mean: Array1<f64> = &sums.row(c) / count as f64;
+                    centers.row_mut(c).assign(&mean);
+                }
+            }
+        }
+
+        let inertia: f64 = points
+            .rows()
+            .into_iter()
+            .zip(&labels)
+            .map(|(p, &label)| squared_distance(p, centers.row(label)))
+            .sum();
+        if !matches!(&best, Some((b, _)) if *b <= inertia) {
+            best = Some((inertia, labels));
+        }
+    }
+
+    best.map(|(_, labels)| labels).unwrap_or_default()
+}
+
+/// Spectral clustering (Ng, Jordan & Weiss) of a square similarity matrix
+/// into at most `k` clusters, with no similarity threshold: the
+/// eigenvectors of the `k` smallest eigenvalues of the normalized Laplacian
+/// embed each entry, embedding rows are scaled to unit length, and k-means
+/// groups them. Clusters come largest first, members in ID order.
+pub fn spectral_clustering(
+    matrix: &SparseSimilarityMatrix,
+    k: usize,
+    seed: u64,
+) -> Result<Vec<Vec<String>>, String> {
+    if k == 0 {
+        return Err("spectral clustering needs k >= 1".to_string());
+    }
+    let laplacian = matrix.normalized_laplacian()?;
+    let ids = laplacian.entry_ids();
+    let n = ids.len();
+    let k = k.min(n);
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    // Laplacian eigenvalues lie in [0, 2], so the smallest ones belong to
+    // the largest of the positive semi-definite 2I - L
+    let vectors = if n <= SPECTRAL_EXACT_LIMIT {
+        let mut shifted = Array2::<f64>::eye(n) * 2.0;
+        for (i, j, value) in laplacian.triplets() {
+            shifted[[i, j]] -= value;
+        }
+        symmetric_eigen(&shifted).1
+    } else {
+        let apply = |x: &Array2<f64>| {
+            let mut y = x * 2.0;
+            for (mut column, input) in y.columns_mut().into_iter().zip(x.columns()) {
+                column -= &laplacian.matvec(&input.to_owned());
+            }
+            y
+        };
+        top_eigenpairs(apply, n, k, 1000, seed).1
+    };
+
+    let mut embedding = vectors.slice(s![.., ..k]).to_owned();
+    for mut row in embedding.rows_mut() {
+        let norm = row.dot(&row).sqrt();
+        if norm > 1e-12 {
+            row.mapv_inplace(|v| v / norm);
+        }
+    }
+
+    let labels = kmeans(&embedding, k, 10, seed);
+    let mut clusters = vec![Vec::new(); k];
+    for (id, label) in ids.iter().zip(labels) {
+        clusters[label].push(id.clone());
+    }
+    clusters.retain(|c| !c.is_empty());
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    Ok(clusters)
+}
+
+/// Stopping rule for `divisive_clustering`: a cluster is bisected only if
+/// the best split passes every test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DivisiveParams {
+    /// Largest conductance of an accepted split: cut weight over the
+    /// smaller side's volume within the cluster
+    pub max_conductance: f64,
+    /// Smallest modularity gain (over the whole graph) of an accepted split
+    pub min_modularity_gain: f64,
+    /// Smallest side of an accepted split
+    pub min_size: usize,
+}
+
+impl Default for DivisiveParams {
+    fn default() -> Self {
+        Self {
+            max_conductance: 0.5,
+            min_modularity_gain: 0.0,
+            min_size: 1,
+        }
+    }
+}
+
+/// Symmetrized weighted adjacency lists (self-loops dropped)
+fn adjacency(matrix: &SparseSimilarityMatrix) -> Vec<Vec<(usize, f64)>> {
+    let mut neighbors = vec![Vec::new(); matrix.entry_ids().len()];
+    for (i, j, weight) in matrix.triplets() {
+        if i != j && weight > 0.0 {
+            neighbors[i].push((j, weight / 2.0));
+            neighbors[j].push((i, weight / 2.0));
+        }
+    }
+    neighbors
+}
+
+/// Connected components of the subgraph induced by `members`
+fn induced_components(neighbors: &[Vec<(usize, f64)>], members: &[usize]) -> Vec<Vec<usize>> {
+    let local: AHashMap<usize, usize> = members.iter().enumerate().map(|(k, &i)| (i, k)).collect();
+    let mut components = UnionFind::new(members.len());
+    for (k, &i) in members.iter().enumerate() {
+        for (j, _) in &neighbors[i] {
+            if let Some(&l) = local.get(j) {
+                components.union(k, l);
+            }
+        }
+    }
+    components
+        .components()
+        .into_iter()
+        .map(|component| component.into_iter().map(|k| members[k]).collect())
+        .collect()
+}
+
+/// `members` ordered by their Fiedler vector entry, the eigenvector of the
+/// second smallest eigenvalue of the cluster's normalized Laplacian
+/// (rescaled by 1/sqrt(degree))
+fn fiedler_order(neighbors: &[Vec<(usize, f64)>], members: &[usize], seed: u64) -> Vec<usize> {
+    let n = members.len();
+    let local: AHashMap<usize, usize> = members.iter().enumerate().map(|(k, &i)| (i, k)).collect();
+    let edges: Vec<Vec<(usize, f64)>> = members
+        .iter()
+        .map(|i| {
+            neighbors[*i]
+                .iter()
+                .filter_map(|(j, w)| local.get(j).map(|&k| (k, *w)))
+                .collect()
+        })
+        .collect();
+    let scale: Vec<f64> = edges
+        .iter()
+        .map(|row| {
+            let degree: f64 = row.iter().map(|(_, w)| w).sum();
+            if degree > 0.0 {
+                degree.sqrt().recip()
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    // Second eigenvector of 2I - L, whose top one is sqrt(degree)
+    let vector = if n <= SPECTRAL_EXACT_LIMIT {
+        let mut shifted = Array2::<f64>::eye(n);
+        for (i, row) in edges.iter().enumerate() {
+            for &(j, w) in row {
+                shifted[[i, j]] += w * scale[i] * scale[j];
+            }
+        }
+        symmetric_eigen(&shifted).1.column(1).to_owned()
+    } else {
+        let apply = |x: &Array2<f64>| {
+            let mut y = x.clone();
+            for (i, row) in edges.iter().enumerate() {
+                for &(j, w) in row {
+                    let weight = w * scale[i] * scale[j];
+                    for c in 0..x.ncols() {
+                        y[[i, c]] += weight * x[[j, c]];
+                    }
+                }
+            }
+            y
+        };
+        top_eigenpairs(apply, n, 2, 1000, seed)
+            .1
+            .column(1)
+            .to_owned()
+    };
+
+    let mut order: Vec<usize> = (0..n).collect();
+    let value = |k: usize| vector[k] * scale[k];
+    order.sort_by(|&a, &b| value(a).total_cmp(&value(b)).then(a.cmp(&b)));
+    order.into_iter().map(|k| members[k]).collect()
+}
+
+/// Bisect `members` at the Fiedler sweep cut of lowest conductance, if it
+/// passes `params`; `members` must induce a connected subgraph
+fn spectral_bisection(
+    neighbors: &[Vec<(usize, f64)>],
+    degrees: &[f64],
+    two_m: f64,
+    members: &[usize],
+    params: &DivisiveParams,
+    seed: u64,
+) -> Option<(Vec<usize>, Vec<usize>)> {
+    let n = members.len();
+    if n < 2 * params.min_size.max(1) {
+        return None;
+    }
+    let order = fiedler_order(neighbors, members, seed);
+    let position: AHashMap<usize, usize> = order.iter().enumerate().map(|(k, &i)| (i, k)).collect();
+    let internal_degree = |i: usize| {
+        neighbors[i]
+            .iter()
+            .filter(|(j, _)| position.contains_key(j))
+            .map(|(_, w)| w)
+            .sum::<f64>()
+    };
+    let volume: f64 = order.iter().map(|&i| internal_degree(i)).sum();
+    let volume_global: f64 = order.iter().map(|&i| degrees[i]).sum();
+
+    // Grow the prefix one node at a time, tracking its cut and volumes
+    let (mut cut, mut prefix_volume, mut prefix_global) = (0.0, 0.0, 0.0);
+    let mut best: Option<(f64, usize, f64, f64)> = None;
+    for (k, &i) in order[..n - 1].iter().enumerate() {
+        let to_prefix: f64 = neighbors[i]
+            .iter()
+            .filter(|(j, _)| position.get(j).is_some_and(|&p| p < k))
+            .map(|(_, w)| w)
+            .sum();
+        cut += internal_degree(i) - 2.0 * to_prefix;
+        prefix_volume += internal_degree(i);
+        prefix_global += degrees[i];
+
+        let size = k + 1;
+        if size < params.min_size || n - size < params.min_size {
+            continue;
+        }
+        let smaller = prefix_volume.min(volume - prefix_volume);
+        let conductance = if smaller > 0.0 { cut / smaller } else { 1.0 };
+        if best.is_none_or(|(c, ..)| conductance < c) {
+            best = Some((conductance, size, cut, prefix_global));
+        }
+    }
+
+    let (conductance, size, cut, side_global) = best?;
+    // Newman modularity of the two sides minus that of the whole cluster
+    let gain =
+        -cut / (two_m / 2.0) + 2.0 * side_global * (volume_global - side_global) / (two_m * two_m);
+    if conductance > params.max_conductance || gain <= params.min_modularity_gain {
+        return None;
+    }
+    let (left, right) = order.split_at(size);
+    Some((left.to_vec(), right.to_vec()))
+}
+
+/// Top-down divisive clustering: start from connected components and
+/// recursively bisect each cluster at its spectral (Fiedler vector) sweep
+/// cut until no split passes the conductance / modularity stopping rule.
+/// Splits giant clusters chained together by a few bridging edges, which
+/// agglomerative and threshold methods merge. Clusters come largest first,
+/// members in ID order.
+pub fn divisive_clustering(
+    matrix: &SparseSimilarityMatrix,
+    params: &DivisiveParams,
+    seed: u64,
+) -> Result<Vec<Vec<String>>, String> {
+    let ids = matrix.entry_ids();
+    if matrix.shape().0 != matrix.shape().1 || ids != matrix.col_ids() {
+        return Err(
+            "divisive clustering needs a square matrix with matching row and column IDs"
+                .to_string(),
+        );
+    }
+    let neighbors = adjacency(matrix);
+    let degrees: Vec<f64> = neighbors
+        .iter()
+        .map(|row| row.iter().map(|(_, w)| w).sum())
+        .collect();
+    let two_m: f64 = degrees.iter().sum();
+
+    // A sweep side may fall apart, so each side restarts from its pieces
+    let mut pending = induced_components(&neighbors, &(0..ids.len()).collect::<Vec<_>>());
+    let mut done = Vec::new();
+    while let Some(members) = pending.pop() {
+        match spectral_bisection(&neighbors, &degrees, two_m, &members, params, seed) {
+            Some((left, right)) => {
+                pending.extend(induced_components(&neighbors, &left));
+                pending.extend(induced_components(&neighbors, &right));
+            }
+            None => done.push(members),
+        }
+    }
+
+    let mut clusters: Vec<Vec<String>> = done
+        .into_iter()
+        .map(|members| {
+            let mut cluster: Vec<String> = members.iter().map(|&i| ids[i].clone()).collect();
+            cluster.sort();
+            cluster
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    Ok(clusters)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -294,6 +1469,8 @@ mod tests {
 
         let clusters = threshold_clustering(similarities, 5, 0.8);
         assert_eq!(clusters.len(), 2); // Two clusters: {0,1,2} and {3,4}
+        // Components come in order of their smallest member
+        assert_eq!(clusters, vec![vec![0, 1, 2], vec![3, 4]]);
     }
 
     #[test]
@@ -307,5 +1484,267 @@ mod tests {
         assert!(!clusters.is_empty());
         assert!(clusters[0].len() >= 2);
     }
-}
 
+    fn pair(a: &str, b: &str, sim: f64) -> (String, String, f64) {
+        (a.to_string(), b.to_string(), sim)
+    }
+
+    #[test]
+    fn test_agglomerative_linkages() {
+        // a-b tight, c joins them loosely, d far from everything
+        let similarities = vec![
+            pair("a", "b", 0.9),
+            pair("a", "c", 0.6),
+            pair("b", "c", 0.2),
+            pair("c", "d", 0.1),
+        ];
+
+        let single = agglomerative_clustering(similarities.clone(), Linkage::Single);
+        let distances: Vec<f64> = single.merges.iter().map(|m| m.distance).collect();
+        assert_eq!(single.merges.len(), 3);
+        assert!((distances[0] - 0.1).abs() < 1e-12);
+        assert!((distances[1] - 0.4).abs() < 1e-12);
+        assert!((distances[2] - 0.9).abs() < 1e-12);
+        assert_eq!(single.merges[0], Merge { left: 0, right: 1, distance: distances[0], size: 2 });
+        assert_eq!(single.merges[1].left, 2);
+        assert_eq!(single.merges[1].right, 4);
+        assert_eq!(single.merges[2].size, 4);
+
+        let complete = agglomerative_clustering(similarities.clone(), Linkage::Complete);
+        assert!((complete.merges[1].distance - 0.8).abs() < 1e-12);
+
+        let average = agglomerative_clustering(similarities, Linkage::Average);
+        assert!((average.merges[1].distance - 0.6).abs() < 1e-12);
+        assert!(average
+            .merges
+            .windows(2)
+            .all(|w| w[0].distance <= w[1].distance));
+    }
+
+    #[test]
+    fn test_dendrogram_cuts() {
+        let similarities = vec![
+            pair("a", "b", 0.9),
+            pair("c", "d", 0.8),
+            pair("b", "c", 0.3),
+        ];
+        let dendrogram = agglomerative_clustering(similarities, Linkage::Average);
+
+        assert_eq!(dendrogram.cut(4).len(), 4);
+        assert_eq!(
+            dendrogram.cut(2),
+            vec![vec!["a", "b"], vec!["c", "d"]]
+        );
+        assert_eq!(dendrogram.cut(1).len(), 1);
+        assert_eq!(dendrogram.cut_height(0.15).len(), 3);
+        assert_eq!(dendrogram.cut_height(0.5), dendrogram.cut(2));
+        assert_eq!("upgma".parse::<Linkage>(), Ok(Linkage::Average));
+    }
+
+    /// Two cliques of `size` joined by one weak link
+    fn two_cliques(size: usize) -> SparseSimilarityMatrix {
+        let mut edges = Vec::new();
+        for prefix in ["a", "b"] {
+            for i in 0..size {
+                for j in i + 1..size {
+                    edges.push((format!("{}{:03}", prefix, i), format!("{}{:03}", prefix, j), 0.9));
+                }
+            }
+        }
+        edges.push(pair("a000", "b000", 0.3));
+        SparseSimilarityMatrix::from_edges(edges, 0.0)
+    }
+
+    #[test]
+    fn test_spectral_clustering() {
+        for size in [5, 160] {
+            let clusters = spectral_clustering(&two_cliques(size), 2, 7).unwrap();
+            assert_eq!(clusters.len(), 2);
+            assert!(clusters[0].iter().all(|id| id.starts_with('a')));
+            assert!(clusters[1].iter().all(|id| id.starts_with('b')));
+            assert_eq!(clusters[0].len(), size);
+        }
+
+        let single = spectral_clustering(&two_cliques(3), 1, 7).unwrap();
+        assert_eq!(single.len(), 1);
+        assert!(spectral_clustering(&two_cliques(3), 0, 7).is_err());
+    }
+
+    #[test]
+    fn test_divisive_clustering() {
+        // Three cliques chained a - b - c; a 2-way spectral split cannot
+        // separate all three, recursive bisection does
+        let mut edges = Vec::new();
+        for prefix in ["a", "b", "c"] {
+            for i in 0..6 {
+                for j in i + 1..6 {
+                    edges.push((format!("{}{}", prefix, i), format!("{}{}", prefix, j), 0.9));
+                }
+            }
+        }
+        edges.push(pair("a0", "b0", 0.2));
+        edges.push(pair("b1", "c0", 0.2));
+        edges.push(pair("x", "y", 0.8));
+        let matrix = SparseSimilarityMatrix::from_edges(edges, 0.0);
+
+        let clusters = divisive_clustering(&matrix, &DivisiveParams::default(), 3).unwrap();
+        assert_eq!(clusters.len(), 4);
+        for (cluster, prefix) in clusters.iter().zip(["a", "b", "c"]) {
+            assert_eq!(cluster.len(), 6);
+            assert!(cluster.iter().all(|id| id.starts_with(prefix)));
+        }
+        assert_eq!(clusters[3], ["x", "y"]);
+
+        // A strict conductance limit keeps the chain together
+        let strict = DivisiveParams {
+            max_conductance: 0.001,
+            ..DivisiveParams::default()
+        };
+        assert_eq!(divisive_clustering(&matrix, &strict, 3).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_consensus_clustering() {
+        let cluster = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        // Two of three metrics split {a, b, c} from {d, e}; one chains them
+        let partitions = vec![
+            vec![cluster(&["a", "b", "c"]), cluster(&["d", "e"])],
+            vec![cluster(&["a", "b"]), cluster(&["c"]), cluster(&["d", "e"])],
+            vec![cluster(&["a", "b", "c", "d", "e"])],
+        ];
+        assert_eq!(
+            consensus_clustering(&partitions, 0.5),
+            vec![cluster(&["a", "b", "c"]), cluster(&["d", "e"])]
+        );
+        assert_eq!(consensus_clustering(&partitions, 1.0).len(), 3);
+
+        let edge_lists = vec![
+            vec![pair("a", "b", 0.9), pair("b", "c", 0.8)],
+            vec![pair("a", "b", 0.7), pair("b", "c", 0.2)],
+            vec![pair("a", "b", 0.9), pair("b", "c", 0.3), pair("c", "d", 0.9)],
+        ];
+        let clusters = consensus_from_edges(edge_lists, 0.5, 0.5);
+        assert_eq!(clusters, vec![cluster(&["a", "b"]), cluster(&["c"]), cluster(&["d"])]);
+    }
+
+    #[test]
+    fn test_merge_cognate_sets() {
+        let sets = |groups: &[&[&str]]| -> Vec<Vec<String>> {
+            groups
+                .iter()
+                .map(|g| g.iter().map(|s| s.to_string()).collect())
+                .collect()
+        };
+        let members = |merged: &[MergedCognateSet]| -> Vec<Vec<String>> {
+            merged.iter().map(|m| m.set.members.clone()).collect()
+        };
+        let a = sets(&[&["a", "b", "c"], &["d", "e"], &["f"]]);
+        let b = sets(&[&["a", "b"], &["c", "d", "e"], &["g"]]);
+
+        let union = merge_cognate_sets(&a, &b, MergeStrategy::Union);
+        assert_eq!(
+            members(&union),
+            sets(&[&["a", "b", "c", "d", "e"], &["f"], &["g"]])
+        );
+        // f is listed (alone) on one side only
+        assert_eq!(union[1].confidence, vec![0.5]);
+        assert_eq!(union[0].set.id, 0);
+
+        let common = merge_cognate_sets(&a, &b, MergeStrategy::Intersection);
+        assert_eq!(members(&common), sets(&[&["a", "b"], &["d", "e"], &["c"]]));
+        // a: {b, c} vs {b}, then {b} vs {b}
+        assert_eq!(common[0].confidence, vec![0.75, 0.75]);
+        // c: {a, b} vs {}, then {d, e} vs {}
+        assert_eq!(common[2].confidence, vec![0.0]);
+
+        // {a, b, c} ~ {a, b} (2/3) merge; {d, e} ~ {c, d, e} (2/3) merge too,
+        // and c, fitting both equally, stays in the first
+        let jaccard = MergeStrategy::Jaccard { min_overlap: 0.6 };
+        let merged = merge_cognate_sets(&a, &b, jaccard);
+        assert_eq!(
+            members(&merged),
+            sets(&[&["a", "b", "c"], &["d", "e"], &["f"], &["g"]])
+        );
+        let strict = MergeStrategy::Jaccard { min_overlap: 1.0 };
+        assert_eq!(merge_cognate_sets(&a, &a, strict).len(), 3);
+        let jaccard: MergeStrategy = "jaccard".parse().unwrap();
+        assert!(jaccard.with_min_overlap(2.0).is_err());
+        assert!(MergeStrategy::Union.with_min_overlap(0.5).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_clusterings() {
+        let similarities = vec![(0, 1, 0.9), (1, 2, 0.85), (0, 2, 0.8), (3, 4, 0.95), (2, 3, 0.1)];
+        let candidates = vec![
+            vec![vec![0, 1, 2], vec![3, 4]],
+            vec![vec![0, 1, 2, 3, 4]],
+            vec![vec![0, 1], vec![2], vec![3, 4]],
+        ];
+        let scores = evaluate_clusterings(&similarities, &candidates);
+        assert_eq!(scores.len(), 3);
+        for (score, clusters) in scores.iter().zip(&candidates) {
+            assert_eq!(score.silhouette, silhouette_score(&similarities, clusters));
+            assert_eq!(
+                score.within_cluster_variance,
+                within_cluster_variance(&similarities, clusters)
+            );
+        }
+        assert!(scores[0].silhouette > scores[2].silhouette);
+        assert_eq!((scores[2].num_clusters, scores[2].num_singletons), (3, 1));
+        assert_eq!(scores[0].davies_bouldin, davies_bouldin_index(&similarities, &candidates[0]));
+        assert!(scores[0].davies_bouldin < scores[2].davies_bouldin);
+        assert!(scores[0].within_between_ratio < scores[2].within_between_ratio);
+        // One cluster has nothing to be separated from
+        assert_eq!(scores[1].davies_bouldin, 0.0);
+        assert_eq!(scores[1].within_between_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_sparse_clusters() {
+        let ids = ["a", "b", "c", "d"];
+        let similarities = vec![
+            (0, 1, 0.9),
+            (0, 2, 0.2),
+            (0, 3, 0.1),
+            (1, 2, 0.3),
+            (1, 3, 0.2),
+            (2, 3, 0.8),
+        ];
+        let edges = similarities
+            .iter()
+            .map(|&(i, j, sim)| (ids[i].to_string(), ids[j].to_string(), sim))
+            .collect();
+        let matrix = SparseSimilarityMatrix::from_edges(edges, 0.0);
+        let named = vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["c".to_string(), "d".to_string()],
+        ];
+        let indexed = vec![vec![0, 1], vec![2, 3]];
+
+        let sparse = SparseClusters::new(&matrix, &named, 1.0).unwrap();
+        let expected = evaluate_clusterings(&similarities, &[indexed]).remove(0);
+        assert_eq!(sparse.scores(), expected);
+        assert_eq!(sparse.silhouette(), expected.silhouette);
+        assert!((sparse.within_between_ratio() - 0.15 / 0.8).abs() < 1e-12);
+
+        // Pairs the matrix dropped count as the missing distance
+        let pruned = SparseSimilarityMatrix::from_edges(
+            vec![
+                ("a".to_string(), "b".to_string(), 0.9),
+                ("c".to_string(), "d".to_string(), 0.8),
+            ],
+            0.0,
+        );
+        let far = SparseClusters::new(&pruned, &named, 1.0).unwrap();
+        let near = SparseClusters::new(&pruned, &named, 0.5).unwrap();
+        assert!(far.davies_bouldin() < near.davies_bouldin());
+        assert!(far.silhouette() > near.silhouette());
+
+        let unknown = vec![vec!["zz".to_string()]];
+        assert_eq!(
+            SparseClusters::new(&matrix, &unknown, 1.0).err(),
+            Some(LangVizError::UnknownEntryId("zz".to_string()))
+        );
+        assert!(SparseClusters::new(&matrix, &named, f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,428 @@
+//! Louvain community detection on the weighted cognate graph.
+//!
+//! Each phase moves nodes greedily to the neighboring community with the
+//! largest modularity gain (computed incrementally from community degree
+//! totals) until no move helps, then aggregates every community into a
+//! single node and repeats. Each phase that merges anything yields one level
+//! of the hierarchy, from the finest partition to the coarsest.
+//!
+//! `cluster_quality` scores any clustering's cut against the full graph, to
+//! tell well-separated cognate sets from arbitrary slices of a dense blob.
+
+use ahash::AHashSet;
+use petgraph::visit::EdgeRef;
+use rayon::prelude::*;
+
+use crate::error::LangVizError;
+use crate::graph::CognateGraph;
+
+/// Stop a local-move phase after this many sweeps even if nodes still move
+const MAX_SWEEPS: usize = 100;
+
+/// Minimum gain for a move, so floating-point noise cannot cause cycling
+const MIN_GAIN: f64 = 1e-12;
+
+/// One level of the Louvain hierarchy
+#[derive(Debug, Clone)]
+pub struct CommunityLevel {
+    /// Communities of original node IDs, ordered by their first node
+    pub communities: Vec<Vec<String>>,
+    pub modularity: f64,
+}
+
+/// Cut quality of one cluster S against the rest of the graph; ratios are 0
+/// when nothing crosses the cut
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterQuality {
+    pub size: usize,
+    /// Weight of edges with both ends in S
+    pub internal_weight: f64,
+    /// Weight of edges leaving S
+    pub cut_weight: f64,
+    /// cut / min(vol(S), vol(V \ S)), volumes being weighted degree sums
+    pub conductance: f64,
+    /// cut / vol(S) + cut / vol(V \ S)
+    pub normalized_cut: f64,
+    /// cut / |S|, the weighted edge expansion
+    pub expansion: f64,
+}
+
+/// Weighted graph being coarsened; self-loops carry internal weight
+struct LevelGraph {
+    adjacency: Vec<Vec<(usize, f64)>>,
+    self_loops: Vec<f64>,
+}
+
+impl LevelGraph {
+    /// Weighted degree, counting a self-loop twice
+    fn degrees(&self) -> Vec<f64> {
+        self.adjacency
+            .iter()
+            .zip(&self.self_loops)
+            .map(|(edges, &internal)| edges.iter().map(|e| e.1).sum::<f64>() + 2.0 * internal)
+            .collect()
+    }
+
+    /// Local moves; returns the community of every node (renumbered 0..k)
+    /// and whether any node moved
+    fn local_moves(&self, resolution: f64, total_weight: f64) -> (Vec<usize>, bool) {
+        let n = self.adjacency.len();
+        let mut community: Vec<usize> = (0..n).collect();
+        if total_weight == 0.0 {
+            return (community, false);
+        }
+        let degrees = self.degrees();
+        let mut totals = degrees.clone();
+        let mut moved_any = false;
+
+        // Scratch space for weights from a node to neighboring communities
+        let mut links = vec![0.0; n];
+        let mut touched: Vec<usize> = Vec::new();
+
+        for _ in 0..MAX_SWEEPS {
+            let mut moved = false;
+            for node in 0..n {
+                let current = community[node];
+                for &(neighbor, weight) in &self.adjacency[node] {
+                    let c = community[neighbor];
+                    if links[c] == 0.0 {
+                        touched.push(c);
+                    }
+                    links[c] += weight;
+                }
+
+                totals[current] -= degrees[node];
+                let gain = |c: usize, links: &[f64]| {
+                    links[c] - resolution * totals[c] * degrees[node] / total_weight
+                };
+                let mut best = current;
+                let mut best_gain = gain(current, &links);
+                for &c in &touched {
+                    let candidate = gain(c, &links);
+                    if candidate > best_gain + MIN_GAIN {
+                        best = c;
+                        best_gain = candidate;
+                    }
+                }
+                totals[best] += degrees[node];
+
+                if best != current {
+                    community[node] = best;
+                    moved = true;
+                    moved_any = true;
+                }
+                for c in touched.drain(..) {
+                    links[c] = 0.0;
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+
+        (renumber(&community), moved_any)
+    }
+
+    /// Collapse each community into one node
+    fn aggregate(&self, community: &[usize], count: usize) -> LevelGraph {
+        let mut self_loops = vec![0.0; count];
+        let mut weights: Vec<ahash::AHashMap<usize, f64>> = vec![Default::default(); count];
+
+        for (node, edges) in self.adjacency.iter().enumerate() {
+            let c = community[node];
+            self_loops[c] += self.self_loops[node];
+            for &(neighbor, weight) in edges {
+                let d = community[neighbor];
+                if c == d {
+                    // Each internal edge is seen from both ends
+                    self_loops[c] += weight / 2.0;
+                } else {
+                    *weights[c].entry(d).or_insert(0.0) += weight;
+                }
+            }
+        }
+
+        let adjacency = weights
+            .into_iter()
+            .map(|map| {
+                let mut edges: Vec<(usize, f64)> = map.into_iter().collect();
+                edges.sort_by_key(|e| e.0);
+                edges
+            })
+            .collect();
+        LevelGraph {
+            adjacency,
+            self_loops,
+        }
+    }
+
+    fn modularity(&self, resolution: f64, total_weight: f64) -> f64 {
+        if total_weight == 0.0 {
+            return 0.0;
+        }
+        self.degrees()
+            .iter()
+            .zip(&self.self_loops)
+            .map(|(&degree, &internal)| {
+                2.0 * internal / total_weight - resolution * (degree / total_weight).powi(2)
+            })
+            .sum()
+    }
+}
+
+/// Relabel communities 0..k in order of first appearance
+fn renumber(community: &[usize]) -> Vec<usize> {
+    let mut labels = vec![usize::MAX; community.len()];
+    let mut next = 0;
+    community
+        .iter()
+        .map(|&c| {
+            if labels[c] == usize::MAX {
+                labels[c] = next;
+                next += 1;
+            }
+            labels[c]
+        })
+        .collect()
+}
+
+impl CognateGraph {
+    /// Full Louvain hierarchy, finest level first. A graph where no merge
+    /// improves modularity yields a single level of singletons.
+    pub fn louvain_hierarchy(&self, resolution: f64) -> Vec<CommunityLevel> {
+        self.louvain_levels(resolution, None)
+    }
+
+    /// Louvain communities of this graph as a union of components of a
+    /// larger graph whose weighted degrees sum to `total_weight` (2m).
+    /// Modularity gains use that total, so with nodes and edges in the
+    /// larger graph's relative order the result is the larger graph's
+    /// partition restricted to these components.
+    pub(crate) fn detect_communities_within(
+        &self,
+        resolution: f64,
+        total_weight: f64,
+    ) -> Vec<Vec<String>> {
+        self.louvain_levels(resolution, Some(total_weight))
+            .pop()
+            .map(|level| level.communities)
+            .unwrap_or_default()
+    }
+
+    /// Louvain hierarchy, normalizing modularity by `total_weight` when
+    /// given and by this graph's own total otherwise
+    fn louvain_levels(&self, resolution: f64, total_weight: Option<f64>) -> Vec<CommunityLevel> {
+        let n = self.graph.node_count();
+        let mut adjacency = vec![Vec::new(); n];
+        let mut self_loops = vec![0.0; n];
+        for edge in self.graph.edge_indices() {
+            let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+            let weight = self.graph[edge];
+            if a == b {
+                self_loops[a.index()] += weight;
+            } else {
+                adjacency[a.index()].push((b.index(), weight));
+                adjacency[b.index()].push((a.index(), weight));
+            }
+        }
+
+        let mut level_graph = LevelGraph {
+            adjacency,
+            self_loops,
+        };
+        let total_weight =
+            total_weight.unwrap_or_else(|| level_graph.degrees().iter().sum::<f64>());
+        // Community of each original node at the current level
+        let mut membership: Vec<usize> = (0..n).collect();
+        let mut levels = Vec::new();
+
+        loop {
+            let (community, moved) = level_graph.local_moves(resolution, total_weight);
+            if !moved && !levels.is_empty() {
+                break;
+            }
+            let count = community.iter().max().map_or(0, |&c| c + 1);
+            for m in membership.iter_mut() {
+                *m = community[*m];
+            }
+            level_graph = level_graph.aggregate(&community, count);
+            levels.push(CommunityLevel {
+                communities: self.group_members(&membership, count),
+                modularity: level_graph.modularity(resolution, total_weight),
+            });
+            if !moved {
+                break;
+            }
+        }
+
+        levels
+    }
+
+    /// Louvain communities at the coarsest (highest-modularity) level
+    pub fn detect_communities(&self, resolution: f64) -> Vec<Vec<String>> {
+        self.louvain_hierarchy(resolution)
+            .pop()
+            .map(|level| level.communities)
+            .unwrap_or_default()
+    }
+
+    /// Conductance, normalized cut and expansion of every cluster (member
+    /// ID lists, which need not cover the graph) against the full graph
+    pub fn cluster_quality(
+        &self,
+        clusters: &[Vec<String>],
+    ) -> Result<Vec<ClusterQuality>, LangVizError> {
+        let total_volume = 2.0 * self.graph.edge_weights().sum::<f64>();
+        let resolved = clusters
+            .iter()
+            .map(|members| {
+                members
+                    .iter()
+                    .map(|id| {
+                        self.node_map
+                            .get(id)
+                            .copied()
+                            .ok_or_else(|| LangVizError::UnknownEntryId(id.clone()))
+                    })
+                    .collect::<Result<AHashSet<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(resolved
+            .par_iter()
+            .map(|members| {
+                let (mut internal, mut cut, mut volume) = (0.0, 0.0, 0.0);
+                for &node in members {
+                    for edge in self.graph.edges(node) {
+                        let weight = *edge.weight();
+                        volume += weight;
+                        if members.contains(&edge.target()) {
+                            internal += weight;
+                        } else {
+                            cut += weight;
+                        }
+                    }
+                }
+                let ratio = |denominator: f64| if cut > 0.0 { cut / denominator } else { 0.0 };
+                let rest = total_volume - volume;
+                ClusterQuality {
+                    size: members.len(),
+                    internal_weight: internal / 2.0,
+                    cut_weight: cut,
+                    conductance: ratio(volume.min(rest)),
+                    normalized_cut: ratio(volume) + ratio(rest),
+                    expansion: ratio(members.len() as f64),
+                }
+            })
+            .collect())
+    }
+
+    fn group_members(&self, membership: &[usize], count: usize) -> Vec<Vec<String>> {
+        let mut groups = vec![Vec::new(); count];
+        for (node, &c) in membership.iter().enumerate() {
+            groups[c].push(self.graph[petgraph::graph::NodeIndex::new(node)].clone());
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn edge(a: &str, b: &str, w: f64) -> SimilarityEdge {
+        SimilarityEdge::new(a.to_string(), b.to_string(), w)
+    }
+
+    /// Two 4-cliques joined by one weak edge
+    fn two_cliques() -> CognateGraph {
+        let mut edges = Vec::new();
+        for group in [["a", "b", "c", "d"], ["e", "f", "g", "h"]] {
+            for i in 0..4 {
+                for j in (i + 1)..4 {
+                    edges.push(edge(group[i], group[j], 1.0));
+                }
+            }
+        }
+        edges.push(edge("d", "e", 0.1));
+        CognateGraph::from_edges(edges, 0.0)
+    }
+
+    #[test]
+    fn test_louvain_two_cliques() {
+        let graph = two_cliques();
+        let mut communities = graph.detect_communities(1.0);
+        for community in communities.iter_mut() {
+            community.sort();
+        }
+        communities.sort();
+        assert_eq!(
+            communities,
+            vec![vec!["a", "b", "c", "d"], vec!["e", "f", "g", "h"]]
+        );
+
+        let hierarchy = graph.louvain_hierarchy(1.0);
+        let last = hierarchy.last().unwrap();
+        assert!(last.modularity > 0.4);
+        // Modularity never decreases going up the hierarchy
+        for pair in hierarchy.windows(2) {
+            assert!(pair[1].modularity >= pair[0].modularity - 1e-12);
+            assert!(pair[1].communities.len() <= pair[0].communities.len());
+        }
+    }
+
+    #[test]
+    fn test_cluster_quality() {
+        let graph = two_cliques();
+        let cluster = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let clusters = [cluster(&["a", "b", "c", "d"]), cluster(&["a", "b", "e", "f"])];
+        let quality = graph.cluster_quality(&clusters).unwrap();
+
+        // Clique: 6 internal edges, volume 12.1, only the weak edge leaves
+        let clique = &quality[0];
+        assert_eq!((clique.size, clique.internal_weight, clique.cut_weight), (4, 6.0, 0.1));
+        assert!((clique.conductance - 0.1 / 12.1).abs() < 1e-12);
+        assert!((clique.normalized_cut - 2.0 * 0.1 / 12.1).abs() < 1e-12);
+        assert!((clique.expansion - 0.025).abs() < 1e-12);
+
+        // A slice across both cliques cuts far more than it keeps
+        assert!(quality[1].conductance > 0.5);
+        assert!(quality[1].cut_weight > quality[1].internal_weight);
+
+        let error = graph.cluster_quality(&[cluster(&["a", "zz"])]).unwrap_err();
+        assert_eq!(error, LangVizError::UnknownEntryId("zz".to_string()));
+    }
+
+    #[test]
+    fn test_louvain_hierarchy_levels() {
+        // Ring of 4 pairs: pairs form first, then neighboring pairs merge
+        let mut edges = Vec::new();
+        let nodes: Vec<String> = (0..16).map(|i| format!("n{}", i)).collect();
+        for i in 0..16 {
+            let weight = if i % 2 == 0 { 1.0 } else { 0.2 };
+            edges.push(edge(&nodes[i], &nodes[(i + 1) % 16], weight));
+        }
+        let graph = CognateGraph::from_edges(edges, 0.0);
+        let hierarchy = graph.louvain_hierarchy(1.0);
+
+        assert!(!hierarchy.is_empty());
+        for level in &hierarchy {
+            let total: usize = level.communities.iter().map(Vec::len).sum();
+            assert_eq!(total, 16);
+        }
+        assert!(hierarchy[0].communities.len() < 16);
+        assert!(hierarchy.last().unwrap().communities.len() > 1);
+    }
+
+    #[test]
+    fn test_louvain_without_edges() {
+        let mut graph = CognateGraph::new();
+        graph.get_or_create_node("a".to_string());
+        graph.get_or_create_node("b".to_string());
+        let hierarchy = graph.louvain_hierarchy(1.0);
+        assert_eq!(hierarchy.len(), 1);
+        assert_eq!(hierarchy[0].communities.len(), 2);
+        assert_eq!(hierarchy[0].modularity, 0.0);
+    }
+}
@@ -0,0 +1,148 @@
+//! Per-component statistics for triaging cognate sets.
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use rayon::prelude::*;
+
+use crate::graph::CognateGraph;
+use crate::paths::bfs_distances;
+
+/// Summary of one connected component
+#[derive(Debug, Clone)]
+pub struct ComponentStats {
+    pub id: usize,
+    pub size: usize,
+    pub num_edges: usize,
+    pub density: f64,
+    pub mean_weight: f64,
+    /// Hop diameter (double-sweep lower bound above `exact_limit` nodes)
+    pub diameter: usize,
+    pub top_member: String,
+    pub top_pagerank: f64,
+}
+
+impl CognateGraph {
+    /// Statistics for every connected component, largest first. Diameters
+    /// are exact for components of at most `exact_limit` nodes.
+    pub fn component_stats(
+        &self,
+        damping: f64,
+        iterations: usize,
+        exact_limit: usize,
+    ) -> Vec<ComponentStats> {
+        let (labels, count) = self.component_labels();
+        let ranks = self.pagerank_scores(damping, iterations);
+
+        // Members per component and each node's index inside its component
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut local = vec![0usize; labels.len()];
+        for (node, &label) in labels.iter().enumerate() {
+            local[node] = members[label].len();
+            members[label].push(node);
+        }
+
+        let mut edge_counts = vec![0usize; count];
+        let mut weight_sums = vec![0.0f64; count];
+        for edge in self.graph.edge_references() {
+            let label = labels[edge.source().index()];
+            edge_counts[label] += 1;
+            weight_sums[label] += *edge.weight();
+        }
+
+        let adjacency = self.adjacency_lists();
+
+        let mut stats: Vec<ComponentStats> = members
+            .par_iter()
+            .enumerate()
+            .map(|(id, nodes)| {
+                let local_adjacency: Vec<Vec<usize>> = nodes
+                    .iter()
+                    .map(|&u| adjacency[u].iter().map(|&v| local[v]).collect())
+                    .collect();
+                let diameter = component_diameter(&local_adjacency, exact_limit);
+
+                let top = nodes
+                    .iter()
+                    .copied()
+                    .max_by(|&a, &b| ranks[a].total_cmp(&ranks[b]))
+                    .unwrap_or(0);
+
+                let size = nodes.len();
+                let num_edges = edge_counts[id];
+                ComponentStats {
+                    id,
+                    size,
+                    num_edges,
+                    density: if size > 1 {
+                        (2 * num_edges) as f64 / (size * (size - 1)) as f64
+                    } else {
+                        0.0
+                    },
+                    mean_weight: if num_edges > 0 {
+                        weight_sums[id] / num_edges as f64
+                    } else {
+                        0.0
+                    },
+                    diameter,
+                    top_member: self.graph[NodeIndex::new(top)].clone(),
+                    top_pagerank: ranks.get(top).copied().unwrap_or(0.0),
+                }
+            })
+            .collect();
+
+        stats.sort_by(|a, b| b.size.cmp(&a.size).then(a.id.cmp(&b.id)));
+        stats
+    }
+}
+
+/// Diameter of a connected graph given as local adjacency lists
+fn component_diameter(adjacency: &[Vec<usize>], exact_limit: usize) -> usize {
+    let eccentricity = |source: usize| {
+        bfs_distances(adjacency, source)
+            .into_iter()
+            .max()
+            .unwrap_or(0)
+    };
+
+    if adjacency.len() <= exact_limit {
+        return (0..adjacency.len()).map(eccentricity).max().unwrap_or(0);
+    }
+
+    // Double sweep: BFS from an arbitrary node, then from the farthest one
+    let first = bfs_distances(adjacency, 0);
+    let far = (0..first.len()).max_by_key(|&i| first[i]).unwrap_or(0);
+    eccentricity(far)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    #[test]
+    fn test_component_stats() {
+        // Star around "hub" plus an isolated pair
+        let edges = vec![
+            SimilarityEdge::new("hub".into(), "a".into(), 0.8),
+            SimilarityEdge::new("hub".into(), "b".into(), 0.9),
+            SimilarityEdge::new("hub".into(), "c".into(), 1.0),
+            SimilarityEdge::new("x".into(), "y".into(), 0.6),
+        ];
+        let graph = CognateGraph::from_edges(edges, 0.5);
+        let stats = graph.component_stats(0.85, 50, 100);
+
+        assert_eq!(stats.len(), 2);
+        let star = &stats[0];
+        assert_eq!(star.size, 4);
+        assert_eq!(star.num_edges, 3);
+        assert!((star.density - 0.5).abs() < 1e-12);
+        assert!((star.mean_weight - 0.9).abs() < 1e-12);
+        assert_eq!(star.diameter, 2);
+        assert_eq!(star.top_member, "hub");
+
+        assert_eq!(stats[1].size, 2);
+        assert_eq!(stats[1].diameter, 1);
+        // Double sweep is exact on trees
+        assert_eq!(graph.component_stats(0.85, 50, 0)[0].diameter, 2);
+    }
+}
@@ -0,0 +1,154 @@
+//! gzip / zstd compression for exported files.
+//!
+//! Writers pick the codec explicitly or from the file extension (`.gz`,
+//! `.zst`); readers sniff the leading magic bytes, so plain and compressed
+//! files open the same way whatever they are called.
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// zstd level used for exports (the library default)
+const ZSTD_LEVEL: i32 = 3;
+
+/// Compression codec for exported files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Codec implied by a file name: `.gz` gzip, `.zst` zstd, else none
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Compression::None),
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            other => Err(format!(
+                "unknown compression '{}' (expected none, gzip or zstd)",
+                other
+            )),
+        }
+    }
+}
+
+/// Run `write` against `sink` through the `compression` encoder, finishing
+/// the compressed stream and flushing the sink
+pub fn write_compressed<W, F>(sink: W, compression: Compression, write: F) -> io::Result<()>
+where
+    W: Write,
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let sink = BufWriter::new(sink);
+    match compression {
+        Compression::None => {
+            let mut sink = sink;
+            write(&mut sink)?;
+            sink.flush()
+        }
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(sink, flate2::Compression::default());
+            write(&mut encoder)?;
+            encoder.finish()?.flush()
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(sink, ZSTD_LEVEL)?;
+            write(&mut encoder)?;
+            encoder.finish()?.flush()
+        }
+    }
+}
+
+/// Create `path` and write it compressed with `compression`, or with the
+/// codec its extension implies
+pub fn write_file<F>(path: &Path, compression: Option<Compression>, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let compression = compression.unwrap_or_else(|| Compression::from_path(path));
+    write_compressed(File::create(path)?, compression, write)
+}
+
+/// Buffered reader over `source`, decompressed when it starts with gzip or
+/// zstd magic bytes
+pub fn decompressed<'a, R: Read + 'a>(source: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let mut source = BufReader::new(source);
+    let head = source.fill_buf()?;
+    Ok(if head.starts_with(GZIP_MAGIC) {
+        Box::new(BufReader::new(MultiGzDecoder::new(source)))
+    } else if head.starts_with(ZSTD_MAGIC) {
+        Box::new(BufReader::new(zstd::Decoder::with_buffer(source)?))
+    } else {
+        Box::new(source)
+    })
+}
+
+/// Open a plain, gzip or zstd file for reading
+pub fn open_file(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    decompressed(File::open(path)?)
+}
+
+/// Whole contents of a plain, gzip or zstd file as UTF-8 text
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let mut text = String::new();
+    open_file(path)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_sniffing() {
+        let text = "source\ttarget\tweight\n".repeat(100);
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            let mut buffer = Vec::new();
+            write_compressed(&mut buffer, compression, |w| w.write_all(text.as_bytes())).unwrap();
+            if compression != Compression::None {
+                assert!(buffer.len() < text.len() / 4);
+            }
+            let mut decoded = String::new();
+            decompressed(buffer.as_slice())
+                .unwrap()
+                .read_to_string(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, text);
+        }
+
+        assert_eq!(
+            Compression::from_path(Path::new("graph.json.gz")),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("edges.tsv.zst")),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("graph.json")),
+            Compression::None
+        );
+        assert_eq!("ZSTD".parse::<Compression>(), Ok(Compression::Zstd));
+        assert!("bz2".parse::<Compression>().is_err());
+    }
+}
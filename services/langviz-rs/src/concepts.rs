@@ -0,0 +1,466 @@
+//! Standard concept lists (Swadesh 100/207, Leipzig–Jakarta) and wordlist
+//! coverage checks, so distances are computed on comparable samples.
+//!
+//! Lists are embedded from `data/concepts/`, one gloss per line in list
+//! order (stability rank for Leipzig–Jakarta). A `/` separates alternative
+//! glosses for the same concept. Glosses match case-insensitively, ignoring
+//! a leading "to " on verbs.
+//!
+//! `concept_cognate_density` reports how each concept splits into cognate
+//! sets and how similar the members of those sets are, to spot concepts
+//! with unstable or noisy data.
+//!
+//! Concepts can carry semantic-field tags ("body", "kinship", ...), matched
+//! like list glosses, to filter wordlists and aggregate results by field,
+//! e.g. which domains borrow most.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+const SWADESH_100: &str = include_str!("../data/concepts/swadesh_100.txt");
+const SWADESH_207: &str = include_str!("../data/concepts/swadesh_207.txt");
+const LEIPZIG_JAKARTA: &str = include_str!("../data/concepts/leipzig_jakarta.txt");
+
+/// Bundled concept list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConceptList {
+    Swadesh100,
+    Swadesh207,
+    LeipzigJakarta,
+}
+
+impl FromStr for ConceptList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "swadesh100" | "swadesh_100" => Ok(ConceptList::Swadesh100),
+            "swadesh207" | "swadesh_207" => Ok(ConceptList::Swadesh207),
+            "leipzig_jakarta" => Ok(ConceptList::LeipzigJakarta),
+            other => Err(format!(
+                "unknown concept list '{}' (expected swadesh100, swadesh207 or leipzig_jakarta)",
+                other
+            )),
+        }
+    }
+}
+
+/// Lowercase, collapse whitespace and drop a leading "to "
+fn normalize_gloss(gloss: &str) -> String {
+    let gloss = gloss.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    match gloss.strip_prefix("to ") {
+        Some(verb) => verb.to_string(),
+        None => gloss,
+    }
+}
+
+impl ConceptList {
+    /// Canonical glosses in list order
+    pub fn concepts(&self) -> Vec<&'static str> {
+        let source = match self {
+            ConceptList::Swadesh100 => SWADESH_100,
+            ConceptList::Swadesh207 => SWADESH_207,
+            ConceptList::LeipzigJakarta => LEIPZIG_JAKARTA,
+        };
+        source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    /// 1-based position of each canonical gloss (stability rank for
+    /// Leipzig–Jakarta), usable with `ConceptWeights::from_ranks`
+    pub fn ranks(&self) -> HashMap<String, usize> {
+        self.concepts()
+            .into_iter()
+            .enumerate()
+            .map(|(i, concept)| (concept.to_string(), i + 1))
+            .collect()
+    }
+
+    /// Map a wordlist gloss to its canonical concept, if on the list. Whole
+    /// entries take precedence over `/` alternatives ("hide" vs "skin/hide").
+    pub fn canonical(&self, gloss: &str) -> Option<&'static str> {
+        self.gloss_index().get(&normalize_gloss(gloss)).copied()
+    }
+
+    fn gloss_index(&self) -> HashMap<String, &'static str> {
+        let concepts = self.concepts();
+        let mut index: HashMap<String, &'static str> = concepts
+            .iter()
+            .map(|&concept| (normalize_gloss(concept), concept))
+            .collect();
+        for &concept in &concepts {
+            for alternative in concept.split('/') {
+                index.entry(normalize_gloss(alternative)).or_insert(concept);
+            }
+        }
+        index
+    }
+}
+
+/// Semantic-field tags per concept; a concept may carry several fields
+#[derive(Debug, Clone, Default)]
+pub struct SemanticFields {
+    /// Normalized gloss -> fields
+    tags: HashMap<String, BTreeSet<String>>,
+}
+
+impl SemanticFields {
+    /// Tags from a {concept: [field, ...]} table
+    pub fn new(tags: &HashMap<String, Vec<String>>) -> Self {
+        let mut fields: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for (concept, concept_fields) in tags {
+            fields
+                .entry(normalize_gloss(concept))
+                .or_default()
+                .extend(concept_fields.iter().cloned());
+        }
+        Self { tags: fields }
+    }
+
+    /// Fields of a concept, sorted (empty when untagged)
+    pub fn fields_of(&self, concept: &str) -> Vec<&str> {
+        self.tags
+            .get(&normalize_gloss(concept))
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Every field in use, sorted
+    pub fn fields(&self) -> Vec<&str> {
+        let fields: BTreeSet<&str> = self.tags.values().flatten().map(String::as_str).collect();
+        fields.into_iter().collect()
+    }
+
+    pub fn has_field(&self, concept: &str, field: &str) -> bool {
+        self.tags
+            .get(&normalize_gloss(concept))
+            .is_some_and(|fields| fields.contains(field))
+    }
+}
+
+/// Keep (language, concept, form) entries whose concept carries one of
+/// `keep`
+pub fn filter_by_field(
+    entries: &[(String, String, String)],
+    fields: &SemanticFields,
+    keep: &[String],
+) -> Vec<(String, String, String)> {
+    entries
+        .iter()
+        .filter(|(_, concept, _)| keep.iter().any(|field| fields.has_field(concept, field)))
+        .cloned()
+        .collect()
+}
+
+/// Share of borrowed entries in one semantic field
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldBorrowing {
+    pub field: String,
+    pub entries: usize,
+    pub borrowed: usize,
+    pub rate: f64,
+    /// Languages with at least one borrowed entry in the field
+    pub borrowing_languages: usize,
+}
+
+/// Borrowing rate per semantic field from (language, concept, borrowed)
+/// entries. An entry counts towards every field of its concept; untagged
+/// concepts are skipped. Highest rate first, ties by field.
+pub fn borrowing_by_field(
+    entries: &[(String, String, bool)],
+    fields: &SemanticFields,
+) -> Vec<FieldBorrowing> {
+    // (entries, borrowed, borrowing languages) per field
+    let mut totals: BTreeMap<&str, (usize, usize, BTreeSet<&str>)> = BTreeMap::new();
+    for (language, concept, borrowed) in entries {
+        for field in fields.fields_of(concept) {
+            let total = totals.entry(field).or_default();
+            total.0 += 1;
+            if *borrowed {
+                total.1 += 1;
+                total.2.insert(language);
+            }
+        }
+    }
+    let mut report: Vec<FieldBorrowing> = totals
+        .into_iter()
+        .map(|(field, (entries, borrowed, languages))| FieldBorrowing {
+            field: field.to_string(),
+            entries,
+            borrowed,
+            rate: borrowed as f64 / entries as f64,
+            borrowing_languages: languages.len(),
+        })
+        .collect();
+    report.sort_by(|a, b| b.rate.total_cmp(&a.rate));
+    report
+}
+
+/// Concept coverage of one language against a list
+#[derive(Debug, Clone)]
+pub struct ConceptCoverage {
+    pub language: String,
+    pub covered: usize,
+    pub total: usize,
+    pub coverage: f64,
+    /// Canonical glosses with no entry, in list order
+    pub missing: Vec<String>,
+}
+
+/// Keep only (language, concept, form) entries whose concept is on the list
+pub fn filter_wordlist(
+    entries: &[(String, String, String)],
+    list: ConceptList,
+) -> Vec<(String, String, String)> {
+    let index = list.gloss_index();
+    entries
+        .iter()
+        .filter(|(_, concept, _)| index.contains_key(&normalize_gloss(concept)))
+        .cloned()
+        .collect()
+}
+
+/// Per-language coverage of the list, sorted by language
+pub fn concept_coverage(
+    entries: &[(String, String, String)],
+    list: ConceptList,
+) -> Vec<ConceptCoverage> {
+    let index = list.gloss_index();
+    let concepts = list.concepts();
+
+    let mut present: BTreeMap<&str, BTreeSet<&'static str>> = BTreeMap::new();
+    for (language, concept, _) in entries {
+        let covered = present.entry(language.as_str()).or_default();
+        if let Some(&canonical) = index.get(&normalize_gloss(concept)) {
+            covered.insert(canonical);
+        }
+    }
+
+    present
+        .into_iter()
+        .map(|(language, covered)| {
+            let missing: Vec<String> = concepts
+                .iter()
+                .filter(|concept| !covered.contains(*concept))
+                .map(|concept| concept.to_string())
+                .collect();
+            ConceptCoverage {
+                language: language.to_string(),
+                covered: covered.len(),
+                total: concepts.len(),
+                coverage: covered.len() as f64 / concepts.len() as f64,
+                missing,
+            }
+        })
+        .collect()
+}
+
+/// Cognate structure of one concept across the sample
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConceptDensity {
+    pub concept: String,
+    pub entries: usize,
+    pub cognate_sets: usize,
+    /// Cognate sets with a single entry
+    pub singletons: usize,
+    /// `(sets - 1) / (entries - 1)`: 0 when all entries are cognate, 1 when
+    /// none are
+    pub fragmentation: f64,
+    /// Mean similarity of scored pairs within one cognate set
+    pub mean_within_similarity: Option<f64>,
+    /// Mean similarity of scored pairs across the concept's cognate sets
+    pub mean_between_similarity: Option<f64>,
+}
+
+/// Per-concept density report for (id, concept, cognate_set) entries and
+/// (id, id, similarity) scores; cognate set labels are scoped per concept
+/// and scores between unknown IDs or different concepts are ignored.
+/// Most fragmented concepts first, ties by concept.
+pub fn concept_cognate_density(
+    entries: &[(String, String, String)],
+    similarities: &[(String, String, f64)],
+) -> Vec<ConceptDensity> {
+    let assignment: HashMap<&str, (&str, &str)> = entries
+        .iter()
+        .map(|(id, concept, set)| (id.as_str(), (concept.as_str(), set.as_str())))
+        .collect();
+
+    let mut set_sizes: BTreeMap<&str, HashMap<&str, usize>> = BTreeMap::new();
+    for (concept, set) in assignment.values() {
+        *set_sizes.entry(concept).or_default().entry(set).or_insert(0) += 1;
+    }
+
+    // (sum, count) of within-set and between-set scores per concept
+    let mut within: HashMap<&str, (f64, usize)> = HashMap::new();
+    let mut between: HashMap<&str, (f64, usize)> = HashMap::new();
+    for (a, b, score) in similarities {
+        let (Some(&(concept_a, set_a)), Some(&(concept_b, set_b))) =
+            (assignment.get(a.as_str()), assignment.get(b.as_str()))
+        else {
+            continue;
+        };
+        if a == b || concept_a != concept_b {
+            continue;
+        }
+        let totals = if set_a == set_b { &mut within } else { &mut between };
+        let total = totals.entry(concept_a).or_insert((0.0, 0));
+        total.0 += score;
+        total.1 += 1;
+    }
+    let mean = |totals: &HashMap<&str, (f64, usize)>, concept: &str| {
+        totals.get(concept).map(|&(sum, count)| sum / count as f64)
+    };
+
+    let mut report: Vec<ConceptDensity> = set_sizes
+        .into_iter()
+        .map(|(concept, sizes)| {
+            let entries: usize = sizes.values().sum();
+            let cognate_sets = sizes.len();
+            ConceptDensity {
+                concept: concept.to_string(),
+                entries,
+                cognate_sets,
+                singletons: sizes.values().filter(|&&size| size == 1).count(),
+                fragmentation: if entries > 1 {
+                    (cognate_sets - 1) as f64 / (entries - 1) as f64
+                } else {
+                    0.0
+                },
+                mean_within_similarity: mean(&within, concept),
+                mean_between_similarity: mean(&between, concept),
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| b.fragmentation.total_cmp(&a.fragmentation));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(language: &str, concept: &str, form: &str) -> (String, String, String) {
+        (language.into(), concept.into(), form.into())
+    }
+
+    #[test]
+    fn test_bundled_lists() {
+        assert_eq!(ConceptList::Swadesh100.concepts().len(), 100);
+        assert_eq!(ConceptList::Swadesh207.concepts().len(), 207);
+        let lj = ConceptList::LeipzigJakarta;
+        assert_eq!(lj.concepts().len(), 100);
+        assert_eq!(lj.ranks()["fire"], 1);
+
+        assert_eq!(lj.canonical("To Go"), Some("go"));
+        assert_eq!(lj.canonical("meat"), Some("flesh/meat"));
+        assert_eq!(lj.canonical("hide"), Some("hide"));
+        assert_eq!(lj.canonical("computer"), None);
+    }
+
+    #[test]
+    fn test_filter_and_coverage() {
+        let entries = vec![
+            entry("eng", "water", "wɔːtə"),
+            entry("eng", "to drink", "dɹɪŋk"),
+            entry("eng", "computer", "kəmpjuːtə"),
+            entry("deu", "Water", "vasɐ"),
+        ];
+
+        let filtered = filter_wordlist(&entries, ConceptList::Swadesh100);
+        assert_eq!(filtered.len(), 3);
+
+        let coverage = concept_coverage(&entries, ConceptList::Swadesh100);
+        assert_eq!(coverage[0].language, "deu");
+        assert_eq!(coverage[0].covered, 1);
+        assert_eq!(coverage[1].covered, 2);
+        assert!((coverage[1].coverage - 0.02).abs() < 1e-12);
+        assert_eq!(coverage[1].missing.len(), 98);
+        assert!(!coverage[1].missing.contains(&"drink".to_string()));
+    }
+
+    #[test]
+    fn test_semantic_fields() {
+        let tags = HashMap::from([
+            ("hand".to_string(), vec!["body".to_string()]),
+            ("To Drink".to_string(), vec!["food".to_string()]),
+            ("tongue".to_string(), vec!["body".into(), "speech".into()]),
+        ]);
+        let fields = SemanticFields::new(&tags);
+        assert_eq!(fields.fields(), ["body", "food", "speech"]);
+        assert_eq!(fields.fields_of("drink"), ["food"]);
+        assert!(fields.fields_of("dog").is_empty());
+
+        let entries = vec![
+            entry("eng", "hand", "hænd"),
+            entry("eng", "drink", "dɹɪŋk"),
+            entry("eng", "dog", "dɒɡ"),
+        ];
+        let body = filter_by_field(&entries, &fields, &["body".to_string()]);
+        assert_eq!(body, [entry("eng", "hand", "hænd")]);
+
+        let flagged = |language: &str, concept: &str, borrowed| {
+            (language.to_string(), concept.to_string(), borrowed)
+        };
+        let report = borrowing_by_field(
+            &[
+                flagged("eng", "hand", false),
+                flagged("eng", "tongue", true),
+                flagged("deu", "tongue", false),
+                flagged("eng", "drink", false),
+                flagged("eng", "dog", true),
+            ],
+            &fields,
+        );
+        let rates: Vec<(&str, usize, f64)> = report
+            .iter()
+            .map(|r| (r.field.as_str(), r.entries, r.rate))
+            .collect();
+        assert_eq!(
+            rates,
+            [("speech", 2, 0.5), ("body", 3, 1.0 / 3.0), ("food", 1, 0.0)]
+        );
+        assert_eq!(report[0].borrowing_languages, 1);
+    }
+
+    #[test]
+    fn test_concept_cognate_density() {
+        // "hand" is one set; "dog" splits into three
+        let entries = vec![
+            entry("eng_hand", "hand", "1"),
+            entry("deu_hand", "hand", "1"),
+            entry("nld_hand", "hand", "1"),
+            entry("eng_dog", "dog", "1"),
+            entry("deu_dog", "dog", "2"),
+            entry("nld_dog", "dog", "2"),
+            entry("fra_dog", "dog", "3"),
+            entry("spa_dog", "dog", "3"),
+        ];
+        let similarities = vec![
+            ("eng_hand".to_string(), "deu_hand".to_string(), 0.9),
+            ("deu_hand".to_string(), "nld_hand".to_string(), 0.7),
+            ("deu_dog".to_string(), "nld_dog".to_string(), 0.8),
+            ("eng_dog".to_string(), "deu_dog".to_string(), 0.2),
+            ("eng_dog".to_string(), "eng_hand".to_string(), 0.5),
+        ];
+
+        let report = concept_cognate_density(&entries, &similarities);
+        assert_eq!(report.len(), 2);
+
+        let dog = &report[0];
+        assert_eq!(dog.concept, "dog");
+        assert_eq!((dog.entries, dog.cognate_sets, dog.singletons), (5, 3, 1));
+        assert!((dog.fragmentation - 0.5).abs() < 1e-12);
+        assert_eq!(dog.mean_within_similarity, Some(0.8));
+        assert_eq!(dog.mean_between_similarity, Some(0.2));
+
+        let hand = &report[1];
+        assert_eq!(hand.fragmentation, 0.0);
+        assert!((hand.mean_within_similarity.unwrap() - 0.8).abs() < 1e-12);
+        assert_eq!(hand.mean_between_similarity, None);
+    }
+}
@@ -0,0 +1,302 @@
+//! Language contact networks inferred from borrowing candidates.
+//!
+//! Each candidate is one word a recipient language is thought to have
+//! borrowed from a donor, with the detector's score. Candidates for the
+//! same (donor, recipient) pair collapse into one contact link carrying how
+//! many loans it stands for, their summed score (the expected number of
+//! loans) and that score split by semantic field. The links form a
+//! `BorrowingGraph`, so degrees, PageRank and strongly connected components
+//! come from the directed analytics; exports follow the GraphML and
+//! NetworkX node-link shapes of the cognate graph, marked as directed.
+
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::borrowing::BorrowingGraph;
+use crate::concepts::SemanticFields;
+use crate::export::{escape, PAGERANK_DAMPING, PAGERANK_ITERATIONS};
+
+/// Borrowing from one language into another
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactLink {
+    pub donor: String,
+    pub recipient: String,
+    /// Candidates behind the link
+    pub loans: usize,
+    /// Summed candidate score
+    pub weight: f64,
+    /// Borrowed concepts, sorted
+    pub concepts: Vec<String>,
+    /// Summed score per semantic field (untagged concepts count in none)
+    pub fields: BTreeMap<String, f64>,
+}
+
+/// Directed donor → recipient contact network
+#[derive(Debug, Clone)]
+pub struct ContactNetwork {
+    graph: BorrowingGraph,
+    links: Vec<ContactLink>,
+    /// Languages in graph node order
+    languages: Vec<String>,
+}
+
+impl ContactNetwork {
+    /// Network from (donor, recipient, concept, score) candidates, keeping
+    /// those scoring at least `min_score`
+    pub fn from_candidates(
+        candidates: &[(String, String, String, f64)],
+        fields: &SemanticFields,
+        min_score: f64,
+    ) -> Result<Self, String> {
+        let mut links: BTreeMap<(&str, &str), ContactLink> = BTreeMap::new();
+        let mut concepts: BTreeMap<(&str, &str), BTreeSet<&str>> = BTreeMap::new();
+        for (donor, recipient, concept, score) in candidates {
+            if !score.is_finite() || *score < 0.0 {
+                return Err(format!(
+                    "score {} of '{}' ({} -> {}) must be finite and >= 0",
+                    score, concept, donor, recipient
+                ));
+            }
+            if donor == recipient {
+                return Err(format!(
+                    "'{}' is borrowed by '{}' from itself",
+                    concept, donor
+                ));
+            }
+            if *score < min_score {
+                continue;
+            }
+            let key = (donor.as_str(), recipient.as_str());
+            let link = links.entry(key).or_insert_with(|| ContactLink {
+                donor: donor.clone(),
+                recipient: recipient.clone(),
+                loans: 0,
+                weight: 0.0,
+                concepts: Vec::new(),
+                fields: BTreeMap::new(),
+            });
+            link.loans += 1;
+            link.weight += score;
+            for field in fields.fields_of(concept) {
+                *link.fields.entry(field.to_string()).or_insert(0.0) += score;
+            }
+            concepts.entry(key).or_default().insert(concept);
+        }
+
+        let mut graph = BorrowingGraph::new();
+        let mut languages = Vec::new();
+        let mut seen = BTreeSet::new();
+        let links: Vec<ContactLink> = links
+            .into_iter()
+            .map(|(key, mut link)| {
+                link.concepts = concepts[&key].iter().map(|c| c.to_string()).collect();
+                for language in [&link.donor, &link.recipient] {
+                    if seen.insert(language.clone()) {
+                        languages.push(language.clone());
+                    }
+                }
+                graph.add_edge(link.donor.clone(), link.recipient.clone(), link.weight);
+                link
+            })
+            .collect();
+        Ok(Self {
+            graph,
+            links,
+            languages,
+        })
+    }
+
+    /// Directed analytics (degrees, PageRank, components) over the links
+    pub fn graph(&self) -> &BorrowingGraph {
+        &self.graph
+    }
+
+    /// Links sorted by (donor, recipient)
+    pub fn links(&self) -> &[ContactLink] {
+        &self.links
+    }
+
+    /// Semantic fields on any link, sorted
+    fn fields(&self) -> Vec<&str> {
+        let fields: BTreeSet<&str> = self
+            .links
+            .iter()
+            .flat_map(|link| link.fields.keys().map(String::as_str))
+            .collect();
+        fields.into_iter().collect()
+    }
+
+    fn export_pagerank(&self, include_pagerank: bool) -> Option<Vec<f64>> {
+        include_pagerank.then(|| {
+            let ranks = self.graph.pagerank(PAGERANK_DAMPING, PAGERANK_ITERATIONS);
+            ranks.into_iter().map(|(_, rank)| rank).collect()
+        })
+    }
+
+    /// Export as directed GraphML: edges carry weight, loans and a
+    /// `field_<name>` score per semantic field
+    pub fn to_graphml(&self, include_pagerank: bool) -> String {
+        let fields = self.fields();
+        let pagerank = self.export_pagerank(include_pagerank);
+        let mut out = String::new();
+
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        if pagerank.is_some() {
+            out.push_str(
+                "  <key id=\"pagerank\" for=\"node\" attr.name=\"pagerank\" \
+                 attr.type=\"double\"/>\n",
+            );
+        }
+        out.push_str(
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+        );
+        out.push_str("  <key id=\"loans\" for=\"edge\" attr.name=\"loans\" attr.type=\"int\"/>\n");
+        for (i, field) in fields.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  <key id=\"f{}\" for=\"edge\" attr.name=\"field_{}\" attr.type=\"double\"/>",
+                i,
+                escape(field)
+            );
+        }
+        out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+
+        for (i, language) in self.languages.iter().enumerate() {
+            let _ = writeln!(out, "    <node id=\"{}\">", escape(language));
+            if let Some(scores) = &pagerank {
+                let _ = writeln!(out, "      <data key=\"pagerank\">{}</data>", scores[i]);
+            }
+            out.push_str("    </node>\n");
+        }
+
+        for (e, link) in self.links.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+                e,
+                escape(&link.donor),
+                escape(&link.recipient)
+            );
+            let _ = writeln!(out, "      <data key=\"weight\">{}</data>", link.weight);
+            let _ = writeln!(out, "      <data key=\"loans\">{}</data>", link.loans);
+            for (i, field) in fields.iter().enumerate() {
+                if let Some(score) = link.fields.get(*field) {
+                    let _ = writeln!(out, "      <data key=\"f{}\">{}</data>", i, score);
+                }
+            }
+            out.push_str("    </edge>\n");
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// `networkx.node_link_data` output of a DiGraph; `link_key` as for
+    /// `CognateGraph::to_node_link_data`. Nodes carry their PageRank, links
+    /// weight, loans, concepts and a {field: score} dict.
+    pub fn to_node_link_data(&self, link_key: &str) -> Value {
+        let nodes: Vec<Value> = self
+            .graph
+            .pagerank(PAGERANK_DAMPING, PAGERANK_ITERATIONS)
+            .into_iter()
+            .map(|(language, rank)| json!({"id": language, "pagerank": rank}))
+            .collect();
+        let links: Vec<Value> = self
+            .links
+            .iter()
+            .map(|link| {
+                json!({
+                    "source": link.donor,
+                    "target": link.recipient,
+                    "weight": link.weight,
+                    "loans": link.loans,
+                    "concepts": link.concepts,
+                    "fields": link.fields,
+                })
+            })
+            .collect();
+
+        let mut data = Map::new();
+        data.insert("directed".to_string(), json!(true));
+        data.insert("multigraph".to_string(), json!(false));
+        data.insert("graph".to_string(), json!({}));
+        data.insert("nodes".to_string(), Value::Array(nodes));
+        data.insert(link_key.to_string(), Value::Array(links));
+        Value::Object(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn candidates() -> Vec<(String, String, String, f64)> {
+        [
+            ("fra", "eng", "beef", 0.9),
+            ("fra", "eng", "pork", 0.8),
+            ("non", "eng", "egg", 0.7),
+            ("non", "eng", "sky", 0.6),
+            ("lat", "fra", "beef", 0.1),
+            ("eng", "fra", "weekend", 0.5),
+        ]
+        .iter()
+        .map(|(d, r, c, s)| (d.to_string(), r.to_string(), c.to_string(), *s))
+        .collect()
+    }
+
+    fn fields() -> SemanticFields {
+        SemanticFields::new(&HashMap::from([
+            ("beef".to_string(), vec!["food".to_string()]),
+            ("pork".to_string(), vec!["food".to_string()]),
+            ("egg".to_string(), vec!["food".to_string()]),
+            ("sky".to_string(), vec!["nature".to_string()]),
+        ]))
+    }
+
+    #[test]
+    fn test_contact_network() {
+        let network = ContactNetwork::from_candidates(&candidates(), &fields(), 0.2).unwrap();
+        let links: Vec<(&str, &str, usize)> = network
+            .links()
+            .iter()
+            .map(|l| (l.donor.as_str(), l.recipient.as_str(), l.loans))
+            .collect();
+        assert_eq!(
+            links,
+            [("eng", "fra", 1), ("fra", "eng", 2), ("non", "eng", 2)]
+        );
+
+        let non_eng = &network.links()[2];
+        assert!((non_eng.weight - 1.3).abs() < 1e-12);
+        assert_eq!(non_eng.concepts, ["egg", "sky"]);
+        assert_eq!(non_eng.fields.len(), 2);
+        assert!(network.links()[0].fields.is_empty());
+
+        let graph = network.graph();
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 3));
+        assert_eq!(graph.stats().num_strong_components, 2);
+
+        let bad = vec![("eng".into(), "eng".into(), "dog".into(), 0.5)];
+        assert!(ContactNetwork::from_candidates(&bad, &fields(), 0.0).is_err());
+    }
+
+    #[test]
+    fn test_contact_exports() {
+        let network = ContactNetwork::from_candidates(&candidates(), &fields(), 0.2).unwrap();
+        let graphml = network.to_graphml(true);
+        assert!(graphml.contains("edgedefault=\"directed\""));
+        assert!(graphml.contains("attr.name=\"field_food\""));
+        assert!(graphml.contains("<edge id=\"e1\" source=\"fra\" target=\"eng\">"));
+        assert_eq!(graphml.matches("<node ").count(), 3);
+
+        let data = network.to_node_link_data("links");
+        assert_eq!(data["directed"], true);
+        assert_eq!(data["links"][1]["loans"], 2);
+        let food = data["links"][1]["fields"]["food"].as_f64().unwrap();
+        assert!((food - 1.7).abs() < 1e-12);
+        assert_eq!(data["links"][2]["concepts"][1], "sky");
+    }
+}
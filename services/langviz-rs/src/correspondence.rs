@@ -0,0 +1,503 @@
+//! Sound correspondences across cognate sets.
+//!
+//! Pairwise matrices: every pair of members of a cognate set from different
+//! languages is aligned (DTW, as for `extract_sound_correspondences`), and
+//! each aligned column is counted in that language pair's segment × segment
+//! matrix. Rows belong to the alphabetically first language; gaps appear as
+//! `-`. Regular correspondences show up as dominant cells, ready for heatmaps.
+//!
+//! Correspondence patterns (CoPaR-style): each cognate set is star-aligned
+//! to its most central member, giving one alignment site per segment of that
+//! member. Sites whose reflexes agree in every shared language are greedily
+//! grouped into patterns, and a pattern's reflexes predict the missing
+//! reflexes of languages absent from a cognate set.
+//!
+//! Correspondence networks: the pairwise matrices as a `CognateGraph` with
+//! one `language:segment` node per segment, so regular correspondences can
+//! be explored with the same community and centrality tooling as words.
+
+use ndarray::Array2;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::graph::CognateGraph;
+
+use crate::phonetic::{dtw_align, nw_align, phonetic_distance, segment_ipa, AlignParams};
+use crate::types::{AttrValue, EditOp};
+
+/// Segment × segment correspondence counts for one language pair
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CorrespondenceMatrix {
+    pub language_a: String,
+    pub language_b: String,
+    /// Row labels (segments of `language_a`, sorted)
+    pub segments_a: Vec<String>,
+    /// Column labels (segments of `language_b`, sorted)
+    pub segments_b: Vec<String>,
+    #[serde(serialize_with = "serialize_counts")]
+    pub counts: Array2<u64>,
+}
+
+fn serialize_counts<S: serde::Serializer>(
+    counts: &Array2<u64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let rows: Vec<Vec<u64>> = counts.outer_iter().map(|row| row.to_vec()).collect();
+    rows.serialize(serializer)
+}
+
+type PairCounts = BTreeMap<(String, String), BTreeMap<(String, String), u64>>;
+
+/// (language_a, language_b, aligned columns) for one member pair
+pub(crate) type AlignedPair<'a> = (&'a str, &'a str, Vec<(String, String)>);
+
+/// Aligned columns of every cross-language member pair of `cognate_sets`,
+/// oriented so language_a < language_b
+pub(crate) fn aligned_pairs(cognate_sets: &[Vec<(String, String)>]) -> Vec<AlignedPair<'_>> {
+    let pairs: Vec<(&str, &str, &str, &str)> = cognate_sets
+        .iter()
+        .flat_map(|members| {
+            members.iter().enumerate().flat_map(move |(i, a)| {
+                members[i + 1..]
+                    .iter()
+                    .filter_map(move |b| match a.0.cmp(&b.0) {
+                        std::cmp::Ordering::Less => Some((&*a.0, &*b.0, &*a.1, &*b.1)),
+                        std::cmp::Ordering::Greater => Some((&*b.0, &*a.0, &*b.1, &*a.1)),
+                        std::cmp::Ordering::Equal => None,
+                    })
+            })
+        })
+        .collect();
+
+    pairs
+        .par_iter()
+        .map(|&(lang_a, lang_b, ipa_a, ipa_b)| {
+            let alignment = dtw_align(ipa_a, ipa_b);
+            let columns = alignment
+                .sequence_a
+                .into_iter()
+                .zip(alignment.sequence_b)
+                .collect();
+            (lang_a, lang_b, columns)
+        })
+        .collect()
+}
+
+/// Correspondence matrices for every language pair sharing at least one
+/// cognate set; `cognate_sets` holds (language, ipa) members. Sorted by
+/// language pair.
+pub fn correspondence_matrices(
+    cognate_sets: &[Vec<(String, String)>],
+) -> Vec<CorrespondenceMatrix> {
+    let mut counts: PairCounts = BTreeMap::new();
+    for (lang_a, lang_b, columns) in aligned_pairs(cognate_sets) {
+        let cells = counts
+            .entry((lang_a.to_string(), lang_b.to_string()))
+            .or_default();
+        for column in columns {
+            *cells.entry(column).or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|((language_a, language_b), cells)| {
+            let rows: BTreeSet<&String> = cells.keys().map(|(a, _)| a).collect();
+            let cols: BTreeSet<&String> = cells.keys().map(|(_, b)| b).collect();
+            let segments_a: Vec<String> = rows.into_iter().cloned().collect();
+            let segments_b: Vec<String> = cols.into_iter().cloned().collect();
+
+            let mut matrix = Array2::<u64>::zeros((segments_a.len(), segments_b.len()));
+            for ((a, b), count) in &cells {
+                let i = segments_a.binary_search(a).unwrap();
+                let j = segments_b.binary_search(b).unwrap();
+                matrix[[i, j]] = *count;
+            }
+
+            CorrespondenceMatrix {
+                language_a,
+                language_b,
+                segments_a,
+                segments_b,
+                counts: matrix,
+            }
+        })
+        .collect()
+}
+
+/// JSON array of matrices with nested-list counts
+pub fn correspondence_matrices_json(matrices: &[CorrespondenceMatrix]) -> String {
+    serde_json::to_string(matrices).unwrap_or_default()
+}
+
+/// Segment-correspondence network over `matrices`: nodes are
+/// `language:segment` (with `language`, `segment` and `frequency`
+/// attributes) and edges are weighted by how often two segments are
+/// aligned. Cells below `min_count` are dropped, as are gap columns unless
+/// `include_gaps`; a node's frequency is the total count of its kept cells.
+pub fn correspondence_graph(
+    matrices: &[CorrespondenceMatrix],
+    min_count: u64,
+    include_gaps: bool,
+) -> CognateGraph {
+    let mut graph = CognateGraph::new();
+    let mut frequency: HashMap<String, (String, String, u64)> = HashMap::new();
+    for m in matrices {
+        for ((i, j), &count) in m.counts.indexed_iter() {
+            let (a, b) = (&m.segments_a[i], &m.segments_b[j]);
+            let gap = a.as_str() == "-" || b.as_str() == "-";
+            if count == 0 || count < min_count || (gap && !include_gaps) {
+                continue;
+            }
+            let mut node = |language: &String, segment: &String| {
+                let id = format!("{}:{}", language, segment);
+                let entry = frequency
+                    .entry(id.clone())
+                    .or_insert_with(|| (language.clone(), segment.clone(), 0));
+                entry.2 += count;
+                id
+            };
+            let source = node(&m.language_a, a);
+            let target = node(&m.language_b, b);
+            graph.add_edge(source, target, count as f64);
+        }
+    }
+
+    let attributes: HashMap<String, HashMap<String, AttrValue>> = frequency
+        .into_iter()
+        .map(|(id, (language, segment, count))| {
+            let attrs = HashMap::from([
+                ("language".to_string(), AttrValue::from(language)),
+                ("segment".to_string(), AttrValue::from(segment)),
+                ("frequency".to_string(), AttrValue::from(count as f64)),
+            ]);
+            (id, attrs)
+        })
+        .collect();
+    graph.set_node_attributes(attributes);
+    graph
+}
+
+/// Alignment site value for a missing language
+pub(crate) const UNKNOWN: &str = "?";
+
+/// Reflex per language at one alignment site: `None` when the language has
+/// no member in the cognate set, `Some("-")` for a gap
+pub type Site = Vec<Option<String>>;
+
+/// Group of alignment sites showing the same cross-language pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrespondencePattern {
+    /// Consensus reflex per language (`None` where no site attests it)
+    pub reflexes: Site,
+    /// (cognate set, site) indices in the pattern
+    pub sites: Vec<(usize, usize)>,
+}
+
+/// Correspondence patterns over a collection of cognate sets
+#[derive(Debug, Clone)]
+pub struct PatternAnalysis {
+    /// Sorted languages; indexes every `Site`
+    pub languages: Vec<String>,
+    /// Alignment sites of each cognate set
+    pub sites: Vec<Vec<Site>>,
+    pub patterns: Vec<CorrespondencePattern>,
+    /// Pattern of each site, parallel to `sites`
+    pub site_patterns: Vec<Vec<usize>>,
+}
+
+/// Star alignment of `forms` onto the most central one (by summed
+/// similarity): one column per segment of that form, holding every form's
+/// aligned segment or `-` for a gap
+pub(crate) fn star_align(forms: &[&str]) -> Vec<Vec<String>> {
+    if forms.is_empty() {
+        return Vec::new();
+    }
+    let reference = (0..forms.len())
+        .max_by(|&a, &b| {
+            let centrality =
+                |i: usize| -> f64 { forms.iter().map(|f| phonetic_distance(forms[i], f)).sum() };
+            centrality(a).total_cmp(&centrality(b)).then(b.cmp(&a))
+        })
+        .unwrap();
+
+    let params = AlignParams::default();
+    let mut columns: Vec<Vec<String>> = segment_ipa(forms[reference])
+        .into_iter()
+        .map(|segment| {
+            let mut column = vec!["-".to_string(); forms.len()];
+            column[reference] = segment;
+            column
+        })
+        .collect();
+
+    for (index, form) in forms.iter().enumerate() {
+        if index == reference {
+            continue;
+        }
+        let alignment = nw_align(forms[reference], form, &params);
+        let mut position = 0;
+        for (op, segment) in alignment.operations.iter().zip(&alignment.sequence_b) {
+            match op {
+                EditOp::Match | EditOp::Substitute => {
+                    columns[position][index] = segment.clone();
+                    position += 1;
+                }
+                EditOp::Delete => position += 1,
+                // Segments without a counterpart in the reference are dropped
+                EditOp::Insert => {}
+            }
+        }
+    }
+    columns
+}
+
+/// Star alignment of one cognate set onto its most central member
+pub(crate) fn align_sites(members: &[(String, String)], languages: &[String]) -> Vec<Site> {
+    // First member per known language (synonyms beyond the first are ignored)
+    let mut by_language: BTreeMap<usize, &str> = BTreeMap::new();
+    for (language, ipa) in members {
+        if let Ok(index) = languages.binary_search(language) {
+            by_language.entry(index).or_insert(ipa.as_str());
+        }
+    }
+    let (indices, forms): (Vec<usize>, Vec<&str>) = by_language.into_iter().unzip();
+
+    star_align(&forms)
+        .into_iter()
+        .map(|column| {
+            let mut site = vec![None; languages.len()];
+            for (&language, segment) in indices.iter().zip(column) {
+                site[language] = Some(segment);
+            }
+            site
+        })
+        .collect()
+}
+
+/// Sites agree where both attest a language and share at least one
+pub(crate) fn compatible(a: &Site, b: &Site) -> bool {
+    let mut shared = false;
+    for (x, y) in a.iter().zip(b) {
+        if let (Some(x), Some(y)) = (x, y) {
+            if x != y {
+                return false;
+            }
+            shared = true;
+        }
+    }
+    shared
+}
+
+/// Star-align every cognate set of (language, ipa) members and greedily
+/// group its sites into correspondence patterns. Sites attested in more
+/// languages are placed first; each joins the first compatible pattern.
+pub fn correspondence_patterns(cognate_sets: &[Vec<(String, String)>]) -> PatternAnalysis {
+    let languages: Vec<String> = cognate_sets
+        .iter()
+        .flatten()
+        .map(|(language, _)| language.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let sites: Vec<Vec<Site>> = cognate_sets
+        .par_iter()
+        .map(|members| align_sites(members, &languages))
+        .collect();
+
+    let mut order: Vec<(usize, usize)> = sites
+        .iter()
+        .enumerate()
+        .flat_map(|(set, set_sites)| (0..set_sites.len()).map(move |site| (set, site)))
+        .collect();
+    let coverage = |&(set, site): &(usize, usize)| sites[set][site].iter().flatten().count();
+    order.sort_by(|a, b| coverage(b).cmp(&coverage(a)).then(a.cmp(b)));
+
+    let mut patterns: Vec<CorrespondencePattern> = Vec::new();
+    let mut site_patterns: Vec<Vec<usize>> = sites.iter().map(|s| vec![0; s.len()]).collect();
+    for (set, site) in order {
+        let values = &sites[set][site];
+        let index = match patterns
+            .iter()
+            .position(|p| compatible(&p.reflexes, values))
+        {
+            Some(index) => index,
+            None => {
+                patterns.push(CorrespondencePattern {
+                    reflexes: vec![None; languages.len()],
+                    sites: Vec::new(),
+                });
+                patterns.len() - 1
+            }
+        };
+        let pattern = &mut patterns[index];
+        for (consensus, value) in pattern.reflexes.iter_mut().zip(values) {
+            if consensus.is_none() {
+                consensus.clone_from(value);
+            }
+        }
+        pattern.sites.push((set, site));
+        site_patterns[set][site] = index;
+    }
+
+    PatternAnalysis {
+        languages,
+        sites,
+        patterns,
+        site_patterns,
+    }
+}
+
+impl PatternAnalysis {
+    /// Predicted reflex of `language` for every cognate set lacking it, as
+    /// (set index, form); segments no pattern attests are `?`
+    pub fn impute(&self, language: &str) -> Vec<(usize, String)> {
+        let Ok(column) = self
+            .languages
+            .binary_search_by(|l| l.as_str().cmp(language))
+        else {
+            return Vec::new();
+        };
+
+        self.sites
+            .iter()
+            .enumerate()
+            .filter(|(_, sites)| !sites.is_empty() && sites.iter().all(|s| s[column].is_none()))
+            .map(|(set, sites)| {
+                let form: String = (0..sites.len())
+                    .filter_map(|site| {
+                        let pattern = &self.patterns[self.site_patterns[set][site]];
+                        match pattern.reflexes[column].as_deref() {
+                            Some("-") => None,
+                            Some(segment) => Some(segment.to_string()),
+                            None => Some(UNKNOWN.to_string()),
+                        }
+                    })
+                    .collect();
+                (set, form)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(language: &str, ipa: &str) -> (String, String) {
+        (language.into(), ipa.into())
+    }
+
+    #[test]
+    fn test_correspondence_matrices() {
+        let sets = vec![
+            vec![member("lat", "pater"), member("eng", "fater")],
+            vec![
+                member("lat", "pes"),
+                member("eng", "fut"),
+                member("eng", "fot"),
+            ],
+            vec![member("lat", "tres")],
+        ];
+        let matrices = correspondence_matrices(&sets);
+        assert_eq!(matrices.len(), 1);
+
+        let m = &matrices[0];
+        assert_eq!(
+            (m.language_a.as_str(), m.language_b.as_str()),
+            ("eng", "lat")
+        );
+        let cell = |a: &str, b: &str| {
+            let i = m.segments_a.iter().position(|s| s == a).unwrap();
+            let j = m.segments_b.iter().position(|s| s == b).unwrap();
+            m.counts[[i, j]]
+        };
+        assert_eq!(cell("f", "p"), 3);
+        assert_eq!(cell("a", "a"), 1);
+        assert_eq!(m.counts.sum(), 11);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&correspondence_matrices_json(&matrices)).unwrap();
+        assert_eq!(json[0]["language_a"], "eng");
+        assert_eq!(
+            json[0]["counts"].as_array().unwrap().len(),
+            m.segments_a.len()
+        );
+    }
+
+    #[test]
+    fn test_correspondence_graph() {
+        let sets = vec![
+            vec![member("lat", "pater"), member("eng", "fater")],
+            vec![member("lat", "pes"), member("eng", "fes")],
+            vec![member("lat", "tres"), member("eng", "tre")],
+        ];
+        let matrices = correspondence_matrices(&sets);
+        let graph = correspondence_graph(&matrices, 1, false);
+        let edges = graph.edge_list();
+        let weight = |a: &str, b: &str| {
+            edges
+                .iter()
+                .find(|(s, t, _)| (s == a && t == b) || (s == b && t == a))
+                .map(|e| e.2)
+        };
+        assert_eq!(weight("eng:f", "lat:p"), Some(2.0));
+        assert_eq!(
+            graph.node_attribute("lat:p", "language"),
+            Some(&AttrValue::from("lat"))
+        );
+        assert_eq!(
+            graph.node_attribute("eng:e", "frequency"),
+            Some(&AttrValue::Number(3.0))
+        );
+        assert!(edges.iter().all(|(s, t, _)| s != "eng:-" && t != "eng:-"));
+
+        // Only the regular correspondences survive a minimum count
+        let regular = correspondence_graph(&matrices, 2, false).edge_list();
+        assert_eq!(regular.len(), 4);
+        let gaps = correspondence_graph(&matrices, 1, true);
+        assert!(gaps.node_attribute("eng:-", "segment").is_some());
+    }
+
+    #[test]
+    fn test_correspondence_patterns_and_imputation() {
+        let sets = vec![
+            vec![
+                member("lat", "pater"),
+                member("eng", "fater"),
+                member("deu", "vater"),
+            ],
+            vec![
+                member("lat", "pes"),
+                member("eng", "fes"),
+                member("deu", "ves"),
+            ],
+            vec![member("lat", "piskis"), member("eng", "fiskis")],
+        ];
+        let analysis = correspondence_patterns(&sets);
+        assert_eq!(analysis.languages, vec!["deu", "eng", "lat"]);
+        assert_eq!(analysis.sites[0].len(), 5);
+
+        // Initial p : f : v sites share one pattern across all three sets
+        let initial = analysis.site_patterns[0][0];
+        assert_eq!(analysis.site_patterns[1][0], initial);
+        assert_eq!(analysis.site_patterns[2][0], initial);
+        assert_eq!(
+            analysis.patterns[initial].reflexes,
+            vec![
+                Some("v".to_string()),
+                Some("f".to_string()),
+                Some("p".to_string())
+            ]
+        );
+
+        // German reflex of "piskis" predicted from the patterns
+        let imputed = analysis.impute("deu");
+        assert_eq!(imputed.len(), 1);
+        assert_eq!(imputed[0].0, 2);
+        assert_eq!(imputed[0].1, "v?s??s");
+        assert!(analysis.impute("got").is_empty());
+    }
+}
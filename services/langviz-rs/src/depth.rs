@@ -0,0 +1,245 @@
+//! Heuristic time depth of cognate sets on a language tree.
+//!
+//! A set attested on both sides of the root split most likely goes back to
+//! the proto-language, while one confined to a subgroup is probably an
+//! innovation of that subgroup (or a loan within it). Each set is placed at
+//! the most recent common ancestor (MRCA) of its languages; the MRCA's age,
+//! relative to the root's, estimates how old the set is. Ages are node
+//! heights from branch lengths, or from branch counts when the tree has no
+//! lengths. Sparse attestation under an old MRCA is weaker evidence (chance
+//! resemblance or borrowing can span branches), so the score blends in how
+//! much of the MRCA's clade the set covers.
+
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::tree::Tree;
+
+/// Inferred depth class of a cognate set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthClass {
+    /// Spans at least two primary branches: reconstructible to the root
+    Root,
+    /// Spans the subgroups of one primary branch
+    Branch,
+    /// Confined to a subgroup below a primary branch
+    Subgroup,
+    /// Attested in a single language
+    Single,
+}
+
+impl DepthClass {
+    pub fn name(self) -> &'static str {
+        match self {
+            DepthClass::Root => "root",
+            DepthClass::Branch => "branch",
+            DepthClass::Subgroup => "subgroup",
+            DepthClass::Single => "single",
+        }
+    }
+}
+
+/// Estimated time depth of one cognate set
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetDepth {
+    /// Distinct languages attesting the set
+    pub languages: usize,
+    /// Share of the tree's languages attesting it
+    pub coverage: f64,
+    /// Share of the MRCA's descendant languages attesting it
+    pub clade_coverage: f64,
+    /// Name of the MRCA node
+    pub mrca: String,
+    /// Primary branches (children of the root) the set reaches
+    pub primary_branches: usize,
+    /// MRCA age over root age, in [0, 1]
+    pub relative_age: f64,
+    /// relative_age · (1 + clade_coverage) / 2
+    pub score: f64,
+    pub class: DepthClass,
+}
+
+/// Depth of each cognate set, given as the languages (tree leaf names) of
+/// its members, in parallel
+pub fn score_set_depths(tree: &Tree, sets: &[Vec<String>]) -> Result<Vec<SetDepth>, String> {
+    let layout = TreeLayout::new(tree);
+    sets.par_iter()
+        .enumerate()
+        .map(|(s, languages)| {
+            let leaves = languages
+                .iter()
+                .map(|language| {
+                    layout.leaf.get(language.as_str()).copied().ok_or_else(|| {
+                        format!(
+                            "language '{}' of set {} is not a leaf of the tree",
+                            language, s
+                        )
+                    })
+                })
+                .collect::<Result<BTreeSet<usize>, _>>()?;
+            if leaves.is_empty() {
+                return Err(format!("set {} has no languages", s));
+            }
+            Ok(layout.depth(&leaves))
+        })
+        .collect()
+}
+
+/// Per-node quantities shared by every set
+struct TreeLayout<'a> {
+    leaf: HashMap<String, usize>,
+    /// Branches from the root
+    level: Vec<usize>,
+    height: Vec<f64>,
+    leaves_below: Vec<usize>,
+    /// Child of the root each node descends from (None for the root)
+    primary: Vec<Option<usize>>,
+    tree: &'a Tree,
+}
+
+impl<'a> TreeLayout<'a> {
+    fn new(tree: &'a Tree) -> Self {
+        let n = tree.nodes.len();
+        let order = tree.preorder();
+        let mut level = vec![0; n];
+        let mut primary = vec![None; n];
+        for &node in &order {
+            if let Some(parent) = tree.nodes[node].parent {
+                level[node] = level[parent] + 1;
+                primary[node] = primary[parent].or(Some(node));
+            }
+        }
+
+        let has_lengths = tree.nodes.iter().any(|node| node.length > 0.0);
+        let mut height = vec![0.0; n];
+        let mut leaves_below = vec![0; n];
+        for &node in order.iter().rev() {
+            let current = &tree.nodes[node];
+            if current.children.is_empty() {
+                leaves_below[node] = 1;
+            }
+            if let Some(parent) = current.parent {
+                let branch = if has_lengths { current.length } else { 1.0 };
+                height[parent] = f64::max(height[parent], height[node] + branch);
+                leaves_below[parent] += leaves_below[node];
+            }
+        }
+
+        let leaf = tree
+            .leaves()
+            .into_iter()
+            .map(|node| (tree.node_name(node), node))
+            .collect();
+        Self {
+            leaf,
+            level,
+            height,
+            leaves_below,
+            primary,
+            tree,
+        }
+    }
+
+    fn mrca(&self, mut a: usize, mut b: usize) -> usize {
+        while a != b {
+            if self.level[a] >= self.level[b] {
+                a = self.tree.nodes[a].parent.unwrap();
+            } else {
+                b = self.tree.nodes[b].parent.unwrap();
+            }
+        }
+        a
+    }
+
+    fn depth(&self, leaves: &BTreeSet<usize>) -> SetDepth {
+        let tree = self.tree;
+        let mrca = leaves
+            .iter()
+            .copied()
+            .reduce(|a, b| self.mrca(a, b))
+            .unwrap();
+        let primary_branches = leaves
+            .iter()
+            .filter_map(|&leaf| self.primary[leaf])
+            .collect::<BTreeSet<_>>()
+            .len();
+        let root_height = self.height[tree.root];
+        let relative_age = if root_height > 0.0 {
+            self.height[mrca] / root_height
+        } else {
+            0.0
+        };
+        let clade_coverage = leaves.len() as f64 / self.leaves_below[mrca] as f64;
+        let class = if leaves.len() == 1 {
+            DepthClass::Single
+        } else if primary_branches > 1 {
+            DepthClass::Root
+        } else if tree.nodes[mrca].parent == Some(tree.root) {
+            DepthClass::Branch
+        } else {
+            DepthClass::Subgroup
+        };
+        SetDepth {
+            languages: leaves.len(),
+            coverage: leaves.len() as f64 / self.leaf.len() as f64,
+            clade_coverage,
+            mrca: tree.node_name(mrca),
+            primary_branches,
+            relative_age,
+            score: relative_age * (1.0 + clade_coverage) / 2.0,
+            class,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sets(languages: &[&[&str]]) -> Vec<Vec<String>> {
+        languages
+            .iter()
+            .map(|set| set.iter().map(|l| l.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_set_depths() {
+        let tree = Tree::from_newick(
+            "(((eng:1,deu:1)wgmc:1,got:2)gmc:2,((lat:1,osc:1)ita:1,gre:2)south:2)pie;",
+        )
+        .unwrap();
+        let depths = score_set_depths(
+            &tree,
+            &sets(&[
+                &["eng", "gre", "lat", "got"],
+                &["eng", "got"],
+                &["eng", "deu", "eng"],
+                &["lat"],
+            ]),
+        )
+        .unwrap();
+        let classes: Vec<DepthClass> = depths.iter().map(|d| d.class).collect();
+        assert_eq!(
+            classes,
+            [
+                DepthClass::Root,
+                DepthClass::Branch,
+                DepthClass::Subgroup,
+                DepthClass::Single
+            ]
+        );
+        assert_eq!(depths[0].mrca, "pie");
+        assert_eq!(depths[0].primary_branches, 2);
+        assert_eq!(depths[0].relative_age, 1.0);
+        assert_eq!(depths[1].relative_age, 0.5);
+        assert_eq!(depths[1].clade_coverage, 2.0 / 3.0);
+        assert_eq!(depths[2].languages, 2);
+        assert_eq!(depths[2].mrca, "wgmc");
+        assert_eq!(depths[3].score, 0.0);
+        assert!(depths.windows(2).all(|w| w[0].score > w[1].score));
+
+        let unknown = score_set_depths(&tree, &sets(&[&["eng", "xyz"]]));
+        assert!(unknown.is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Determinism mode for parallel floating-point reductions.
+//!
+//! Floating-point addition is not associative, so a parallel sum whose
+//! partial results are combined in whatever order threads finish can differ
+//! in the last bits from run to run, enough to flip a borderline threshold
+//! or tie. In determinism mode (the default) reductions split their input
+//! into fixed-size chunks by index, sum each chunk sequentially and add the
+//! chunk sums in order, so identical inputs give bit-identical results
+//! whatever the thread count or scheduling. Turning it off lets rayon
+//! combine partial sums adaptively, which avoids the intermediate buffers on
+//! very large inputs.
+
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Items per chunk of a deterministic sum
+const SUM_CHUNK: usize = 4096;
+
+/// Number of blocks a deterministic element-wise sum is split into
+const VECTOR_BLOCKS: usize = 64;
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(true);
+
+/// Turn determinism mode on or off for the whole process
+pub fn set_deterministic(enabled: bool) {
+    DETERMINISTIC.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::Relaxed)
+}
+
+/// Sum of `values`, ordered in determinism mode
+pub fn par_sum<I>(values: I) -> f64
+where
+    I: IndexedParallelIterator<Item = f64>,
+{
+    sum_with(values, is_deterministic())
+}
+
+fn sum_with<I>(values: I, deterministic: bool) -> f64
+where
+    I: IndexedParallelIterator<Item = f64>,
+{
+    if !deterministic {
+        return values.sum();
+    }
+    let partials: Vec<f64> = values
+        .chunks(SUM_CHUNK)
+        .map(|chunk| chunk.into_iter().sum())
+        .collect();
+    partials.into_iter().sum()
+}
+
+/// Element-wise sum of `per_item(i)` (vectors of length `len`) over
+/// `0..n`, ordered in determinism mode
+pub fn par_sum_vectors<F>(n: usize, len: usize, per_item: F) -> Vec<f64>
+where
+    F: Fn(usize) -> Vec<f64> + Sync,
+{
+    sum_vectors_with(n, len, per_item, is_deterministic())
+}
+
+fn sum_vectors_with<F>(n: usize, len: usize, per_item: F, deterministic: bool) -> Vec<f64>
+where
+    F: Fn(usize) -> Vec<f64> + Sync,
+{
+    let add = |mut total: Vec<f64>, part: Vec<f64>| {
+        for (t, p) in total.iter_mut().zip(part) {
+            *t += p;
+        }
+        total
+    };
+    if !deterministic {
+        return (0..n)
+            .into_par_iter()
+            .map(&per_item)
+            .reduce(|| vec![0.0; len], add);
+    }
+    let block = n.div_ceil(VECTOR_BLOCKS).max(1);
+    let partials: Vec<Vec<f64>> = (0..n.div_ceil(block))
+        .into_par_iter()
+        .map(|b| {
+            (b * block..((b + 1) * block).min(n))
+                .map(&per_item)
+                .fold(vec![0.0; len], add)
+        })
+        .collect();
+    partials.into_iter().fold(vec![0.0; len], add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_threads<T: Send>(threads: usize, f: impl FnOnce() -> T + Send) -> T {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        pool.install(f)
+    }
+
+    #[test]
+    fn test_ordered_sums_ignore_thread_count() {
+        // Magnitudes far apart, so the grouping shows in the low bits
+        let values: Vec<f64> = (0..50_000)
+            .map(|i| ((i * 7919) % 1000) as f64 * 10f64.powi(i % 17 - 8))
+            .collect();
+        let sum = |threads| with_threads(threads, || sum_with(values.par_iter().copied(), true));
+        assert_eq!(sum(1).to_bits(), sum(4).to_bits());
+        assert_eq!(sum(3).to_bits(), sum(8).to_bits());
+        let loose = sum_with(values.par_iter().copied(), false);
+        assert!((loose - sum(2)).abs() <= 1e-9 * sum(2).abs());
+
+        let vectors = |threads, deterministic| {
+            with_threads(threads, || {
+                sum_vectors_with(1000, 3, |i| vec![values[i], 1.0, -values[i]], deterministic)
+            })
+        };
+        assert_eq!(vectors(1, true), vectors(6, true));
+        assert_eq!(vectors(4, false)[1], 1000.0);
+        assert!(sum_vectors_with(0, 2, |_| Vec::new(), true) == [0.0, 0.0]);
+    }
+}
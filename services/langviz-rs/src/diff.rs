@@ -0,0 +1,265 @@
+//! Differences between two cognate graphs, e.g. one per historical stage.
+//!
+//! Edges are matched as unordered node-ID pairs (parallel edges keep their
+//! maximum weight, as in the set operations). Communities are detected on
+//! each graph separately with Louvain and compared through their shared
+//! members: an overlap matrix counts the nodes each "before" community
+//! hands to each "after" community, and communities drawing on several
+//! counterparts are reported as merges or splits.
+
+use ahash::AHashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::graph::CognateGraph;
+
+/// One edge present in either graph; `before` is None for added edges and
+/// `after` for removed ones
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EdgeChange {
+    pub source: String,
+    pub target: String,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+}
+
+/// Node whose number of distinct neighbors changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DegreeChange {
+    pub id: String,
+    pub before: usize,
+    pub after: usize,
+}
+
+/// A community and the counterparts on the other side it shares members
+/// with: "after" communities built from several "before" ones (merges) or
+/// "before" communities spread over several "after" ones (splits)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommunityEvent {
+    pub community: usize,
+    pub parts: Vec<usize>,
+}
+
+/// Structural difference from one graph ("before") to another ("after")
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub added_edges: Vec<EdgeChange>,
+    pub removed_edges: Vec<EdgeChange>,
+    /// Shared edges whose weight moved by more than the tolerance
+    pub reweighted_edges: Vec<EdgeChange>,
+    pub degree_changes: Vec<DegreeChange>,
+    pub communities_before: Vec<Vec<String>>,
+    pub communities_after: Vec<Vec<String>>,
+    /// Members shared by before community `i` and after community `j`
+    pub community_overlap: Vec<Vec<usize>>,
+    pub merges: Vec<CommunityEvent>,
+    pub splits: Vec<CommunityEvent>,
+}
+
+impl GraphDiff {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("graph diff serializes")
+    }
+}
+
+/// Distinct neighbors per node ID from sorted-pair edge weights
+fn degrees<'a>(pairs: &AHashMap<(&'a str, &'a str), f64>) -> AHashMap<&'a str, usize> {
+    let mut degrees = AHashMap::new();
+    for &(a, b) in pairs.keys() {
+        if a != b {
+            *degrees.entry(a).or_insert(0) += 1;
+            *degrees.entry(b).or_insert(0) += 1;
+        }
+    }
+    degrees
+}
+
+/// Communities on one side of `overlap` (rows, or columns when
+/// `transpose`) sharing members with at least two on the other side
+fn events(overlap: &[Vec<usize>], transpose: bool) -> Vec<CommunityEvent> {
+    let (rows, cols) = if transpose {
+        (overlap.first().map_or(0, Vec::len), overlap.len())
+    } else {
+        (overlap.len(), overlap.first().map_or(0, Vec::len))
+    };
+    (0..rows)
+        .filter_map(|i| {
+            let parts: Vec<usize> = (0..cols)
+                .filter(|&j| {
+                    let shared = if transpose {
+                        overlap[j][i]
+                    } else {
+                        overlap[i][j]
+                    };
+                    shared > 0
+                })
+                .collect();
+            (parts.len() > 1).then_some(CommunityEvent {
+                community: i,
+                parts,
+            })
+        })
+        .collect()
+}
+
+impl CognateGraph {
+    /// Difference from this graph to `other`: node and edge changes (shared
+    /// edges count as reweighted when their weights differ by more than
+    /// `tolerance`), degree deltas, and how Louvain communities at
+    /// `resolution` carried over. Lists are sorted by ID.
+    pub fn diff(&self, other: &CognateGraph, resolution: f64, tolerance: f64) -> GraphDiff {
+        let before = self.pair_weights();
+        let after = other.pair_weights();
+        let pairs: BTreeSet<(&str, &str)> = before.keys().chain(after.keys()).copied().collect();
+
+        let (mut added_edges, mut removed_edges, mut reweighted_edges) =
+            (Vec::new(), Vec::new(), Vec::new());
+        for (a, b) in pairs {
+            let (old, new) = (before.get(&(a, b)).copied(), after.get(&(a, b)).copied());
+            let change = EdgeChange {
+                source: a.to_string(),
+                target: b.to_string(),
+                before: old,
+                after: new,
+            };
+            match (old, new) {
+                (None, Some(_)) => added_edges.push(change),
+                (Some(_), None) => removed_edges.push(change),
+                (Some(w), Some(w2)) if (w2 - w).abs() > tolerance => reweighted_edges.push(change),
+                _ => {}
+            }
+        }
+
+        let ids_before: BTreeSet<&str> = self.node_map.keys().map(String::as_str).collect();
+        let ids_after: BTreeSet<&str> = other.node_map.keys().map(String::as_str).collect();
+        let to_strings = |ids: Vec<&&str>| ids.into_iter().map(|id| id.to_string()).collect();
+        let added_nodes = to_strings(ids_after.difference(&ids_before).collect());
+        let removed_nodes = to_strings(ids_before.difference(&ids_after).collect());
+
+        let (degrees_before, degrees_after) = (degrees(&before), degrees(&after));
+        let degree_changes = ids_before
+            .union(&ids_after)
+            .filter_map(|&id| {
+                let old = degrees_before.get(id).copied().unwrap_or(0);
+                let new = degrees_after.get(id).copied().unwrap_or(0);
+                (old != new).then(|| DegreeChange {
+                    id: id.to_string(),
+                    before: old,
+                    after: new,
+                })
+            })
+            .collect();
+
+        let communities_before = self.detect_communities(resolution);
+        let communities_after = other.detect_communities(resolution);
+        let community_of: BTreeMap<&str, usize> = communities_after
+            .iter()
+            .enumerate()
+            .flat_map(|(c, members)| members.iter().map(move |id| (id.as_str(), c)))
+            .collect();
+        let community_overlap: Vec<Vec<usize>> = communities_before
+            .iter()
+            .map(|members| {
+                let mut row = vec![0; communities_after.len()];
+                for id in members {
+                    if let Some(&c) = community_of.get(id.as_str()) {
+                        row[c] += 1;
+                    }
+                }
+                row
+            })
+            .collect();
+
+        GraphDiff {
+            added_nodes,
+            removed_nodes,
+            added_edges,
+            removed_edges,
+            reweighted_edges,
+            degree_changes,
+            merges: events(&community_overlap, true),
+            splits: events(&community_overlap, false),
+            communities_before,
+            communities_after,
+            community_overlap,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn graph(edges: &[(&str, &str, f64)]) -> CognateGraph {
+        CognateGraph::from_edges(
+            edges
+                .iter()
+                .map(|(a, b, w)| SimilarityEdge::new(a.to_string(), b.to_string(), *w))
+                .collect(),
+            0.0,
+        )
+    }
+
+    #[test]
+    fn test_graph_diff() {
+        // Two triangles joined by a weak bridge, then fused into one clique
+        // while `f` drops out and `g` joins
+        let before = graph(&[
+            ("a", "b", 0.9),
+            ("b", "c", 0.9),
+            ("a", "c", 0.9),
+            ("d", "e", 0.9),
+            ("e", "f", 0.9),
+            ("d", "f", 0.9),
+            ("c", "d", 0.1),
+        ]);
+        let mut edges = vec![("d", "e", 0.9), ("e", "g", 0.5), ("c", "d", 0.7)];
+        for (i, a) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            for b in ["a", "b", "c", "d", "e"].iter().skip(i + 1) {
+                if !edges.iter().any(|&(x, y, _)| (x, y) == (*a, *b)) {
+                    edges.push((a, b, 0.9));
+                }
+            }
+        }
+        let after = graph(&edges);
+        let diff = before.diff(&after, 1.0, 1e-9);
+
+        assert_eq!(diff.added_nodes, ["g"]);
+        assert_eq!(diff.removed_nodes, ["f"]);
+        assert_eq!(diff.removed_edges.len(), 2);
+        assert!(diff
+            .added_edges
+            .iter()
+            .any(|e| e.source == "e" && e.target == "g"));
+        assert_eq!(
+            diff.reweighted_edges,
+            vec![EdgeChange {
+                source: "c".to_string(),
+                target: "d".to_string(),
+                before: Some(0.1),
+                after: Some(0.7),
+            }]
+        );
+        let e = diff.degree_changes.iter().find(|c| c.id == "e").unwrap();
+        assert_eq!((e.before, e.after), (2, 5));
+
+        assert_eq!(diff.communities_before.len(), 2);
+        assert_eq!(diff.communities_after.len(), 1);
+        assert_eq!(diff.community_overlap, vec![vec![3], vec![2]]);
+        assert_eq!(
+            diff.merges,
+            vec![CommunityEvent {
+                community: 0,
+                parts: vec![0, 1],
+            }]
+        );
+        assert!(diff.splits.is_empty());
+
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json()).unwrap();
+        assert_eq!(json["added_edges"][0]["before"], serde_json::Value::Null);
+        assert!(before.diff(&before, 1.0, 0.0).reweighted_edges.is_empty());
+    }
+}
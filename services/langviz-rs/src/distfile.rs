@@ -0,0 +1,297 @@
+//! Labeled distance matrices in PHYLIP and CSV / TSV files.
+//!
+//! PHYLIP files start with the number of taxa, followed by one row per
+//! taxon: its name and then either the full row of distances (square) or
+//! only those to earlier taxa (lower-triangular, with or without the zero
+//! diagonal). Names are whitespace-delimited (relaxed PHYLIP) and rows may
+//! wrap over several lines. CSV / TSV files carry the labels both in the
+//! header row, after a corner cell, and in the first column, in the same
+//! order. Files may be plain, gzip or zstd; written matrices are square.
+
+use ndarray::{Array2, ArrayView2};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::characters::phylip_name;
+use crate::compress::read_to_string;
+use crate::ingest::split_record;
+
+/// Distance-matrix file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceFormat {
+    Phylip,
+    Csv,
+    Tsv,
+}
+
+impl DistanceFormat {
+    /// Format implied by a file name, ignoring `.gz` / `.zst`: `.phy`,
+    /// `.phylip` and `.dist` PHYLIP, `.tsv` and `.tab` TSV, else CSV
+    pub fn from_path(path: &Path) -> Self {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let name = name
+            .strip_suffix(".gz")
+            .or_else(|| name.strip_suffix(".zst"))
+            .unwrap_or(name);
+        match name.rsplit_once('.').map(|(_, extension)| extension) {
+            Some("phy" | "phylip" | "dist") => DistanceFormat::Phylip,
+            Some("tsv" | "tab") => DistanceFormat::Tsv,
+            _ => DistanceFormat::Csv,
+        }
+    }
+}
+
+impl FromStr for DistanceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "phylip" => Ok(DistanceFormat::Phylip),
+            "csv" => Ok(DistanceFormat::Csv),
+            "tsv" => Ok(DistanceFormat::Tsv),
+            other => Err(format!(
+                "unknown distance format '{}' (expected phylip, csv or tsv)",
+                other
+            )),
+        }
+    }
+}
+
+/// Labels and distances of a matrix file's text
+pub fn parse_distance_matrix(
+    text: &str,
+    format: DistanceFormat,
+) -> Result<(Vec<String>, Array2<f64>), String> {
+    match format {
+        DistanceFormat::Phylip => parse_phylip(text),
+        DistanceFormat::Csv => parse_delimited(text, ','),
+        DistanceFormat::Tsv => parse_delimited(text, '\t'),
+    }
+}
+
+/// Read a plain, gzip or zstd matrix file, in `format` or the one its name
+/// implies
+pub fn read_distance_matrix(
+    path: &Path,
+    format: Option<DistanceFormat>,
+) -> Result<(Vec<String>, Array2<f64>), String> {
+    let text =
+        read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    parse_distance_matrix(
+        &text,
+        format.unwrap_or_else(|| DistanceFormat::from_path(path)),
+    )
+}
+
+fn parse_value(token: &str, row: &str) -> Result<f64, String> {
+    match token.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(format!("invalid distance '{}' in row '{}'", token, row)),
+    }
+}
+
+fn parse_phylip(text: &str) -> Result<(Vec<String>, Array2<f64>), String> {
+    let mut tokens = text.split_whitespace();
+    let n: usize = tokens
+        .next()
+        .ok_or("PHYLIP matrix is empty")?
+        .parse()
+        .map_err(|_| "PHYLIP matrix must start with the number of taxa".to_string())?;
+    let tokens: Vec<&str> = tokens.collect();
+    // Values per row i: n (square), i (lower) or i + 1 (lower with diagonal)
+    let row_values: fn(usize, usize) -> usize = if tokens.len() == n + n * n {
+        |_, n| n
+    } else if tokens.len() == n + n * n.saturating_sub(1) / 2 {
+        |i, _| i
+    } else if tokens.len() == n + n * (n + 1) / 2 {
+        |i, _| i + 1
+    } else {
+        return Err(format!(
+            "PHYLIP matrix of {} taxa has {} names and values, expected {} (square) \
+             or {} (lower-triangular)",
+            n,
+            tokens.len(),
+            n + n * n,
+            n + n * n.saturating_sub(1) / 2
+        ));
+    };
+
+    let mut labels = Vec::with_capacity(n);
+    let mut distances = Array2::zeros((n, n));
+    let mut tokens = tokens.into_iter();
+    for i in 0..n {
+        // Counts were checked above
+        let name = tokens.next().unwrap();
+        for j in 0..row_values(i, n) {
+            let value = parse_value(tokens.next().unwrap(), name)?;
+            distances[[i, j]] = value;
+            if j != i {
+                distances[[j, i]] = value;
+            }
+        }
+        labels.push(name.to_string());
+    }
+    Ok((labels, distances))
+}
+
+fn parse_delimited(text: &str, delimiter: char) -> Result<(Vec<String>, Array2<f64>), String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header = split_record(lines.next().ok_or("distance matrix is empty")?, delimiter);
+    let labels: Vec<String> = header
+        .iter()
+        .skip(1)
+        .map(|label| label.trim().to_string())
+        .collect();
+    let n = labels.len();
+
+    let mut distances = Array2::zeros((n, n));
+    let mut rows = 0;
+    for line in lines {
+        let fields = split_record(line, delimiter);
+        let label = fields[0].trim();
+        if rows == n || label != labels[rows] {
+            return Err(format!(
+                "row '{}' does not match column {} of the header",
+                label,
+                rows + 1
+            ));
+        }
+        if fields.len() != n + 1 {
+            return Err(format!(
+                "row '{}' has {} distances, expected {}",
+                label,
+                fields.len() - 1,
+                n
+            ));
+        }
+        for (j, field) in fields[1..].iter().enumerate() {
+            distances[[rows, j]] = parse_value(field, label)?;
+        }
+        rows += 1;
+    }
+    if rows != n {
+        return Err(format!("matrix has {} columns but {} rows", n, rows));
+    }
+    Ok((labels, distances))
+}
+
+/// Write a square matrix; PHYLIP names have whitespace replaced by `_`
+pub fn write_distance_matrix(
+    out: &mut dyn Write,
+    labels: &[String],
+    distances: ArrayView2<f64>,
+    format: DistanceFormat,
+) -> io::Result<()> {
+    let n = labels.len();
+    if distances.dim() != (n, n) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "distance matrix is {:?} but there are {} labels",
+                distances.dim(),
+                n
+            ),
+        ));
+    }
+    let delimiter = match format {
+        DistanceFormat::Phylip => {
+            let names: Vec<String> = labels.iter().map(|l| phylip_name(l)).collect();
+            let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+            writeln!(out, "{}", n)?;
+            for (name, row) in names.iter().zip(distances.rows()) {
+                write!(out, "{:width$}", name, width = width)?;
+                for value in row {
+                    write!(out, " {}", value)?;
+                }
+                writeln!(out)?;
+            }
+            return Ok(());
+        }
+        DistanceFormat::Csv => ',',
+        DistanceFormat::Tsv => '\t',
+    };
+    for label in labels {
+        write!(out, "{}{}", delimiter, csv_field(label, delimiter))?;
+    }
+    writeln!(out)?;
+    for (label, row) in labels.iter().zip(distances.rows()) {
+        write!(out, "{}", csv_field(label, delimiter))?;
+        for value in row {
+            write!(out, "{}{}", delimiter, value)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Field double-quoted when it holds the delimiter or a quote
+fn csv_field(field: &str, delimiter: char) -> String {
+    if field.contains(delimiter) || field.contains('"') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn labels() -> Vec<String> {
+        vec!["eng".into(), "deu".into(), "Old Norse, West".into()]
+    }
+
+    fn written(distances: &Array2<f64>, format: DistanceFormat) -> String {
+        let mut out = Vec::new();
+        write_distance_matrix(&mut out, &labels(), distances.view(), format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let distances = array![[0.0, 0.25, 0.5], [0.25, 0.0, 0.125], [0.5, 0.125, 0.0]];
+
+        let csv = written(&distances, DistanceFormat::Csv);
+        assert!(csv.starts_with(",eng,deu,\"Old Norse, West\"\n"));
+        let (read, matrix) = parse_distance_matrix(&csv, DistanceFormat::Csv).unwrap();
+        assert_eq!((read, matrix), (labels(), distances.clone()));
+
+        let phylip = written(&distances, DistanceFormat::Phylip);
+        assert_eq!(phylip.lines().nth(3), Some("Old_Norse,_West 0.5 0.125 0"));
+        let (read, matrix) = parse_distance_matrix(&phylip, DistanceFormat::Phylip).unwrap();
+        assert_eq!(read[2], "Old_Norse,_West");
+        assert_eq!(matrix, distances);
+
+        let lower = "3\na\nb 0.25\nc 0.5\n  0.125\n";
+        let (read, matrix) = parse_distance_matrix(lower, DistanceFormat::Phylip).unwrap();
+        assert_eq!(read, ["a", "b", "c"]);
+        assert_eq!(matrix, distances);
+    }
+
+    #[test]
+    fn test_rejects_malformed_matrices() {
+        let bad = [
+            ("3\na 0 1\nb 1 0\nc 1\n", DistanceFormat::Phylip),
+            (",a,b\na,0,1\nc,1,0\n", DistanceFormat::Csv),
+            (",a,b\na,0,x\nb,1,0\n", DistanceFormat::Csv),
+            ("\ta\tb\na\t0\t1\n", DistanceFormat::Tsv),
+        ];
+        for (text, format) in bad {
+            assert!(parse_distance_matrix(text, format).is_err(), "{}", text);
+        }
+        let mut out = Vec::new();
+        let square = Array2::<f64>::zeros((2, 2));
+        assert!(
+            write_distance_matrix(&mut out, &labels(), square.view(), DistanceFormat::Csv).is_err()
+        );
+
+        let path = Path::new("dists.phy.gz");
+        assert_eq!(DistanceFormat::from_path(path), DistanceFormat::Phylip);
+        assert_eq!(
+            DistanceFormat::from_path(Path::new("d.tsv")),
+            DistanceFormat::Tsv
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Node embeddings and layouts for cognate graphs.
+//!
+//! Every method returns a `NodeEmbedding`: an (n × d) coordinate matrix plus
+//! the node ID for each row, so Python receives one numpy array instead of
+//! dicts of lists.
+
+use ndarray::Array2;
+use petgraph::visit::EdgeRef;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::error::LangVizError;
+use crate::graph::CognateGraph;
+use crate::linalg::top_eigenpairs;
+
+/// Row-aligned node coordinates
+#[derive(Debug, Clone)]
+pub struct NodeEmbedding {
+    pub ids: Vec<String>,
+    pub coords: Array2<f64>,
+}
+
+/// node2vec walk and training parameters
+#[derive(Debug, Clone)]
+pub struct Node2VecParams {
+    pub dimensions: usize,
+    pub walk_length: usize,
+    pub walks_per_node: usize,
+    /// Return parameter (higher = less backtracking)
+    pub p: f64,
+    /// In-out parameter (higher = stay local, BFS-like)
+    pub q: f64,
+    pub window: usize,
+    pub negative: usize,
+    pub epochs: usize,
+    pub learning_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for Node2VecParams {
+    fn default() -> Self {
+        Self {
+            dimensions: 32,
+            walk_length: 20,
+            walks_per_node: 10,
+            p: 1.0,
+            q: 1.0,
+            window: 5,
+            negative: 5,
+            epochs: 1,
+            learning_rate: 0.025,
+            seed: 0,
+        }
+    }
+}
+
+impl Node2VecParams {
+    /// The walk biases `p` and `q` must be finite and positive: the
+    /// transition weights use 1/p and 1/q
+    pub fn validate(&self) -> Result<(), LangVizError> {
+        for (name, value) in [("p", self.p), ("q", self.q)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(LangVizError::InvalidParameter {
+                    name: name.to_string(),
+                    value,
+                    expected: "a finite value > 0".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CognateGraph {
+    /// Weighted neighbor lists indexed by node index
+    fn weighted_adjacency(&self) -> Vec<Vec<(usize, f64)>> {
+        let mut adjacency = vec![Vec::new(); self.graph.node_count()];
+        for edge in self.graph.edge_references() {
+            let (s, t) = (edge.source().index(), edge.target().index());
+            if s != t {
+                adjacency[s].push((t, *edge.weight()));
+                adjacency[t].push((s, *edge.weight()));
+            }
+        }
+        adjacency
+    }
+
+    /// Laplacian eigenmaps: the leading non-trivial eigenvectors of the
+    /// normalized adjacency D^-1/2 W D^-1/2, rescaled by D^-1/2
+    pub fn spectral_embedding(&self, dimensions: usize, seed: u64) -> NodeEmbedding {
+        let n = self.graph.node_count();
+        let adjacency = self.weighted_adjacency();
+        let degree: Vec<f64> = adjacency
+            .iter()
+            .map(|nbrs| nbrs.iter().map(|(_, w)| w).sum())
+            .collect();
+        let inv_sqrt: Vec<f64> = degree
+            .iter()
+            .map(|&d| if d > 0.0 { 1.0 / d.sqrt() } else { 0.0 })
+            .collect();
+
+        // (I + N) / 2 has the same eigenvectors as N but is PSD
+        let apply = |x: &Array2<f64>| {
+            let mut y = x * 0.5;
+            for (i, nbrs) in adjacency.iter().enumerate() {
+                for &(j, w) in nbrs {
+                    let scale = 0.5 * w * inv_sqrt[i] * inv_sqrt[j];
+                    for c in 0..x.ncols() {
+                        y[[i, c]] += scale * x[[j, c]];
+                    }
+                }
+            }
+            y
+        };
+
+        let k = (dimensions + 1).min(n);
+        let (_, vectors) = top_eigenpairs(apply, n, k, 300, seed);
+
+        let d = dimensions.min(k.saturating_sub(1));
+        let mut coords = Array2::<f64>::zeros((n, dimensions));
+        for i in 0..n {
+            for c in 0..d {
+                coords[[i, c]] = vectors[[i, c + 1]] * inv_sqrt[i];
+            }
+        }
+
+        NodeEmbedding {
+            ids: self.node_ids(),
+            coords,
+        }
+    }
+
+    /// Fruchterman-Reingold force-directed layout (weighted attraction)
+    pub fn force_layout(&self, dimensions: usize, iterations: usize, seed: u64) -> NodeEmbedding {
+        let n = self.graph.node_count();
+        let dimensions = dimensions.max(1);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut pos = Array2::from_shape_fn((n, dimensions), |_| rng.gen::<f64>() - 0.5);
+
+        if n > 1 {
+            let k = (1.0 / n as f64).powf(1.0 / dimensions as f64);
+            let adjacency = self.weighted_adjacency();
+            let mut temperature = 0.1;
+            let cooling = temperature / (iterations.max(1) + 1) as f64;
+
+            for _ in 0..iterations {
+                let displacement: Vec<Vec<f64>> = (0..n)
+                    .into_par_iter()
+                    .map(|i| {
+                        let mut disp = vec![0.0; dimensions];
+                        // Repulsion from every node
+                        for j in 0..n {
+                            if i == j {
+                                continue;
+                            }
+                            let mut dist2 = 0.0;
+                            for c in 0..dimensions {
+                                let d = pos[[i, c]] - pos[[j, c]];
+                                dist2 += d * d;
+                            }
+                            let dist2 = dist2.max(1e-9);
+                            for c in 0..dimensions {
+                                disp[c] += (pos[[i, c]] - pos[[j, c]]) * k * k / dist2;
+                            }
+                        }
+                        // Attraction along edges, scaled by similarity
+                        for &(j, w) in &adjacency[i] {
+                            let mut dist2 = 0.0;
+                            for c in 0..dimensions {
+                                let d = pos[[i, c]] - pos[[j, c]];
+                                dist2 += d * d;
+                            }
+                            let dist = dist2.sqrt();
+                            for c in 0..dimensions {
+                                disp[c] -= (pos[[i, c]] - pos[[j, c]]) * dist * w / k;
+                            }
+                        }
+                        disp
+                    })
+                    .collect();
+
+                for (i, disp) in displacement.iter().enumerate() {
+                    let length = disp.iter().map(|d| d * d).sum::<f64>().sqrt();
+                    if length > 0.0 {
+                        let step = length.min(temperature) / length;
+                        for c in 0..dimensions {
+                            pos[[i, c]] += disp[c] * step;
+                        }
+                    }
+                }
+                temperature -= cooling;
+            }
+        }
+
+        NodeEmbedding {
+            ids: self.node_ids(),
+            coords: pos,
+        }
+    }
+
+    /// node2vec: second-order biased random walks followed by skip-gram
+    /// training with negative sampling
+    pub fn node2vec(&self, params: &Node2VecParams) -> NodeEmbedding {
+        let n = self.graph.node_count();
+        let dim = params.dimensions.max(1);
+        let adjacency = self.weighted_adjacency();
+        let walks = self.biased_walks(&adjacency, params);
+
+        let mut rng = StdRng::seed_from_u64(params.seed.wrapping_add(1));
+        let mut input = Array2::from_shape_fn((n, dim), |_| (rng.gen::<f64>() - 0.5) / dim as f64);
+        let mut output = Array2::<f64>::zeros((n, dim));
+
+        // Unigram^0.75 noise distribution over walk occurrences
+        let mut counts = vec![0.0f64; n];
+        for walk in &walks {
+            for &v in walk {
+                counts[v] += 1.0;
+            }
+        }
+        let mut noise_cdf: Vec<f64> = counts.iter().map(|c| c.powf(0.75)).collect();
+        let total: f64 = noise_cdf.iter().sum();
+        let mut acc = 0.0;
+        for x in noise_cdf.iter_mut() {
+            acc += *x / total.max(f64::MIN_POSITIVE);
+            *x = acc;
+        }
+
+        let total_steps = (params.epochs.max(1) * walks.len()).max(1) as f64;
+        let mut step = 0.0;
+        let mut grad = vec![0.0; dim];
+
+        for _ in 0..params.epochs.max(1) {
+            for walk in &walks {
+                let lr = params.learning_rate * (1.0 - step / total_steps).max(1e-4);
+                step += 1.0;
+
+                for (pos, &center) in walk.iter().enumerate() {
+                    let lo = pos.saturating_sub(params.window);
+                    let hi = (pos + params.window + 1).min(walk.len());
+                    for (ctx_pos, &context) in walk.iter().enumerate().take(hi).skip(lo) {
+                        if ctx_pos == pos {
+                            continue;
+                        }
+                        grad.iter_mut().for_each(|g| *g = 0.0);
+
+                        for s in 0..=params.negative {
+                            let (target, label) = if s == 0 {
+                                (context, 1.0)
+                            } else {
+                                let r: f64 = rng.gen();
+                                (noise_cdf.partition_point(|&c| c < r).min(n - 1), 0.0)
+                            };
+                            if s > 0 && target == context {
+                                continue;
+                            }
+
+                            let dot: f64 = (0..dim)
+                                .map(|c| input[[center, c]] * output[[target, c]])
+                                .sum();
+                            let g = lr * (label - 1.0 / (1.0 + (-dot).exp()));
+                            for c in 0..dim {
+                                grad[c] += g * output[[target, c]];
+                                output[[target, c]] += g * input[[center, c]];
+                            }
+                        }
+
+                        for c in 0..dim {
+                            input[[center, c]] += grad[c];
+                        }
+                    }
+                }
+            }
+        }
+
+        NodeEmbedding {
+            ids: self.node_ids(),
+            coords: input,
+        }
+    }
+
+    /// Generate node2vec walks (p/q-biased, weight-proportional transitions)
+    fn biased_walks(
+        &self,
+        adjacency: &[Vec<(usize, f64)>],
+        params: &Node2VecParams,
+    ) -> Vec<Vec<usize>> {
+        let n = adjacency.len();
+
+        (0..params.walks_per_node)
+            .into_par_iter()
+            .flat_map_iter(|round| {
+                let mut rng = StdRng::seed_from_u64(
+                    params.seed ^ (round as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15),
+                );
+                (0..n)
+                    .map(|start| {
+                        let mut walk = vec![start];
+                        while walk.len() < params.walk_length.max(1) {
+                            let current = *walk.last().unwrap();
+                            let nbrs = &adjacency[current];
+                            if nbrs.is_empty() {
+                                break;
+                            }
+
+                            let previous = walk.len().checked_sub(2).map(|i| walk[i]);
+                            let bias = |next: usize| match previous {
+                                None => 1.0,
+                                Some(prev) if prev == next => 1.0 / params.p,
+                                Some(prev) if adjacency[prev].iter().any(|&(x, _)| x == next) => {
+                                    1.0
+                                }
+                                Some(_) => 1.0 / params.q,
+                            };
+
+                            let total: f64 = nbrs.iter().map(|&(x, w)| w * bias(x)).sum();
+                            let mut r = rng.gen::<f64>() * total;
+                            let mut chosen = nbrs[nbrs.len() - 1].0;
+                            for &(x, w) in nbrs {
+                                r -= w * bias(x);
+                                if r <= 0.0 {
+                                    chosen = x;
+                                    break;
+                                }
+                            }
+                            walk.push(chosen);
+                        }
+                        walk
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn two_cliques() -> CognateGraph {
+        let mut edges = Vec::new();
+        for group in ["a", "b"] {
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    edges.push(SimilarityEdge::new(
+                        format!("{}{}", group, i),
+                        format!("{}{}", group, j),
+                        0.9,
+                    ));
+                }
+            }
+        }
+        edges.push(SimilarityEdge::new("a0".into(), "b0".into(), 0.1));
+        CognateGraph::from_edges(edges, 0.0)
+    }
+
+    fn row(e: &NodeEmbedding, id: &str) -> usize {
+        e.ids.iter().position(|x| x == id).unwrap()
+    }
+
+    #[test]
+    fn test_spectral_separates_cliques() {
+        let e = two_cliques().spectral_embedding(1, 3);
+        assert_eq!(e.coords.shape(), &[8, 1]);
+        let a = e.coords[[row(&e, "a2"), 0]];
+        let b = e.coords[[row(&e, "b2"), 0]];
+        assert!(a * b < 0.0);
+    }
+
+    #[test]
+    fn test_layout_and_node2vec_shapes() {
+        let graph = two_cliques();
+        let layout = graph.force_layout(2, 50, 1);
+        assert_eq!(layout.coords.shape(), &[8, 2]);
+        assert!(layout.coords.iter().all(|v| v.is_finite()));
+
+        let params = Node2VecParams {
+            dimensions: 8,
+            walks_per_node: 4,
+            ..Default::default()
+        };
+        let emb = graph.node2vec(&params);
+        assert_eq!(emb.coords.shape(), &[8, 8]);
+        assert_eq!(emb.ids.len(), 8);
+    }
+
+    #[test]
+    fn test_node2vec_params_validation() {
+        assert!(Node2VecParams::default().validate().is_ok());
+        for (p, q) in [
+            (0.0, 1.0),
+            (1.0, -2.0),
+            (f64::NAN, 1.0),
+            (1.0, f64::INFINITY),
+        ] {
+            let params = Node2VecParams {
+                p,
+                q,
+                ..Default::default()
+            };
+            assert!(matches!(
+                params.validate(),
+                Err(LangVizError::InvalidParameter { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Crate-wide error type for invalid input.
+//!
+//! The Python bindings validate arguments up front and raise one exception
+//! class per variant, all subclasses of `LangVizError` (itself a
+//! `ValueError`), with messages naming the offending item.
+
+use std::fmt;
+
+/// Invalid input to a LangViz kernel
+#[derive(Debug, Clone, PartialEq)]
+pub enum LangVizError {
+    /// Edge at `index` whose weight is NaN, infinite or negative
+    InvalidWeight {
+        index: usize,
+        source: String,
+        target: String,
+        weight: f64,
+    },
+    /// ID missing from the graph or matrix it was looked up in
+    UnknownEntryId(String),
+    /// Named input that needs at least one item
+    EmptyInput(String),
+    /// Named input whose shape doesn't fit the rest
+    DimensionMismatch {
+        what: String,
+        expected: String,
+        actual: String,
+    },
+    /// Parameter outside its valid range
+    InvalidParameter {
+        name: String,
+        value: f64,
+        expected: String,
+    },
+}
+
+impl fmt::Display for LangVizError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LangVizError::InvalidWeight {
+                index,
+                source,
+                target,
+                weight,
+            } => write!(
+                f,
+                "edge {} ({} -> {}) has invalid weight {}: weights must be finite and >= 0",
+                index, source, target, weight
+            ),
+            LangVizError::UnknownEntryId(id) => write!(f, "unknown entry ID '{}'", id),
+            LangVizError::EmptyInput(what) => write!(f, "{} must not be empty", what),
+            LangVizError::DimensionMismatch {
+                what,
+                expected,
+                actual,
+            } => write!(f, "{} has shape {}, expected {}", what, actual, expected),
+            LangVizError::InvalidParameter {
+                name,
+                value,
+                expected,
+            } => write!(f, "{} = {} is invalid: expected {}", name, value, expected),
+        }
+    }
+}
+
+impl std::error::Error for LangVizError {}
+
+/// Reject the first (source, target, weight) edge whose weight is NaN,
+/// infinite or negative
+pub fn validate_weights<'a, I>(edges: I) -> Result<(), LangVizError>
+where
+    I: IntoIterator<Item = (&'a str, &'a str, f64)>,
+{
+    for (index, (source, target, weight)) in edges.into_iter().enumerate() {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(LangVizError::InvalidWeight {
+                index,
+                source: source.to_string(),
+                target: target.to_string(),
+                weight,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Similarity thresholds must be finite and non-negative (a NaN threshold
+/// would silently drop every edge)
+pub fn validate_threshold(threshold: f64) -> Result<(), LangVizError> {
+    if threshold.is_finite() && threshold >= 0.0 {
+        Ok(())
+    } else {
+        Err(LangVizError::InvalidParameter {
+            name: "threshold".to_string(),
+            value: threshold,
+            expected: "a finite value >= 0".to_string(),
+        })
+    }
+}
+
+/// PageRank damping factors are probabilities
+pub fn validate_damping(damping: f64) -> Result<(), LangVizError> {
+    if (0.0..=1.0).contains(&damping) {
+        Ok(())
+    } else {
+        Err(LangVizError::InvalidParameter {
+            name: "damping".to_string(),
+            value: damping,
+            expected: "a value in [0, 1]".to_string(),
+        })
+    }
+}
+
+/// A `what` matrix must be n × n for `n` IDs (or just square when `n` is
+/// None)
+pub fn validate_square(what: &str, shape: &[usize], n: Option<usize>) -> Result<(), LangVizError> {
+    let n = n.unwrap_or(shape[0]);
+    if shape == [n, n] {
+        Ok(())
+    } else {
+        Err(LangVizError::DimensionMismatch {
+            what: what.to_string(),
+            expected: format!("({}, {})", n, n),
+            actual: format!("{:?}", shape),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_names_the_failing_item() {
+        let edges = [("a", "b", 0.9), ("b", "c", f64::NAN), ("c", "d", -1.0)];
+        let error = validate_weights(edges).unwrap_err();
+        assert!(matches!(
+            error,
+            LangVizError::InvalidWeight { index: 1, .. }
+        ));
+        assert!(error.to_string().contains("b -> c"));
+        assert!(validate_weights([("a", "b", 0.0)]).is_ok());
+
+        assert!(validate_threshold(0.7).is_ok());
+        assert!(validate_threshold(f64::NAN).is_err());
+        assert!(validate_threshold(-0.1).is_err());
+        assert!(validate_damping(0.85).is_ok());
+        assert!(validate_damping(f64::NAN).is_err());
+        assert!(validate_damping(1.5).is_err());
+
+        assert!(validate_square("distances", &[3, 3], Some(3)).is_ok());
+        let error = validate_square("distances", &[3, 2], None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "distances has shape [3, 2], expected (3, 3)"
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Etymology lookup: the reconstructed proto-form a new word most likely
+//! descends from.
+//!
+//! Each language is described either by its ordered sound laws or by
+//! learned proto → reflex correspondences given as an edit cost model.
+//! With sound laws every proto-form is derived once when the index is built,
+//! and a word is scored by `phonetic_distance` against each predicted
+//! reflex; the derivation lists the rules that changed the form. With
+//! correspondences the word is scored by `phonetic_distance_with_costs`
+//! against the proto-form itself, and the derivation lists the
+//! non-matching steps of the weighted alignment, applied left to right.
+
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::phonetic::{
+    dtw_align_with_costs, phonetic_distance, phonetic_distance_with_costs, CostModel,
+};
+use crate::soundchange::{derivation, Derivation, DerivationStep, SoundChangeRule};
+use crate::types::EditOp;
+
+/// Gap symbol in aligned sequences
+const GAP: &str = "-";
+
+/// Null segment in correspondence steps
+const NULL: &str = "∅";
+
+/// How a language's words derive from the proto-language
+#[derive(Debug, Clone)]
+pub enum LanguageModel {
+    /// Ordered sound laws
+    Rules(Vec<SoundChangeRule>),
+    /// Proto/reflex segment costs (low cost = regular correspondence)
+    Correspondences(CostModel),
+}
+
+/// A candidate proto-form for a word
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtoMatch {
+    pub proto_id: String,
+    pub proto_form: String,
+    pub language: String,
+    /// Reflex the sound laws predict (None under correspondences)
+    pub reflex: Option<String>,
+    /// Similarity of word and derivation, in [0, 1]
+    pub score: f64,
+    /// Proto-form to reflex (sound laws) or to the word (correspondences)
+    pub derivation: Derivation,
+}
+
+/// Proto-forms derived into each modeled language
+#[derive(Debug, Clone)]
+pub struct EtymologyIndex {
+    /// (id, form) per proto-form
+    proto_forms: Vec<(String, String)>,
+    languages: BTreeMap<String, LanguageModel>,
+    /// Derivation of each proto-form, for languages with sound laws
+    derived: BTreeMap<String, Vec<Derivation>>,
+}
+
+impl EtymologyIndex {
+    /// Index (id, form) proto-forms under per-language models, deriving
+    /// every proto-form through each set of sound laws (in parallel)
+    pub fn new(
+        proto_forms: Vec<(String, String)>,
+        languages: BTreeMap<String, LanguageModel>,
+    ) -> Result<Self, String> {
+        if proto_forms.is_empty() {
+            return Err("proto_forms must not be empty".to_string());
+        }
+        if languages.is_empty() {
+            return Err("at least one language needs sound laws or correspondences".to_string());
+        }
+        let derived = languages
+            .iter()
+            .filter_map(|(language, model)| match model {
+                LanguageModel::Rules(rules) => {
+                    let derivations = proto_forms
+                        .par_iter()
+                        .map(|(_, form)| derivation(form, rules))
+                        .collect();
+                    Some((language.clone(), derivations))
+                }
+                LanguageModel::Correspondences(_) => None,
+            })
+            .collect();
+        Ok(Self {
+            proto_forms,
+            languages,
+            derived,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.proto_forms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proto_forms.is_empty()
+    }
+
+    /// Modeled languages, sorted
+    pub fn languages(&self) -> Vec<&str> {
+        self.languages.keys().map(String::as_str).collect()
+    }
+
+    /// The `top_k` best proto-forms for `word` of `language` (every modeled
+    /// language when None), best first; ties keep proto-form order
+    pub fn lookup(
+        &self,
+        word: &str,
+        language: Option<&str>,
+        top_k: usize,
+    ) -> Result<Vec<ProtoMatch>, String> {
+        let languages: Vec<&str> = match language {
+            Some(language) if !self.languages.contains_key(language) => {
+                return Err(format!(
+                    "no sound laws or correspondences for '{}'",
+                    language
+                ));
+            }
+            Some(language) => vec![language],
+            None => self.languages(),
+        };
+        let mut candidates: Vec<(usize, &str, f64)> = languages
+            .iter()
+            .flat_map(|&language| {
+                (0..self.proto_forms.len())
+                    .into_par_iter()
+                    .map(move |p| (p, language, self.score(word, language, p)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        // Stable, so ties stay in (language, proto-form) order
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+        candidates.truncate(top_k);
+        Ok(candidates
+            .into_iter()
+            .map(|(p, language, score)| self.explain(word, language, p, score))
+            .collect())
+    }
+
+    /// `lookup` for many (word, language) queries, in parallel
+    pub fn batch_lookup(
+        &self,
+        queries: &[(String, Option<String>)],
+        top_k: usize,
+    ) -> Result<Vec<Vec<ProtoMatch>>, String> {
+        queries
+            .par_iter()
+            .map(|(word, language)| self.lookup(word, language.as_deref(), top_k))
+            .collect()
+    }
+
+    fn score(&self, word: &str, language: &str, proto: usize) -> f64 {
+        match &self.languages[language] {
+            LanguageModel::Rules(_) => {
+                phonetic_distance(&self.derived[language][proto].output, word)
+            }
+            LanguageModel::Correspondences(costs) => {
+                phonetic_distance_with_costs(&self.proto_forms[proto].1, word, costs)
+            }
+        }
+    }
+
+    fn explain(&self, word: &str, language: &str, proto: usize, score: f64) -> ProtoMatch {
+        let (proto_id, proto_form) = &self.proto_forms[proto];
+        let (reflex, derivation) = match &self.languages[language] {
+            LanguageModel::Rules(_) => {
+                let derivation = self.derived[language][proto].clone();
+                (Some(derivation.output.clone()), derivation)
+            }
+            LanguageModel::Correspondences(costs) => {
+                (None, correspondence_derivation(proto_form, word, costs))
+            }
+        };
+        ProtoMatch {
+            proto_id: proto_id.clone(),
+            proto_form: proto_form.clone(),
+            language: language.to_string(),
+            reflex,
+            score,
+            derivation,
+        }
+    }
+}
+
+/// Proto-form to word through the non-matching steps of their weighted
+/// alignment, applied left to right
+fn correspondence_derivation(proto_form: &str, word: &str, costs: &CostModel) -> Derivation {
+    let alignment = dtw_align_with_costs(proto_form, word, costs);
+    let ungapped = |sequence: &[String]| -> String {
+        sequence
+            .iter()
+            .filter(|s| *s != GAP)
+            .map(String::as_str)
+            .collect()
+    };
+    let null_for_gap = |segment: &str| if segment == GAP { NULL } else { segment }.to_string();
+    let mut before = proto_form.to_string();
+    let steps = (0..alignment.operations.len())
+        .filter(|&k| alignment.operations[k] != EditOp::Match)
+        .map(|k| {
+            let rule = format!(
+                "{} > {}",
+                null_for_gap(&alignment.sequence_a[k]),
+                null_for_gap(&alignment.sequence_b[k])
+            );
+            let after =
+                ungapped(&alignment.sequence_b[..=k]) + &ungapped(&alignment.sequence_a[k + 1..]);
+            let before = std::mem::replace(&mut before, after.clone());
+            DerivationStep {
+                rule,
+                before,
+                after,
+            }
+        })
+        .collect();
+    Derivation {
+        input: proto_form.to_string(),
+        output: word.to_string(),
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soundchange::parse_rules;
+    use std::collections::HashMap;
+
+    fn proto_forms() -> Vec<(String, String)> {
+        [
+            ("*pater", "pater"),
+            ("*treyes", "treyes"),
+            ("*kmtom", "kmtom"),
+        ]
+        .iter()
+        .map(|(id, form)| (id.to_string(), form.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_lookup_with_sound_laws() {
+        let grimm = parse_rules(&["p > f", "t > θ", "k > h", "e > a / _r"]).unwrap();
+        let languages = BTreeMap::from([("gmc".to_string(), LanguageModel::Rules(grimm))]);
+        let index = EtymologyIndex::new(proto_forms(), languages).unwrap();
+
+        let best = index.lookup("faθar", Some("gmc"), 1).unwrap().remove(0);
+        assert_eq!(best.proto_id, "*pater");
+        assert_eq!(best.reflex.as_deref(), Some("faθar"));
+        assert_eq!(best.score, 1.0);
+        let steps: Vec<(&str, &str)> = best
+            .derivation
+            .steps
+            .iter()
+            .map(|s| (s.rule.as_str(), s.after.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("p > f", "fater"),
+                ("t > θ", "faθer"),
+                ("e > a / _r", "faθar")
+            ]
+        );
+
+        let ranked = index.lookup("hmθom", None, 3).unwrap();
+        assert_eq!(ranked[0].proto_id, "*kmtom");
+        assert!(ranked.windows(2).all(|w| w[0].score >= w[1].score));
+        assert!(index.lookup("faθar", Some("lat"), 1).is_err());
+    }
+
+    #[test]
+    fn test_lookup_with_correspondences() {
+        let table: HashMap<(String, String), f64> = [(("p", "f"), 0.1), (("t", "θ"), 0.1)]
+            .iter()
+            .map(|((a, b), cost)| ((a.to_string(), b.to_string()), *cost))
+            .collect();
+        let costs = CostModel::from_table(&table, 1.0).unwrap();
+        let languages =
+            BTreeMap::from([("gmc".to_string(), LanguageModel::Correspondences(costs))]);
+        let index = EtymologyIndex::new(proto_forms(), languages).unwrap();
+
+        let matches = index
+            .batch_lookup(&[("faθer".to_string(), None)], 2)
+            .unwrap()
+            .remove(0);
+        assert_eq!(matches[0].proto_id, "*pater");
+        assert_eq!(matches[0].reflex, None);
+        let steps: Vec<(&str, &str, &str)> = matches[0]
+            .derivation
+            .steps
+            .iter()
+            .map(|s| (s.rule.as_str(), s.before.as_str(), s.after.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            [("p > f", "pater", "fater"), ("t > θ", "fater", "faθer")]
+        );
+        assert!(matches[0].score > matches[1].score);
+    }
+}
@@ -0,0 +1,164 @@
+//! ROC and precision–recall evaluation of similarity scores.
+//!
+//! Given a score and a gold cognate label per pair, `score_curves` sorts
+//! the scores once (in parallel) and sweeps the threshold from the highest
+//! score down, emitting one curve point per distinct score. The areas are
+//! computed on the full curves; `max_points` only thins what is returned,
+//! so comparing metrics or parameters on millions of labeled pairs doesn't
+//! mean shipping millions of points to Python.
+
+use rayon::prelude::*;
+
+/// ROC and precision–recall curves of one set of scores
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreCurves {
+    /// (false positive rate, true positive rate, threshold), from the
+    /// strictest threshold (+inf, nothing predicted) down
+    pub roc: Vec<(f64, f64, f64)>,
+    /// (recall, precision, threshold), starting at recall 0, precision 1
+    pub pr: Vec<(f64, f64, f64)>,
+    /// Area under the ROC curve (trapezoids)
+    pub roc_auc: f64,
+    /// Precision averaged over the recall steps (area under the PR step
+    /// curve, without the optimism of trapezoids)
+    pub average_precision: f64,
+    pub positives: usize,
+    pub negatives: usize,
+}
+
+/// Curves and areas of (score, is_cognate) samples, which need both labels;
+/// with `max_points`, each curve is thinned to that many evenly spaced
+/// points (the ends always kept)
+pub fn score_curves(
+    samples: &[(f64, bool)],
+    max_points: Option<usize>,
+) -> Result<ScoreCurves, String> {
+    if let Some(&(score, _)) = samples.iter().find(|s| s.0.is_nan()) {
+        return Err(format!("score {} is not a number", score));
+    }
+    if max_points.is_some_and(|m| m < 2) {
+        return Err("max_points must be at least 2".to_string());
+    }
+    let positives = samples.iter().filter(|s| s.1).count();
+    let negatives = samples.len() - positives;
+    if positives == 0 || negatives == 0 {
+        return Err("evaluation needs both cognate and non-cognate pairs".to_string());
+    }
+
+    let mut sorted = samples.to_vec();
+    sorted.par_sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+
+    let (p, n) = (positives as f64, negatives as f64);
+    let mut roc = vec![(0.0, 0.0, f64::INFINITY)];
+    let mut pr = vec![(0.0, 1.0, f64::INFINITY)];
+    let (mut roc_auc, mut average_precision) = (0.0, 0.0);
+    let (mut tp, mut fp) = (0usize, 0usize);
+    for (k, &(score, label)) in sorted.iter().enumerate() {
+        if label {
+            tp += 1;
+        } else {
+            fp += 1;
+        }
+        // One point per distinct score, once all its ties are counted
+        if sorted.get(k + 1).is_some_and(|next| next.0 == score) {
+            continue;
+        }
+        let (fpr, tpr) = (fp as f64 / n, tp as f64 / p);
+        let &(last_fpr, last_tpr, _) = roc.last().unwrap();
+        roc_auc += (fpr - last_fpr) * (tpr + last_tpr) / 2.0;
+        roc.push((fpr, tpr, score));
+
+        let precision = tp as f64 / (tp + fp) as f64;
+        let last_recall = pr.last().unwrap().0;
+        average_precision += (tpr - last_recall) * precision;
+        pr.push((tpr, precision, score));
+    }
+
+    if let Some(max_points) = max_points {
+        roc = thin(roc, max_points);
+        pr = thin(pr, max_points);
+    }
+    Ok(ScoreCurves {
+        roc,
+        pr,
+        roc_auc,
+        average_precision,
+        positives,
+        negatives,
+    })
+}
+
+/// `score_curves` for several score columns over the same labels, in
+/// parallel
+pub fn batch_score_curves(
+    scores: &[Vec<f64>],
+    labels: &[bool],
+    max_points: Option<usize>,
+) -> Result<Vec<ScoreCurves>, String> {
+    scores
+        .par_iter()
+        .map(|column| {
+            if column.len() != labels.len() {
+                return Err(format!(
+                    "{} scores for {} labels",
+                    column.len(),
+                    labels.len()
+                ));
+            }
+            let samples: Vec<(f64, bool)> =
+                column.iter().copied().zip(labels.iter().copied()).collect();
+            score_curves(&samples, max_points)
+        })
+        .collect()
+}
+
+/// At most `max_points` evenly spaced points of `curve`, ends included
+fn thin<T: Copy>(curve: Vec<T>, max_points: usize) -> Vec<T> {
+    if curve.len() <= max_points {
+        return curve;
+    }
+    let last = curve.len() - 1;
+    (0..max_points)
+        .map(|k| curve[k * last / (max_points - 1)])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_curves() {
+        // A positive tied with a negative at 0.7
+        let samples = [
+            (0.9, true),
+            (0.8, true),
+            (0.7, false),
+            (0.7, true),
+            (0.2, false),
+        ];
+        let curves = score_curves(&samples, None).unwrap();
+        assert_eq!((curves.positives, curves.negatives), (3, 2));
+        assert_eq!(curves.roc.len(), 5);
+        assert_eq!(curves.roc[3], (0.5, 1.0, 0.7));
+        assert_eq!(*curves.roc.last().unwrap(), (1.0, 1.0, 0.2));
+        // 5 of 6 positive-negative pairs ranked right, the tie at 0.7 half
+        assert!((curves.roc_auc - 5.5 / 6.0).abs() < 1e-12);
+        // Recall steps 1/3, 1/3, 1/3 at precision 1, 1, 3/4
+        assert!((curves.average_precision - (1.0 + 1.0 + 0.75) / 3.0).abs() < 1e-12);
+        assert_eq!(curves.pr[0], (0.0, 1.0, f64::INFINITY));
+
+        let perfect = score_curves(&[(0.9, true), (0.1, false)], None).unwrap();
+        assert_eq!((perfect.roc_auc, perfect.average_precision), (1.0, 1.0));
+
+        let thinned = score_curves(&samples, Some(3)).unwrap();
+        assert_eq!(thinned.roc.len(), 3);
+        assert_eq!(thinned.roc_auc, curves.roc_auc);
+        assert_eq!(thinned.roc[2], curves.roc[4]);
+
+        let batch = batch_score_curves(&[vec![0.1, 0.9], vec![0.9, 0.1]], &[true, false], None);
+        let aucs: Vec<f64> = batch.unwrap().iter().map(|c| c.roc_auc).collect();
+        assert_eq!(aucs, [0.0, 1.0]);
+        assert!(score_curves(&[(0.5, true)], None).is_err());
+    }
+}
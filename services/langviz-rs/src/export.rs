@@ -0,0 +1,531 @@
+//! GraphML and GEXF export for Gephi, Cytoscape and similar tools.
+//!
+//! Both formats carry the edge weight and provenance plus every node
+//! attribute set on the graph (language, gloss, frequency, ...), declared
+//! in sorted key order: as double attributes when every value of the key
+//! is numeric, as string attributes otherwise. PageRank can be added as a
+//! numeric node attribute.
+//!
+//! NetworkX node-link and adjacency data come out as JSON values in the
+//! exact shape `networkx.readwrite.json_graph` reads back: node attributes
+//! sit beside `"id"`, and parallel edges make the graph a multigraph with
+//! per-pair integer `"key"`s.
+//!
+//! Edge lists are tab-separated `source target weight provenance` rows
+//! under a header line, with an empty provenance column for untagged edges.
+
+use ahash::AHashMap;
+use petgraph::graph::{EdgeReference, NodeIndex};
+use petgraph::visit::EdgeRef;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::io::{self, BufRead};
+
+use crate::graph::CognateGraph;
+use crate::types::{AttrValue, ProvenanceEdge};
+
+/// Header row of exported edge lists
+const EDGE_LIST_HEADER: &str = "source\ttarget\tweight\tprovenance";
+
+/// PageRank settings used for the exported score
+pub(crate) const PAGERANK_DAMPING: f64 = 0.85;
+pub(crate) const PAGERANK_ITERATIONS: usize = 100;
+
+/// Escape text for use in XML content and attribute values
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Edge-list field with its separators (tab, newline) replaced by spaces
+fn edge_list_field(text: &str) -> std::borrow::Cow<'_, str> {
+    if text.contains(['\t', '\n', '\r']) {
+        text.replace(['\t', '\n', '\r'], " ").into()
+    } else {
+        text.into()
+    }
+}
+
+/// Read an edge list written by `CognateGraph::write_edge_list`; the header
+/// is optional and the provenance column may be left out
+pub fn read_edge_list<R: BufRead>(reader: R) -> Result<Vec<ProvenanceEdge>, String> {
+    let mut edges = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("failed to read edge list: {}", e))?;
+        if line.is_empty() || (number == 0 && line.starts_with("source\t")) {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        if !(3..=4).contains(&fields.len()) {
+            return Err(format!(
+                "edge list line {}: expected 3 or 4 tab-separated fields, got {}",
+                number + 1,
+                fields.len()
+            ));
+        }
+        let weight: f64 = fields[2].trim().parse().map_err(|_| {
+            format!("edge list line {}: invalid weight '{}'", number + 1, fields[2])
+        })?;
+        let provenance = fields.get(3).filter(|p| !p.is_empty()).map(|p| p.to_string());
+        edges.push((fields[0].to_string(), fields[1].to_string(), weight, provenance));
+    }
+    Ok(edges)
+}
+
+impl CognateGraph {
+    /// Node attribute keys in use, sorted, with their declared type
+    fn attribute_keys(&self) -> Vec<(&str, &'static str)> {
+        let keys: BTreeSet<&str> = self
+            .node_attrs
+            .values()
+            .flat_map(|attrs| attrs.keys().map(String::as_str))
+            .collect();
+        keys.into_iter()
+            .map(|key| {
+                let numeric = self
+                    .node_attrs
+                    .values()
+                    .filter_map(|attrs| attrs.get(key))
+                    .all(|value| matches!(value, AttrValue::Number(_)));
+                (key, if numeric { "double" } else { "string" })
+            })
+            .collect()
+    }
+
+    fn has_provenance(&self) -> bool {
+        self.graph
+            .edge_indices()
+            .any(|edge| self.edge_provenance(edge).is_some())
+    }
+
+    fn export_pagerank(&self, include_pagerank: bool) -> Option<Vec<f64>> {
+        include_pagerank.then(|| self.pagerank_scores(PAGERANK_DAMPING, PAGERANK_ITERATIONS))
+    }
+
+    /// Export as GraphML (undirected, node IDs are the word IDs)
+    pub fn to_graphml(&self, include_pagerank: bool) -> String {
+        let keys = self.attribute_keys();
+        let pagerank = self.export_pagerank(include_pagerank);
+        let has_provenance = self.has_provenance();
+        let mut out = String::new();
+
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (i, (key, kind)) in keys.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  <key id=\"n{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                i,
+                escape(key),
+                kind
+            );
+        }
+        if pagerank.is_some() {
+            out.push_str(
+                "  <key id=\"pagerank\" for=\"node\" attr.name=\"pagerank\" \
+                 attr.type=\"double\"/>\n",
+            );
+        }
+        out.push_str(
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+        );
+        if has_provenance {
+            out.push_str(
+                "  <key id=\"provenance\" for=\"edge\" attr.name=\"provenance\" \
+                 attr.type=\"string\"/>\n",
+            );
+        }
+        out.push_str("  <graph id=\"G\" edgedefault=\"undirected\">\n");
+
+        for idx in self.graph.node_indices() {
+            let _ = writeln!(out, "    <node id=\"{}\">", escape(&self.graph[idx]));
+            if let Some(attrs) = self.node_attrs.get(&idx) {
+                for (i, (key, _)) in keys.iter().enumerate() {
+                    if let Some(value) = attrs.get(*key) {
+                        let value = escape(&value.to_string());
+                        let _ = writeln!(out, "      <data key=\"n{}\">{}</data>", i, value);
+                    }
+                }
+            }
+            if let Some(scores) = &pagerank {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"pagerank\">{}</data>",
+                    scores[idx.index()]
+                );
+            }
+            out.push_str("    </node>\n");
+        }
+
+        for edge in self.graph.edge_references() {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+                edge.id().index(),
+                escape(&self.graph[edge.source()]),
+                escape(&self.graph[edge.target()])
+            );
+            let _ = writeln!(out, "      <data key=\"weight\">{}</data>", edge.weight());
+            if let Some(provenance) = self.edge_provenance(edge.id()) {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"provenance\">{}</data>",
+                    escape(provenance)
+                );
+            }
+            out.push_str("    </edge>\n");
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Export as GEXF 1.3 (static, undirected)
+    pub fn to_gexf(&self, include_pagerank: bool) -> String {
+        let keys = self.attribute_keys();
+        let pagerank = self.export_pagerank(include_pagerank);
+        let has_provenance = self.has_provenance();
+        let mut out = String::new();
+
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        out.push_str("  <graph mode=\"static\" defaultedgetype=\"undirected\">\n");
+
+        if !keys.is_empty() || pagerank.is_some() {
+            out.push_str("    <attributes class=\"node\">\n");
+            for (i, (key, kind)) in keys.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>",
+                    i,
+                    escape(key),
+                    kind
+                );
+            }
+            if pagerank.is_some() {
+                out.push_str(
+                    "      <attribute id=\"pagerank\" title=\"pagerank\" type=\"double\"/>\n",
+                );
+            }
+            out.push_str("    </attributes>\n");
+        }
+        if has_provenance {
+            out.push_str("    <attributes class=\"edge\">\n");
+            out.push_str(
+                "      <attribute id=\"provenance\" title=\"provenance\" type=\"string\"/>\n",
+            );
+            out.push_str("    </attributes>\n");
+        }
+
+        out.push_str("    <nodes>\n");
+        for idx in self.graph.node_indices() {
+            let id = escape(&self.graph[idx]);
+            let mut values = Vec::new();
+            if let Some(attrs) = self.node_attrs.get(&idx) {
+                for (i, (key, _)) in keys.iter().enumerate() {
+                    if let Some(value) = attrs.get(*key) {
+                        values.push((i.to_string(), escape(&value.to_string())));
+                    }
+                }
+            }
+            if let Some(scores) = &pagerank {
+                values.push(("pagerank".to_string(), scores[idx.index()].to_string()));
+            }
+
+            if values.is_empty() {
+                let _ = writeln!(out, "      <node id=\"{}\" label=\"{}\"/>", id, id);
+                continue;
+            }
+            let _ = writeln!(out, "      <node id=\"{}\" label=\"{}\">", id, id);
+            out.push_str("        <attvalues>\n");
+            for (key, value) in values {
+                let _ = writeln!(
+                    out,
+                    "          <attvalue for=\"{}\" value=\"{}\"/>",
+                    key, value
+                );
+            }
+            out.push_str("        </attvalues>\n      </node>\n");
+        }
+        out.push_str("    </nodes>\n");
+
+        out.push_str("    <edges>\n");
+        for edge in self.graph.edge_references() {
+            let _ = write!(
+                out,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\" weight=\"{}\"",
+                edge.id().index(),
+                escape(&self.graph[edge.source()]),
+                escape(&self.graph[edge.target()]),
+                edge.weight()
+            );
+            match self.edge_provenance(edge.id()) {
+                Some(provenance) => {
+                    let _ = writeln!(
+                        out,
+                        ">\n        <attvalues>\n          \
+                         <attvalue for=\"provenance\" value=\"{}\"/>\n        \
+                         </attvalues>\n      </edge>",
+                        escape(provenance)
+                    );
+                }
+                None => out.push_str("/>\n"),
+            }
+        }
+        out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+        out
+    }
+
+    /// NetworkX edge keys (0, 1, ... per node pair in insertion order), or
+    /// `None` when no pair has parallel edges
+    fn multigraph_keys(&self) -> Option<Vec<usize>> {
+        let mut seen: AHashMap<(NodeIndex, NodeIndex), usize> = AHashMap::new();
+        let keys: Vec<usize> = self
+            .graph
+            .edge_references()
+            .map(|edge| {
+                let (a, b) = (edge.source(), edge.target());
+                let count = seen.entry((a.min(b), a.max(b))).or_insert(0);
+                *count += 1;
+                *count - 1
+            })
+            .collect();
+        keys.iter().any(|&k| k > 0).then_some(keys)
+    }
+
+    /// Node dict: attributes flattened beside the ID
+    fn networkx_node(&self, idx: NodeIndex) -> Map<String, Value> {
+        let mut node = Map::new();
+        if let Some(attrs) = self.node_attrs.get(&idx) {
+            for (key, value) in attrs {
+                node.insert(key.clone(), json!(value));
+            }
+        }
+        node.insert("id".to_string(), json!(self.graph[idx]));
+        node
+    }
+
+    /// Edge data dict: weight, provenance when set, key for multigraphs
+    fn networkx_edge_data(
+        &self,
+        edge: EdgeReference<'_, f64>,
+        keys: Option<&Vec<usize>>,
+    ) -> Map<String, Value> {
+        let mut data = Map::new();
+        data.insert("weight".to_string(), json!(edge.weight()));
+        if let Some(provenance) = self.edge_provenance(edge.id()) {
+            data.insert("provenance".to_string(), json!(provenance));
+        }
+        if let Some(keys) = keys {
+            data.insert("key".to_string(), json!(keys[edge.id().index()]));
+        }
+        data
+    }
+
+    /// Stream the edges as a tab-separated edge list (see the module docs)
+    pub fn write_edge_list<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{}", EDGE_LIST_HEADER)?;
+        for edge in self.graph.edge_references() {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}",
+                edge_list_field(&self.graph[edge.source()]),
+                edge_list_field(&self.graph[edge.target()]),
+                edge.weight(),
+                edge_list_field(self.edge_provenance(edge.id()).unwrap_or("")),
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// `networkx.node_link_data` output; `link_key` names the edge list
+    /// ("links" before NetworkX 3.4's `edges=` switch, "edges" after)
+    pub fn to_node_link_data(&self, link_key: &str) -> Value {
+        let keys = self.multigraph_keys();
+        let nodes: Vec<Value> = self
+            .graph
+            .node_indices()
+            .map(|idx| Value::Object(self.networkx_node(idx)))
+            .collect();
+        let links: Vec<Value> = self
+            .graph
+            .edge_references()
+            .map(|edge| {
+                let mut link = self.networkx_edge_data(edge, keys.as_ref());
+                link.insert("source".to_string(), json!(self.graph[edge.source()]));
+                link.insert("target".to_string(), json!(self.graph[edge.target()]));
+                Value::Object(link)
+            })
+            .collect();
+
+        let mut data = Map::new();
+        data.insert("directed".to_string(), json!(false));
+        data.insert("multigraph".to_string(), json!(keys.is_some()));
+        data.insert("graph".to_string(), json!({}));
+        data.insert("nodes".to_string(), Value::Array(nodes));
+        data.insert(link_key.to_string(), Value::Array(links));
+        Value::Object(data)
+    }
+
+    /// `networkx.adjacency_data` output: per node, in node order, the list
+    /// of neighbor dicts (edge data plus the neighbor's `"id"`)
+    pub fn to_adjacency_data(&self) -> Value {
+        let keys = self.multigraph_keys();
+        let mut adjacency: Vec<Vec<Value>> = vec![Vec::new(); self.graph.node_count()];
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source(), edge.target());
+            let ends = if a == b { vec![(a, b)] } else { vec![(a, b), (b, a)] };
+            for (node, neighbor) in ends {
+                let mut entry = self.networkx_edge_data(edge, keys.as_ref());
+                entry.insert("id".to_string(), json!(self.graph[neighbor]));
+                adjacency[node.index()].push(Value::Object(entry));
+            }
+        }
+        let nodes: Vec<Value> = self
+            .graph
+            .node_indices()
+            .map(|idx| Value::Object(self.networkx_node(idx)))
+            .collect();
+
+        json!({
+            "directed": false,
+            "multigraph": keys.is_some(),
+            "graph": [],
+            "nodes": nodes,
+            "adjacency": adjacency,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn sample_graph() -> CognateGraph {
+        let mut graph = CognateGraph::new();
+        graph.add_edge("a".into(), "b&c".into(), 0.9);
+        graph.add_edge_with_provenance("a".into(), "d".into(), 0.5, Some("wiktionary"));
+
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "a".to_string(),
+            HashMap::from([
+                ("language".to_string(), "lat".to_string()),
+                ("gloss".to_string(), "\"father\"".to_string()),
+            ]),
+        );
+        graph.set_node_attributes(attrs);
+        graph.set_node_attributes(HashMap::from([(
+            "b&c".to_string(),
+            HashMap::from([("rank".to_string(), 12.0)]),
+        )]));
+        graph
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        let xml = sample_graph().to_graphml(true);
+        assert!(xml.contains("<key id=\"n0\" for=\"node\" attr.name=\"gloss\""));
+        assert!(xml.contains("<key id=\"n1\" for=\"node\" attr.name=\"language\""));
+        assert!(xml.contains("<node id=\"b&amp;c\">"));
+        assert!(xml.contains("<data key=\"n0\">&quot;father&quot;</data>"));
+        assert!(xml.contains(
+            "<key id=\"n2\" for=\"node\" attr.name=\"rank\" attr.type=\"double\"/>"
+        ));
+        assert!(xml.contains("<data key=\"n2\">12</data>"));
+        assert!(xml.contains("<data key=\"weight\">0.9</data>"));
+        assert!(xml.contains("<data key=\"provenance\">wiktionary</data>"));
+        assert_eq!(xml.matches("<data key=\"pagerank\">").count(), 3);
+        assert_eq!(xml.matches("<edge ").count(), 2);
+
+        let plain = CognateGraph::from_edges(Vec::new(), 0.0).to_graphml(false);
+        assert!(!plain.contains("pagerank") && !plain.contains("provenance"));
+    }
+
+    #[test]
+    fn test_to_gexf() {
+        let xml = sample_graph().to_gexf(false);
+        assert!(xml.contains("<attribute id=\"1\" title=\"language\" type=\"string\"/>"));
+        assert!(xml.contains("<attvalue for=\"1\" value=\"lat\"/>"));
+        assert!(xml.contains("<attribute id=\"2\" title=\"rank\" type=\"double\"/>"));
+        assert!(xml.contains("<node id=\"d\" label=\"d\"/>"));
+        assert!(xml.contains("source=\"a\" target=\"b&amp;c\" weight=\"0.9\"/>"));
+        assert!(xml.contains("<attvalue for=\"provenance\" value=\"wiktionary\"/>"));
+        assert!(!xml.contains("pagerank"));
+        assert_eq!(xml.matches("<edge ").count(), 2);
+    }
+
+    #[test]
+    fn test_networkx_data() {
+        let graph = sample_graph();
+        let data = graph.to_node_link_data("links");
+        assert_eq!(data["multigraph"], false);
+        assert_eq!(
+            data["nodes"][0],
+            json!({"id": "a", "language": "lat", "gloss": "\"father\""})
+        );
+        assert_eq!(data["nodes"][1], json!({"id": "b&c", "rank": 12.0}));
+        assert_eq!(
+            data["links"][1],
+            json!({"source": "a", "target": "d", "weight": 0.5, "provenance": "wiktionary"})
+        );
+
+        let adjacency = graph.to_adjacency_data();
+        assert_eq!(adjacency["graph"], json!([]));
+        assert_eq!(adjacency["adjacency"][0].as_array().unwrap().len(), 2);
+        assert_eq!(
+            adjacency["adjacency"][2],
+            json!([{"id": "a", "weight": 0.5, "provenance": "wiktionary"}])
+        );
+
+        let mut multi = CognateGraph::new();
+        multi.add_edge("a".into(), "b".into(), 0.9);
+        multi.add_edge("b".into(), "a".into(), 0.7);
+        let data = multi.to_node_link_data("edges");
+        assert_eq!(data["multigraph"], true);
+        assert_eq!(data["edges"][1]["key"], 1);
+        assert!(data.get("links").is_none());
+    }
+
+    #[test]
+    fn test_edge_list_and_json_round_trip() {
+        let mut graph = sample_graph();
+        graph.add_edge("lone\tid".into(), "e".into(), 0.25);
+
+        let mut edge_list = Vec::new();
+        graph.write_edge_list(&mut edge_list).unwrap();
+        let edges = read_edge_list(edge_list.as_slice()).unwrap();
+        let expected: Vec<ProvenanceEdge> = graph
+            .provenance_edge_list()
+            .into_iter()
+            .map(|(s, t, w, p)| (s.replace('\t', " "), t, w, p))
+            .collect();
+        assert_eq!(edges, expected);
+        assert_eq!(edges[1].3.as_deref(), Some("wiktionary"));
+        assert!(read_edge_list("a\tb\tnot-a-number\n".as_bytes()).is_err());
+
+        let mut json = Vec::new();
+        graph.write_json(&mut json).unwrap();
+        let restored = CognateGraph::read_json(json.as_slice()).unwrap();
+        assert_eq!(restored.to_json(), graph.to_json());
+
+        // Unversioned documents from before format versions still load
+        let legacy = r#"{"nodes":[{"id":"a"},{"id":"b"}],
+            "edges":[{"source":"a","target":"b","weight":0.5}]}"#;
+        let legacy = CognateGraph::read_json(legacy.as_bytes()).unwrap();
+        assert_eq!(legacy.stats().num_edges, 1);
+        assert!(CognateGraph::read_json(r#"{"format_version":99}"#.as_bytes()).is_err());
+    }
+}
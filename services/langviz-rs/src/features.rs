@@ -0,0 +1,373 @@
+//! Built-in panphon-style articulatory feature table.
+//!
+//! Base symbols come from `data/ipa_features.csv` (embedded at compile time,
+//! values `+`/`-`/`0`). Segments produced by `segment_ipa` are resolved by
+//! looking up the base symbol and applying diacritics and modifier letters;
+//! tied affricates take the features of their release with `delrel` set.
+//!
+//! Batch jobs precompute a `FeatureDistanceTable` over the segment inventory
+//! of their forms once, then index into it instead of resolving and
+//! comparing feature vectors for every aligned cell.
+
+use ndarray::Array2;
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::phonetic::{feature_weighted_distance, segment_ipa, unit_indel_distance};
+use crate::types::IPASegment;
+
+/// Number of articulatory features per segment
+pub const NUM_FEATURES: usize = 24;
+
+/// Feature names in vector order
+pub const FEATURE_NAMES: [&str; NUM_FEATURES] = [
+    "syl", "son", "cons", "cont", "delrel", "lat", "nas", "strid", "voi", "sg", "cg", "ant",
+    "cor", "distr", "lab", "hi", "lo", "back", "round", "velaric", "tense", "long", "hitone",
+    "hireg",
+];
+
+const FEATURE_CSV: &str = include_str!("../data/ipa_features.csv");
+
+type FeatureVector = [i8; NUM_FEATURES];
+
+pub(crate) fn feature_index(name: &str) -> usize {
+    FEATURE_NAMES
+        .iter()
+        .position(|&f| f == name)
+        .expect("unknown feature name")
+}
+
+fn feature_table() -> &'static HashMap<char, FeatureVector> {
+    static TABLE: OnceLock<HashMap<char, FeatureVector>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        FEATURE_CSV
+            .lines()
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split(',');
+                let symbol = fields
+                    .next()
+                    .and_then(|s| s.chars().next())
+                    .expect("feature table row without symbol");
+                let mut features = [0i8; NUM_FEATURES];
+                for (slot, value) in features.iter_mut().zip(fields) {
+                    *slot = match value.trim() {
+                        "+" => 1,
+                        "-" => -1,
+                        _ => 0,
+                    };
+                }
+                (symbol, features)
+            })
+            .collect()
+    })
+}
+
+/// Single-character affricate ligatures as (stop, release)
+fn ligature_parts(c: char) -> Option<(char, char)> {
+    match c {
+        'ʦ' => Some(('t', 's')),
+        'ʣ' => Some(('d', 'z')),
+        'ʧ' => Some(('t', 'ʃ')),
+        'ʤ' => Some(('d', 'ʒ')),
+        'ʨ' => Some(('t', 'ɕ')),
+        'ʥ' => Some(('d', 'ʑ')),
+        _ => None,
+    }
+}
+
+/// Feature changes for a diacritic or modifier letter
+fn modifier_changes(c: char) -> &'static [(&'static str, i8)] {
+    match c {
+        'ʰ' | '\u{0324}' => &[("sg", 1)],
+        'ʱ' => &[("sg", 1), ("voi", 1)],
+        'ʼ' | '\u{0330}' => &[("cg", 1)],
+        'ː' | 'ˑ' => &[("long", 1)],
+        '\u{0303}' => &[("nas", 1)],
+        'ʷ' => &[("lab", 1), ("round", 1)],
+        'ʲ' => &[("hi", 1), ("back", -1)],
+        'ˠ' => &[("hi", 1), ("back", 1)],
+        'ˤ' => &[("lo", 1), ("back", 1)],
+        '\u{0325}' | '\u{030A}' => &[("voi", -1)],
+        '\u{032C}' => &[("voi", 1)],
+        '\u{0329}' | '\u{030D}' => &[("syl", 1)],
+        '\u{032F}' => &[("syl", -1)],
+        '\u{032A}' => &[("ant", 1), ("distr", 1)],
+        // Tone: register (hireg) and level within the register (hitone)
+        '˥' | '\u{030B}' => &[("hireg", 1), ("hitone", 1)],
+        '˦' | '\u{0301}' => &[("hireg", 1), ("hitone", -1)],
+        '˧' | '\u{0304}' => &[("hireg", 0), ("hitone", 0)],
+        '˨' | '\u{0300}' => &[("hireg", -1), ("hitone", 1)],
+        '˩' | '\u{030F}' => &[("hireg", -1), ("hitone", -1)],
+        _ => &[],
+    }
+}
+
+/// Feature vector for one segment, or `None` when no symbol in it is known
+pub fn segment_features(segment: &str) -> Option<FeatureVector> {
+    let table = feature_table();
+    let mut features: Option<FeatureVector> = None;
+    let mut tied = false;
+
+    // Precomposed letters (é, ã) are looked up through their decomposition
+    for c in segment.nfd() {
+        if let Some(base) = table.get(&c) {
+            features = Some(match features {
+                // Second symbol of a tie: affricate with the release's features
+                Some(_) if tied => {
+                    let mut affricate = *base;
+                    affricate[feature_index("delrel")] = 1;
+                    affricate[feature_index("cont")] = -1;
+                    affricate
+                }
+                Some(current) => current,
+                None => *base,
+            });
+            tied = false;
+        } else if let Some((_, release)) = ligature_parts(c) {
+            let mut affricate = *table.get(&release)?;
+            affricate[feature_index("delrel")] = 1;
+            affricate[feature_index("cont")] = -1;
+            features = Some(affricate);
+        } else if c == '\u{0361}' || c == '\u{035C}' {
+            tied = true;
+        } else if let Some(current) = features.as_mut() {
+            for &(name, value) in modifier_changes(c) {
+                current[feature_index(name)] = value;
+            }
+        } else if !modifier_changes(c).is_empty() {
+            // Standalone tone letters
+            let mut tone = [0i8; NUM_FEATURES];
+            for &(name, value) in modifier_changes(c) {
+                tone[feature_index(name)] = value;
+            }
+            features = Some(tone);
+        }
+    }
+
+    features
+}
+
+/// Number of features on which two vectors differ
+fn differing_features(a: &FeatureVector, b: &FeatureVector) -> usize {
+    a.iter().zip(b).filter(|(x, y)| x != y).count()
+}
+
+/// Feature-weighted substitution cost: the share of differing features, 0
+/// for identical segments and 1 when either segment is unknown
+pub fn feature_cost(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 0.0;
+    }
+    match (segment_features(a), segment_features(b)) {
+        (Some(fa), Some(fb)) => differing_features(&fa, &fb) as f64 / NUM_FEATURES as f64,
+        _ => 1.0,
+    }
+}
+
+/// Segment × segment feature comparisons over a segment inventory,
+/// computed once
+#[derive(Debug, Clone, Default)]
+pub struct FeatureDistanceTable {
+    /// Inventory, sorted
+    segments: Vec<String>,
+    index: HashMap<String, usize>,
+    /// Whether the feature table knows each segment
+    known: Vec<bool>,
+    /// Differing feature counts, row-major; unknown segments count as
+    /// all-zero vectors
+    differing: Vec<u8>,
+}
+
+impl FeatureDistanceTable {
+    /// Table over the given segments (deduplicated)
+    pub fn new(segments: impl IntoIterator<Item = String>) -> Self {
+        let mut segments: Vec<String> = segments.into_iter().collect();
+        segments.sort_unstable();
+        segments.dedup();
+        let vectors: Vec<Option<FeatureVector>> =
+            segments.par_iter().map(|s| segment_features(s)).collect();
+        let n = segments.len();
+        let differing = (0..n * n)
+            .into_par_iter()
+            .map(|cell| {
+                let a = vectors[cell / n].unwrap_or([0; NUM_FEATURES]);
+                let b = vectors[cell % n].unwrap_or([0; NUM_FEATURES]);
+                differing_features(&a, &b) as u8
+            })
+            .collect();
+        let index = segments
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.clone(), i))
+            .collect();
+        Self {
+            known: vectors.iter().map(Option::is_some).collect(),
+            segments,
+            index,
+            differing,
+        }
+    }
+
+    /// Table over every segment of `forms` (IPA strings)
+    pub fn from_forms<S: AsRef<str> + Sync>(forms: &[S]) -> Self {
+        let inventory: BTreeSet<String> = forms
+            .par_iter()
+            .flat_map_iter(|form| segment_ipa(form.as_ref()))
+            .collect();
+        Self::new(inventory)
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Inventory in row order
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    pub fn index_of(&self, segment: &str) -> Option<usize> {
+        self.index.get(segment).copied()
+    }
+
+    /// `feature_cost` between the segments at rows `i` and `j`
+    pub fn cost_at(&self, i: usize, j: usize) -> f64 {
+        if i == j {
+            0.0
+        } else if !(self.known[i] && self.known[j]) {
+            1.0
+        } else {
+            self.differing[i * self.len() + j] as f64 / NUM_FEATURES as f64
+        }
+    }
+
+    /// `feature_cost`, looked up for segments in the inventory
+    pub fn cost(&self, a: &str, b: &str) -> f64 {
+        match (self.index_of(a), self.index_of(b)) {
+            (Some(i), Some(j)) => self.cost_at(i, j),
+            _ => feature_cost(a, b),
+        }
+    }
+
+    /// Costs of all inventory pairs, rows and columns in `segments` order
+    pub fn matrix(&self) -> Array2<f64> {
+        let n = self.len();
+        Array2::from_shape_fn((n, n), |(i, j)| self.cost_at(i, j))
+    }
+
+    /// `feature_distance` between two IPA strings; strings with segments
+    /// outside the inventory are compared without the table
+    pub fn distance(&self, ipa_a: &str, ipa_b: &str) -> f64 {
+        let rows = |ipa: &str| -> Option<Vec<usize>> {
+            segment_ipa(ipa).iter().map(|s| self.index_of(s)).collect()
+        };
+        let (Some(a), Some(b)) = (rows(ipa_a), rows(ipa_b)) else {
+            return feature_distance(ipa_a, ipa_b);
+        };
+        let n = self.len();
+        unit_indel_distance(a.len(), b.len(), |i, j| {
+            if a[i] == b[j] {
+                0.0
+            } else {
+                self.differing[a[i] * n + b[j]] as f64 / NUM_FEATURES as f64
+            }
+        })
+    }
+}
+
+/// Segment an IPA string and attach feature vectors (zeros when unknown)
+pub fn ipa_feature_segments(ipa: &str) -> Vec<IPASegment> {
+    segment_ipa(ipa)
+        .into_iter()
+        .map(|segment| {
+            let features = segment_features(&segment).unwrap_or([0; NUM_FEATURES]);
+            IPASegment::new(segment, features)
+        })
+        .collect()
+}
+
+/// Feature-weighted edit distance between two IPA strings (0 = identical)
+pub fn feature_distance(ipa_a: &str, ipa_b: &str) -> f64 {
+    feature_weighted_distance(&ipa_feature_segments(ipa_a), &ipa_feature_segments(ipa_b))
+}
+
+/// Batch feature-weighted distances for multiple pairs (parallelized),
+/// over a feature distance table for the pairs' inventory
+pub fn batch_feature_distance(pairs: &[(String, String)]) -> Vec<f64> {
+    let forms: Vec<&str> = pairs
+        .iter()
+        .flat_map(|(a, b)| [a.as_str(), b.as_str()])
+        .collect();
+    let table = FeatureDistanceTable::from_forms(&forms);
+    pairs
+        .par_iter()
+        .map(|(a, b)| table.distance(a, b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_features() {
+        let p = segment_features("p").unwrap();
+        let b = segment_features("b").unwrap();
+        let diff: Vec<&str> = (0..NUM_FEATURES)
+            .filter(|&i| p[i] != b[i])
+            .map(|i| FEATURE_NAMES[i])
+            .collect();
+        assert_eq!(diff, vec!["voi"]);
+
+        let aspirated = segment_features("pʰ").unwrap();
+        assert_eq!(aspirated[feature_index("sg")], 1);
+        assert_eq!(segment_features("t͡ʃ"), segment_features("ʧ"));
+        assert_eq!(segment_features("t͡ʃ").unwrap()[feature_index("delrel")], 1);
+        assert_eq!(segment_features("ã").unwrap()[feature_index("nas")], 1);
+        assert!(segment_features("☃").is_none());
+    }
+
+    #[test]
+    fn test_feature_distance() {
+        assert_eq!(feature_distance("pater", "pater"), 0.0);
+        // Voicing differs in one feature; p -> a differs in many more
+        let voicing = feature_distance("pa", "ba");
+        let vowel = feature_distance("pa", "aa");
+        assert!(voicing > 0.0 && voicing < vowel);
+        assert_eq!(batch_feature_distance(&[("pa".into(), "ba".into())]), vec![voicing]);
+    }
+
+    #[test]
+    fn test_feature_distance_table() {
+        use crate::phonetic::{nw_align, AlignParams, SubstitutionCost};
+
+        let forms = ["pater", "t͡ʃiŋ", "☃a", "bʰrater"];
+        let table = FeatureDistanceTable::from_forms(&forms);
+        assert!(table.segments().contains(&"t͡ʃ".to_string()));
+        for a in table.segments() {
+            for b in table.segments() {
+                assert_eq!(table.cost(a, b), feature_cost(a, b), "{} / {}", a, b);
+            }
+        }
+        // Segments outside the inventory fall back to direct comparison
+        assert_eq!(table.cost("p", "k"), feature_cost("p", "k"));
+        for (a, b) in [("pater", "bʰrater"), ("☃a", "pa"), ("t͡ʃiŋ", "")] {
+            assert_eq!(table.distance(a, b), feature_distance(a, b));
+        }
+        assert_eq!(table.matrix().dim(), (table.len(), table.len()));
+
+        let params = |substitution| AlignParams {
+            substitution,
+            ..AlignParams::default()
+        };
+        let direct = nw_align("pater", "bʰrater", &params(SubstitutionCost::FeatureWeighted));
+        let precomputed = SubstitutionCost::FeatureWeighted.precomputed(&forms);
+        let indexed = nw_align("pater", "bʰrater", &params(precomputed));
+        assert_eq!(direct.sequence_a, indexed.sequence_a);
+        assert_eq!(direct.cost, indexed.cost);
+    }
+}
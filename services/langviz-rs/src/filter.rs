@@ -0,0 +1,350 @@
+//! Predicate DSL for filtering cognate graphs without leaving Rust.
+//!
+//! Expressions combine comparisons with `and`, `or`, `not` and parentheses:
+//!
+//! ```text
+//! language in {lat, grc, san} and degree >= 2
+//! weight >= 0.8 and not source == "eng_father"
+//! ```
+//!
+//! Field names are resolved by the caller (node filters see `id`, `degree`
+//! and node attributes; edge filters see `source`, `target` and `weight`).
+
+/// Value of a field as seen by a predicate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue<'a> {
+    Number(f64),
+    Text(&'a str),
+}
+
+impl FieldValue<'_> {
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            FieldValue::Number(n) => Some(*n),
+            FieldValue::Text(t) => t.trim().parse().ok(),
+        }
+    }
+
+    fn as_text(&self) -> String {
+        match self {
+            FieldValue::Number(n) => n.to_string(),
+            FieldValue::Text(t) => t.to_string(),
+        }
+    }
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn holds(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            CompareOp::Eq => ordering == Equal,
+            CompareOp::Ne => ordering != Equal,
+            CompareOp::Lt => ordering == Less,
+            CompareOp::Le => ordering != Greater,
+            CompareOp::Gt => ordering == Greater,
+            CompareOp::Ge => ordering != Less,
+        }
+    }
+}
+
+/// Parsed filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Compare {
+        field: String,
+        op: CompareOp,
+        value: String,
+    },
+    In {
+        field: String,
+        values: Vec<String>,
+    },
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// Parse a filter expression
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let predicate = parser.parse_or()?;
+
+        if parser.pos < parser.tokens.len() {
+            return Err(format!(
+                "unexpected token {:?} in filter expression",
+                parser.tokens[parser.pos]
+            ));
+        }
+
+        Ok(predicate)
+    }
+
+    /// Evaluate against a field resolver (unknown fields never match)
+    pub fn evaluate<'a, F>(&self, lookup: &F) -> bool
+    where
+        F: Fn(&str) -> Option<FieldValue<'a>>,
+    {
+        match self {
+            Predicate::Compare { field, op, value } => match lookup(field) {
+                Some(actual) => compare(&actual, *op, value),
+                None => false,
+            },
+            Predicate::In { field, values } => match lookup(field) {
+                Some(actual) => values.iter().any(|v| compare(&actual, CompareOp::Eq, v)),
+                None => false,
+            },
+            Predicate::And(a, b) => a.evaluate(lookup) && b.evaluate(lookup),
+            Predicate::Or(a, b) => a.evaluate(lookup) || b.evaluate(lookup),
+            Predicate::Not(inner) => !inner.evaluate(lookup),
+        }
+    }
+}
+
+/// Compare numerically when both sides are numbers, textually otherwise
+fn compare(actual: &FieldValue, op: CompareOp, expected: &str) -> bool {
+    if let (Some(a), Ok(b)) = (actual.as_number(), expected.trim().parse::<f64>()) {
+        return match a.partial_cmp(&b) {
+            Some(ordering) => op.holds(ordering),
+            None => false,
+        };
+    }
+
+    op.holds(actual.as_text().as_str().cmp(expected))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '{' => {
+                tokens.push(Token::LBrace);
+                i += 1;
+            }
+            '}' => {
+                tokens.push(Token::RBrace);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| "unterminated string in filter expression".to_string())?;
+                tokens.push(Token::Quoted(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            '>' | '<' | '=' | '!' => {
+                let next = chars.get(i + 1).copied();
+                let (op, width) = match (c, next) {
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('=', Some('=')) => (CompareOp::Eq, 2),
+                    ('!', Some('=')) => (CompareOp::Ne, 2),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('=', _) => (CompareOp::Eq, 1),
+                    _ => return Err("expected '!=' in filter expression".to_string()),
+                };
+                tokens.push(Token::Op(op));
+                i += width;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !"(){},\"'<>=!".contains(chars[i])
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.tokens.get(self.pos) {
+            Some(token) if *token == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            other => Err(format!("expected {:?}, found {:?}", expected, other)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Predicate, String> {
+        let mut left = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Predicate::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Predicate, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            left = Predicate::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Predicate, String> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Predicate::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.tokens.get(self.pos) == Some(&Token::LParen) {
+            self.pos += 1;
+            let inner = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(inner);
+        }
+
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Predicate, String> {
+        let field = self.parse_value()?;
+
+        if self.peek_keyword("in") {
+            self.pos += 1;
+            self.expect(Token::LBrace)?;
+            let mut values = Vec::new();
+            if self.tokens.get(self.pos) != Some(&Token::RBrace) {
+                loop {
+                    values.push(self.parse_value()?);
+                    if self.tokens.get(self.pos) == Some(&Token::Comma) {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            self.expect(Token::RBrace)?;
+            return Ok(Predicate::In { field, values });
+        }
+
+        let op = match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => *op,
+            other => {
+                return Err(format!(
+                    "expected comparison after '{}', found {:?}",
+                    field, other
+                ))
+            }
+        };
+        self.pos += 1;
+
+        let value = self.parse_value()?;
+        Ok(Predicate::Compare { field, op, value })
+    }
+
+    fn parse_value(&mut self) -> Result<String, String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => {
+                let value = w.clone();
+                self.pos += 1;
+                Ok(value)
+            }
+            other => Err(format!("expected field or value, found {:?}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_evaluate() {
+        let predicate = Predicate::parse("language in {lat, grc} and degree >= 2").unwrap();
+
+        let lookup = |lang: &'static str, degree: f64| {
+            move |field: &str| match field {
+                "language" => Some(FieldValue::Text(lang)),
+                "degree" => Some(FieldValue::Number(degree)),
+                _ => None,
+            }
+        };
+
+        assert!(predicate.evaluate(&lookup("lat", 3.0)));
+        assert!(!predicate.evaluate(&lookup("lat", 1.0)));
+        assert!(!predicate.evaluate(&lookup("eng", 5.0)));
+    }
+
+    #[test]
+    fn test_not_and_parentheses() {
+        let predicate = Predicate::parse("not (weight < 0.5 or source = 'x')").unwrap();
+        let lookup = |field: &str| match field {
+            "weight" => Some(FieldValue::Number(0.9)),
+            "source" => Some(FieldValue::Text("y")),
+            _ => None,
+        };
+        assert!(predicate.evaluate(&lookup));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Predicate::parse("degree >=").is_err());
+        assert!(Predicate::parse("language in {lat").is_err());
+        assert!(Predicate::parse("weight 0.5").is_err());
+    }
+}
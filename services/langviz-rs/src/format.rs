@@ -0,0 +1,159 @@
+//! Format versions of serialized artifacts.
+//!
+//! Every file LangViz writes records the version of its format: graph and
+//! classifier JSON in a top-level `format_version` field, sessions in
+//! `version`, binary matrix files in their header (and result stores in
+//! `PRAGMA user_version`). Documents written before versioning have no
+//! field and read as version 1.
+//!
+//! Readers accept every version up to the current one. Older JSON documents
+//! are upgraded one version at a time by the artifact's migrations before
+//! being deserialized; files from a newer release are rejected with both
+//! versions in the message.
+
+use serde_json::Value;
+
+use crate::sparse::SPARSE_FORMAT_VERSION;
+
+/// Current graph JSON (`to_json` / `write_json`) format version
+pub const GRAPH_JSON_VERSION: u32 = 1;
+
+/// Current classifier model JSON format version
+pub const CLASSIFIER_FORMAT_VERSION: u32 = 1;
+
+/// Upgrade a document from one version to the next, in place
+pub type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// A versioned JSON artifact
+pub struct JsonFormat {
+    /// Name used in error messages ("session", "graph JSON", ...)
+    pub name: &'static str,
+    /// Top-level field holding the version
+    pub version_key: &'static str,
+    pub current: u32,
+    /// `migrations[i]` upgrades version `i + 1` to `i + 2`
+    pub migrations: &'static [Migration],
+}
+
+pub const GRAPH_JSON: JsonFormat = JsonFormat {
+    name: "graph JSON",
+    version_key: "format_version",
+    current: GRAPH_JSON_VERSION,
+    migrations: &[],
+};
+
+pub const CLASSIFIER_JSON: JsonFormat = JsonFormat {
+    name: "classifier",
+    version_key: "format_version",
+    current: CLASSIFIER_FORMAT_VERSION,
+    migrations: &[],
+};
+
+/// Reject version 0 and versions newer than `current`
+pub fn check_version(name: &str, version: u32, current: u32) -> Result<(), String> {
+    if version == 0 {
+        Err(format!("invalid {} format version 0", name))
+    } else if version > current {
+        Err(format!(
+            "{} format version {} is newer than supported version {}",
+            name, version, current
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+impl JsonFormat {
+    /// Upgrade `document` to the current version, returning the version it
+    /// was written with
+    pub fn upgrade(&self, document: &mut Value) -> Result<u32, String> {
+        debug_assert_eq!(self.migrations.len() + 1, self.current as usize);
+        let object = document
+            .as_object_mut()
+            .ok_or_else(|| format!("invalid {} file: expected a JSON object", self.name))?;
+        let version = match object.get(self.version_key) {
+            None => 1,
+            Some(value) => value
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| format!("invalid {} format version {}", self.name, value))?,
+        };
+        check_version(self.name, version, self.current)?;
+
+        for migration in &self.migrations[version as usize - 1..] {
+            migration(document)?;
+        }
+        document[self.version_key] = self.current.into();
+        Ok(version)
+    }
+
+    /// `document` with the current version stamped in
+    pub fn stamp(&self, mut document: Value) -> Value {
+        if let Some(object) = document.as_object_mut() {
+            object.insert(self.version_key.to_string(), self.current.into());
+        }
+        document
+    }
+}
+
+/// (artifact, current format version) for every versioned artifact
+pub fn format_versions() -> Vec<(&'static str, u32)> {
+    #[cfg_attr(not(feature = "sqlite"), allow(unused_mut))]
+    let mut versions = vec![
+        ("graph_json", GRAPH_JSON_VERSION),
+        ("classifier", CLASSIFIER_FORMAT_VERSION),
+        ("session", crate::session::SESSION_FORMAT_VERSION),
+        ("sparse_matrix", SPARSE_FORMAT_VERSION),
+        ("mapped_matrix", crate::mapped::MAPPED_FORMAT_VERSION),
+        ("checkpoint", crate::checkpoint::CHECKPOINT_FORMAT_VERSION),
+    ];
+    #[cfg(feature = "sqlite")]
+    versions.push(("result_store", crate::store::STORE_SCHEMA_VERSION as u32));
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename_weight(document: &mut Value) -> Result<(), String> {
+        let weight = document.as_object_mut().unwrap().remove("w");
+        document["weight"] = weight.ok_or("missing w")?;
+        Ok(())
+    }
+
+    fn scale_weight(document: &mut Value) -> Result<(), String> {
+        let weight = document["weight"].as_f64().ok_or("missing weight")?;
+        document["weight"] = (weight * 100.0).into();
+        Ok(())
+    }
+
+    #[test]
+    fn test_upgrade_runs_pending_migrations() {
+        let format = JsonFormat {
+            name: "test",
+            version_key: "format_version",
+            current: 3,
+            migrations: &[rename_weight, scale_weight],
+        };
+
+        let mut legacy = serde_json::json!({"w": 0.5});
+        assert_eq!(format.upgrade(&mut legacy), Ok(1));
+        assert_eq!(
+            legacy,
+            serde_json::json!({"weight": 50.0, "format_version": 3})
+        );
+
+        let mut v2 = serde_json::json!({"weight": 0.5, "format_version": 2});
+        assert_eq!(format.upgrade(&mut v2), Ok(2));
+        assert_eq!(v2["weight"], 50.0);
+
+        let mut newer = serde_json::json!({"format_version": 4});
+        let error = format.upgrade(&mut newer).unwrap_err();
+        assert_eq!(
+            error,
+            "test format version 4 is newer than supported version 3"
+        );
+        assert!(format.upgrade(&mut serde_json::json!([1, 2])).is_err());
+    }
+}
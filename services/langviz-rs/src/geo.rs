@@ -0,0 +1,259 @@
+//! Geographic views of language networks.
+//!
+//! Languages are placed by the coordinates of the metadata registry, given
+//! as (latitude, longitude) in degrees per language code. GeoJSON positions
+//! are written in the standard longitude-first order.
+//!
+//! Isogloss detection connects geographic neighbors (the Gabriel graph:
+//! two languages are neighbors when no third lies inside the circle over
+//! their connecting segment) and scores every neighbor pair by how far its
+//! linguistic distance stands above the other neighbor pairs (a z-score).
+//! Sharp jumps are returned as boundary segments: the perpendicular
+//! bisector of the pair, as long as the pair is far apart. Geometry uses an
+//! equirectangular projection around the mean latitude, which is adequate
+//! at dialect and family scale.
+
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::calibration::pair_key;
+
+/// (latitude, longitude) per language
+pub type Coordinates = HashMap<String, (f64, f64)>;
+
+fn position(&(latitude, longitude): &(f64, f64)) -> Value {
+    json!([longitude, latitude])
+}
+
+/// GeoJSON FeatureCollection with one LineString per (language, language,
+/// weight) link at or above `min_weight`, carrying `source`, `target` and
+/// `weight` properties. Links with a language lacking coordinates are
+/// skipped. With `include_points`, every linked language is added as a
+/// Point feature with a `language` property.
+pub fn links_to_geojson(
+    links: &[(String, String, f64)],
+    coordinates: &Coordinates,
+    min_weight: f64,
+    include_points: bool,
+) -> String {
+    let mut features = Vec::new();
+    let mut linked: BTreeSet<&str> = BTreeSet::new();
+
+    for (source, target, weight) in links {
+        if *weight < min_weight {
+            continue;
+        }
+        let (Some(from), Some(to)) = (coordinates.get(source), coordinates.get(target)) else {
+            continue;
+        };
+        linked.insert(source);
+        linked.insert(target);
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": [position(from), position(to)],
+            },
+            "properties": {
+                "source": source,
+                "target": target,
+                "weight": weight,
+            },
+        }));
+    }
+
+    if include_points {
+        for language in linked {
+            features.push(json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": position(&coordinates[language]),
+                },
+                "properties": { "language": language },
+            }));
+        }
+    }
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+    .to_string()
+}
+
+/// Candidate isogloss boundary between two neighboring languages
+#[derive(Debug, Clone, PartialEq)]
+pub struct IsoglossSegment {
+    pub language_a: String,
+    pub language_b: String,
+    /// Linguistic distance across the boundary
+    pub distance: f64,
+    /// Z-score of that distance among all neighbor pairs
+    pub score: f64,
+    /// Segment endpoints as (latitude, longitude)
+    pub start: (f64, f64),
+    pub end: (f64, f64),
+}
+
+/// Boundaries across geographic neighbor pairs whose (language, language,
+/// distance) score reaches `min_z`, strongest first. Languages without
+/// coordinates and neighbor pairs without a distance are ignored.
+pub fn isogloss_boundaries(
+    coordinates: &Coordinates,
+    distances: &[(String, String, f64)],
+    min_z: f64,
+) -> Vec<IsoglossSegment> {
+    let distance_of: HashMap<(String, String), f64> = distances
+        .iter()
+        .map(|(a, b, d)| (pair_key(a, b), *d))
+        .collect();
+
+    let mut languages: Vec<&String> = coordinates.keys().collect();
+    languages.sort();
+    if languages.is_empty() {
+        return Vec::new();
+    }
+    let mean_latitude =
+        languages.iter().map(|l| coordinates[*l].0).sum::<f64>() / languages.len() as f64;
+    let scale = mean_latitude.to_radians().cos();
+    let points: Vec<(f64, f64)> = languages
+        .iter()
+        .map(|l| {
+            let (latitude, longitude) = coordinates[*l];
+            (longitude * scale, latitude)
+        })
+        .collect();
+
+    // Gabriel graph neighbor pairs that have a linguistic distance
+    let mut neighbors: Vec<(usize, usize, f64)> = Vec::new();
+    for i in 0..points.len() {
+        for j in (i + 1)..points.len() {
+            let Some(&distance) = distance_of.get(&pair_key(languages[i], languages[j])) else {
+                continue;
+            };
+            let center = (
+                (points[i].0 + points[j].0) / 2.0,
+                (points[i].1 + points[j].1) / 2.0,
+            );
+            let radius_sq = squared_distance(points[i], points[j]) / 4.0;
+            let blocked = (0..points.len())
+                .filter(|&k| k != i && k != j)
+                .any(|k| squared_distance(points[k], center) < radius_sq);
+            if !blocked {
+                neighbors.push((i, j, distance));
+            }
+        }
+    }
+    if neighbors.is_empty() {
+        return Vec::new();
+    }
+
+    let count = neighbors.len() as f64;
+    let mean = neighbors.iter().map(|n| n.2).sum::<f64>() / count;
+    let std_dev = (neighbors.iter().map(|n| (n.2 - mean).powi(2)).sum::<f64>() / count).sqrt();
+
+    let mut segments: Vec<IsoglossSegment> = neighbors
+        .into_iter()
+        .filter_map(|(i, j, distance)| {
+            let score = if std_dev > 0.0 {
+                (distance - mean) / std_dev
+            } else {
+                0.0
+            };
+            if score < min_z {
+                return None;
+            }
+            let (a, b) = (points[i], points[j]);
+            let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+            // Perpendicular of a→b, with the pair's length
+            let half = ((a.1 - b.1) / 2.0, (b.0 - a.0) / 2.0);
+            let unproject = |(x, y): (f64, f64)| (y, x / scale);
+            Some(IsoglossSegment {
+                language_a: languages[i].clone(),
+                language_b: languages[j].clone(),
+                distance,
+                score,
+                start: unproject((middle.0 - half.0, middle.1 - half.1)),
+                end: unproject((middle.0 + half.0, middle.1 + half.1)),
+            })
+        })
+        .collect();
+    segments.sort_by(|a, b| b.score.total_cmp(&a.score));
+    segments
+}
+
+fn squared_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinates() -> Coordinates {
+        HashMap::from([
+            ("eng".to_string(), (51.5, -0.1)),
+            ("deu".to_string(), (52.5, 13.4)),
+            ("fra".to_string(), (48.9, 2.35)),
+        ])
+    }
+
+    #[test]
+    fn test_links_to_geojson() {
+        let links = vec![
+            ("eng".to_string(), "deu".to_string(), 0.8),
+            ("eng".to_string(), "fra".to_string(), 0.2),
+            ("deu".to_string(), "xxx".to_string(), 0.9),
+        ];
+        let geojson = links_to_geojson(&links, &coordinates(), 0.5, true);
+        let value: Value = serde_json::from_str(&geojson).unwrap();
+        assert_eq!(value["type"], "FeatureCollection");
+
+        let features = value["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0]["geometry"]["type"], "LineString");
+        assert_eq!(
+            features[0]["geometry"]["coordinates"],
+            json!([[-0.1, 51.5], [13.4, 52.5]])
+        );
+        assert_eq!(features[0]["properties"]["weight"], 0.8);
+        assert_eq!(features[1]["properties"]["language"], "deu");
+        assert_eq!(features[2]["geometry"]["coordinates"], json!([-0.1, 51.5]));
+
+        let lines_only = links_to_geojson(&links, &coordinates(), 0.0, false);
+        let value: Value = serde_json::from_str(&lines_only).unwrap();
+        assert_eq!(value["features"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_isogloss_boundaries() {
+        // Two dialect areas along the equator, split between x2 and y1
+        let coordinates: Coordinates = [("x1", 0.0), ("x2", 1.0), ("y1", 2.0), ("y2", 3.0)]
+            .iter()
+            .map(|(l, lon)| (l.to_string(), (0.0, *lon)))
+            .collect();
+        let mut distances = Vec::new();
+        for (a, b) in [("x1", "x2"), ("y1", "y2"), ("x1", "y1"), ("x1", "y2")] {
+            distances.push((a.to_string(), b.to_string(), 0.8));
+        }
+        distances[0].2 = 0.1;
+        distances[1].2 = 0.1;
+        distances.push(("y1".to_string(), "x2".to_string(), 0.7));
+
+        let boundaries = isogloss_boundaries(&coordinates, &distances, 1.0);
+        assert_eq!(boundaries.len(), 1);
+        let boundary = &boundaries[0];
+        assert_eq!(
+            (boundary.language_a.as_str(), boundary.language_b.as_str()),
+            ("x2", "y1")
+        );
+        assert_eq!(boundary.distance, 0.7);
+        // Vertical segment through longitude 1.5
+        assert!((boundary.start.1 - 1.5).abs() < 1e-9 && (boundary.end.1 - 1.5).abs() < 1e-9);
+        assert!((boundary.start.0 - boundary.end.0).abs() > 0.99);
+
+        assert!(isogloss_boundaries(&coordinates, &distances, 5.0).is_empty());
+        assert!(isogloss_boundaries(&HashMap::new(), &distances, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,307 @@
+//! wgpu compute backend for the `kernels` batch distances (built with the
+//! `gpu` feature).
+//!
+//! One shader invocation handles one pair. The edit-distance shader keeps
+//! its two DP rows in fixed private arrays, so it only takes pairs of up to
+//! `MAX_SEGMENTS` segments (`kernels` keeps longer ones on the CPU); cosine
+//! similarity runs in f32. Pairs go to the device in chunks, so buffers
+//! stay within the adapter's binding limits. The device is set up once, on
+//! first use, and shared by every call.
+
+use bytemuck::Pod;
+use ndarray::ArrayView2;
+use std::sync::{mpsc, OnceLock};
+use wgpu::util::DeviceExt;
+
+/// Longest form (in segments) the edit-distance shader handles
+pub const MAX_SEGMENTS: usize = 64;
+const WORKGROUP: u32 = 64;
+/// Pairs per dispatch
+const CHUNK: usize = 1 << 18;
+
+const EDIT_SHADER: &str = r#"
+struct Params { band: u32, count: u32, pad0: u32, pad1: u32 }
+
+@group(0) @binding(0) var<storage, read> codes: array<u32>;
+// (start_a, len_a, start_b, len_b) per pair
+@group(0) @binding(1) var<storage, read> spans: array<vec4<u32>>;
+@group(0) @binding(2) var<storage, read_write> distances: array<u32>;
+@group(0) @binding(3) var<uniform> params: Params;
+
+const UNREACHED: u32 = 0x3fffffffu;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= params.count) {
+        return;
+    }
+    let span = spans[index];
+    let len_a = span.y;
+    let len_b = span.w;
+    let band = max(params.band, max(len_a, len_b) - min(len_a, len_b));
+
+    var prev: array<u32, 65>;
+    var curr: array<u32, 65>;
+    for (var j = 0u; j <= len_b; j++) {
+        prev[j] = select(UNREACHED, j, j <= band);
+    }
+    for (var i = 1u; i <= len_a; i++) {
+        for (var j = 0u; j <= len_b; j++) {
+            curr[j] = UNREACHED;
+        }
+        if (i <= band) {
+            curr[0] = i;
+        }
+        let first = select(1u, i - band, i > band);
+        let last = min(i + band, len_b);
+        let a = codes[span.x + i - 1u];
+        for (var j = first; j <= last; j++) {
+            let substitute = prev[j - 1u] + select(1u, 0u, a == codes[span.z + j - 1u]);
+            curr[j] = min(substitute, min(prev[j] + 1u, curr[j - 1u] + 1u));
+        }
+        for (var j = 0u; j <= len_b; j++) {
+            prev[j] = curr[j];
+        }
+    }
+    distances[index] = prev[len_b];
+}
+"#;
+
+const COSINE_SHADER: &str = r#"
+struct Params { dims: u32, count: u32, pad0: u32, pad1: u32 }
+
+@group(0) @binding(0) var<storage, read> embeddings: array<f32>;
+@group(0) @binding(1) var<storage, read> pairs: array<vec2<u32>>;
+@group(0) @binding(2) var<storage, read_write> similarities: array<f32>;
+@group(0) @binding(3) var<uniform> params: Params;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= params.count) {
+        return;
+    }
+    let a = pairs[index].x * params.dims;
+    let b = pairs[index].y * params.dims;
+    var product = 0.0;
+    var norm_a = 0.0;
+    var norm_b = 0.0;
+    for (var k = 0u; k < params.dims; k++) {
+        let x = embeddings[a + k];
+        let y = embeddings[b + k];
+        product += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    let norms = norm_a * norm_b;
+    similarities[index] = select(0.0, product / sqrt(norms), norms > 0.0);
+}
+"#;
+
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    edit: wgpu::ComputePipeline,
+    cosine: wgpu::ComputePipeline,
+    /// Largest storage buffer binding, in bytes
+    max_binding: u64,
+}
+
+static GPU: OnceLock<Result<Gpu, String>> = OnceLock::new();
+
+fn gpu() -> Result<&'static Gpu, String> {
+    GPU.get_or_init(Gpu::new).as_ref().map_err(Clone::clone)
+}
+
+/// Whether a GPU adapter was found and set up
+pub fn available() -> bool {
+    gpu().is_ok()
+}
+
+impl Gpu {
+    fn new() -> Result<Self, String> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .map_err(|e| format!("no GPU adapter: {}", e))?;
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("langviz"),
+            required_limits: limits.clone(),
+            ..Default::default()
+        }))
+        .map_err(|e| format!("GPU device request failed: {}", e))?;
+
+        let pipeline = |label: &str, source: &str| {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: None,
+                module: &module,
+                entry_point: Some("main"),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        Ok(Self {
+            edit: pipeline("edit_distance", EDIT_SHADER),
+            cosine: pipeline("cosine", COSINE_SHADER),
+            max_binding: u64::from(limits.max_storage_buffer_binding_size),
+            device,
+            queue,
+        })
+    }
+
+    /// Run `pipeline` over `count` pairs with two input buffers and the
+    /// uniform `params`, reading back one `T` per pair
+    fn dispatch<T: Pod>(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        inputs: [&[u8]; 2],
+        params: [u32; 4],
+        count: usize,
+    ) -> Result<Vec<T>, String> {
+        let device = &self.device;
+        if let Some(input) = inputs
+            .iter()
+            .find(|input| input.len() as u64 > self.max_binding)
+        {
+            return Err(format!(
+                "{} bytes exceed the GPU's {}-byte storage binding limit",
+                input.len(),
+                self.max_binding
+            ));
+        }
+        let storage = |contents: &[u8]| {
+            // Zero-sized bindings are invalid
+            let padding = [0u8; 4];
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: if contents.is_empty() {
+                    &padding
+                } else {
+                    contents
+                },
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        };
+        let (first, second) = (storage(inputs[0]), storage(inputs[1]));
+        let uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let size = (count * std::mem::size_of::<T>()) as u64;
+        let output = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let entries: Vec<wgpu::BindGroupEntry> = [&first, &second, &output, &uniform]
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((count as u32).div_ceil(WORKGROUP), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|e| format!("GPU poll failed: {}", e))?;
+        receiver
+            .recv()
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("GPU readback failed: {}", e))?;
+        let values = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        readback.unmap();
+        Ok(values)
+    }
+}
+
+/// Banded unit-cost edit distances of code pairs of at most
+/// `MAX_SEGMENTS` segments each
+pub fn banded_edit_distances(
+    pairs: &[(Vec<u32>, Vec<u32>)],
+    band: Option<usize>,
+) -> Result<Vec<u32>, String> {
+    let gpu = gpu()?;
+    // A band of MAX_SEGMENTS already covers every cell
+    let band = band.unwrap_or(MAX_SEGMENTS).min(MAX_SEGMENTS) as u32;
+    let mut distances = Vec::with_capacity(pairs.len());
+    for chunk in pairs.chunks(CHUNK) {
+        let mut codes: Vec<u32> = Vec::new();
+        let mut spans: Vec<[u32; 4]> = Vec::with_capacity(chunk.len());
+        for (a, b) in chunk {
+            spans.push([codes.len() as u32, a.len() as u32, 0, b.len() as u32]);
+            codes.extend(a);
+            spans.last_mut().expect("just pushed")[2] = codes.len() as u32;
+            codes.extend(b);
+        }
+        distances.extend(gpu.dispatch::<u32>(
+            &gpu.edit,
+            [bytemuck::cast_slice(&codes), bytemuck::cast_slice(&spans)],
+            [band, chunk.len() as u32, 0, 0],
+            chunk.len(),
+        )?);
+    }
+    Ok(distances)
+}
+
+/// Cosine similarities of embedding row pairs, computed in f32
+pub fn cosine_similarities(
+    embeddings: ArrayView2<f64>,
+    pairs: &[(usize, usize)],
+) -> Result<Vec<f64>, String> {
+    let gpu = gpu()?;
+    let values: Vec<f32> = embeddings.iter().map(|&value| value as f32).collect();
+    let dims = embeddings.ncols() as u32;
+    let mut similarities = Vec::with_capacity(pairs.len());
+    for chunk in pairs.chunks(CHUNK) {
+        let indices: Vec<[u32; 2]> = chunk.iter().map(|&(i, j)| [i as u32, j as u32]).collect();
+        let chunk_similarities = gpu.dispatch::<f32>(
+            &gpu.cosine,
+            [
+                bytemuck::cast_slice(&values),
+                bytemuck::cast_slice(&indices),
+            ],
+            [dims, chunk.len() as u32, 0, 0],
+            chunk.len(),
+        )?;
+        similarities.extend(chunk_similarities.into_iter().map(f64::from));
+    }
+    Ok(similarities)
+}
@@ -3,18 +3,90 @@
 //! Replaces NetworkX operations with optimized Rust implementations using petgraph.
 
 use ahash::AHashMap;
-use petgraph::graph::{Graph, NodeIndex, UnGraph};
-use petgraph::algo::{connected_components, dijkstra};
+use petgraph::graph::{EdgeIndex, EdgeReference, Graph, NodeIndex, UnGraph};
+use petgraph::algo::dijkstra;
 use petgraph::visit::EdgeRef;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::sync::OnceLock;
 
-use crate::types::{CognateSet, SimilarityEdge};
+use crate::centrality::sum_over_sources;
+use crate::cluster::UnionFind;
+use crate::error::LangVizError;
+use crate::filter::{FieldValue, Predicate};
+use crate::format::{GRAPH_JSON, GRAPH_JSON_VERSION};
+use crate::types::{AttrValue, CognateSet, ProvenanceEdge, SimilarityEdge, WeightedEdge};
 
 /// High-performance graph builder for cognate networks
+#[derive(Clone)]
 pub struct CognateGraph {
-    graph: UnGraph<String, f64>,
-    node_map: AHashMap<String, NodeIndex>,
+    pub(crate) graph: UnGraph<String, f64>,
+    pub(crate) node_map: AHashMap<String, NodeIndex>,
+    /// Optional per-node metadata (language, gloss, frequency, ...)
+    pub(crate) node_attrs: AHashMap<NodeIndex, BTreeMap<String, AttrValue>>,
+    /// Data source tag per edge
+    pub(crate) provenance: ProvenanceTable,
+    /// Connected components, built on first query and kept current by edge
+    /// additions; edge removal drops it for a lazy rebuild
+    pub(crate) components: OnceLock<UnionFind>,
+    /// Statistics and sorted node strengths, built on first query and
+    /// dropped by any change to nodes, edges or weights
+    pub(crate) stats_cache: OnceLock<StatsCache>,
+}
+
+/// `GraphStats` of a graph with the node strengths behind them, kept so
+/// what-if queries can patch the strength summary for a few nodes
+#[derive(Debug, Clone)]
+pub(crate) struct StatsCache {
+    pub(crate) stats: GraphStats,
+    /// Strength of each node, by node index
+    pub(crate) strengths: Vec<f64>,
+    /// All strengths, ascending
+    pub(crate) sorted: Vec<f64>,
+    /// Position of each node's strength in `sorted`, by node index
+    pub(crate) rank: Vec<usize>,
+}
+
+/// Interned provenance labels, one slot per edge (indexed by edge index)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProvenanceTable {
+    labels: Vec<String>,
+    label_index: AHashMap<String, u32>,
+    per_edge: Vec<Option<u32>>,
+}
+
+impl ProvenanceTable {
+    fn push(&mut self, label: Option<&str>) {
+        let id = label.map(|l| match self.label_index.get(l) {
+            Some(&id) => id,
+            None => {
+                let id = self.labels.len() as u32;
+                self.labels.push(l.to_string());
+                self.label_index.insert(l.to_string(), id);
+                id
+            }
+        });
+        self.per_edge.push(id);
+    }
+
+    /// Mirror `Graph::remove_edge`, which moves the last edge into the gap
+    fn swap_remove(&mut self, edge_index: usize) {
+        self.per_edge.swap_remove(edge_index);
+    }
+
+    /// Provenance of the edge at `edge_index`
+    pub(crate) fn get(&self, edge_index: usize) -> Option<&str> {
+        self.per_edge
+            .get(edge_index)
+            .copied()
+            .flatten()
+            .map(|id| self.labels[id as usize].as_str())
+    }
 }
 
 impl CognateGraph {
@@ -23,6 +95,10 @@ impl CognateGraph {
         Self {
             graph: UnGraph::new_undirected(),
             node_map: AHashMap::new(),
+            node_attrs: AHashMap::new(),
+            provenance: ProvenanceTable::default(),
+            components: OnceLock::new(),
+            stats_cache: OnceLock::new(),
         }
     }
 
@@ -38,7 +114,12 @@ impl CognateGraph {
 
         // Add nodes and edges
         for edge in filtered {
-            graph_builder.add_edge(edge.source, edge.target, edge.weight.0);
+            graph_builder.add_edge_with_provenance(
+                edge.source,
+                edge.target,
+                edge.weight.0,
+                edge.provenance.as_deref(),
+            );
         }
 
         graph_builder
@@ -46,186 +127,627 @@ impl CognateGraph {
 
     /// Add edge to graph (creates nodes if needed)
     pub fn add_edge(&mut self, source: String, target: String, weight: f64) {
+        self.add_edge_with_provenance(source, target, weight, None);
+    }
+
+    /// Add edge tagged with the data source that produced it
+    pub fn add_edge_with_provenance(
+        &mut self,
+        source: String,
+        target: String,
+        weight: f64,
+        provenance: Option<&str>,
+    ) {
         let source_idx = self.get_or_create_node(source);
         let target_idx = self.get_or_create_node(target);
         self.graph.add_edge(source_idx, target_idx, weight);
+        self.provenance.push(provenance);
+        if let Some(components) = self.components.get_mut() {
+            components.union(source_idx.index(), target_idx.index());
+        }
+        self.stats_cache.take();
+    }
+
+    /// Add (source, target, weight) edges, creating nodes as needed
+    pub fn add_edges(&mut self, edges: Vec<SimilarityEdge>) {
+        for edge in edges {
+            self.add_edge_with_provenance(
+                edge.source,
+                edge.target,
+                edge.weight.0,
+                edge.provenance.as_deref(),
+            );
+        }
+    }
+
+    /// Remove every edge lighter than `threshold` (nodes are kept) and
+    /// return how many were removed
+    pub fn remove_edges_below(&mut self, threshold: f64) -> usize {
+        let mut removed = 0;
+        // Walking backwards, the edge swapped into a freed slot was already visited
+        for index in (0..self.graph.edge_count()).rev() {
+            let edge = EdgeIndex::new(index);
+            if self.graph[edge] < threshold {
+                self.graph.remove_edge(edge);
+                self.provenance.swap_remove(index);
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.components = OnceLock::new();
+            self.stats_cache.take();
+        }
+        removed
+    }
+
+    /// Set the weight of every edge between `source` and `target`; returns
+    /// false if there is none
+    pub fn update_edge_weight(&mut self, source: &str, target: &str, weight: f64) -> bool {
+        let (Some(&a), Some(&b)) = (self.node_map.get(source), self.node_map.get(target)) else {
+            return false;
+        };
+        let edges: Vec<EdgeIndex> = self.graph.edges_connecting(a, b).map(|e| e.id()).collect();
+        for &edge in &edges {
+            self.graph[edge] = weight;
+        }
+        if !edges.is_empty() {
+            self.stats_cache.take();
+        }
+        !edges.is_empty()
+    }
+
+    /// Provenance tag of an edge, if any
+    pub fn edge_provenance(&self, edge: EdgeIndex) -> Option<&str> {
+        self.provenance.get(edge.index())
+    }
+
+    /// Edges tagged with any of the given sources
+    pub fn edges_by_provenance(&self, sources: &[String]) -> Vec<ProvenanceEdge> {
+        self.provenance_edge_list()
+            .into_iter()
+            .filter(|(_, _, _, p)| p.as_ref().is_some_and(|p| sources.contains(p)))
+            .collect()
+    }
+
+    /// Number of edges per provenance tag (untagged edges under `None`)
+    pub fn provenance_counts(&self) -> BTreeMap<Option<String>, usize> {
+        let mut counts = BTreeMap::new();
+        for edge in self.graph.edge_references() {
+            let label = self.edge_provenance(edge.id()).map(str::to_string);
+            *counts.entry(label).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Export edges with their provenance tags
+    pub fn provenance_edge_list(&self) -> Vec<ProvenanceEdge> {
+        self.graph
+            .edge_references()
+            .map(|edge| {
+                (
+                    self.graph[edge.source()].clone(),
+                    self.graph[edge.target()].clone(),
+                    *edge.weight(),
+                    self.edge_provenance(edge.id()).map(str::to_string),
+                )
+            })
+            .collect()
     }
 
     /// Get or create node index
-    fn get_or_create_node(&mut self, id: String) -> NodeIndex {
+    pub(crate) fn get_or_create_node(&mut self, id: String) -> NodeIndex {
         if let Some(&idx) = self.node_map.get(&id) {
             idx
         } else {
             let idx = self.graph.add_node(id.clone());
             self.node_map.insert(id, idx);
+            if let Some(components) = self.components.get_mut() {
+                components.push();
+            }
+            self.stats_cache.take();
             idx
         }
     }
 
-    /// Find connected components (cognate sets)
-    pub fn find_cognate_sets(&self) -> Vec<CognateSet> {
-        let _num_components = connected_components(&self.graph);
-        let mut components: HashMap<usize, Vec<String>> = HashMap::new();
+    /// Compact integer ID of a node: its position in `node_ids`. Nodes are
+    /// never removed, so indices stay fixed for the graph's lifetime and
+    /// survive a `write_json` / `read_json` round trip.
+    pub fn node_index(&self, id: &str) -> Option<usize> {
+        self.node_map.get(id).map(|idx| idx.index())
+    }
+
+    /// String ID of the node at `index`
+    pub fn node_id(&self, index: usize) -> Option<&str> {
+        self.graph
+            .node_weight(NodeIndex::new(index))
+            .map(String::as_str)
+    }
 
-        // Use Tarjan's algorithm implicitly through petgraph
-        let mut component_map = vec![0; self.graph.node_count()];
-        let mut current_component = 0;
+    /// Every node ID, in index order
+    pub fn node_ids(&self) -> Vec<String> {
+        self.graph.node_weights().cloned().collect()
+    }
 
-        for node_idx in self.graph.node_indices() {
-            if component_map[node_idx.index()] == 0 {
-                current_component += 1;
-                self.mark_component(node_idx, current_component, &mut component_map);
+    /// Edges as (source index, target index, weight), for payloads that
+    /// send `node_ids` once instead of repeating string IDs
+    pub fn indexed_edge_list(&self) -> Vec<(u32, u32, f64)> {
+        self.graph
+            .edge_references()
+            .map(|edge| {
+                (
+                    edge.source().index() as u32,
+                    edge.target().index() as u32,
+                    *edge.weight(),
+                )
+            })
+            .collect()
+    }
+
+    /// Attach metadata to existing nodes (unknown IDs are ignored)
+    pub fn set_node_attributes<V: Into<AttrValue>>(
+        &mut self,
+        attributes: HashMap<String, HashMap<String, V>>,
+    ) {
+        for (id, attrs) in attributes {
+            if let Some(&idx) = self.node_map.get(&id) {
+                self.node_attrs
+                    .entry(idx)
+                    .or_default()
+                    .extend(attrs.into_iter().map(|(k, v)| (k, v.into())));
             }
         }
+    }
 
-        // Group nodes by component
-        for (idx, node) in self.graph.node_indices().zip(self.graph.node_weights()) {
-            let comp_id = component_map[idx.index()];
-            components
-                .entry(comp_id)
-                .or_insert_with(Vec::new)
-                .push(node.clone());
-        }
+    /// Look up a single node attribute
+    pub fn node_attribute(&self, id: &str, key: &str) -> Option<&AttrValue> {
+        let idx = self.node_map.get(id)?;
+        self.node_attrs.get(idx)?.get(key)
+    }
 
-        // Convert to CognateSet structs
-        components
-            .into_iter()
-            .map(|(id, members)| CognateSet::new(id, members))
-            .collect()
+    /// All attributes of a node, sorted by key (empty for unknown IDs or
+    /// bare nodes)
+    pub fn node_attributes(&self, id: &str) -> BTreeMap<String, AttrValue> {
+        self.node_map
+            .get(id)
+            .and_then(|idx| self.node_attrs.get(idx))
+            .cloned()
+            .unwrap_or_default()
     }
 
-    /// Mark connected component using DFS
-    fn mark_component(&self, start: NodeIndex, component_id: usize, component_map: &mut [usize]) {
-        let mut stack = vec![start];
-        while let Some(node) = stack.pop() {
-            if component_map[node.index()] != 0 {
-                continue;
+    /// Induced subgraph of nodes matching `node_filter`, keeping only edges
+    /// that match `edge_filter`. Node degree is taken from this graph.
+    pub fn filter(
+        &self,
+        node_filter: Option<&Predicate>,
+        edge_filter: Option<&Predicate>,
+    ) -> CognateGraph {
+        let keep: Vec<bool> = self
+            .graph
+            .node_indices()
+            .map(|idx| match node_filter {
+                Some(predicate) => {
+                    let degree = self.graph.neighbors(idx).count() as f64;
+                    let attrs = self.node_attrs.get(&idx);
+                    predicate.evaluate(&|field: &str| match field {
+                        "id" => Some(FieldValue::Text(self.graph[idx].as_str())),
+                        "degree" => Some(FieldValue::Number(degree)),
+                        key => attrs?.get(key).map(|v| match v {
+                            AttrValue::Text(text) => FieldValue::Text(text),
+                            AttrValue::Number(number) => FieldValue::Number(*number),
+                        }),
+                    })
+                }
+                None => true,
+            })
+            .collect();
+
+        self.subgraph(&keep, |edge| match edge_filter {
+            Some(predicate) => predicate.evaluate(&|field: &str| match field {
+                "source" => Some(FieldValue::Text(self.graph[edge.source()].as_str())),
+                "target" => Some(FieldValue::Text(self.graph[edge.target()].as_str())),
+                "weight" => Some(FieldValue::Number(*edge.weight())),
+                "provenance" => self.edge_provenance(edge.id()).map(FieldValue::Text),
+                _ => None,
+            }),
+            None => true,
+        })
+    }
+
+    /// Subgraph over nodes flagged in `keep` (indexed by node index) with
+    /// the edges between them that satisfy `keep_edge`; attributes are copied
+    pub(crate) fn subgraph<F>(&self, keep: &[bool], keep_edge: F) -> CognateGraph
+    where
+        F: Fn(EdgeReference<'_, f64>) -> bool,
+    {
+        let mut subgraph = CognateGraph::new();
+
+        for idx in self.graph.node_indices().filter(|idx| keep[idx.index()]) {
+            let new_idx = subgraph.get_or_create_node(self.graph[idx].clone());
+            if let Some(attrs) = self.node_attrs.get(&idx) {
+                subgraph.node_attrs.insert(new_idx, attrs.clone());
             }
-            component_map[node.index()] = component_id;
+        }
 
-            for neighbor in self.graph.neighbors(node) {
-                if component_map[neighbor.index()] == 0 {
-                    stack.push(neighbor);
-                }
+        for edge in self.graph.edge_references() {
+            if keep[edge.source().index()] && keep[edge.target().index()] && keep_edge(edge) {
+                subgraph.add_edge_with_provenance(
+                    self.graph[edge.source()].clone(),
+                    self.graph[edge.target()].clone(),
+                    *edge.weight(),
+                    self.edge_provenance(edge.id()),
+                );
             }
         }
+
+        subgraph
     }
 
-    /// Detect communities using Louvain algorithm (simplified)
-    pub fn detect_communities(&self, resolution: f64) -> Vec<Vec<String>> {
-        // Simplified Louvain: use modularity-based greedy clustering
-        let mut communities: Vec<Vec<NodeIndex>> = self
-            .graph
-            .node_indices()
-            .map(|idx| vec![idx])
-            .collect();
+    /// The `n` highest-weighted edges for every language pair, where each
+    /// node's language is read from the `language_key` attribute. Pairs are
+    /// keyed with the languages in sorted order; unlabeled nodes are skipped.
+    pub fn top_edges_per_language_pair(
+        &self,
+        language_key: &str,
+        n: usize,
+    ) -> BTreeMap<(String, String), Vec<WeightedEdge>> {
+        let mut groups: BTreeMap<(String, String), Vec<WeightedEdge>> = BTreeMap::new();
 
-        let mut improved = true;
-        let mut iteration = 0;
-        const MAX_ITERATIONS: usize = 10;
-
-        while improved && iteration < MAX_ITERATIONS {
-            improved = false;
-            iteration += 1;
-
-            // Try moving each node to neighbor's community
-            for node in self.graph.node_indices() {
-                let current_community = self.find_node_community(node, &communities);
-                let mut best_community = current_community;
-                let mut best_modularity = self.compute_modularity(&communities, resolution);
-
-                // Check each neighbor's community
-                for neighbor in self.graph.neighbors(node) {
-                    let neighbor_community = self.find_node_community(neighbor, &communities);
-                    if neighbor_community != current_community {
-                        // Try moving node to neighbor's community
-                        let new_communities =
-                            self.move_node(node, current_community, neighbor_community, &communities);
-                        let new_modularity = self.compute_modularity(&new_communities, resolution);
-
-                        if new_modularity > best_modularity {
-                            best_modularity = new_modularity;
-                            best_community = neighbor_community;
-                            improved = true;
-                        }
+        for edge in self.graph.edge_references() {
+            let lang_of = |idx: NodeIndex| {
+                self.node_attrs
+                    .get(&idx)
+                    .and_then(|attrs| attrs.get(language_key))
+                    .and_then(AttrValue::as_text)
+            };
+            let (Some(lang_a), Some(lang_b)) = (lang_of(edge.source()), lang_of(edge.target()))
+            else {
+                continue;
+            };
+
+            let key = if lang_a <= lang_b {
+                (lang_a.to_string(), lang_b.to_string())
+            } else {
+                (lang_b.to_string(), lang_a.to_string())
+            };
+
+            groups.entry(key).or_default().push((
+                self.graph[edge.source()].clone(),
+                self.graph[edge.target()].clone(),
+                *edge.weight(),
+            ));
+        }
+
+        for edges in groups.values_mut() {
+            edges.sort_by(|a, b| b.2.total_cmp(&a.2));
+            edges.truncate(n);
+        }
+
+        groups
+    }
+
+    /// Export edges as (source, target, weight) tuples
+    pub fn edge_list(&self) -> Vec<WeightedEdge> {
+        self.graph
+            .edge_references()
+            .map(|edge| {
+                (
+                    self.graph[edge.source()].clone(),
+                    self.graph[edge.target()].clone(),
+                    *edge.weight(),
+                )
+            })
+            .collect()
+    }
+
+    /// Find connected components (cognate sets), ordered by first node
+    pub fn find_cognate_sets(&self) -> Vec<CognateSet> {
+        let (labels, count) = self.component_labels();
+        let mut components: Vec<Vec<String>> = vec![Vec::new(); count];
+        for (idx, node) in self.graph.node_indices().zip(self.graph.node_weights()) {
+            components[labels[idx.index()]].push(node.clone());
+        }
+
+        components
+            .into_iter()
+            .enumerate()
+            .map(|(id, members)| CognateSet::new(id + 1, members))
+            .collect()
+    }
+
+    /// Weighted label propagation communities, ordered by first node.
+    ///
+    /// Every node starts in its own community. Each sweep visits the nodes
+    /// in a random order and moves each to the label with the largest total
+    /// edge weight among its neighbors, updating in place (asynchronously);
+    /// a node keeps its label if that label is among the best, and other
+    /// ties are broken at random. Stops when a sweep changes nothing or
+    /// after `max_iter` sweeps. Each sweep is linear in the edge count and
+    /// the result is deterministic for a given `seed`.
+    pub fn label_propagation(&self, max_iter: usize, seed: u64) -> Vec<Vec<String>> {
+        let n = self.graph.node_count();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut labels: Vec<usize> = (0..n).collect();
+        let mut order: Vec<NodeIndex> = self.graph.node_indices().collect();
+
+        // Scratch space for label weights around one node
+        let mut weights = vec![0.0; n];
+        let mut touched: Vec<usize> = Vec::new();
+        let mut best: Vec<usize> = Vec::new();
+
+        for _ in 0..max_iter {
+            order.shuffle(&mut rng);
+            let mut changed = false;
+            for &node in &order {
+                for edge in self.graph.edges(node) {
+                    let neighbor = edge.target().index();
+                    if neighbor == node.index() {
+                        continue;
                     }
+                    let label = labels[neighbor];
+                    if weights[label] == 0.0 {
+                        touched.push(label);
+                    }
+                    weights[label] += edge.weight();
+                }
+                if touched.is_empty() {
+                    continue;
                 }
 
-                if best_community != current_community {
-                    communities = self.move_node(node, current_community, best_community, &communities);
+                let top = touched.iter().map(|&l| weights[l]).fold(f64::MIN, f64::max);
+                best.clear();
+                best.extend(touched.iter().copied().filter(|&l| weights[l] >= top - 1e-12));
+                best.sort_unstable();
+                let current = labels[node.index()];
+                if !best.contains(&current) {
+                    labels[node.index()] = *best.choose(&mut rng).unwrap();
+                    changed = true;
                 }
+
+                for label in touched.drain(..) {
+                    weights[label] = 0.0;
+                }
+            }
+            if !changed {
+                break;
             }
         }
 
-        // Convert to string IDs
+        let mut first_seen: AHashMap<usize, usize> = AHashMap::new();
+        let mut communities: Vec<Vec<String>> = Vec::new();
+        for (idx, node) in self.graph.node_indices().zip(self.graph.node_weights()) {
+            let next = first_seen.len();
+            let community = *first_seen.entry(labels[idx.index()]).or_insert(next);
+            if community == communities.len() {
+                communities.push(Vec::new());
+            }
+            communities[community].push(node.clone());
+        }
         communities
+    }
+
+    /// Maximum-weight spanning forest (Kruskal): the strongest edges that
+    /// still connect every component, strongest first
+    pub fn max_spanning_forest(&self) -> Vec<WeightedEdge> {
+        let forest = self.spanning_forest_edges();
+        forest
             .into_iter()
-            .filter(|c| !c.is_empty())
-            .map(|community| {
-                community
-                    .into_iter()
-                    .map(|idx| self.graph[idx].clone())
-                    .collect()
+            .map(|edge| self.weighted_edge(edge))
+            .collect()
+    }
+
+    /// The maximum spanning forest as a graph over all nodes, keeping node
+    /// attributes and edge provenance
+    pub fn max_spanning_forest_graph(&self) -> CognateGraph {
+        let mut in_forest = vec![false; self.graph.edge_count()];
+        for edge in self.spanning_forest_edges() {
+            in_forest[edge.index()] = true;
+        }
+        let keep = vec![true; self.graph.node_count()];
+        self.subgraph(&keep, |edge| in_forest[edge.id().index()])
+    }
+
+    fn spanning_forest_edges(&self) -> Vec<EdgeIndex> {
+        let mut edges: Vec<EdgeIndex> = self.graph.edge_indices().collect();
+        // Stable sort keeps insertion order among equal weights
+        edges.sort_by(|&a, &b| self.graph[b].total_cmp(&self.graph[a]));
+
+        let mut components = UnionFind::new(self.graph.node_count());
+        edges
+            .into_iter()
+            .filter(|&edge| {
+                let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+                let (root_a, root_b) = (components.find(a.index()), components.find(b.index()));
+                components.union(root_a, root_b);
+                root_a != root_b
             })
             .collect()
     }
 
-    fn find_node_community(&self, node: NodeIndex, communities: &[Vec<NodeIndex>]) -> usize {
-        for (idx, community) in communities.iter().enumerate() {
-            if community.contains(&node) {
-                return idx;
+    /// Bridges: edges whose removal disconnects their component, weakest
+    /// first. A lone edge joining two clusters is the typical shape of a
+    /// false cognate link.
+    pub fn bridges(&self) -> Vec<WeightedEdge> {
+        let (mut bridges, _, _) = self.biconnectivity();
+        bridges.sort_by(|&a, &b| self.graph[a].total_cmp(&self.graph[b]));
+        bridges.into_iter().map(|edge| self.weighted_edge(edge)).collect()
+    }
+
+    /// Articulation points: nodes whose removal disconnects their
+    /// component, in node order
+    pub fn articulation_points(&self) -> Vec<String> {
+        let (_, is_cut, _) = self.biconnectivity();
+        self.graph
+            .node_indices()
+            .filter(|idx| is_cut[idx.index()])
+            .map(|idx| self.graph[idx].clone())
+            .collect()
+    }
+
+    /// Biconnected components (maximal edge sets without an articulation
+    /// point) as subgraphs, largest first. Each bridge is its own
+    /// component; isolated nodes belong to none.
+    pub fn biconnected_components(&self) -> Vec<CognateGraph> {
+        let (_, _, components) = self.biconnectivity();
+        let mut component_of = vec![usize::MAX; self.graph.edge_count()];
+        for (label, component) in components.iter().enumerate() {
+            for edge in component {
+                component_of[edge.index()] = label;
             }
         }
-        0
+
+        let mut subgraphs: Vec<CognateGraph> = (0..components.len())
+            .map(|label| {
+                let mut keep = vec![false; self.graph.node_count()];
+                for &edge in &components[label] {
+                    let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+                    keep[a.index()] = true;
+                    keep[b.index()] = true;
+                }
+                self.subgraph(&keep, |edge| component_of[edge.id().index()] == label)
+            })
+            .collect();
+        subgraphs.sort_by_key(|g| std::cmp::Reverse(g.graph.edge_count()));
+        subgraphs
     }
 
-    fn move_node(
-        &self,
-        node: NodeIndex,
-        from: usize,
-        to: usize,
-        communities: &[Vec<NodeIndex>],
-    ) -> Vec<Vec<NodeIndex>> {
-        let mut new_communities = communities.to_vec();
-        new_communities[from].retain(|&n| n != node);
-        new_communities[to].push(node);
-        new_communities
+    fn weighted_edge(&self, edge: EdgeIndex) -> WeightedEdge {
+        let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+        (self.graph[a].clone(), self.graph[b].clone(), self.graph[edge])
     }
 
-    fn compute_modularity(&self, communities: &[Vec<NodeIndex>], resolution: f64) -> f64 {
-        let m = self.graph.edge_count() as f64;
-        if m == 0.0 {
-            return 0.0;
-        }
+    /// Tarjan's low-link DFS (iterative): bridge edges, articulation flag
+    /// per node index, and the edges of each biconnected component.
+    /// Parallel edges are told apart by edge index, so a doubled link is
+    /// never a bridge; self-loops are ignored.
+    fn biconnectivity(&self) -> (Vec<EdgeIndex>, Vec<bool>, Vec<Vec<EdgeIndex>>) {
+        let n = self.graph.node_count();
+        let mut discovery = vec![usize::MAX; n];
+        let mut low = vec![0usize; n];
+        let mut is_cut = vec![false; n];
+        let mut bridges = Vec::new();
+        let mut components = Vec::new();
+        let mut edge_stack: Vec<EdgeIndex> = Vec::new();
+        let mut time = 0;
 
-        let mut modularity = 0.0;
+        let incident = |node: NodeIndex| -> Vec<(EdgeIndex, usize)> {
+            self.graph
+                .edges(node)
+                .map(|e| {
+                    let other = if e.source() == node { e.target() } else { e.source() };
+                    (e.id(), other.index())
+                })
+                .filter(|&(_, other)| other != node.index())
+                .collect()
+        };
 
-        for community in communities.iter().filter(|c| !c.is_empty()) {
-            let mut internal_edges = 0.0;
-            let mut total_degree = 0.0;
+        for root in self.graph.node_indices() {
+            if discovery[root.index()] != usize::MAX {
+                continue;
+            }
+            discovery[root.index()] = time;
+            low[root.index()] = time;
+            time += 1;
+            let mut root_children = 0;
 
-            for &node in community {
-                total_degree += self.graph.edges(node).count() as f64;
+            // (node, edge from the DFS parent, incident edges, next edge)
+            let mut stack = vec![(root.index(), None, incident(root), 0usize)];
+            while let Some(frame) = stack.last_mut() {
+                let (node, parent_edge) = (frame.0, frame.1);
+                if let Some(&(edge, next)) = frame.2.get(frame.3) {
+                    frame.3 += 1;
+                    if Some(edge) == parent_edge {
+                        continue;
+                    }
+                    if discovery[next] == usize::MAX {
+                        discovery[next] = time;
+                        low[next] = time;
+                        time += 1;
+                        edge_stack.push(edge);
+                        stack.push((next, Some(edge), incident(NodeIndex::new(next)), 0));
+                    } else if discovery[next] < discovery[node] {
+                        // Back edge to an ancestor, seen once from below
+                        low[node] = low[node].min(discovery[next]);
+                        edge_stack.push(edge);
+                    }
+                    continue;
+                }
 
-                for edge in self.graph.edges(node) {
-                    if community.contains(&edge.target()) {
-                        internal_edges += 1.0;
+                stack.pop();
+                let (Some(parent_frame), Some(tree_edge)) = (stack.last(), parent_edge) else {
+                    continue;
+                };
+                let parent = parent_frame.0;
+                low[parent] = low[parent].min(low[node]);
+                if low[node] > discovery[parent] {
+                    bridges.push(tree_edge);
+                }
+                if low[node] >= discovery[parent] {
+                    if parent == root.index() {
+                        root_children += 1;
+                    } else {
+                        is_cut[parent] = true;
+                    }
+                    let mut component = Vec::new();
+                    while let Some(edge) = edge_stack.pop() {
+                        component.push(edge);
+                        if edge == tree_edge {
+                            break;
+                        }
                     }
+                    components.push(component);
                 }
             }
+            is_cut[root.index()] = root_children > 1;
+        }
+
+        (bridges, is_cut, components)
+    }
+
+    /// Union-find over the current edges, built on first use
+    fn component_index(&self) -> &UnionFind {
+        self.components.get_or_init(|| {
+            let mut components = UnionFind::new(self.graph.node_count());
+            for edge in self.graph.edge_references() {
+                components.union(edge.source().index(), edge.target().index());
+            }
+            components
+        })
+    }
 
-            internal_edges /= 2.0; // Each edge counted twice
-            modularity += (internal_edges / m) - resolution * (total_degree / (2.0 * m)).powi(2);
+    /// Component label (0-based, in order of first node) for every node
+    /// index, plus the component count
+    pub(crate) fn component_labels(&self) -> (Vec<usize>, usize) {
+        let components = self.component_index();
+        let mut root_labels: AHashMap<usize, usize> = AHashMap::new();
+        let labels = (0..self.graph.node_count())
+            .map(|node| {
+                let next = root_labels.len();
+                *root_labels.entry(components.root(node)).or_insert(next)
+            })
+            .collect();
+        (labels, root_labels.len())
+    }
+
+    /// Compute PageRank centrality, keyed by node ID
+    pub fn compute_pagerank(&self, damping: f64, iterations: usize) -> BTreeMap<String, f64> {
+        if self.graph.node_count() == 0 {
+            return BTreeMap::new();
         }
 
-        modularity
+        let ranks = self.pagerank_scores(damping, iterations);
+
+        self.graph
+            .node_indices()
+            .zip(ranks)
+            .map(|(idx, rank)| (self.graph[idx].clone(), rank))
+            .collect()
     }
 
-    /// Compute PageRank centrality
-    pub fn compute_pagerank(&self, damping: f64, iterations: usize) -> HashMap<String, f64> {
+    /// PageRank scores indexed by node index
+    pub(crate) fn pagerank_scores(&self, damping: f64, iterations: usize) -> Vec<f64> {
         let n = self.graph.node_count();
         if n == 0 {
-            return HashMap::new();
+            return Vec::new();
         }
 
         let mut ranks: Vec<f64> = vec![1.0 / n as f64; n];
@@ -233,30 +755,283 @@ impl CognateGraph {
 
         for _ in 0..iterations {
             new_ranks.fill((1.0 - damping) / n as f64);
+            self.spread_rank(&ranks, &mut new_ranks, damping);
+            std::mem::swap(&mut ranks, &mut new_ranks);
+        }
 
-            for node_idx in self.graph.node_indices() {
-                let out_degree = self.graph.edges(node_idx).count();
-                if out_degree > 0 {
-                    let rank_contribution = ranks[node_idx.index()] / out_degree as f64;
-                    for neighbor in self.graph.neighbors(node_idx) {
-                        new_ranks[neighbor.index()] += damping * rank_contribution;
-                    }
+        ranks
+    }
+
+    /// Add `damping` times each node's rank, split evenly over its edges,
+    /// to its neighbors in `next`; returns the rank held by nodes without
+    /// edges
+    fn spread_rank(&self, ranks: &[f64], next: &mut [f64], damping: f64) -> f64 {
+        let mut stranded = 0.0;
+        for node_idx in self.graph.node_indices() {
+            let out_degree = self.graph.edges(node_idx).count();
+            if out_degree > 0 {
+                let rank_contribution = ranks[node_idx.index()] / out_degree as f64;
+                for neighbor in self.graph.neighbors(node_idx) {
+                    next[neighbor.index()] += damping * rank_contribution;
                 }
+            } else {
+                stranded += ranks[node_idx.index()];
             }
+        }
+        stranded
+    }
 
+    /// PageRank whose random surfer restarts at the `seeds`, in proportion
+    /// to their weights (at any node with equal odds without seeds), so
+    /// scores measure relatedness to the seed set. Rank stranded on nodes
+    /// without edges restarts too, so scores sum to 1. Iterates until the
+    /// summed absolute change is at most `tol`, or `max_iterations` times.
+    pub fn compute_personalized_pagerank(
+        &self,
+        seeds: Option<&HashMap<String, f64>>,
+        damping: f64,
+        tol: f64,
+        max_iterations: usize,
+    ) -> Result<BTreeMap<String, f64>, LangVizError> {
+        let parameter = |name: &str, value: f64, expected: &str| LangVizError::InvalidParameter {
+            name: name.to_string(),
+            value,
+            expected: expected.to_string(),
+        };
+        if !(0.0..=1.0).contains(&damping) {
+            return Err(parameter("damping", damping, "a value in [0, 1]"));
+        }
+        if tol.is_nan() || tol < 0.0 {
+            return Err(parameter("tol", tol, "a value >= 0"));
+        }
+        let n = self.graph.node_count();
+        if n == 0 {
+            return Ok(BTreeMap::new());
+        }
+
+        let mut restart = vec![1.0 / n as f64; n];
+        if let Some(seeds) = seeds {
+            restart.fill(0.0);
+            for (id, &weight) in seeds {
+                let idx = self
+                    .node_map
+                    .get(id)
+                    .ok_or_else(|| LangVizError::UnknownEntryId(id.clone()))?;
+                if !(weight.is_finite() && weight >= 0.0) {
+                    let name = format!("seed weight of '{}'", id);
+                    return Err(parameter(&name, weight, "a finite value >= 0"));
+                }
+                restart[idx.index()] = weight;
+            }
+            let total: f64 = restart.iter().sum();
+            if !(total.is_finite() && total > 0.0) {
+                return Err(parameter("seeds", total, "a positive total weight"));
+            }
+            restart.iter_mut().for_each(|r| *r /= total);
+        }
+
+        let mut ranks = restart.clone();
+        let mut new_ranks = vec![0.0; n];
+        for _ in 0..max_iterations {
+            new_ranks.fill(0.0);
+            let stranded = self.spread_rank(&ranks, &mut new_ranks, damping);
+            let teleport = 1.0 - damping + damping * stranded;
+            for (rank, share) in new_ranks.iter_mut().zip(&restart) {
+                *rank += teleport * share;
+            }
+            let change: f64 = ranks
+                .iter()
+                .zip(&new_ranks)
+                .map(|(a, b)| (a - b).abs())
+                .sum();
             std::mem::swap(&mut ranks, &mut new_ranks);
+            if change <= tol {
+                break;
+            }
         }
 
-        // Convert to HashMap with node IDs
-        self.graph
+        Ok(self
+            .graph
             .node_indices()
-            .zip(ranks.into_iter())
+            .zip(ranks)
             .map(|(idx, rank)| (self.graph[idx].clone(), rank))
+            .collect())
+    }
+
+    /// Compute PageRank, degree, betweenness, closeness and eigenvector
+    /// centrality for every node in a single call
+    pub fn compute_centralities(&self, damping: f64, iterations: usize) -> Vec<NodeCentrality> {
+        let pagerank = self.pagerank_scores(damping, iterations);
+        // One simple-graph view (no self-loops or parallel edges) for the
+        // degree and traversal-based scores
+        let adjacency = self.adjacency_lists();
+        let degree = degree_scores(&adjacency);
+        let betweenness = self.betweenness_scores(&adjacency);
+        let closeness = self.closeness_scores(&adjacency);
+        let eigenvector = self.eigenvector_scores(iterations, 1e-9);
+
+        self.graph
+            .node_indices()
+            .map(|idx| {
+                let i = idx.index();
+                NodeCentrality {
+                    id: self.graph[idx].clone(),
+                    pagerank: pagerank[i],
+                    degree: degree[i],
+                    betweenness: betweenness[i],
+                    closeness: closeness[i],
+                    eigenvector: eigenvector[i],
+                    attributes: self.node_attrs.get(&idx).cloned().unwrap_or_default(),
+                }
+            })
             .collect()
     }
 
-    /// Compute shortest path distances from source node
-    pub fn shortest_paths(&self, source_id: &str) -> Option<HashMap<String, f64>> {
+    /// Unweighted betweenness centrality (Brandes), parallelized over sources
+    fn betweenness_scores(&self, adjacency: &[Vec<usize>]) -> Vec<f64> {
+        let n = self.graph.node_count();
+        if n < 3 {
+            return vec![0.0; n];
+        }
+
+        let raw = sum_over_sources(n, |source| {
+            let mut stack = Vec::with_capacity(n);
+            let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+            let mut sigma = vec![0.0; n];
+            let mut distance = vec![-1i64; n];
+            let mut queue = VecDeque::new();
+
+            sigma[source] = 1.0;
+            distance[source] = 0;
+            queue.push_back(source);
+
+            while let Some(v) = queue.pop_front() {
+                stack.push(v);
+                for &w in &adjacency[v] {
+                    if distance[w] < 0 {
+                        distance[w] = distance[v] + 1;
+                        queue.push_back(w);
+                    }
+                    if distance[w] == distance[v] + 1 {
+                        sigma[w] += sigma[v];
+                        predecessors[w].push(v);
+                    }
+                }
+            }
+
+            // Accumulate dependencies in reverse BFS order
+            let mut delta = vec![0.0; n];
+            let mut contribution = vec![0.0; n];
+            while let Some(w) = stack.pop() {
+                for &v in &predecessors[w] {
+                    delta[v] += (sigma[v] / sigma[w]) * (1.0 + delta[w]);
+                }
+                if w != source {
+                    contribution[w] += delta[w];
+                }
+            }
+            contribution
+        });
+
+        // Each undirected path is counted from both endpoints
+        let scale = 1.0 / ((n - 1) * (n - 2)) as f64;
+        raw.into_iter().map(|b| b * scale).collect()
+    }
+
+    /// Closeness centrality over hop distances (Wasserman-Faust scaling
+    /// for disconnected graphs)
+    fn closeness_scores(&self, adjacency: &[Vec<usize>]) -> Vec<f64> {
+        let n = self.graph.node_count();
+        if n < 2 {
+            return vec![0.0; n];
+        }
+
+        (0..n)
+            .into_par_iter()
+            .map(|source| {
+                let mut distance = vec![usize::MAX; n];
+                let mut queue = VecDeque::new();
+                distance[source] = 0;
+                queue.push_back(source);
+
+                let mut total = 0usize;
+                let mut reachable = 0usize;
+                while let Some(v) = queue.pop_front() {
+                    for &w in &adjacency[v] {
+                        if distance[w] == usize::MAX {
+                            distance[w] = distance[v] + 1;
+                            total += distance[w];
+                            reachable += 1;
+                            queue.push_back(w);
+                        }
+                    }
+                }
+
+                if total == 0 {
+                    0.0
+                } else {
+                    let r = reachable as f64;
+                    (r / total as f64) * (r / (n - 1) as f64)
+                }
+            })
+            .collect()
+    }
+
+    /// Weighted eigenvector centrality via power iteration (L2-normalized)
+    fn eigenvector_scores(&self, max_iterations: usize, tolerance: f64) -> Vec<f64> {
+        let n = self.graph.node_count();
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let mut scores = vec![1.0 / (n as f64).sqrt(); n];
+        let mut next = vec![0.0; n];
+
+        for _ in 0..max_iterations.max(1) {
+            // Iterate on (A + I) so bipartite structures still converge
+            next.copy_from_slice(&scores);
+            for edge in self.graph.edge_references() {
+                let (s, t) = (edge.source().index(), edge.target().index());
+                next[s] += edge.weight() * scores[t];
+                next[t] += edge.weight() * scores[s];
+            }
+
+            let norm = next.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                return vec![0.0; n];
+            }
+            next.iter_mut().for_each(|x| *x /= norm);
+
+            let change: f64 = scores.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+            std::mem::swap(&mut scores, &mut next);
+            if change < tolerance * n as f64 {
+                break;
+            }
+        }
+
+        scores
+    }
+
+    /// Neighbor lists indexed by node index (deduplicated, no self-loops)
+    pub(crate) fn adjacency_lists(&self) -> Vec<Vec<usize>> {
+        self.graph
+            .node_indices()
+            .map(|idx| {
+                let mut neighbors: Vec<usize> = self
+                    .graph
+                    .neighbors(idx)
+                    .map(|n| n.index())
+                    .filter(|&n| n != idx.index())
+                    .collect();
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                neighbors
+            })
+            .collect()
+    }
+
+    /// Compute shortest path distances from source node, keyed by node ID
+    pub fn shortest_paths(&self, source_id: &str) -> Option<BTreeMap<String, f64>> {
         let source_idx = self.node_map.get(source_id)?;
 
         let paths = dijkstra(&self.graph, *source_idx, None, |e| *e.weight());
@@ -271,8 +1046,208 @@ impl CognateGraph {
 
     /// Get graph statistics
     pub fn stats(&self) -> GraphStats {
-        let num_nodes = self.graph.node_count();
-        let num_edges = self.graph.edge_count();
+        self.stats_cache().stats.clone()
+    }
+
+    /// Statistics with the node strengths behind them, computed once until
+    /// the graph changes
+    pub(crate) fn stats_cache(&self) -> &StatsCache {
+        self.stats_cache.get_or_init(|| self.compute_stats_cache())
+    }
+
+    fn compute_stats_cache(&self) -> StatsCache {
+        // Component size distribution: (size, number of components of that size)
+        let (labels, num_components) = self.component_labels();
+        let mut sizes = vec![0usize; num_components];
+        for label in labels {
+            sizes[label] += 1;
+        }
+        let mut distribution: BTreeMap<usize, usize> = BTreeMap::new();
+        for &size in &sizes {
+            *distribution.entry(size).or_insert(0) += 1;
+        }
+
+        let strengths = self.node_strengths();
+        let mut order: Vec<usize> = (0..strengths.len()).collect();
+        order.sort_by(|&a, &b| strengths[a].total_cmp(&strengths[b]));
+        let sorted: Vec<f64> = order.iter().map(|&node| strengths[node]).collect();
+        let mut rank = vec![0; strengths.len()];
+        for (position, &node) in order.iter().enumerate() {
+            rank[node] = position;
+        }
+        let strength =
+            StrengthSummary::from_sorted(sorted.len(), sorted.iter().sum(), |k| sorted[k]);
+
+        StatsCache {
+            stats: GraphStats::from_parts(
+                self.graph.node_count(),
+                self.graph.edge_count(),
+                distribution,
+                strength,
+            ),
+            strengths,
+            sorted,
+            rank,
+        }
+    }
+
+    /// Weighted degree (sum of incident edge weights) per node, indexed by
+    /// node index
+    pub(crate) fn node_strengths(&self) -> Vec<f64> {
+        let mut strengths = vec![0.0; self.graph.node_count()];
+        for edge in self.graph.edge_references() {
+            strengths[edge.source().index()] += edge.weight();
+            strengths[edge.target().index()] += edge.weight();
+        }
+        strengths
+    }
+
+    /// Export graph to JSON for visualization; node attributes are
+    /// included under `attributes`
+    pub fn to_json(&self) -> String {
+        let mut buffer = Vec::new();
+        self.write_json(&mut buffer)
+            .expect("writing JSON to memory cannot fail");
+        String::from_utf8(buffer).expect("serde_json writes UTF-8")
+    }
+
+    /// Stream the `to_json` document to `writer` one node and edge at a
+    /// time, so huge graphs export without building the text in memory
+    pub fn write_json<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "{{\"format_version\":{},\"nodes\":[", GRAPH_JSON_VERSION)?;
+        for (i, idx) in self.graph.node_indices().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            let mut node = serde_json::json!({
+                "id": self.graph[idx],
+            });
+            if let Some(attrs) = self.node_attrs.get(&idx).filter(|a| !a.is_empty()) {
+                node["attributes"] = serde_json::json!(attrs);
+            }
+            serde_json::to_writer(&mut *writer, &node)?;
+        }
+
+        writer.write_all(b"],\"edges\":[")?;
+        for (i, edge) in self.graph.edge_references().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            let mut value = serde_json::json!({
+                "source": self.graph[edge.source()],
+                "target": self.graph[edge.target()],
+                "weight": edge.weight(),
+            });
+            if let Some(provenance) = self.edge_provenance(edge.id()) {
+                value["provenance"] = serde_json::json!(provenance);
+            }
+            serde_json::to_writer(&mut *writer, &value)?;
+        }
+        writer.write_all(b"]}")?;
+        writer.flush()
+    }
+
+    /// Rebuild a graph from a `to_json` / `write_json` document of any
+    /// supported format version, keeping node order (and so node indices),
+    /// isolated nodes, node attributes and edge provenance. Edge endpoints
+    /// may be given as string IDs or as indices into `nodes`.
+    pub fn read_json<R: Read>(reader: R) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct JsonNode {
+            id: String,
+            #[serde(default)]
+            attributes: BTreeMap<String, AttrValue>,
+        }
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum JsonNodeRef {
+            Index(usize),
+            Id(String),
+        }
+        #[derive(Deserialize)]
+        struct JsonEdge {
+            source: JsonNodeRef,
+            target: JsonNodeRef,
+            weight: f64,
+            provenance: Option<String>,
+        }
+        #[derive(Deserialize)]
+        struct JsonGraph {
+            nodes: Vec<JsonNode>,
+            edges: Vec<JsonEdge>,
+        }
+
+        let mut document: serde_json::Value = serde_json::from_reader(reader)
+            .map_err(|e| format!("invalid graph JSON: {}", e))?;
+        GRAPH_JSON.upgrade(&mut document)?;
+        let document: JsonGraph =
+            serde_json::from_value(document).map_err(|e| format!("invalid graph JSON: {}", e))?;
+        let mut graph = Self::new();
+        for node in document.nodes {
+            let idx = graph.get_or_create_node(node.id);
+            if !node.attributes.is_empty() {
+                graph.node_attrs.insert(idx, node.attributes);
+            }
+        }
+        let node_count = graph.graph.node_count();
+        let resolve = |node: JsonNodeRef, graph: &Self| match node {
+            JsonNodeRef::Id(id) => Ok(id),
+            JsonNodeRef::Index(index) => {
+                graph.node_id(index).map(str::to_string).ok_or_else(|| {
+                    format!(
+                        "invalid graph JSON: node index {} out of range for {} nodes",
+                        index, node_count
+                    )
+                })
+            }
+        };
+        for edge in document.edges {
+            let source = resolve(edge.source, &graph)?;
+            let target = resolve(edge.target, &graph)?;
+            graph.add_edge_with_provenance(source, target, edge.weight, edge.provenance.as_deref());
+        }
+        Ok(graph)
+    }
+}
+
+impl Default for CognateGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Graph statistics
+#[derive(Debug, Clone)]
+pub struct GraphStats {
+    pub num_nodes: usize,
+    pub num_edges: usize,
+    pub avg_degree: f64,
+    pub density: f64,
+    pub num_components: usize,
+    pub largest_component_size: usize,
+    /// Share of nodes in the largest component
+    pub largest_component_fraction: f64,
+    /// Components consisting of a single node
+    pub num_singletons: usize,
+    /// (component size, number of components of that size), ascending by size
+    pub component_size_distribution: Vec<(usize, usize)>,
+    /// Sum of edge weights
+    pub total_weight: f64,
+    /// Node strength (weighted degree) summary; 0 for an empty graph
+    pub mean_strength: f64,
+    pub median_strength: f64,
+    pub max_strength: f64,
+}
+
+impl GraphStats {
+    /// Statistics of a graph with this many nodes and edges, this
+    /// {component size: count} distribution and this strength summary
+    pub(crate) fn from_parts(
+        num_nodes: usize,
+        num_edges: usize,
+        distribution: BTreeMap<usize, usize>,
+        strength: StrengthSummary,
+    ) -> Self {
         let avg_degree = if num_nodes > 0 {
             (2 * num_edges) as f64 / num_nodes as f64
         } else {
@@ -285,62 +1260,483 @@ impl CognateGraph {
             0.0
         };
 
-        let num_components = connected_components(&self.graph);
+        let largest_component_size = distribution.keys().next_back().copied().unwrap_or(0);
+        let largest_component_fraction = if num_nodes > 0 {
+            largest_component_size as f64 / num_nodes as f64
+        } else {
+            0.0
+        };
 
         GraphStats {
             num_nodes,
             num_edges,
             avg_degree,
             density,
-            num_components,
+            num_components: distribution.values().sum(),
+            largest_component_size,
+            largest_component_fraction,
+            num_singletons: distribution.get(&1).copied().unwrap_or(0),
+            component_size_distribution: distribution.into_iter().collect(),
+            total_weight: strength.total_weight,
+            mean_strength: strength.mean,
+            median_strength: strength.median,
+            max_strength: strength.max,
         }
     }
+}
 
-    /// Export graph to JSON for visualization
-    pub fn to_json(&self) -> String {
-        let nodes: Vec<_> = self
-            .graph
-            .node_indices()
-            .map(|idx| {
-                serde_json::json!({
-                    "id": self.graph[idx],
-                })
-            })
-            .collect();
-
-        let edges: Vec<_> = self
-            .graph
-            .edge_references()
-            .map(|edge| {
-                serde_json::json!({
-                    "source": self.graph[edge.source()],
-                    "target": self.graph[edge.target()],
-                    "weight": edge.weight(),
-                })
-            })
-            .collect();
+/// Summary of node strengths (weighted degrees); all 0 for no nodes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StrengthSummary {
+    pub(crate) total_weight: f64,
+    pub(crate) mean: f64,
+    pub(crate) median: f64,
+    pub(crate) max: f64,
+}
 
-        serde_json::json!({
-            "nodes": nodes,
-            "edges": edges,
-        })
-        .to_string()
+impl StrengthSummary {
+    /// Summary of `count` strengths summing to `total`, where `nth(k)` is
+    /// the k-th smallest
+    pub(crate) fn from_sorted(count: usize, total: f64, nth: impl Fn(usize) -> f64) -> Self {
+        if count == 0 {
+            return StrengthSummary {
+                total_weight: 0.0,
+                mean: 0.0,
+                median: 0.0,
+                max: 0.0,
+            };
+        }
+        let median = if count % 2 == 1 {
+            nth(count / 2)
+        } else {
+            (nth(count / 2 - 1) + nth(count / 2)) / 2.0
+        };
+        StrengthSummary {
+            // Every edge adds its weight to both endpoints
+            total_weight: total / 2.0,
+            mean: total / count as f64,
+            median,
+            max: nth(count - 1),
+        }
     }
 }
 
-impl Default for CognateGraph {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Degree centrality over `adjacency` (distinct neighbors, self-loops
+/// excluded), normalized by the maximum possible degree (n - 1)
+fn degree_scores(adjacency: &[Vec<usize>]) -> Vec<f64> {
+    let n = adjacency.len();
+    let scale = if n > 1 { 1.0 / (n - 1) as f64 } else { 0.0 };
+    adjacency
+        .iter()
+        .map(|neighbors| neighbors.len() as f64 * scale)
+        .collect()
 }
 
-/// Graph statistics
+/// Per-node centrality scores computed together by `compute_centralities`
 #[derive(Debug, Clone)]
-pub struct GraphStats {
-    pub num_nodes: usize,
-    pub num_edges: usize,
-    pub avg_degree: f64,
-    pub density: f64,
-    pub num_components: usize,
+pub struct NodeCentrality {
+    pub id: String,
+    pub pagerank: f64,
+    pub degree: f64,
+    pub betweenness: f64,
+    pub closeness: f64,
+    pub eigenvector: f64,
+    /// The node's attributes, so results are self-describing
+    pub attributes: BTreeMap<String, AttrValue>,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(a: &str, b: &str, w: f64) -> SimilarityEdge {
+        SimilarityEdge::new(a.to_string(), b.to_string(), w)
+    }
+
+    #[test]
+    fn test_centralities_star() {
+        let edges = vec![
+            edge("hub", "a", 0.9),
+            edge("hub", "b", 0.9),
+            edge("hub", "c", 0.9),
+        ];
+        let graph = CognateGraph::from_edges(edges, 0.5);
+        let scores = graph.compute_centralities(0.85, 50);
+        assert_eq!(scores.len(), 4);
+
+        let hub = scores.iter().find(|c| c.id == "hub").unwrap();
+        let leaf = scores.iter().find(|c| c.id == "a").unwrap();
+
+        assert!((hub.betweenness - 1.0).abs() < 1e-9);
+        assert_eq!(leaf.betweenness, 0.0);
+        assert!((hub.degree - 1.0).abs() < 1e-9);
+        assert!((hub.closeness - 1.0).abs() < 1e-9);
+        assert!(hub.eigenvector > leaf.eigenvector);
+        assert!(hub.pagerank > leaf.pagerank);
+    }
+
+    #[test]
+    fn test_centralities_ignore_parallel_edges_and_loops() {
+        let mut graph =
+            CognateGraph::from_edges(vec![edge("hub", "a", 0.9), edge("hub", "b", 0.9)], 0.5);
+        graph.add_edge("hub".into(), "a".into(), 0.8);
+        graph.add_edge("hub".into(), "hub".into(), 0.7);
+        let scores = graph.compute_centralities(0.85, 50);
+
+        let hub = scores.iter().find(|c| c.id == "hub").unwrap();
+        let leaf = scores.iter().find(|c| c.id == "a").unwrap();
+        assert!((hub.degree - 1.0).abs() < 1e-9);
+        assert!((leaf.degree - 0.5).abs() < 1e-9);
+        assert!((hub.betweenness - 1.0).abs() < 1e-9);
+        assert!((hub.closeness - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_personalized_pagerank() {
+        // Two triangles joined by c-d, plus an isolated node
+        let mut graph = CognateGraph::from_edges(
+            vec![
+                edge("a", "b", 0.9),
+                edge("b", "c", 0.9),
+                edge("c", "a", 0.9),
+                edge("c", "d", 0.9),
+                edge("d", "e", 0.9),
+                edge("e", "f", 0.9),
+                edge("f", "d", 0.9),
+            ],
+            0.5,
+        );
+        graph.get_or_create_node("z".to_string());
+
+        let seeds = HashMap::from([("a".to_string(), 1.0)]);
+        let ranks = graph
+            .compute_personalized_pagerank(Some(&seeds), 0.85, 1e-12, 1000)
+            .unwrap();
+        assert!((ranks.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(ranks["a"] > ranks["b"] && ranks["b"] > ranks["e"]);
+        assert_eq!(ranks["z"], 0.0);
+
+        // Without seeds the ranks are symmetric under the a..c <-> d..f swap
+        let global = graph
+            .compute_personalized_pagerank(None, 0.85, 1e-12, 1000)
+            .unwrap();
+        assert!((global["a"] - global["f"]).abs() < 1e-9);
+        assert!(global["z"] > 0.0);
+
+        let unknown = HashMap::from([("q".to_string(), 1.0)]);
+        let error = graph.compute_personalized_pagerank(Some(&unknown), 0.85, 1e-9, 10);
+        assert_eq!(error, Err(LangVizError::UnknownEntryId("q".to_string())));
+        let zero = HashMap::from([("a".to_string(), 0.0)]);
+        assert!(graph
+            .compute_personalized_pagerank(Some(&zero), 0.85, 1e-9, 10)
+            .is_err());
+    }
+
+    #[test]
+    fn test_label_propagation() {
+        let mut edges = Vec::new();
+        for group in [["a", "b", "c", "d"], ["e", "f", "g", "h"]] {
+            for i in 0..4 {
+                for j in (i + 1)..4 {
+                    edges.push(edge(group[i], group[j], 1.0));
+                }
+            }
+        }
+        edges.push(edge("d", "e", 0.1));
+        edges.push(edge("x", "y", 0.9));
+        let mut graph = CognateGraph::from_edges(edges, 0.0);
+        graph.get_or_create_node("solo".to_string());
+
+        let communities = graph.label_propagation(100, 7);
+        assert_eq!(
+            communities,
+            vec![
+                vec!["a", "b", "c", "d"],
+                vec!["e", "f", "g", "h"],
+                vec!["x", "y"],
+                vec!["solo"],
+            ]
+        );
+        assert_eq!(graph.label_propagation(100, 7), communities);
+        assert_eq!(graph.label_propagation(0, 7).len(), graph.graph.node_count());
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut graph = CognateGraph::from_edges(
+            vec![edge("a", "b", 0.9), edge("c", "d", 0.4), edge("b", "c", 0.7)],
+            0.0,
+        );
+        graph.add_edge("d".into(), "e".into(), 0.3);
+        assert_eq!(graph.find_cognate_sets().len(), 1);
+
+        // Additions keep the cached components current
+        let tagged =
+            SimilarityEdge::with_provenance("x".into(), "y".into(), 0.95, Some("run2".into()));
+        graph.add_edges(vec![tagged]);
+        assert_eq!(graph.stats().num_components, 2);
+
+        assert_eq!(graph.remove_edges_below(0.5), 2);
+        assert_eq!(graph.graph.edge_count(), 3);
+        assert_eq!(graph.stats().num_nodes, 7);
+        let mut sizes: Vec<usize> = graph.find_cognate_sets().iter().map(|s| s.size).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 1, 2, 3]);
+
+        // Provenance follows the last edge as removal moves it into a gap
+        assert_eq!(graph.edges_by_provenance(&["run2".to_string()]).len(), 1);
+        assert_eq!(graph.edges_by_provenance(&["run2".to_string()])[0].0, "x");
+
+        assert!(graph.update_edge_weight("b", "a", 0.2));
+        assert!(!graph.update_edge_weight("a", "x", 0.2));
+        assert_eq!(graph.remove_edges_below(0.5), 1);
+        assert_eq!(graph.find_cognate_sets().len(), 5);
+    }
+
+    #[test]
+    fn test_max_spanning_forest() {
+        let graph = CognateGraph::from_edges(
+            vec![
+                edge("a", "b", 0.9),
+                edge("b", "c", 0.8),
+                edge("a", "c", 0.5),
+                edge("c", "d", 0.6),
+                edge("x", "y", 0.7),
+            ],
+            0.0,
+        );
+        let forest = graph.max_spanning_forest();
+        let weights: Vec<f64> = forest.iter().map(|e| e.2).collect();
+        assert_eq!(weights, vec![0.9, 0.8, 0.7, 0.6]);
+
+        // Same components, one edge fewer per cycle
+        let reduced = graph.max_spanning_forest_graph();
+        assert_eq!(reduced.stats().num_nodes, 6);
+        assert_eq!(reduced.graph.edge_count(), 4);
+        assert_eq!(reduced.find_cognate_sets().len(), graph.find_cognate_sets().len());
+    }
+
+    #[test]
+    fn test_component_size_distribution() {
+        let mut graph = CognateGraph::from_edges(
+            vec![edge("a", "b", 0.9), edge("b", "c", 0.9), edge("d", "e", 0.9)],
+            0.5,
+        );
+        graph.get_or_create_node("f".into());
+        graph.get_or_create_node("g".into());
+
+        let stats = graph.stats();
+        assert_eq!(stats.num_components, 4);
+        assert_eq!(stats.largest_component_size, 3);
+        assert!((stats.largest_component_fraction - 3.0 / 7.0).abs() < 1e-12);
+        assert_eq!(stats.num_singletons, 2);
+        assert_eq!(stats.component_size_distribution, vec![(1, 2), (2, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn test_strength_stats() {
+        let mut graph = CognateGraph::from_edges(
+            vec![edge("a", "b", 0.9), edge("b", "c", 0.6), edge("c", "d", 0.7)],
+            0.5,
+        );
+        graph.get_or_create_node("e".into());
+
+        // Strengths: a 0.9, b 1.5, c 1.3, d 0.7, e 0
+        let stats = graph.stats();
+        assert!((stats.total_weight - 2.2).abs() < 1e-12);
+        assert!((stats.mean_strength - 4.4 / 5.0).abs() < 1e-12);
+        assert!((stats.median_strength - 0.9).abs() < 1e-12);
+        assert!((stats.max_strength - 1.5).abs() < 1e-12);
+        assert_eq!(CognateGraph::new().stats().median_strength, 0.0);
+    }
+
+    #[test]
+    fn test_filter_by_attribute_and_weight() {
+        let edges = vec![
+            edge("lat_pater", "grc_pater", 0.9),
+            edge("lat_pater", "eng_father", 0.7),
+            edge("grc_pater", "san_pitar", 0.6),
+        ];
+        let mut graph = CognateGraph::from_edges(edges, 0.5);
+
+        let languages = [
+            ("lat_pater", "lat"),
+            ("grc_pater", "grc"),
+            ("eng_father", "eng"),
+            ("san_pitar", "san"),
+        ];
+        graph.set_node_attributes(
+            languages
+                .iter()
+                .map(|(id, lang)| {
+                    let attrs = HashMap::from([("language".to_string(), lang.to_string())]);
+                    (id.to_string(), attrs)
+                })
+                .collect(),
+        );
+
+        let nodes = Predicate::parse("language in {lat, grc, san}").unwrap();
+        let edges = Predicate::parse("weight >= 0.65").unwrap();
+        let filtered = graph.filter(Some(&nodes), Some(&edges));
+
+        let stats = filtered.stats();
+        assert_eq!(stats.num_nodes, 3);
+        assert_eq!(stats.num_edges, 1);
+        assert_eq!(
+            filtered.node_attribute("san_pitar", "language"),
+            Some(&AttrValue::from("san"))
+        );
+
+        let hubs = Predicate::parse("degree >= 2").unwrap();
+        assert_eq!(graph.filter(Some(&hubs), None).stats().num_nodes, 2);
+    }
+
+    #[test]
+    fn test_typed_node_attributes() {
+        let edges = vec![edge("a", "b", 0.9), edge("b", "c", 0.8)];
+        let mut graph = CognateGraph::from_edges(edges, 0.5);
+        graph.set_node_attributes(HashMap::from([
+            ("a".to_string(), HashMap::from([("frequency".to_string(), 120.0)])),
+            ("b".to_string(), HashMap::from([("frequency".to_string(), 8.0)])),
+        ]));
+        graph.set_node_attributes(HashMap::from([(
+            "a".to_string(),
+            HashMap::from([("language".to_string(), "lat")]),
+        )]));
+        assert_eq!(graph.node_attributes("a").len(), 2);
+        let frequency = graph.node_attribute("b", "frequency");
+        assert_eq!(frequency.and_then(AttrValue::as_number), Some(8.0));
+
+        let frequent = Predicate::parse("frequency >= 10").unwrap();
+        assert_eq!(graph.filter(Some(&frequent), None).stats().num_nodes, 1);
+
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json()).unwrap();
+        assert_eq!(json["nodes"][0]["attributes"]["frequency"], 120.0);
+        assert_eq!(json["nodes"][0]["attributes"]["language"], "lat");
+        assert!(json["nodes"][2].get("attributes").is_none());
+        let mut streamed = Vec::new();
+        graph.write_json(&mut streamed).unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&streamed).unwrap(), json);
+
+        let centralities = graph.compute_centralities(0.85, 50);
+        assert_eq!(centralities[1].attributes["frequency"], AttrValue::Number(8.0));
+    }
+
+    #[test]
+    fn test_edge_provenance() {
+        let edges = vec![
+            SimilarityEdge::with_provenance("a".into(), "b".into(), 0.9, Some("manual".into())),
+            SimilarityEdge::with_provenance(
+                "b".into(),
+                "c".into(),
+                0.8,
+                Some("levenshtein".into()),
+            ),
+            edge("c", "d", 0.7),
+        ];
+        let graph = CognateGraph::from_edges(edges, 0.5);
+
+        let manual = graph.edges_by_provenance(&["manual".to_string()]);
+        assert_eq!(manual.len(), 1);
+        assert_eq!(manual[0].3.as_deref(), Some("manual"));
+
+        let counts = graph.provenance_counts();
+        assert_eq!(counts[&None], 1);
+        assert_eq!(counts[&Some("levenshtein".to_string())], 1);
+
+        let auto = Predicate::parse("provenance != manual").unwrap();
+        let filtered = graph.filter(None, Some(&auto));
+        assert_eq!(filtered.stats().num_edges, 1);
+        assert_eq!(filtered.provenance_edge_list()[0].3.as_deref(), Some("levenshtein"));
+    }
+
+    #[test]
+    fn test_stable_node_indices() {
+        let graph = CognateGraph::from_edges(
+            vec![edge("c", "a", 0.9), edge("a", "b", 0.8), edge("d", "e", 0.7)],
+            0.5,
+        );
+        assert_eq!(graph.node_ids(), ["c", "a", "b", "d", "e"]);
+        assert_eq!(graph.node_index("b"), Some(2));
+        assert_eq!(graph.node_id(2), Some("b"));
+        assert_eq!(graph.node_index("z"), None);
+        assert_eq!(graph.node_id(5), None);
+        assert_eq!(graph.indexed_edge_list()[0], (0, 1, 0.9));
+
+        // Indices survive a JSON round trip
+        let restored = CognateGraph::read_json(graph.to_json().as_bytes()).unwrap();
+        assert_eq!(restored.node_ids(), graph.node_ids());
+        assert_eq!(restored.indexed_edge_list(), graph.indexed_edge_list());
+
+        // Edges may reference nodes by index
+        let compact =
+            r#"{"nodes":[{"id":"x"},{"id":"y"}],"edges":[{"source":0,"target":1,"weight":0.5}]}"#;
+        let compact = CognateGraph::read_json(compact.as_bytes()).unwrap();
+        assert_eq!(
+            compact.edge_list(),
+            [("x".to_string(), "y".to_string(), 0.5)]
+        );
+        let dangling = r#"{"nodes":[{"id":"x"}],"edges":[{"source":0,"target":3,"weight":0.5}]}"#;
+        assert!(CognateGraph::read_json(dangling.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_top_edges_per_language_pair() {
+        let edges = vec![
+            edge("lat_a", "grc_a", 0.9),
+            edge("lat_b", "grc_b", 0.7),
+            edge("grc_c", "lat_c", 0.8),
+            edge("lat_a", "eng_a", 0.6),
+        ];
+        let mut graph = CognateGraph::from_edges(edges, 0.5);
+        graph.set_node_attributes(
+            ["lat_a", "lat_b", "lat_c", "grc_a", "grc_b", "grc_c", "eng_a"]
+                .iter()
+                .map(|id| {
+                    let lang = id.split('_').next().unwrap().to_string();
+                    (id.to_string(), HashMap::from([("language".to_string(), lang)]))
+                })
+                .collect(),
+        );
+
+        let top = graph.top_edges_per_language_pair("language", 2);
+        assert_eq!(top.len(), 2);
+
+        let grc_lat = &top[&("grc".to_string(), "lat".to_string())];
+        assert_eq!(grc_lat.len(), 2);
+        assert_eq!(grc_lat[0].2, 0.9);
+        assert_eq!(grc_lat[1].2, 0.8);
+    }
+
+    #[test]
+    fn test_bridges_and_articulation_points() {
+        // Two triangles joined by the weak link c - d, a doubled link e - f
+        // hanging off d, and an isolated pair g - h
+        let edges = vec![
+            edge("a", "b", 0.9),
+            edge("b", "c", 0.9),
+            edge("a", "c", 0.9),
+            edge("c", "d", 0.55),
+            edge("d", "e", 0.9),
+            edge("e", "f", 0.9),
+            edge("d", "f", 0.9),
+            edge("f", "x", 0.8),
+            edge("f", "x", 0.7),
+            edge("g", "h", 0.6),
+        ];
+        let graph = CognateGraph::from_edges(edges, 0.5);
+
+        let bridges = graph.bridges();
+        assert_eq!(bridges.len(), 2);
+        assert_eq!(bridges[0], ("c".to_string(), "d".to_string(), 0.55));
+        assert_eq!(bridges[1].2, 0.6);
+        assert_eq!(graph.articulation_points(), vec!["c", "d", "f"]);
+
+        let sizes: Vec<usize> = graph
+            .biconnected_components()
+            .iter()
+            .map(|g| g.stats().num_edges)
+            .collect();
+        assert_eq!(sizes, vec![3, 3, 2, 1, 1]);
+    }
+}
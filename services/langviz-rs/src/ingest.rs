@@ -0,0 +1,263 @@
+//! Bulk edge-list ingestion straight from files.
+//!
+//! Reading (source, target, weight) rows in Rust skips building millions of
+//! Python tuples. CSV / TSV files (plain, gzip or zstd) are split into lines
+//! and parsed in parallel; fields may be double-quoted (`""` escapes a
+//! quote) but may not contain line breaks. Parquet files (cargo feature
+//! `parquet`) are decoded with Arrow, reading only the three edge columns.
+
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::compress::read_to_string;
+use crate::types::WeightedEdge;
+
+/// Names of the source, target and weight columns
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeColumns<'a> {
+    pub source: &'a str,
+    pub target: &'a str,
+    pub weight: &'a str,
+}
+
+impl Default for EdgeColumns<'_> {
+    fn default() -> Self {
+        Self {
+            source: "source",
+            target: "target",
+            weight: "weight",
+        }
+    }
+}
+
+/// Tab for `.tsv` / `.tab` files (compressed or not), comma otherwise
+pub fn default_delimiter(path: &Path) -> char {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if name.contains(".tsv") || name.contains(".tab") {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+/// Fields of one CSV record
+pub(crate) fn split_record(line: &str, delimiter: char) -> Vec<Cow<'_, str>> {
+    if !line.contains('"') {
+        return line.split(delimiter).map(Cow::Borrowed).collect();
+    }
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(Cow::Owned(std::mem::take(&mut field))),
+            c => field.push(c),
+        }
+    }
+    fields.push(Cow::Owned(field));
+    fields
+}
+
+/// Parse CSV text with a header row naming the edge columns
+pub fn parse_edges_csv(
+    text: &str,
+    columns: EdgeColumns,
+    delimiter: char,
+) -> Result<Vec<WeightedEdge>, String> {
+    let mut lines = text.lines();
+    let header = split_record(lines.next().ok_or("CSV file is empty")?, delimiter);
+    let position = |name: &str| {
+        header
+            .iter()
+            .position(|field| field.trim() == name)
+            .ok_or_else(|| {
+                format!(
+                    "CSV has no '{}' column (columns: {})",
+                    name,
+                    header.join(", ")
+                )
+            })
+    };
+    let (source, target, weight) = (
+        position(columns.source)?,
+        position(columns.target)?,
+        position(columns.weight)?,
+    );
+    let needed = source.max(target).max(weight) + 1;
+
+    let rows: Vec<&str> = lines.collect();
+    rows.par_iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            // Line numbers are 1-based and count the header
+            let fields = split_record(line, delimiter);
+            if fields.len() < needed {
+                return Err(format!(
+                    "CSV line {}: expected at least {} fields, got {}",
+                    i + 2,
+                    needed,
+                    fields.len()
+                ));
+            }
+            let value: f64 = fields[weight]
+                .trim()
+                .parse()
+                .map_err(|_| format!("CSV line {}: invalid weight '{}'", i + 2, fields[weight]))?;
+            Ok((
+                fields[source].clone().into_owned(),
+                fields[target].clone().into_owned(),
+                value,
+            ))
+        })
+        .collect()
+}
+
+/// Read a plain, gzip or zstd CSV / TSV edge list
+pub fn read_edges_csv(
+    path: &Path,
+    columns: EdgeColumns,
+    delimiter: Option<char>,
+) -> Result<Vec<WeightedEdge>, String> {
+    let text =
+        read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    parse_edges_csv(
+        &text,
+        columns,
+        delimiter.unwrap_or_else(|| default_delimiter(path)),
+    )
+}
+
+/// Read the edge columns of a Parquet file; IDs may be any type castable to
+/// text and weights any numeric type, without nulls
+#[cfg(feature = "parquet")]
+pub fn read_edges_parquet(path: &Path, columns: EdgeColumns) -> Result<Vec<WeightedEdge>, String> {
+    use arrow_array::{Array, ArrayRef, Float64Array, RecordBatch, StringArray};
+    use arrow_schema::DataType;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::arrow::ProjectionMask;
+
+    let invalid = |e: &dyn std::fmt::Display| format!("invalid Parquet file: {}", e);
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(|e| invalid(&e))?;
+    let schema = builder.schema().clone();
+    let names = [columns.source, columns.target, columns.weight];
+    let indices = names
+        .iter()
+        .map(|&name| {
+            schema
+                .index_of(name)
+                .map_err(|_| format!("Parquet file has no '{}' column", name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+    let reader = builder
+        .with_projection(mask)
+        .build()
+        .map_err(|e| invalid(&e))?;
+
+    let column = |batch: &RecordBatch, name: &str, to: &DataType| -> Result<ArrayRef, String> {
+        let array = batch.column_by_name(name).expect("projected column");
+        if array.null_count() > 0 {
+            return Err(format!("Parquet column '{}' contains nulls", name));
+        }
+        arrow_cast::cast(array, to).map_err(|e| format!("Parquet column '{}': {}", name, e))
+    };
+
+    let mut edges = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(|e| invalid(&e))?;
+        let sources = column(&batch, columns.source, &DataType::Utf8)?;
+        let targets = column(&batch, columns.target, &DataType::Utf8)?;
+        let weights = column(&batch, columns.weight, &DataType::Float64)?;
+        let sources = sources.as_any().downcast_ref::<StringArray>().unwrap();
+        let targets = targets.as_any().downcast_ref::<StringArray>().unwrap();
+        let weights = weights.as_any().downcast_ref::<Float64Array>().unwrap();
+        edges.par_extend((0..batch.num_rows()).into_par_iter().map(|i| {
+            (
+                sources.value(i).to_string(),
+                targets.value(i).to_string(),
+                weights.value(i),
+            )
+        }));
+    }
+    Ok(edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_edges_csv() {
+        let text = "weight,source,note,target\r\n\
+                    0.9,lat_pater,x,eng_father\r\n\
+                    \r\n\
+                    0.5,\"a, \"\"quoted\"\" id\",,b\r\n";
+        let edges = parse_edges_csv(text, EdgeColumns::default(), ',').unwrap();
+        assert_eq!(
+            edges,
+            vec![
+                ("lat_pater".to_string(), "eng_father".to_string(), 0.9),
+                ("a, \"quoted\" id".to_string(), "b".to_string(), 0.5),
+            ]
+        );
+
+        let columns = EdgeColumns {
+            source: "from",
+            ..EdgeColumns::default()
+        };
+        assert!(parse_edges_csv(text, columns, ',')
+            .unwrap_err()
+            .contains("no 'from' column"));
+        let text = "source\ttarget\tweight\na\tb\thigh\n";
+        let error = parse_edges_csv(text, EdgeColumns::default(), '\t');
+        assert_eq!(error.unwrap_err(), "CSV line 2: invalid weight 'high'");
+        assert_eq!(default_delimiter(Path::new("edges.tsv.gz")), '\t');
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_read_edges_parquet() {
+        use arrow_array::{Float32Array, Int64Array, RecordBatch, StringArray};
+        use std::sync::Arc;
+
+        let batch = RecordBatch::try_from_iter([
+            ("source", Arc::new(StringArray::from(vec!["a", "b"])) as _),
+            ("target", Arc::new(Int64Array::from(vec![7, 8])) as _),
+            ("score", Arc::new(Float32Array::from(vec![0.5, 0.25])) as _),
+        ])
+        .unwrap();
+        let path =
+            std::env::temp_dir().join(format!("langviz_edges_{}.parquet", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let columns = EdgeColumns {
+            weight: "score",
+            ..EdgeColumns::default()
+        };
+        let edges = read_edges_parquet(&path, columns);
+        let missing = read_edges_parquet(&path, EdgeColumns::default());
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            edges.unwrap(),
+            vec![
+                ("a".to_string(), "7".to_string(), 0.5),
+                ("b".to_string(), "8".to_string(), 0.25),
+            ]
+        );
+        assert!(missing.is_err());
+    }
+}
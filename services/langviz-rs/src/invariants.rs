@@ -0,0 +1,298 @@
+//! Kernel invariants checked on user data.
+//!
+//! The unit tests pin the similarity and alignment kernels down on a
+//! handful of forms; these validators run the same sanity checks over a
+//! real dataset so a pipeline can assert them before trusting the output:
+//!
+//! - similarity: every score finite and in [0, 1], `s(a, b) == s(b, a)`
+//!   and `s(a, a) == 1`
+//! - triangle: distances `1 - s` obey `d(a, c) <= d(a, b) + d(b, c)`.
+//!   Not every metric is a true metric (Jaro-Winkler is not), so this is a
+//!   report of how far a dataset strays rather than a bug check.
+//! - alignment: DTW and Needleman–Wunsch operation costs sum to the
+//!   reported cost, the DTW cost matches the path-free `dtw_cost`, and the
+//!   aligned sequences are the input segments with gaps where the
+//!   operations say.
+//!
+//! Each broken invariant is returned as a `Violation`; an empty result
+//! means the data passed.
+
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::phonetic::{
+    compute_similarity_matrix_with_metric, dtw_align, dtw_cost, nw_align, segment_ipa, AlignParams,
+    CostModel, SimilarityMetric,
+};
+use crate::types::{Alignment, EditOp};
+
+/// Gap symbol in aligned sequences
+const GAP: &str = "-";
+
+/// Excess of `d(a, c)` over `d(a, b) + d(b, c)`, with (a, b, c)
+type Excess = (f64, usize, usize, usize);
+
+/// One broken invariant
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// "range", "identity", "symmetry", "triangle", "alignment_cost" or
+    /// "alignment_path"
+    pub check: &'static str,
+    /// Forms involved; for "triangle", (a, b, c) with `d(a, c)` too long
+    pub forms: Vec<String>,
+    /// How far off the invariant is (0 for structural mismatches)
+    pub amount: f64,
+    pub detail: String,
+}
+
+impl Violation {
+    fn new(check: &'static str, forms: &[&str], amount: f64, detail: String) -> Self {
+        Self {
+            check,
+            forms: forms.iter().map(|s| s.to_string()).collect(),
+            amount,
+            detail,
+        }
+    }
+}
+
+/// Range and symmetry of `metric` on each pair, and identity on every form
+/// they mention; checked in parallel, violations in input order
+pub fn check_similarity(
+    pairs: &[(String, String)],
+    metric: SimilarityMetric,
+    tolerance: f64,
+) -> Vec<Violation> {
+    let forms: BTreeSet<&str> = pairs
+        .iter()
+        .flat_map(|(a, b)| [a.as_str(), b.as_str()])
+        .collect();
+    let forms: Vec<&str> = forms.into_iter().collect();
+    let mut violations: Vec<Violation> = forms
+        .par_iter()
+        .filter_map(|&form| {
+            let sim = metric.similarity(form, form);
+            let off = (sim - 1.0).abs();
+            (off.is_nan() || off > tolerance)
+                .then(|| Violation::new("identity", &[form], off, format!("s(a, a) = {}", sim)))
+        })
+        .collect();
+
+    let pair_violations: Vec<Violation> = pairs
+        .par_iter()
+        .flat_map_iter(|(a, b)| {
+            let (ab, ba) = (metric.similarity(a, b), metric.similarity(b, a));
+            let mut found = Vec::new();
+            let outside = (-ab).max(ab - 1.0);
+            if !ab.is_finite() || outside > tolerance {
+                let detail = format!("s(a, b) = {} outside [0, 1]", ab);
+                found.push(Violation::new("range", &[a, b], outside.abs(), detail));
+            }
+            let gap = (ab - ba).abs();
+            if gap.is_nan() || gap > tolerance {
+                let detail = format!("s(a, b) = {} but s(b, a) = {}", ab, ba);
+                found.push(Violation::new("symmetry", &[a, b], gap, detail));
+            }
+            found
+        })
+        .collect();
+    violations.extend(pair_violations);
+    violations
+}
+
+/// Triangle inequality of the distances `1 - s` over every triple of
+/// `forms`: the number of violating (a, b, c) and the `max_reports` worst,
+/// largest excess first
+pub fn triangle_violations(
+    forms: &[String],
+    metric: SimilarityMetric,
+    tolerance: f64,
+    max_reports: usize,
+) -> (usize, Vec<Violation>) {
+    let sim = compute_similarity_matrix_with_metric(forms, metric);
+    let n = forms.len();
+    let d = |i: usize, j: usize| 1.0 - sim[[i, j]];
+
+    // Per row: the count and its worst `max_reports`
+    let rows: Vec<(usize, Vec<Excess>)> = (0..n)
+        .into_par_iter()
+        .map(|a| {
+            let mut count = 0;
+            let mut worst = Vec::new();
+            for c in a + 1..n {
+                for b in (0..n).filter(|&b| b != a && b != c) {
+                    let excess = d(a, c) - d(a, b) - d(b, c);
+                    if excess > tolerance {
+                        count += 1;
+                        worst.push((excess, a, b, c));
+                    }
+                }
+            }
+            worst.sort_by(|x, y| y.0.total_cmp(&x.0));
+            worst.truncate(max_reports);
+            (count, worst)
+        })
+        .collect();
+
+    let count = rows.iter().map(|row| row.0).sum();
+    let mut worst: Vec<Excess> = rows.into_iter().flat_map(|row| row.1).collect();
+    worst.sort_by(|x, y| y.0.total_cmp(&x.0));
+    worst.truncate(max_reports);
+    let violations = worst
+        .into_iter()
+        .map(|(excess, a, b, c)| {
+            let detail = format!(
+                "d(a, c) = {} > d(a, b) + d(b, c) = {}",
+                d(a, c),
+                d(a, b) + d(b, c)
+            );
+            Violation::new(
+                "triangle",
+                &[&forms[a], &forms[b], &forms[c]],
+                excess,
+                detail,
+            )
+        })
+        .collect();
+    (count, violations)
+}
+
+/// Cost and path consistency of the default DTW and Needleman–Wunsch
+/// alignments of each pair, checked in parallel
+pub fn check_alignments(pairs: &[(String, String)], tolerance: f64) -> Vec<Violation> {
+    let params = AlignParams::default();
+    let costs = CostModel::default();
+    pairs
+        .par_iter()
+        .flat_map_iter(|(a, b)| {
+            let dtw = dtw_align(a, b);
+            let mut found = alignment_violations("dtw", a, b, &dtw, tolerance);
+            let direct = dtw_cost(a, b, &costs, None);
+            let gap = (direct - dtw.cost).abs();
+            if gap.is_nan() || gap > tolerance {
+                let detail = format!("dtw: path cost {} but dtw_cost {}", dtw.cost, direct);
+                found.push(Violation::new("alignment_cost", &[a, b], gap, detail));
+            }
+            let nw = nw_align(a, b, &params);
+            found.extend(alignment_violations("nw", a, b, &nw, tolerance));
+            found
+        })
+        .collect()
+}
+
+fn alignment_violations(
+    kind: &str,
+    a: &str,
+    b: &str,
+    alignment: &Alignment,
+    tolerance: f64,
+) -> Vec<Violation> {
+    let mut found = Vec::new();
+    let total: f64 = alignment.op_costs.iter().sum();
+    let gap = (total - alignment.cost).abs();
+    if gap.is_nan() || gap > tolerance {
+        let detail = format!(
+            "{}: op costs sum to {} but cost is {}",
+            kind, total, alignment.cost
+        );
+        found.push(Violation::new("alignment_cost", &[a, b], gap, detail));
+    }
+
+    let (segments_a, segments_b) = (segment_ipa(a), segment_ipa(b));
+    if segments_a.is_empty() || segments_b.is_empty() {
+        return found;
+    }
+    let n = alignment.operations.len();
+    let path_error = if alignment.sequence_a.len() != n
+        || alignment.sequence_b.len() != n
+        || alignment.op_costs.len() != n
+    {
+        Some(format!(
+            "{}: {} operations for aligned lengths {} and {}",
+            kind,
+            n,
+            alignment.sequence_a.len(),
+            alignment.sequence_b.len()
+        ))
+    } else if let Some(step) = (0..n).find(|&k| {
+        let (x, y) = (&alignment.sequence_a[k], &alignment.sequence_b[k]);
+        match alignment.operations[k] {
+            EditOp::Match => x != y || x == GAP,
+            EditOp::Substitute => x == y || x == GAP || y == GAP,
+            EditOp::Insert => x != GAP || y == GAP,
+            EditOp::Delete => x == GAP || y != GAP,
+        }
+    }) {
+        Some(format!(
+            "{}: operation {} at step {} doesn't fit {} / {}",
+            kind,
+            alignment.operations[step].code(),
+            step,
+            alignment.sequence_a[step],
+            alignment.sequence_b[step]
+        ))
+    } else {
+        let ungapped = |sequence: &[String]| -> Vec<String> {
+            sequence.iter().filter(|s| *s != GAP).cloned().collect()
+        };
+        (ungapped(&alignment.sequence_a) != segments_a
+            || ungapped(&alignment.sequence_b) != segments_b)
+            .then(|| format!("{}: aligned sequences don't spell the input segments", kind))
+    };
+    if let Some(detail) = path_error {
+        found.push(Violation::new("alignment_path", &[a, b], 0.0, detail));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(forms: &[&str]) -> Vec<String> {
+        forms.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_invariants_on_real_forms() {
+        let forms = strings(&["pater", "fater", "mater", "vater", "padre", "otac"]);
+        let pairs: Vec<(String, String)> = forms
+            .iter()
+            .flat_map(|a| forms.iter().map(move |b| (a.clone(), b.clone())))
+            .collect();
+        for metric in ["levenshtein", "lcs", "jaro_winkler"] {
+            let metric: SimilarityMetric = metric.parse().unwrap();
+            assert!(check_similarity(&pairs, metric, 1e-9).is_empty());
+        }
+        assert!(check_alignments(&pairs, 1e-9).is_empty());
+
+        // A broken alignment is caught on cost and path
+        let mut alignment = nw_align("pater", "padre", &AlignParams::default());
+        alignment.cost += 1.0;
+        alignment.sequence_b.swap(0, 1);
+        let found = alignment_violations("nw", "pater", "padre", &alignment, 1e-9);
+        let checks: Vec<&str> = found.iter().map(|v| v.check).collect();
+        assert_eq!(checks, ["alignment_cost", "alignment_path"]);
+        assert!((found[0].amount - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_triangle_violations() {
+        let forms = strings(&["ab", "abxy", "xy", "ba", "pater"]);
+        let metric: SimilarityMetric = "levenshtein".parse().unwrap();
+        let (count, worst) = triangle_violations(&forms, metric, 1e-9, 2);
+        assert!(worst.len() == count.min(2));
+        for pair in worst.windows(2) {
+            assert!(pair[0].amount >= pair[1].amount);
+        }
+
+        // Jaro-Winkler is not a metric
+        let metric: SimilarityMetric = "jaro_winkler".parse().unwrap();
+        let (count, worst) = triangle_violations(&forms, metric, 1e-9, usize::MAX);
+        assert!(count > 0);
+        assert_eq!(worst.len(), count);
+        assert!(worst
+            .iter()
+            .all(|v| v.check == "triangle" && v.forms.len() == 3));
+    }
+}
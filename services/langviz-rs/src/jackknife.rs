@@ -0,0 +1,392 @@
+//! Jackknife support for the groupings of a lexicostatistical tree.
+//!
+//! Each replicate drops a random share of the languages or of the concepts,
+//! recomputes the distances and rebuilds the tree by UPGMA or
+//! Neighbor-Joining. A clade of the full-data tree is supported by a
+//! replicate when the replicate tree, read as unrooted, has the split the
+//! clade makes among the languages the replicate kept; replicates where that
+//! split is trivial (fewer than two kept members on either side) cannot test
+//! it and are not counted. Each replicate's normalized Robinson–Foulds
+//! distance to the full tree over its languages sums up how much the whole
+//! topology moved. Language pairs without a shared concept are taken as
+//! distance 1. Replicate `i` is drawn with seed `seed + i`, so runs are
+//! reproducible whatever the thread count.
+
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use crate::lexicostat::{CognateTable, ConceptWeights};
+use crate::tree::Tree;
+
+/// What each replicate drops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JackknifeUnit {
+    Languages,
+    Concepts,
+}
+
+impl FromStr for JackknifeUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "languages" => Ok(JackknifeUnit::Languages),
+            "concepts" => Ok(JackknifeUnit::Concepts),
+            other => Err(format!(
+                "unknown jackknife unit '{}' (expected languages or concepts)",
+                other
+            )),
+        }
+    }
+}
+
+/// Distance-based tree builder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeMethod {
+    Upgma,
+    NeighborJoining,
+}
+
+impl FromStr for TreeMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "upgma" => Ok(TreeMethod::Upgma),
+            "nj" => Ok(TreeMethod::NeighborJoining),
+            other => Err(format!(
+                "unknown tree method '{}' (expected upgma or nj)",
+                other
+            )),
+        }
+    }
+}
+
+impl TreeMethod {
+    fn build(self, ids: &[String], distances: &Array2<f64>) -> Result<Tree, String> {
+        match self {
+            TreeMethod::Upgma => Tree::upgma(ids, distances.view(), None),
+            TreeMethod::NeighborJoining => Tree::neighbor_joining(ids, distances.view(), None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JackknifeParams {
+    pub unit: JackknifeUnit,
+    pub method: TreeMethod,
+    /// Share of the units dropped per replicate (at least one)
+    pub drop_fraction: f64,
+    pub replicates: usize,
+    pub seed: u64,
+}
+
+impl Default for JackknifeParams {
+    fn default() -> Self {
+        Self {
+            unit: JackknifeUnit::Languages,
+            method: TreeMethod::Upgma,
+            drop_fraction: 0.1,
+            replicates: 100,
+            seed: 0,
+        }
+    }
+}
+
+/// A clade of the full-data tree and how often replicates recover it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CladeSupport {
+    pub members: Vec<String>,
+    /// Replicates that could test the clade
+    pub tested: usize,
+    /// Of those, replicates whose tree has it
+    pub recovered: usize,
+}
+
+impl CladeSupport {
+    /// `recovered` / `tested`, NaN when no replicate tested the clade
+    pub fn support(&self) -> f64 {
+        self.recovered as f64 / self.tested as f64
+    }
+}
+
+/// Full-data tree with jackknife support for its clades
+#[derive(Debug, Clone)]
+pub struct Jackknife {
+    pub tree: Tree,
+    /// Clades of the tree with 2 to n - 2 languages, in preorder
+    pub clades: Vec<CladeSupport>,
+    /// Normalized Robinson–Foulds distance of each replicate tree to the
+    /// full tree over its languages
+    pub robinson_foulds: Vec<f64>,
+}
+
+/// Jackknife the lexicostatistical tree of (language, concept, cognate
+/// class) judgments
+pub fn jackknife(
+    judgments: &[(String, String, String)],
+    weights: &ConceptWeights,
+    params: &JackknifeParams,
+) -> Result<Jackknife, String> {
+    if !(params.drop_fraction > 0.0 && params.drop_fraction < 1.0) {
+        return Err(format!(
+            "drop_fraction must be in (0, 1), got {}",
+            params.drop_fraction
+        ));
+    }
+    let table = CognateTable::from_judgments(judgments);
+    let languages = table.languages();
+    let n = languages.len();
+    let units = match params.unit {
+        JackknifeUnit::Languages => n,
+        JackknifeUnit::Concepts => table.concepts().len(),
+    };
+    let dropped = ((units as f64 * params.drop_fraction).round() as usize).max(1);
+    let (minimum, name) = match params.unit {
+        JackknifeUnit::Languages => (4, "languages"),
+        JackknifeUnit::Concepts => (1, "concepts"),
+    };
+    if n < 4 || units < dropped + minimum {
+        return Err(format!(
+            "dropping {} of {} {} leaves too little to build trees from",
+            dropped, units, name
+        ));
+    }
+
+    let distances = table
+        .distance_matrix(weights)
+        .mapv(|d| if d.is_nan() { 1.0 } else { d });
+    let tree = params.method.build(languages, &distances)?;
+    let index: HashMap<&str, usize> = languages
+        .iter()
+        .enumerate()
+        .map(|(i, language)| (language.as_str(), i))
+        .collect();
+    let clades: Vec<Vec<usize>> = below_sets(&tree, &index)
+        .into_iter()
+        .filter(|members| members.len() >= 2 && members.len() + 2 <= n)
+        .collect();
+    let all: Vec<usize> = (0..n).collect();
+
+    let outcomes: Vec<(Vec<Option<bool>>, f64)> = (0..params.replicates)
+        .into_par_iter()
+        .map(|replicate| {
+            let mut rng = StdRng::seed_from_u64(params.seed.wrapping_add(replicate as u64));
+            let (kept, distances) = match params.unit {
+                JackknifeUnit::Languages => {
+                    let mut kept = index::sample(&mut rng, n, n - dropped).into_vec();
+                    kept.sort_unstable();
+                    let distances = Array2::from_shape_fn((kept.len(), kept.len()), |(i, j)| {
+                        distances[[kept[i], kept[j]]]
+                    });
+                    (kept, distances)
+                }
+                JackknifeUnit::Concepts => {
+                    let concepts = table.concepts();
+                    let gone: HashSet<usize> = index::sample(&mut rng, units, dropped)
+                        .into_iter()
+                        .collect();
+                    let reweighted = concepts
+                        .iter()
+                        .enumerate()
+                        .map(|(c, concept)| {
+                            let weight = if gone.contains(&c) {
+                                0.0
+                            } else {
+                                weights.weight(concept)
+                            };
+                            (concept.clone(), weight)
+                        })
+                        .collect();
+                    let distances = table
+                        .distance_matrix(&ConceptWeights::new(reweighted, 0.0))
+                        .mapv(|d| if d.is_nan() { 1.0 } else { d });
+                    (all.clone(), distances)
+                }
+            };
+            let ids: Vec<String> = kept.iter().map(|&i| languages[i].clone()).collect();
+            let replicate_tree = params.method.build(&ids, &distances)?;
+            let found = split_set(&below_sets(&replicate_tree, &index), &kept);
+
+            let tests = clades
+                .iter()
+                .map(|clade| split(clade, &kept).map(|side| found.contains(&side)))
+                .collect();
+            let expected = split_set(&clades, &kept);
+            let total = expected.len() + found.len();
+            let differing = expected.symmetric_difference(&found).count();
+            let normalized = if total == 0 {
+                0.0
+            } else {
+                differing as f64 / total as f64
+            };
+            Ok((tests, normalized))
+        })
+        .collect::<Result<_, String>>()?;
+
+    let supports = clades
+        .iter()
+        .enumerate()
+        .map(|(c, members)| {
+            let tests = outcomes.iter().filter_map(|(tests, _)| tests[c]);
+            CladeSupport {
+                members: members.iter().map(|&i| languages[i].clone()).collect(),
+                tested: tests.clone().count(),
+                recovered: tests.filter(|&found| found).count(),
+            }
+        })
+        .collect();
+    Ok(Jackknife {
+        tree,
+        clades: supports,
+        robinson_foulds: outcomes.into_iter().map(|(_, rf)| rf).collect(),
+    })
+}
+
+/// Sorted language indices below each non-root node, in preorder
+fn below_sets(tree: &Tree, index: &HashMap<&str, usize>) -> Vec<Vec<usize>> {
+    let order = tree.preorder();
+    let mut below: Vec<Vec<usize>> = vec![Vec::new(); tree.nodes.len()];
+    for &node in order.iter().rev() {
+        if tree.nodes[node].children.is_empty() {
+            below[node].push(index[tree.node_name(node).as_str()]);
+        }
+        if let Some(parent) = tree.nodes[node].parent {
+            let members = below[node].clone();
+            below[parent].extend(members);
+        }
+    }
+    order
+        .into_iter()
+        .filter(|&node| node != tree.root)
+        .map(|node| {
+            let mut members = std::mem::take(&mut below[node]);
+            members.sort_unstable();
+            members
+        })
+        .collect()
+}
+
+/// Side of the split `members` makes among `kept` (sorted) that leaves out
+/// `kept[0]`, or `None` when either side has fewer than two languages
+fn split(members: &[usize], kept: &[usize]) -> Option<Vec<usize>> {
+    let inside: Vec<usize> = kept
+        .iter()
+        .copied()
+        .filter(|language| members.binary_search(language).is_ok())
+        .collect();
+    if inside.len() < 2 || inside.len() + 2 > kept.len() {
+        return None;
+    }
+    if inside[0] != kept[0] {
+        return Some(inside);
+    }
+    Some(
+        kept.iter()
+            .copied()
+            .filter(|language| inside.binary_search(language).is_err())
+            .collect(),
+    )
+}
+
+fn split_set(clades: &[Vec<usize>], kept: &[usize]) -> HashSet<Vec<usize>> {
+    clades
+        .iter()
+        .filter_map(|clade| split(clade, kept))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two clear subgroups {a, b, c} and {d, e, f}, with g loosely closer
+    /// to the second
+    fn judgments() -> Vec<(String, String, String)> {
+        let classes = [
+            ("a", "1111111"),
+            ("b", "1111112"),
+            ("c", "1111121"),
+            ("d", "2222222"),
+            ("e", "2222223"),
+            ("f", "2222232"),
+            ("g", "3322333"),
+        ];
+        classes
+            .iter()
+            .flat_map(|(language, row)| {
+                row.chars().enumerate().map(move |(concept, class)| {
+                    (
+                        language.to_string(),
+                        format!("c{}", concept),
+                        class.to_string(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_language_jackknife() {
+        let params = JackknifeParams {
+            replicates: 20,
+            seed: 7,
+            ..Default::default()
+        };
+        let result = jackknife(&judgments(), &ConceptWeights::default(), &params).unwrap();
+        assert_eq!(result.robinson_foulds.len(), 20);
+
+        let abc = result
+            .clades
+            .iter()
+            .find(|clade| clade.members == ["a", "b", "c"])
+            .unwrap();
+        assert!(abc.tested > 0 && abc.tested <= 20);
+        assert_eq!(abc.support(), 1.0);
+
+        let again = jackknife(&judgments(), &ConceptWeights::default(), &params).unwrap();
+        assert_eq!(again.clades, result.clades);
+        assert_eq!(again.robinson_foulds, result.robinson_foulds);
+    }
+
+    #[test]
+    fn test_concept_jackknife() {
+        let params = JackknifeParams {
+            unit: JackknifeUnit::Concepts,
+            method: TreeMethod::NeighborJoining,
+            drop_fraction: 0.3,
+            replicates: 10,
+            seed: 1,
+        };
+        let result = jackknife(&judgments(), &ConceptWeights::default(), &params).unwrap();
+        assert!(result.clades.iter().all(|clade| clade.tested == 10));
+        let def = result
+            .clades
+            .iter()
+            .find(|clade| clade.members == ["a", "b", "c"] || clade.members == ["d", "e", "f"])
+            .unwrap();
+        assert_eq!(def.support(), 1.0);
+    }
+
+    #[test]
+    fn test_rejects_bad_parameters() {
+        let weights = ConceptWeights::default();
+        let params = JackknifeParams {
+            drop_fraction: 1.0,
+            ..Default::default()
+        };
+        assert!(jackknife(&judgments(), &weights, &params).is_err());
+        let params = JackknifeParams {
+            drop_fraction: 0.9,
+            ..Default::default()
+        };
+        assert!(jackknife(&judgments(), &weights, &params).is_err());
+        assert!("bootstrap".parse::<JackknifeUnit>().is_err());
+        assert!("nj".parse::<TreeMethod>() == Ok(TreeMethod::NeighborJoining));
+    }
+}
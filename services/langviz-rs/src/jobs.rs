@@ -0,0 +1,309 @@
+//! Background jobs for the heaviest kernels.
+//!
+//! `Job::spawn` runs a closure on the rayon pool and returns at once; the
+//! caller polls `status` and `progress`, or blocks in `wait` (the Python
+//! bindings release the GIL there, so async web handlers can await a job
+//! from a worker thread). The closure reports progress through
+//! `JobProgress::checkpoint`, which is also where a requested cancellation
+//! takes effect. A panicking job is reported as failed instead of leaving
+//! its waiters hanging.
+
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::phonetic::{SimilarityBlock, SimilarityBlocks};
+
+/// Lifecycle of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Submitted, waiting for a pool thread
+    Pending,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Pending | JobStatus::Running)
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a job produced no result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobError {
+    Cancelled,
+    Failed(String),
+}
+
+impl From<String> for JobError {
+    fn from(message: String) -> Self {
+        JobError::Failed(message)
+    }
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::Cancelled => f.write_str("job was cancelled"),
+            JobError::Failed(message) => write!(f, "job failed: {}", message),
+        }
+    }
+}
+
+/// Progress counters and cancellation flag shared with the running job
+#[derive(Debug, Default)]
+pub struct JobProgress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl JobProgress {
+    /// Record `done` of `total` units; Err once cancellation was requested
+    pub fn checkpoint(&self, done: usize, total: usize) -> Result<(), JobError> {
+        self.total.store(total, Ordering::Relaxed);
+        self.done.store(done, Ordering::Relaxed);
+        if self.is_cancelled() {
+            Err(JobError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// (done, total) units as last reported
+    pub fn get(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Make the next checkpoint fail with `JobError::Cancelled`
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+enum Outcome<T> {
+    Pending,
+    Running,
+    /// Done; None once the value was taken
+    Done(Option<T>),
+    Failed(String),
+    Cancelled,
+}
+
+impl<T> Outcome<T> {
+    fn status(&self) -> JobStatus {
+        match self {
+            Outcome::Pending => JobStatus::Pending,
+            Outcome::Running => JobStatus::Running,
+            Outcome::Done(_) => JobStatus::Done,
+            Outcome::Failed(_) => JobStatus::Failed,
+            Outcome::Cancelled => JobStatus::Cancelled,
+        }
+    }
+}
+
+struct JobState<T> {
+    outcome: Mutex<Outcome<T>>,
+    finished: Condvar,
+    progress: JobProgress,
+}
+
+impl<T> JobState<T> {
+    fn lock(&self) -> MutexGuard<'_, Outcome<T>> {
+        self.outcome.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn finish(&self, outcome: Outcome<T>) {
+        *self.lock() = outcome;
+        self.finished.notify_all();
+    }
+}
+
+/// Handle on a closure running in the background
+pub struct Job<T> {
+    state: Arc<JobState<T>>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    /// Start `work` on the rayon pool
+    pub fn spawn<F>(work: F) -> Self
+    where
+        F: FnOnce(&JobProgress) -> Result<T, JobError> + Send + 'static,
+    {
+        let state = Arc::new(JobState {
+            outcome: Mutex::new(Outcome::Pending),
+            finished: Condvar::new(),
+            progress: JobProgress::default(),
+        });
+        let job_state = Arc::clone(&state);
+        rayon::spawn(move || {
+            let state = job_state;
+            {
+                let mut outcome = state.lock();
+                if state.progress.is_cancelled() {
+                    *outcome = Outcome::Cancelled;
+                    state.finished.notify_all();
+                    return;
+                }
+                *outcome = Outcome::Running;
+            }
+            let result = catch_unwind(AssertUnwindSafe(|| work(&state.progress)));
+            state.finish(match result {
+                Ok(Ok(value)) => Outcome::Done(Some(value)),
+                Ok(Err(JobError::Cancelled)) => Outcome::Cancelled,
+                Ok(Err(JobError::Failed(message))) => Outcome::Failed(message),
+                Err(panic) => Outcome::Failed(panic_message(&*panic)),
+            });
+        });
+        Self { state }
+    }
+}
+
+impl<T> Job<T> {
+    pub fn status(&self) -> JobStatus {
+        self.state.lock().status()
+    }
+
+    /// (done, total) units as last reported by the job
+    pub fn progress(&self) -> (usize, usize) {
+        self.state.progress.get()
+    }
+
+    /// Ask the job to stop at its next checkpoint; a job that hasn't
+    /// started yet never runs
+    pub fn cancel(&self) {
+        self.state.progress.cancel();
+    }
+
+    /// Block until the job finishes or `timeout` passes; true if finished
+    pub fn wait(&self, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut outcome = self.state.lock();
+        while !outcome.status().is_finished() {
+            let finished = &self.state.finished;
+            outcome = match deadline {
+                None => finished
+                    .wait(outcome)
+                    .unwrap_or_else(PoisonError::into_inner),
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return false;
+                    }
+                    let waited = finished.wait_timeout(outcome, left);
+                    waited.unwrap_or_else(PoisonError::into_inner).0
+                }
+            };
+        }
+        true
+    }
+
+    /// Result of a finished job: the value the first time, the error every
+    /// time; None while it is still running or after the value was taken
+    pub fn take(&self) -> Option<Result<T, JobError>> {
+        match &mut *self.state.lock() {
+            Outcome::Done(value) => value.take().map(Ok),
+            Outcome::Failed(message) => Some(Err(JobError::Failed(message.clone()))),
+            Outcome::Cancelled => Some(Err(JobError::Cancelled)),
+            Outcome::Pending | Outcome::Running => None,
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked".to_string())
+}
+
+/// Drive similarity blocks to completion, reporting pairs done as progress
+/// and stopping between blocks once cancelled
+pub fn run_blocks(
+    blocks: SimilarityBlocks<'_>,
+    progress: &JobProgress,
+    mut sink: impl FnMut(SimilarityBlock),
+) -> Result<(), JobError> {
+    for block in blocks {
+        let (done, total) = (block.done, block.total);
+        sink(block);
+        progress.checkpoint(done, total)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phonetic::similarity_blocks;
+
+    #[test]
+    fn test_job_lifecycle() {
+        let forms: Vec<String> = ["pater", "fater", "mater", "vasa"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let job = Job::spawn(move |progress| {
+            let mut pairs = Vec::new();
+            run_blocks(similarity_blocks(&forms, 1, 0.5), progress, |block| {
+                pairs.extend(block.pairs)
+            })?;
+            Ok(pairs)
+        });
+        assert!(job.wait(None));
+        assert_eq!(job.status(), JobStatus::Done);
+        assert_eq!(job.progress(), (6, 6));
+        assert!(!job.take().unwrap().unwrap().is_empty());
+        assert!(job.take().is_none());
+
+        // Cancelled at the first checkpoint after the request
+        let (started, release) = std::sync::mpsc::channel();
+        let (resume, gate) = std::sync::mpsc::channel::<()>();
+        let job: Job<()> = Job::spawn(move |progress| {
+            started.send(()).unwrap();
+            gate.recv().unwrap();
+            progress.checkpoint(1, 2)?;
+            Ok(())
+        });
+        release.recv().unwrap();
+        assert_eq!(job.status(), JobStatus::Running);
+        job.cancel();
+        resume.send(()).unwrap();
+        assert!(job.wait(Some(Duration::from_secs(10))));
+        assert_eq!(job.take(), Some(Err(JobError::Cancelled)));
+
+        let job: Job<()> = Job::spawn(|_| panic!("boom"));
+        job.wait(None);
+        assert_eq!(job.status(), JobStatus::Failed);
+        assert_eq!(job.take(), Some(Err(JobError::Failed("boom".to_string()))));
+    }
+}
@@ -0,0 +1,139 @@
+//! k-core decomposition: separating tight cognate cores from peripheral
+//! attachments.
+//!
+//! The k-core is the largest subgraph in which every node has at least `k`
+//! neighbors; a node's core number is the largest `k` whose core contains
+//! it. Computed with the Batagelj–Zaversnik bucket algorithm in O(m).
+//! Degrees count distinct neighbors, ignoring self-loops and edge weights.
+
+use crate::graph::CognateGraph;
+
+impl CognateGraph {
+    /// Distinct non-self neighbors per node index
+    fn simple_adjacency(&self) -> Vec<Vec<usize>> {
+        self.graph
+            .node_indices()
+            .map(|idx| {
+                let mut neighbors: Vec<usize> = self
+                    .graph
+                    .neighbors(idx)
+                    .filter(|&n| n != idx)
+                    .map(|n| n.index())
+                    .collect();
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                neighbors
+            })
+            .collect()
+    }
+
+    /// Core number per node index
+    pub(crate) fn core_number_scores(&self) -> Vec<usize> {
+        let adjacency = self.simple_adjacency();
+        let n = adjacency.len();
+        let mut degree: Vec<usize> = adjacency.iter().map(Vec::len).collect();
+        let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+        // Nodes sorted by degree, with the start of each degree's bucket
+        let mut bucket_start = vec![0usize; max_degree + 2];
+        for &d in &degree {
+            bucket_start[d + 1] += 1;
+        }
+        for d in 1..bucket_start.len() {
+            bucket_start[d] += bucket_start[d - 1];
+        }
+        let mut order = vec![0usize; n];
+        let mut position = vec![0usize; n];
+        let mut next = bucket_start.clone();
+        for (node, &d) in degree.iter().enumerate() {
+            position[node] = next[d];
+            order[next[d]] = node;
+            next[d] += 1;
+        }
+
+        for i in 0..n {
+            let node = order[i];
+            for &neighbor in &adjacency[node] {
+                if degree[neighbor] > degree[node] {
+                    // Move the neighbor to the front of its bucket, then
+                    // shrink the bucket past it
+                    let d = degree[neighbor];
+                    let front = bucket_start[d];
+                    let swapped = order[front];
+                    if swapped != neighbor {
+                        order.swap(front, position[neighbor]);
+                        position[swapped] = position[neighbor];
+                        position[neighbor] = front;
+                    }
+                    bucket_start[d] += 1;
+                    degree[neighbor] -= 1;
+                }
+            }
+        }
+
+        degree
+    }
+
+    /// (node ID, core number) for every node, in node order
+    pub fn core_numbers(&self) -> Vec<(String, usize)> {
+        self.graph
+            .node_indices()
+            .zip(self.core_number_scores())
+            .map(|(idx, core)| (self.graph[idx].clone(), core))
+            .collect()
+    }
+
+    /// Induced subgraph of the nodes with core number at least `k`;
+    /// attributes and provenance are kept
+    pub fn k_core(&self, k: usize) -> CognateGraph {
+        let keep: Vec<bool> = self.core_number_scores().iter().map(|&c| c >= k).collect();
+        self.subgraph(&keep, |_| true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    /// A 4-clique {a, b, c, d} with a tail d - e - f and a pendant g on a
+    fn graph() -> CognateGraph {
+        let pairs = [
+            ("a", "b"),
+            ("a", "c"),
+            ("a", "d"),
+            ("b", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("a", "g"),
+        ];
+        let edges = pairs
+            .iter()
+            .map(|(s, t)| SimilarityEdge::new(s.to_string(), t.to_string(), 0.9))
+            .collect();
+        CognateGraph::from_edges(edges, 0.5)
+    }
+
+    #[test]
+    fn test_core_numbers() {
+        let cores: std::collections::HashMap<String, usize> =
+            graph().core_numbers().into_iter().collect();
+        for id in ["a", "b", "c", "d"] {
+            assert_eq!(cores[id], 3);
+        }
+        for id in ["e", "f", "g"] {
+            assert_eq!(cores[id], 1);
+        }
+    }
+
+    #[test]
+    fn test_k_core() {
+        let core = graph().k_core(3);
+        let stats = core.stats();
+        assert_eq!((stats.num_nodes, stats.num_edges), (4, 6));
+        assert_eq!(graph().k_core(1).stats().num_nodes, 7);
+        assert_eq!(graph().k_core(4).stats().num_nodes, 0);
+    }
+}
@@ -0,0 +1,262 @@
+//! Batch distance kernels with a selectable compute backend.
+//!
+//! The two massively parallel workloads — unit-cost edit distance over
+//! integer-encoded segments and cosine similarity between embedding rows —
+//! run on the CPU (rayon) by default. Built with the `gpu` feature, the
+//! same kernels can run as wgpu compute shaders (`gpu` module): one shader
+//! invocation per pair, for million-pair jobs. Edit distances are exact
+//! integers on either backend; GPU cosines are computed in f32.
+//!
+//! The edit distance takes an optional Sakoe-Chiba `band` (widened to the
+//! length difference, as in `dtw_cost`): only cells within that many
+//! segments of the diagonal are filled, so a banded distance is an upper
+//! bound that equals the full one whenever the optimal path stays inside.
+
+use ahash::AHashMap;
+use ndarray::ArrayView2;
+use rayon::prelude::*;
+use std::str::FromStr;
+
+use crate::phonetic::segment_ipa;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Cpu,
+    /// wgpu compute shaders; needs the `gpu` feature and an adapter
+    Gpu,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cpu" => Ok(Backend::Cpu),
+            "gpu" => Ok(Backend::Gpu),
+            other => Err(format!("unknown backend '{}' (expected cpu or gpu)", other)),
+        }
+    }
+}
+
+/// Whether the GPU backend can run here: built with `gpu` and an adapter
+/// found
+pub fn gpu_available() -> bool {
+    #[cfg(feature = "gpu")]
+    {
+        crate::gpu::available()
+    }
+    #[cfg(not(feature = "gpu"))]
+    {
+        false
+    }
+}
+
+/// Segments of every pair as integer codes from one shared symbol table
+pub fn encode_pairs(pairs: &[(String, String)]) -> Vec<(Vec<u32>, Vec<u32>)> {
+    let segmented: Vec<(Vec<String>, Vec<String>)> = pairs
+        .par_iter()
+        .map(|(a, b)| (segment_ipa(a), segment_ipa(b)))
+        .collect();
+    let mut symbols: AHashMap<String, u32> = AHashMap::new();
+    let mut encode = |segments: Vec<String>| -> Vec<u32> {
+        segments
+            .into_iter()
+            .map(|segment| {
+                let next = symbols.len() as u32;
+                *symbols.entry(segment).or_insert(next)
+            })
+            .collect()
+    };
+    segmented
+        .into_iter()
+        .map(|(a, b)| (encode(a), encode(b)))
+        .collect()
+}
+
+/// Unit-cost edit distance of two code sequences within `band` of the
+/// diagonal (`None` for the full matrix)
+pub fn banded_edit_distance(a: &[u32], b: &[u32], band: Option<usize>) -> u32 {
+    const UNREACHED: u32 = u32::MAX / 2;
+    let (len_a, len_b) = (a.len(), b.len());
+    let band = band.map_or(len_a.max(len_b), |band| band.max(len_a.abs_diff(len_b)));
+
+    let mut prev: Vec<u32> = (0..=len_b)
+        .map(|j| if j <= band { j as u32 } else { UNREACHED })
+        .collect();
+    let mut curr = vec![UNREACHED; len_b + 1];
+    for i in 1..=len_a {
+        curr.fill(UNREACHED);
+        if i <= band {
+            curr[0] = i as u32;
+        }
+        for j in i.saturating_sub(band).max(1)..=(i + band).min(len_b) {
+            let substitute = prev[j - 1] + u32::from(a[i - 1] != b[j - 1]);
+            curr[j] = substitute.min(prev[j] + 1).min(curr[j - 1] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[len_b]
+}
+
+/// `banded_edit_distance` of every pair on `backend`
+pub fn batch_banded_edit_distance(
+    pairs: &[(Vec<u32>, Vec<u32>)],
+    band: Option<usize>,
+    backend: Backend,
+) -> Result<Vec<u32>, String> {
+    match backend {
+        Backend::Cpu => Ok(pairs
+            .par_iter()
+            .map(|(a, b)| banded_edit_distance(a, b, band))
+            .collect()),
+        #[cfg(feature = "gpu")]
+        Backend::Gpu => {
+            // Pairs too long for the shader's fixed rows stay on the CPU
+            let fits =
+                |(a, b): &(Vec<u32>, Vec<u32>)| a.len().max(b.len()) <= crate::gpu::MAX_SEGMENTS;
+            let short: Vec<(Vec<u32>, Vec<u32>)> =
+                pairs.iter().filter(|p| fits(p)).cloned().collect();
+            let mut shader = crate::gpu::banded_edit_distances(&short, band)?.into_iter();
+            Ok(pairs
+                .iter()
+                .map(|pair| {
+                    if fits(pair) {
+                        shader.next().unwrap_or_default()
+                    } else {
+                        banded_edit_distance(&pair.0, &pair.1, band)
+                    }
+                })
+                .collect())
+        }
+        #[cfg(not(feature = "gpu"))]
+        Backend::Gpu => Err(gpu_missing()),
+    }
+}
+
+/// Similarity per IPA pair from the banded edit distance, normalized like
+/// `phonetic_distance`: one minus distance over the longer segment count
+pub fn batch_banded_similarity(
+    pairs: &[(String, String)],
+    band: Option<usize>,
+    backend: Backend,
+) -> Result<Vec<f64>, String> {
+    let encoded = encode_pairs(pairs);
+    let distances = batch_banded_edit_distance(&encoded, band, backend)?;
+    Ok(encoded
+        .iter()
+        .zip(distances)
+        .map(|((a, b), distance)| match a.len().max(b.len()) {
+            0 => 1.0,
+            longest => (1.0 - distance as f64 / longest as f64).max(0.0),
+        })
+        .collect())
+}
+
+/// Cosine similarity of embedding rows (i, j) for every pair; 0 when
+/// either row is all zeros
+pub fn batch_cosine_similarity(
+    embeddings: ArrayView2<f64>,
+    pairs: &[(usize, usize)],
+    backend: Backend,
+) -> Result<Vec<f64>, String> {
+    let rows = embeddings.nrows();
+    if let Some(&(i, j)) = pairs.iter().find(|&&(i, j)| i >= rows || j >= rows) {
+        return Err(format!(
+            "pair ({}, {}) is out of range for {} embedding rows",
+            i, j, rows
+        ));
+    }
+    match backend {
+        Backend::Cpu => Ok(pairs
+            .par_iter()
+            .map(|&(i, j)| {
+                let (a, b) = (embeddings.row(i), embeddings.row(j));
+                let norms = a.dot(&a) * b.dot(&b);
+                if norms > 0.0 {
+                    a.dot(&b) / norms.sqrt()
+                } else {
+                    0.0
+                }
+            })
+            .collect()),
+        #[cfg(feature = "gpu")]
+        Backend::Gpu => crate::gpu::cosine_similarities(embeddings, pairs),
+        #[cfg(not(feature = "gpu"))]
+        Backend::Gpu => Err(gpu_missing()),
+    }
+}
+
+#[cfg(not(feature = "gpu"))]
+fn gpu_missing() -> String {
+    "the gpu backend needs langviz-core built with the gpu feature".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phonetic::levenshtein;
+    use ndarray::array;
+
+    #[test]
+    fn test_banded_edit_distance() {
+        let pairs: Vec<(String, String)> = [("pater", "fater"), ("tres", "θriː"), ("", "abc")]
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect();
+        let encoded = encode_pairs(&pairs);
+        // Shared codes: the t of "pater" and of "tres" agree
+        assert_eq!(encoded[0].0[2], encoded[1].0[0]);
+        for (a, b) in &encoded {
+            assert_eq!(banded_edit_distance(a, b, None) as usize, levenshtein(a, b));
+        }
+
+        // The cheap path (two deletions, two insertions) strays two cells
+        // off the diagonal, so a band of one overestimates
+        let (a, b) = ([1, 2, 3, 4, 5, 6], [3, 4, 5, 6, 7, 8]);
+        assert_eq!(banded_edit_distance(&a, &b, None), 4);
+        assert_eq!(banded_edit_distance(&a, &b, Some(2)), 4);
+        assert_eq!(banded_edit_distance(&a, &b, Some(1)), 6);
+
+        let similarities = batch_banded_similarity(&pairs, None, Backend::Cpu).unwrap();
+        assert_eq!(similarities[0], 0.8);
+        assert_eq!(similarities[2], 0.0);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        let embeddings = array![[1.0, 0.0], [1.0, 1.0], [0.0, 0.0], [-2.0, 0.0]];
+        let pairs = [(0, 1), (0, 2), (0, 3), (1, 1)];
+        let similarities =
+            batch_cosine_similarity(embeddings.view(), &pairs, Backend::Cpu).unwrap();
+        assert!((similarities[0] - 0.5f64.sqrt()).abs() < 1e-12);
+        assert_eq!(&similarities[1..], [0.0, -1.0, 1.0]);
+        assert!(batch_cosine_similarity(embeddings.view(), &[(0, 4)], Backend::Cpu).is_err());
+        assert!("tpu".parse::<Backend>().is_err());
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn test_gpu_matches_cpu() {
+        if !gpu_available() {
+            return;
+        }
+        let words = ["pater", "fater", "tres", "θriː", "", "kornu", "hornaz"];
+        let pairs: Vec<(String, String)> = words
+            .iter()
+            .flat_map(|a| words.iter().map(move |b| (a.to_string(), b.to_string())))
+            .collect();
+        let encoded = encode_pairs(&pairs);
+        for band in [None, Some(1)] {
+            assert_eq!(
+                batch_banded_edit_distance(&encoded, band, Backend::Gpu).unwrap(),
+                batch_banded_edit_distance(&encoded, band, Backend::Cpu).unwrap()
+            );
+        }
+        let embeddings = array![[1.0, 0.0, 2.0], [1.0, 1.0, 0.5], [0.0, 0.0, 0.0]];
+        let pairs = [(0, 1), (0, 2), (1, 1)];
+        let gpu = batch_cosine_similarity(embeddings.view(), &pairs, Backend::Gpu).unwrap();
+        let cpu = batch_cosine_similarity(embeddings.view(), &pairs, Backend::Cpu).unwrap();
+        assert!(gpu.iter().zip(&cpu).all(|(g, c)| (g - c).abs() < 1e-5));
+    }
+}
@@ -0,0 +1,301 @@
+//! Minimum lateral networks: reconciling cognate distributions with a
+//! reference tree through borrowing events.
+//!
+//! Each cognate set's presence at the leaves is explained by gains and
+//! losses on the tree under weighted (Sankoff) parsimony, with a gain
+//! costing `gain_penalty` losses and the set absent above the root. A set
+//! gained once is inherited vertically; every further gain of the optimal
+//! scenario is a lateral event, attributed to the nearest node carrying the
+//! set outside the recipient's subtree (and, where possible, off its line
+//! of ancestors). Higher penalties make parallel origins costlier, so fewer
+//! lateral events are inferred. The resulting network is written in
+//! Extended Newick: each recipient sits below a hybrid node `#H<k>` that
+//! also appears as a leaf beside each of its donors.
+
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::ancestral::{leaf_presence, parsimony_states};
+use crate::tree::{newick_label, Tree};
+
+/// Borrowing from a donor node into a recipient node
+#[derive(Debug, Clone, PartialEq)]
+pub struct LateralTransfer {
+    pub donor: String,
+    pub recipient: String,
+    /// Indices of the sets transferred, ascending
+    pub sets: Vec<usize>,
+}
+
+/// Parsimony history of one cognate set
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetHistory {
+    /// Nodes gaining the set, the vertical origin (closest to the root)
+    /// first
+    pub origins: Vec<String>,
+    pub losses: usize,
+}
+
+/// Tree plus the lateral transfers needed to explain the cognate sets
+#[derive(Debug, Clone)]
+pub struct LateralNetwork {
+    pub histories: Vec<SetHistory>,
+    /// Sorted by (recipient, donor) node
+    pub transfers: Vec<LateralTransfer>,
+    tree: Tree,
+    /// (donor, recipient) node per transfer
+    edges: Vec<(usize, usize)>,
+}
+
+/// Infer the minimum lateral network of `sets`, each given as the
+/// languages (leaf names) attesting it
+pub fn minimum_lateral_network(
+    tree: &Tree,
+    sets: &[Vec<String>],
+    gain_penalty: f64,
+) -> Result<LateralNetwork, String> {
+    if !gain_penalty.is_finite() || gain_penalty <= 0.0 {
+        return Err(format!(
+            "gain penalty {} must be finite and > 0",
+            gain_penalty
+        ));
+    }
+    let order = tree.preorder();
+    let mut level = vec![0; tree.nodes.len()];
+    for &node in &order {
+        if let Some(parent) = tree.nodes[node].parent {
+            level[node] = level[parent] + 1;
+        }
+    }
+    let scenarios: Vec<Scenario> = leaf_presence(tree, sets)?
+        .par_iter()
+        .map(|present| Scenario::infer(tree, &order, &level, present, gain_penalty))
+        .collect();
+
+    let mut grouped: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for (s, scenario) in scenarios.iter().enumerate() {
+        for &(donor, recipient) in &scenario.transfers {
+            grouped.entry((recipient, donor)).or_default().push(s);
+        }
+    }
+    let transfers = grouped
+        .iter()
+        .map(|(&(recipient, donor), sets)| LateralTransfer {
+            donor: tree.node_name(donor),
+            recipient: tree.node_name(recipient),
+            sets: sets.clone(),
+        })
+        .collect();
+    let histories = scenarios
+        .into_iter()
+        .map(|scenario| SetHistory {
+            origins: scenario.gains.iter().map(|&g| tree.node_name(g)).collect(),
+            losses: scenario.losses,
+        })
+        .collect();
+    Ok(LateralNetwork {
+        histories,
+        transfers,
+        tree: tree.clone(),
+        edges: grouped.into_keys().map(|(r, d)| (d, r)).collect(),
+    })
+}
+
+/// Optimal gain/loss scenario of one set
+struct Scenario {
+    /// Vertical origin first
+    gains: Vec<usize>,
+    losses: usize,
+    /// (donor, recipient) per lateral gain
+    transfers: Vec<(usize, usize)>,
+}
+
+impl Scenario {
+    fn infer(
+        tree: &Tree,
+        order: &[usize],
+        level: &[usize],
+        present: &[bool],
+        gain_penalty: f64,
+    ) -> Self {
+        let state = parsimony_states(tree, order, present, gain_penalty);
+
+        let mut gains = Vec::new();
+        let mut losses = 0;
+        for &node in order {
+            let parent = tree.nodes[node].parent.map_or(0, |p| state[p]);
+            match (parent, state[node]) {
+                (0, 1) => gains.push(node),
+                (1, 0) => losses += 1,
+                _ => {}
+            }
+        }
+        // Stable, so gains at one level stay in preorder
+        gains.sort_by_key(|&node| level[node]);
+
+        let carriers: Vec<usize> = order.iter().copied().filter(|&n| state[n] == 1).collect();
+        let transfers = gains
+            .iter()
+            .skip(1)
+            .map(|&recipient| {
+                let donor = nearest_donor(tree, level, &carriers, recipient);
+                (donor, recipient)
+            })
+            .collect();
+        Self {
+            gains,
+            losses,
+            transfers,
+        }
+    }
+}
+
+/// Closest carrier outside the recipient's subtree, preferring nodes off
+/// its line of ancestors (ties by preorder)
+fn nearest_donor(tree: &Tree, level: &[usize], carriers: &[usize], recipient: usize) -> usize {
+    let mut best: Option<(bool, usize, usize)> = None;
+    for &carrier in carriers {
+        let mrca = mrca(tree, level, carrier, recipient);
+        if mrca == recipient {
+            continue;
+        }
+        let ancestor = mrca == carrier;
+        let distance = level[carrier] + level[recipient] - 2 * level[mrca];
+        if best.is_none_or(|(a, d, _)| (ancestor, distance) < (a, d)) {
+            best = Some((ancestor, distance, carrier));
+        }
+    }
+    // The vertical origin is always a carrier outside the subtree
+    best.map(|(_, _, carrier)| carrier).unwrap()
+}
+
+fn mrca(tree: &Tree, level: &[usize], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        if level[a] >= level[b] {
+            a = tree.nodes[a].parent.unwrap();
+        } else {
+            b = tree.nodes[b].parent.unwrap();
+        }
+    }
+    a
+}
+
+impl LateralNetwork {
+    /// Lateral events over all sets
+    pub fn num_lateral_events(&self) -> usize {
+        self.transfers.iter().map(|t| t.sets.len()).sum()
+    }
+
+    /// Extended Newick of the tree with one hybrid node per recipient
+    /// (see the module docs); inserted nodes have zero-length branches
+    pub fn to_extended_newick(&self) -> String {
+        let mut hybrid: HashMap<usize, usize> = HashMap::new();
+        let mut donated: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &(donor, recipient) in &self.edges {
+            let next = hybrid.len() + 1;
+            let h = *hybrid.entry(recipient).or_insert(next);
+            donated.entry(donor).or_default().push(h);
+        }
+        let mut out = String::new();
+        self.write(self.tree.root, &hybrid, &donated, &mut out);
+        out.push(';');
+        out
+    }
+
+    fn write(
+        &self,
+        node: usize,
+        hybrid: &HashMap<usize, usize>,
+        donated: &HashMap<usize, Vec<usize>>,
+        out: &mut String,
+    ) {
+        let current = &self.tree.nodes[node];
+        let donations = donated.get(&node);
+        if hybrid.contains_key(&node) {
+            out.push('(');
+        }
+        if donations.is_some() {
+            out.push('(');
+        }
+        if !current.children.is_empty() {
+            out.push('(');
+            for (i, &child) in current.children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                self.write(child, hybrid, donated, out);
+            }
+            out.push(')');
+        }
+        if let Some(name) = &current.name {
+            out.push_str(&newick_label(name));
+        }
+        if let Some(donations) = donations {
+            out.push_str(":0");
+            for h in donations {
+                out.push_str(&format!(",#H{}", h));
+            }
+            out.push(')');
+        }
+        if let Some(h) = hybrid.get(&node) {
+            out.push_str(&format!(":0)#H{}", h));
+        }
+        if current.parent.is_some() {
+            out.push_str(&format!(":{}", current.length));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sets(languages: &[&[&str]]) -> Vec<Vec<String>> {
+        languages
+            .iter()
+            .map(|set| set.iter().map(|l| l.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_minimum_lateral_network() {
+        let tree = Tree::from_newick(
+            "(((a:1,b:1)ab:1,(c:1,d:1)cd:1)x:1,((e:1,f:1)ef:1,(g:1,h:1)gh:1)y:1)root;",
+        )
+        .unwrap();
+        let cognates = sets(&[&["a", "b", "g"], &["a", "b", "c", "d", "e"], &["a", "b"]]);
+        let network = minimum_lateral_network(&tree, &cognates, 2.0).unwrap();
+
+        // {a, b, g}: two gains (4) beat a root gain with three losses (5)
+        assert_eq!(network.histories[0].origins, ["ab", "g"]);
+        assert_eq!(network.histories[0].losses, 0);
+        assert_eq!(network.histories[1].origins, ["x", "e"]);
+        assert_eq!(network.histories[2].origins, ["ab"]);
+        let transfers: Vec<(&str, &str, &[usize])> = network
+            .transfers
+            .iter()
+            .map(|t| (t.donor.as_str(), t.recipient.as_str(), t.sets.as_slice()))
+            .collect();
+        assert_eq!(transfers, [("x", "e", &[1][..]), ("ab", "g", &[0][..])]);
+        assert_eq!(network.num_lateral_events(), 2);
+
+        let newick = network.to_extended_newick();
+        assert!(newick.starts_with("(((((a:1,b:1)ab:0,#H2):1,(c:1,d:1)cd:1)x:0,#H1):1,"));
+        assert!(newick.contains("((e:0)#H1:1,f:1)ef:1"));
+        assert!(newick.contains("((g:0)#H2:1,h:1)gh:1"));
+
+        // A high gain penalty explains {a, b, g} by losses instead (7 < 8)
+        let network = minimum_lateral_network(&tree, &cognates[..1], 4.0).unwrap();
+        assert_eq!(network.histories[0].origins, ["root"]);
+        assert_eq!(network.histories[0].losses, 3);
+        assert!(network.transfers.is_empty());
+        assert_eq!(network.to_extended_newick(), tree.to_newick());
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        let tree = Tree::from_newick("(a,b);").unwrap();
+        assert!(minimum_lateral_network(&tree, &sets(&[&["a", "z"]]), 2.0).is_err());
+        assert!(minimum_lateral_network(&tree, &sets(&[&["a"]]), 0.0).is_err());
+    }
+}
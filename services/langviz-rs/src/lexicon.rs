@@ -0,0 +1,241 @@
+//! Similarity between two languages' lexicons, concept by concept.
+//!
+//! Forms are grouped into concept slots; within each slot present in both
+//! lexicons every form of one language is compared with every form of the
+//! other (synonyms included) and the closest pair kept. The lexicon
+//! distance is one minus the mean of those best similarities, so slots
+//! missing from either side neither help nor hurt; they are listed instead.
+//!
+//! The same mean-of-best aggregation ranks concepts against each other
+//! across a whole wordlist: `similar_concepts` scores every other concept
+//! by how closely its forms (in any language) match the query concept's
+//! forms, so high scorers point at polysemy (same language) or semantic
+//! shifts between related languages.
+
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::phonetic::SimilarityMetric;
+
+/// Closest pair of forms for one concept
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConceptMatch {
+    pub concept: String,
+    pub form_a: String,
+    pub form_b: String,
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexiconSimilarity {
+    /// Best match per concept with forms on both sides, in concept order
+    pub matches: Vec<ConceptMatch>,
+    /// Concepts lacking a form in either lexicon
+    pub missing: Vec<String>,
+    /// Mean best-match similarity (0 when no concept is shared)
+    pub mean_similarity: f64,
+    /// One minus `mean_similarity` (1 when no concept is shared)
+    pub distance: f64,
+}
+
+/// Compare two lexicons of (concept, form) entries over `concepts`, or over
+/// every concept of either lexicon (sorted) when `None`
+pub fn lexicon_similarity(
+    forms_a: &[(String, String)],
+    forms_b: &[(String, String)],
+    concepts: Option<&[String]>,
+    metric: SimilarityMetric,
+) -> LexiconSimilarity {
+    let (slots_a, slots_b) = (slots(forms_a), slots(forms_b));
+    let concepts: Vec<&str> = match concepts {
+        Some(concepts) => concepts.iter().map(String::as_str).collect(),
+        None => slots_a
+            .keys()
+            .chain(slots_b.keys())
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+    };
+
+    let results: Vec<Result<ConceptMatch, &str>> = concepts
+        .par_iter()
+        .map(|&concept| {
+            let (Some(a), Some(b)) = (slots_a.get(concept), slots_b.get(concept)) else {
+                return Err(concept);
+            };
+            let mut best = (a[0], b[0], f64::NEG_INFINITY);
+            for &form_a in a {
+                for &form_b in b {
+                    let similarity = metric.similarity(form_a, form_b);
+                    if similarity > best.2 {
+                        best = (form_a, form_b, similarity);
+                    }
+                }
+            }
+            Ok(ConceptMatch {
+                concept: concept.to_string(),
+                form_a: best.0.to_string(),
+                form_b: best.1.to_string(),
+                similarity: best.2,
+            })
+        })
+        .collect();
+
+    let mut matches = Vec::new();
+    let mut missing = Vec::new();
+    for result in results {
+        match result {
+            Ok(matched) => matches.push(matched),
+            Err(concept) => missing.push(concept.to_string()),
+        }
+    }
+    let mean_similarity = if matches.is_empty() {
+        0.0
+    } else {
+        matches.iter().map(|m| m.similarity).sum::<f64>() / matches.len() as f64
+    };
+    LexiconSimilarity {
+        matches,
+        missing,
+        mean_similarity,
+        distance: 1.0 - mean_similarity,
+    }
+}
+
+/// A concept ranked against the query concept by `similar_concepts`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConceptNeighbor {
+    pub concept: String,
+    /// Mean, over the query concept's forms, of each form's best similarity
+    /// to a form of this concept
+    pub score: f64,
+    /// The closest (language, form) pair: query side, then this concept
+    pub best: ((String, String), (String, String)),
+    pub best_similarity: f64,
+}
+
+/// The `k` concepts of (language, concept, form) `entries` whose forms are
+/// most similar to those of `concept`, best first (ties by concept)
+pub fn similar_concepts(
+    entries: &[(String, String, String)],
+    concept: &str,
+    k: usize,
+    metric: SimilarityMetric,
+) -> Result<Vec<ConceptNeighbor>, String> {
+    let mut by_concept: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+    for (language, gloss, form) in entries {
+        by_concept.entry(gloss).or_default().push((language, form));
+    }
+    let query = by_concept
+        .remove(concept)
+        .ok_or_else(|| format!("concept '{}' has no forms", concept))?;
+
+    let mut neighbors: Vec<ConceptNeighbor> = by_concept
+        .par_iter()
+        .map(|(&other, forms)| {
+            let mut total = 0.0;
+            let mut best = (query[0], forms[0], f64::NEG_INFINITY);
+            for &a in &query {
+                let mut closest = f64::NEG_INFINITY;
+                for &b in forms {
+                    let similarity = metric.similarity(a.1, b.1);
+                    closest = closest.max(similarity);
+                    if similarity > best.2 {
+                        best = (a, b, similarity);
+                    }
+                }
+                total += closest;
+            }
+            let owned = |(language, form): (&str, &str)| (language.to_string(), form.to_string());
+            ConceptNeighbor {
+                concept: other.to_string(),
+                score: total / query.len() as f64,
+                best: (owned(best.0), owned(best.1)),
+                best_similarity: best.2,
+            }
+        })
+        .collect();
+    neighbors.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.concept.cmp(&b.concept)));
+    neighbors.truncate(k);
+    Ok(neighbors)
+}
+
+/// Forms per concept
+fn slots(forms: &[(String, String)]) -> BTreeMap<&str, Vec<&str>> {
+    let mut slots: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (concept, form) in forms {
+        slots.entry(concept).or_default().push(form);
+    }
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lexicon(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(concept, form)| (concept.to_string(), form.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_lexicon_similarity() {
+        let latin = lexicon(&[("father", "pater"), ("two", "duo"), ("fish", "piskis")]);
+        let german = lexicon(&[
+            ("father", "fater"),
+            ("two", "tsvai"),
+            ("two", "duo"),
+            ("water", "vasser"),
+        ]);
+        let result = lexicon_similarity(&latin, &german, None, SimilarityMetric::Levenshtein);
+
+        let concepts: Vec<&str> = result.matches.iter().map(|m| m.concept.as_str()).collect();
+        assert_eq!(concepts, ["father", "two"]);
+        assert_eq!(result.missing, ["fish", "water"]);
+        // The synonym identical to the Latin form wins its slot
+        assert_eq!(result.matches[1].form_b, "duo");
+        assert_eq!(result.matches[1].similarity, 1.0);
+        assert!((result.mean_similarity - 0.9).abs() < 1e-12);
+        assert!((result.distance - 0.1).abs() < 1e-12);
+
+        let only = ["father".to_string(), "hand".to_string()];
+        let result =
+            lexicon_similarity(&latin, &german, Some(&only), SimilarityMetric::Levenshtein);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.missing, ["hand"]);
+    }
+
+    #[test]
+    fn test_similar_concepts() {
+        // German "Zeit" (time) against its English cognate "tide"; "sea" and
+        // "hand" share nothing with it
+        let entries: Vec<(String, String, String)> = [
+            ("deu", "time", "tsait"),
+            ("eng", "time", "taim"),
+            ("eng", "tide", "taid"),
+            ("deu", "tide", "geˈtsaitə"),
+            ("eng", "sea", "siː"),
+            ("eng", "hand", "hænd"),
+            ("deu", "hand", "hant"),
+        ]
+        .iter()
+        .map(|(l, c, f)| (l.to_string(), c.to_string(), f.to_string()))
+        .collect();
+        let neighbors =
+            similar_concepts(&entries, "time", 2, SimilarityMetric::Levenshtein).unwrap();
+        assert_eq!(neighbors.len(), 2);
+        assert_eq!(neighbors[0].concept, "tide");
+        assert_eq!(
+            neighbors[0].best,
+            (
+                ("eng".to_string(), "taim".to_string()),
+                ("eng".to_string(), "taid".to_string())
+            )
+        );
+        assert!(neighbors[0].score > neighbors[1].score);
+        assert!(similar_concepts(&entries, "fire", 2, SimilarityMetric::Levenshtein).is_err());
+    }
+}
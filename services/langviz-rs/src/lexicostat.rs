@@ -0,0 +1,376 @@
+//! Lexicostatistics over cognate judgments.
+//!
+//! Judgments are (language, concept, cognate class) triples. Class labels are
+//! scoped to their concept, and a language may list several classes for one
+//! concept (synonyms). Two languages agree on a concept when they share at
+//! least one class.
+//!
+//! Languages with patchy concept coverage make the estimator matter. By
+//! default a pair's shared fraction counts only the concepts both attest;
+//! dividing by the whole list instead (the raw percentage) treats missing
+//! entries as replacements and pushes poorly covered languages away from
+//! everything. Overlap estimates from a handful of concepts are unbiased but
+//! noisy, so a shrunk estimator pulls them towards the mean shared fraction
+//! of all pairs by a pseudocount of concepts, the pull fading as the overlap
+//! grows.
+
+use ahash::AHashMap;
+use ndarray::Array2;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+use crate::concepts::SemanticFields;
+
+/// Per-concept stability weights (e.g. Swadesh or Leipzig–Jakarta scores)
+#[derive(Debug, Clone)]
+pub struct ConceptWeights {
+    weights: HashMap<String, f64>,
+    /// Weight for concepts missing from the table
+    pub default_weight: f64,
+}
+
+impl Default for ConceptWeights {
+    fn default() -> Self {
+        Self {
+            weights: HashMap::new(),
+            default_weight: 1.0,
+        }
+    }
+}
+
+impl ConceptWeights {
+    pub fn new(weights: HashMap<String, f64>, default_weight: f64) -> Self {
+        Self {
+            weights,
+            default_weight,
+        }
+    }
+
+    /// Weights from stability ranks (1 = most stable): the most stable of
+    /// `n` concepts weighs 1.0, the least stable 1/n
+    pub fn from_ranks(ranks: &HashMap<String, usize>, default_weight: f64) -> Self {
+        let n = ranks.values().copied().max().unwrap_or(1).max(1) as f64;
+        let weights = ranks
+            .iter()
+            .map(|(concept, &rank)| {
+                let rank = rank.max(1) as f64;
+                (concept.clone(), (n - rank + 1.0).max(1.0) / n)
+            })
+            .collect();
+        Self::new(weights, default_weight)
+    }
+
+    pub fn weight(&self, concept: &str) -> f64 {
+        self.weights
+            .get(concept)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+}
+
+/// How a language pair's shared fraction is estimated from its concepts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceEstimator {
+    /// Over the concepts both languages attest
+    Overlap,
+    /// Over every concept of the table, missing entries counting as not
+    /// shared
+    ListLength,
+    /// Overlap estimate shrunk towards the pooled shared fraction of all
+    /// pairs by `pseudocount` (weighted) concepts
+    Shrunk { pseudocount: f64 },
+}
+
+impl FromStr for DistanceEstimator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overlap" => Ok(DistanceEstimator::Overlap),
+            "list_length" => Ok(DistanceEstimator::ListLength),
+            "shrunk" => Ok(DistanceEstimator::Shrunk { pseudocount: 5.0 }),
+            other => Err(format!(
+                "unknown distance estimator '{}' (expected overlap, list_length or shrunk)",
+                other
+            )),
+        }
+    }
+}
+
+/// Languages × concepts table of cognate class sets
+#[derive(Debug, Clone, Default)]
+pub struct CognateTable {
+    pub(crate) languages: Vec<String>,
+    pub(crate) concepts: Vec<String>,
+    /// Interned (concept index, class label) pairs
+    pub(crate) classes: Vec<(usize, String)>,
+    /// `cells[language][concept]`: sorted class IDs, empty when missing
+    pub(crate) cells: Vec<Vec<Vec<u32>>>,
+}
+
+impl CognateTable {
+    /// Build from (language, concept, cognate class) judgments; languages and
+    /// concepts are sorted alphabetically
+    pub fn from_judgments(judgments: &[(String, String, String)]) -> Self {
+        let languages: Vec<String> = judgments
+            .iter()
+            .map(|(l, _, _)| l.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let concepts: Vec<String> = judgments
+            .iter()
+            .map(|(_, c, _)| c.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let lang_index: AHashMap<&str, usize> = languages
+            .iter()
+            .enumerate()
+            .map(|(i, l)| (l.as_str(), i))
+            .collect();
+        let concept_index: AHashMap<&str, usize> = concepts
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.as_str(), i))
+            .collect();
+
+        let mut classes: Vec<(usize, String)> = Vec::new();
+        let mut class_index: AHashMap<(usize, &str), u32> = AHashMap::new();
+        let mut cells = vec![vec![Vec::new(); concepts.len()]; languages.len()];
+
+        for (language, concept, class) in judgments {
+            let l = lang_index[language.as_str()];
+            let c = concept_index[concept.as_str()];
+            let id = *class_index.entry((c, class.as_str())).or_insert_with(|| {
+                classes.push((c, class.clone()));
+                (classes.len() - 1) as u32
+            });
+            cells[l][c].push(id);
+        }
+
+        for row in cells.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.sort_unstable();
+                cell.dedup();
+            }
+        }
+
+        Self {
+            languages,
+            concepts,
+            classes,
+            cells,
+        }
+    }
+
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    pub fn concepts(&self) -> &[String] {
+        &self.concepts
+    }
+
+    /// Weights of the shared cognate concepts and of all concepts the two
+    /// languages both attest
+    fn weighted_overlap(&self, a: usize, b: usize, weights: &ConceptWeights) -> (f64, f64) {
+        let mut shared = 0.0;
+        let mut total = 0.0;
+
+        for (c, concept) in self.concepts.iter().enumerate() {
+            let (ca, cb) = (&self.cells[a][c], &self.cells[b][c]);
+            if ca.is_empty() || cb.is_empty() {
+                continue;
+            }
+            let w = weights.weight(concept);
+            total += w;
+            if ca.iter().any(|id| cb.binary_search(id).is_ok()) {
+                shared += w;
+            }
+        }
+
+        (shared, total)
+    }
+
+    /// Symmetric lexicostatistical distance matrix (1 - weighted shared
+    /// fraction), NaN for language pairs without a common concept
+    pub fn distance_matrix(&self, weights: &ConceptWeights) -> Array2<f64> {
+        self.estimated_distance_matrix(weights, DistanceEstimator::Overlap)
+    }
+
+    /// `distance_matrix` under `estimator`; only the overlap estimate leaves
+    /// pairs without a common concept NaN
+    pub fn estimated_distance_matrix(
+        &self,
+        weights: &ConceptWeights,
+        estimator: DistanceEstimator,
+    ) -> Array2<f64> {
+        let n = self.languages.len();
+        let pairs: Vec<(usize, usize)> = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .collect();
+
+        let overlaps: Vec<(f64, f64)> = pairs
+            .par_iter()
+            .map(|&(i, j)| self.weighted_overlap(i, j, weights))
+            .collect();
+        let list_length: f64 = self.concepts.iter().map(|c| weights.weight(c)).sum();
+        let (all_shared, all_total) = overlaps
+            .iter()
+            .fold((0.0, 0.0), |(s, t), &(shared, total)| {
+                (s + shared, t + total)
+            });
+        let pooled = all_shared / all_total;
+
+        let mut matrix = Array2::<f64>::zeros((n, n));
+        for (&(i, j), &(shared, total)) in pairs.iter().zip(&overlaps) {
+            let fraction = match estimator {
+                DistanceEstimator::Overlap => shared / total,
+                DistanceEstimator::ListLength => shared / list_length,
+                DistanceEstimator::Shrunk { pseudocount } => {
+                    (shared + pseudocount * pooled) / (total + pseudocount)
+                }
+            };
+            let d = 1.0 - fraction;
+            matrix[[i, j]] = d;
+            matrix[[j, i]] = d;
+        }
+        matrix
+    }
+
+    /// Concepts attested by both languages of each pair; the diagonal holds
+    /// each language's coverage
+    pub fn overlap_counts(&self) -> Array2<u64> {
+        let n = self.languages.len();
+        Array2::from_shape_fn((n, n), |(a, b)| {
+            self.cells[a]
+                .iter()
+                .zip(&self.cells[b])
+                .filter(|(ca, cb)| !ca.is_empty() && !cb.is_empty())
+                .count() as u64
+        })
+    }
+
+    /// `distance_matrix` per semantic field, over the field's concepts only
+    /// (untagged concepts are left out)
+    pub fn field_distance_matrices(
+        &self,
+        weights: &ConceptWeights,
+        fields: &SemanticFields,
+    ) -> BTreeMap<String, Array2<f64>> {
+        fields
+            .fields()
+            .into_iter()
+            .map(|field| {
+                let in_field = self
+                    .concepts
+                    .iter()
+                    .map(|concept| {
+                        let weight = if fields.has_field(concept, field) {
+                            weights.weight(concept)
+                        } else {
+                            0.0
+                        };
+                        (concept.clone(), weight)
+                    })
+                    .collect();
+                let matrix = self.distance_matrix(&ConceptWeights::new(in_field, 0.0));
+                (field.to_string(), matrix)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn judgments() -> Vec<(String, String, String)> {
+        [
+            ("eng", "hand", "1"),
+            ("deu", "hand", "1"),
+            ("eng", "water", "2"),
+            ("deu", "water", "2"),
+            ("eng", "dog", "3"),
+            ("deu", "dog", "4"),
+            ("deu", "dog", "3"),
+            ("eng", "mountain", "5"),
+            ("deu", "mountain", "6"),
+            ("fra", "hand", "7"),
+        ]
+        .iter()
+        .map(|(l, c, k)| (l.to_string(), c.to_string(), k.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_unweighted_distance() {
+        let table = CognateTable::from_judgments(&judgments());
+        assert_eq!(table.languages(), &["deu", "eng", "fra"]);
+
+        let d = table.distance_matrix(&ConceptWeights::default());
+        // hand, water and dog (via synonym) shared; mountain not
+        assert!((d[[0, 1]] - 0.25).abs() < 1e-12);
+        assert_eq!(d[[1, 2]], 1.0);
+        assert_eq!(d[[0, 0]], 0.0);
+    }
+
+    #[test]
+    fn test_stable_concepts_weigh_more() {
+        let table = CognateTable::from_judgments(&judgments());
+        let weights = ConceptWeights::new(HashMap::from([("mountain".to_string(), 3.0)]), 1.0);
+        let d = table.distance_matrix(&weights);
+        assert!((d[[0, 1]] - 0.5).abs() < 1e-12);
+
+        let ranked = ConceptWeights::from_ranks(
+            &HashMap::from([("hand".to_string(), 1), ("mountain".to_string(), 4)]),
+            0.5,
+        );
+        assert_eq!(ranked.weight("hand"), 1.0);
+        assert_eq!(ranked.weight("mountain"), 0.25);
+        assert_eq!(ranked.weight("unknown"), 0.5);
+    }
+
+    #[test]
+    fn test_coverage_aware_estimators() {
+        let table = CognateTable::from_judgments(&judgments());
+        let weights = ConceptWeights::default();
+        let overlaps = table.overlap_counts();
+        assert_eq!(overlaps.row(0).to_vec(), vec![4, 4, 1]);
+        assert_eq!(overlaps[[2, 2]], 1);
+
+        // fra attests only hand, so against the full list it looks unrelated
+        let raw = table.estimated_distance_matrix(&weights, DistanceEstimator::ListLength);
+        assert_eq!(raw[[0, 2]], 1.0);
+        assert_eq!(raw[[0, 1]], 0.25);
+
+        // Pooled shared fraction: 3 of the 4 + 1 + 1 overlapping concepts
+        let estimator = DistanceEstimator::Shrunk { pseudocount: 2.0 };
+        let shrunk = table.estimated_distance_matrix(&weights, estimator);
+        assert!((shrunk[[1, 2]] - (1.0 - 1.0 / 3.0)).abs() < 1e-12);
+        assert!((shrunk[[0, 1]] - (1.0 - 4.0 / 6.0)).abs() < 1e-12);
+        assert!("shrunk".parse::<DistanceEstimator>().is_ok());
+        assert!("raw".parse::<DistanceEstimator>().is_err());
+    }
+
+    #[test]
+    fn test_field_distances() {
+        let table = CognateTable::from_judgments(&judgments());
+        let tags = HashMap::from([
+            ("hand".to_string(), vec!["body".to_string()]),
+            ("dog".to_string(), vec!["animals".to_string()]),
+            ("mountain".to_string(), vec!["nature".to_string()]),
+            ("water".to_string(), vec!["nature".to_string()]),
+        ]);
+        let matrices =
+            table.field_distance_matrices(&ConceptWeights::default(), &SemanticFields::new(&tags));
+        assert_eq!(matrices.len(), 3);
+        // deu-eng share water but not mountain; eng-fra differ on hand
+        assert_eq!(matrices["nature"][[0, 1]], 0.5);
+        assert_eq!(matrices["body"][[1, 2]], 1.0);
+        assert!(matrices["nature"][[1, 2]].is_nan());
+    }
+}
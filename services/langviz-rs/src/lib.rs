@@ -6,36 +6,488 @@
 //! - Sparse matrix operations
 //! - Clustering primitives
 
+// pyo3 0.20 expands #[new] into impls nested in a function, which newer
+// rustc reports as non_local_definitions
+#![allow(non_local_definitions)]
+
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyReadonlyArray1, PyReadonlyArray2};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyCapsule, PyDict, PyList};
 
+mod active;
+mod agreement;
+mod ancestral;
+mod arrow;
+mod assignment;
+mod bipartite;
+mod borrowability;
+mod borrowing;
+mod bundling;
+mod calibration;
+mod centrality;
+mod characters;
+mod checkpoint;
+mod classifier;
 mod cluster;
+mod community;
+mod components;
+mod compress;
+mod concepts;
+mod contact;
+mod correspondence;
+mod depth;
+mod determinism;
+mod diff;
+mod distfile;
+mod embedding;
+mod error;
+mod etymology;
+mod evaluation;
+mod export;
+mod features;
+mod filter;
+mod format;
+mod geo;
+#[cfg(feature = "gpu")]
+mod gpu;
 mod graph;
+mod ingest;
+mod invariants;
+mod jackknife;
+mod jobs;
+mod kcore;
+mod kernels;
+mod lateral;
+mod lexicon;
+mod lexicostat;
+mod linalg;
+mod mapped;
+mod mds;
+mod multigraph;
+mod neighborhood;
+mod noise;
+mod nullmodel;
+mod orthography;
+mod partial;
+mod pairstats;
+mod paths;
 mod phonetic;
+mod reconstruct;
+mod reflex;
+mod rooting;
+mod ruleorder;
+mod sampling;
+mod scoring;
+mod seriation;
+mod session;
+mod setops;
+mod shared;
+mod sitebootstrap;
 mod sparse;
+mod soundchange;
+mod soundclass;
+mod sparsify;
+mod split;
+mod stability;
+#[cfg(feature = "sqlite")]
+mod store;
+mod summary;
+mod synthetic;
+mod transcription;
+mod tree;
+mod treedist;
+mod tsne;
 mod types;
+mod whatif;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
 
-use cluster::{threshold_clustering_with_ids, silhouette_score, within_cluster_variance};
-use graph::{CognateGraph, GraphStats};
+use active::{suggest_pairs, PairSuggestion};
+use agreement::{annotator_agreement, Agreement};
+use ancestral::{reconstruct_ancestral_states, AncestralStates, BranchEvents, Reconstruction};
+use arrow::{import_stream, read_columns, Column, ColumnKind};
+use assignment::{assign_cognate_set_ids, IdAssignment};
+use bipartite::{project_bipartite, BipartiteSide, ProjectionWeighting};
+use borrowability::{borrowability, Borrowability, BorrowingRate};
+use borrowing::{BorrowingGraph, DirectedGraphStats, NodeDegree};
+use bundling::bundle_edges;
+use calibration::{CalibrationMode, PairThresholds, ProbabilityCalibration, ProbabilityMethod};
+use characters::{CharacterMatrix, ImputationMethod};
+use checkpoint::{AllPairsRun, CheckpointPolicy, RunTask};
+use classifier::{CognateClassifier, TrainOptions, PAIR_FEATURE_NAMES};
+use cluster::{
+    agglomerative_clustering, consensus_clustering, consensus_from_edges, davies_bouldin_index,
+    divisive_clustering, evaluate_clusterings, merge_cognate_sets, spectral_clustering,
+    threshold_clustering_with_ids, silhouette_score, within_between_ratio, within_cluster_variance,
+    ClusteringScores, Dendrogram, DivisiveParams, Linkage, MergeStrategy, MergedCognateSet,
+    SparseClusters,
+};
+use community::ClusterQuality;
+use components::ComponentStats;
+use compress::{open_file, read_to_string, write_compressed, write_file, Compression};
+use concepts::{
+    borrowing_by_field, concept_cognate_density, concept_coverage, filter_by_field,
+    filter_wordlist, ConceptCoverage, ConceptDensity, ConceptList, FieldBorrowing, SemanticFields,
+};
+use contact::{ContactLink, ContactNetwork};
+use correspondence::{
+    correspondence_graph, correspondence_matrices, correspondence_matrices_json,
+    correspondence_patterns, PatternAnalysis, Site,
+};
+use depth::{score_set_depths, SetDepth};
+use determinism::{is_deterministic, set_deterministic};
+use distfile::{read_distance_matrix, write_distance_matrix, DistanceFormat};
+use embedding::{Node2VecParams, NodeEmbedding};
+use error::{
+    validate_damping, validate_square, validate_threshold, validate_weights, LangVizError,
+};
+use etymology::{EtymologyIndex, LanguageModel, ProtoMatch};
+use evaluation::{batch_score_curves, score_curves, ScoreCurves};
+use export::read_edge_list;
+use features::{batch_feature_distance, feature_distance, FeatureDistanceTable};
+use filter::Predicate;
+use format::format_versions;
+use geo::{isogloss_boundaries, links_to_geojson, IsoglossSegment};
+use graph::{CognateGraph, GraphStats, NodeCentrality};
+#[cfg(feature = "parquet")]
+use ingest::read_edges_parquet;
+use ingest::{read_edges_csv, EdgeColumns};
+use invariants::{check_alignments, check_similarity, triangle_violations, Violation};
+use jackknife::{jackknife, CladeSupport, Jackknife, JackknifeParams};
+use jobs::{run_blocks, Job, JobError, JobProgress};
+use kernels::{batch_banded_similarity, batch_cosine_similarity, gpu_available, Backend};
+use lateral::{minimum_lateral_network, LateralNetwork};
+use lexicon::{lexicon_similarity, similar_concepts, ConceptNeighbor, LexiconSimilarity};
+use lexicostat::{CognateTable, ConceptWeights, DistanceEstimator};
+use mapped::MappedSimilarityMatrix;
+use mds::classical_mds;
+use multigraph::{CognateMultiGraph, EdgeAggregation};
+use neighborhood::LinkPredictor;
+use noise::{perturb_forms, robustness_curve, NoiseParams, RobustnessPoint};
+use nullmodel::{NullModel, NullModelStats};
+use orthography::{
+    batch_orthographic_similarity, orthographic_similarity, segment_orthography, OrthoUnit,
+    OrthographyOptions,
+};
+use partial::{
+    detect_partial_cognates, partial_cognate_edges, MorphemeSegmentation, PartialCognateParams,
+    PartialCognates,
+};
+use pairstats::{sample_pair_stats, PairSampleStats};
+use paths::PathStats;
 use phonetic::{
-    batch_phonetic_distance, compute_similarity_matrix, dtw_align, extract_sound_correspondences,
-    lcs_ratio, phonetic_distance,
+    batch_dtw_align, batch_metric_similarity, batch_phonetic_distance,
+    batch_phonetic_distance_with_costs, block_by_key, compute_similarity_matrix,
+    compute_similarity_matrix_with_costs, compute_similarity_matrix_with_metric,
+    contextual_sound_correspondences, dtw_align, dtw_align_with_costs,
+    extract_sound_correspondences, lcs_ratio, nw_align, phonetic_distance_with_costs,
+    phonetic_key, segment_ipa, similarity_blocks, sw_align, AlignParams, CostModel, KeyScheme,
+    PhoneticIndex, SimilarityBlock, SimilarityBlocks, SimilarityMetric, SoundCorrespondence,
+    SoundCorrespondences, SubstitutionCost,
 };
-use sparse::{batch_knn, threshold_filter, SparseSimilarityMatrix};
-use types::{Alignment, CognateSet, SimilarityEdge};
+use reconstruct::{reconstruct_proto, reconstruct_protos};
+use reflex::ReflexPrediction;
+use rooting::{midpoint_root, root_with_outgroup};
+use ruleorder::{analyze_rule_ordering, OrderingAnalysis};
+use sampling::SamplingMethod;
+use scoring::{batch_pair_scores, PAIR_SCORE_NAMES};
+use seriation::{spectral_seriation, Seriation};
+use session::{CurationEdit, Session};
+use setops::SetOperation;
+use shared::Shared;
+use sitebootstrap::{bootstrap_correspondences, CorrespondenceConfidence};
+use soundchange::{
+    apply_sound_changes, derive_sound_changes, evaluate_rules, parse_rules, Derivation,
+    RuleEvaluation, RuleException, SoundChangeRule,
+};
+use soundclass::SoundClassCosts;
+use sparse::{batch_knn, sigmoid_weights, threshold_filter, SparseSimilarityMatrix};
+use sparsify::SparsificationMethod;
+use split::{SplitBy, WordlistSplit};
+use stability::{concept_replacement_rates, list_ranks, stability_correlation, ConceptRate};
+use summary::{default_cluster_names, expand_clusters};
+#[cfg(feature = "sqlite")]
+use store::ResultStore;
+use synthetic::{
+    generate_wordlist, simulate_tree, SyntheticDataset, SyntheticParams, TreeSimulationParams,
+};
+use transcription::{detect_transcription_errors, TranscriptionFlag};
+use tree::Tree;
+use treedist::{compare_trees, TreeComparison};
+use tsne::{tsne_embedding, TsneParams};
+use types::{AttrValue, Alignment, CognateSet, ProvenanceEdge, SimilarityEdge, WeightedEdge};
+use whatif::{GraphChange, WhatIf};
+
+/// Per-node attribute dictionaries keyed by node ID, as passed from Python
+type NodeAttributes = HashMap<String, HashMap<String, AttrValue>>;
+
+impl<'source> FromPyObject<'source> for AttrValue {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if let Ok(text) = ob.extract::<String>() {
+            return Ok(AttrValue::Text(text));
+        }
+        ob.extract::<f64>().map(AttrValue::Number).map_err(|_| {
+            pyo3::exceptions::PyTypeError::new_err("node attribute values must be str or number")
+        })
+    }
+}
+
+impl IntoPy<PyObject> for AttrValue {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            AttrValue::Text(text) => text.into_py(py),
+            AttrValue::Number(number) => number.into_py(py),
+        }
+    }
+}
+
+/// Python exceptions for `LangVizError` variants
+mod exceptions {
+    pyo3::create_exception!(
+        langviz_core,
+        LangVizError,
+        pyo3::exceptions::PyValueError,
+        "Invalid input to a LangViz kernel"
+    );
+    pyo3::create_exception!(langviz_core, InvalidWeightError, LangVizError);
+    pyo3::create_exception!(langviz_core, UnknownEntryIdError, LangVizError);
+    pyo3::create_exception!(langviz_core, EmptyInputError, LangVizError);
+    pyo3::create_exception!(langviz_core, DimensionMismatchError, LangVizError);
+    pyo3::create_exception!(langviz_core, InvalidParameterError, LangVizError);
+}
+
+impl From<LangVizError> for PyErr {
+    fn from(error: LangVizError) -> Self {
+        let message = error.to_string();
+        match error {
+            LangVizError::InvalidWeight { .. } => exceptions::InvalidWeightError::new_err(message),
+            LangVizError::UnknownEntryId(_) => exceptions::UnknownEntryIdError::new_err(message),
+            LangVizError::EmptyInput(_) => exceptions::EmptyInputError::new_err(message),
+            LangVizError::DimensionMismatch { .. } => {
+                exceptions::DimensionMismatchError::new_err(message)
+            }
+            LangVizError::InvalidParameter { .. } => {
+                exceptions::InvalidParameterError::new_err(message)
+            }
+        }
+    }
+}
+
+/// `validate_weights` over (source, target, weight, provenance) edges
+fn validate_provenance_weights(edges: &[ProvenanceEdge]) -> Result<(), LangVizError> {
+    validate_weights(edges.iter().map(|(s, t, w, _)| (s.as_str(), t.as_str(), *w)))
+}
+
+/// Tabular input rows: a list of tuples, or any object exporting
+/// `__arrow_c_stream__` (pyarrow Table, polars or pandas DataFrame) whose
+/// columns are picked by name and read without per-row Python conversion
+struct Rows<T>(Vec<T>);
+
+/// (source, target, weight) rows; DataFrames need those three columns
+type EdgeRows = Rows<(String, String, f64)>;
+/// (language, concept, form) wordlist rows
+type WordlistRows = Rows<(String, String, String)>;
+/// (id, language, concept, form) wordlist rows
+type EntryRows = Rows<(String, String, String, String)>;
+
+impl<T> std::ops::Deref for Rows<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> IntoIterator for Rows<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// The `wanted` columns of an object exporting `__arrow_c_stream__`, or
+/// None for any other object
+fn arrow_columns(ob: &PyAny, wanted: &[(&str, ColumnKind)]) -> PyResult<Option<Vec<Column>>> {
+    if !ob.hasattr("__arrow_c_stream__")? {
+        return Ok(None);
+    }
+    let capsule: &PyCapsule = ob.call_method0("__arrow_c_stream__")?.downcast()?;
+    if capsule.name()?.and_then(|name| name.to_str().ok()) != Some("arrow_array_stream") {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "__arrow_c_stream__ must return an 'arrow_array_stream' capsule",
+        ));
+    }
+    let stream = unsafe { import_stream(capsule.pointer().cast()) }
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    read_columns(stream, wanted)
+        .map(Some)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Text columns `names` of an Arrow-exporting object, or None
+fn arrow_text_columns(ob: &PyAny, names: &[&str]) -> PyResult<Option<Vec<Vec<String>>>> {
+    let wanted: Vec<(&str, ColumnKind)> =
+        names.iter().map(|&name| (name, ColumnKind::Text)).collect();
+    let Some(columns) = arrow_columns(ob, &wanted)? else {
+        return Ok(None);
+    };
+    Ok(Some(
+        columns
+            .into_iter()
+            .map(|column| match column {
+                Column::Text(values) => values,
+                Column::Number(values) => values.iter().map(f64::to_string).collect(),
+            })
+            .collect(),
+    ))
+}
+
+/// Edge rows from the named columns of an Arrow-exporting object, or None
+fn arrow_edge_rows(ob: &PyAny, columns: EdgeColumns) -> PyResult<Option<Vec<WeightedEdge>>> {
+    let wanted = [
+        (columns.source, ColumnKind::Text),
+        (columns.target, ColumnKind::Text),
+        (columns.weight, ColumnKind::Number),
+    ];
+    let Some(columns) = arrow_columns(ob, &wanted)? else {
+        return Ok(None);
+    };
+    let Ok([Column::Text(sources), Column::Text(targets), Column::Number(weights)]) =
+        <[Column; 3]>::try_from(columns)
+    else {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "unexpected edge column types",
+        ));
+    };
+    Ok(Some(
+        sources
+            .into_iter()
+            .zip(targets)
+            .zip(weights)
+            .map(|((source, target), weight)| (source, target, weight))
+            .collect(),
+    ))
+}
+
+fn validate_edge_rows(rows: &[WeightedEdge]) -> PyResult<()> {
+    validate_weights(rows.iter().map(|(s, t, w)| (s.as_str(), t.as_str(), *w)))?;
+    Ok(())
+}
+
+impl<'source> FromPyObject<'source> for EdgeRows {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        // Loaded edge lists were validated when read
+        if let Ok(edges) = ob.extract::<PyRef<PyEdgeList>>() {
+            return Ok(Rows(edges.inner.clone()));
+        }
+        let rows = match arrow_edge_rows(ob, EdgeColumns::default())? {
+            Some(rows) => rows,
+            None => ob.extract()?,
+        };
+        validate_edge_rows(&rows)?;
+        Ok(Rows(rows))
+    }
+}
+
+impl<'source> FromPyObject<'source> for WordlistRows {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let Some(columns) = arrow_text_columns(ob, &["language", "concept", "form"])? else {
+            return Ok(Rows(ob.extract()?));
+        };
+        let [languages, concepts, forms] = <[Vec<String>; 3]>::try_from(columns).unwrap();
+        Ok(Rows(
+            languages
+                .into_iter()
+                .zip(concepts)
+                .zip(forms)
+                .map(|((language, concept), form)| (language, concept, form))
+                .collect(),
+        ))
+    }
+}
+
+impl<'source> FromPyObject<'source> for EntryRows {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let names = ["id", "language", "concept", "form"];
+        let Some(columns) = arrow_text_columns(ob, &names)? else {
+            return Ok(Rows(ob.extract()?));
+        };
+        let [ids, languages, concepts, forms] = <[Vec<String>; 4]>::try_from(columns).unwrap();
+        Ok(Rows(
+            ids.into_iter()
+                .zip(languages)
+                .zip(concepts)
+                .zip(forms)
+                .map(|(((id, language), concept), form)| (id, language, concept, form))
+                .collect(),
+        ))
+    }
+}
 
 // ============================================================================
 // PHONETIC FUNCTIONS
 // ============================================================================
 
+fn similarity_metric(metric: &str) -> PyResult<SimilarityMetric> {
+    metric.parse().map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Similarity of two IPA strings; metric is "levenshtein", "lcs",
+/// "jaccard" / "jaccard3" and "dice" / "dice3" (segment bigrams / trigrams)
+/// or "jaro_winkler"
+#[pyfunction]
+#[pyo3(signature = (ipa_a, ipa_b, metric="levenshtein"))]
+fn py_phonetic_distance(ipa_a: &str, ipa_b: &str, metric: &str) -> PyResult<f64> {
+    Ok(similarity_metric(metric)?.similarity(ipa_a, ipa_b))
+}
+
+#[pyfunction]
+#[pyo3(signature = (pairs, metric="levenshtein"))]
+fn py_batch_phonetic_distance(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    metric: &str,
+) -> PyResult<Vec<f64>> {
+    let metric = similarity_metric(metric)?;
+    py.allow_threads(|| {
+        Ok(match metric {
+            SimilarityMetric::Levenshtein => batch_phonetic_distance(pairs),
+            metric => batch_metric_similarity(&pairs, metric),
+        })
+    })
+}
+
+/// IPA segments as used by the distance and alignment functions
 #[pyfunction]
-fn py_phonetic_distance(ipa_a: &str, ipa_b: &str) -> PyResult<f64> {
-    Ok(phonetic_distance(ipa_a, ipa_b))
+fn py_segment_ipa(ipa: &str) -> PyResult<Vec<String>> {
+    Ok(segment_ipa(ipa))
 }
 
+/// Coarse sound-class key for blocking; scheme is "consonants" (vowels
+/// dropped), "full" (vowels as V) or "dolgopolsky" (first two consonants)
 #[pyfunction]
-fn py_batch_phonetic_distance(pairs: Vec<(String, String)>) -> PyResult<Vec<f64>> {
-    Ok(batch_phonetic_distance(pairs))
+#[pyo3(signature = (ipa, scheme="consonants"))]
+fn py_phonetic_key(ipa: &str, scheme: &str) -> PyResult<String> {
+    let scheme: KeyScheme = scheme.parse().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(phonetic_key(ipa, scheme))
+}
+
+/// Index groups of words sharing a phonetic key (two or more members);
+/// compare pairs within each group instead of all pairs
+#[pyfunction]
+#[pyo3(signature = (ipa_strings, scheme="consonants"))]
+fn py_block_by_key(
+    py: Python<'_>,
+    ipa_strings: Vec<String>,
+    scheme: &str,
+) -> PyResult<Vec<Vec<usize>>> {
+    let scheme: KeyScheme = scheme.parse().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    py.allow_threads(|| Ok(block_by_key(&ipa_strings, scheme)))
 }
 
 #[pyfunction]
@@ -49,309 +501,8304 @@ fn py_dtw_align(ipa_a: &str, ipa_b: &str) -> PyResult<PyAlignment> {
     Ok(PyAlignment::from(alignment))
 }
 
+/// Segment-pair cost table from Python: {(segment_a, segment_b): cost},
+/// with "-" on one side for a segment's insertion/deletion cost
+type CostTable = HashMap<(String, String), f64>;
+
+fn cost_model(costs: &CostTable, indel_cost: f64) -> PyResult<CostModel> {
+    CostModel::from_table(costs, indel_cost).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// `py_phonetic_distance` with a user-supplied cost table (e.g. an IPA
+/// confusion matrix) instead of uniform costs
 #[pyfunction]
-fn py_compute_similarity_matrix(ipa_strings: Vec<String>) -> PyResult<Vec<Vec<f64>>> {
-    let matrix = compute_similarity_matrix(&ipa_strings);
-    let rows: Vec<Vec<f64>> = matrix
-        .outer_iter()
-        .map(|row| row.to_vec())
-        .collect();
-    Ok(rows)
+#[pyo3(signature = (ipa_a, ipa_b, costs, indel_cost=1.0))]
+fn py_phonetic_distance_with_costs(
+    ipa_a: &str,
+    ipa_b: &str,
+    costs: CostTable,
+    indel_cost: f64,
+) -> PyResult<f64> {
+    let model = cost_model(&costs, indel_cost)?;
+    Ok(phonetic_distance_with_costs(ipa_a, ipa_b, &model))
 }
 
-// ============================================================================
-// GRAPH FUNCTIONS
-// ============================================================================
+#[pyfunction]
+#[pyo3(signature = (pairs, costs, indel_cost=1.0))]
+fn py_batch_phonetic_distance_with_costs(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    costs: CostTable,
+    indel_cost: f64,
+) -> PyResult<Vec<f64>> {
+    let model = cost_model(&costs, indel_cost)?;
+    py.allow_threads(|| Ok(batch_phonetic_distance_with_costs(&pairs, &model)))
+}
+
+fn compute_backend(backend: &str) -> PyResult<Backend> {
+    backend.parse().map_err(pyo3::exceptions::PyValueError::new_err)
+}
 
+/// Whether `backend="gpu"` can run: built with the `gpu` feature and a GPU
+/// adapter found
 #[pyfunction]
-fn py_build_cognate_graph(
-    edges: Vec<(String, String, f64)>,
-    threshold: f64,
-) -> PyResult<usize> {
-    let similarity_edges: Vec<SimilarityEdge> = edges
-        .into_iter()
-        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
-        .collect();
+fn py_gpu_available() -> bool {
+    gpu_available()
+}
 
-    let _graph = CognateGraph::from_edges(similarity_edges, threshold);
-    
-    // Store in global registry (simplified for now - return placeholder)
-    Ok(0)
+/// `py_batch_phonetic_distance` as a banded edit distance over
+/// integer-encoded segments: only cells within `band` segments of the
+/// diagonal are filled (an upper bound on the distance, exact for
+/// `band=None`). `backend="gpu"` runs it as a compute shader
+#[pyfunction]
+#[pyo3(signature = (pairs, band=None, backend="cpu"))]
+fn py_batch_banded_similarity(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    band: Option<usize>,
+    backend: &str,
+) -> PyResult<Vec<f64>> {
+    let backend = compute_backend(backend)?;
+    py.allow_threads(|| batch_banded_similarity(&pairs, band, backend))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
 }
 
+/// Cosine similarity of (row, row) pairs of an (n × d) embedding matrix
+/// (numpy); `backend="gpu"` computes in f32
 #[pyfunction]
-fn py_find_cognate_sets(edges: Vec<(String, String, f64)>, threshold: f64) -> PyResult<Vec<PyCognateSet>> {
-    let similarity_edges: Vec<SimilarityEdge> = edges
-        .into_iter()
-        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
-        .collect();
+#[pyo3(signature = (embeddings, pairs, backend="cpu"))]
+fn py_batch_cosine_similarity<'py>(
+    py: Python<'py>,
+    embeddings: PyReadonlyArray2<f64>,
+    pairs: Vec<(usize, usize)>,
+    backend: &str,
+) -> PyResult<&'py PyArray1<f64>> {
+    let backend = compute_backend(backend)?;
+    let embeddings = embeddings.as_array().to_owned();
+    let similarities = py
+        .allow_threads(|| batch_cosine_similarity(embeddings.view(), &pairs, backend))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(similarities.into_pyarray(py))
+}
 
-    let graph = CognateGraph::from_edges(similarity_edges, threshold);
-    let sets = graph.find_cognate_sets();
-    
-    Ok(sets.into_iter().map(PyCognateSet::from).collect())
+/// Sound-class cost model: built-in Dolgopolsky-style classes, with
+/// `classes` assigning segments to classes of one's own
+fn sound_class_model(
+    classes: Option<HashMap<String, String>>,
+    within: f64,
+    across: f64,
+    class_costs: Option<HashMap<String, f64>>,
+    class_pair_costs: Option<Vec<(String, String, f64)>>,
+    indel_cost: f64,
+) -> PyResult<CostModel> {
+    let costs = SoundClassCosts::new(
+        classes.unwrap_or_default(),
+        within,
+        across,
+        class_costs.unwrap_or_default(),
+        &class_pair_costs.unwrap_or_default(),
+    )
+    .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(CostModel {
+        substitution: SubstitutionCost::SoundClasses(Arc::new(costs)),
+        default_indel: indel_cost,
+        ..CostModel::default()
+    })
 }
 
+/// `py_phonetic_distance` where substitutions within a sound class cost
+/// `within` and across classes `across`; `class_costs` overrides `within`
+/// per class and `class_pair_costs` ((class_a, class_b, cost) triples)
+/// overrides `across` per class pair
 #[pyfunction]
-fn py_detect_communities(
-    edges: Vec<(String, String, f64)>,
+#[pyo3(signature = (
+    ipa_a, ipa_b, classes=None, within=0.5, across=1.0, class_costs=None,
+    class_pair_costs=None, indel_cost=1.0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_sound_class_distance(
+    ipa_a: &str,
+    ipa_b: &str,
+    classes: Option<HashMap<String, String>>,
+    within: f64,
+    across: f64,
+    class_costs: Option<HashMap<String, f64>>,
+    class_pair_costs: Option<Vec<(String, String, f64)>>,
+    indel_cost: f64,
+) -> PyResult<f64> {
+    let model = sound_class_model(
+        classes,
+        within,
+        across,
+        class_costs,
+        class_pair_costs,
+        indel_cost,
+    )?;
+    Ok(phonetic_distance_with_costs(ipa_a, ipa_b, &model))
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    pairs, classes=None, within=0.5, across=1.0, class_costs=None,
+    class_pair_costs=None, indel_cost=1.0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_batch_sound_class_distance(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    classes: Option<HashMap<String, String>>,
+    within: f64,
+    across: f64,
+    class_costs: Option<HashMap<String, f64>>,
+    class_pair_costs: Option<Vec<(String, String, f64)>>,
+    indel_cost: f64,
+) -> PyResult<Vec<f64>> {
+    let model = sound_class_model(
+        classes,
+        within,
+        across,
+        class_costs,
+        class_pair_costs,
+        indel_cost,
+    )?;
+    py.allow_threads(|| Ok(batch_phonetic_distance_with_costs(&pairs, &model)))
+}
+
+#[pyfunction]
+fn py_dtw_align_with_costs(ipa_a: &str, ipa_b: &str, costs: CostTable) -> PyResult<PyAlignment> {
+    let model = cost_model(&costs, 1.0)?;
+    Ok(PyAlignment::from(dtw_align_with_costs(ipa_a, ipa_b, &model)))
+}
+
+/// (cost, [(segment_a, segment_b), ...]) summary of one DTW alignment
+type DtwSummaryRow = (f64, Vec<(String, String)>);
+
+/// DTW-align many (ipa_a, ipa_b) pairs in parallel: one (cost,
+/// correspondences) summary per pair, the correspondences being the
+/// substituted (segment_a, segment_b) pairs. `band` bounds the search to
+/// that many segments off the diagonal (widened to the length difference);
+/// `with_correspondences=False` computes costs only, skipping the paths
+#[pyfunction]
+#[pyo3(signature = (pairs, band=None, with_correspondences=true, costs=None))]
+fn py_batch_dtw_align(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    band: Option<usize>,
+    with_correspondences: bool,
+    costs: Option<CostTable>,
+) -> PyResult<Vec<DtwSummaryRow>> {
+    let model = match &costs {
+        Some(costs) => cost_model(costs, 1.0)?,
+        None => CostModel::default(),
+    };
+    py.allow_threads(|| {
+        Ok(batch_dtw_align(&pairs, &model, band, with_correspondences)
+            .into_iter()
+            .map(|summary| (summary.cost, summary.correspondences))
+            .collect())
+    })
+}
+
+/// Range, symmetry and identity of `metric` on each pair; an empty list
+/// means every check passed
+#[pyfunction]
+#[pyo3(signature = (pairs, metric="levenshtein", tolerance=1e-9))]
+fn py_check_similarity(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    metric: &str,
+    tolerance: f64,
+) -> PyResult<Vec<PyInvariantViolation>> {
+    let metric = similarity_metric(metric)?;
+    Ok(py.allow_threads(|| {
+        check_similarity(&pairs, metric, tolerance)
+            .into_iter()
+            .map(PyInvariantViolation::from)
+            .collect()
+    }))
+}
+
+/// Triangle inequality of `1 - similarity` over every triple of `forms`:
+/// (number of violating triples, the `max_reports` worst)
+#[pyfunction]
+#[pyo3(signature = (forms, metric="levenshtein", tolerance=1e-9, max_reports=100))]
+fn py_triangle_violations(
+    py: Python<'_>,
+    forms: Vec<String>,
+    metric: &str,
+    tolerance: f64,
+    max_reports: usize,
+) -> PyResult<(usize, Vec<PyInvariantViolation>)> {
+    let metric = similarity_metric(metric)?;
+    let (count, worst) =
+        py.allow_threads(|| triangle_violations(&forms, metric, tolerance, max_reports));
+    let worst = worst.into_iter().map(PyInvariantViolation::from).collect();
+    Ok((count, worst))
+}
+
+/// Cost and path consistency of the DTW and Needleman–Wunsch alignments of
+/// each pair
+#[pyfunction]
+#[pyo3(signature = (pairs, tolerance=1e-9))]
+fn py_check_alignments(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    tolerance: f64,
+) -> Vec<PyInvariantViolation> {
+    py.allow_threads(|| {
+        check_alignments(&pairs, tolerance)
+            .into_iter()
+            .map(PyInvariantViolation::from)
+            .collect()
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (ipa_strings, costs, indel_cost=1.0))]
+fn py_compute_similarity_matrix_with_costs<'py>(
+    py: Python<'py>,
+    ipa_strings: Vec<String>,
+    costs: CostTable,
+    indel_cost: f64,
+) -> PyResult<&'py PyArray2<f64>> {
+    let model = cost_model(&costs, indel_cost)?;
+    let matrix = py.allow_threads(|| compute_similarity_matrix_with_costs(&ipa_strings, &model));
+    Ok(matrix.into_pyarray(py))
+}
+
+/// Sound correspondences over DTW-aligned (ipa_a, ipa_b) word pairs, with
+/// word-position and segment contexts, expected counts, PMI and chi-square
+#[pyfunction]
+fn py_sound_correspondences(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+) -> PyResult<PySoundCorrespondences> {
+    py.allow_threads(|| {
+        let alignments: Vec<Alignment> = pairs.iter().map(|(a, b)| dtw_align(a, b)).collect();
+        Ok(PySoundCorrespondences {
+            inner: contextual_sound_correspondences(&alignments),
+        })
+    })
+}
+
+/// Build alignment parameters; `substitution` is "uniform", "feature",
+/// "sound_class" (default class costs) or "matrix" (requires `cost_matrix`
+/// as (segment_a, segment_b, cost) triples)
+fn align_params(
+    gap_open: f64,
+    gap_extend: f64,
+    substitution: &str,
+    cost_matrix: Option<Vec<(String, String, f64)>>,
+) -> PyResult<AlignParams> {
+    let substitution = match (substitution, cost_matrix) {
+        ("matrix", Some(costs)) => SubstitutionCost::from_triples(&costs),
+        ("matrix", None) => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "substitution='matrix' requires cost_matrix",
+            ))
+        }
+        (name, _) => name
+            .parse::<SubstitutionCost>()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?,
+    };
+    Ok(AlignParams {
+        gap_open,
+        gap_extend,
+        substitution,
+    })
+}
+
+/// Global (Needleman–Wunsch) alignment with affine gap penalties
+#[pyfunction]
+#[pyo3(signature = (
+    ipa_a, ipa_b, gap_open=1.0, gap_extend=0.5, substitution="uniform", cost_matrix=None
+))]
+fn py_nw_align(
+    ipa_a: &str,
+    ipa_b: &str,
+    gap_open: f64,
+    gap_extend: f64,
+    substitution: &str,
+    cost_matrix: Option<Vec<(String, String, f64)>>,
+) -> PyResult<PyAlignment> {
+    let params = align_params(gap_open, gap_extend, substitution, cost_matrix)?;
+    Ok(PyAlignment::from(nw_align(ipa_a, ipa_b, &params)))
+}
+
+/// Local (Smith–Waterman) alignment with affine gap penalties
+#[pyfunction]
+#[pyo3(signature = (
+    ipa_a, ipa_b, gap_open=1.0, gap_extend=0.5, substitution="uniform", cost_matrix=None
+))]
+fn py_sw_align(
+    ipa_a: &str,
+    ipa_b: &str,
+    gap_open: f64,
+    gap_extend: f64,
+    substitution: &str,
+    cost_matrix: Option<Vec<(String, String, f64)>>,
+) -> PyResult<PyAlignment> {
+    let params = align_params(gap_open, gap_extend, substitution, cost_matrix)?;
+    Ok(PyAlignment::from(sw_align(ipa_a, ipa_b, &params)))
+}
+
+/// Feature-weighted edit distance using the built-in IPA feature table
+/// (0 = identical)
+#[pyfunction]
+fn py_feature_distance(ipa_a: &str, ipa_b: &str) -> PyResult<f64> {
+    Ok(feature_distance(ipa_a, ipa_b))
+}
+
+#[pyfunction]
+fn py_batch_feature_distance(py: Python<'_>, pairs: Vec<(String, String)>) -> PyResult<Vec<f64>> {
+    py.allow_threads(|| Ok(batch_feature_distance(&pairs)))
+}
+
+/// Feature-weighted substitution costs between all segments of the forms'
+/// inventory, computed once: (segments, numpy cost matrix in that order)
+#[pyfunction]
+fn py_feature_distance_table<'py>(
+    py: Python<'py>,
+    forms: Vec<String>,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    let table = py.allow_threads(|| FeatureDistanceTable::from_forms(&forms));
+    let costs = table.matrix().into_pyarray(py);
+    Ok((table.segments().to_vec(), costs))
+}
+
+/// Per-pair feature matrix: (score names, pairs × scores numpy array) with
+/// every distance metric plus DTW alignment summary statistics
+#[pyfunction]
+fn py_batch_pair_features<'py>(
+    py: Python<'py>,
+    pairs: Vec<(String, String)>,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    let matrix = py.allow_threads(|| batch_pair_scores(&pairs));
+    let names = PAIR_SCORE_NAMES.iter().map(|name| name.to_string()).collect();
+    Ok((names, matrix.into_pyarray(py)))
+}
+
+/// Pairwise similarity matrix as an n × n numpy array (handed over without
+/// copying); metrics as in `py_phonetic_distance`
+#[pyfunction]
+#[pyo3(signature = (ipa_strings, metric="levenshtein"))]
+fn py_compute_similarity_matrix<'py>(
+    py: Python<'py>,
+    ipa_strings: Vec<String>,
+    metric: &str,
+) -> PyResult<&'py PyArray2<f64>> {
+    let metric = similarity_metric(metric)?;
+    let matrix = py.allow_threads(|| match metric {
+        SimilarityMetric::Levenshtein => compute_similarity_matrix(&ipa_strings),
+        metric => compute_similarity_matrix_with_metric(&ipa_strings, metric),
+    });
+    Ok(matrix.into_pyarray(py))
+}
+
+/// Compare two languages' lexicons of (concept, form) entries slot by slot:
+/// the closest form pair per concept (synonyms included) and one minus the
+/// mean best similarity as the lexicon distance. `concepts` defaults to
+/// every concept of either lexicon; metrics as in `py_phonetic_distance`
+#[pyfunction]
+#[pyo3(signature = (forms_a, forms_b, concepts=None, metric="levenshtein"))]
+fn py_lexicon_similarity(
+    py: Python<'_>,
+    forms_a: Vec<(String, String)>,
+    forms_b: Vec<(String, String)>,
+    concepts: Option<Vec<String>>,
+    metric: &str,
+) -> PyResult<PyLexiconSimilarity> {
+    let metric = similarity_metric(metric)?;
+    py.allow_threads(|| {
+        let result = lexicon_similarity(&forms_a, &forms_b, concepts.as_deref(), metric);
+        Ok(result.into())
+    })
+}
+
+/// The `k` concepts of (language, concept, form) entries whose forms, in
+/// any language, best match those of `concept`: candidates for polysemy
+/// or semantic shift, best first
+#[pyfunction]
+#[pyo3(signature = (entries, concept, k=10, metric="levenshtein"))]
+fn py_similar_concepts(
+    py: Python<'_>,
+    entries: WordlistRows,
+    concept: &str,
+    k: usize,
+    metric: &str,
+) -> PyResult<Vec<PyConceptNeighbor>> {
+    let metric = similarity_metric(metric)?;
+    py.allow_threads(|| {
+        let neighbors = similar_concepts(&entries, concept, k, metric)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(neighbors.into_iter().map(PyConceptNeighbor::from).collect())
+    })
+}
+
+/// Similarity distribution of `sample_size` random distinct pairs of
+/// `ipa_strings` (reservoir-sampled, seeded): quantiles and an edge-count
+/// estimate per threshold in seconds, before the full O(n²) job
+#[pyfunction]
+#[pyo3(signature = (ipa_strings, sample_size=10000, metric="levenshtein", seed=0))]
+fn py_sample_pair_stats(
+    py: Python<'_>,
+    ipa_strings: Vec<String>,
+    sample_size: usize,
+    metric: &str,
+    seed: u64,
+) -> PyResult<PyPairSampleStats> {
+    let metric = similarity_metric(metric)?;
+    py.allow_threads(|| sample_pair_stats(&ipa_strings, sample_size, metric, seed))
+        .map(PyPairSampleStats::from)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Compute similarity blocks with the GIL released, handing each to `sink`
+/// and then calling `progress(done_pairs, total_pairs)`. Returns false if
+/// the callback returned False (cancellation); Ctrl-C and exceptions raised
+/// by the callback abort with an error.
+fn run_similarity_blocks(
+    py: Python<'_>,
+    mut blocks: SimilarityBlocks<'_>,
+    progress: Option<&PyAny>,
+    mut sink: impl FnMut(SimilarityBlock),
+) -> PyResult<bool> {
+    while let Some(block) = py.allow_threads(|| blocks.next()) {
+        let (done, total) = (block.done, block.total);
+        sink(block);
+        py.check_signals()?;
+        if let Some(callback) = progress {
+            let result = callback.call1((done, total))?;
+            if result.extract::<bool>().ok() == Some(false) {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Dense similarity matrix computed `block_size` rows at a time, calling
+/// `progress(done_pairs, total_pairs)` between blocks; returns None if the
+/// callback returns False
+#[pyfunction]
+#[pyo3(signature = (ipa_strings, block_size=1024, progress=None, metric="levenshtein"))]
+fn py_compute_similarity_matrix_chunked<'py>(
+    py: Python<'py>,
+    ipa_strings: Vec<String>,
+    block_size: usize,
+    progress: Option<&PyAny>,
+    metric: &str,
+) -> PyResult<Option<&'py PyArray2<f64>>> {
+    let blocks = similarity_blocks(&ipa_strings, block_size, f64::NEG_INFINITY)
+        .with_metric(similarity_metric(metric)?);
+    let n = ipa_strings.len();
+    let mut matrix = ndarray::Array2::<f64>::eye(n);
+    let finished = run_similarity_blocks(py, blocks, progress, |block| {
+        for (i, j, sim) in block.pairs {
+            matrix[[i, j]] = sim;
+            matrix[[j, i]] = sim;
+        }
+    })?;
+    Ok(finished.then(|| matrix.into_pyarray(py)))
+}
+
+fn check_id_count(ids: &[String], ipa_strings: &[String]) -> PyResult<()> {
+    if ids.len() != ipa_strings.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} ids for {} IPA strings",
+            ids.len(),
+            ipa_strings.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Sparse similarity matrix over `ids` keeping only pairs at or above
+/// `threshold`, computed block by block without the dense matrix; progress
+/// and cancellation as in `py_compute_similarity_matrix_chunked`. With
+/// `length_filter`, pairs whose segment lengths alone keep them below the
+/// threshold are skipped without being scored (same result, less work)
+#[pyfunction]
+#[pyo3(signature = (
+    ids,
+    ipa_strings,
+    threshold,
+    block_size=1024,
+    progress=None,
+    metric="levenshtein",
+    length_filter=true
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_similarity_matrix_sparse(
+    py: Python<'_>,
+    ids: Vec<String>,
+    ipa_strings: Vec<String>,
     threshold: f64,
-    resolution: f64,
-) -> PyResult<Vec<Vec<String>>> {
-    let similarity_edges: Vec<SimilarityEdge> = edges
+    block_size: usize,
+    progress: Option<&PyAny>,
+    metric: &str,
+    length_filter: bool,
+) -> PyResult<Option<PySparseMatrix>> {
+    validate_threshold(threshold)?;
+    check_id_count(&ids, &ipa_strings)?;
+    let blocks = similarity_blocks(&ipa_strings, block_size, threshold)
+        .with_metric(similarity_metric(metric)?)
+        .with_length_filter(length_filter);
+    let mut pairs = Vec::new();
+    let finished = run_similarity_blocks(py, blocks, progress, |block| pairs.extend(block.pairs))?;
+    if !finished {
+        return Ok(None);
+    }
+    let inner = py
+        .allow_threads(|| SparseSimilarityMatrix::from_similarity_pairs(ids, &pairs))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(Some(PySparseMatrix { inner }))
+}
+
+/// Sparse matrix keeping each entry's `k` most similar entries (at or above
+/// `min_sim`), computed in Rust without a dense matrix; `blocking` is a
+/// phonetic key scheme ("consonants", "full" or "dolgopolsky") limiting
+/// comparisons to words sharing a key. Rows are top-k independently, so the
+/// matrix need not be symmetric
+#[pyfunction]
+#[pyo3(signature = (ids, ipa_strings, k, min_sim=0.0, metric="levenshtein", blocking=None))]
+fn py_similarity_matrix_topk(
+    py: Python<'_>,
+    ids: Vec<String>,
+    ipa_strings: Vec<String>,
+    k: usize,
+    min_sim: f64,
+    metric: &str,
+    blocking: Option<&str>,
+) -> PyResult<PySparseMatrix> {
+    let metric = similarity_metric(metric)?;
+    let blocking = blocking
+        .map(str::parse::<KeyScheme>)
+        .transpose()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let inner = py
+        .allow_threads(|| {
+            SparseSimilarityMatrix::from_ipa_topk(ids, &ipa_strings, k, min_sim, metric, blocking)
+        })
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(PySparseMatrix { inner })
+}
+
+fn orthography_options(
+    casefold: bool,
+    strip_diacritics: bool,
+    unit: &str,
+) -> PyResult<OrthographyOptions> {
+    let unit: OrthoUnit = unit
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(OrthographyOptions {
+        casefold,
+        strip_diacritics,
+        unit,
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (text, casefold=true, strip_diacritics=false, unit="grapheme"))]
+fn py_segment_orthography(
+    text: &str,
+    casefold: bool,
+    strip_diacritics: bool,
+    unit: &str,
+) -> PyResult<Vec<String>> {
+    let options = orthography_options(casefold, strip_diacritics, unit)?;
+    Ok(segment_orthography(text, &options))
+}
+
+#[pyfunction]
+#[pyo3(signature = (form_a, form_b, casefold=true, strip_diacritics=false, unit="grapheme"))]
+fn py_orthographic_similarity(
+    form_a: &str,
+    form_b: &str,
+    casefold: bool,
+    strip_diacritics: bool,
+    unit: &str,
+) -> PyResult<f64> {
+    let options = orthography_options(casefold, strip_diacritics, unit)?;
+    Ok(orthographic_similarity(form_a, form_b, &options))
+}
+
+#[pyfunction]
+#[pyo3(signature = (pairs, casefold=true, strip_diacritics=false, unit="grapheme"))]
+fn py_batch_orthographic_similarity(
+    py: Python<'_>,
+    pairs: Vec<(String, String)>,
+    casefold: bool,
+    strip_diacritics: bool,
+    unit: &str,
+) -> PyResult<Vec<f64>> {
+    py.allow_threads(|| {
+        let options = orthography_options(casefold, strip_diacritics, unit)?;
+        Ok(batch_orthographic_similarity(&pairs, &options))
+    })
+}
+
+/// (language_a, language_b, row segments, column segments, counts)
+type CorrespondenceTuple<'py> = (String, String, Vec<String>, Vec<String>, &'py PyArray2<u64>);
+
+/// Segment × segment correspondence counts (numpy uint64) per language
+/// pair; `cognate_sets` holds (language, ipa) members
+#[pyfunction]
+fn py_correspondence_matrices<'py>(
+    py: Python<'py>,
+    cognate_sets: Vec<Vec<(String, String)>>,
+) -> PyResult<Vec<CorrespondenceTuple<'py>>> {
+    let matrices = py.allow_threads(|| correspondence_matrices(&cognate_sets));
+    Ok(matrices
         .into_iter()
-        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
-        .collect();
+        .map(|m| {
+            let counts = m.counts.into_pyarray(py);
+            (m.language_a, m.language_b, m.segments_a, m.segments_b, counts)
+        })
+        .collect())
+}
 
-    let graph = CognateGraph::from_edges(similarity_edges, threshold);
-    Ok(graph.detect_communities(resolution))
+/// Same matrices as a JSON array (counts as nested lists)
+#[pyfunction]
+fn py_correspondence_matrices_json(
+    py: Python<'_>,
+    cognate_sets: Vec<Vec<(String, String)>>,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        Ok(correspondence_matrices_json(&correspondence_matrices(&cognate_sets)))
+    })
 }
 
+/// Bootstrap confidence for correspondences seen at least `min_count`
+/// times: each language pair's aligned sites are resampled `replicates`
+/// times for a `level` percentile interval on each correspondence's
+/// frequency and the share of replicates backing it as a sound law
 #[pyfunction]
-fn py_compute_pagerank(
-    edges: Vec<(String, String, f64)>,
+#[pyo3(signature = (cognate_sets, replicates=1000, level=0.95, min_count=1, seed=0))]
+fn py_bootstrap_correspondences(
+    py: Python<'_>,
+    cognate_sets: Vec<Vec<(String, String)>>,
+    replicates: usize,
+    level: f64,
+    min_count: u64,
+    seed: u64,
+) -> PyResult<Vec<PyCorrespondenceConfidence>> {
+    py.allow_threads(|| {
+        bootstrap_correspondences(&cognate_sets, replicates, level, min_count, seed)
+    })
+    .map(|confidences| confidences.into_iter().map(Into::into).collect())
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Greedy correspondence-pattern clustering over star-aligned cognate sets
+/// of (language, ipa) members
+#[pyfunction]
+fn py_correspondence_patterns(
+    py: Python<'_>,
+    cognate_sets: Vec<Vec<(String, String)>>,
+) -> PyResult<PyCorrespondencePatterns> {
+    py.allow_threads(|| {
+        let inner = correspondence_patterns(&cognate_sets);
+        Ok(PyCorrespondencePatterns { inner })
+    })
+}
+
+/// Candidate proto-form of a cognate set's IPA reflexes, reconstructed
+/// column by column by feature-weighted majority vote over their star
+/// alignment, with the confidence of each reconstructed segment
+#[pyfunction]
+fn py_reconstruct_proto(
+    py: Python<'_>,
+    cognate_set_ipa: Vec<String>,
+) -> PyResult<(String, Vec<f64>)> {
+    let proto = py.allow_threads(|| reconstruct_proto(&cognate_set_ipa))?;
+    Ok((proto.form, proto.confidence))
+}
+
+/// `py_reconstruct_proto` for many cognate sets in parallel
+#[pyfunction]
+fn py_reconstruct_protos(
+    py: Python<'_>,
+    cognate_sets: Vec<Vec<String>>,
+) -> PyResult<Vec<(String, Vec<f64>)>> {
+    let protos = py.allow_threads(|| reconstruct_protos(&cognate_sets))?;
+    Ok(protos.into_iter().map(|p| (p.form, p.confidence)).collect())
+}
+
+/// Members of (language, ipa) cognate sets whose best alignment against
+/// the other members is still improbable under the learned correspondences
+/// (mean surprisal of at least `min_score` nats per column)
+#[pyfunction]
+#[pyo3(signature = (cognate_sets, min_score=2.0))]
+fn py_detect_transcription_errors(
+    py: Python<'_>,
+    cognate_sets: Vec<Vec<(String, String)>>,
+    min_score: f64,
+) -> Vec<PyTranscriptionFlag> {
+    py.allow_threads(|| {
+        detect_transcription_errors(&cognate_sets, min_score)
+            .into_iter()
+            .map(PyTranscriptionFlag::from)
+            .collect()
+    })
+}
+
+/// Morpheme-level cognate sets for (id, language, concept, form) entries;
+/// `segmentation` is "markers" (user-provided `+`/`-`/`_`/space boundaries)
+/// or "syllables" (markers plus automatic syllable splitting)
+#[pyfunction]
+#[pyo3(signature = (entries, threshold=0.6, segmentation="markers"))]
+fn py_detect_partial_cognates(
+    py: Python<'_>,
+    entries: EntryRows,
     threshold: f64,
-    damping: f64,
-    iterations: usize,
-) -> PyResult<Vec<(String, f64)>> {
-    let similarity_edges: Vec<SimilarityEdge> = edges
-        .into_iter()
-        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
-        .collect();
+    segmentation: &str,
+) -> PyResult<Vec<PyPartialCognates>> {
+    validate_threshold(threshold)?;
+    let segmentation = segmentation
+        .parse::<MorphemeSegmentation>()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let params = PartialCognateParams {
+        threshold,
+        segmentation,
+    };
+    py.allow_threads(|| {
+        let words = detect_partial_cognates(&entries, &params);
+        Ok(words.into_iter().map(PyPartialCognates::from).collect())
+    })
+}
 
-    let graph = CognateGraph::from_edges(similarity_edges, threshold);
-    let ranks = graph.compute_pagerank(damping, iterations);
-    
-    let mut result: Vec<(String, f64)> = ranks.into_iter().collect();
-    result.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-    
-    Ok(result)
+/// Word-level edges (id_a, id_b, shared-set Jaccard) from partial cognate
+/// annotations, ready for graph construction
+#[pyfunction]
+fn py_partial_cognate_edges(
+    py: Python<'_>,
+    words: Vec<PyPartialCognates>,
+) -> PyResult<Vec<WeightedEdge>> {
+    let words: Vec<PartialCognates> = words.iter().map(PartialCognates::from).collect();
+    py.allow_threads(|| Ok(partial_cognate_edges(&words)))
+}
+
+fn sound_change_rules(rules: &[String]) -> PyResult<Vec<SoundChangeRule>> {
+    parse_rules(rules).map_err(pyo3::exceptions::PyValueError::new_err)
 }
 
+/// Apply ordered sound change rules ("p > f / #_", "e > i / _r") to IPA
+/// forms, returning the derived forms
 #[pyfunction]
-fn py_graph_stats(edges: Vec<(String, String, f64)>, threshold: f64) -> PyResult<PyGraphStats> {
-    let similarity_edges: Vec<SimilarityEdge> = edges
-        .into_iter()
-        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
-        .collect();
+fn py_apply_sound_changes(
+    py: Python<'_>,
+    words: Vec<String>,
+    rules: Vec<String>,
+) -> PyResult<Vec<String>> {
+    let rules = sound_change_rules(&rules)?;
+    py.allow_threads(|| Ok(apply_sound_changes(&words, &rules)))
+}
 
-    let graph = CognateGraph::from_edges(similarity_edges, threshold);
-    Ok(PyGraphStats::from(graph.stats()))
+/// `py_apply_sound_changes` with the steps: per word, each rule that
+/// changed it with the form before and after
+#[pyfunction]
+fn py_derive_sound_changes(
+    py: Python<'_>,
+    words: Vec<String>,
+    rules: Vec<String>,
+) -> PyResult<Vec<PyDerivation>> {
+    let rules = sound_change_rules(&rules)?;
+    py.allow_threads(|| {
+        let derivations = derive_sound_changes(&words, &rules);
+        Ok(derivations.into_iter().map(PyDerivation::from).collect())
+    })
 }
 
+/// Derive each proto-form with `rules` and compare it with the attested
+/// reflex at the same position
 #[pyfunction]
-fn py_graph_to_json(edges: Vec<(String, String, f64)>, threshold: f64) -> PyResult<String> {
-    let similarity_edges: Vec<SimilarityEdge> = edges
-        .into_iter()
-        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
-        .collect();
+fn py_evaluate_rules(
+    py: Python<'_>,
+    proto_forms: Vec<String>,
+    attested: Vec<String>,
+    rules: Vec<String>,
+) -> PyResult<PyRuleEvaluation> {
+    if proto_forms.len() != attested.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} proto-forms for {} attested forms",
+            proto_forms.len(),
+            attested.len()
+        )));
+    }
+    let rules = sound_change_rules(&rules)?;
+    py.allow_threads(|| Ok(evaluate_rules(&proto_forms, &attested, &rules).into()))
+}
+
+/// Feeding / bleeding between `rules` on the proto-forms, and which rule
+/// orders the attested reflexes support or contradict
+#[pyfunction]
+fn py_analyze_rule_ordering(
+    py: Python<'_>,
+    proto_forms: Vec<String>,
+    attested: Vec<String>,
+    rules: Vec<String>,
+) -> PyResult<PyRuleOrdering> {
+    if proto_forms.len() != attested.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} proto-forms for {} attested forms",
+            proto_forms.len(),
+            attested.len()
+        )));
+    }
+    let rules = sound_change_rules(&rules)?;
+    py.allow_threads(|| Ok(analyze_rule_ordering(&proto_forms, &attested, &rules).into()))
+}
+
+// ============================================================================
+// GRAPH FUNCTIONS
+// ============================================================================
+
+/// Build a reusable graph handle (construct once, query many times)
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, node_attributes=None))]
+fn py_build_cognate_graph(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    node_attributes: Option<NodeAttributes>,
+) -> PyResult<PyCognateGraph> {
+    validate_threshold(threshold)?;
+    PyCognateGraph::new(py, edges, threshold, node_attributes)
+}
 
-    let graph = CognateGraph::from_edges(similarity_edges, threshold);
-    Ok(graph.to_json())
+#[pyfunction]
+fn py_find_cognate_sets(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<Vec<PyCognateSet>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        let sets = graph.find_cognate_sets();
+    
+        Ok(sets.into_iter().map(PyCognateSet::from).collect())
+    })
+}
+
+/// Louvain communities; with `return_hierarchy=True`, every level of the
+/// hierarchy (finest first) instead of only the coarsest partition
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, resolution=1.0, return_hierarchy=false))]
+fn py_detect_communities(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    resolution: f64,
+    return_hierarchy: bool,
+) -> PyResult<PyObject> {
+    validate_threshold(threshold)?;
+    let graph = py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        CognateGraph::from_edges(similarity_edges, threshold)
+    });
+    Ok(communities_to_py(py, &graph, resolution, return_hierarchy))
+}
+
+/// Weighted label propagation communities: near-linear time for very large
+/// graphs, deterministic for a given seed
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, max_iter=100, seed=0))]
+fn py_detect_communities_lpa(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    max_iter: usize,
+    seed: u64,
+) -> PyResult<Vec<Vec<String>>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.label_propagation(max_iter, seed))
+    })
+}
+
+/// Louvain modularity and average clustering coefficient of the graph and
+/// of `n_samples` random graphs with the same degrees, for empirical
+/// p-values; `model` is "rewire" (degree-preserving edge swaps) or
+/// "configuration" (weighted configuration model). Sample `i` is drawn with
+/// seed `seed + i`
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, n_samples=100, seed=0, model="rewire", resolution=1.0))]
+fn py_null_model_stats(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    n_samples: usize,
+    seed: u64,
+    model: &str,
+    resolution: f64,
+) -> PyResult<PyNullModelStats> {
+    validate_threshold(threshold)?;
+    let model: NullModel = model
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        graph
+            .null_model_stats(model, n_samples, resolution, seed)
+            .into()
+    }))
+}
+
+fn communities_to_py(
+    py: Python<'_>,
+    graph: &CognateGraph,
+    resolution: f64,
+    return_hierarchy: bool,
+) -> PyObject {
+    let mut levels = py.allow_threads(|| graph.louvain_hierarchy(resolution));
+    if return_hierarchy {
+        let levels: Vec<Vec<Vec<String>>> = levels.into_iter().map(|l| l.communities).collect();
+        levels.into_py(py)
+    } else {
+        levels.pop().map(|l| l.communities).unwrap_or_default().into_py(py)
+    }
+}
+
+/// Maximum-weight spanning forest as (source, target, weight) edges, or
+/// with `as_json=True` as a `to_json` graph string over all nodes
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, as_json=false))]
+fn py_max_spanning_forest(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    as_json: bool,
+) -> PyResult<PyObject> {
+    validate_threshold(threshold)?;
+    let graph = py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        CognateGraph::from_edges(similarity_edges, threshold)
+    });
+    Ok(spanning_forest_to_py(py, &graph, as_json))
+}
+
+fn spanning_forest_to_py(py: Python<'_>, graph: &CognateGraph, as_json: bool) -> PyObject {
+    if as_json {
+        py.allow_threads(|| graph.max_spanning_forest_graph().to_json()).into_py(py)
+    } else {
+        py.allow_threads(|| graph.max_spanning_forest()).into_py(py)
+    }
+}
+
+#[pyfunction]
+fn py_compute_pagerank(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    damping: f64,
+    iterations: usize,
+) -> PyResult<Vec<(String, f64)>> {
+    validate_threshold(threshold)?;
+    validate_damping(damping)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        let ranks = graph.compute_pagerank(damping, iterations);
+
+        // Ranks are keyed by ID, so the stable sort breaks ties by ID
+        let mut result: Vec<(String, f64)> = ranks.into_iter().collect();
+        result.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Ok(result)
+    })
+}
+
+/// PageRank restarting at the `seeds` ({id: weight}, or None for every
+/// node), iterated until the total change is at most `tol`; highest first
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, seeds, damping=0.85, tol=1e-10, max_iterations=1000))]
+fn py_personalized_pagerank(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    seeds: Option<HashMap<String, f64>>,
+    damping: f64,
+    tol: f64,
+    max_iterations: usize,
+) -> PyResult<Vec<(String, f64)>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        let ranks =
+            graph.compute_personalized_pagerank(seeds.as_ref(), damping, tol, max_iterations)?;
+
+        let mut result: Vec<(String, f64)> = ranks.into_iter().collect();
+        result.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Ok(result)
+    })
+}
+
+/// Weighted betweenness (edge length = 1 / similarity), highest first
+#[pyfunction]
+fn py_compute_betweenness(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<Vec<(String, f64)>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.compute_betweenness())
+    })
+}
+
+/// Weighted closeness (edge length = 1 / similarity), highest first
+#[pyfunction]
+fn py_compute_closeness(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<Vec<(String, f64)>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.compute_closeness())
+    })
+}
+
+#[pyfunction]
+fn py_compute_centralities(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    damping: f64,
+    iterations: usize,
+) -> PyResult<Vec<PyNodeCentrality>> {
+    validate_threshold(threshold)?;
+    validate_damping(damping)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        let centralities = graph.compute_centralities(damping, iterations);
+
+        Ok(centralities.into_iter().map(PyNodeCentrality::from).collect())
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, node_filter=None, edge_filter=None, node_attributes=None))]
+fn py_filter_graph(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    node_filter: Option<&str>,
+    edge_filter: Option<&str>,
+    node_attributes: Option<NodeAttributes>,
+) -> PyResult<Vec<(String, String, f64)>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let node_predicate = node_filter
+            .map(Predicate::parse)
+            .transpose()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let edge_predicate = edge_filter
+            .map(Predicate::parse)
+            .transpose()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let mut graph = CognateGraph::from_edges(similarity_edges, threshold);
+        if let Some(attributes) = node_attributes {
+            graph.set_node_attributes(attributes);
+        }
+
+        let filtered = graph.filter(node_predicate.as_ref(), edge_predicate.as_ref());
+        Ok(filtered.edge_list())
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, node_attributes, n, language_key="language"))]
+fn py_top_edges_per_language_pair(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    node_attributes: NodeAttributes,
+    n: usize,
+    language_key: &str,
+) -> PyResult<Vec<(String, String, Vec<WeightedEdge>)>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let mut graph = CognateGraph::from_edges(similarity_edges, threshold);
+        graph.set_node_attributes(node_attributes);
+
+        Ok(graph
+            .top_edges_per_language_pair(language_key, n)
+            .into_iter()
+            .map(|((lang_a, lang_b), top)| (lang_a, lang_b, top))
+            .collect())
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, method, target_size, seed=0))]
+fn py_sample_graph(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    method: &str,
+    target_size: usize,
+    seed: u64,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let method: SamplingMethod = method
+            .parse()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.sample(method, target_size, seed).edge_list())
+    })
+}
+
+/// Parse a sparsification method name, overriding its default k, exponent
+/// or ratio with `parameter`
+fn sparsification_method(method: &str, parameter: Option<f64>) -> PyResult<SparsificationMethod> {
+    let method: SparsificationMethod = method
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    match parameter {
+        Some(value) => method
+            .with_parameter(value)
+            .map_err(pyo3::exceptions::PyValueError::new_err),
+        None => Ok(method),
+    }
+}
+
+/// Fit per-language-pair thresholds from (lang_a, lang_b, score) scores of
+/// unrelated word pairs
+#[pyfunction]
+#[pyo3(signature = (unrelated, quantile=0.95))]
+fn py_calibrate_pair_thresholds(
+    py: Python<'_>,
+    unrelated: Vec<(String, String, f64)>,
+    quantile: f64,
+) -> PyPairThresholds {
+    py.allow_threads(|| PyPairThresholds {
+        inner: PairThresholds::fit(&unrelated, quantile),
+    })
+}
+
+/// Map from raw similarity to P(cognate) fitted on (score, is_cognate)
+/// samples; `method` is "platt" (sigmoid) or "isotonic" (monotone steps)
+#[pyfunction]
+#[pyo3(signature = (samples, method="isotonic"))]
+fn py_calibrate_probabilities(
+    py: Python<'_>,
+    samples: Vec<(f64, bool)>,
+    method: &str,
+) -> PyResult<PyProbabilityCalibration> {
+    let method: ProbabilityMethod = method
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let inner = py
+        .allow_threads(|| ProbabilityCalibration::fit(&samples, method))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(PyProbabilityCalibration { inner })
+}
+
+/// ROC and precision–recall curves with their areas for a score and gold
+/// cognate label per pair; `max_points` thins the returned curves (the
+/// areas always use every point)
+#[pyfunction]
+#[pyo3(signature = (scores, labels, max_points=None))]
+fn py_score_curves(
+    py: Python<'_>,
+    scores: Vec<f64>,
+    labels: Vec<bool>,
+    max_points: Option<usize>,
+) -> PyResult<PyScoreCurves> {
+    if scores.len() != labels.len() {
+        return Err(LangVizError::DimensionMismatch {
+            what: "labels".to_string(),
+            expected: format!("({},)", scores.len()),
+            actual: format!("({},)", labels.len()),
+        }
+        .into());
+    }
+    let samples: Vec<(f64, bool)> = scores.into_iter().zip(labels).collect();
+    let inner = py
+        .allow_threads(|| score_curves(&samples, max_points))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(PyScoreCurves { inner })
+}
+
+/// `py_score_curves` for several score columns (e.g. one per metric or
+/// parameter setting) against the same labels, in parallel
+#[pyfunction]
+#[pyo3(signature = (score_columns, labels, max_points=None))]
+fn py_batch_score_curves(
+    py: Python<'_>,
+    score_columns: Vec<Vec<f64>>,
+    labels: Vec<bool>,
+    max_points: Option<usize>,
+) -> PyResult<Vec<PyScoreCurves>> {
+    let curves = py
+        .allow_threads(|| batch_score_curves(&score_columns, &labels, max_points))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(curves
+        .into_iter()
+        .map(|inner| PyScoreCurves { inner })
+        .collect())
+}
+
+/// Cohen's (per annotator pair) and Fleiss' kappa of (annotator,
+/// partition) annotations of the same items, over the pair decisions
+/// "same cognate set or not" their partitions imply
+#[pyfunction]
+fn py_annotator_agreement(
+    py: Python<'_>,
+    annotations: Vec<(String, Vec<Vec<String>>)>,
+) -> PyResult<PyAgreement> {
+    let inner = py
+        .allow_threads(|| annotator_agreement(&annotations))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(PyAgreement { inner })
+}
+
+/// The `top_k` unlabeled (source, target, score) candidates most worth
+/// labeling: scores near `threshold` whose label would flip the most
+/// co-memberships in the threshold clustering; (source, target, is_cognate)
+/// labels override their pairs' scores and are never suggested
+#[pyfunction]
+#[pyo3(signature = (candidates, labeled=None, threshold=0.5, top_k=20))]
+fn py_suggest_pairs(
+    py: Python<'_>,
+    candidates: Vec<(String, String, f64)>,
+    labeled: Option<Vec<(String, String, bool)>>,
+    threshold: f64,
+    top_k: usize,
+) -> PyResult<Vec<PyPairSuggestion>> {
+    let labeled = labeled.unwrap_or_default();
+    let suggestions =
+        py.allow_threads(|| suggest_pairs(&candidates, &labeled, threshold, top_k))?;
+    Ok(suggestions
+        .into_iter()
+        .map(|inner| PyPairSuggestion { inner })
+        .collect())
+}
+
+/// Synthetic wordlist evolved from random proto-forms, with known cognate
+/// classes; `sound_changes` defaults to a built-in pool
+#[pyfunction]
+#[pyo3(signature = (
+    num_languages=5,
+    num_concepts=100,
+    min_syllables=2,
+    max_syllables=3,
+    sound_changes=None,
+    changes_per_language=3,
+    replacement_rate=0.1,
+    borrowing_rate=0.05,
+    seed=0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_generate_wordlist(
+    py: Python<'_>,
+    num_languages: usize,
+    num_concepts: usize,
+    min_syllables: usize,
+    max_syllables: usize,
+    sound_changes: Option<Vec<(String, String)>>,
+    changes_per_language: usize,
+    replacement_rate: f64,
+    borrowing_rate: f64,
+    seed: u64,
+) -> PySyntheticDataset {
+    let defaults = SyntheticParams::default();
+    let params = SyntheticParams {
+        num_languages,
+        num_concepts,
+        min_syllables,
+        max_syllables,
+        sound_changes: sound_changes.unwrap_or(defaults.sound_changes),
+        changes_per_language,
+        replacement_rate,
+        borrowing_rate,
+        seed,
+    };
+    py.allow_threads(|| PySyntheticDataset {
+        inner: generate_wordlist(&params),
+    })
+}
+
+/// Evolve random proto-forms down a Newick tree; branch lengths scale the
+/// expected replacements and sound changes. Leaves become the languages.
+#[pyfunction]
+#[pyo3(signature = (
+    newick,
+    num_concepts=100,
+    min_syllables=2,
+    max_syllables=3,
+    sound_changes=None,
+    sound_change_rate=1.0,
+    replacement_rate=0.1,
+    seed=0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_simulate_tree(
+    py: Python<'_>,
+    newick: &str,
+    num_concepts: usize,
+    min_syllables: usize,
+    max_syllables: usize,
+    sound_changes: Option<Vec<(String, String)>>,
+    sound_change_rate: f64,
+    replacement_rate: f64,
+    seed: u64,
+) -> PyResult<PySyntheticDataset> {
+    let tree = Tree::from_newick(newick).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let defaults = TreeSimulationParams::default();
+    let params = TreeSimulationParams {
+        num_concepts,
+        min_syllables,
+        max_syllables,
+        sound_changes: sound_changes.unwrap_or(defaults.sound_changes),
+        sound_change_rate,
+        replacement_rate,
+        seed,
+    };
+    Ok(py.allow_threads(|| PySyntheticDataset {
+        inner: simulate_tree(&tree, &params),
+    }))
+}
+
+/// Inject transcription noise (segment swaps, diacritic loss) into IPA
+/// forms; deterministic for a given seed
+#[pyfunction]
+#[pyo3(signature = (forms, swap_rate=0.05, diacritic_loss_rate=0.1, seed=0))]
+fn py_perturb_ipa(
+    py: Python<'_>,
+    forms: Vec<String>,
+    swap_rate: f64,
+    diacritic_loss_rate: f64,
+    seed: u64,
+) -> Vec<String> {
+    let params = NoiseParams {
+        swap_rate,
+        diacritic_loss_rate,
+        seed,
+    };
+    py.allow_threads(|| perturb_forms(&forms, &params))
+}
+
+/// How threshold clustering of (id, concept, ipa) entries degrades as the
+/// noise rates are scaled by each of `scales`
+#[pyfunction]
+#[pyo3(signature = (
+    entries,
+    threshold=0.6,
+    scales=vec![0.5, 1.0, 2.0, 4.0],
+    swap_rate=0.05,
+    diacritic_loss_rate=0.1,
+    trials=5,
+    seed=0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_noise_robustness(
+    py: Python<'_>,
+    entries: Vec<(String, String, String)>,
+    threshold: f64,
+    scales: Vec<f64>,
+    swap_rate: f64,
+    diacritic_loss_rate: f64,
+    trials: usize,
+    seed: u64,
+) -> PyResult<Vec<PyRobustnessPoint>> {
+    validate_threshold(threshold)?;
+    let params = NoiseParams {
+        swap_rate,
+        diacritic_loss_rate,
+        seed,
+    };
+    Ok(py.allow_threads(|| {
+        robustness_curve(&entries, threshold, &params, &scales, trials)
+            .into_iter()
+            .map(PyRobustnessPoint::from)
+            .collect()
+    }))
+}
+
+/// Keep each node's locally strongest edges instead of applying a global
+/// threshold
+#[pyfunction]
+#[pyo3(signature = (edges, method="top_k", parameter=None))]
+fn py_sparsify_graph(
+    py: Python<'_>,
+    edges: EdgeRows,
+    method: &str,
+    parameter: Option<f64>,
+) -> PyResult<Vec<WeightedEdge>> {
+    py.allow_threads(|| {
+        let method = sparsification_method(method, parameter)?;
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, f64::NEG_INFINITY);
+        Ok(graph.sparsify(method).edge_list())
+    })
+}
+
+/// InvalidParameter unless k >= 1 and the boost / penalty factors are
+/// finite and non-negative
+fn validate_reweight(k: usize, boost: f64, penalty: f64) -> PyResult<()> {
+    let invalid = |name: &str, value: f64, expected: &str| LangVizError::InvalidParameter {
+        name: name.to_string(),
+        value,
+        expected: expected.to_string(),
+    };
+    if k == 0 {
+        return Err(invalid("k", 0.0, "k >= 1").into());
+    }
+    for (name, value) in [("boost", boost), ("penalty", penalty)] {
+        if !(value.is_finite() && value >= 0.0) {
+            return Err(invalid(name, value, "a finite value >= 0").into());
+        }
+    }
+    Ok(())
+}
+
+/// Reweight edges by mutual kNN agreement: boosted when both endpoints rank
+/// the edge in their top k, penalized when only one (or neither) does
+#[pyfunction]
+#[pyo3(signature = (edges, k=5, boost=1.2, penalty=0.5))]
+fn py_mutual_knn_reweight(
+    py: Python<'_>,
+    edges: EdgeRows,
+    k: usize,
+    boost: f64,
+    penalty: f64,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_reweight(k, boost, penalty)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, f64::NEG_INFINITY);
+        Ok(graph.mutual_knn_reweight(k, boost, penalty).edge_list())
+    })
+}
+
+/// Cognate sets (connected components) of the sparsified graph
+#[pyfunction]
+#[pyo3(signature = (edges, method="top_k", parameter=None))]
+fn py_find_cognate_sets_sparsified(
+    py: Python<'_>,
+    edges: EdgeRows,
+    method: &str,
+    parameter: Option<f64>,
+) -> PyResult<Vec<PyCognateSet>> {
+    py.allow_threads(|| {
+        let method = sparsification_method(method, parameter)?;
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, f64::NEG_INFINITY);
+        let sets = graph.sparsify(method).find_cognate_sets();
+        Ok(sets.into_iter().map(PyCognateSet::from).collect())
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, aggregation, threshold, label_weights=None))]
+fn py_aggregate_multi_edges(
+    py: Python<'_>,
+    edges: Vec<(String, String, f64, String)>,
+    aggregation: &str,
+    threshold: f64,
+    label_weights: Option<HashMap<String, f64>>,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    let aggregation = edge_aggregation(aggregation, label_weights)?;
+    Ok(py.allow_threads(|| {
+        let multigraph = CognateMultiGraph::from_labeled_edges(edges);
+        multigraph.aggregate(&aggregation, threshold).edge_list()
+    }))
+}
+
+/// Parse an aggregation policy; `label_weights` fill in "weighted"
+fn edge_aggregation(
+    aggregation: &str,
+    label_weights: Option<HashMap<String, f64>>,
+) -> PyResult<EdgeAggregation> {
+    let mut aggregation: EdgeAggregation = aggregation
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    if let (EdgeAggregation::Weighted(weights), Some(label_weights)) =
+        (&mut aggregation, label_weights)
+    {
+        *weights = label_weights;
+    }
+    Ok(aggregation)
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges_a, edges_b, threshold, operation, policy="max"))]
+fn py_graph_set_operation(
+    py: Python<'_>,
+    edges_a: EdgeRows,
+    edges_b: EdgeRows,
+    threshold: f64,
+    operation: &str,
+    policy: &str,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let operation: SetOperation = operation
+            .parse()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let policy: EdgeAggregation = policy
+            .parse()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        let build = |edges: EdgeRows| {
+            let similarity_edges: Vec<SimilarityEdge> = edges
+                .into_iter()
+                .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+                .collect();
+            CognateGraph::from_edges(similarity_edges, threshold)
+        };
+
+        let graph_a = build(edges_a);
+        let graph_b = build(edges_b);
+        Ok(graph_a.set_operation(&graph_b, operation, &policy).edge_list())
+    })
+}
+
+#[pyfunction]
+fn py_filter_by_provenance(
+    py: Python<'_>,
+    edges: Vec<ProvenanceEdge>,
+    threshold: f64,
+    sources: Vec<String>,
+) -> PyResult<Vec<ProvenanceEdge>> {
+    validate_provenance_weights(&edges)?;
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w, p)| SimilarityEdge::with_provenance(s, t, w, p))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.edges_by_provenance(&sources))
+    })
+}
+
+#[pyfunction]
+fn py_provenance_counts(
+    py: Python<'_>,
+    edges: Vec<ProvenanceEdge>,
+    threshold: f64,
+) -> PyResult<Vec<(Option<String>, usize)>> {
+    validate_provenance_weights(&edges)?;
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w, p)| SimilarityEdge::with_provenance(s, t, w, p))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.provenance_counts().into_iter().collect())
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, pairs=None, min_score=0.0))]
+fn py_neighbor_jaccard(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    pairs: Option<Vec<(String, String)>>,
+    min_score: f64,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(match pairs {
+            Some(pairs) => graph.jaccard_for_pairs(&pairs),
+            None => graph.jaccard_all_pairs(min_score),
+        })
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, method="adamic_adar", top_k=None))]
+fn py_predict_links(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    method: &str,
+    top_k: Option<usize>,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let predictor: LinkPredictor = method
+            .parse()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.predict_links(predictor, top_k))
+    })
+}
+
+/// (source, target, control points) of one bundled edge
+type BundledEdgeRow = (String, String, Vec<Vec<f64>>);
+
+/// Hierarchical edge bundling: control points per (source, target) edge,
+/// routed through the centroids of the endpoints' communities in
+/// `hierarchy` (levels of communities, finest first, e.g.
+/// `py_detect_communities(..., return_hierarchy=True)`) laid out at
+/// `coords` (numpy, one row per id). `beta` is the bundling strength
+/// (1 = fully bundled, 0 = straight)
+#[pyfunction]
+#[pyo3(signature = (ids, coords, hierarchy, edges, beta=0.85))]
+fn py_bundle_edges(
+    py: Python<'_>,
+    ids: Vec<String>,
+    coords: PyReadonlyArray2<f64>,
+    hierarchy: Vec<Vec<Vec<String>>>,
+    edges: Vec<(String, String)>,
+    beta: f64,
+) -> PyResult<Vec<BundledEdgeRow>> {
+    let coords = coords.as_array().to_owned();
+    let bundled = py
+        .allow_threads(|| bundle_edges(&ids, coords.view(), &hierarchy, &edges, beta))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(bundled
+        .into_iter()
+        .map(|edge| (edge.source, edge.target, edge.points))
+        .collect())
+}
+
+/// Convert an embedding into (ID order, n × d numpy array)
+fn embedding_to_py(py: Python<'_>, embedding: NodeEmbedding) -> (Vec<String>, &PyArray2<f64>) {
+    (embedding.ids, embedding.coords.into_pyarray(py))
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, dimensions=2, seed=0))]
+fn py_spectral_embedding<'py>(
+    py: Python<'py>,
+    edges: EdgeRows,
+    threshold: f64,
+    dimensions: usize,
+    seed: u64,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    validate_threshold(threshold)?;
+    let similarity_edges: Vec<SimilarityEdge> = edges
+        .into_iter()
+        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+        .collect();
+
+    let embedding = py.allow_threads(|| {
+        CognateGraph::from_edges(similarity_edges, threshold).spectral_embedding(dimensions, seed)
+    });
+    Ok(embedding_to_py(py, embedding))
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, dimensions=2, iterations=100, seed=0))]
+fn py_force_layout<'py>(
+    py: Python<'py>,
+    edges: EdgeRows,
+    threshold: f64,
+    dimensions: usize,
+    iterations: usize,
+    seed: u64,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    validate_threshold(threshold)?;
+    let similarity_edges: Vec<SimilarityEdge> = edges
+        .into_iter()
+        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+        .collect();
+
+    let embedding = py.allow_threads(|| {
+        CognateGraph::from_edges(similarity_edges, threshold).force_layout(
+            dimensions,
+            iterations,
+            seed,
+        )
+    });
+    Ok(embedding_to_py(py, embedding))
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    edges,
+    threshold,
+    dimensions=32,
+    walk_length=20,
+    walks_per_node=10,
+    p=1.0,
+    q=1.0,
+    window=5,
+    epochs=1,
+    seed=0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_node2vec<'py>(
+    py: Python<'py>,
+    edges: EdgeRows,
+    threshold: f64,
+    dimensions: usize,
+    walk_length: usize,
+    walks_per_node: usize,
+    p: f64,
+    q: f64,
+    window: usize,
+    epochs: usize,
+    seed: u64,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    validate_threshold(threshold)?;
+    let similarity_edges: Vec<SimilarityEdge> = edges
+        .into_iter()
+        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+        .collect();
+
+    let params = Node2VecParams {
+        dimensions,
+        walk_length,
+        walks_per_node,
+        p,
+        q,
+        window,
+        epochs,
+        seed,
+        ..Default::default()
+    };
+    params.validate()?;
+    let embedding = py.allow_threads(|| {
+        CognateGraph::from_edges(similarity_edges, threshold).node2vec(&params)
+    });
+    Ok(embedding_to_py(py, embedding))
+}
+
+/// Core number (largest k whose k-core contains the node) per node
+#[pyfunction]
+fn py_core_numbers(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<Vec<(String, usize)>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        Ok(CognateGraph::from_edges(similarity_edges, threshold).core_numbers())
+    })
+}
+
+/// Edges of the k-core: the subgraph where every node keeps at least `k`
+/// neighbors
+#[pyfunction]
+fn py_k_core(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    k: usize,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        Ok(CognateGraph::from_edges(similarity_edges, threshold)
+            .k_core(k)
+            .edge_list())
+    })
+}
+
+/// Bridge edges (whose removal disconnects the graph), weakest first
+#[pyfunction]
+fn py_find_bridges(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        Ok(CognateGraph::from_edges(similarity_edges, threshold).bridges())
+    })
+}
+
+/// Articulation points (nodes whose removal disconnects the graph); with
+/// `split_components`, (points, biconnected component edge lists)
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, split_components=false))]
+fn py_find_articulation_points(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    split_components: bool,
+) -> PyResult<PyObject> {
+    validate_threshold(threshold)?;
+    let (points, components) = py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        let components: Vec<Vec<WeightedEdge>> = if split_components {
+            graph
+                .biconnected_components()
+                .iter()
+                .map(CognateGraph::edge_list)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        (graph.articulation_points(), components)
+    });
+    Ok(if split_components {
+        (points, components).into_py(py)
+    } else {
+        points.into_py(py)
+    })
+}
+
+/// 2-D t-SNE coordinates for every word of a square sparse similarity
+/// matrix; repulsion is exact up to `exact_limit` words, Barnes–Hut above
+#[pyfunction]
+#[pyo3(signature = (
+    matrix,
+    perplexity=30.0,
+    iterations=1000,
+    learning_rate=None,
+    early_exaggeration=12.0,
+    theta=0.5,
+    exact_limit=1000,
+    seed=0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_tsne_embedding<'py>(
+    py: Python<'py>,
+    matrix: PyRef<PySparseMatrix>,
+    perplexity: f64,
+    iterations: usize,
+    learning_rate: Option<f64>,
+    early_exaggeration: f64,
+    theta: f64,
+    exact_limit: usize,
+    seed: u64,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    let params = TsneParams {
+        perplexity,
+        iterations,
+        learning_rate,
+        early_exaggeration,
+        theta,
+        exact_limit,
+        seed,
+    };
+    let matrix = &matrix.inner;
+    let embedding = py
+        .allow_threads(|| tsne_embedding(matrix, &params))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(embedding_to_py(py, embedding))
+}
+
+#[pyfunction]
+fn py_graph_stats(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<PyGraphStats> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(PyGraphStats::from(graph.stats()))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, max_sources=256, seed=0))]
+fn py_path_stats(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    max_sources: usize,
+    seed: u64,
+) -> PyResult<PyPathStats> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(PyPathStats::from(graph.path_stats(max_sources, seed)))
+    })
+}
+
+/// Conductance, normalized cut and expansion of each cluster (member ID
+/// lists, e.g. from any clustering function) against the full graph
+#[pyfunction]
+#[pyo3(signature = (edges, clusters, threshold=0.0))]
+fn py_cluster_quality(
+    py: Python<'_>,
+    edges: EdgeRows,
+    clusters: Vec<Vec<String>>,
+    threshold: f64,
+) -> PyResult<Vec<PyClusterQuality>> {
+    validate_threshold(threshold)?;
+    let quality = py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        graph.cluster_quality(&clusters)
+    })?;
+    Ok(quality.into_iter().map(PyClusterQuality::from).collect())
+}
+
+/// Cluster names, defaulting to cluster_0, cluster_1, ...
+fn cluster_names(clusters: &[Vec<String>], names: Option<Vec<String>>) -> Vec<String> {
+    names.unwrap_or_else(|| default_cluster_names(clusters.len()))
+}
+
+/// Edges (cluster_a, cluster_b, mass) of the cluster-level summary graph:
+/// the summed weight of member edges between each pair of clusters
+#[pyfunction]
+#[pyo3(signature = (edges, clusters, names=None, threshold=0.0))]
+fn py_cluster_graph(
+    py: Python<'_>,
+    edges: EdgeRows,
+    clusters: Vec<Vec<String>>,
+    names: Option<Vec<String>>,
+    threshold: f64,
+) -> PyResult<Vec<WeightedEdge>> {
+    validate_threshold(threshold)?;
+    let names = cluster_names(&clusters, names);
+    let summary = py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        graph.cluster_graph(&clusters, &names)
+    })?;
+    Ok(summary.edge_list())
+}
+
+/// Project a bipartite (source, target, weight) edge list, e.g. (cognate
+/// set, language) rows, onto its "source" or "target" side; weighting is
+/// "count" (shared neighbors), "product" (of edge weights), "jaccard" or
+/// "newman" (1 / (degree - 1) per shared neighbor)
+#[pyfunction]
+#[pyo3(signature = (edges, side="target", weighting="count"))]
+fn py_project_bipartite(
+    py: Python<'_>,
+    edges: EdgeRows,
+    side: &str,
+    weighting: &str,
+) -> PyResult<Vec<WeightedEdge>> {
+    let side: BipartiteSide = side.parse().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let weighting: ProjectionWeighting = weighting
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    py.allow_threads(|| {
+        let edges: Vec<WeightedEdge> = edges.into_iter().collect();
+        project_bipartite(&edges, side, weighting)
+    })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Member IDs of each group of summary-graph nodes (cluster names), e.g.
+/// communities found on the summary graph, mapped back through `clusters`
+#[pyfunction]
+#[pyo3(signature = (groups, clusters, names=None))]
+fn py_expand_clusters(
+    groups: Vec<Vec<String>>,
+    clusters: Vec<Vec<String>>,
+    names: Option<Vec<String>>,
+) -> PyResult<Vec<Vec<String>>> {
+    let names = cluster_names(&clusters, names);
+    Ok(expand_clusters(&groups, &clusters, &names)?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, damping=0.85, iterations=100, exact_limit=1000))]
+fn py_component_stats(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    damping: f64,
+    iterations: usize,
+    exact_limit: usize,
+) -> PyResult<Vec<PyComponentStats>> {
+    validate_threshold(threshold)?;
+    validate_damping(damping)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        let stats = graph.component_stats(damping, iterations, exact_limit);
+
+        Ok(stats.into_iter().map(PyComponentStats::from).collect())
+    })
+}
+
+#[pyfunction]
+fn py_graph_to_json(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<String> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+            .collect();
+
+        let graph = CognateGraph::from_edges(similarity_edges, threshold);
+        Ok(graph.to_json())
+    })
+}
+
+/// Build a graph for export, with optional per-node attributes
+fn export_graph(
+    edges: EdgeRows,
+    threshold: f64,
+    node_attributes: Option<NodeAttributes>,
+) -> CognateGraph {
+    let similarity_edges: Vec<SimilarityEdge> = edges
+        .into_iter()
+        .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+        .collect();
+
+    let mut graph = CognateGraph::from_edges(similarity_edges, threshold);
+    if let Some(attributes) = node_attributes {
+        graph.set_node_attributes(attributes);
+    }
+    graph
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, node_attributes=None, include_pagerank=false))]
+fn py_graph_to_graphml(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    node_attributes: Option<NodeAttributes>,
+    include_pagerank: bool,
+) -> PyResult<String> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let graph = export_graph(edges, threshold, node_attributes);
+        Ok(graph.to_graphml(include_pagerank))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, node_attributes=None, include_pagerank=false))]
+fn py_graph_to_gexf(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    node_attributes: Option<NodeAttributes>,
+    include_pagerank: bool,
+) -> PyResult<String> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let graph = export_graph(edges, threshold, node_attributes);
+        Ok(graph.to_gexf(include_pagerank))
+    })
+}
+
+/// Bytes buffered before a chunk is handed to a Python file object
+const PY_WRITE_CHUNK: usize = 1 << 16;
+
+/// Buffered `io::Write` over a Python file-like object, taking the GIL only
+/// to hand over full chunks: `str` cut at UTF-8 boundaries for text files,
+/// `bytes` otherwise
+struct PyFileWriter {
+    file: PyObject,
+    text: bool,
+    buffer: Vec<u8>,
+}
+
+impl PyFileWriter {
+    fn send(&mut self) -> std::io::Result<()> {
+        let end = if self.text {
+            std::str::from_utf8(&self.buffer).map_or_else(|e| e.valid_up_to(), str::len)
+        } else {
+            self.buffer.len()
+        };
+        if end == 0 {
+            return Ok(());
+        }
+        Python::with_gil(|py| {
+            let chunk = &self.buffer[..end];
+            let chunk: PyObject = if self.text {
+                String::from_utf8_lossy(chunk).into_py(py)
+            } else {
+                pyo3::types::PyBytes::new(py, chunk).into_py(py)
+            };
+            self.file.call_method1(py, "write", (chunk,)).map(|_| ())
+        })
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+        self.buffer.drain(..end);
+        Ok(())
+    }
+}
+
+impl std::io::Write for PyFileWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() >= PY_WRITE_CHUNK {
+            self.send()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send()
+    }
+}
+
+/// Stream an export to a path (str / os.PathLike) or a writable file-like
+/// object. `compression` ("gzip", "zstd" or "none") defaults to what the
+/// path's extension implies, and to none for file objects, which must be
+/// binary when compressed.
+fn write_export<F>(
+    py: Python<'_>,
+    file: &PyAny,
+    compression: Option<&str>,
+    write: F,
+) -> PyResult<()>
+where
+    F: FnOnce(&mut dyn std::io::Write) -> std::io::Result<()> + Send,
+{
+    let compression = compression
+        .map(str::parse::<Compression>)
+        .transpose()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let result = if let Ok(path) = file.extract::<std::path::PathBuf>() {
+        py.allow_threads(|| write_file(&path, compression, write))
+    } else {
+        let compression = compression.unwrap_or(Compression::None);
+        let text = file.is_instance(py.import("io")?.getattr("TextIOBase")?)?;
+        if text && compression != Compression::None {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "compressed output needs a file opened in binary mode",
+            ));
+        }
+        let writer = PyFileWriter {
+            file: file.into_py(py),
+            text,
+            buffer: Vec::with_capacity(PY_WRITE_CHUNK),
+        };
+        py.allow_threads(|| write_compressed(writer, compression, write))
+    };
+    result.map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+}
+
+/// Write the `py_graph_to_json` document to a path or file object without
+/// building it in memory
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, file, node_attributes=None, compression=None))]
+fn py_graph_write_json(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    file: &PyAny,
+    node_attributes: Option<NodeAttributes>,
+    compression: Option<&str>,
+) -> PyResult<()> {
+    validate_threshold(threshold)?;
+    let graph = py.allow_threads(|| export_graph(edges, threshold, node_attributes));
+    write_export(py, file, compression, |w| graph.write_json(w))
+}
+
+/// (source, target, weight, provenance) edges from an edge list written by
+/// `write_edge_list` (plain, gzip or zstd)
+#[pyfunction]
+fn py_read_edge_list(py: Python<'_>, path: std::path::PathBuf) -> PyResult<Vec<ProvenanceEdge>> {
+    py.allow_threads(|| {
+        let reader = open_file(&path).map_err(|e| e.to_string())?;
+        read_edge_list(reader)
+    })
+    .map_err(pyo3::exceptions::PyIOError::new_err)
+}
+
+/// Edge list read in Rust from a CSV / TSV file (plain, gzip or zstd) with a
+/// header row; the delimiter defaults to tab for .tsv files, else comma. The
+/// handle can be passed anywhere an edge list is expected.
+#[pyfunction]
+#[pyo3(signature = (
+    path, source_col="source", target_col="target", weight_col="weight", delimiter=None
+))]
+fn py_load_edges_csv(
+    py: Python<'_>,
+    path: std::path::PathBuf,
+    source_col: &str,
+    target_col: &str,
+    weight_col: &str,
+    delimiter: Option<char>,
+) -> PyResult<PyEdgeList> {
+    let columns = EdgeColumns {
+        source: source_col,
+        target: target_col,
+        weight: weight_col,
+    };
+    let inner = py
+        .allow_threads(|| read_edges_csv(&path, columns, delimiter))
+        .map_err(pyo3::exceptions::PyIOError::new_err)?;
+    validate_edge_rows(&inner)?;
+    Ok(PyEdgeList { inner })
+}
+
+/// Edge list read in Rust from the named columns of a Parquet file
+#[cfg(feature = "parquet")]
+#[pyfunction]
+#[pyo3(signature = (path, source_col="source", target_col="target", weight_col="weight"))]
+fn py_load_edges_parquet(
+    py: Python<'_>,
+    path: std::path::PathBuf,
+    source_col: &str,
+    target_col: &str,
+    weight_col: &str,
+) -> PyResult<PyEdgeList> {
+    let columns = EdgeColumns {
+        source: source_col,
+        target: target_col,
+        weight: weight_col,
+    };
+    let inner = py
+        .allow_threads(|| read_edges_parquet(&path, columns))
+        .map_err(pyo3::exceptions::PyIOError::new_err)?;
+    validate_edge_rows(&inner)?;
+    Ok(PyEdgeList { inner })
+}
+
+/// Edge list from the named columns of a pyarrow table or polars / pandas
+/// DataFrame (anything exporting `__arrow_c_stream__`)
+#[pyfunction]
+#[pyo3(signature = (table, source_col="source", target_col="target", weight_col="weight"))]
+fn py_load_edges_arrow(
+    table: &PyAny,
+    source_col: &str,
+    target_col: &str,
+    weight_col: &str,
+) -> PyResult<PyEdgeList> {
+    let columns = EdgeColumns {
+        source: source_col,
+        target: target_col,
+        weight: weight_col,
+    };
+    let inner = arrow_edge_rows(table, columns)?.ok_or_else(|| {
+        pyo3::exceptions::PyTypeError::new_err("expected an object with __arrow_c_stream__")
+    })?;
+    validate_edge_rows(&inner)?;
+    Ok(PyEdgeList { inner })
+}
+
+/// Text of a plain, gzip or zstd file, e.g. a compressed GraphML export for
+/// `networkx.parse_graphml`
+#[pyfunction]
+fn py_read_text(py: Python<'_>, path: std::path::PathBuf) -> PyResult<String> {
+    py.allow_threads(|| read_to_string(&path))
+        .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+}
+
+/// Python dicts/lists for a JSON value (via the json module, so the
+/// structure is exactly what json.loads would give)
+fn json_to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    let loads = py.import("json")?.getattr("loads")?;
+    Ok(loads.call1((value.to_string(),))?.into_py(py))
+}
+
+/// Dict for `networkx.node_link_graph`; pass `link_key="edges"` for the
+/// NetworkX >= 3.4 `edges=` convention
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, node_attributes=None, link_key="links"))]
+fn py_graph_to_node_link_data(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    node_attributes: Option<NodeAttributes>,
+    link_key: &str,
+) -> PyResult<PyObject> {
+    validate_threshold(threshold)?;
+    let data = py.allow_threads(|| {
+        export_graph(edges, threshold, node_attributes).to_node_link_data(link_key)
+    });
+    json_to_py(py, &data)
+}
+
+/// Dict for `networkx.adjacency_graph`
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, node_attributes=None))]
+fn py_graph_to_adjacency_data(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    node_attributes: Option<NodeAttributes>,
+) -> PyResult<PyObject> {
+    validate_threshold(threshold)?;
+    let data = py
+        .allow_threads(|| export_graph(edges, threshold, node_attributes).to_adjacency_data());
+    json_to_py(py, &data)
+}
+
+/// Language–language links as a GeoJSON FeatureCollection of weighted
+/// LineStrings; `coordinates` maps languages to (latitude, longitude)
+#[pyfunction]
+#[pyo3(signature = (links, coordinates, min_weight=0.0, include_points=false))]
+fn py_links_to_geojson(
+    py: Python<'_>,
+    links: Vec<(String, String, f64)>,
+    coordinates: HashMap<String, (f64, f64)>,
+    min_weight: f64,
+    include_points: bool,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        Ok(links_to_geojson(&links, &coordinates, min_weight, include_points))
+    })
+}
+
+/// Candidate isogloss boundaries: geographic neighbor pairs (Gabriel
+/// graph) whose (language, language, distance) stands `min_z` standard
+/// deviations above the other neighbor pairs, strongest first
+#[pyfunction]
+#[pyo3(signature = (coordinates, distances, min_z=1.0))]
+fn py_isogloss_boundaries(
+    py: Python<'_>,
+    coordinates: HashMap<String, (f64, f64)>,
+    distances: Vec<(String, String, f64)>,
+    min_z: f64,
+) -> Vec<PyIsoglossSegment> {
+    py.allow_threads(|| {
+        isogloss_boundaries(&coordinates, &distances, min_z)
+            .into_iter()
+            .map(PyIsoglossSegment::from)
+            .collect()
+    })
+}
+
+/// In/out degree and strength per language of a (donor, recipient, weight)
+/// borrowing network
+#[pyfunction]
+fn py_directed_degrees(
+    py: Python<'_>,
+    edges: EdgeRows,
+) -> PyResult<Vec<PyNodeDegree>> {
+    py.allow_threads(|| {
+        let graph = BorrowingGraph::from_edges(edges.0);
+        Ok(graph.degrees().into_iter().map(PyNodeDegree::from).collect())
+    })
+}
+
+/// Weighted PageRank along donor → recipient arcs
+#[pyfunction]
+#[pyo3(signature = (edges, damping=0.85, iterations=100))]
+fn py_directed_pagerank(
+    py: Python<'_>,
+    edges: EdgeRows,
+    damping: f64,
+    iterations: usize,
+) -> PyResult<Vec<(String, f64)>> {
+    validate_damping(damping)?;
+    py.allow_threads(|| Ok(BorrowingGraph::from_edges(edges.0).pagerank(damping, iterations)))
+}
+
+#[pyfunction]
+fn py_strongly_connected_components(
+    py: Python<'_>,
+    edges: EdgeRows,
+) -> PyResult<Vec<Vec<String>>> {
+    py.allow_threads(|| Ok(BorrowingGraph::from_edges(edges.0).strongly_connected_components()))
+}
+
+#[pyfunction]
+fn py_directed_graph_stats(
+    py: Python<'_>,
+    edges: EdgeRows,
+) -> PyResult<PyDirectedGraphStats> {
+    py.allow_threads(|| {
+        let graph = BorrowingGraph::from_edges(edges.0);
+        Ok(PyDirectedGraphStats::from(graph.stats()))
+    })
+}
+
+// ============================================================================
+// CLUSTERING FUNCTIONS
+// ============================================================================
+
+#[pyfunction]
+fn py_threshold_clustering(
+    py: Python<'_>,
+    similarities: EdgeRows,
+    threshold: f64,
+) -> PyResult<Vec<Vec<String>>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| Ok(threshold_clustering_with_ids(similarities.0, threshold)))
+}
+
+#[pyfunction]
+fn py_silhouette_score(
+    py: Python<'_>,
+    similarities: Vec<(usize, usize, f64)>,
+    clusters: Vec<Vec<usize>>,
+) -> PyResult<f64> {
+    py.allow_threads(|| Ok(silhouette_score(&similarities, &clusters)))
+}
+
+#[pyfunction]
+fn py_within_cluster_variance(
+    py: Python<'_>,
+    similarities: Vec<(usize, usize, f64)>,
+    clusters: Vec<Vec<usize>>,
+) -> PyResult<f64> {
+    py.allow_threads(|| Ok(within_cluster_variance(&similarities, &clusters)))
+}
+
+/// Davies-Bouldin index over indexed similarities (distance 1 - similarity,
+/// mean pairwise scatter and separation); lower is better
+#[pyfunction]
+fn py_davies_bouldin_index(
+    py: Python<'_>,
+    similarities: Vec<(usize, usize, f64)>,
+    clusters: Vec<Vec<usize>>,
+) -> PyResult<f64> {
+    py.allow_threads(|| Ok(davies_bouldin_index(&similarities, &clusters)))
+}
+
+/// Mean within-cluster over mean between-cluster distance; lower is better
+#[pyfunction]
+fn py_within_between_ratio(
+    py: Python<'_>,
+    similarities: Vec<(usize, usize, f64)>,
+    clusters: Vec<Vec<usize>>,
+) -> PyResult<f64> {
+    py.allow_threads(|| Ok(within_between_ratio(&similarities, &clusters)))
+}
+
+/// Silhouette, within-cluster variance, Davies-Bouldin, within/between ratio
+/// and cluster counts for each candidate clustering over the same indexed
+/// similarities, in one parallel pass
+#[pyfunction]
+fn py_evaluate_clusterings(
+    py: Python<'_>,
+    similarities: Vec<(usize, usize, f64)>,
+    clusterings: Vec<Vec<Vec<usize>>>,
+) -> PyResult<Vec<PyClusteringScores>> {
+    py.allow_threads(|| {
+        Ok(evaluate_clusterings(&similarities, &clusterings)
+            .into_iter()
+            .map(PyClusteringScores::from)
+            .collect())
+    })
+}
+
+/// Hierarchical agglomerative clustering over (id, id, similarity) edges;
+/// linkage is "single", "complete" or "average" (UPGMA)
+#[pyfunction]
+#[pyo3(signature = (similarities, linkage="average"))]
+fn py_agglomerative_clustering(
+    py: Python<'_>,
+    similarities: EdgeRows,
+    linkage: &str,
+) -> PyResult<PyDendrogram> {
+    let linkage: Linkage = linkage.parse().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    py.allow_threads(|| {
+        Ok(PyDendrogram {
+            inner: agglomerative_clustering(similarities.0, linkage),
+        })
+    })
+}
+
+/// One robust partition from several edge lists over the same items (one
+/// per similarity metric): each is clustered at `threshold`, and items stay
+/// together when at least `agreement` of those clusterings agree
+#[pyfunction]
+#[pyo3(signature = (edge_lists, threshold, agreement=0.5))]
+fn py_consensus_clustering(
+    py: Python<'_>,
+    edge_lists: Vec<EdgeRows>,
+    threshold: f64,
+    agreement: f64,
+) -> PyResult<Vec<Vec<String>>> {
+    validate_threshold(threshold)?;
+    if edge_lists.is_empty() {
+        return Err(LangVizError::EmptyInput("edge_lists".to_string()).into());
+    }
+    let edge_lists: Vec<Vec<(String, String, f64)>> =
+        edge_lists.into_iter().map(|edges| edges.0).collect();
+    py.allow_threads(|| Ok(consensus_from_edges(edge_lists, threshold, agreement)))
+}
+
+/// Consensus of ready-made clusterings (lists of member lists)
+#[pyfunction]
+#[pyo3(signature = (clusterings, agreement=0.5))]
+fn py_consensus_from_clusterings(
+    py: Python<'_>,
+    clusterings: Vec<Vec<Vec<String>>>,
+    agreement: f64,
+) -> PyResult<Vec<Vec<String>>> {
+    if clusterings.is_empty() {
+        return Err(LangVizError::EmptyInput("clusterings".to_string()).into());
+    }
+    py.allow_threads(|| Ok(consensus_clustering(&clusterings, agreement)))
+}
+
+/// Reconcile two collections of cognate sets (member lists) by "union",
+/// "intersection" or "jaccard" overlap (sets merge at `min_jaccard`,
+/// default 0.5), with a confidence per member
+#[pyfunction]
+#[pyo3(signature = (sets_a, sets_b, strategy="union", min_jaccard=None))]
+fn py_merge_cognate_sets(
+    py: Python<'_>,
+    sets_a: Vec<Vec<String>>,
+    sets_b: Vec<Vec<String>>,
+    strategy: &str,
+    min_jaccard: Option<f64>,
+) -> PyResult<Vec<PyMergedCognateSet>> {
+    let mut strategy: MergeStrategy = strategy
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    if let Some(value) = min_jaccard {
+        strategy = strategy
+            .with_min_overlap(value)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    }
+    py.allow_threads(|| {
+        Ok(merge_cognate_sets(&sets_a, &sets_b, strategy)
+            .into_iter()
+            .map(PyMergedCognateSet::from)
+            .collect())
+    })
+}
+
+/// Spectral clustering of a sparse similarity matrix into at most `k`
+/// clusters (largest first), with no similarity threshold to tune
+#[pyfunction]
+#[pyo3(signature = (matrix, k, seed=0))]
+fn py_spectral_clustering(
+    py: Python<'_>,
+    matrix: PyRef<PySparseMatrix>,
+    k: usize,
+    seed: u64,
+) -> PyResult<Vec<Vec<String>>> {
+    let matrix = &matrix.inner;
+    py.allow_threads(|| spectral_clustering(matrix, k, seed))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Divisive clustering of a sparse similarity matrix: connected components
+/// recursively bisected at Fiedler vector sweep cuts while a split's
+/// conductance is at most `max_conductance` and it raises modularity by
+/// more than `min_modularity_gain`; clusters come largest first
+#[pyfunction]
+#[pyo3(signature = (matrix, max_conductance=0.5, min_modularity_gain=0.0, min_size=1, seed=0))]
+fn py_divisive_clustering(
+    py: Python<'_>,
+    matrix: PyRef<PySparseMatrix>,
+    max_conductance: f64,
+    min_modularity_gain: f64,
+    min_size: usize,
+    seed: u64,
+) -> PyResult<Vec<Vec<String>>> {
+    let invalid = |name: &str, value: f64, expected: &str| LangVizError::InvalidParameter {
+        name: name.to_string(),
+        value,
+        expected: expected.to_string(),
+    };
+    if !(max_conductance.is_finite() && max_conductance >= 0.0) {
+        return Err(invalid("max_conductance", max_conductance, "a finite value >= 0").into());
+    }
+    if !min_modularity_gain.is_finite() {
+        return Err(invalid("min_modularity_gain", min_modularity_gain, "a finite value").into());
+    }
+    if min_size == 0 {
+        return Err(invalid("min_size", 0.0, "min_size >= 1").into());
+    }
+    let params = DivisiveParams {
+        max_conductance,
+        min_modularity_gain,
+        min_size,
+    };
+    let matrix = &matrix.inner;
+    py.allow_threads(|| divisive_clustering(matrix, &params, seed))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Map clusters (member lists) onto an existing member -> cognate set ID
+/// table, keeping IDs stable across re-runs and reporting splits/merges
+#[pyfunction]
+#[pyo3(signature = (clusters, existing, new_id_prefix=""))]
+fn py_assign_cognate_set_ids(
+    py: Python<'_>,
+    clusters: Vec<Vec<String>>,
+    existing: HashMap<String, String>,
+    new_id_prefix: &str,
+) -> PyResult<PyIdAssignment> {
+    py.allow_threads(|| {
+        let assignment = assign_cognate_set_ids(&clusters, &existing, new_id_prefix);
+        Ok(PyIdAssignment::from(assignment))
+    })
+}
+
+// ============================================================================
+// SPARSE MATRIX FUNCTIONS
+// ============================================================================
+
+#[pyfunction]
+fn py_sparse_matrix_from_edges(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<PySparseMatrix> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| {
+        let matrix = SparseSimilarityMatrix::from_edges(edges.0, threshold);
+        Ok(PySparseMatrix { inner: matrix })
+    })
+}
+
+/// Sparsify a dense n × n similarity matrix (numpy, read in place)
+#[pyfunction]
+fn py_sparse_matrix_from_dense(
+    ids: Vec<String>,
+    matrix: PyReadonlyArray2<f64>,
+    threshold: f64,
+) -> PyResult<PySparseMatrix> {
+    validate_threshold(threshold)?;
+    validate_square("matrix", matrix.shape(), Some(ids.len()))?;
+    // The GIL stays held: the view borrows memory Python threads could mutate
+    let inner = SparseSimilarityMatrix::from_dense(ids, matrix.as_array(), threshold)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(PySparseMatrix { inner })
+}
+
+#[pyfunction]
+fn py_threshold_filter(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+) -> PyResult<Vec<(String, String, f64)>> {
+    validate_threshold(threshold)?;
+    py.allow_threads(|| Ok(threshold_filter(edges.0, threshold)))
+}
+
+/// Soft thresholding: weights rescaled by a sigmoid centred on `threshold`
+/// (0.5 at the cutoff; larger `steepness` approaches a hard filter), dropping
+/// edges whose rescaled weight falls below `min_weight`
+#[pyfunction]
+#[pyo3(signature = (edges, threshold, steepness=10.0, min_weight=0.0))]
+fn py_soft_threshold(
+    py: Python<'_>,
+    edges: EdgeRows,
+    threshold: f64,
+    steepness: f64,
+    min_weight: f64,
+) -> PyResult<Vec<(String, String, f64)>> {
+    validate_threshold(threshold)?;
+    if !(steepness.is_finite() && steepness > 0.0) {
+        return Err(LangVizError::InvalidParameter {
+            name: "steepness".to_string(),
+            value: steepness,
+            expected: "a finite value > 0".to_string(),
+        }
+        .into());
+    }
+    py.allow_threads(|| Ok(sigmoid_weights(edges.0, threshold, steepness, min_weight)))
+}
+
+// ============================================================================
+// LEXICOSTATISTICS FUNCTIONS
+// ============================================================================
+
+/// Pairwise language distances from (language, concept, cognate_class)
+/// judgments; `weights` maps concepts to stability weights. `estimator`
+/// divides shared concepts by those both languages attest ("overlap"), by
+/// the whole list ("list_length") or shrinks the overlap estimate towards
+/// the pooled average by `pseudocount` concepts ("shrunk")
+#[pyfunction]
+#[pyo3(signature = (
+    judgments,
+    weights=None,
+    default_weight=1.0,
+    estimator="overlap",
+    pseudocount=5.0
+))]
+fn py_lexicostatistical_distances<'py>(
+    py: Python<'py>,
+    judgments: Vec<(String, String, String)>,
+    weights: Option<HashMap<String, f64>>,
+    default_weight: f64,
+    estimator: &str,
+    pseudocount: f64,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    let mut estimator: DistanceEstimator = estimator
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    if let DistanceEstimator::Shrunk { pseudocount: p } = &mut estimator {
+        if !(pseudocount.is_finite() && pseudocount >= 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "pseudocount must be finite and >= 0, got {}",
+                pseudocount
+            )));
+        }
+        *p = pseudocount;
+    }
+    let weights = ConceptWeights::new(weights.unwrap_or_default(), default_weight);
+    let (languages, distances) = py.allow_threads(|| {
+        let table = CognateTable::from_judgments(&judgments);
+        (
+            table.languages().to_vec(),
+            table.estimated_distance_matrix(&weights, estimator),
+        )
+    });
+    Ok((languages, distances.into_pyarray(py)))
+}
+
+/// Languages and, per pair, the number of concepts both attest (the
+/// diagonal holds each language's coverage)
+#[pyfunction]
+fn py_concept_overlap<'py>(
+    py: Python<'py>,
+    judgments: Vec<(String, String, String)>,
+) -> PyResult<(Vec<String>, &'py PyArray2<u64>)> {
+    let (languages, overlaps) = py.allow_threads(|| {
+        let table = CognateTable::from_judgments(&judgments);
+        (table.languages().to_vec(), table.overlap_counts())
+    });
+    Ok((languages, overlaps.into_pyarray(py)))
+}
+
+/// Languages and {field: distance matrix}
+type FieldDistances<'py> = (Vec<String>, BTreeMap<String, &'py PyArray2<f64>>);
+
+/// `py_lexicostatistical_distances` per semantic field, over the concepts
+/// `fields` ({concept: [field, ...]}) tags with that field
+#[pyfunction]
+#[pyo3(signature = (judgments, fields, weights=None, default_weight=1.0))]
+fn py_field_lexicostatistical_distances<'py>(
+    py: Python<'py>,
+    judgments: Vec<(String, String, String)>,
+    fields: HashMap<String, Vec<String>>,
+    weights: Option<HashMap<String, f64>>,
+    default_weight: f64,
+) -> PyResult<FieldDistances<'py>> {
+    let weights = ConceptWeights::new(weights.unwrap_or_default(), default_weight);
+    let (languages, matrices) = py.allow_threads(|| {
+        let table = CognateTable::from_judgments(&judgments);
+        let fields = SemanticFields::new(&fields);
+        (
+            table.languages().to_vec(),
+            table.field_distance_matrices(&weights, &fields),
+        )
+    });
+    let matrices = matrices
+        .into_iter()
+        .map(|(field, matrix)| (field, matrix.into_pyarray(py)))
+        .collect();
+    Ok((languages, matrices))
+}
+
+/// Cognate presence/absence matrix of (language, concept, cognate_class)
+/// judgments, one character per class, for BEAST, MrBayes or RAxML
+#[pyclass(frozen)]
+struct PyCharacterMatrix {
+    inner: CharacterMatrix,
+}
+
+#[pymethods]
+impl PyCharacterMatrix {
+    #[new]
+    fn new(py: Python<'_>, judgments: Vec<(String, String, String)>) -> Self {
+        let inner = py.allow_threads(|| {
+            CharacterMatrix::from_table(&CognateTable::from_judgments(&judgments))
+        });
+        Self { inner }
+    }
+
+    #[getter]
+    fn languages(&self) -> Vec<String> {
+        self.inner.languages().to_vec()
+    }
+
+    /// (concept, class) per character
+    #[getter]
+    fn characters(&self) -> Vec<(String, String)> {
+        self.inner
+            .characters()
+            .iter()
+            .map(|c| (c.concept.clone(), c.class.clone()))
+            .collect()
+    }
+
+    /// (concept, first, last) character positions, 1-based inclusive
+    #[getter]
+    fn charsets(&self) -> Vec<(String, usize, usize)> {
+        self.inner.charsets().to_vec()
+    }
+
+    /// NEXUS with one charset per concept
+    fn to_nexus(&self) -> String {
+        self.inner.to_nexus()
+    }
+
+    /// Relaxed PHYLIP (charsets come from `to_partitions`)
+    fn to_phylip(&self) -> String {
+        self.inner.to_phylip()
+    }
+
+    /// RAxML-style partition file, one binary partition per concept
+    fn to_partitions(&self) -> String {
+        self.inner.to_partitions()
+    }
+
+    /// Copy with missing concepts filled in by `method`: "nearest" (the
+    /// closest attesting language's classes) or "tree" (the commonest class
+    /// of the closest attesting clade of `tree`)
+    #[pyo3(signature = (method="nearest", tree=None))]
+    fn imputed(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        tree: Option<PyRef<'_, PyTree>>,
+    ) -> PyResult<Self> {
+        let method: ImputationMethod = method
+            .parse()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let mut inner = self.inner.clone();
+        match (method, tree) {
+            (ImputationMethod::Nearest, _) => py.allow_threads(|| inner.impute_nearest()),
+            (ImputationMethod::Tree, Some(tree)) => {
+                let tree = &tree.inner;
+                py.allow_threads(|| inner.impute_on_tree(tree))
+                    .map_err(pyo3::exceptions::PyValueError::new_err)?
+            }
+            (ImputationMethod::Tree, None) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "tree imputation needs a tree",
+                ))
+            }
+        }
+        Ok(Self { inner })
+    }
+
+    /// (language, concept, method, source languages) per imputed cell
+    #[getter]
+    fn imputations(&self) -> Vec<(String, String, String, Vec<String>)> {
+        let languages = self.inner.languages();
+        self.inner
+            .imputations()
+            .iter()
+            .map(|imputation| {
+                (
+                    languages[imputation.language].clone(),
+                    self.inner.charsets()[imputation.concept].0.clone(),
+                    imputation.method.name().to_string(),
+                    imputation
+                        .sources
+                        .iter()
+                        .map(|&s| languages[s].clone())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// TSV of imputed cells, to accompany `to_phylip`
+    fn to_imputations(&self) -> String {
+        self.inner.to_imputations()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CharacterMatrix(languages={}, characters={})",
+            self.inner.languages().len(),
+            self.inner.characters().len()
+        )
+    }
+}
+
+/// (n × dimensions) coordinates, eigenvalue and explained share per axis
+type MdsResult<'py> = (&'py PyArray2<f64>, Vec<f64>, Vec<f64>);
+
+/// Classical MDS (PCoA) of an n × n distance matrix (numpy): returns the
+/// (n × dimensions) coordinates, the eigenvalue of each axis and the share
+/// of structure each axis explains
+#[pyfunction]
+#[pyo3(signature = (distances, dimensions=2, seed=0))]
+fn py_classical_mds<'py>(
+    py: Python<'py>,
+    distances: PyReadonlyArray2<f64>,
+    dimensions: usize,
+    seed: u64,
+) -> PyResult<MdsResult<'py>> {
+    validate_square("distances", distances.shape(), None)?;
+    let distances = distances.as_array().to_owned();
+    let mds = py
+        .allow_threads(|| classical_mds(distances.view(), dimensions, seed))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((mds.coords.into_pyarray(py), mds.eigenvalues, mds.explained))
+}
+
+/// Order varieties along a dialect continuum by spectral seriation of their
+/// n × n distance matrix (numpy), e.g. from `py_lexicostatistical_distances`
+#[pyfunction]
+fn py_spectral_seriation(
+    py: Python<'_>,
+    ids: Vec<String>,
+    distances: PyReadonlyArray2<f64>,
+) -> PyResult<PySeriation> {
+    validate_square("distances", distances.shape(), Some(ids.len()))?;
+    let distances = distances.as_array().to_owned();
+    py.allow_threads(|| {
+        spectral_seriation(&ids, distances.view())
+            .map(PySeriation::from)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    })
+}
+
+/// n × n distances for `ids` from a numpy distance matrix, or from a sparse
+/// similarity matrix as 1 - similarity (absent pairs at distance 1)
+fn tree_distances(ids: &[String], distances: &PyAny) -> PyResult<ndarray::Array2<f64>> {
+    if let Ok(matrix) = distances.extract::<PyRef<PySparseMatrix>>() {
+        matrix.check_entries(ids.iter().map(String::as_str))?;
+        let mut distances = matrix.inner.to_dense_submatrix(ids).mapv(|s| 1.0 - s);
+        distances.diag_mut().fill(0.0);
+        return Ok(distances);
+    }
+    let distances: PyReadonlyArray2<f64> = distances.extract()?;
+    validate_square("distances", distances.shape(), Some(ids.len()))?;
+    Ok(distances.as_array().to_owned())
+}
+
+fn parse_distance_format(format: Option<&str>) -> PyResult<Option<DistanceFormat>> {
+    format
+        .map(str::parse)
+        .transpose()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// (labels, distance matrix) from a PHYLIP (square or lower-triangular) or
+/// CSV / TSV file, plain, gzip or zstd; `format` ("phylip", "csv", "tsv")
+/// defaults to what the file name implies
+#[pyfunction]
+#[pyo3(signature = (path, format=None))]
+fn py_read_distance_matrix<'py>(
+    py: Python<'py>,
+    path: std::path::PathBuf,
+    format: Option<&str>,
+) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+    let format = parse_distance_format(format)?;
+    let (labels, distances) = py
+        .allow_threads(|| read_distance_matrix(&path, format))
+        .map_err(pyo3::exceptions::PyIOError::new_err)?;
+    Ok((labels, distances.into_pyarray(py)))
+}
+
+/// Write a labeled square distance matrix (numpy) to a path or file object
+/// as PHYLIP, CSV or TSV; `format` defaults to what the path implies and to
+/// PHYLIP for file objects
+#[pyfunction]
+#[pyo3(signature = (labels, distances, file, format=None, compression=None))]
+fn py_write_distance_matrix(
+    py: Python<'_>,
+    labels: Vec<String>,
+    distances: PyReadonlyArray2<f64>,
+    file: &PyAny,
+    format: Option<&str>,
+    compression: Option<&str>,
+) -> PyResult<()> {
+    let format = match parse_distance_format(format)? {
+        Some(format) => format,
+        None => match file.extract::<std::path::PathBuf>() {
+            Ok(path) => DistanceFormat::from_path(&path),
+            Err(_) => DistanceFormat::Phylip,
+        },
+    };
+    let distances = distances.as_array();
+    if distances.dim() != (labels.len(), labels.len()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "distance matrix is {:?} but there are {} labels",
+            distances.dim(),
+            labels.len()
+        )));
+    }
+    let distances = distances.to_owned();
+    write_export(py, file, compression, |w| {
+        write_distance_matrix(w, &labels, distances.view(), format)
+    })
+}
+
+/// UPGMA tree over `ids` from a numpy distance matrix or a sparse
+/// similarity matrix (distance 1 - similarity); every clade of an optional
+/// `constraint` tree over some of the ids (known subgroups) is kept whole
+#[pyfunction]
+#[pyo3(signature = (ids, distances, constraint=None))]
+fn py_build_upgma_tree(
+    py: Python<'_>,
+    ids: Vec<String>,
+    distances: &PyAny,
+    constraint: Option<PyRef<'_, PyTree>>,
+) -> PyResult<PyTree> {
+    let distances = tree_distances(&ids, distances)?;
+    let constraint = constraint.as_ref().map(|tree| &tree.inner);
+    py.allow_threads(|| Tree::upgma(&ids, distances.view(), constraint))
+        .map(|inner| PyTree { inner })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Neighbor-Joining tree over `ids`, inputs as for `py_build_upgma_tree`
+#[pyfunction]
+#[pyo3(signature = (ids, distances, constraint=None))]
+fn py_build_nj_tree(
+    py: Python<'_>,
+    ids: Vec<String>,
+    distances: &PyAny,
+    constraint: Option<PyRef<'_, PyTree>>,
+) -> PyResult<PyTree> {
+    let distances = tree_distances(&ids, distances)?;
+    let constraint = constraint.as_ref().map(|tree| &tree.inner);
+    py.allow_threads(|| Tree::neighbor_joining(&ids, distances.view(), constraint))
+        .map(|inner| PyTree { inner })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Jackknife support for the lexicostatistical tree of (language, concept,
+/// cognate_class) judgments: each of `replicates` drops `drop_fraction` of
+/// the `unit` ("languages" or "concepts"), rebuilds the tree by `method`
+/// ("upgma" or "nj") and checks which clades of the full tree it recovers.
+/// Replicate `i` is drawn with seed `seed + i`
+#[pyfunction]
+#[pyo3(signature = (
+    judgments,
+    unit="languages",
+    method="upgma",
+    drop_fraction=0.1,
+    replicates=100,
+    seed=0,
+    weights=None,
+    default_weight=1.0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_jackknife_tree(
+    py: Python<'_>,
+    judgments: Vec<(String, String, String)>,
+    unit: &str,
+    method: &str,
+    drop_fraction: f64,
+    replicates: usize,
+    seed: u64,
+    weights: Option<HashMap<String, f64>>,
+    default_weight: f64,
+) -> PyResult<PyJackknife> {
+    let params = JackknifeParams {
+        unit: unit.parse().map_err(pyo3::exceptions::PyValueError::new_err)?,
+        method: method.parse().map_err(pyo3::exceptions::PyValueError::new_err)?,
+        drop_fraction,
+        replicates,
+        seed,
+    };
+    let weights = ConceptWeights::new(weights.unwrap_or_default(), default_weight);
+    py.allow_threads(|| jackknife(&judgments, &weights, &params))
+        .map(|inner| PyJackknife { inner })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Weight table from stability ranks (1 = most stable), e.g. a Swadesh or
+/// Leipzig–Jakarta ranking
+#[pyfunction]
+fn py_concept_weights_from_ranks(
+    ranks: HashMap<String, usize>,
+) -> PyResult<BTreeMap<String, f64>> {
+    let weights = ConceptWeights::from_ranks(&ranks, 1.0);
+    Ok(ranks
+        .into_keys()
+        .map(|concept| {
+            let weight = weights.weight(&concept);
+            (concept, weight)
+        })
+        .collect())
+}
+
+fn parse_concept_list(name: &str) -> PyResult<ConceptList> {
+    name.parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Canonical glosses of a bundled list ("swadesh100", "swadesh207",
+/// "leipzig_jakarta")
+#[pyfunction]
+fn py_concept_list(name: &str) -> PyResult<Vec<String>> {
+    Ok(parse_concept_list(name)?
+        .concepts()
+        .into_iter()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Keep (language, concept, form) entries whose concept is on the list
+#[pyfunction]
+#[pyo3(signature = (entries, concept_list="swadesh100"))]
+fn py_filter_wordlist(
+    py: Python<'_>,
+    entries: WordlistRows,
+    concept_list: &str,
+) -> PyResult<Vec<(String, String, String)>> {
+    py.allow_threads(|| Ok(filter_wordlist(&entries, parse_concept_list(concept_list)?)))
+}
+
+/// Keep (language, concept, form) entries whose concept `fields`
+/// ({concept: [field, ...]}) tags with one of `keep`
+#[pyfunction]
+fn py_filter_wordlist_by_field(
+    py: Python<'_>,
+    entries: WordlistRows,
+    fields: HashMap<String, Vec<String>>,
+    keep: Vec<String>,
+) -> PyResult<Vec<(String, String, String)>> {
+    py.allow_threads(|| {
+        let fields = SemanticFields::new(&fields);
+        Ok(filter_by_field(&entries, &fields, &keep))
+    })
+}
+
+/// Borrowing rate per semantic field from (language, concept, borrowed)
+/// entries, highest first; untagged concepts are skipped
+#[pyfunction]
+fn py_borrowing_by_field(
+    py: Python<'_>,
+    entries: Vec<(String, String, bool)>,
+    fields: HashMap<String, Vec<String>>,
+) -> PyResult<Vec<PyFieldBorrowing>> {
+    py.allow_threads(|| {
+        let report = borrowing_by_field(&entries, &SemanticFields::new(&fields));
+        Ok(report.into_iter().map(PyFieldBorrowing::from).collect())
+    })
+}
+
+/// Borrowing rates per concept and per semantic field (`fields` maps
+/// concepts to field lists) of (language, concept, borrowed) entries, with
+/// Wilson intervals at `confidence`
+#[pyfunction]
+#[pyo3(signature = (entries, fields=None, confidence=0.95))]
+fn py_borrowability(
+    py: Python<'_>,
+    entries: Vec<(String, String, bool)>,
+    fields: Option<HashMap<String, Vec<String>>>,
+    confidence: f64,
+) -> PyResult<PyBorrowability> {
+    py.allow_threads(|| {
+        let fields = SemanticFields::new(&fields.unwrap_or_default());
+        borrowability(&entries, &fields, confidence)
+            .map(|inner| PyBorrowability { inner })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    })
+}
+
+/// Per-language coverage of a concept list with the missing concepts
+#[pyfunction]
+#[pyo3(signature = (entries, concept_list="swadesh100"))]
+fn py_concept_coverage(
+    py: Python<'_>,
+    entries: WordlistRows,
+    concept_list: &str,
+) -> PyResult<Vec<PyConceptCoverage>> {
+    py.allow_threads(|| {
+        let coverage = concept_coverage(&entries, parse_concept_list(concept_list)?);
+        Ok(coverage.into_iter().map(PyConceptCoverage::from).collect())
+    })
+}
+
+/// Per-concept cognate density for (id, concept, cognate_set) entries and
+/// (id, id, similarity) scores, most fragmented concepts first
+#[pyfunction]
+fn py_concept_cognate_density(
+    py: Python<'_>,
+    entries: Vec<(String, String, String)>,
+    similarities: EdgeRows,
+) -> PyResult<Vec<PyConceptDensity>> {
+    py.allow_threads(|| {
+        let report = concept_cognate_density(&entries, &similarities.0);
+        Ok(report.into_iter().map(PyConceptDensity::from).collect())
+    })
+}
+
+fn parse_split_by(by: &str) -> PyResult<SplitBy> {
+    by.parse().map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Train/test split of (id, language, concept, form) entries keeping each
+/// concept (`by="concept"`) or language family (`by="family"`, with a
+/// {language: family} dict) whole; the test side is fold 1
+#[pyfunction]
+#[pyo3(signature = (entries, by="concept", test_fraction=0.2, families=None, seed=0))]
+fn py_train_test_split(
+    py: Python<'_>,
+    entries: EntryRows,
+    by: &str,
+    test_fraction: f64,
+    families: Option<HashMap<String, String>>,
+    seed: u64,
+) -> PyResult<PyWordlistSplit> {
+    let by = parse_split_by(by)?;
+    py.allow_threads(|| {
+        let split =
+            WordlistSplit::train_test(&entries, by, families.as_ref(), test_fraction, seed)?;
+        Ok(PyWordlistSplit { inner: split })
+    })
+}
+
+/// `k` folds of (id, language, concept, form) entries with whole concepts
+/// or families per fold, balanced by entry count
+#[pyfunction]
+#[pyo3(signature = (entries, k=5, by="concept", families=None, seed=0))]
+fn py_group_k_fold(
+    py: Python<'_>,
+    entries: EntryRows,
+    k: usize,
+    by: &str,
+    families: Option<HashMap<String, String>>,
+    seed: u64,
+) -> PyResult<PyWordlistSplit> {
+    let by = parse_split_by(by)?;
+    py.allow_threads(|| {
+        let split = WordlistSplit::k_fold(&entries, by, families.as_ref(), k, seed)?;
+        Ok(PyWordlistSplit { inner: split })
+    })
+}
+
+// ============================================================================
+// PYTHON WRAPPER TYPES
+// ============================================================================
+
+#[pyclass]
+struct PyAlignment {
+    inner: Alignment,
+}
+
+impl From<Alignment> for PyAlignment {
+    fn from(inner: Alignment) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl PyAlignment {
+    #[getter]
+    fn sequence_a(&self) -> Vec<String> {
+        self.inner.sequence_a.clone()
+    }
+
+    #[getter]
+    fn sequence_b(&self) -> Vec<String> {
+        self.inner.sequence_b.clone()
+    }
+
+    #[getter]
+    fn cost(&self) -> f64 {
+        self.inner.cost
+    }
+
+    fn correspondences(&self) -> Vec<(String, String)> {
+        let (sequence_a, sequence_b) = (&self.inner.sequence_a, &self.inner.sequence_b);
+        let mut rules = Vec::new();
+        for i in 0..sequence_a.len().min(sequence_b.len()) {
+            if sequence_a[i] != sequence_b[i] && sequence_a[i] != "-" && sequence_b[i] != "-" {
+                rules.push((sequence_a[i].clone(), sequence_b[i].clone()));
+            }
+        }
+        rules
+    }
+
+    /// (operation, segment_a, segment_b, cost) per aligned position;
+    /// operation is "match", "substitute", "insert" or "delete" and gaps
+    /// are "-"
+    fn operations(&self) -> Vec<(&'static str, String, String, f64)> {
+        let alignment = &self.inner;
+        alignment
+            .operations
+            .iter()
+            .zip(&alignment.op_costs)
+            .zip(alignment.sequence_a.iter().zip(&alignment.sequence_b))
+            .map(|((op, &cost), (a, b))| (op.name(), a.clone(), b.clone(), cost))
+            .collect()
+    }
+
+    /// Run-length operation string, e.g. "3M1S2M1I" (M match, S substitute,
+    /// I insert, D delete)
+    fn cigar(&self) -> String {
+        self.inner.cigar()
+    }
+
+    /// JSON object with both sequences, the total cost, the CIGAR string
+    /// and an {op, a, b, cost} entry per aligned position
+    fn to_json(&self) -> String {
+        self.inner.to_json().to_string()
+    }
+}
+
+#[pyclass]
+struct PySoundCorrespondence {
+    #[pyo3(get)]
+    segment_a: String,
+    #[pyo3(get)]
+    segment_b: String,
+    #[pyo3(get)]
+    count: usize,
+    #[pyo3(get)]
+    expected: f64,
+    #[pyo3(get)]
+    pmi: f64,
+    #[pyo3(get)]
+    chi_square: f64,
+    #[pyo3(get)]
+    initial: usize,
+    #[pyo3(get)]
+    medial: usize,
+    #[pyo3(get)]
+    final_count: usize,
+    #[pyo3(get)]
+    contexts: Vec<(String, String, usize)>,
+}
+
+impl From<&SoundCorrespondence> for PySoundCorrespondence {
+    fn from(correspondence: &SoundCorrespondence) -> Self {
+        let [initial, medial, final_count] = correspondence.positions;
+        Self {
+            segment_a: correspondence.segment_a.clone(),
+            segment_b: correspondence.segment_b.clone(),
+            count: correspondence.count,
+            expected: correspondence.expected,
+            pmi: correspondence.pmi,
+            chi_square: correspondence.chi_square,
+            initial,
+            medial,
+            final_count,
+            contexts: correspondence.contexts.clone(),
+        }
+    }
+}
+
+#[pyclass]
+struct PySoundCorrespondences {
+    inner: SoundCorrespondences,
+}
+
+#[pymethods]
+impl PySoundCorrespondences {
+    /// Aligned columns counted, identical ones included
+    #[getter]
+    fn columns(&self) -> usize {
+        self.inner.columns
+    }
+
+    /// Correspondences, most significant first, seen at least `min_count`
+    /// times and reaching the optional PMI / chi-square minimums
+    #[pyo3(signature = (min_count=1, min_pmi=None, min_chi_square=None))]
+    fn filter(
+        &self,
+        min_count: usize,
+        min_pmi: Option<f64>,
+        min_chi_square: Option<f64>,
+    ) -> Vec<PySoundCorrespondence> {
+        self.inner
+            .filter(min_count, min_pmi, min_chi_square)
+            .into_iter()
+            .map(PySoundCorrespondence::from)
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.correspondences.len()
+    }
+}
+
+#[pyclass]
+struct PySeriation {
+    #[pyo3(get)]
+    order: Vec<usize>,
+    #[pyo3(get)]
+    ids: Vec<String>,
+    #[pyo3(get)]
+    positions: Vec<f64>,
+    #[pyo3(get)]
+    path_length: f64,
+}
+
+impl From<Seriation> for PySeriation {
+    fn from(seriation: Seriation) -> Self {
+        Self {
+            order: seriation.order,
+            ids: seriation.ids,
+            positions: seriation.positions,
+            path_length: seriation.path_length,
+        }
+    }
+}
+
+#[pyclass]
+struct PyCognateSet {
+    #[pyo3(get)]
+    id: usize,
+    #[pyo3(get)]
+    members: Vec<String>,
+    #[pyo3(get)]
+    size: usize,
+}
+
+impl From<CognateSet> for PyCognateSet {
+    fn from(set: CognateSet) -> Self {
+        Self {
+            id: set.id,
+            members: set.members,
+            size: set.size,
+        }
+    }
+}
+
+#[pyclass]
+struct PyMergedCognateSet {
+    #[pyo3(get)]
+    id: usize,
+    #[pyo3(get)]
+    members: Vec<String>,
+    #[pyo3(get)]
+    size: usize,
+    /// Membership confidence per member, parallel to `members`
+    #[pyo3(get)]
+    confidence: Vec<f64>,
+}
+
+impl From<MergedCognateSet> for PyMergedCognateSet {
+    fn from(merged: MergedCognateSet) -> Self {
+        Self {
+            id: merged.set.id,
+            members: merged.set.members,
+            size: merged.set.size,
+            confidence: merged.confidence,
+        }
+    }
+}
+
+#[pyclass]
+struct PyGraphStats {
+    #[pyo3(get)]
+    num_nodes: usize,
+    #[pyo3(get)]
+    num_edges: usize,
+    #[pyo3(get)]
+    avg_degree: f64,
+    #[pyo3(get)]
+    density: f64,
+    #[pyo3(get)]
+    num_components: usize,
+    #[pyo3(get)]
+    largest_component_size: usize,
+    #[pyo3(get)]
+    largest_component_fraction: f64,
+    #[pyo3(get)]
+    num_singletons: usize,
+    #[pyo3(get)]
+    component_size_distribution: Vec<(usize, usize)>,
+    #[pyo3(get)]
+    total_weight: f64,
+    #[pyo3(get)]
+    mean_strength: f64,
+    #[pyo3(get)]
+    median_strength: f64,
+    #[pyo3(get)]
+    max_strength: f64,
+}
+
+impl From<GraphStats> for PyGraphStats {
+    fn from(stats: GraphStats) -> Self {
+        Self {
+            num_nodes: stats.num_nodes,
+            num_edges: stats.num_edges,
+            avg_degree: stats.avg_degree,
+            density: stats.density,
+            num_components: stats.num_components,
+            largest_component_size: stats.largest_component_size,
+            largest_component_fraction: stats.largest_component_fraction,
+            num_singletons: stats.num_singletons,
+            component_size_distribution: stats.component_size_distribution,
+            total_weight: stats.total_weight,
+            mean_strength: stats.mean_strength,
+            median_strength: stats.median_strength,
+            max_strength: stats.max_strength,
+        }
+    }
+}
+
+/// Impact of a hypothetical edge or node change; component and community
+/// lists cover only the components the change touches
+#[pyclass(frozen)]
+struct PyWhatIf {
+    inner: WhatIf,
+}
+
+#[pymethods]
+impl PyWhatIf {
+    #[getter]
+    fn before(&self) -> PyGraphStats {
+        PyGraphStats::from(self.inner.before.clone())
+    }
+
+    #[getter]
+    fn after(&self) -> PyGraphStats {
+        PyGraphStats::from(self.inner.after.clone())
+    }
+
+    #[getter]
+    fn components_before(&self) -> Vec<Vec<String>> {
+        self.inner.components_before.clone()
+    }
+
+    #[getter]
+    fn components_after(&self) -> Vec<Vec<String>> {
+        self.inner.components_after.clone()
+    }
+
+    #[getter]
+    fn communities_before(&self) -> Vec<Vec<String>> {
+        self.inner.communities_before.clone()
+    }
+
+    #[getter]
+    fn communities_after(&self) -> Vec<Vec<String>> {
+        self.inner.communities_after.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyWhatIf(components={} -> {}, communities={} -> {})",
+            self.inner.before.num_components,
+            self.inner.after.num_components,
+            self.inner.communities_before.len(),
+            self.inner.communities_after.len()
+        )
+    }
+}
+
+#[pyclass]
+struct PyNodeDegree {
+    #[pyo3(get)]
+    id: String,
+    #[pyo3(get)]
+    in_degree: usize,
+    #[pyo3(get)]
+    out_degree: usize,
+    #[pyo3(get)]
+    in_strength: f64,
+    #[pyo3(get)]
+    out_strength: f64,
+}
+
+impl From<NodeDegree> for PyNodeDegree {
+    fn from(degree: NodeDegree) -> Self {
+        Self {
+            id: degree.id,
+            in_degree: degree.in_degree,
+            out_degree: degree.out_degree,
+            in_strength: degree.in_strength,
+            out_strength: degree.out_strength,
+        }
+    }
+}
+
+#[pyclass]
+struct PyDirectedGraphStats {
+    #[pyo3(get)]
+    num_nodes: usize,
+    #[pyo3(get)]
+    num_edges: usize,
+    #[pyo3(get)]
+    density: f64,
+    #[pyo3(get)]
+    reciprocity: f64,
+    #[pyo3(get)]
+    num_strong_components: usize,
+    #[pyo3(get)]
+    largest_strong_component_size: usize,
+}
+
+impl From<DirectedGraphStats> for PyDirectedGraphStats {
+    fn from(stats: DirectedGraphStats) -> Self {
+        Self {
+            num_nodes: stats.num_nodes,
+            num_edges: stats.num_edges,
+            density: stats.density,
+            reciprocity: stats.reciprocity,
+            num_strong_components: stats.num_strong_components,
+            largest_strong_component_size: stats.largest_strong_component_size,
+        }
+    }
+}
+
+#[pyclass]
+struct PyPathStats {
+    #[pyo3(get)]
+    diameter: usize,
+    #[pyo3(get)]
+    avg_path_length: f64,
+    #[pyo3(get)]
+    exact: bool,
+    #[pyo3(get)]
+    num_sources: usize,
+}
+
+impl From<PathStats> for PyPathStats {
+    fn from(stats: PathStats) -> Self {
+        Self {
+            diameter: stats.diameter,
+            avg_path_length: stats.avg_path_length,
+            exact: stats.exact,
+            num_sources: stats.num_sources,
+        }
+    }
+}
+
+#[pyclass]
+struct PyLexiconSimilarity {
+    /// (concept, form_a, form_b, similarity) per concept attested on both
+    /// sides, in concept order
+    #[pyo3(get)]
+    matches: Vec<(String, String, String, f64)>,
+    /// Concepts lacking a form in either lexicon
+    #[pyo3(get)]
+    missing: Vec<String>,
+    #[pyo3(get)]
+    mean_similarity: f64,
+    #[pyo3(get)]
+    distance: f64,
+}
+
+impl From<LexiconSimilarity> for PyLexiconSimilarity {
+    fn from(result: LexiconSimilarity) -> Self {
+        Self {
+            matches: result
+                .matches
+                .into_iter()
+                .map(|m| (m.concept, m.form_a, m.form_b, m.similarity))
+                .collect(),
+            missing: result.missing,
+            mean_similarity: result.mean_similarity,
+            distance: result.distance,
+        }
+    }
+}
+
+#[pyclass]
+struct PyConceptNeighbor {
+    #[pyo3(get)]
+    concept: String,
+    /// Mean best similarity of the query concept's forms to this concept's
+    #[pyo3(get)]
+    score: f64,
+    /// ((language, form) of the query concept, (language, form) of this
+    /// one) for the closest pair
+    #[pyo3(get)]
+    best: ((String, String), (String, String)),
+    #[pyo3(get)]
+    best_similarity: f64,
+}
+
+impl From<ConceptNeighbor> for PyConceptNeighbor {
+    fn from(neighbor: ConceptNeighbor) -> Self {
+        Self {
+            concept: neighbor.concept,
+            score: neighbor.score,
+            best: neighbor.best,
+            best_similarity: neighbor.best_similarity,
+        }
+    }
+}
+
+#[pyclass]
+struct PyPairSampleStats {
+    #[pyo3(get)]
+    total_pairs: u64,
+    /// Sampled similarities, ascending
+    #[pyo3(get)]
+    similarities: Vec<f64>,
+    #[pyo3(get)]
+    mean: f64,
+    #[pyo3(get)]
+    std_dev: f64,
+    #[pyo3(get)]
+    min: f64,
+    #[pyo3(get)]
+    max: f64,
+    /// (q, similarity) for q in 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99
+    #[pyo3(get)]
+    quantiles: Vec<(f64, f64)>,
+    inner: PairSampleStats,
+}
+
+#[pymethods]
+impl PyPairSampleStats {
+    /// Share of sampled pairs at or above `threshold`
+    fn fraction_above(&self, threshold: f64) -> f64 {
+        self.inner.fraction_above(threshold)
+    }
+
+    /// Estimated number of edges the full job would keep at `threshold`
+    fn estimated_edges(&self, threshold: f64) -> f64 {
+        self.inner.estimated_edges(threshold)
+    }
+}
+
+impl From<PairSampleStats> for PyPairSampleStats {
+    fn from(stats: PairSampleStats) -> Self {
+        Self {
+            total_pairs: stats.total_pairs,
+            similarities: stats.similarities.clone(),
+            mean: stats.mean,
+            std_dev: stats.std_dev,
+            min: stats.min,
+            max: stats.max,
+            quantiles: stats.quantiles.clone(),
+            inner: stats,
+        }
+    }
+}
+
+#[pyclass]
+struct PyConceptCoverage {
+    #[pyo3(get)]
+    language: String,
+    #[pyo3(get)]
+    covered: usize,
+    #[pyo3(get)]
+    total: usize,
+    #[pyo3(get)]
+    coverage: f64,
+    #[pyo3(get)]
+    missing: Vec<String>,
+}
+
+impl From<ConceptCoverage> for PyConceptCoverage {
+    fn from(report: ConceptCoverage) -> Self {
+        Self {
+            language: report.language,
+            covered: report.covered,
+            total: report.total,
+            coverage: report.coverage,
+            missing: report.missing,
+        }
+    }
+}
+
+#[pyclass]
+struct PyFieldBorrowing {
+    #[pyo3(get)]
+    field: String,
+    #[pyo3(get)]
+    entries: usize,
+    #[pyo3(get)]
+    borrowed: usize,
+    #[pyo3(get)]
+    rate: f64,
+    #[pyo3(get)]
+    borrowing_languages: usize,
+}
+
+impl From<FieldBorrowing> for PyFieldBorrowing {
+    fn from(report: FieldBorrowing) -> Self {
+        Self {
+            field: report.field,
+            entries: report.entries,
+            borrowed: report.borrowed,
+            rate: report.rate,
+            borrowing_languages: report.borrowing_languages,
+        }
+    }
+}
+
+#[pyclass]
+struct PyBorrowingRate {
+    /// Concept or field
+    #[pyo3(get)]
+    key: String,
+    #[pyo3(get)]
+    entries: usize,
+    #[pyo3(get)]
+    borrowed: usize,
+    #[pyo3(get)]
+    borrowing_languages: usize,
+    #[pyo3(get)]
+    rate: f64,
+    #[pyo3(get)]
+    lower: f64,
+    #[pyo3(get)]
+    upper: f64,
+}
+
+impl From<&BorrowingRate> for PyBorrowingRate {
+    fn from(rate: &BorrowingRate) -> Self {
+        Self {
+            key: rate.key.clone(),
+            entries: rate.entries,
+            borrowed: rate.borrowed,
+            borrowing_languages: rate.borrowing_languages,
+            rate: rate.rate,
+            lower: rate.lower,
+            upper: rate.upper,
+        }
+    }
+}
+
+/// Borrowing rates per concept and semantic field, highest first
+#[pyclass(frozen)]
+struct PyBorrowability {
+    inner: Borrowability,
+}
+
+#[pymethods]
+impl PyBorrowability {
+    #[getter]
+    fn confidence(&self) -> f64 {
+        self.inner.confidence
+    }
+
+    #[getter]
+    fn concepts(&self) -> Vec<PyBorrowingRate> {
+        self.inner
+            .concepts
+            .iter()
+            .map(PyBorrowingRate::from)
+            .collect()
+    }
+
+    #[getter]
+    fn fields(&self) -> Vec<PyBorrowingRate> {
+        self.inner
+            .fields
+            .iter()
+            .map(PyBorrowingRate::from)
+            .collect()
+    }
+
+    /// {"confidence", "concepts", "fields"} JSON for the visualization
+    /// layer; each rate has key, entries, borrowed, borrowing_languages,
+    /// rate, lower and upper
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyBorrowability(concepts={}, fields={})",
+            self.inner.concepts.len(),
+            self.inner.fields.len()
+        )
+    }
+}
+
+#[pyclass]
+struct PyConceptDensity {
+    #[pyo3(get)]
+    concept: String,
+    #[pyo3(get)]
+    entries: usize,
+    #[pyo3(get)]
+    cognate_sets: usize,
+    #[pyo3(get)]
+    singletons: usize,
+    /// (sets - 1) / (entries - 1): 0 = all cognate, 1 = none cognate
+    #[pyo3(get)]
+    fragmentation: f64,
+    #[pyo3(get)]
+    mean_within_similarity: Option<f64>,
+    #[pyo3(get)]
+    mean_between_similarity: Option<f64>,
+}
+
+impl From<ConceptDensity> for PyConceptDensity {
+    fn from(report: ConceptDensity) -> Self {
+        Self {
+            concept: report.concept,
+            entries: report.entries,
+            cognate_sets: report.cognate_sets,
+            singletons: report.singletons,
+            fragmentation: report.fragmentation,
+            mean_within_similarity: report.mean_within_similarity,
+            mean_between_similarity: report.mean_between_similarity,
+        }
+    }
+}
+
+/// Folds of a grouped wordlist split
+#[pyclass(frozen)]
+struct PyWordlistSplit {
+    inner: WordlistSplit,
+}
+
+#[pymethods]
+impl PyWordlistSplit {
+    #[getter]
+    fn k(&self) -> usize {
+        self.inner.k
+    }
+
+    /// Fold of each entry, in input order
+    #[getter]
+    fn folds(&self) -> Vec<usize> {
+        self.inner.folds.clone()
+    }
+
+    /// (concept or family, fold), sorted by group
+    #[getter]
+    fn groups(&self) -> Vec<(String, usize)> {
+        self.inner
+            .groups
+            .iter()
+            .map(|(group, &fold)| (group.clone(), fold))
+            .collect()
+    }
+
+    fn fold_of(&self, id: &str) -> Option<usize> {
+        self.inner.fold_of(id)
+    }
+
+    /// Entry IDs in `fold`, in input order
+    fn fold_ids(&self, fold: usize) -> Vec<String> {
+        self.inner
+            .fold_ids(fold)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// (train, test) indices of (id, id) pairs: test pairs have both
+    /// entries in `test_fold` (default: the last fold), train pairs neither;
+    /// pairs straddling the two are left out
+    #[pyo3(signature = (pairs, test_fold=None))]
+    fn split_pairs(
+        &self,
+        py: Python<'_>,
+        pairs: Vec<(String, String)>,
+        test_fold: Option<usize>,
+    ) -> PyResult<(Vec<usize>, Vec<usize>)> {
+        let test_fold = test_fold.unwrap_or(self.inner.k - 1);
+        if test_fold >= self.inner.k {
+            return Err(LangVizError::InvalidParameter {
+                name: "test_fold".to_string(),
+                value: test_fold as f64,
+                expected: format!("a fold below {}", self.inner.k),
+            }
+            .into());
+        }
+        py.allow_threads(|| {
+            let split = self.inner.split_pairs(&pairs, test_fold)?;
+            Ok((split.train, split.test))
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyWordlistSplit(k={}, entries={}, groups={})",
+            self.inner.k,
+            self.inner.folds.len(),
+            self.inner.groups.len()
+        )
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+struct PyPartialCognates {
+    #[pyo3(get)]
+    id: String,
+    #[pyo3(get)]
+    language: String,
+    #[pyo3(get)]
+    concept: String,
+    #[pyo3(get)]
+    morphemes: Vec<String>,
+    #[pyo3(get)]
+    cognate_ids: Vec<usize>,
+}
+
+#[pymethods]
+impl PyPartialCognates {
+    /// Whether any morpheme shares a partial cognate set with `other`
+    fn is_partial_cognate(&self, other: &PyPartialCognates) -> bool {
+        self.cognate_ids.iter().any(|id| other.cognate_ids.contains(id))
+    }
+}
+
+impl From<PartialCognates> for PyPartialCognates {
+    fn from(word: PartialCognates) -> Self {
+        Self {
+            id: word.id,
+            language: word.language,
+            concept: word.concept,
+            morphemes: word.morphemes,
+            cognate_ids: word.cognate_ids,
+        }
+    }
+}
+
+impl From<&PyPartialCognates> for PartialCognates {
+    fn from(word: &PyPartialCognates) -> Self {
+        Self {
+            id: word.id.clone(),
+            language: word.language.clone(),
+            concept: word.concept.clone(),
+            morphemes: word.morphemes.clone(),
+            cognate_ids: word.cognate_ids.clone(),
+        }
+    }
+}
+
+#[pyclass]
+struct PyIdAssignment {
+    /// Assigned ID per input cluster
+    #[pyo3(get)]
+    cluster_ids: Vec<String>,
+    #[pyo3(get)]
+    new_ids: Vec<String>,
+    #[pyo3(get)]
+    retired_ids: Vec<String>,
+    /// (kind, id, related ids) with kind "split" or "merge"
+    #[pyo3(get)]
+    conflicts: Vec<(String, String, Vec<String>)>,
+}
+
+impl From<IdAssignment> for PyIdAssignment {
+    fn from(assignment: IdAssignment) -> Self {
+        Self {
+            cluster_ids: assignment.cluster_ids,
+            new_ids: assignment.new_ids,
+            retired_ids: assignment.retired_ids,
+            conflicts: assignment
+                .conflicts
+                .into_iter()
+                .map(|c| (c.kind.as_str().to_string(), c.id, c.related))
+                .collect(),
+        }
+    }
+}
+
+#[pyclass]
+struct PyComponentStats {
+    #[pyo3(get)]
+    id: usize,
+    #[pyo3(get)]
+    size: usize,
+    #[pyo3(get)]
+    num_edges: usize,
+    #[pyo3(get)]
+    density: f64,
+    #[pyo3(get)]
+    mean_weight: f64,
+    #[pyo3(get)]
+    diameter: usize,
+    #[pyo3(get)]
+    top_member: String,
+    #[pyo3(get)]
+    top_pagerank: f64,
+}
+
+impl From<ComponentStats> for PyComponentStats {
+    fn from(c: ComponentStats) -> Self {
+        Self {
+            id: c.id,
+            size: c.size,
+            num_edges: c.num_edges,
+            density: c.density,
+            mean_weight: c.mean_weight,
+            diameter: c.diameter,
+            top_member: c.top_member,
+            top_pagerank: c.top_pagerank,
+        }
+    }
+}
+
+#[pyclass]
+struct PyClusterQuality {
+    #[pyo3(get)]
+    size: usize,
+    #[pyo3(get)]
+    internal_weight: f64,
+    #[pyo3(get)]
+    cut_weight: f64,
+    #[pyo3(get)]
+    conductance: f64,
+    #[pyo3(get)]
+    normalized_cut: f64,
+    #[pyo3(get)]
+    expansion: f64,
+}
+
+impl From<ClusterQuality> for PyClusterQuality {
+    fn from(q: ClusterQuality) -> Self {
+        Self {
+            size: q.size,
+            internal_weight: q.internal_weight,
+            cut_weight: q.cut_weight,
+            conductance: q.conductance,
+            normalized_cut: q.normalized_cut,
+            expansion: q.expansion,
+        }
+    }
+}
+
+#[pyclass]
+struct PyNodeCentrality {
+    #[pyo3(get)]
+    id: String,
+    #[pyo3(get)]
+    pagerank: f64,
+    #[pyo3(get)]
+    degree: f64,
+    #[pyo3(get)]
+    betweenness: f64,
+    #[pyo3(get)]
+    closeness: f64,
+    #[pyo3(get)]
+    eigenvector: f64,
+    #[pyo3(get)]
+    attributes: BTreeMap<String, AttrValue>,
+}
+
+impl From<NodeCentrality> for PyNodeCentrality {
+    fn from(c: NodeCentrality) -> Self {
+        Self {
+            id: c.id,
+            pagerank: c.pagerank,
+            degree: c.degree,
+            betweenness: c.betweenness,
+            closeness: c.closeness,
+            eigenvector: c.eigenvector,
+            attributes: c.attributes,
+        }
+    }
+}
+
+/// Directed borrowing network handle (donor → recipient arcs); safe to
+/// share between Python threads
+#[pyclass(frozen)]
+struct PyBorrowingGraph {
+    inner: Shared<BorrowingGraph>,
+}
+
+#[pymethods]
+impl PyBorrowingGraph {
+    #[new]
+    fn new(py: Python<'_>, edges: EdgeRows) -> Self {
+        py.allow_threads(|| Self {
+            inner: BorrowingGraph::from_edges(edges.0).into(),
+        })
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.get().node_count()
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.get().edge_count()
+    }
+
+    /// Add a donor → recipient arc; an existing arc gains `weight`
+    fn add_edge(&self, donor: String, recipient: String, weight: f64) {
+        self.inner
+            .update(|inner| inner.add_edge(donor, recipient, weight));
+    }
+
+    fn degrees(&self) -> Vec<PyNodeDegree> {
+        self.inner
+            .get()
+            .degrees()
+            .into_iter()
+            .map(PyNodeDegree::from)
+            .collect()
+    }
+
+    #[pyo3(signature = (damping=0.85, iterations=100))]
+    fn pagerank(
+        &self,
+        py: Python<'_>,
+        damping: f64,
+        iterations: usize,
+    ) -> PyResult<Vec<(String, f64)>> {
+        validate_damping(damping)?;
+        Ok(py.allow_threads(|| self.inner.get().pagerank(damping, iterations)))
+    }
+
+    fn strongly_connected_components(&self, py: Python<'_>) -> Vec<Vec<String>> {
+        py.allow_threads(|| self.inner.get().strongly_connected_components())
+    }
+
+    fn stats(&self, py: Python<'_>) -> PyDirectedGraphStats {
+        py.allow_threads(|| PyDirectedGraphStats::from(self.inner.get().stats()))
+    }
+
+    fn __repr__(&self) -> String {
+        let graph = self.inner.get();
+        format!(
+            "PyBorrowingGraph(nodes={}, edges={})",
+            graph.node_count(),
+            graph.edge_count()
+        )
+    }
+}
+
+#[pyclass]
+struct PyContactLink {
+    #[pyo3(get)]
+    donor: String,
+    #[pyo3(get)]
+    recipient: String,
+    #[pyo3(get)]
+    loans: usize,
+    /// Summed candidate score
+    #[pyo3(get)]
+    weight: f64,
+    #[pyo3(get)]
+    concepts: Vec<String>,
+    /// Summed score per semantic field
+    #[pyo3(get)]
+    fields: BTreeMap<String, f64>,
+}
+
+impl From<&ContactLink> for PyContactLink {
+    fn from(link: &ContactLink) -> Self {
+        Self {
+            donor: link.donor.clone(),
+            recipient: link.recipient.clone(),
+            loans: link.loans,
+            weight: link.weight,
+            concepts: link.concepts.clone(),
+            fields: link.fields.clone(),
+        }
+    }
+}
+
+/// Directed language contact network from borrowing candidates
+#[pyclass(frozen)]
+struct PyContactNetwork {
+    inner: ContactNetwork,
+}
+
+#[pymethods]
+impl PyContactNetwork {
+    /// From (donor, recipient, concept, score) candidates scoring at least
+    /// `min_score`; `fields` maps concepts to semantic fields
+    #[new]
+    #[pyo3(signature = (candidates, fields=None, min_score=0.0))]
+    fn new(
+        py: Python<'_>,
+        candidates: Vec<(String, String, String, f64)>,
+        fields: Option<HashMap<String, Vec<String>>>,
+        min_score: f64,
+    ) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let fields = SemanticFields::new(&fields.unwrap_or_default());
+            ContactNetwork::from_candidates(&candidates, &fields, min_score)
+                .map(|inner| Self { inner })
+                .map_err(pyo3::exceptions::PyValueError::new_err)
+        })
+    }
+
+    /// Links sorted by (donor, recipient)
+    fn links(&self) -> Vec<PyContactLink> {
+        self.inner.links().iter().map(PyContactLink::from).collect()
+    }
+
+    /// The links as a borrowing graph (weights are the summed scores) for
+    /// degrees, PageRank and strongly connected components
+    fn graph(&self) -> PyBorrowingGraph {
+        PyBorrowingGraph {
+            inner: self.inner.graph().clone().into(),
+        }
+    }
+
+    #[pyo3(signature = (include_pagerank=false))]
+    fn to_graphml(&self, py: Python<'_>, include_pagerank: bool) -> String {
+        py.allow_threads(|| self.inner.to_graphml(include_pagerank))
+    }
+
+    /// Dict for `networkx.node_link_graph` (a DiGraph)
+    #[pyo3(signature = (link_key="links"))]
+    fn node_link_data(&self, py: Python<'_>, link_key: &str) -> PyResult<PyObject> {
+        let data = py.allow_threads(|| self.inner.to_node_link_data(link_key));
+        json_to_py(py, &data)
+    }
+
+    fn __repr__(&self) -> String {
+        let graph = self.inner.graph();
+        format!(
+            "PyContactNetwork(languages={}, links={})",
+            graph.node_count(),
+            graph.edge_count()
+        )
+    }
+}
+
+/// Parallel labeled edges (one per metric or data source) kept apart until
+/// aggregated into a `PyCognateGraph`
+#[pyclass]
+struct PyCognateMultiGraph {
+    inner: CognateMultiGraph,
+}
+
+#[pymethods]
+impl PyCognateMultiGraph {
+    /// From (source, target, weight, label) edges; (a, b) and (b, a) are
+    /// parallel
+    #[new]
+    fn new(py: Python<'_>, edges: Vec<(String, String, f64, String)>) -> Self {
+        py.allow_threads(|| Self {
+            inner: CognateMultiGraph::from_labeled_edges(edges),
+        })
+    }
+
+    /// Number of distinct node pairs
+    fn pair_count(&self) -> usize {
+        self.inner.pair_count()
+    }
+
+    /// Total number of parallel edges
+    fn edge_count(&self) -> usize {
+        self.inner.edge_count()
+    }
+
+    /// (label, weight) of every edge between two nodes, in insertion order
+    fn edges_between(&self, a: &str, b: &str) -> Vec<(Option<String>, f64)> {
+        self.inner.edges_between(a, b).to_vec()
+    }
+
+    /// Collapse parallel edges by `aggregation` (as in
+    /// `py_aggregate_multi_edges`) and keep weights >= `threshold`
+    #[pyo3(signature = (aggregation, threshold, label_weights=None))]
+    fn aggregate(
+        &self,
+        py: Python<'_>,
+        aggregation: &str,
+        threshold: f64,
+        label_weights: Option<HashMap<String, f64>>,
+    ) -> PyResult<PyCognateGraph> {
+        validate_threshold(threshold)?;
+        let aggregation = edge_aggregation(aggregation, label_weights)?;
+        let inner = py.allow_threads(|| self.inner.aggregate(&aggregation, threshold));
+        Ok(PyCognateGraph {
+            inner: inner.into(),
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyCognateMultiGraph(pairs={}, edges={})",
+            self.inner.pair_count(),
+            self.inner.edge_count()
+        )
+    }
+}
+
+/// Graph handle: built once, then queried without rebuilding per call.
+/// Queries from several Python threads run concurrently on a snapshot;
+/// in-place updates wait for one another and don't affect running queries.
+#[pyclass(frozen)]
+#[derive(Clone)]
+struct PyCognateGraph {
+    inner: Shared<CognateGraph>,
+}
+
+impl PyCognateGraph {
+    fn what_if(&self, py: Python<'_>, change: GraphChange, resolution: f64) -> PyResult<PyWhatIf> {
+        let inner = py
+            .allow_threads(|| self.inner.get().what_if(&change, resolution))
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyWhatIf { inner })
+    }
+}
+
+#[pymethods]
+impl PyCognateGraph {
+    #[new]
+    #[pyo3(signature = (edges, threshold, node_attributes=None))]
+    fn new(
+        py: Python<'_>,
+        edges: EdgeRows,
+        threshold: f64,
+        node_attributes: Option<NodeAttributes>,
+    ) -> PyResult<Self> {
+        validate_threshold(threshold)?;
+        py.allow_threads(|| {
+            let similarity_edges: Vec<SimilarityEdge> = edges
+                .into_iter()
+                .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+                .collect();
+
+            let mut inner = CognateGraph::from_edges(similarity_edges, threshold);
+            if let Some(attributes) = node_attributes {
+                inner.set_node_attributes(attributes);
+            }
+            Ok(Self {
+                inner: inner.into(),
+            })
+        })
+    }
+
+    /// Build with per-language-pair calibration instead of one threshold;
+    /// node languages are read from `language_key` in `node_attributes`
+    #[staticmethod]
+    #[pyo3(signature = (
+        edges,
+        calibration,
+        node_attributes,
+        language_key="language",
+        mode="threshold",
+        min_z=2.0
+    ))]
+    fn from_calibrated_edges(
+        py: Python<'_>,
+        edges: EdgeRows,
+        calibration: PyRef<PyPairThresholds>,
+        node_attributes: NodeAttributes,
+        language_key: &str,
+        mode: &str,
+        min_z: f64,
+    ) -> PyResult<Self> {
+        let calibration = &calibration.inner;
+        py.allow_threads(|| {
+            let mode = match mode
+                .parse()
+                .map_err(pyo3::exceptions::PyValueError::new_err)?
+            {
+                CalibrationMode::ZScore { .. } => CalibrationMode::ZScore { min_z },
+                mode => mode,
+            };
+            let languages: HashMap<String, String> = node_attributes
+                .iter()
+                .filter_map(|(id, attrs)| {
+                    let language = attrs.get(language_key)?.as_text()?;
+                    Some((id.clone(), language.to_string()))
+                })
+                .collect();
+            let similarity_edges: Vec<SimilarityEdge> = edges
+                .into_iter()
+                .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+                .collect();
+
+            let mut inner = CognateGraph::from_calibrated_edges(
+                similarity_edges,
+                &languages,
+                calibration,
+                mode,
+            );
+            inner.set_node_attributes(node_attributes);
+            Ok(Self {
+                inner: inner.into(),
+            })
+        })
+    }
+
+    /// Build from (source, target, weight, provenance) tuples
+    /// Segment-correspondence network over cognate sets of (language, ipa)
+    /// members: `language:segment` nodes (with language, segment and
+    /// frequency attributes), edges weighted by correspondence counts
+    #[staticmethod]
+    #[pyo3(signature = (cognate_sets, min_count=1, include_gaps=false))]
+    fn from_correspondences(
+        py: Python<'_>,
+        cognate_sets: Vec<Vec<(String, String)>>,
+        min_count: u64,
+        include_gaps: bool,
+    ) -> Self {
+        py.allow_threads(|| {
+            let matrices = correspondence_matrices(&cognate_sets);
+            let inner = correspondence_graph(&matrices, min_count, include_gaps);
+            Self {
+                inner: inner.into(),
+            }
+        })
+    }
+
+    #[staticmethod]
+    fn from_provenance_edges(
+        py: Python<'_>,
+        edges: Vec<ProvenanceEdge>,
+        threshold: f64,
+    ) -> PyResult<Self> {
+        validate_provenance_weights(&edges)?;
+        validate_threshold(threshold)?;
+        Ok(py.allow_threads(|| {
+            let similarity_edges: Vec<SimilarityEdge> = edges
+                .into_iter()
+                .map(|(s, t, w, p)| SimilarityEdge::with_provenance(s, t, w, p))
+                .collect();
+
+            Self {
+                inner: CognateGraph::from_edges(similarity_edges, threshold).into(),
+            }
+        }))
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.get().graph.node_count()
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.get().graph.edge_count()
+    }
+
+    fn set_node_attributes(&self, attributes: NodeAttributes) {
+        self.inner
+            .update(|inner| inner.set_node_attributes(attributes));
+    }
+
+    /// Attributes of one node (empty if unknown or bare)
+    fn node_attributes(&self, id: &str) -> BTreeMap<String, AttrValue> {
+        self.inner.get().node_attributes(id)
+    }
+
+    /// One attribute of a node (None if the node or key is missing)
+    fn node_attribute(&self, id: &str, key: &str) -> Option<AttrValue> {
+        self.inner.get().node_attribute(id, key).cloned()
+    }
+
+    /// Stable integer ID of a node (None if unknown)
+    fn node_index(&self, id: &str) -> Option<usize> {
+        self.inner.get().node_index(id)
+    }
+
+    /// String ID of the node with integer ID `index` (None if out of range)
+    fn node_id(&self, index: usize) -> Option<String> {
+        self.inner.get().node_id(index).map(str::to_string)
+    }
+
+    /// Every node ID, in integer ID order
+    fn node_ids(&self) -> Vec<String> {
+        self.inner.get().node_ids()
+    }
+
+    /// Edges as (source index, target index, weight)
+    fn indexed_edges(&self, py: Python<'_>) -> Vec<(u32, u32, f64)> {
+        py.allow_threads(|| self.inner.get().indexed_edge_list())
+    }
+
+    /// Add (source, target, weight) edges in place; components stay cached
+    fn add_edges(&self, py: Python<'_>, edges: EdgeRows) {
+        py.allow_threads(|| {
+            let similarity_edges: Vec<SimilarityEdge> = edges
+                .into_iter()
+                .map(|(s, t, w)| SimilarityEdge::new(s, t, w))
+                .collect();
+            self.inner.update(|inner| inner.add_edges(similarity_edges));
+        })
+    }
+
+    /// Drop edges lighter than `threshold` in place; returns the count removed
+    fn remove_edges_below(&self, py: Python<'_>, threshold: f64) -> PyResult<usize> {
+        validate_threshold(threshold)?;
+        Ok(py.allow_threads(|| {
+            self.inner
+                .update(|inner| inner.remove_edges_below(threshold))
+        }))
+    }
+
+    /// Set the weight of the edge between two nodes; False if absent
+    fn update_edge_weight(&self, source: &str, target: &str, weight: f64) -> PyResult<bool> {
+        validate_weights([(source, target, weight)])?;
+        Ok(self
+            .inner
+            .update(|inner| inner.update_edge_weight(source, target, weight)))
+    }
+
+    fn edges(&self) -> Vec<WeightedEdge> {
+        self.inner.get().edge_list()
+    }
+
+    fn provenance_edges(&self) -> Vec<ProvenanceEdge> {
+        self.inner.get().provenance_edge_list()
+    }
+
+    fn find_cognate_sets(&self, py: Python<'_>) -> Vec<PyCognateSet> {
+        py.allow_threads(|| {
+            self.inner
+                .get()
+                .find_cognate_sets()
+                .into_iter()
+                .map(PyCognateSet::from)
+                .collect()
+        })
+    }
+
+    #[pyo3(signature = (resolution=1.0, return_hierarchy=false))]
+    fn detect_communities(
+        &self,
+        py: Python<'_>,
+        resolution: f64,
+        return_hierarchy: bool,
+    ) -> PyObject {
+        communities_to_py(py, &self.inner.get(), resolution, return_hierarchy)
+    }
+
+    #[pyo3(signature = (max_iter=100, seed=0))]
+    fn detect_communities_lpa(
+        &self,
+        py: Python<'_>,
+        max_iter: usize,
+        seed: u64,
+    ) -> Vec<Vec<String>> {
+        py.allow_threads(|| self.inner.get().label_propagation(max_iter, seed))
+    }
+
+    /// (id, score) pairs, highest first; with `with_attributes`,
+    /// (id, score, attributes) triples
+    #[pyo3(signature = (damping, iterations, with_attributes=false))]
+    fn compute_pagerank(
+        &self,
+        py: Python<'_>,
+        damping: f64,
+        iterations: usize,
+        with_attributes: bool,
+    ) -> PyResult<PyObject> {
+        validate_damping(damping)?;
+        // One snapshot, so the attributes match the ranked graph
+        let graph = self.inner.get();
+        let result = py.allow_threads(|| {
+            let mut result: Vec<(String, f64)> = graph
+                .compute_pagerank(damping, iterations)
+                .into_iter()
+                .collect();
+            result.sort_by(|a, b| b.1.total_cmp(&a.1));
+            result
+        });
+        if !with_attributes {
+            return Ok(result.into_py(py));
+        }
+        Ok(result
+            .into_iter()
+            .map(|(id, score)| {
+                let attributes = graph.node_attributes(&id);
+                (id, score, attributes)
+            })
+            .collect::<Vec<_>>()
+            .into_py(py))
+    }
+
+    fn compute_betweenness(&self, py: Python<'_>) -> Vec<(String, f64)> {
+        py.allow_threads(|| self.inner.get().compute_betweenness())
+    }
+
+    fn compute_closeness(&self, py: Python<'_>) -> Vec<(String, f64)> {
+        py.allow_threads(|| self.inner.get().compute_closeness())
+    }
+
+    fn compute_centralities(
+        &self,
+        py: Python<'_>,
+        damping: f64,
+        iterations: usize,
+    ) -> PyResult<Vec<PyNodeCentrality>> {
+        validate_damping(damping)?;
+        Ok(py.allow_threads(|| {
+            self.inner
+                .get()
+                .compute_centralities(damping, iterations)
+                .into_iter()
+                .map(PyNodeCentrality::from)
+                .collect()
+        }))
+    }
+
+    fn shortest_paths(&self, source_id: &str) -> PyResult<BTreeMap<String, f64>> {
+        self.inner
+            .get()
+            .shortest_paths(source_id)
+            .ok_or_else(|| LangVizError::UnknownEntryId(source_id.to_string()).into())
+    }
+
+    fn stats(&self, py: Python<'_>) -> PyGraphStats {
+        py.allow_threads(|| PyGraphStats::from(self.inner.get().stats()))
+    }
+
+    #[pyo3(signature = (max_sources=256, seed=0))]
+    fn path_stats(&self, py: Python<'_>, max_sources: usize, seed: u64) -> PyPathStats {
+        py.allow_threads(|| PyPathStats::from(self.inner.get().path_stats(max_sources, seed)))
+    }
+
+    /// Handle on the cluster-level summary graph: one node per cluster
+    /// (with size and internal_weight attributes), edges weighted by the
+    /// member similarity mass between clusters
+    #[pyo3(signature = (clusters, names=None))]
+    fn cluster_graph(
+        &self,
+        py: Python<'_>,
+        clusters: Vec<Vec<String>>,
+        names: Option<Vec<String>>,
+    ) -> PyResult<Self> {
+        let names = cluster_names(&clusters, names);
+        let inner = py.allow_threads(|| self.inner.get().cluster_graph(&clusters, &names))?;
+        Ok(Self {
+            inner: inner.into(),
+        })
+    }
+
+    /// Conductance, normalized cut and expansion of each cluster
+    fn cluster_quality(
+        &self,
+        py: Python<'_>,
+        clusters: Vec<Vec<String>>,
+    ) -> PyResult<Vec<PyClusterQuality>> {
+        let quality = py.allow_threads(|| self.inner.get().cluster_quality(&clusters))?;
+        Ok(quality.into_iter().map(PyClusterQuality::from).collect())
+    }
+
+    #[pyo3(signature = (damping=0.85, iterations=100, exact_limit=1000))]
+    fn component_stats(
+        &self,
+        py: Python<'_>,
+        damping: f64,
+        iterations: usize,
+        exact_limit: usize,
+    ) -> PyResult<Vec<PyComponentStats>> {
+        validate_damping(damping)?;
+        Ok(py.allow_threads(|| {
+            self.inner
+                .get()
+                .component_stats(damping, iterations, exact_limit)
+                .into_iter()
+                .map(PyComponentStats::from)
+                .collect()
+        }))
+    }
+
+    fn to_json(&self, py: Python<'_>) -> String {
+        py.allow_threads(|| self.inner.get().to_json())
+    }
+
+    /// Stream `to_json` to a path or file object, node by node; compressed
+    /// per `compression` or the path's extension (.gz, .zst)
+    #[pyo3(signature = (file, compression=None))]
+    fn write_json(
+        &self,
+        py: Python<'_>,
+        file: &PyAny,
+        compression: Option<&str>,
+    ) -> PyResult<()> {
+        write_export(py, file, compression, |w| self.inner.get().write_json(w))
+    }
+
+    /// Graph from a `to_json` / `write_json` file (plain, gzip or zstd)
+    #[staticmethod]
+    fn read_json(py: Python<'_>, path: std::path::PathBuf) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let reader = open_file(&path).map_err(|e| e.to_string())?;
+            CognateGraph::read_json(reader)
+        })
+        .map(|inner| Self {
+            inner: inner.into(),
+        })
+        .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    /// Tab-separated source/target/weight/provenance rows, like `write_json`
+    #[pyo3(signature = (file, compression=None))]
+    fn write_edge_list(
+        &self,
+        py: Python<'_>,
+        file: &PyAny,
+        compression: Option<&str>,
+    ) -> PyResult<()> {
+        write_export(py, file, compression, |w| {
+            self.inner.get().write_edge_list(w)
+        })
+    }
+
+    /// `to_graphml` written to a path or file object, like `write_json`
+    #[pyo3(signature = (file, include_pagerank=false, compression=None))]
+    fn write_graphml(
+        &self,
+        py: Python<'_>,
+        file: &PyAny,
+        include_pagerank: bool,
+        compression: Option<&str>,
+    ) -> PyResult<()> {
+        let graphml = py.allow_threads(|| self.inner.get().to_graphml(include_pagerank));
+        write_export(py, file, compression, |w| w.write_all(graphml.as_bytes()))
+    }
+
+    #[pyo3(signature = (as_json=false))]
+    fn max_spanning_forest(&self, py: Python<'_>, as_json: bool) -> PyObject {
+        spanning_forest_to_py(py, &self.inner.get(), as_json)
+    }
+
+    #[pyo3(signature = (include_pagerank=false))]
+    fn to_graphml(&self, py: Python<'_>, include_pagerank: bool) -> String {
+        py.allow_threads(|| self.inner.get().to_graphml(include_pagerank))
+    }
+
+    #[pyo3(signature = (include_pagerank=false))]
+    fn to_gexf(&self, py: Python<'_>, include_pagerank: bool) -> String {
+        py.allow_threads(|| self.inner.get().to_gexf(include_pagerank))
+    }
+
+    /// Dict for `networkx.node_link_graph`
+    #[pyo3(signature = (link_key="links"))]
+    fn node_link_data(&self, py: Python<'_>, link_key: &str) -> PyResult<PyObject> {
+        let data = py.allow_threads(|| self.inner.get().to_node_link_data(link_key));
+        json_to_py(py, &data)
+    }
+
+    /// Dict for `networkx.adjacency_graph`
+    fn adjacency_data(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let data = py.allow_threads(|| self.inner.get().to_adjacency_data());
+        json_to_py(py, &data)
+    }
+
+    #[pyo3(signature = (node_filter=None, edge_filter=None))]
+    fn filter(
+        &self,
+        py: Python<'_>,
+        node_filter: Option<&str>,
+        edge_filter: Option<&str>,
+    ) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let node_predicate = node_filter
+                .map(Predicate::parse)
+                .transpose()
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            let edge_predicate = edge_filter
+                .map(Predicate::parse)
+                .transpose()
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+            Ok(Self {
+                inner: self
+                    .inner
+                    .get()
+                    .filter(node_predicate.as_ref(), edge_predicate.as_ref())
+                    .into(),
+            })
+        })
+    }
+
+    #[pyo3(signature = (n, language_key="language"))]
+    fn top_edges_per_language_pair(
+        &self,
+        py: Python<'_>,
+        n: usize,
+        language_key: &str,
+    ) -> Vec<(String, String, Vec<WeightedEdge>)> {
+        py.allow_threads(|| {
+            self.inner
+                .get()
+                .top_edges_per_language_pair(language_key, n)
+                .into_iter()
+                .map(|((lang_a, lang_b), top)| (lang_a, lang_b, top))
+                .collect()
+        })
+    }
+
+    #[pyo3(signature = (method, target_size, seed=0))]
+    fn sample(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        target_size: usize,
+        seed: u64,
+    ) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let method: SamplingMethod = method
+                .parse()
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            Ok(Self {
+                inner: self.inner.get().sample(method, target_size, seed).into(),
+            })
+        })
+    }
+
+    /// Handle on the k-core (every node keeps at least `k` neighbors)
+    fn k_core(&self, py: Python<'_>, k: usize) -> Self {
+        py.allow_threads(|| Self {
+            inner: self.inner.get().k_core(k).into(),
+        })
+    }
+
+    fn core_numbers(&self, py: Python<'_>) -> Vec<(String, usize)> {
+        py.allow_threads(|| self.inner.get().core_numbers())
+    }
+
+    /// Bridge edges, weakest first
+    fn bridges(&self, py: Python<'_>) -> Vec<WeightedEdge> {
+        py.allow_threads(|| self.inner.get().bridges())
+    }
+
+    fn articulation_points(&self, py: Python<'_>) -> Vec<String> {
+        py.allow_threads(|| self.inner.get().articulation_points())
+    }
+
+    /// One handle per biconnected component, largest first
+    fn biconnected_components(&self, py: Python<'_>) -> Vec<Self> {
+        py.allow_threads(|| {
+            self.inner
+                .get()
+                .biconnected_components()
+                .into_iter()
+                .map(|inner| Self {
+                    inner: inner.into(),
+                })
+                .collect()
+        })
+    }
+
+    /// Threshold-free handle keeping each node's locally strongest edges
+    #[pyo3(signature = (method="top_k", parameter=None))]
+    fn sparsify(&self, py: Python<'_>, method: &str, parameter: Option<f64>) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let method = sparsification_method(method, parameter)?;
+            Ok(Self {
+                inner: self.inner.get().sparsify(method).into(),
+            })
+        })
+    }
+
+    /// Handle with edges reweighted by mutual kNN agreement
+    #[pyo3(signature = (k=5, boost=1.2, penalty=0.5))]
+    fn mutual_knn_reweight(
+        &self,
+        py: Python<'_>,
+        k: usize,
+        boost: f64,
+        penalty: f64,
+    ) -> PyResult<Self> {
+        validate_reweight(k, boost, penalty)?;
+        Ok(py.allow_threads(|| Self {
+            inner: self
+                .inner
+                .get()
+                .mutual_knn_reweight(k, boost, penalty)
+                .into(),
+        }))
+    }
+
+    #[pyo3(signature = (other, operation, policy="max"))]
+    fn set_operation(
+        &self,
+        py: Python<'_>,
+        other: &PyCognateGraph,
+        operation: &str,
+        policy: &str,
+    ) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let operation: SetOperation = operation
+                .parse()
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            let policy: EdgeAggregation = policy
+                .parse()
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            let inner = self
+                .inner
+                .get()
+                .set_operation(&other.inner.get(), operation, &policy);
+            Ok(Self {
+                inner: inner.into(),
+            })
+        })
+    }
+
+    /// JSON diff from this graph to `other` (e.g. two historical stages):
+    /// added / removed / reweighted edges (weights moving by more than
+    /// `tolerance`), added and removed nodes, degree changes, Louvain
+    /// communities of both graphs with their overlap matrix, and merges and
+    /// splits between them
+    #[pyo3(signature = (other, resolution=1.0, tolerance=0.0))]
+    fn diff(
+        &self,
+        py: Python<'_>,
+        other: &PyCognateGraph,
+        resolution: f64,
+        tolerance: f64,
+    ) -> String {
+        py.allow_threads(|| {
+            self.inner
+                .get()
+                .diff(&other.inner.get(), resolution, tolerance)
+                .to_json()
+        })
+    }
+
+    /// Components, Louvain communities and statistics if every edge
+    /// between `source` and `target` were removed (the graph is unchanged)
+    #[pyo3(signature = (source, target, resolution=1.0))]
+    fn what_if_remove_edge(
+        &self,
+        py: Python<'_>,
+        source: String,
+        target: String,
+        resolution: f64,
+    ) -> PyResult<PyWhatIf> {
+        self.what_if(py, GraphChange::RemoveEdge { source, target }, resolution)
+    }
+
+    /// `what_if_remove_edge` for adding an edge
+    #[pyo3(signature = (source, target, weight, resolution=1.0))]
+    fn what_if_add_edge(
+        &self,
+        py: Python<'_>,
+        source: String,
+        target: String,
+        weight: f64,
+        resolution: f64,
+    ) -> PyResult<PyWhatIf> {
+        validate_weights([(source.as_str(), target.as_str(), weight)])?;
+        let change = GraphChange::AddEdge {
+            source,
+            target,
+            weight,
+        };
+        self.what_if(py, change, resolution)
+    }
+
+    /// `what_if_remove_edge` for removing a node and its edges
+    #[pyo3(signature = (id, resolution=1.0))]
+    fn what_if_remove_node(
+        &self,
+        py: Python<'_>,
+        id: String,
+        resolution: f64,
+    ) -> PyResult<PyWhatIf> {
+        self.what_if(py, GraphChange::RemoveNode(id), resolution)
+    }
+
+    /// `what_if_remove_edge` for adding a node with (neighbor, weight)
+    /// edges to existing nodes
+    #[pyo3(signature = (id, edges, resolution=1.0))]
+    fn what_if_add_node(
+        &self,
+        py: Python<'_>,
+        id: String,
+        edges: Vec<(String, f64)>,
+        resolution: f64,
+    ) -> PyResult<PyWhatIf> {
+        validate_weights(
+            edges
+                .iter()
+                .map(|(neighbor, w)| (id.as_str(), neighbor.as_str(), *w)),
+        )?;
+        self.what_if(py, GraphChange::AddNode { id, edges }, resolution)
+    }
+
+    fn edges_by_provenance(&self, sources: Vec<String>) -> Vec<ProvenanceEdge> {
+        self.inner.get().edges_by_provenance(&sources)
+    }
+
+    fn provenance_counts(&self) -> Vec<(Option<String>, usize)> {
+        self.inner.get().provenance_counts().into_iter().collect()
+    }
+
+    #[pyo3(signature = (pairs=None, min_score=0.0))]
+    fn neighbor_jaccard(
+        &self,
+        py: Python<'_>,
+        pairs: Option<Vec<(String, String)>>,
+        min_score: f64,
+    ) -> Vec<WeightedEdge> {
+        py.allow_threads(|| {
+            let graph = self.inner.get();
+            match pairs {
+                Some(pairs) => graph.jaccard_for_pairs(&pairs),
+                None => graph.jaccard_all_pairs(min_score),
+            }
+        })
+    }
+
+    #[pyo3(signature = (method="adamic_adar", top_k=None))]
+    fn predict_links(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        top_k: Option<usize>,
+    ) -> PyResult<Vec<WeightedEdge>> {
+        py.allow_threads(|| {
+            let predictor: LinkPredictor = method
+                .parse()
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            Ok(self.inner.get().predict_links(predictor, top_k))
+        })
+    }
+
+    #[pyo3(signature = (dimensions=2, seed=0))]
+    fn spectral_embedding<'py>(
+        &self,
+        py: Python<'py>,
+        dimensions: usize,
+        seed: u64,
+    ) -> (Vec<String>, &'py PyArray2<f64>) {
+        let embedding = py.allow_threads(|| self.inner.get().spectral_embedding(dimensions, seed));
+        embedding_to_py(py, embedding)
+    }
+
+    #[pyo3(signature = (dimensions=2, iterations=100, seed=0))]
+    fn force_layout<'py>(
+        &self,
+        py: Python<'py>,
+        dimensions: usize,
+        iterations: usize,
+        seed: u64,
+    ) -> (Vec<String>, &'py PyArray2<f64>) {
+        let embedding =
+            py.allow_threads(|| self.inner.get().force_layout(dimensions, iterations, seed));
+        embedding_to_py(py, embedding)
+    }
+
+    #[pyo3(signature = (
+        dimensions=32,
+        walk_length=20,
+        walks_per_node=10,
+        p=1.0,
+        q=1.0,
+        window=5,
+        epochs=1,
+        seed=0
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn node2vec<'py>(
+        &self,
+        py: Python<'py>,
+        dimensions: usize,
+        walk_length: usize,
+        walks_per_node: usize,
+        p: f64,
+        q: f64,
+        window: usize,
+        epochs: usize,
+        seed: u64,
+    ) -> PyResult<(Vec<String>, &'py PyArray2<f64>)> {
+        let params = Node2VecParams {
+            dimensions,
+            walk_length,
+            walks_per_node,
+            p,
+            q,
+            window,
+            epochs,
+            seed,
+            ..Default::default()
+        };
+        params.validate()?;
+        let embedding = py.allow_threads(|| self.inner.get().node2vec(&params));
+        Ok(embedding_to_py(py, embedding))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.get().graph.node_count()
+    }
+
+    fn __repr__(&self) -> String {
+        let graph = self.inner.get();
+        format!(
+            "PyCognateGraph(nodes={}, edges={})",
+            graph.graph.node_count(),
+            graph.graph.edge_count()
+        )
+    }
+}
+
+/// Sparse similarity matrix handle; read-only, so Python threads can query
+/// it concurrently
+#[pyclass(frozen)]
+struct PySparseMatrix {
+    inner: SparseSimilarityMatrix,
+}
+
+/// scipy CSR arrays: (data, indices, indptr, shape)
+type ScipyCsr<'py> = (
+    &'py PyArray1<f64>,
+    &'py PyArray1<i64>,
+    &'py PyArray1<i64>,
+    (usize, usize),
+);
+
+impl PySparseMatrix {
+    /// UnknownEntryId for the first of `entry_ids` missing from the rows
+    fn check_entries<'a>(&self, entry_ids: impl IntoIterator<Item = &'a str>) -> PyResult<()> {
+        let known: std::collections::HashSet<&str> =
+            self.inner.entry_ids().iter().map(String::as_str).collect();
+        match entry_ids.into_iter().find(|id| !known.contains(id)) {
+            Some(id) => Err(LangVizError::UnknownEntryId(id.to_string()).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[pymethods]
+impl PySparseMatrix {
+    fn knn(&self, entry_id: &str, k: usize) -> PyResult<Vec<(String, f64)>> {
+        self.check_entries([entry_id])?;
+        Ok(self.inner.knn(entry_id, k))
+    }
+
+    /// k nearest neighbors for many entries (parallelized)
+    fn batch_knn(
+        &self,
+        py: Python<'_>,
+        entry_ids: Vec<String>,
+        k: usize,
+    ) -> PyResult<Vec<Vec<(String, f64)>>> {
+        self.check_entries(entry_ids.iter().map(String::as_str))?;
+        Ok(py.allow_threads(|| batch_knn(&self.inner, &entry_ids, k)))
+    }
+
+    fn neighbors_above_threshold(
+        &self,
+        entry_id: &str,
+        threshold: f64,
+    ) -> PyResult<Vec<(String, f64)>> {
+        validate_threshold(threshold)?;
+        self.check_entries([entry_id])?;
+        Ok(self.inner.neighbors_above_threshold(entry_id, threshold))
+    }
+
+    fn shape(&self) -> (usize, usize) {
+        self.inner.shape()
+    }
+
+    fn nnz(&self) -> usize {
+        self.inner.nnz()
+    }
+
+    fn sparsity(&self) -> f64 {
+        self.inner.sparsity()
+    }
+
+    fn entry_ids(&self) -> Vec<String> {
+        self.inner.entry_ids().to_vec()
+    }
+
+    /// scipy-style CSR arrays `(data, indices, indptr, shape)`; the arrays
+    /// are moved into NumPy without further copying
+    fn to_scipy<'py>(&self, py: Python<'py>) -> ScipyCsr<'py> {
+        let (indptr, indices, data) = py.allow_threads(|| self.inner.csr_parts());
+        let to_i64 =
+            |values: Vec<usize>| -> Vec<i64> { values.into_iter().map(|v| v as i64).collect() };
+        (
+            data.into_pyarray(py),
+            to_i64(indices).into_pyarray(py),
+            to_i64(indptr).into_pyarray(py),
+            self.inner.shape(),
+        )
+    }
+
+    /// Build from scipy-style CSR arrays (int64 `indices` / `indptr`);
+    /// `ids` label the rows, and the columns unless `col_ids` is given
+    #[staticmethod]
+    #[pyo3(signature = (data, indices, indptr, ids, col_ids=None))]
+    fn from_csr(
+        data: PyReadonlyArray1<f64>,
+        indices: PyReadonlyArray1<i64>,
+        indptr: PyReadonlyArray1<i64>,
+        ids: Vec<String>,
+        col_ids: Option<Vec<String>>,
+    ) -> PyResult<Self> {
+        let to_usize = |values: PyReadonlyArray1<i64>, name: &str| {
+            values
+                .as_array()
+                .iter()
+                .map(|&v| usize::try_from(v))
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|_| {
+                    pyo3::exceptions::PyValueError::new_err(format!("negative value in {}", name))
+                })
+        };
+        let indices = to_usize(indices, "indices")?;
+        let indptr = to_usize(indptr, "indptr")?;
+        let data: Vec<f64> = data.as_array().to_vec();
+        let col_ids = col_ids.unwrap_or_else(|| ids.clone());
+
+        let inner = SparseSimilarityMatrix::from_csr(ids, col_ids, &indptr, &indices, &data)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Silhouette of clusters of entry IDs; pairs absent from the matrix
+    /// count as `missing_distance` (1 - similarity)
+    #[pyo3(signature = (clusters, missing_distance=1.0))]
+    fn silhouette_score(
+        &self,
+        py: Python<'_>,
+        clusters: Vec<Vec<String>>,
+        missing_distance: f64,
+    ) -> PyResult<f64> {
+        let clusters = SparseClusters::new(&self.inner, &clusters, missing_distance)?;
+        Ok(py.allow_threads(|| clusters.silhouette()))
+    }
+
+    #[pyo3(signature = (clusters, missing_distance=1.0))]
+    fn within_cluster_variance(
+        &self,
+        py: Python<'_>,
+        clusters: Vec<Vec<String>>,
+        missing_distance: f64,
+    ) -> PyResult<f64> {
+        let clusters = SparseClusters::new(&self.inner, &clusters, missing_distance)?;
+        Ok(py.allow_threads(|| clusters.within_cluster_variance()))
+    }
+
+    #[pyo3(signature = (clusters, missing_distance=1.0))]
+    fn davies_bouldin_index(
+        &self,
+        py: Python<'_>,
+        clusters: Vec<Vec<String>>,
+        missing_distance: f64,
+    ) -> PyResult<f64> {
+        let clusters = SparseClusters::new(&self.inner, &clusters, missing_distance)?;
+        Ok(py.allow_threads(|| clusters.davies_bouldin()))
+    }
+
+    #[pyo3(signature = (clusters, missing_distance=1.0))]
+    fn within_between_ratio(
+        &self,
+        py: Python<'_>,
+        clusters: Vec<Vec<String>>,
+        missing_distance: f64,
+    ) -> PyResult<f64> {
+        let clusters = SparseClusters::new(&self.inner, &clusters, missing_distance)?;
+        Ok(py.allow_threads(|| clusters.within_between_ratio()))
+    }
+
+    /// All quality indices of one clustering in one call
+    #[pyo3(signature = (clusters, missing_distance=1.0))]
+    fn clustering_scores(
+        &self,
+        py: Python<'_>,
+        clusters: Vec<Vec<String>>,
+        missing_distance: f64,
+    ) -> PyResult<PyClusteringScores> {
+        let clusters = SparseClusters::new(&self.inner, &clusters, missing_distance)?;
+        Ok(py.allow_threads(|| clusters.scores()).into())
+    }
+
+    /// Save as a compact binary CSR file (IDs included)
+    fn save(&self, py: Python<'_>, path: &str) -> PyResult<()> {
+        py.allow_threads(|| self.inner.save(std::path::Path::new(path)))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    #[staticmethod]
+    fn load(py: Python<'_>, path: &str) -> PyResult<Self> {
+        let inner = py
+            .allow_threads(|| SparseSimilarityMatrix::load(std::path::Path::new(path)))
+            .map_err(pyo3::exceptions::PyIOError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Save for `PyMappedSparseMatrix.open`: the CSR arrays at `path`, the
+    /// IDs in a `path + ".ids"` sidecar
+    fn save_mapped(&self, py: Python<'_>, path: &str) -> PyResult<()> {
+        py.allow_threads(|| self.inner.save_mapped(std::path::Path::new(path)))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+}
+
+/// Read-only sparse similarity matrix served from memory-mapped files, for
+/// matrices too large to hold in RAM
+#[pyclass]
+struct PyMappedSparseMatrix {
+    inner: MappedSimilarityMatrix,
+}
+
+impl PyMappedSparseMatrix {
+    /// UnknownEntryId for the first of `entry_ids` missing from the rows
+    fn check_entries<'a>(&self, entry_ids: impl IntoIterator<Item = &'a str>) -> PyResult<()> {
+        match entry_ids
+            .into_iter()
+            .find(|id| self.inner.index_of(id).is_none())
+        {
+            Some(id) => Err(LangVizError::UnknownEntryId(id.to_string()).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[pymethods]
+impl PyMappedSparseMatrix {
+    /// Map a matrix written by `PySparseMatrix.save_mapped`
+    #[staticmethod]
+    fn open(py: Python<'_>, path: &str) -> PyResult<Self> {
+        let inner = py
+            .allow_threads(|| MappedSimilarityMatrix::open(std::path::Path::new(path)))
+            .map_err(pyo3::exceptions::PyIOError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Convert a `PySparseMatrix.save` file to the mapped layout at `path`
+    /// and map it, streaming the arrays instead of loading them
+    #[staticmethod]
+    fn from_saved(py: Python<'_>, saved: &str, path: &str) -> PyResult<Self> {
+        let inner = py
+            .allow_threads(|| {
+                MappedSimilarityMatrix::from_saved(
+                    std::path::Path::new(saved),
+                    std::path::Path::new(path),
+                )
+            })
+            .map_err(pyo3::exceptions::PyIOError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    fn knn(&self, entry_id: &str, k: usize) -> PyResult<Vec<(String, f64)>> {
+        self.check_entries([entry_id])?;
+        Ok(self.inner.knn(entry_id, k))
+    }
+
+    /// k nearest neighbors for many entries (parallelized)
+    fn batch_knn(
+        &self,
+        py: Python<'_>,
+        entry_ids: Vec<String>,
+        k: usize,
+    ) -> PyResult<Vec<Vec<(String, f64)>>> {
+        self.check_entries(entry_ids.iter().map(String::as_str))?;
+        Ok(py.allow_threads(|| self.inner.batch_knn(&entry_ids, k)))
+    }
+
+    fn neighbors_above_threshold(
+        &self,
+        entry_id: &str,
+        threshold: f64,
+    ) -> PyResult<Vec<(String, f64)>> {
+        validate_threshold(threshold)?;
+        self.check_entries([entry_id])?;
+        Ok(self.inner.neighbors_above_threshold(entry_id, threshold))
+    }
+
+    /// Matrix-vector product with one value per column
+    fn matvec<'py>(
+        &self,
+        py: Python<'py>,
+        vec: PyReadonlyArray1<f64>,
+    ) -> PyResult<&'py PyArray1<f64>> {
+        let vec = vec.as_array().to_owned();
+        let cols = self.inner.shape().1;
+        if vec.len() != cols {
+            return Err(LangVizError::DimensionMismatch {
+                what: "vec".to_string(),
+                expected: format!("({},)", cols),
+                actual: format!("({},)", vec.len()),
+            }
+            .into());
+        }
+        let product = py.allow_threads(|| self.inner.matvec(&vec));
+        Ok(product.into_pyarray(py))
+    }
+
+    /// Stored value at (row, col), None for an absent entry
+    fn get(&self, row: usize, col: usize) -> PyResult<Option<f64>> {
+        let (rows, cols) = self.inner.shape();
+        if row >= rows || col >= cols {
+            return Err(pyo3::exceptions::PyIndexError::new_err(format!(
+                "({}, {}) is out of bounds for shape ({}, {})",
+                row, col, rows, cols
+            )));
+        }
+        Ok(self.inner.get(row, col))
+    }
+
+    fn shape(&self) -> (usize, usize) {
+        self.inner.shape()
+    }
+
+    fn nnz(&self) -> usize {
+        self.inner.nnz()
+    }
+
+    fn sparsity(&self) -> f64 {
+        self.inner.sparsity()
+    }
+
+    fn __repr__(&self) -> String {
+        let (rows, cols) = self.inner.shape();
+        format!(
+            "PyMappedSparseMatrix(shape=({}, {}), nnz={})",
+            rows,
+            cols,
+            self.inner.nnz()
+        )
+    }
+}
+
+/// Correspondence patterns found across cognate sets
+#[pyclass]
+struct PyCorrespondencePatterns {
+    inner: PatternAnalysis,
+}
+
+#[pymethods]
+impl PyCorrespondencePatterns {
+    /// Languages indexing every site and pattern
+    #[getter]
+    fn languages(&self) -> Vec<String> {
+        self.inner.languages.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.patterns.len()
+    }
+
+    /// (consensus reflex per language, (set, site) members) per pattern;
+    /// reflexes are None where unattested and "-" for gaps
+    fn patterns(&self) -> Vec<(Site, Vec<(usize, usize)>)> {
+        self.inner
+            .patterns
+            .iter()
+            .map(|p| (p.reflexes.clone(), p.sites.clone()))
+            .collect()
+    }
+
+    /// Alignment sites per cognate set (reflex per language)
+    fn sites(&self) -> Vec<Vec<Site>> {
+        self.inner.sites.clone()
+    }
+
+    /// Pattern index of every site, per cognate set
+    fn site_patterns(&self) -> Vec<Vec<usize>> {
+        self.inner.site_patterns.clone()
+    }
+
+    /// Predicted (set index, form) for sets lacking `language`
+    fn impute(&self, language: &str) -> Vec<(usize, String)> {
+        self.inner.impute(language)
+    }
+
+    /// Expected `language` reflex of a cognate set of (language, ipa)
+    /// members, with confidence; None for a language never seen
+    fn predict_reflex(
+        &self,
+        members: Vec<(String, String)>,
+        language: &str,
+    ) -> Option<PyReflexPrediction> {
+        self.inner
+            .predict_reflex(&members, language)
+            .map(PyReflexPrediction::from)
+    }
+
+    /// (set index, prediction) for every analyzed set lacking `language`
+    fn predict_missing(
+        &self,
+        py: Python<'_>,
+        language: &str,
+    ) -> Vec<(usize, PyReflexPrediction)> {
+        py.allow_threads(|| {
+            self.inner
+                .predict_missing(language)
+                .into_iter()
+                .map(|(set, prediction)| (set, PyReflexPrediction::from(prediction)))
+                .collect()
+        })
+    }
+}
+
+/// Boundary segment between two neighboring languages
+#[pyclass]
+#[derive(Clone)]
+struct PyIsoglossSegment {
+    #[pyo3(get)]
+    language_a: String,
+    #[pyo3(get)]
+    language_b: String,
+    #[pyo3(get)]
+    distance: f64,
+    /// Z-score among all neighbor pairs
+    #[pyo3(get)]
+    score: f64,
+    /// Endpoints as (latitude, longitude)
+    #[pyo3(get)]
+    start: (f64, f64),
+    #[pyo3(get)]
+    end: (f64, f64),
+}
+
+impl From<IsoglossSegment> for PyIsoglossSegment {
+    fn from(segment: IsoglossSegment) -> Self {
+        Self {
+            language_a: segment.language_a,
+            language_b: segment.language_b,
+            distance: segment.distance,
+            score: segment.score,
+            start: segment.start,
+            end: segment.end,
+        }
+    }
+}
+
+/// (source, target, weight) edges loaded in Rust, accepted wherever an
+/// edge list is expected without converting rows to Python objects
+#[pyclass]
+struct PyEdgeList {
+    inner: Vec<WeightedEdge>,
+}
+
+#[pymethods]
+impl PyEdgeList {
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// First `n` edges as tuples
+    #[pyo3(signature = (n=5))]
+    fn head(&self, n: usize) -> Vec<WeightedEdge> {
+        self.inner.iter().take(n).cloned().collect()
+    }
+
+    /// All edges as a list of tuples
+    fn to_list(&self) -> Vec<WeightedEdge> {
+        self.inner.clone()
+    }
+}
+
+/// Internal quality indices of one candidate clustering
+#[pyclass]
+#[derive(Clone)]
+struct PyClusteringScores {
+    #[pyo3(get)]
+    silhouette: f64,
+    #[pyo3(get)]
+    within_cluster_variance: f64,
+    #[pyo3(get)]
+    davies_bouldin: f64,
+    #[pyo3(get)]
+    within_between_ratio: f64,
+    #[pyo3(get)]
+    num_clusters: usize,
+    #[pyo3(get)]
+    num_singletons: usize,
+}
+
+impl From<ClusteringScores> for PyClusteringScores {
+    fn from(scores: ClusteringScores) -> Self {
+        Self {
+            silhouette: scores.silhouette,
+            within_cluster_variance: scores.within_cluster_variance,
+            davies_bouldin: scores.davies_bouldin,
+            within_between_ratio: scores.within_between_ratio,
+            num_clusters: scores.num_clusters,
+            num_singletons: scores.num_singletons,
+        }
+    }
+}
+
+/// Clustering agreement with the clean run at one noise scale
+#[pyclass]
+#[derive(Clone)]
+struct PyRobustnessPoint {
+    #[pyo3(get)]
+    scale: f64,
+    /// Fraction of forms the noise changed
+    #[pyo3(get)]
+    changed_forms: f64,
+    #[pyo3(get)]
+    precision: f64,
+    #[pyo3(get)]
+    recall: f64,
+    #[pyo3(get)]
+    f1: f64,
+    #[pyo3(get)]
+    num_clusters: f64,
+}
+
+impl From<RobustnessPoint> for PyRobustnessPoint {
+    fn from(point: RobustnessPoint) -> Self {
+        Self {
+            scale: point.scale,
+            changed_forms: point.changed_forms,
+            precision: point.precision,
+            recall: point.recall,
+            f1: point.f1,
+            num_clusters: point.num_clusters,
+        }
+    }
+}
+
+/// Observed graph statistics against their null-model distributions
+#[pyclass]
+#[derive(Clone)]
+struct PyNullModelStats {
+    #[pyo3(get)]
+    observed_modularity: f64,
+    #[pyo3(get)]
+    observed_clustering: f64,
+    /// Louvain modularity of each random graph
+    #[pyo3(get)]
+    modularity: Vec<f64>,
+    /// Average clustering coefficient of each random graph
+    #[pyo3(get)]
+    clustering: Vec<f64>,
+    /// One-sided empirical p-values: (1 + samples >= observed) / (1 + samples)
+    #[pyo3(get)]
+    modularity_p_value: f64,
+    #[pyo3(get)]
+    clustering_p_value: f64,
+}
+
+impl From<NullModelStats> for PyNullModelStats {
+    fn from(stats: NullModelStats) -> Self {
+        Self {
+            modularity_p_value: stats.modularity_p_value(),
+            clustering_p_value: stats.clustering_p_value(),
+            observed_modularity: stats.observed_modularity,
+            observed_clustering: stats.observed_clustering,
+            modularity: stats.modularity,
+            clustering: stats.clustering,
+        }
+    }
+}
+
+/// Generated wordlist with its ground truth
+#[pyclass]
+struct PySyntheticDataset {
+    inner: SyntheticDataset,
+}
+
+#[pymethods]
+impl PySyntheticDataset {
+    /// (id, language, concept, ipa, cognate_class, borrowed) per entry
+    fn entries(&self) -> Vec<(String, String, String, String, String, bool)> {
+        self.inner
+            .entries
+            .iter()
+            .map(|e| {
+                (
+                    e.id.clone(),
+                    e.language.clone(),
+                    e.concept.clone(),
+                    e.ipa.clone(),
+                    e.cognate_class.clone(),
+                    e.borrowed,
+                )
+            })
+            .collect()
+    }
+
+    /// Proto-form per concept
+    #[getter]
+    fn proto_forms(&self) -> Vec<String> {
+        self.inner.proto_forms.clone()
+    }
+
+    /// (language, [(segment, replacement), ...]) in application order
+    #[getter]
+    fn language_changes(&self) -> Vec<(String, Vec<(String, String)>)> {
+        self.inner.language_changes.clone()
+    }
+
+    /// Entry ID -> true cognate class
+    fn gold_classes(&self) -> BTreeMap<String, String> {
+        self.inner
+            .entries
+            .iter()
+            .map(|e| (e.id.clone(), e.cognate_class.clone()))
+            .collect()
+    }
+
+    /// Phonetic similarity edges between entries sharing a concept
+    fn similarity_edges(&self, py: Python<'_>) -> Vec<(String, String, f64)> {
+        py.allow_threads(|| self.inner.similarity_edges())
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.entries.len()
+    }
+}
+
+/// Per-language-pair background score calibration
+#[pyclass]
+struct PyPairThresholds {
+    inner: PairThresholds,
+}
+
+#[pymethods]
+impl PyPairThresholds {
+    /// Threshold for a language pair (either order); pairs without
+    /// background scores use the pooled threshold
+    fn threshold(&self, lang_a: &str, lang_b: &str) -> f64 {
+        self.inner.get(Some(lang_a), Some(lang_b)).threshold
+    }
+
+    /// (lang_a, lang_b, mean, std_dev, threshold, count) per fitted pair
+    fn pairs(&self) -> Vec<(String, String, f64, f64, f64, usize)> {
+        self.inner
+            .pairs
+            .iter()
+            .map(|((a, b), c)| (a.clone(), b.clone(), c.mean, c.std_dev, c.threshold, c.count))
+            .collect()
+    }
+
+    #[getter]
+    fn global_threshold(&self) -> f64 {
+        self.inner.global.threshold
+    }
+}
+
+/// Fitted map from raw similarity scores to cognate probabilities
+#[pyclass]
+struct PyProbabilityCalibration {
+    inner: ProbabilityCalibration,
+}
+
+#[pymethods]
+impl PyProbabilityCalibration {
+    /// "platt" or "isotonic"
+    #[getter]
+    fn method(&self) -> &'static str {
+        match self.inner {
+            ProbabilityCalibration::Platt { .. } => "platt",
+            ProbabilityCalibration::Isotonic { .. } => "isotonic",
+        }
+    }
+
+    fn probability(&self, score: f64) -> f64 {
+        self.inner.probability(score)
+    }
+
+    fn probabilities(&self, py: Python<'_>, scores: Vec<f64>) -> Vec<f64> {
+        py.allow_threads(|| scores.iter().map(|&s| self.inner.probability(s)).collect())
+    }
+
+    /// Lowest score with P(cognate) >= `probability` (-inf if every score
+    /// qualifies), or None if no score reaches it
+    fn threshold(&self, probability: f64) -> PyResult<Option<f64>> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(LangVizError::InvalidParameter {
+                name: "probability".to_string(),
+                value: probability,
+                expected: "a value in [0, 1]".to_string(),
+            }
+            .into());
+        }
+        Ok(self.inner.threshold(probability))
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.inner {
+            ProbabilityCalibration::Platt { slope, intercept } => format!(
+                "PyProbabilityCalibration(platt, slope={}, intercept={})",
+                slope, intercept
+            ),
+            ProbabilityCalibration::Isotonic { scores, .. } => format!(
+                "PyProbabilityCalibration(isotonic, breakpoints={})",
+                scores.len()
+            ),
+        }
+    }
+}
+
+/// ROC and precision–recall curves of one set of scores
+#[pyclass]
+struct PyScoreCurves {
+    inner: ScoreCurves,
+}
+
+#[pymethods]
+impl PyScoreCurves {
+    /// (false positive rate, true positive rate, threshold), strictest
+    /// threshold first
+    #[getter]
+    fn roc(&self) -> Vec<(f64, f64, f64)> {
+        self.inner.roc.clone()
+    }
+
+    /// (recall, precision, threshold), strictest threshold first
+    #[getter]
+    fn pr(&self) -> Vec<(f64, f64, f64)> {
+        self.inner.pr.clone()
+    }
+
+    #[getter]
+    fn roc_auc(&self) -> f64 {
+        self.inner.roc_auc
+    }
+
+    #[getter]
+    fn average_precision(&self) -> f64 {
+        self.inner.average_precision
+    }
+
+    #[getter]
+    fn positives(&self) -> usize {
+        self.inner.positives
+    }
+
+    #[getter]
+    fn negatives(&self) -> usize {
+        self.inner.negatives
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyScoreCurves(roc_auc={:.4}, average_precision={:.4})",
+            self.inner.roc_auc, self.inner.average_precision
+        )
+    }
+}
+
+/// Inter-annotator agreement on cognate partitions
+#[pyclass(frozen)]
+struct PyAgreement {
+    inner: Agreement,
+}
+
+#[pymethods]
+impl PyAgreement {
+    #[getter]
+    fn annotators(&self) -> Vec<String> {
+        self.inner.annotators.clone()
+    }
+
+    #[getter]
+    fn items(&self) -> usize {
+        self.inner.items
+    }
+
+    #[getter]
+    fn pairs(&self) -> u64 {
+        self.inner.pairs
+    }
+
+    /// Cohen's kappa matrix, rows and columns in `annotators` order
+    #[getter]
+    fn cohen(&self) -> Vec<Vec<f64>> {
+        self.inner.cohen.clone()
+    }
+
+    #[getter]
+    fn fleiss(&self) -> f64 {
+        self.inner.fleiss
+    }
+
+    /// Mean fraction of annotator pairs agreeing on an item pair
+    #[getter]
+    fn observed(&self) -> f64 {
+        self.inner.observed
+    }
+
+    /// Cohen's kappa of two named annotators
+    fn cohen_kappa(&self, a: &str, b: &str) -> PyResult<f64> {
+        let position = |name: &str| {
+            self.inner
+                .annotators
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(format!("no annotator '{}'", name))
+                })
+        };
+        Ok(self.inner.cohen[position(a)?][position(b)?])
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyAgreement(annotators={}, items={}, fleiss={:.4})",
+            self.inner.annotators.len(),
+            self.inner.items,
+            self.inner.fleiss
+        )
+    }
+}
+
+/// An unlabeled pair suggested for labeling
+#[pyclass(frozen)]
+struct PyPairSuggestion {
+    inner: PairSuggestion,
+}
+
+#[pymethods]
+impl PyPairSuggestion {
+    #[getter]
+    fn source(&self) -> String {
+        self.inner.source.clone()
+    }
+
+    #[getter]
+    fn target(&self) -> String {
+        self.inner.target.clone()
+    }
+
+    #[getter]
+    fn score(&self) -> f64 {
+        self.inner.score
+    }
+
+    /// 1 at the threshold, 0 at the far end of the score range
+    #[getter]
+    fn uncertainty(&self) -> f64 {
+        self.inner.uncertainty
+    }
+
+    /// Item pairs whose co-membership the pair's label would flip
+    #[getter]
+    fn changed_pairs(&self) -> u64 {
+        self.inner.changed_pairs
+    }
+
+    #[getter]
+    fn informativeness(&self) -> f64 {
+        self.inner.informativeness
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyPairSuggestion({} - {}, score={:.3}, changed_pairs={})",
+            self.inner.source, self.inner.target, self.inner.score, self.inner.changed_pairs
+        )
+    }
+}
+
+/// BK-tree over segment edit distance for "forms within distance d of X"
+/// queries without all-pairs comparison; read-only like `PySparseMatrix`
+#[pyclass(frozen)]
+struct PyPhoneticIndex {
+    inner: PhoneticIndex,
+}
+
+#[pymethods]
+impl PyPhoneticIndex {
+    #[new]
+    fn new(py: Python<'_>, ipa_strings: Vec<String>) -> Self {
+        py.allow_threads(|| Self {
+            inner: PhoneticIndex::build(ipa_strings),
+        })
+    }
+
+    /// (index, distance) of indexed forms within `max_dist` segment edits,
+    /// closest first
+    fn query(&self, py: Python<'_>, ipa: &str, max_dist: usize) -> Vec<(usize, usize)> {
+        py.allow_threads(|| self.inner.query(ipa, max_dist))
+    }
+
+    /// (i, j, distance) for every pair within `max_dist` edits, i < j
+    fn all_pairs_within(&self, py: Python<'_>, max_dist: usize) -> Vec<(usize, usize, usize)> {
+        py.allow_threads(|| self.inner.all_pairs_within(max_dist))
+    }
+
+    #[getter]
+    fn ipa_strings(&self) -> Vec<String> {
+        self.inner.ipa_strings().to_vec()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.inner.is_empty()
+    }
+}
+
+/// Proto-forms derived into each language by its sound laws or learned
+/// correspondences, for "which proto-form does this word come from"
+/// lookups; read-only like `PyPhoneticIndex`
+#[pyclass(frozen)]
+struct PyEtymologyIndex {
+    inner: EtymologyIndex,
+}
+
+#[pymethods]
+impl PyEtymologyIndex {
+    /// `proto_forms` are (id, form); `sound_laws` maps a language to its
+    /// ordered rules and `correspondences` maps a language to a
+    /// {(proto_segment, reflex_segment): cost} table (see
+    /// `py_phonetic_distance_with_costs`)
+    #[new]
+    #[pyo3(signature = (proto_forms, sound_laws=None, correspondences=None, indel_cost=1.0))]
+    fn new(
+        py: Python<'_>,
+        proto_forms: Vec<(String, String)>,
+        sound_laws: Option<HashMap<String, Vec<String>>>,
+        correspondences: Option<HashMap<String, CostTable>>,
+        indel_cost: f64,
+    ) -> PyResult<Self> {
+        let mut languages = std::collections::BTreeMap::new();
+        for (language, rules) in sound_laws.unwrap_or_default() {
+            languages.insert(language, LanguageModel::Rules(sound_change_rules(&rules)?));
+        }
+        for (language, costs) in correspondences.unwrap_or_default() {
+            if languages.contains_key(&language) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "'{}' has both sound laws and correspondences",
+                    language
+                )));
+            }
+            let model = LanguageModel::Correspondences(cost_model(&costs, indel_cost)?);
+            languages.insert(language, model);
+        }
+        py.allow_threads(|| {
+            let inner = EtymologyIndex::new(proto_forms, languages)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            Ok(Self { inner })
+        })
+    }
+
+    /// The `top_k` best proto-forms for `word`, as a reflex in `language`
+    /// (every modeled language when None), best first
+    #[pyo3(signature = (word, language=None, top_k=5))]
+    fn lookup(
+        &self,
+        py: Python<'_>,
+        word: &str,
+        language: Option<&str>,
+        top_k: usize,
+    ) -> PyResult<Vec<PyProtoMatch>> {
+        py.allow_threads(|| {
+            let matches = self
+                .inner
+                .lookup(word, language, top_k)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            Ok(matches.into_iter().map(PyProtoMatch::from).collect())
+        })
+    }
+
+    /// `lookup` for many (word, language or None) queries, in parallel
+    #[pyo3(signature = (queries, top_k=1))]
+    fn batch_lookup(
+        &self,
+        py: Python<'_>,
+        queries: Vec<(String, Option<String>)>,
+        top_k: usize,
+    ) -> PyResult<Vec<Vec<PyProtoMatch>>> {
+        py.allow_threads(|| {
+            let results = self
+                .inner
+                .batch_lookup(&queries, top_k)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            Ok(results
+                .into_iter()
+                .map(|matches| matches.into_iter().map(PyProtoMatch::from).collect())
+                .collect())
+        })
+    }
+
+    #[getter]
+    fn languages(&self) -> Vec<String> {
+        self.inner
+            .languages()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.inner.is_empty()
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+struct PyProtoMatch {
+    #[pyo3(get)]
+    proto_id: String,
+    #[pyo3(get)]
+    proto_form: String,
+    #[pyo3(get)]
+    language: String,
+    /// Reflex predicted by the sound laws (None under correspondences)
+    #[pyo3(get)]
+    reflex: Option<String>,
+    #[pyo3(get)]
+    score: f64,
+    /// Proto-form to reflex (sound laws) or to the word (correspondences,
+    /// with "a > b" steps)
+    #[pyo3(get)]
+    derivation: PyDerivation,
+}
+
+impl From<ProtoMatch> for PyProtoMatch {
+    fn from(found: ProtoMatch) -> Self {
+        Self {
+            proto_id: found.proto_id,
+            proto_form: found.proto_form,
+            language: found.language,
+            reflex: found.reflex,
+            score: found.score,
+            derivation: found.derivation.into(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyProtoMatch {
+    fn __repr__(&self) -> String {
+        format!(
+            "PyProtoMatch(proto_id={:?}, language={:?}, score={:.4})",
+            self.proto_id, self.language, self.score
+        )
+    }
+}
+
+/// Value computed by a background job, turned into Python objects when
+/// first retrieved
+enum JobOutput {
+    Matrix(ndarray::Array2<f64>),
+    Sparse(SparseSimilarityMatrix),
+    Pipeline(Box<CognateGraph>, Vec<CognateSet>),
+}
+
+impl JobOutput {
+    fn into_object(self, py: Python<'_>) -> PyObject {
+        match self {
+            JobOutput::Matrix(matrix) => matrix.into_pyarray(py).into_py(py),
+            JobOutput::Sparse(inner) => PySparseMatrix { inner }.into_py(py),
+            JobOutput::Pipeline(graph, sets) => {
+                let graph = PyCognateGraph {
+                    inner: (*graph).into(),
+                };
+                let sets: Vec<PyCognateSet> = sets.into_iter().map(PyCognateSet::from).collect();
+                (graph, sets).into_py(py)
+            }
+        }
+    }
+}
+
+/// Handle on a computation running on the background pool: poll `status()`
+/// and `progress()`, block in `wait()` / `result()` with the GIL released
+/// (e.g. from `asyncio.to_thread`), or `cancel()` it
+#[pyclass(frozen)]
+struct PyJob {
+    job: Job<JobOutput>,
+    result: GILOnceCell<PyObject>,
+}
+
+impl PyJob {
+    fn spawn<F>(work: F) -> Self
+    where
+        F: FnOnce(&JobProgress) -> Result<JobOutput, JobError> + Send + 'static,
+    {
+        Self {
+            job: Job::spawn(work),
+            result: GILOnceCell::new(),
+        }
+    }
+}
+
+/// Non-negative, finite `value` seconds as a Duration
+fn seconds(name: &str, value: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(value).map_err(|_| {
+        LangVizError::InvalidParameter {
+            name: name.to_string(),
+            value,
+            expected: "a finite number of seconds >= 0".to_string(),
+        }
+        .into()
+    })
+}
+
+#[pymethods]
+impl PyJob {
+    /// "pending", "running", "done", "failed" or "cancelled"
+    fn status(&self) -> &'static str {
+        self.job.status().as_str()
+    }
+
+    /// True once the job has finished, whatever the outcome
+    fn done(&self) -> bool {
+        self.job.status().is_finished()
+    }
+
+    /// (done, total) units of work; pairs for the similarity stages
+    fn progress(&self) -> (usize, usize) {
+        self.job.progress()
+    }
+
+    /// Stop at the next block boundary (a job still pending never starts)
+    fn cancel(&self) {
+        self.job.cancel();
+    }
+
+    /// Block until the job finishes or `timeout` seconds pass; True if it
+    /// finished
+    #[pyo3(signature = (timeout=None))]
+    fn wait(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<bool> {
+        let timeout = timeout.map(|t| seconds("timeout", t)).transpose()?;
+        Ok(py.allow_threads(|| self.job.wait(timeout)))
+    }
+
+    /// The job's result, waiting as in `wait`; raises TimeoutError if it is
+    /// still running and RuntimeError if it failed or was cancelled
+    #[pyo3(signature = (timeout=None))]
+    fn result(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<PyObject> {
+        if !self.wait(py, timeout)? {
+            return Err(pyo3::exceptions::PyTimeoutError::new_err(format!(
+                "job still {} after {} s",
+                self.job.status(),
+                timeout.unwrap_or_default()
+            )));
+        }
+        let result = self.result.get_or_try_init(py, || match self.job.take() {
+            Some(Ok(output)) => Ok(output.into_object(py)),
+            Some(Err(error)) => Err(pyo3::exceptions::PyRuntimeError::new_err(error.to_string())),
+            // Taken and cached under the GIL, so only reachable on a bug
+            None => Err(pyo3::exceptions::PyRuntimeError::new_err(
+                "job result already taken",
+            )),
+        })?;
+        Ok(result.clone_ref(py))
+    }
+
+    fn __repr__(&self) -> String {
+        let (done, total) = self.job.progress();
+        format!(
+            "PyJob(status={}, progress={}/{})",
+            self.job.status(),
+            done,
+            total
+        )
+    }
+}
+
+/// Where a job saves its progress, if anywhere
+fn checkpoint_policy(
+    checkpoint: Option<&str>,
+    interval: f64,
+) -> PyResult<Option<CheckpointPolicy>> {
+    let Some(path) = checkpoint else {
+        return Ok(None);
+    };
+    Ok(Some(CheckpointPolicy {
+        path: path.into(),
+        interval: seconds("checkpoint_interval", interval)?,
+    }))
+}
+
+/// Finish `run` from wherever it stopped as a background job
+fn spawn_run(mut run: AllPairsRun, checkpoint: Option<CheckpointPolicy>) -> PyJob {
+    PyJob::spawn(move |progress| {
+        run.run(checkpoint.as_ref(), progress)?;
+        Ok(match run.task {
+            RunTask::Dense => JobOutput::Matrix(run.to_matrix()),
+            RunTask::Sparse => JobOutput::Sparse(run.to_sparse()?),
+            RunTask::Pipeline => {
+                let graph = run.to_graph();
+                let sets = graph.find_cognate_sets();
+                JobOutput::Pipeline(Box::new(graph), sets)
+            }
+        })
+    })
+}
+
+/// `py_compute_similarity_matrix` as a background job, computed
+/// `block_size` rows at a time; `result()` is the numpy matrix. With a
+/// `checkpoint` path, finished rows are saved there every
+/// `checkpoint_interval` seconds for `py_resume_job`
+#[pyfunction]
+#[pyo3(signature = (
+    ipa_strings,
+    metric="levenshtein",
+    block_size=1024,
+    checkpoint=None,
+    checkpoint_interval=60.0
+))]
+fn py_submit_similarity_matrix(
+    ipa_strings: Vec<String>,
+    metric: &str,
+    block_size: usize,
+    checkpoint: Option<&str>,
+    checkpoint_interval: f64,
+) -> PyResult<PyJob> {
+    if let Some(policy) = checkpoint_policy(checkpoint, checkpoint_interval)? {
+        // Finished rows are kept as pairs so they can be saved
+        let run = AllPairsRun::new(
+            RunTask::Dense,
+            Vec::new(),
+            ipa_strings,
+            None,
+            metric,
+            block_size,
+            true,
+        )
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        return Ok(spawn_run(run, Some(policy)));
+    }
+    let metric = similarity_metric(metric)?;
+    Ok(PyJob::spawn(move |progress| {
+        let mut matrix = ndarray::Array2::<f64>::eye(ipa_strings.len());
+        let blocks =
+            similarity_blocks(&ipa_strings, block_size, f64::NEG_INFINITY).with_metric(metric);
+        run_blocks(blocks, progress, |block| {
+            for (i, j, sim) in block.pairs {
+                matrix[[i, j]] = sim;
+                matrix[[j, i]] = sim;
+            }
+        })?;
+        Ok(JobOutput::Matrix(matrix))
+    }))
+}
+
+/// Sparse or pipeline run of the pairs at or above `threshold`
+#[allow(clippy::too_many_arguments)]
+fn submit_thresholded(
+    task: RunTask,
+    ids: Vec<String>,
+    ipa_strings: Vec<String>,
+    threshold: f64,
+    metric: &str,
+    block_size: usize,
+    length_filter: bool,
+    checkpoint: Option<&str>,
+    checkpoint_interval: f64,
+) -> PyResult<PyJob> {
+    validate_threshold(threshold)?;
+    let policy = checkpoint_policy(checkpoint, checkpoint_interval)?;
+    let run = AllPairsRun::new(
+        task,
+        ids,
+        ipa_strings,
+        Some(threshold),
+        metric,
+        block_size,
+        length_filter,
+    )
+    .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(spawn_run(run, policy))
+}
+
+/// `py_similarity_matrix_sparse` as a background job; `result()` is the
+/// sparse matrix handle. Checkpoints as in `py_submit_similarity_matrix`
+#[pyfunction]
+#[pyo3(signature = (
+    ids,
+    ipa_strings,
+    threshold,
+    metric="levenshtein",
+    block_size=1024,
+    length_filter=true,
+    checkpoint=None,
+    checkpoint_interval=60.0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_submit_similarity_matrix_sparse(
+    ids: Vec<String>,
+    ipa_strings: Vec<String>,
+    threshold: f64,
+    metric: &str,
+    block_size: usize,
+    length_filter: bool,
+    checkpoint: Option<&str>,
+    checkpoint_interval: f64,
+) -> PyResult<PyJob> {
+    submit_thresholded(
+        RunTask::Sparse,
+        ids,
+        ipa_strings,
+        threshold,
+        metric,
+        block_size,
+        length_filter,
+        checkpoint,
+        checkpoint_interval,
+    )
+}
+
+/// Forms-to-cognate-sets pipeline as a background job: similarity of all
+/// pairs (progress counts them), the graph of pairs at or above
+/// `threshold`, and its cognate sets. `result()` is (graph handle, cognate
+/// sets); every ID is a node, so unmatched forms come out as singletons.
+/// Checkpoints as in `py_submit_similarity_matrix`
+#[pyfunction]
+#[pyo3(signature = (
+    ids,
+    ipa_strings,
+    threshold,
+    metric="levenshtein",
+    block_size=1024,
+    length_filter=true,
+    checkpoint=None,
+    checkpoint_interval=60.0
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_submit_pipeline(
+    ids: Vec<String>,
+    ipa_strings: Vec<String>,
+    threshold: f64,
+    metric: &str,
+    block_size: usize,
+    length_filter: bool,
+    checkpoint: Option<&str>,
+    checkpoint_interval: f64,
+) -> PyResult<PyJob> {
+    submit_thresholded(
+        RunTask::Pipeline,
+        ids,
+        ipa_strings,
+        threshold,
+        metric,
+        block_size,
+        length_filter,
+        checkpoint,
+        checkpoint_interval,
+    )
+}
+
+/// Resume the job that saved `checkpoint` (after a crash or `cancel()`),
+/// computing only the rows it had not finished and checkpointing to the
+/// same file; `result()` is what the original job would have returned
+#[pyfunction]
+#[pyo3(signature = (checkpoint, checkpoint_interval=60.0))]
+fn py_resume_job(py: Python<'_>, checkpoint: &str, checkpoint_interval: f64) -> PyResult<PyJob> {
+    let policy = checkpoint_policy(Some(checkpoint), checkpoint_interval)?;
+    let run = py
+        .allow_threads(|| AllPairsRun::load(std::path::Path::new(checkpoint)))
+        .map_err(pyo3::exceptions::PyIOError::new_err)?;
+    Ok(spawn_run(run, policy))
+}
+
+#[pyclass]
+struct PyDendrogram {
+    inner: Dendrogram,
+}
+
+#[pymethods]
+impl PyDendrogram {
+    /// Flat clusters after cutting into (at most) k clusters
+    fn cut(&self, k: usize) -> Vec<Vec<String>> {
+        self.inner.cut(k)
+    }
+
+    /// Flat clusters from merges at distance <= height (1 - similarity)
+    fn cut_height(&self, height: f64) -> Vec<Vec<String>> {
+        self.inner.cut_height(height)
+    }
+
+    /// (left, right, distance, size) per merge, as in a scipy linkage matrix
+    fn merges(&self) -> Vec<(usize, usize, f64, usize)> {
+        self.inner
+            .merges
+            .iter()
+            .map(|m| (m.left, m.right, m.distance, m.size))
+            .collect()
+    }
+
+    #[getter]
+    fn ids(&self) -> Vec<String> {
+        self.inner.ids.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.ids.len()
+    }
+}
+
+/// Rooted tree with branch lengths (UPGMA, Neighbor-Joining or Newick)
+#[pyclass]
+struct PyTree {
+    inner: Tree,
+}
+
+#[pymethods]
+impl PyTree {
+    #[staticmethod]
+    fn from_newick(newick: &str) -> PyResult<Self> {
+        Tree::from_newick(newick)
+            .map(|inner| Self { inner })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn to_newick(&self) -> String {
+        self.inner.to_newick()
+    }
+
+    /// Nested {"name", "length", "children"} JSON for tree layouts
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// Leaf names, left to right
+    #[getter]
+    fn leaf_names(&self) -> Vec<String> {
+        self.inner.leaf_names()
+    }
+
+    /// Copy rooted at the midpoint of the branch separating the `outgroup`
+    /// leaves from the rest, the tree taken as unrooted
+    fn root_with_outgroup(&self, outgroup: Vec<String>) -> PyResult<PyTree> {
+        root_with_outgroup(&self.inner, &outgroup)
+            .map(|inner| PyTree { inner })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Copy rooted halfway along the longest leaf-to-leaf path
+    fn midpoint_root(&self) -> PyResult<PyTree> {
+        midpoint_root(&self.inner)
+            .map(|inner| PyTree { inner })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Time depth of cognate sets given as their members' languages (leaf
+    /// names): MRCA, primary branches reached, relative MRCA age, score
+    /// and depth class, in parallel
+    fn cognate_set_depths(
+        &self,
+        py: Python<'_>,
+        sets: Vec<Vec<String>>,
+    ) -> PyResult<Vec<PySetDepth>> {
+        let depths = py
+            .allow_threads(|| score_set_depths(&self.inner, &sets))
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(depths
+            .into_iter()
+            .map(|inner| PySetDepth { inner })
+            .collect())
+    }
+
+    /// Gains and losses of cognate sets, given as their members' languages
+    /// (leaf names), by `method` "parsimony" (gains cost `gain_penalty`
+    /// losses) or "likelihood" (two-state model with `gain_rate` and
+    /// `loss_rate`, events kept from `min_probability`)
+    #[pyo3(signature = (
+        sets,
+        method="parsimony",
+        gain_penalty=2.0,
+        gain_rate=0.5,
+        loss_rate=1.0,
+        min_probability=0.5
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn reconstruct_ancestral_states(
+        &self,
+        py: Python<'_>,
+        sets: Vec<Vec<String>>,
+        method: &str,
+        gain_penalty: f64,
+        gain_rate: f64,
+        loss_rate: f64,
+        min_probability: f64,
+    ) -> PyResult<PyAncestralStates> {
+        let method = match method {
+            "parsimony" => Reconstruction::Parsimony { gain_penalty },
+            "likelihood" => Reconstruction::Likelihood {
+                gain_rate,
+                loss_rate,
+            },
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "unknown method '{}' (expected parsimony or likelihood)",
+                    other
+                )))
+            }
+        };
+        py.allow_threads(|| {
+            reconstruct_ancestral_states(&self.inner, &sets, method, min_probability)
+        })
+        .map(|inner| PyAncestralStates { inner })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Minimum lateral network of cognate sets given as their members'
+    /// languages (leaf names): the fewest borrowing events, under gain /
+    /// loss parsimony with gains costing `gain_penalty` losses, that
+    /// reconcile the sets with this tree
+    #[pyo3(signature = (sets, gain_penalty=2.0))]
+    fn minimum_lateral_network(
+        &self,
+        py: Python<'_>,
+        sets: Vec<Vec<String>>,
+        gain_penalty: f64,
+    ) -> PyResult<PyLateralNetwork> {
+        py.allow_threads(|| minimum_lateral_network(&self.inner, &sets, gain_penalty))
+            .map(|inner| PyLateralNetwork { inner })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Replacement rates per concept of (language, concept, cognate_class)
+    /// judgments on this tree, ranked most stable first
+    fn concept_stability(
+        &self,
+        py: Python<'_>,
+        judgments: Vec<(String, String, String)>,
+    ) -> PyResult<PyConceptStability> {
+        py.allow_threads(|| concept_replacement_rates(&self.inner, &judgments))
+            .map(|rates| PyConceptStability { rates })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Robinson–Foulds and quartet distances to `other` (raw and
+    /// normalized), both trees taken unrooted over their shared leaves
+    fn compare(&self, py: Python<'_>, other: PyRef<'_, PyTree>) -> PyResult<PyTreeComparison> {
+        let other = &other.inner;
+        py.allow_threads(|| compare_trees(&self.inner, other))
+            .map(|comparison| PyTreeComparison::from(&comparison))
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.nodes.len()
+    }
+}
+
+#[pyclass]
+struct PyTreeComparison {
+    #[pyo3(get)]
+    shared_leaves: usize,
+    /// Non-trivial splits of (self, other)
+    #[pyo3(get)]
+    splits: (usize, usize),
+    #[pyo3(get)]
+    robinson_foulds: usize,
+    #[pyo3(get)]
+    robinson_foulds_normalized: f64,
+    #[pyo3(get)]
+    quartets: u64,
+    #[pyo3(get)]
+    quartet_distance: u64,
+    #[pyo3(get)]
+    quartet_distance_normalized: f64,
+}
+
+impl From<&TreeComparison> for PyTreeComparison {
+    fn from(comparison: &TreeComparison) -> Self {
+        Self {
+            shared_leaves: comparison.shared_leaves,
+            splits: comparison.splits,
+            robinson_foulds: comparison.robinson_foulds,
+            robinson_foulds_normalized: comparison.robinson_foulds_normalized,
+            quartets: comparison.quartets,
+            quartet_distance: comparison.quartet_distance,
+            quartet_distance_normalized: comparison.quartet_distance_normalized,
+        }
+    }
+}
+
+#[pyclass]
+struct PyConceptRate {
+    #[pyo3(get)]
+    concept: String,
+    #[pyo3(get)]
+    languages: usize,
+    #[pyo3(get)]
+    cognate_classes: usize,
+    #[pyo3(get)]
+    replacements: usize,
+    #[pyo3(get)]
+    tree_length: f64,
+    #[pyo3(get)]
+    rate: f64,
+    /// 1 = most stable
+    #[pyo3(get)]
+    rank: usize,
+}
+
+impl From<&ConceptRate> for PyConceptRate {
+    fn from(rate: &ConceptRate) -> Self {
+        Self {
+            concept: rate.concept.clone(),
+            languages: rate.languages,
+            cognate_classes: rate.cognate_classes,
+            replacements: rate.replacements,
+            tree_length: rate.tree_length,
+            rate: rate.rate,
+            rank: rate.rank,
+        }
+    }
+}
+
+/// Empirical concept stability ranking from replacement rates on a tree
+#[pyclass(frozen)]
+struct PyConceptStability {
+    rates: Vec<ConceptRate>,
+}
+
+#[pymethods]
+impl PyConceptStability {
+    /// Rates, most stable first
+    #[getter]
+    fn rates(&self) -> Vec<PyConceptRate> {
+        self.rates.iter().map(PyConceptRate::from).collect()
+    }
+
+    /// {concept: rank} of the empirical ranking
+    fn ranks(&self) -> HashMap<String, usize> {
+        self.rates
+            .iter()
+            .map(|rate| (rate.concept.clone(), rate.rank))
+            .collect()
+    }
+
+    /// (Spearman's rho, shared concepts) against a published ranking:
+    /// `ranks` ({concept: rank}, 1 = most stable) or else the order of a
+    /// bundled `concept_list`
+    #[pyo3(signature = (concept_list="leipzig_jakarta", ranks=None))]
+    fn correlation(
+        &self,
+        concept_list: &str,
+        ranks: Option<HashMap<String, usize>>,
+    ) -> PyResult<(f64, usize)> {
+        let reference = match ranks {
+            Some(ranks) => ranks,
+            None => {
+                let concepts = self.rates.iter().map(|rate| rate.concept.as_str());
+                list_ranks(parse_concept_list(concept_list)?, concepts)
+            }
+        };
+        let correlation = stability_correlation(&self.rates, &reference);
+        Ok((correlation.spearman, correlation.shared))
+    }
+
+    fn __len__(&self) -> usize {
+        self.rates.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ConceptStability(concepts={})", self.rates.len())
+    }
+}
+
+#[pyclass]
+struct PyCladeSupport {
+    #[pyo3(get)]
+    members: Vec<String>,
+    /// Replicates that could test the clade
+    #[pyo3(get)]
+    tested: usize,
+    #[pyo3(get)]
+    recovered: usize,
+    /// recovered / tested, NaN when untested
+    #[pyo3(get)]
+    support: f64,
+}
+
+impl From<&CladeSupport> for PyCladeSupport {
+    fn from(clade: &CladeSupport) -> Self {
+        Self {
+            members: clade.members.clone(),
+            tested: clade.tested,
+            recovered: clade.recovered,
+            support: clade.support(),
+        }
+    }
+}
+
+/// Full-data tree with jackknife support for its clades
+#[pyclass(frozen)]
+struct PyJackknife {
+    inner: Jackknife,
+}
+
+#[pymethods]
+impl PyJackknife {
+    #[getter]
+    fn tree(&self) -> PyTree {
+        PyTree {
+            inner: self.inner.tree.clone(),
+        }
+    }
+
+    /// Clades of the tree with 2 to n - 2 languages, in preorder
+    #[getter]
+    fn clades(&self) -> Vec<PyCladeSupport> {
+        self.inner.clades.iter().map(PyCladeSupport::from).collect()
+    }
+
+    /// Normalized Robinson–Foulds distance of each replicate tree to the
+    /// full tree over its languages
+    #[getter]
+    fn robinson_foulds(&self) -> Vec<f64> {
+        self.inner.robinson_foulds.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.robinson_foulds.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Jackknife(clades={}, replicates={})",
+            self.inner.clades.len(),
+            self.inner.robinson_foulds.len()
+        )
+    }
+}
+
+#[pyclass]
+struct PyCorrespondenceConfidence {
+    #[pyo3(get)]
+    language_a: String,
+    #[pyo3(get)]
+    language_b: String,
+    #[pyo3(get)]
+    segment_a: String,
+    #[pyo3(get)]
+    segment_b: String,
+    #[pyo3(get)]
+    count: u64,
+    /// Share of segment_a's sites aligned with segment_b
+    #[pyo3(get)]
+    frequency: f64,
+    #[pyo3(get)]
+    low: f64,
+    #[pyo3(get)]
+    high: f64,
+    /// Share of replicates where segment_b is segment_a's commonest partner
+    #[pyo3(get)]
+    rule_support: f64,
+}
+
+impl From<CorrespondenceConfidence> for PyCorrespondenceConfidence {
+    fn from(confidence: CorrespondenceConfidence) -> Self {
+        Self {
+            language_a: confidence.language_a,
+            language_b: confidence.language_b,
+            segment_a: confidence.segment_a,
+            segment_b: confidence.segment_b,
+            count: confidence.count,
+            frequency: confidence.frequency,
+            low: confidence.low,
+            high: confidence.high,
+            rule_support: confidence.rule_support,
+        }
+    }
+}
+
+#[pyclass]
+struct PyBranchEvents {
+    /// Node below the branch
+    #[pyo3(get)]
+    node: String,
+    /// None for the root, whose gains are the sets present there
+    #[pyo3(get)]
+    parent: Option<String>,
+    #[pyo3(get)]
+    length: f64,
+    /// (set index, probability) pairs
+    #[pyo3(get)]
+    gains: Vec<(usize, f64)>,
+    #[pyo3(get)]
+    losses: Vec<(usize, f64)>,
+}
+
+impl From<&BranchEvents> for PyBranchEvents {
+    fn from(branch: &BranchEvents) -> Self {
+        Self {
+            node: branch.node.clone(),
+            parent: branch.parent.clone(),
+            length: branch.length,
+            gains: branch.gains.clone(),
+            losses: branch.losses.clone(),
+        }
+    }
+}
+
+/// Reconstructed cognate-set states and per-branch events of a tree
+#[pyclass(frozen)]
+struct PyAncestralStates {
+    inner: AncestralStates,
+}
+
+#[pymethods]
+impl PyAncestralStates {
+    /// Node names in preorder
+    #[getter]
+    fn nodes(&self) -> Vec<String> {
+        self.inner.nodes.clone()
+    }
+
+    /// Presence probability of every set at `node`
+    fn presence(&self, node: &str) -> PyResult<Vec<f64>> {
+        let index = self.inner.nodes.iter().position(|n| n == node);
+        let index = index.ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("unknown node '{}'", node))
+        })?;
+        Ok(self.inner.presence[index].clone())
+    }
+
+    /// Events on the branch above every node, in preorder
+    fn branches(&self) -> Vec<PyBranchEvents> {
+        self.inner
+            .branches
+            .iter()
+            .map(PyBranchEvents::from)
+            .collect()
+    }
+
+    /// {"nodes", "presence", "branches"} JSON for tree visualizations
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyAncestralStates(nodes={}, sets={})",
+            self.inner.nodes.len(),
+            self.inner.presence.first().map_or(0, Vec::len)
+        )
+    }
+}
+
+/// A reference tree with the lateral transfers explaining cognate sets
+#[pyclass(frozen)]
+struct PyLateralNetwork {
+    inner: LateralNetwork,
+}
+
+#[pymethods]
+impl PyLateralNetwork {
+    /// (donor, recipient, set indices) per transfer; donors and recipients
+    /// are tree nodes
+    fn transfers(&self) -> Vec<(String, String, Vec<usize>)> {
+        self.inner
+            .transfers
+            .iter()
+            .map(|t| (t.donor.clone(), t.recipient.clone(), t.sets.clone()))
+            .collect()
+    }
+
+    /// Nodes gaining each set, its vertical origin first
+    fn origins(&self) -> Vec<Vec<String>> {
+        let histories = &self.inner.histories;
+        histories.iter().map(|h| h.origins.clone()).collect()
+    }
+
+    /// Losses per set
+    fn losses(&self) -> Vec<usize> {
+        self.inner.histories.iter().map(|h| h.losses).collect()
+    }
+
+    #[getter]
+    fn num_lateral_events(&self) -> usize {
+        self.inner.num_lateral_events()
+    }
+
+    /// The network in Extended Newick, one `#H<k>` hybrid per recipient
+    fn to_extended_newick(&self) -> String {
+        self.inner.to_extended_newick()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyLateralNetwork(sets={}, lateral_events={})",
+            self.inner.histories.len(),
+            self.inner.num_lateral_events()
+        )
+    }
+}
+
+/// Estimated time depth of a cognate set
+#[pyclass(frozen)]
+struct PySetDepth {
+    inner: SetDepth,
+}
+
+#[pymethods]
+impl PySetDepth {
+    /// Distinct languages attesting the set
+    #[getter]
+    fn languages(&self) -> usize {
+        self.inner.languages
+    }
+
+    /// Share of the tree's languages attesting the set
+    #[getter]
+    fn coverage(&self) -> f64 {
+        self.inner.coverage
+    }
+
+    /// Share of the MRCA's descendant languages attesting the set
+    #[getter]
+    fn clade_coverage(&self) -> f64 {
+        self.inner.clade_coverage
+    }
+
+    #[getter]
+    fn mrca(&self) -> String {
+        self.inner.mrca.clone()
+    }
+
+    #[getter]
+    fn primary_branches(&self) -> usize {
+        self.inner.primary_branches
+    }
+
+    /// MRCA age over root age
+    #[getter]
+    fn relative_age(&self) -> f64 {
+        self.inner.relative_age
+    }
+
+    #[getter]
+    fn score(&self) -> f64 {
+        self.inner.score
+    }
+
+    /// "root", "branch", "subgroup" or "single"
+    #[getter]
+    fn depth_class(&self) -> &'static str {
+        self.inner.class.name()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PySetDepth({}, mrca={:?}, score={:.3})",
+            self.inner.class.name(),
+            self.inner.mrca,
+            self.inner.score
+        )
+    }
+}
+
+/// Likely transcription error for curation
+#[pyclass]
+#[derive(Clone)]
+struct PyTranscriptionFlag {
+    /// Cognate set and member index in the input
+    #[pyo3(get)]
+    set_index: usize,
+    #[pyo3(get)]
+    member_index: usize,
+    #[pyo3(get)]
+    language: String,
+    #[pyo3(get)]
+    ipa: String,
+    /// Mean surprisal (nats per column) of the best alignment
+    #[pyo3(get)]
+    score: f64,
+    /// Member index of the best alignment partner
+    #[pyo3(get)]
+    partner_index: usize,
+    /// Most improbable (segment, partner segment) column
+    #[pyo3(get)]
+    worst_column: (String, String),
 }
 
-// ============================================================================
-// CLUSTERING FUNCTIONS
-// ============================================================================
-
-#[pyfunction]
-fn py_threshold_clustering(
-    similarities: Vec<(String, String, f64)>,
-    threshold: f64,
-) -> PyResult<Vec<Vec<String>>> {
-    Ok(threshold_clustering_with_ids(similarities, threshold))
+impl From<TranscriptionFlag> for PyTranscriptionFlag {
+    fn from(flag: TranscriptionFlag) -> Self {
+        Self {
+            set_index: flag.set,
+            member_index: flag.member,
+            language: flag.language,
+            ipa: flag.ipa,
+            score: flag.score,
+            partner_index: flag.partner,
+            worst_column: flag.worst_column,
+        }
+    }
 }
 
-#[pyfunction]
-fn py_silhouette_score(
-    similarities: Vec<(usize, usize, f64)>,
-    clusters: Vec<Vec<usize>>,
-) -> PyResult<f64> {
-    Ok(silhouette_score(&similarities, &clusters))
+/// Broken kernel invariant found by a validator
+#[pyclass]
+#[derive(Clone)]
+struct PyInvariantViolation {
+    /// "range", "identity", "symmetry", "triangle", "alignment_cost" or
+    /// "alignment_path"
+    #[pyo3(get)]
+    check: String,
+    #[pyo3(get)]
+    forms: Vec<String>,
+    /// How far off the invariant is (0 for structural mismatches)
+    #[pyo3(get)]
+    amount: f64,
+    #[pyo3(get)]
+    detail: String,
 }
 
-#[pyfunction]
-fn py_within_cluster_variance(
-    similarities: Vec<(usize, usize, f64)>,
-    clusters: Vec<Vec<usize>>,
-) -> PyResult<f64> {
-    Ok(within_cluster_variance(&similarities, &clusters))
+#[pymethods]
+impl PyInvariantViolation {
+    fn __repr__(&self) -> String {
+        format!("PyInvariantViolation({}: {})", self.check, self.detail)
+    }
 }
 
-// ============================================================================
-// SPARSE MATRIX FUNCTIONS
-// ============================================================================
-
-#[pyfunction]
-fn py_sparse_matrix_from_edges(
-    edges: Vec<(String, String, f64)>,
-    threshold: f64,
-) -> PyResult<PySparseMatrix> {
-    let matrix = SparseSimilarityMatrix::from_edges(edges, threshold);
-    Ok(PySparseMatrix { inner: matrix })
+impl From<Violation> for PyInvariantViolation {
+    fn from(violation: Violation) -> Self {
+        Self {
+            check: violation.check.to_string(),
+            forms: violation.forms,
+            amount: violation.amount,
+            detail: violation.detail,
+        }
+    }
 }
 
-#[pyfunction]
-fn py_threshold_filter(
-    edges: Vec<(String, String, f64)>,
-    threshold: f64,
-) -> PyResult<Vec<(String, String, f64)>> {
-    Ok(threshold_filter(edges, threshold))
+#[pyclass]
+#[derive(Clone)]
+struct PyReflexPrediction {
+    #[pyo3(get)]
+    form: String,
+    #[pyo3(get)]
+    confidence: f64,
+    /// (segment, confidence) per alignment site; "-" predicts a gap
+    #[pyo3(get)]
+    segments: Vec<(String, f64)>,
+    /// One step per site: attested segments, evidence, predicted segment
+    #[pyo3(get)]
+    derivation: PyDerivation,
 }
 
-// ============================================================================
-// PYTHON WRAPPER TYPES
-// ============================================================================
+impl From<ReflexPrediction> for PyReflexPrediction {
+    fn from(prediction: ReflexPrediction) -> Self {
+        Self {
+            form: prediction.form,
+            confidence: prediction.confidence,
+            segments: prediction.segments,
+            derivation: prediction.derivation.into(),
+        }
+    }
+}
 
+/// Step-by-step derivation of a form: each applied rule (or correspondence,
+/// or piece of evidence) with the form before and after it
 #[pyclass]
-struct PyAlignment {
+#[derive(Clone)]
+struct PyDerivation {
     #[pyo3(get)]
-    sequence_a: Vec<String>,
+    input: String,
     #[pyo3(get)]
-    sequence_b: Vec<String>,
+    output: String,
     #[pyo3(get)]
-    cost: f64,
+    steps: Vec<PyDerivationStep>,
 }
 
-impl From<Alignment> for PyAlignment {
-    fn from(alignment: Alignment) -> Self {
+impl From<Derivation> for PyDerivation {
+    fn from(derivation: Derivation) -> Self {
         Self {
-            sequence_a: alignment.sequence_a,
-            sequence_b: alignment.sequence_b,
-            cost: alignment.cost,
+            input: derivation.input,
+            output: derivation.output,
+            steps: derivation
+                .steps
+                .into_iter()
+                .map(|step| PyDerivationStep {
+                    rule: step.rule,
+                    before: step.before,
+                    after: step.after,
+                })
+                .collect(),
         }
     }
 }
 
 #[pymethods]
-impl PyAlignment {
-    fn correspondences(&self) -> Vec<(String, String)> {
-        let mut rules = Vec::new();
-        for i in 0..self.sequence_a.len().min(self.sequence_b.len()) {
-            if self.sequence_a[i] != self.sequence_b[i]
-                && self.sequence_a[i] != "-"
-                && self.sequence_b[i] != "-"
-            {
-                rules.push((self.sequence_a[i].clone(), self.sequence_b[i].clone()));
-            }
-        }
-        rules
+impl PyDerivation {
+    fn __len__(&self) -> usize {
+        self.steps.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyDerivation({:?} > {:?}, steps={})",
+            self.input,
+            self.output,
+            self.steps.len()
+        )
     }
 }
 
 #[pyclass]
-struct PyCognateSet {
+#[derive(Clone)]
+struct PyDerivationStep {
     #[pyo3(get)]
-    id: usize,
+    rule: String,
     #[pyo3(get)]
-    members: Vec<String>,
+    before: String,
     #[pyo3(get)]
-    size: usize,
+    after: String,
 }
 
-impl From<CognateSet> for PyCognateSet {
-    fn from(set: CognateSet) -> Self {
+#[pymethods]
+impl PyDerivationStep {
+    fn __repr__(&self) -> String {
+        format!(
+            "PyDerivationStep({}: {} > {})",
+            self.rule, self.before, self.after
+        )
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+struct PyRuleEvaluation {
+    #[pyo3(get)]
+    predicted: Vec<String>,
+    #[pyo3(get)]
+    matches: Vec<bool>,
+    #[pyo3(get)]
+    exact_matches: usize,
+    #[pyo3(get)]
+    accuracy: f64,
+    #[pyo3(get)]
+    mean_similarity: f64,
+    /// Words each rule changed
+    #[pyo3(get)]
+    rule_applications: Vec<usize>,
+    /// Mispredicted words, most deviant first
+    #[pyo3(get)]
+    exceptions: Vec<PyRuleException>,
+    exceptions_table: String,
+}
+
+#[pymethods]
+impl PyRuleEvaluation {
+    /// Exceptions as a TSV review table (index, proto, expected, attested,
+    /// deviation, applied rules)
+    fn to_exceptions(&self) -> String {
+        self.exceptions_table.clone()
+    }
+}
+
+impl From<RuleEvaluation> for PyRuleEvaluation {
+    fn from(evaluation: RuleEvaluation) -> Self {
         Self {
-            id: set.id,
-            members: set.members,
-            size: set.size,
+            exceptions_table: evaluation.exceptions_table(),
+            exceptions: evaluation
+                .exceptions
+                .iter()
+                .map(PyRuleException::from)
+                .collect(),
+            predicted: evaluation.predicted,
+            matches: evaluation.matches,
+            exact_matches: evaluation.exact_matches,
+            accuracy: evaluation.accuracy,
+            mean_similarity: evaluation.mean_similarity,
+            rule_applications: evaluation.rule_applications,
         }
     }
 }
 
 #[pyclass]
-struct PyGraphStats {
+#[derive(Clone)]
+struct PyRuleOrdering {
+    /// (earlier, later, feeding words, bleeding words) per interacting pair
     #[pyo3(get)]
-    num_nodes: usize,
+    interactions: Vec<(usize, usize, usize, usize)>,
+    /// (first, second, supporting words, contradicting words) per
+    /// interacting pair, `first` being earlier in the given order
     #[pyo3(get)]
-    num_edges: usize,
+    evidence: Vec<(usize, usize, usize, usize)>,
+    /// Rule pairs with evidence for both orders
     #[pyo3(get)]
-    avg_degree: f64,
+    conflicts: Vec<(usize, usize)>,
     #[pyo3(get)]
-    density: f64,
+    consistent_order: Option<Vec<usize>>,
     #[pyo3(get)]
-    num_components: usize,
+    given_order_consistent: bool,
 }
 
-impl From<GraphStats> for PyGraphStats {
-    fn from(stats: GraphStats) -> Self {
+#[pyclass]
+#[derive(Clone)]
+struct PyRuleException {
+    #[pyo3(get)]
+    index: usize,
+    #[pyo3(get)]
+    proto: String,
+    #[pyo3(get)]
+    expected: String,
+    #[pyo3(get)]
+    attested: String,
+    /// One minus the phonetic similarity of expected and attested
+    #[pyo3(get)]
+    deviation: f64,
+    /// Rules that changed the word, in order
+    #[pyo3(get)]
+    applied: Vec<String>,
+}
+
+impl From<&RuleException> for PyRuleException {
+    fn from(exception: &RuleException) -> Self {
         Self {
-            num_nodes: stats.num_nodes,
-            num_edges: stats.num_edges,
-            avg_degree: stats.avg_degree,
-            density: stats.density,
-            num_components: stats.num_components,
+            index: exception.index,
+            proto: exception.proto.clone(),
+            expected: exception.expected.clone(),
+            attested: exception.attested.clone(),
+            deviation: exception.deviation,
+            applied: exception.applied.clone(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyRuleException {
+    fn __repr__(&self) -> String {
+        format!(
+            "PyRuleException({}: {} > {}, attested {})",
+            self.index, self.proto, self.expected, self.attested
+        )
+    }
+}
+
+impl From<OrderingAnalysis> for PyRuleOrdering {
+    fn from(analysis: OrderingAnalysis) -> Self {
+        Self {
+            interactions: analysis
+                .interactions
+                .iter()
+                .map(|i| (i.earlier, i.later, i.feeding, i.bleeding))
+                .collect(),
+            evidence: analysis
+                .evidence
+                .iter()
+                .map(|e| (e.first, e.second, e.supporting, e.contradicting))
+                .collect(),
+            conflicts: analysis.conflicts(),
+            consistent_order: analysis.consistent_order,
+            given_order_consistent: analysis.given_order_consistent,
         }
     }
 }
 
+/// Trained logistic-regression cognate model
 #[pyclass]
-struct PySparseMatrix {
-    inner: SparseSimilarityMatrix,
+struct PyCognateClassifier {
+    inner: CognateClassifier,
 }
 
 #[pymethods]
-impl PySparseMatrix {
-    fn knn(&self, entry_id: &str, k: usize) -> Vec<(String, f64)> {
-        self.inner.knn(entry_id, k)
+impl PyCognateClassifier {
+    /// Fit on (ipa_a, ipa_b, is_cognate) pairs
+    #[staticmethod]
+    #[pyo3(signature = (pairs, epochs=500, learning_rate=0.1, l2=1e-3))]
+    fn train(
+        py: Python<'_>,
+        pairs: Vec<(String, String, bool)>,
+        epochs: usize,
+        learning_rate: f64,
+        l2: f64,
+    ) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let options = TrainOptions {
+                epochs,
+                learning_rate,
+                l2,
+            };
+            let inner = CognateClassifier::train(&pairs, options)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            Ok(Self { inner })
+        })
     }
 
-    fn neighbors_above_threshold(&self, entry_id: &str, threshold: f64) -> Vec<(String, f64)> {
-        self.inner.neighbors_above_threshold(entry_id, threshold)
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = CognateClassifier::from_json(json)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(Self { inner })
     }
 
-    fn shape(&self) -> (usize, usize) {
-        self.inner.shape()
+    fn to_json(&self) -> PyResult<String> {
+        self.inner
+            .to_json()
+            .map_err(pyo3::exceptions::PyValueError::new_err)
     }
 
-    fn nnz(&self) -> usize {
-        self.inner.nnz()
+    /// Learned weight per (standardized) feature
+    fn weights(&self) -> BTreeMap<String, f64> {
+        PAIR_FEATURE_NAMES
+            .iter()
+            .zip(self.inner.weights)
+            .map(|(name, weight)| (name.to_string(), weight))
+            .collect()
     }
 
-    fn sparsity(&self) -> f64 {
-        self.inner.sparsity()
+    #[getter]
+    fn bias(&self) -> f64 {
+        self.inner.bias
     }
 
-    fn entry_ids(&self) -> Vec<String> {
-        self.inner.entry_ids().to_vec()
+    fn predict(&self, ipa_a: &str, ipa_b: &str) -> f64 {
+        self.inner.predict_proba(ipa_a, ipa_b)
+    }
+
+    fn predict_batch(&self, py: Python<'_>, pairs: Vec<(String, String)>) -> Vec<f64> {
+        py.allow_threads(|| self.inner.batch_predict(&pairs))
+    }
+
+    /// Weight (id_a, id_b, ipa_a, ipa_b) candidates by cognate probability,
+    /// ready for graph construction
+    fn score_edges(
+        &self,
+        py: Python<'_>,
+        candidates: Vec<(String, String, String, String)>,
+    ) -> Vec<WeightedEdge> {
+        py.allow_threads(|| {
+            let pairs: Vec<(String, String)> = candidates
+                .iter()
+                .map(|(_, _, a, b)| (a.clone(), b.clone()))
+                .collect();
+            let probabilities = self.inner.batch_predict(&pairs);
+            candidates
+                .into_iter()
+                .zip(probabilities)
+                .map(|((id_a, id_b, _, _), p)| (id_a, id_b, p))
+                .collect()
+        })
+    }
+}
+
+#[pyclass]
+struct PySession {
+    inner: Session,
+}
+
+#[pymethods]
+impl PySession {
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: Session::new(),
+        }
+    }
+
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        let inner = Session::load(std::path::Path::new(path))
+            .map_err(pyo3::exceptions::PyIOError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        self.inner
+            .save(std::path::Path::new(path))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    #[getter]
+    fn version(&self) -> u32 {
+        self.inner.version
+    }
+
+    #[pyo3(signature = (edges, threshold, node_attributes=None))]
+    fn set_graph(
+        &mut self,
+        edges: Vec<ProvenanceEdge>,
+        threshold: f64,
+        node_attributes: Option<NodeAttributes>,
+    ) -> PyResult<()> {
+        validate_provenance_weights(&edges)?;
+        validate_threshold(threshold)?;
+        let similarity_edges: Vec<SimilarityEdge> = edges
+            .into_iter()
+            .map(|(s, t, w, p)| SimilarityEdge::with_provenance(s, t, w, p))
+            .collect();
+
+        let mut graph = CognateGraph::from_edges(similarity_edges, threshold);
+        if let Some(attributes) = node_attributes {
+            graph.set_node_attributes(attributes);
+        }
+        self.inner.graph = Some(graph.snapshot());
+        Ok(())
+    }
+
+    fn store_graph(&mut self, graph: &PyCognateGraph) {
+        self.inner.graph = Some(graph.inner.get().snapshot());
+    }
+
+    fn graph(&self) -> Option<PyCognateGraph> {
+        self.inner.graph.clone().map(|snapshot| PyCognateGraph {
+            inner: CognateGraph::from_snapshot(snapshot).into(),
+        })
+    }
+
+    fn graph_edges(&self) -> Vec<ProvenanceEdge> {
+        self.inner
+            .graph
+            .as_ref()
+            .map(|g| g.edges.clone())
+            .unwrap_or_default()
+    }
+
+    fn graph_nodes(&self) -> Vec<String> {
+        self.inner
+            .graph
+            .as_ref()
+            .map(|g| g.nodes.clone())
+            .unwrap_or_default()
+    }
+
+    fn node_attributes(&self) -> NodeAttributes {
+        self.inner
+            .graph
+            .iter()
+            .flat_map(|g| g.node_attributes.iter())
+            .map(|(id, attrs)| (id.clone(), attrs.clone().into_iter().collect()))
+            .collect()
+    }
+
+    fn set_sparse_matrix(&mut self, matrix: &PySparseMatrix) {
+        self.inner.sparse = Some(matrix.inner.snapshot());
+    }
+
+    fn sparse_matrix(&self) -> PyResult<Option<PySparseMatrix>> {
+        self.inner
+            .sparse
+            .clone()
+            .map(|snapshot| {
+                SparseSimilarityMatrix::from_snapshot(snapshot)
+                    .map(|inner| PySparseMatrix { inner })
+                    .map_err(pyo3::exceptions::PyValueError::new_err)
+            })
+            .transpose()
+    }
+
+    fn set_clustering(&mut self, name: String, clusters: Vec<Vec<String>>) {
+        self.inner.clusterings.insert(name, clusters);
+    }
+
+    fn clustering(&self, name: &str) -> Option<Vec<Vec<String>>> {
+        self.inner.clusterings.get(name).cloned()
+    }
+
+    fn clustering_names(&self) -> Vec<String> {
+        self.inner.clusterings.keys().cloned().collect()
+    }
+
+    /// Merge sets (IDs = positions in the clustering) into the lowest ID,
+    /// linking them in the stored graph, and record the edit; returns the
+    /// merged set's ID
+    #[pyo3(signature = (clustering, set_ids, annotator=None, note=None))]
+    fn merge_sets(
+        &mut self,
+        clustering: &str,
+        set_ids: Vec<usize>,
+        annotator: Option<String>,
+        note: Option<String>,
+    ) -> PyResult<usize> {
+        self.inner
+            .merge_sets(clustering, &set_ids, annotator, note)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Split a set into the lists of `partition`, cutting the stored
+    /// graph's edges between parts, and record the edit; returns the parts'
+    /// IDs (the first keeps `set_id`, the rest are appended)
+    #[pyo3(signature = (clustering, set_id, partition, annotator=None, note=None))]
+    fn split_set(
+        &mut self,
+        clustering: &str,
+        set_id: usize,
+        partition: Vec<Vec<String>>,
+        annotator: Option<String>,
+        note: Option<String>,
+    ) -> PyResult<Vec<usize>> {
+        self.inner
+            .split_set(clustering, set_id, partition, annotator, note)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Delete the stored graph's edges between (source, target) pairs, in
+    /// either direction, and record the edit; returns the number deleted
+    #[pyo3(signature = (pairs, annotator=None, note=None))]
+    fn delete_edges(
+        &mut self,
+        pairs: Vec<(String, String)>,
+        annotator: Option<String>,
+        note: Option<String>,
+    ) -> PyResult<usize> {
+        self.inner
+            .delete_edges(&pairs, annotator, note)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Audit log of curation edits, oldest first, optionally for one
+    /// clustering or one annotator
+    #[pyo3(signature = (clustering=None, annotator=None))]
+    fn edits(&self, clustering: Option<&str>, annotator: Option<&str>) -> Vec<PyCurationEdit> {
+        self.inner
+            .edits
+            .iter()
+            .filter(|edit| clustering.is_none_or(|name| edit.clustering.as_deref() == Some(name)))
+            .filter(|edit| annotator.is_none_or(|name| edit.annotator.as_deref() == Some(name)))
+            .map(|edit| PyCurationEdit {
+                inner: edit.clone(),
+            })
+            .collect()
+    }
+
+    /// The audit log as JSON, for review alongside the curated data
+    fn audit_log_json(&self) -> PyResult<String> {
+        self.inner
+            .audit_log_json()
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn set_cost_matrix(&mut self, name: String, costs: Vec<(String, String, f64)>) {
+        self.inner.cost_matrices.insert(name, costs);
+    }
+
+    fn cost_matrix(&self, name: &str) -> Option<Vec<(String, String, f64)>> {
+        self.inner.cost_matrices.get(name).cloned()
+    }
+
+    fn set_config(&mut self, key: String, value: String) {
+        self.inner.config.insert(key, value);
+    }
+
+    fn config(&self) -> BTreeMap<String, String> {
+        self.inner.config.clone()
+    }
+}
+
+/// A manual merge, split or edge deletion recorded in a session
+#[pyclass(frozen)]
+struct PyCurationEdit {
+    inner: CurationEdit,
+}
+
+#[pymethods]
+impl PyCurationEdit {
+    /// Milliseconds since the Unix epoch (0 for edits from before audit
+    /// logging)
+    #[getter]
+    fn timestamp_ms(&self) -> u64 {
+        self.inner.timestamp_ms
+    }
+
+    #[getter]
+    fn annotator(&self) -> Option<String> {
+        self.inner.annotator.clone()
+    }
+
+    /// None for edge deletions
+    #[getter]
+    fn clustering(&self) -> Option<String> {
+        self.inner.clustering.clone()
+    }
+
+    /// "merge", "split" or "delete_edges"
+    #[getter]
+    fn kind(&self) -> &'static str {
+        self.inner.kind.name()
+    }
+
+    /// Set IDs the edit read
+    #[getter]
+    fn sets(&self) -> Vec<usize> {
+        self.inner.sets.clone()
+    }
+
+    /// Members of those sets before the edit
+    #[getter]
+    fn before(&self) -> Vec<Vec<String>> {
+        self.inner.before.clone()
+    }
+
+    /// (set ID, members) after the edit
+    #[getter]
+    fn result(&self) -> Vec<(usize, Vec<String>)> {
+        self.inner.result.clone()
+    }
+
+    #[getter]
+    fn added_edges(&self) -> Vec<ProvenanceEdge> {
+        self.inner.added_edges.clone()
+    }
+
+    #[getter]
+    fn removed_edges(&self) -> Vec<ProvenanceEdge> {
+        self.inner.removed_edges.clone()
+    }
+
+    #[getter]
+    fn note(&self) -> Option<String> {
+        self.inner.note.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyCurationEdit({}, clustering={:?}, sets={:?}, annotator={:?})",
+            self.kind(),
+            self.inner.clustering,
+            self.inner.sets,
+            self.inner.annotator
+        )
     }
 }
 
+/// SQLite result database (built with the `sqlite` feature); see the
+/// README for the schema
+#[cfg(feature = "sqlite")]
+#[pyclass]
+struct PyResultStore {
+    inner: ResultStore,
+}
+
+#[cfg(feature = "sqlite")]
+#[pymethods]
+impl PyResultStore {
+    /// Open or create the database at `path` (":memory:" for a private
+    /// in-memory store)
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        let inner = ResultStore::open(std::path::Path::new(path))
+            .map_err(pyo3::exceptions::PyIOError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Store in a private in-memory database
+    #[staticmethod]
+    fn in_memory() -> PyResult<Self> {
+        let inner = ResultStore::in_memory().map_err(pyo3::exceptions::PyIOError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    #[pyo3(signature = (edges, run=None))]
+    fn write_edges(
+        &mut self,
+        py: Python<'_>,
+        edges: EdgeRows,
+        run: Option<&str>,
+    ) -> PyResult<usize> {
+        let edges: Vec<ProvenanceEdge> = edges
+            .into_iter()
+            .map(|(s, t, w)| (s, t, w, None))
+            .collect();
+        py.allow_threads(|| self.inner.write_edges(&edges, run))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    /// Write a graph's edges with their provenance
+    #[pyo3(signature = (graph, run=None))]
+    fn write_graph(
+        &mut self,
+        py: Python<'_>,
+        graph: &PyCognateGraph,
+        run: Option<&str>,
+    ) -> PyResult<usize> {
+        let edges = graph.inner.get().provenance_edge_list();
+        py.allow_threads(|| self.inner.write_edges(&edges, run))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    #[pyo3(signature = (sets, run=None))]
+    fn write_cognate_sets(
+        &mut self,
+        py: Python<'_>,
+        sets: Vec<PyRef<PyCognateSet>>,
+        run: Option<&str>,
+    ) -> PyResult<usize> {
+        let sets: Vec<CognateSet> = sets
+            .iter()
+            .map(|set| CognateSet::new(set.id, set.members.clone()))
+            .collect();
+        py.allow_threads(|| self.inner.write_cognate_sets(&sets, run))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    /// Write (word_a, word_b, alignment) rows
+    #[pyo3(signature = (alignments, run=None))]
+    fn write_alignments(
+        &mut self,
+        py: Python<'_>,
+        alignments: Vec<(String, String, PyRef<PyAlignment>)>,
+        run: Option<&str>,
+    ) -> PyResult<usize> {
+        let alignments: Vec<(String, String, Alignment)> = alignments
+            .into_iter()
+            .map(|(a, b, alignment)| (a, b, alignment.inner.clone()))
+            .collect();
+        py.allow_threads(|| self.inner.write_alignments(&alignments, run))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    #[pyo3(signature = (correspondences, run=None))]
+    fn write_correspondences(
+        &mut self,
+        py: Python<'_>,
+        correspondences: &PySoundCorrespondences,
+        run: Option<&str>,
+    ) -> PyResult<usize> {
+        let rows = &correspondences.inner.correspondences;
+        py.allow_threads(|| self.inner.write_correspondences(rows, run))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    /// Delete the rows of `run`, or everything when `run` is None
+    #[pyo3(signature = (run=None))]
+    fn clear(&mut self, py: Python<'_>, run: Option<&str>) -> PyResult<()> {
+        py.allow_threads(|| self.inner.clear(run))
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+
+    /// Row count per table
+    fn counts(&self) -> PyResult<BTreeMap<&'static str, usize>> {
+        self.inner
+            .counts()
+            .map(|counts| counts.into_iter().collect())
+            .map_err(pyo3::exceptions::PyIOError::new_err)
+    }
+}
+
+/// Turn determinism mode on or off for the process. On (the default),
+/// parallel floating-point sums are combined in a fixed order, so identical
+/// inputs give bit-identical outputs whatever the thread count; off lets
+/// threads combine them as they finish, slightly faster on large inputs
+#[pyfunction]
+fn py_set_deterministic(enabled: bool) {
+    set_deterministic(enabled);
+}
+
+#[pyfunction]
+fn py_is_deterministic() -> bool {
+    is_deterministic()
+}
+
 // ============================================================================
 // MODULE DEFINITION
 // ============================================================================
 
 #[pymodule]
-fn langviz_core(_py: Python, m: &PyModule) -> PyResult<()> {
+fn langviz_core(py: Python, m: &PyModule) -> PyResult<()> {
     // Phonetic functions
     m.add_function(wrap_pyfunction!(py_phonetic_distance, m)?)?;
     m.add_function(wrap_pyfunction!(py_batch_phonetic_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(py_segment_ipa, m)?)?;
+    m.add_function(wrap_pyfunction!(py_phonetic_key, m)?)?;
+    m.add_function(wrap_pyfunction!(py_block_by_key, m)?)?;
     m.add_function(wrap_pyfunction!(py_lcs_ratio, m)?)?;
     m.add_function(wrap_pyfunction!(py_dtw_align, m)?)?;
+    m.add_function(wrap_pyfunction!(py_phonetic_distance_with_costs, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_phonetic_distance_with_costs, m)?)?;
+    m.add_function(wrap_pyfunction!(py_gpu_available, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_banded_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_cosine_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sound_class_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_sound_class_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(py_dtw_align_with_costs, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_dtw_align, m)?)?;
+    m.add_function(wrap_pyfunction!(py_check_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(py_triangle_violations, m)?)?;
+    m.add_function(wrap_pyfunction!(py_check_alignments, m)?)?;
+    m.add_function(wrap_pyfunction!(py_compute_similarity_matrix_with_costs, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sound_correspondences, m)?)?;
+    m.add_function(wrap_pyfunction!(py_nw_align, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sw_align, m)?)?;
+    m.add_function(wrap_pyfunction!(py_feature_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_feature_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(py_feature_distance_table, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_pair_features, m)?)?;
     m.add_function(wrap_pyfunction!(py_compute_similarity_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(py_lexicon_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(py_similar_concepts, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sample_pair_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_compute_similarity_matrix_chunked, m)?)?;
+    m.add_function(wrap_pyfunction!(py_similarity_matrix_sparse, m)?)?;
+    m.add_function(wrap_pyfunction!(py_similarity_matrix_topk, m)?)?;
+    m.add_function(wrap_pyfunction!(py_submit_similarity_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(py_submit_similarity_matrix_sparse, m)?)?;
+    m.add_function(wrap_pyfunction!(py_submit_pipeline, m)?)?;
+    m.add_function(wrap_pyfunction!(py_resume_job, m)?)?;
+    m.add_function(wrap_pyfunction!(py_segment_orthography, m)?)?;
+    m.add_function(wrap_pyfunction!(py_orthographic_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_orthographic_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(py_correspondence_matrices, m)?)?;
+    m.add_function(wrap_pyfunction!(py_correspondence_matrices_json, m)?)?;
+    m.add_function(wrap_pyfunction!(py_detect_transcription_errors, m)?)?;
+    m.add_function(wrap_pyfunction!(py_bootstrap_correspondences, m)?)?;
+    m.add_function(wrap_pyfunction!(py_correspondence_patterns, m)?)?;
+    m.add_function(wrap_pyfunction!(py_reconstruct_proto, m)?)?;
+    m.add_function(wrap_pyfunction!(py_reconstruct_protos, m)?)?;
+    m.add_function(wrap_pyfunction!(py_detect_partial_cognates, m)?)?;
+    m.add_function(wrap_pyfunction!(py_partial_cognate_edges, m)?)?;
+    m.add_function(wrap_pyfunction!(py_apply_sound_changes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_derive_sound_changes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_evaluate_rules, m)?)?;
+    m.add_function(wrap_pyfunction!(py_analyze_rule_ordering, m)?)?;
 
     // Graph functions
     m.add_function(wrap_pyfunction!(py_build_cognate_graph, m)?)?;
     m.add_function(wrap_pyfunction!(py_find_cognate_sets, m)?)?;
     m.add_function(wrap_pyfunction!(py_detect_communities, m)?)?;
+    m.add_function(wrap_pyfunction!(py_detect_communities_lpa, m)?)?;
+    m.add_function(wrap_pyfunction!(py_max_spanning_forest, m)?)?;
     m.add_function(wrap_pyfunction!(py_compute_pagerank, m)?)?;
+    m.add_function(wrap_pyfunction!(py_personalized_pagerank, m)?)?;
+    m.add_function(wrap_pyfunction!(py_directed_degrees, m)?)?;
+    m.add_function(wrap_pyfunction!(py_directed_pagerank, m)?)?;
+    m.add_function(wrap_pyfunction!(py_strongly_connected_components, m)?)?;
+    m.add_function(wrap_pyfunction!(py_directed_graph_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_compute_betweenness, m)?)?;
+    m.add_function(wrap_pyfunction!(py_compute_closeness, m)?)?;
+    m.add_function(wrap_pyfunction!(py_compute_centralities, m)?)?;
+    m.add_function(wrap_pyfunction!(py_filter_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(py_top_edges_per_language_pair, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sample_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sparsify_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(py_mutual_knn_reweight, m)?)?;
+    m.add_function(wrap_pyfunction!(py_calibrate_pair_thresholds, m)?)?;
+    m.add_function(wrap_pyfunction!(py_calibrate_probabilities, m)?)?;
+    m.add_function(wrap_pyfunction!(py_score_curves, m)?)?;
+    m.add_function(wrap_pyfunction!(py_batch_score_curves, m)?)?;
+    m.add_function(wrap_pyfunction!(py_annotator_agreement, m)?)?;
+    m.add_function(wrap_pyfunction!(py_suggest_pairs, m)?)?;
+    m.add_function(wrap_pyfunction!(py_generate_wordlist, m)?)?;
+    m.add_function(wrap_pyfunction!(py_simulate_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(py_perturb_ipa, m)?)?;
+    m.add_function(wrap_pyfunction!(py_noise_robustness, m)?)?;
+    m.add_function(wrap_pyfunction!(py_null_model_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_find_cognate_sets_sparsified, m)?)?;
+    m.add_function(wrap_pyfunction!(py_aggregate_multi_edges, m)?)?;
+    m.add_function(wrap_pyfunction!(py_graph_set_operation, m)?)?;
+    m.add_function(wrap_pyfunction!(py_filter_by_provenance, m)?)?;
+    m.add_function(wrap_pyfunction!(py_provenance_counts, m)?)?;
+    m.add_function(wrap_pyfunction!(py_neighbor_jaccard, m)?)?;
+    m.add_function(wrap_pyfunction!(py_predict_links, m)?)?;
+    m.add_function(wrap_pyfunction!(py_spectral_embedding, m)?)?;
+    m.add_function(wrap_pyfunction!(py_force_layout, m)?)?;
+    m.add_function(wrap_pyfunction!(py_bundle_edges, m)?)?;
+    m.add_function(wrap_pyfunction!(py_tsne_embedding, m)?)?;
+    m.add_function(wrap_pyfunction!(py_node2vec, m)?)?;
     m.add_function(wrap_pyfunction!(py_graph_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_core_numbers, m)?)?;
+    m.add_function(wrap_pyfunction!(py_k_core, m)?)?;
+    m.add_function(wrap_pyfunction!(py_find_bridges, m)?)?;
+    m.add_function(wrap_pyfunction!(py_find_articulation_points, m)?)?;
+    m.add_function(wrap_pyfunction!(py_path_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_component_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cluster_quality, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cluster_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(py_expand_clusters, m)?)?;
+    m.add_function(wrap_pyfunction!(py_project_bipartite, m)?)?;
     m.add_function(wrap_pyfunction!(py_graph_to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(py_graph_write_json, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_edge_list, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_text, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_edges_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_edges_arrow, m)?)?;
+    #[cfg(feature = "parquet")]
+    m.add_function(wrap_pyfunction!(py_load_edges_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(py_graph_to_graphml, m)?)?;
+    m.add_function(wrap_pyfunction!(py_graph_to_gexf, m)?)?;
+    m.add_function(wrap_pyfunction!(py_graph_to_node_link_data, m)?)?;
+    m.add_function(wrap_pyfunction!(py_graph_to_adjacency_data, m)?)?;
+    m.add_function(wrap_pyfunction!(py_links_to_geojson, m)?)?;
+    m.add_function(wrap_pyfunction!(py_isogloss_boundaries, m)?)?;
 
     // Clustering functions
     m.add_function(wrap_pyfunction!(py_threshold_clustering, m)?)?;
     m.add_function(wrap_pyfunction!(py_silhouette_score, m)?)?;
     m.add_function(wrap_pyfunction!(py_within_cluster_variance, m)?)?;
+    m.add_function(wrap_pyfunction!(py_davies_bouldin_index, m)?)?;
+    m.add_function(wrap_pyfunction!(py_within_between_ratio, m)?)?;
+    m.add_function(wrap_pyfunction!(py_evaluate_clusterings, m)?)?;
+    m.add_function(wrap_pyfunction!(py_agglomerative_clustering, m)?)?;
+    m.add_function(wrap_pyfunction!(py_spectral_clustering, m)?)?;
+    m.add_function(wrap_pyfunction!(py_divisive_clustering, m)?)?;
+    m.add_function(wrap_pyfunction!(py_consensus_clustering, m)?)?;
+    m.add_function(wrap_pyfunction!(py_consensus_from_clusterings, m)?)?;
+    m.add_function(wrap_pyfunction!(py_merge_cognate_sets, m)?)?;
+    m.add_function(wrap_pyfunction!(py_assign_cognate_set_ids, m)?)?;
 
     // Sparse matrix functions
     m.add_function(wrap_pyfunction!(py_sparse_matrix_from_edges, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sparse_matrix_from_dense, m)?)?;
     m.add_function(wrap_pyfunction!(py_threshold_filter, m)?)?;
+    m.add_function(wrap_pyfunction!(py_soft_threshold, m)?)?;
+
+    // Lexicostatistics functions
+    m.add_function(wrap_pyfunction!(py_lexicostatistical_distances, m)?)?;
+    m.add_function(wrap_pyfunction!(py_concept_overlap, m)?)?;
+    m.add_function(wrap_pyfunction!(py_field_lexicostatistical_distances, m)?)?;
+    m.add_function(wrap_pyfunction!(py_concept_weights_from_ranks, m)?)?;
+    m.add_function(wrap_pyfunction!(py_classical_mds, m)?)?;
+    m.add_function(wrap_pyfunction!(py_spectral_seriation, m)?)?;
+    m.add_function(wrap_pyfunction!(py_build_upgma_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(py_build_nj_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(py_jackknife_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_distance_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(py_write_distance_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(py_concept_list, m)?)?;
+    m.add_function(wrap_pyfunction!(py_filter_wordlist, m)?)?;
+    m.add_function(wrap_pyfunction!(py_filter_wordlist_by_field, m)?)?;
+    m.add_function(wrap_pyfunction!(py_borrowing_by_field, m)?)?;
+    m.add_function(wrap_pyfunction!(py_borrowability, m)?)?;
+    m.add_function(wrap_pyfunction!(py_concept_coverage, m)?)?;
+    m.add_function(wrap_pyfunction!(py_concept_cognate_density, m)?)?;
+    m.add_function(wrap_pyfunction!(py_train_test_split, m)?)?;
+    m.add_function(wrap_pyfunction!(py_group_k_fold, m)?)?;
+    m.add_function(wrap_pyfunction!(py_set_deterministic, m)?)?;
+    m.add_function(wrap_pyfunction!(py_is_deterministic, m)?)?;
 
     // Classes
     m.add_class::<PyAlignment>()?;
+    m.add_class::<PySoundCorrespondence>()?;
+    m.add_class::<PySoundCorrespondences>()?;
+    m.add_class::<PySeriation>()?;
     m.add_class::<PyCognateSet>()?;
+    m.add_class::<PyMergedCognateSet>()?;
     m.add_class::<PyGraphStats>()?;
+    m.add_class::<PyWhatIf>()?;
+    m.add_class::<PyComponentStats>()?;
+    m.add_class::<PyClusterQuality>()?;
+    m.add_class::<PyLexiconSimilarity>()?;
+    m.add_class::<PyConceptNeighbor>()?;
+    m.add_class::<PyPairSampleStats>()?;
+    m.add_class::<PyConceptCoverage>()?;
+    m.add_class::<PyFieldBorrowing>()?;
+    m.add_class::<PyBorrowingRate>()?;
+    m.add_class::<PyBorrowability>()?;
+    m.add_class::<PyConceptDensity>()?;
+    m.add_class::<PyWordlistSplit>()?;
+    m.add_class::<PyPartialCognates>()?;
+    m.add_class::<PyIdAssignment>()?;
+    m.add_class::<PyNodeCentrality>()?;
+    m.add_class::<PyPathStats>()?;
+    m.add_class::<PyCognateGraph>()?;
+    m.add_class::<PyCognateMultiGraph>()?;
+    m.add_class::<PyBorrowingGraph>()?;
+    m.add_class::<PyContactLink>()?;
+    m.add_class::<PyContactNetwork>()?;
+    m.add_class::<PyCharacterMatrix>()?;
+    m.add_class::<PyNodeDegree>()?;
+    m.add_class::<PyDirectedGraphStats>()?;
     m.add_class::<PySparseMatrix>()?;
+    m.add_class::<PyMappedSparseMatrix>()?;
+    m.add_class::<PySession>()?;
+    m.add_class::<PyCurationEdit>()?;
+    #[cfg(feature = "sqlite")]
+    m.add_class::<PyResultStore>()?;
+    m.add_class::<PyCognateClassifier>()?;
+    m.add_class::<PyCorrespondencePatterns>()?;
+    m.add_class::<PyCorrespondenceConfidence>()?;
+    m.add_class::<PyReflexPrediction>()?;
+    m.add_class::<PyDerivation>()?;
+    m.add_class::<PyDerivationStep>()?;
+    m.add_class::<PyEtymologyIndex>()?;
+    m.add_class::<PyProtoMatch>()?;
+    m.add_class::<PyRuleEvaluation>()?;
+    m.add_class::<PyRuleException>()?;
+    m.add_class::<PyRuleOrdering>()?;
+    m.add_class::<PyPairThresholds>()?;
+    m.add_class::<PyProbabilityCalibration>()?;
+    m.add_class::<PyScoreCurves>()?;
+    m.add_class::<PyAgreement>()?;
+    m.add_class::<PyPairSuggestion>()?;
+    m.add_class::<PySyntheticDataset>()?;
+    m.add_class::<PyDendrogram>()?;
+    m.add_class::<PyTree>()?;
+    m.add_class::<PyTreeComparison>()?;
+    m.add_class::<PyCladeSupport>()?;
+    m.add_class::<PyJackknife>()?;
+    m.add_class::<PySetDepth>()?;
+    m.add_class::<PyLateralNetwork>()?;
+    m.add_class::<PyBranchEvents>()?;
+    m.add_class::<PyConceptRate>()?;
+    m.add_class::<PyConceptStability>()?;
+    m.add_class::<PyAncestralStates>()?;
+    m.add_class::<PyPhoneticIndex>()?;
+    m.add_class::<PyJob>()?;
+    m.add_class::<PyIsoglossSegment>()?;
+    m.add_class::<PyRobustnessPoint>()?;
+    m.add_class::<PyNullModelStats>()?;
+    m.add_class::<PyClusteringScores>()?;
+    m.add_class::<PyEdgeList>()?;
+    m.add_class::<PyTranscriptionFlag>()?;
+    m.add_class::<PyInvariantViolation>()?;
+
+    // Current format version of every serialized artifact
+    let versions: HashMap<&str, u32> = format_versions().into_iter().collect();
+    m.add("FORMAT_VERSIONS", versions)?;
+
+    // Input validation errors (all subclasses of LangVizError, a ValueError)
+    m.add("LangVizError", py.get_type::<exceptions::LangVizError>())?;
+    m.add("InvalidWeightError", py.get_type::<exceptions::InvalidWeightError>())?;
+    m.add("UnknownEntryIdError", py.get_type::<exceptions::UnknownEntryIdError>())?;
+    m.add("EmptyInputError", py.get_type::<exceptions::EmptyInputError>())?;
+    m.add("DimensionMismatchError", py.get_type::<exceptions::DimensionMismatchError>())?;
+    m.add("InvalidParameterError", py.get_type::<exceptions::InvalidParameterError>())?;
 
     Ok(())
 }
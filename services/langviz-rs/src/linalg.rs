@@ -0,0 +1,141 @@
+//! Small dense linear-algebra helpers (symmetric eigenproblems).
+//!
+//! Enough to drive spectral methods without pulling in a LAPACK binding:
+//! Jacobi rotations for small dense matrices and subspace iteration for
+//! the leading eigenvectors of large implicit operators.
+
+use ndarray::{Array2, Axis};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Eigen-decomposition of a symmetric matrix via cyclic Jacobi rotations.
+/// Returns eigenvalues sorted descending and eigenvectors as columns.
+pub fn symmetric_eigen(matrix: &Array2<f64>) -> (Vec<f64>, Array2<f64>) {
+    let n = matrix.nrows();
+    let mut a = matrix.clone();
+    let mut v = Array2::<f64>::eye(n);
+
+    for _sweep in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[[i, j]] * a[[i, j]])
+            .sum();
+        if off < 1e-22 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[[p, q]].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[[q, q]] - a[[p, p]]) / (2.0 * a[[p, q]]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let akp = a[[k, p]];
+                    let akq = a[[k, q]];
+                    a[[k, p]] = c * akp - s * akq;
+                    a[[k, q]] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let apk = a[[p, k]];
+                    let aqk = a[[q, k]];
+                    a[[p, k]] = c * apk - s * aqk;
+                    a[[q, k]] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let vkp = v[[k, p]];
+                    let vkq = v[[k, q]];
+                    v[[k, p]] = c * vkp - s * vkq;
+                    v[[k, q]] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[[j, j]].total_cmp(&a[[i, i]]));
+
+    let values = order.iter().map(|&i| a[[i, i]]).collect();
+    let vectors = v.select(Axis(1), &order);
+    (values, vectors)
+}
+
+/// Orthonormalize the columns of `x` in place (modified Gram-Schmidt)
+pub fn orthonormalize(x: &mut Array2<f64>) {
+    let k = x.ncols();
+    for j in 0..k {
+        for i in 0..j {
+            let proj = x.column(i).dot(&x.column(j));
+            let qi = x.column(i).to_owned();
+            x.column_mut(j).scaled_add(-proj, &qi);
+        }
+        let norm = x.column(j).dot(&x.column(j)).sqrt();
+        if norm > 1e-12 {
+            x.column_mut(j).mapv_inplace(|v| v / norm);
+        }
+    }
+}
+
+/// Leading `k` eigenpairs of a symmetric positive semi-definite operator of
+/// size `n`, given as a function computing `A X` for an n×k block.
+/// Uses subspace iteration followed by a Rayleigh-Ritz projection.
+pub fn top_eigenpairs<F>(
+    apply: F,
+    n: usize,
+    k: usize,
+    iterations: usize,
+    seed: u64,
+) -> (Vec<f64>, Array2<f64>)
+where
+    F: Fn(&Array2<f64>) -> Array2<f64>,
+{
+    let k = k.min(n);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut x = Array2::from_shape_fn((n, k), |_| rng.gen::<f64>() - 0.5);
+    orthonormalize(&mut x);
+
+    for _ in 0..iterations {
+        let mut y = apply(&x);
+        orthonormalize(&mut y);
+
+        let delta = (&y - &x).mapv(f64::abs).sum();
+        x = y;
+        if delta < 1e-10 * (n * k) as f64 {
+            break;
+        }
+    }
+
+    // Rayleigh-Ritz: rotate the converged subspace onto eigenvectors
+    let ax = apply(&x);
+    let h = x.t().dot(&ax);
+    let (values, rotation) = symmetric_eigen(&h);
+    (values, x.dot(&rotation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let m = array![[2.0, 1.0], [1.0, 2.0]];
+        let (values, vectors) = symmetric_eigen(&m);
+        assert!((values[0] - 3.0).abs() < 1e-9);
+        assert!((values[1] - 1.0).abs() < 1e-9);
+        assert!((vectors[[0, 0]].abs() - vectors[[1, 0]].abs()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_top_eigenpairs() {
+        let m = array![[4.0, 1.0, 0.0], [1.0, 3.0, 0.0], [0.0, 0.0, 1.0]];
+        let (values, _) = top_eigenpairs(|x| m.dot(x), 3, 2, 200, 1);
+        let (exact, _) = symmetric_eigen(&m);
+        assert!((values[0] - exact[0]).abs() < 1e-6);
+        assert!((values[1] - exact[1]).abs() < 1e-6);
+    }
+}
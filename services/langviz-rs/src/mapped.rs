@@ -0,0 +1,423 @@
+//! Memory-mapped similarity matrices for data that doesn't fit in RAM.
+//!
+//! `MappedSimilarityMatrix` answers the row queries of
+//! `SparseSimilarityMatrix` (`knn`, `neighbors_above_threshold`, `matvec`)
+//! straight from files on disk. The matrix file holds the CSR arrays as
+//! little-endian u64 / f64 after a fixed header, so a row is read in place
+//! from the mapping; the string IDs go to a `.ids` sidecar with byte
+//! offsets per ID and the row order sorted by ID, so looking one up is a
+//! binary search rather than a table in memory. Only the pages a query
+//! touches are resident, and the OS can drop them again under pressure.
+//!
+//! Files are written by `SparseSimilarityMatrix::save_mapped`, or converted
+//! from a `save`d matrix by `MappedSimilarityMatrix::from_saved` without
+//! loading its arrays.
+
+use memmap2::Mmap;
+use ndarray::Array1;
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::format::check_version;
+use crate::sparse::{top_k, write_u64, SavedHeader};
+
+/// Leading bytes of a mapped matrix file and of its ID sidecar
+const MATRIX_MAGIC: &[u8; 8] = b"LVMAPCSR";
+const IDS_MAGIC: &[u8; 8] = b"LVMAPIDS";
+
+/// Current format version of mapped matrix files
+pub const MAPPED_FORMAT_VERSION: u32 = 1;
+
+/// Magic, version, reserved u32, then rows, cols and nnz as u64
+const MATRIX_HEADER: usize = 40;
+
+/// Magic, version, reserved u32, then rows and cols as u64
+const IDS_HEADER: usize = 32;
+
+/// Sidecar holding the IDs of the matrix at `path`
+pub fn ids_path(path: &Path) -> PathBuf {
+    let mut ids = path.as_os_str().to_owned();
+    ids.push(".ids");
+    PathBuf::from(ids)
+}
+
+/// Write a mapped matrix: the header, then `arrays` (which must write
+/// indptr, indices and values in the layout of `SparseSimilarityMatrix`'s
+/// saved files), and the ID sidecar
+pub fn write_mapped(
+    path: &Path,
+    row_ids: &[String],
+    col_ids: &[String],
+    nnz: usize,
+    arrays: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> Result<(), String> {
+    let error = |e: io::Error| format!("cannot write mapped matrix {}: {}", path.display(), e);
+    let header = |writer: &mut BufWriter<File>, magic: &[u8; 8]| -> io::Result<()> {
+        writer.write_all(magic)?;
+        writer.write_all(&MAPPED_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        write_u64(writer, row_ids.len() as u64)?;
+        write_u64(writer, col_ids.len() as u64)
+    };
+
+    let mut writer = BufWriter::new(File::create(path).map_err(error)?);
+    header(&mut writer, MATRIX_MAGIC)
+        .and_then(|_| write_u64(&mut writer, nnz as u64))
+        .and_then(|_| arrays(&mut writer))
+        .and_then(|_| writer.flush())
+        .map_err(error)?;
+
+    // Byte offsets of the row IDs, the rows in ID order, then the offsets
+    // of the column IDs (continuing after the row bytes) and the bytes
+    let mut order: Vec<usize> = (0..row_ids.len()).collect();
+    order.par_sort_by(|&a, &b| row_ids[a].cmp(&row_ids[b]));
+    let mut writer = BufWriter::new(File::create(ids_path(path)).map_err(error)?);
+    let mut sidecar = || -> io::Result<()> {
+        header(&mut writer, IDS_MAGIC)?;
+        let mut offset = 0;
+        write_offsets(&mut writer, row_ids, &mut offset)?;
+        for &row in &order {
+            write_u64(&mut writer, row as u64)?;
+        }
+        write_offsets(&mut writer, col_ids, &mut offset)?;
+        for id in row_ids.iter().chain(col_ids) {
+            writer.write_all(id.as_bytes())?;
+        }
+        writer.flush()
+    };
+    sidecar().map_err(error)
+}
+
+/// `offset`, then the running end offset after each of `ids`
+fn write_offsets(writer: &mut impl Write, ids: &[String], offset: &mut u64) -> io::Result<()> {
+    write_u64(writer, *offset)?;
+    for id in ids {
+        *offset += id.len() as u64;
+        write_u64(writer, *offset)?;
+    }
+    Ok(())
+}
+
+/// Read-only similarity matrix backed by memory-mapped files
+pub struct MappedSimilarityMatrix {
+    matrix: Mmap,
+    ids: Mmap,
+    rows: usize,
+    cols: usize,
+    nnz: usize,
+}
+
+impl MappedSimilarityMatrix {
+    /// Map a matrix written by `save_mapped` or `from_saved`. The header,
+    /// file sizes and CSR structure are checked once (a sequential scan of
+    /// the arrays); queries then trust them.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let map = |path: &Path| -> Result<Mmap, String> {
+            let file = File::open(path)
+                .map_err(|e| format!("cannot open mapped matrix {}: {}", path.display(), e))?;
+            // The files are only read; changing them while mapped is
+            // undefined behavior, as with any memory-mapped file
+            unsafe { Mmap::map(&file) }.map_err(|e| format!("cannot map {}: {}", path.display(), e))
+        };
+        let matrix = map(path)?;
+        let ids = map(&ids_path(path))?;
+
+        let (rows, cols) = read_header(&matrix, MATRIX_MAGIC, MATRIX_HEADER)?;
+        let nnz = u64_at(&matrix, 32) as usize;
+        let expected = MATRIX_HEADER as u128 + 8 * (rows as u128 + 1 + 2 * nnz as u128);
+        if matrix.len() as u128 != expected {
+            return Err(format!(
+                "invalid mapped matrix: {} bytes, expected {}",
+                matrix.len(),
+                expected
+            ));
+        }
+        if read_header(&ids, IDS_MAGIC, IDS_HEADER)? != (rows, cols) {
+            return Err("invalid mapped matrix: ID sidecar doesn't match the matrix".to_string());
+        }
+        let mapped = Self {
+            matrix,
+            ids,
+            rows,
+            cols,
+            nnz,
+        };
+        mapped.validate()?;
+        Ok(mapped)
+    }
+
+    /// Convert a matrix file written by `SparseSimilarityMatrix::save` to
+    /// the mapped layout at `path`; only the IDs are held in memory, the
+    /// arrays are copied through
+    pub fn from_saved(saved: &Path, path: &Path) -> Result<Self, String> {
+        let file = File::open(saved)
+            .map_err(|e| format!("cannot open matrix file {}: {}", saved.display(), e))?;
+        let mut reader = BufReader::new(file);
+        let header = SavedHeader::read(&mut reader)?;
+        let bytes = 8 * (header.row_ids.len() as u64 + 1 + 2 * header.nnz as u64);
+        write_mapped(
+            path,
+            &header.row_ids,
+            &header.col_ids,
+            header.nnz,
+            |writer| {
+                let copied = io::copy(&mut (&mut reader).take(bytes), writer)?;
+                if copied != bytes {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                Ok(())
+            },
+        )?;
+        Self::open(path)
+    }
+
+    /// indptr rising from 0 to nnz, column indices in bounds and strictly
+    /// increasing within a row, ID offsets rising within the sidecar
+    fn validate(&self) -> Result<(), String> {
+        let invalid = |what: &str| Err(format!("invalid mapped matrix: {}", what));
+        if self.indptr(0) != 0 || self.indptr(self.rows) != self.nnz {
+            return invalid("indptr must run from 0 to the number of stored entries");
+        }
+        let bad_row = (0..self.rows).into_par_iter().find_any(|&row| {
+            let (start, end) = (self.indptr(row), self.indptr(row + 1));
+            start > end
+                || end > self.nnz
+                || (start..end).any(|k| {
+                    let col = self.index(k);
+                    col >= self.cols || (k > start && col <= self.index(k - 1))
+                })
+        });
+        if let Some(row) = bad_row {
+            return invalid(&format!("row {} has an invalid column structure", row));
+        }
+
+        if self.ids.len() < self.id_bytes_start() {
+            return invalid("ID sidecar is truncated");
+        }
+        let n_ids = self.rows + self.cols;
+        let bytes = self.ids.len() - self.id_bytes_start();
+        let offsets_ok = (0..n_ids).all(|i| {
+            let range = self.id_range(i);
+            range.start <= range.end && range.end <= bytes
+        });
+        let order_ok = (0..self.rows).all(|k| self.sorted_row(k) < self.rows);
+        if !offsets_ok || !order_ok {
+            return invalid("ID sidecar is corrupt");
+        }
+        if (0..n_ids).any(|i| std::str::from_utf8(self.id_bytes(i)).is_err()) {
+            return invalid("ID is not valid UTF-8");
+        }
+        Ok(())
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.nnz
+    }
+
+    pub fn sparsity(&self) -> f64 {
+        let total = self.rows * self.cols;
+        if total == 0 {
+            0.0
+        } else {
+            1.0 - (self.nnz as f64 / total as f64)
+        }
+    }
+
+    /// ID of row `row`
+    pub fn row_id(&self, row: usize) -> &str {
+        self.id(row)
+    }
+
+    /// ID of column `col`
+    pub fn col_id(&self, col: usize) -> &str {
+        self.id(self.rows + col)
+    }
+
+    /// Row of `entry_id` (the first, if repeated), by binary search over
+    /// the sorted row order
+    pub fn index_of(&self, entry_id: &str) -> Option<usize> {
+        let (mut low, mut high) = (0, self.rows);
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.row_id(self.sorted_row(mid)) < entry_id {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let row = (low < self.rows).then(|| self.sorted_row(low))?;
+        (self.row_id(row) == entry_id).then_some(row)
+    }
+
+    /// Stored (column, value) entries of `row`, in column order
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        (self.indptr(row)..self.indptr(row + 1)).map(|k| (self.index(k), self.value(k)))
+    }
+
+    /// Stored value at (row, col), None for an absent entry
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        let (mut low, mut high) = (self.indptr(row), self.indptr(row + 1));
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.index(mid).cmp(&col) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(self.value(mid)),
+            }
+        }
+        None
+    }
+
+    /// `SparseSimilarityMatrix::knn` on the mapped rows
+    pub fn knn(&self, entry_id: &str, k: usize) -> Vec<(String, f64)> {
+        let Some(idx) = self.index_of(entry_id) else {
+            return vec![];
+        };
+        top_k(self.row(idx), idx, k)
+            .into_iter()
+            .map(|(col, value)| (self.col_id(col).to_string(), value))
+            .collect()
+    }
+
+    /// `knn` for many entries in parallel
+    pub fn batch_knn(&self, entry_ids: &[String], k: usize) -> Vec<Vec<(String, f64)>> {
+        entry_ids.par_iter().map(|id| self.knn(id, k)).collect()
+    }
+
+    /// `SparseSimilarityMatrix::neighbors_above_threshold` on the mapped
+    /// rows
+    pub fn neighbors_above_threshold(&self, entry_id: &str, threshold: f64) -> Vec<(String, f64)> {
+        let Some(idx) = self.index_of(entry_id) else {
+            return vec![];
+        };
+        self.row(idx)
+            .filter(|&(col, value)| col != idx && value >= threshold)
+            .map(|(col, value)| (self.col_id(col).to_string(), value))
+            .collect()
+    }
+
+    /// Matrix-vector product, rows computed in parallel; `vec` must have
+    /// one entry per column
+    pub fn matvec(&self, vec: &Array1<f64>) -> Array1<f64> {
+        let result: Vec<f64> = (0..self.rows)
+            .into_par_iter()
+            .map(|row| self.row(row).map(|(col, value)| value * vec[col]).sum())
+            .collect();
+        Array1::from(result)
+    }
+
+    fn indptr(&self, row: usize) -> usize {
+        u64_at(&self.matrix, MATRIX_HEADER + 8 * row) as usize
+    }
+
+    fn index(&self, k: usize) -> usize {
+        u64_at(&self.matrix, MATRIX_HEADER + 8 * (self.rows + 1 + k)) as usize
+    }
+
+    fn value(&self, k: usize) -> f64 {
+        f64::from_bits(u64_at(
+            &self.matrix,
+            MATRIX_HEADER + 8 * (self.rows + 1 + self.nnz + k),
+        ))
+    }
+
+    /// Row at position `k` of the ID-sorted order
+    fn sorted_row(&self, k: usize) -> usize {
+        u64_at(&self.ids, IDS_HEADER + 8 * (self.rows + 1 + k)) as usize
+    }
+
+    /// Byte range of ID `i` (rows first, then columns) within the ID bytes
+    fn id_range(&self, i: usize) -> Range<usize> {
+        // Column offsets follow the row offsets and the sorted order
+        let slot = if i < self.rows { i } else { i + self.rows + 1 };
+        let offset = |slot: usize| u64_at(&self.ids, IDS_HEADER + 8 * slot) as usize;
+        offset(slot)..offset(slot + 1)
+    }
+
+    fn id_bytes_start(&self) -> usize {
+        IDS_HEADER + 8 * (2 * self.rows + self.cols + 2)
+    }
+
+    fn id_bytes(&self, i: usize) -> &[u8] {
+        let range = self.id_range(i);
+        let start = self.id_bytes_start();
+        &self.ids[start + range.start..start + range.end]
+    }
+
+    fn id(&self, i: usize) -> &str {
+        // Checked to be UTF-8 on open
+        std::str::from_utf8(self.id_bytes(i)).unwrap_or_default()
+    }
+}
+
+/// Little-endian u64 at byte `offset`
+fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// (rows, cols) from a header, after checking magic, version and size
+fn read_header(bytes: &[u8], magic: &[u8; 8], size: usize) -> Result<(usize, usize), String> {
+    if bytes.len() < size || &bytes[..8] != magic {
+        return Err("invalid mapped matrix: not a mapped matrix file".to_string());
+    }
+    let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+    check_version("mapped matrix", version, MAPPED_FORMAT_VERSION)?;
+    Ok((u64_at(bytes, 16) as usize, u64_at(bytes, 24) as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sparse::SparseSimilarityMatrix;
+
+    #[test]
+    fn test_mapped_matches_in_memory() {
+        let edges = vec![
+            ("w1".to_string(), "w2".to_string(), 0.9),
+            ("w1".to_string(), "w3".to_string(), 0.6),
+            ("w2".to_string(), "w3".to_string(), 0.7),
+            ("w3".to_string(), "w4".to_string(), 0.8),
+        ];
+        let matrix = SparseSimilarityMatrix::from_edges(edges, 0.5);
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("langviz-{}.lvmap", std::process::id()));
+        matrix.save_mapped(&path).unwrap();
+        let mapped = MappedSimilarityMatrix::open(&path).unwrap();
+
+        assert_eq!(mapped.shape(), matrix.shape());
+        assert_eq!(mapped.nnz(), matrix.nnz());
+        assert_eq!(mapped.index_of("w3"), Some(2));
+        assert_eq!(mapped.index_of("w0"), None);
+        for id in ["w1", "w2", "w3", "w4"] {
+            assert_eq!(mapped.knn(id, 2), matrix.knn(id, 2));
+            assert_eq!(
+                mapped.neighbors_above_threshold(id, 0.65),
+                matrix.neighbors_above_threshold(id, 0.65)
+            );
+        }
+        let vec = Array1::from(vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(mapped.matvec(&vec), matrix.matvec(&vec));
+        assert_eq!(mapped.get(0, 1), Some(0.9));
+        assert_eq!(mapped.get(0, 3), None);
+
+        // Converted from a saved file, without loading it
+        let saved = dir.join(format!("langviz-{}.lvsparse", std::process::id()));
+        matrix.save(&saved).unwrap();
+        let converted = MappedSimilarityMatrix::from_saved(&saved, &path).unwrap();
+        assert_eq!(converted.knn("w2", 3), matrix.knn("w2", 3));
+        assert_eq!(converted.col_id(3), "w4");
+
+        std::fs::write(&path, b"LVMAPCSR").unwrap();
+        assert!(MappedSimilarityMatrix::open(&path).is_err());
+        for file in [saved, ids_path(&path), path] {
+            std::fs::remove_file(file).unwrap();
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Classical multidimensional scaling (Torgerson MDS / PCoA).
+//!
+//! Squared distances are double-centered into a Gram matrix
+//! `B = -1/2 J D² J`, and the leading eigenvectors of `B`, scaled by the
+//! square roots of their eigenvalues, are the coordinates. Distances that
+//! are not Euclidean give `B` negative eigenvalues; those axes carry no
+//! real coordinates and are left at zero.
+
+use ndarray::{Array2, ArrayView2};
+
+use crate::linalg::{symmetric_eigen, top_eigenpairs};
+
+/// Largest matrix decomposed exactly; larger ones use subspace iteration
+const EXACT_LIMIT: usize = 300;
+
+/// MDS coordinates with the share of structure each axis captures
+#[derive(Debug, Clone)]
+pub struct Mds {
+    /// (n × dimensions) coordinates, rows in input order
+    pub coords: Array2<f64>,
+    /// Eigenvalue of each axis
+    pub eigenvalues: Vec<f64>,
+    /// Eigenvalue of each axis over the trace of `B`
+    pub explained: Vec<f64>,
+}
+
+/// Embed an n × n distance matrix (made symmetric by averaging) in
+/// `dimensions` dimensions. Each axis is oriented so its largest-magnitude
+/// coordinate is positive.
+pub fn classical_mds(
+    distances: ArrayView2<f64>,
+    dimensions: usize,
+    seed: u64,
+) -> Result<Mds, String> {
+    let n = distances.nrows();
+    if distances.ncols() != n {
+        return Err(format!(
+            "distance matrix must be square, got shape {:?}",
+            distances.shape()
+        ));
+    }
+
+    let squared = Array2::from_shape_fn((n, n), |(i, j)| {
+        let d = (distances[[i, j]] + distances[[j, i]]) / 2.0;
+        d * d
+    });
+    let row_means: Vec<f64> = squared
+        .rows()
+        .into_iter()
+        .map(|r| r.sum() / n as f64)
+        .collect();
+    let grand_mean = row_means.iter().sum::<f64>() / n.max(1) as f64;
+    let gram = Array2::from_shape_fn((n, n), |(i, j)| {
+        -0.5 * (squared[[i, j]] - row_means[i] - row_means[j] + grand_mean)
+    });
+
+    let k = dimensions.min(n);
+    let (values, vectors) = if n <= EXACT_LIMIT {
+        symmetric_eigen(&gram)
+    } else {
+        // Shift by a Gershgorin bound so the operator is PSD with the same
+        // eigenvector order
+        let shift = gram
+            .rows()
+            .into_iter()
+            .map(|r| r.iter().map(|v| v.abs()).sum::<f64>())
+            .fold(0.0, f64::max);
+        let (values, vectors) = top_eigenpairs(|x| gram.dot(x) + x * shift, n, k, 500, seed);
+        (values.into_iter().map(|v| v - shift).collect(), vectors)
+    };
+
+    let trace: f64 = gram.diag().sum();
+    let mut coords = Array2::<f64>::zeros((n, dimensions));
+    let mut eigenvalues = vec![0.0; dimensions];
+    for axis in 0..k {
+        let value = values[axis];
+        eigenvalues[axis] = value;
+        if value <= 0.0 {
+            continue;
+        }
+        let column = vectors.column(axis);
+        let sign = column
+            .iter()
+            .copied()
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .map_or(1.0, f64::signum);
+        let scale = sign * value.sqrt();
+        for i in 0..n {
+            coords[[i, axis]] = column[i] * scale;
+        }
+    }
+    let explained = eigenvalues
+        .iter()
+        .map(|&v| if trace > 0.0 { v / trace } else { 0.0 })
+        .collect();
+
+    Ok(Mds {
+        coords,
+        eigenvalues,
+        explained,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Corners of a 3 × 4 rectangle
+    fn rectangle() -> Array2<f64> {
+        let points = [(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (0.0, 3.0)];
+        Array2::from_shape_fn((4, 4), |(i, j)| {
+            let (a, b): ((f64, f64), (f64, f64)) = (points[i], points[j]);
+            ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+        })
+    }
+
+    #[test]
+    fn test_recovers_planar_distances() {
+        let distances = rectangle();
+        let mds = classical_mds(distances.view(), 2, 0).unwrap();
+        assert_eq!(mds.coords.shape(), &[4, 2]);
+        for i in 0..4 {
+            for j in 0..4 {
+                let d = (&mds.coords.row(i) - &mds.coords.row(j))
+                    .mapv(|v| v * v)
+                    .sum()
+                    .sqrt();
+                assert!((d - distances[[i, j]]).abs() < 1e-6);
+            }
+        }
+        // The long side comes first and the plane holds everything
+        assert!(mds.eigenvalues[0] > mds.eigenvalues[1]);
+        assert!((mds.explained.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_large_matrix_uses_iteration() {
+        // 20 × 16 grid, past the exact-decomposition limit
+        let points: Vec<(f64, f64)> = (0..320)
+            .map(|i| ((i % 20) as f64, (i / 20) as f64))
+            .collect();
+        let distances = Array2::from_shape_fn((320, 320), |(i, j)| {
+            ((points[i].0 - points[j].0).powi(2) + (points[i].1 - points[j].1).powi(2)).sqrt()
+        });
+        let mds = classical_mds(distances.view(), 2, 0).unwrap();
+        let d = (&mds.coords.row(0) - &mds.coords.row(319))
+            .mapv(|v| v * v)
+            .sum()
+            .sqrt();
+        assert!((d - distances[[0, 319]]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_extra_dimensions_and_shape_check() {
+        let mds = classical_mds(rectangle().view(), 3, 0).unwrap();
+        assert!(mds.coords.column(2).iter().all(|v| v.abs() < 1e-6));
+        // Sums of squared centered coordinates: 4 · 2² and 4 · 1.5²
+        let expected = [16.0, 9.0, 0.0];
+        for (value, expected) in mds.eigenvalues.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-6);
+        }
+        assert!(classical_mds(Array2::zeros((2, 3)).view(), 2, 0).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Multigraph storage for parallel similarity edges (one per metric or data
+//! source) with aggregation down to the simple `CognateGraph` used by analytics.
+
+use ahash::AHashMap;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::graph::CognateGraph;
+
+/// Policy for collapsing parallel edges into one weight
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdgeAggregation {
+    Max,
+    Min,
+    Mean,
+    Sum,
+    /// Weighted mean using per-label weights (unlisted labels weigh 1.0)
+    Weighted(HashMap<String, f64>),
+}
+
+impl FromStr for EdgeAggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "max" => Ok(EdgeAggregation::Max),
+            "min" => Ok(EdgeAggregation::Min),
+            "mean" => Ok(EdgeAggregation::Mean),
+            "sum" => Ok(EdgeAggregation::Sum),
+            "weighted" => Ok(EdgeAggregation::Weighted(HashMap::new())),
+            other => Err(format!(
+                "unknown aggregation '{}' (expected max, min, mean, sum or weighted)",
+                other
+            )),
+        }
+    }
+}
+
+impl EdgeAggregation {
+    /// Combine labeled parallel weights into a single weight
+    pub fn combine(&self, values: &[(Option<String>, f64)]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+
+        let weights = values.iter().map(|(_, w)| *w);
+        match self {
+            EdgeAggregation::Max => weights.fold(f64::NEG_INFINITY, f64::max),
+            EdgeAggregation::Min => weights.fold(f64::INFINITY, f64::min),
+            EdgeAggregation::Sum => weights.sum(),
+            EdgeAggregation::Mean => weights.sum::<f64>() / values.len() as f64,
+            EdgeAggregation::Weighted(label_weights) => {
+                let mut total = 0.0;
+                let mut norm = 0.0;
+                for (label, w) in values {
+                    let lw = label
+                        .as_ref()
+                        .and_then(|l| label_weights.get(l))
+                        .copied()
+                        .unwrap_or(1.0);
+                    total += lw * w;
+                    norm += lw;
+                }
+                if norm > 0.0 {
+                    total / norm
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// All parallel edges between one unordered node pair
+#[derive(Debug, Clone)]
+pub struct MultiEdge {
+    pub source: String,
+    pub target: String,
+    pub values: Vec<(Option<String>, f64)>,
+}
+
+/// Undirected multigraph keyed by node ID, preserving insertion order
+#[derive(Debug, Clone, Default)]
+pub struct CognateMultiGraph {
+    pairs: Vec<MultiEdge>,
+    pair_index: AHashMap<(String, String), usize>,
+}
+
+impl CognateMultiGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build from (source, target, weight, label) tuples
+    pub fn from_labeled_edges(edges: Vec<(String, String, f64, String)>) -> Self {
+        let mut multigraph = Self::new();
+        for (source, target, weight, label) in edges {
+            multigraph.add_edge(source, target, weight, Some(label));
+        }
+        multigraph
+    }
+
+    /// Add one parallel edge; (a, b) and (b, a) share a slot
+    pub fn add_edge(&mut self, source: String, target: String, weight: f64, label: Option<String>) {
+        let key = if source <= target {
+            (source.clone(), target.clone())
+        } else {
+            (target.clone(), source.clone())
+        };
+
+        match self.pair_index.get(&key) {
+            Some(&i) => self.pairs[i].values.push((label, weight)),
+            None => {
+                self.pair_index.insert(key, self.pairs.len());
+                self.pairs.push(MultiEdge {
+                    source,
+                    target,
+                    values: vec![(label, weight)],
+                });
+            }
+        }
+    }
+
+    /// Number of distinct node pairs
+    pub fn pair_count(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Total number of parallel edges
+    pub fn edge_count(&self) -> usize {
+        self.pairs.iter().map(|p| p.values.len()).sum()
+    }
+
+    /// Parallel edges between two nodes, in insertion order
+    pub fn edges_between(&self, a: &str, b: &str) -> &[(Option<String>, f64)] {
+        let key = if a <= b {
+            (a.to_string(), b.to_string())
+        } else {
+            (b.to_string(), a.to_string())
+        };
+        self.pair_index
+            .get(&key)
+            .map(|&i| self.pairs[i].values.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Collapse parallel edges and keep aggregated weights >= threshold
+    pub fn aggregate(&self, aggregation: &EdgeAggregation, threshold: f64) -> CognateGraph {
+        let mut graph = CognateGraph::new();
+        for pair in &self.pairs {
+            let weight = aggregation.combine(&pair.values);
+            if weight >= threshold {
+                graph.add_edge(pair.source.clone(), pair.target.clone(), weight);
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> CognateMultiGraph {
+        CognateMultiGraph::from_labeled_edges(vec![
+            ("a".into(), "b".into(), 0.9, "levenshtein".into()),
+            ("b".into(), "a".into(), 0.5, "lcs".into()),
+            ("b".into(), "c".into(), 0.4, "levenshtein".into()),
+        ])
+    }
+
+    #[test]
+    fn test_parallel_edges_share_slot() {
+        let mg = sample();
+        assert_eq!(mg.pair_count(), 2);
+        assert_eq!(mg.edge_count(), 3);
+        assert_eq!(mg.edges_between("b", "a").len(), 2);
+    }
+
+    #[test]
+    fn test_aggregation_policies() {
+        let mg = sample();
+        let values = mg.edges_between("a", "b");
+
+        assert_eq!(EdgeAggregation::Max.combine(values), 0.9);
+        assert_eq!(EdgeAggregation::Min.combine(values), 0.5);
+        assert!((EdgeAggregation::Mean.combine(values) - 0.7).abs() < 1e-12);
+
+        let weighted = EdgeAggregation::Weighted(HashMap::from([("levenshtein".to_string(), 3.0)]));
+        assert!((weighted.combine(values) - 0.8).abs() < 1e-12);
+
+        let graph = mg.aggregate(&EdgeAggregation::Max, 0.6);
+        assert_eq!(graph.stats().num_edges, 1);
+    }
+}
@@ -0,0 +1,267 @@
+//! Neighborhood-overlap measures between nodes.
+//!
+//! Two words whose neighbor sets overlap heavily are likely to belong to the
+//! same cognate set even when their direct phonetic similarity is weak. The
+//! same overlap drives link prediction: candidate edges the phonetic metrics
+//! missed, ranked for manual review.
+
+use petgraph::graph::NodeIndex;
+use rayon::prelude::*;
+use std::str::FromStr;
+
+use crate::graph::CognateGraph;
+use crate::types::WeightedEdge;
+
+/// Link-prediction score over common neighbors `w` of a candidate pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkPredictor {
+    /// Number of shared neighbors
+    CommonNeighbors,
+    /// Sum of 1 / ln(deg(w))
+    AdamicAdar,
+    /// Sum of 1 / deg(w)
+    ResourceAllocation,
+}
+
+impl FromStr for LinkPredictor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "common_neighbors" => Ok(LinkPredictor::CommonNeighbors),
+            "adamic_adar" => Ok(LinkPredictor::AdamicAdar),
+            "resource_allocation" => Ok(LinkPredictor::ResourceAllocation),
+            other => Err(format!(
+                "unknown link predictor '{}' (expected common_neighbors, adamic_adar or resource_allocation)",
+                other
+            )),
+        }
+    }
+}
+
+/// Size of the intersection of two sorted, deduplicated neighbor lists
+fn intersection_size(a: &[usize], b: &[usize]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+fn jaccard(a: &[usize], b: &[usize]) -> f64 {
+    let common = intersection_size(a, b);
+    let union = a.len() + b.len() - common;
+    if union == 0 {
+        0.0
+    } else {
+        common as f64 / union as f64
+    }
+}
+
+/// Sort scored pairs by descending score, then by node IDs
+pub(crate) fn sort_scored_pairs(pairs: &mut [WeightedEdge]) {
+    pairs.sort_by(|x, y| {
+        y.2.total_cmp(&x.2)
+            .then_with(|| x.0.cmp(&y.0))
+            .then_with(|| x.1.cmp(&y.1))
+    });
+}
+
+impl CognateGraph {
+    /// For every node `u`, accumulate `contribution(w)` over each common
+    /// neighbor `w` shared with a later node `v > u`. Only pairs with at
+    /// least one common neighbor appear (contributions must be positive).
+    pub(crate) fn common_neighbor_scores<F>(
+        &self,
+        adjacency: &[Vec<usize>],
+        contribution: F,
+    ) -> Vec<(usize, usize, f64)>
+    where
+        F: Fn(usize) -> f64 + Sync,
+    {
+        let n = adjacency.len();
+
+        (0..n)
+            .into_par_iter()
+            .map_init(
+                || (vec![0.0; n], Vec::new()),
+                |(scores, touched), u| {
+                    for &w in &adjacency[u] {
+                        let c = contribution(w);
+                        for &v in &adjacency[w] {
+                            if v <= u {
+                                continue;
+                            }
+                            if scores[v] == 0.0 {
+                                touched.push(v);
+                            }
+                            scores[v] += c;
+                        }
+                    }
+
+                    touched.sort_unstable();
+                    touched.dedup();
+                    let pairs: Vec<(usize, usize, f64)> = touched
+                        .drain(..)
+                        .map(|v| (u, v, std::mem::take(&mut scores[v])))
+                        .collect();
+                    pairs
+                },
+            )
+            .flatten()
+            .collect()
+    }
+
+    /// Jaccard scores for the given pairs; pairs naming unknown nodes are skipped
+    pub fn jaccard_for_pairs(&self, pairs: &[(String, String)]) -> Vec<WeightedEdge> {
+        let adjacency = self.adjacency_lists();
+        pairs
+            .par_iter()
+            .filter_map(|(a, b)| {
+                let ia = self.node_map.get(a)?;
+                let ib = self.node_map.get(b)?;
+                let score = jaccard(&adjacency[ia.index()], &adjacency[ib.index()]);
+                Some((a.clone(), b.clone(), score))
+            })
+            .collect()
+    }
+
+    /// Jaccard scores for all node pairs sharing at least one neighbor and
+    /// scoring >= `min_score`, highest first
+    pub fn jaccard_all_pairs(&self, min_score: f64) -> Vec<WeightedEdge> {
+        let adjacency = self.adjacency_lists();
+        let common = self.common_neighbor_scores(&adjacency, |_| 1.0);
+
+        let mut result: Vec<WeightedEdge> = common
+            .into_par_iter()
+            .filter_map(|(u, v, shared)| {
+                let shared = shared as usize;
+                let union = adjacency[u].len() + adjacency[v].len() - shared;
+                let score = shared as f64 / union as f64;
+                (score >= min_score).then(|| {
+                    (
+                        self.graph[NodeIndex::new(u)].clone(),
+                        self.graph[NodeIndex::new(v)].clone(),
+                        score,
+                    )
+                })
+            })
+            .collect();
+
+        sort_scored_pairs(&mut result);
+        result
+    }
+
+    /// Rank currently unlinked node pairs by the given predictor, best
+    /// first; `top_k` of `None` returns every candidate
+    pub fn predict_links(
+        &self,
+        predictor: LinkPredictor,
+        top_k: Option<usize>,
+    ) -> Vec<WeightedEdge> {
+        let adjacency = self.adjacency_lists();
+        let degree = |w: usize| adjacency[w].len() as f64;
+
+        let scores = match predictor {
+            LinkPredictor::CommonNeighbors => self.common_neighbor_scores(&adjacency, |_| 1.0),
+            // A common neighbor has degree >= 2, so ln(deg) > 0
+            LinkPredictor::AdamicAdar => {
+                self.common_neighbor_scores(&adjacency, |w| 1.0 / degree(w).ln())
+            }
+            LinkPredictor::ResourceAllocation => {
+                self.common_neighbor_scores(&adjacency, |w| 1.0 / degree(w))
+            }
+        };
+
+        let mut result: Vec<WeightedEdge> = scores
+            .into_par_iter()
+            .filter(|&(u, v, _)| adjacency[u].binary_search(&v).is_err())
+            .map(|(u, v, score)| {
+                (
+                    self.graph[NodeIndex::new(u)].clone(),
+                    self.graph[NodeIndex::new(v)].clone(),
+                    score,
+                )
+            })
+            .collect();
+
+        sort_scored_pairs(&mut result);
+        if let Some(k) = top_k {
+            result.truncate(k);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    #[test]
+    fn test_neighbor_jaccard() {
+        // a and b share c and d; e hangs off d only
+        let edges = vec![
+            SimilarityEdge::new("a".into(), "c".into(), 0.9),
+            SimilarityEdge::new("a".into(), "d".into(), 0.9),
+            SimilarityEdge::new("b".into(), "c".into(), 0.9),
+            SimilarityEdge::new("b".into(), "d".into(), 0.9),
+            SimilarityEdge::new("d".into(), "e".into(), 0.9),
+        ];
+        let graph = CognateGraph::from_edges(edges, 0.5);
+
+        let pair = graph.jaccard_for_pairs(&[("a".into(), "b".into())]);
+        assert_eq!(pair, [("a".to_string(), "b".to_string(), 1.0)]);
+        assert!(graph
+            .jaccard_for_pairs(&[("a".into(), "missing".into())])
+            .is_empty());
+
+        let all = graph.jaccard_all_pairs(0.0);
+        assert_eq!(all[0], ("a".to_string(), "b".to_string(), 1.0));
+        // c and e have no neighbor in common
+        assert!(!all.iter().any(|(s, t, _)| s == "c" && t == "e"));
+
+        let strong = graph.jaccard_all_pairs(0.9);
+        assert_eq!(strong.len(), 1);
+
+        let listed =
+            graph.jaccard_for_pairs(&[("c".into(), "d".into()), ("a".into(), "zzz".into())]);
+        assert_eq!(listed.len(), 1);
+        assert!((listed[0].2 - 2.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_predict_links() {
+        // Square a-b-c-d-a plus hub h linked to a and c
+        let edges = vec![
+            SimilarityEdge::new("a".into(), "b".into(), 0.9),
+            SimilarityEdge::new("b".into(), "c".into(), 0.9),
+            SimilarityEdge::new("c".into(), "d".into(), 0.9),
+            SimilarityEdge::new("d".into(), "a".into(), 0.9),
+            SimilarityEdge::new("h".into(), "a".into(), 0.9),
+            SimilarityEdge::new("h".into(), "c".into(), 0.9),
+        ];
+        let graph = CognateGraph::from_edges(edges, 0.5);
+
+        let common = graph.predict_links(LinkPredictor::CommonNeighbors, None);
+        assert_eq!(common[0], ("a".to_string(), "c".to_string(), 3.0));
+        // Existing edges are never proposed
+        assert!(!common.iter().any(|(s, t, _)| s == "a" && t == "b"));
+
+        let ra = graph.predict_links("resource_allocation".parse().unwrap(), Some(1));
+        assert_eq!(ra.len(), 1);
+        assert!((ra[0].2 - 1.5).abs() < 1e-12);
+
+        let aa = graph.predict_links(LinkPredictor::AdamicAdar, None);
+        assert!((aa[0].2 - 3.0 / 2f64.ln()).abs() < 1e-12);
+        assert!("katz".parse::<LinkPredictor>().is_err());
+    }
+}
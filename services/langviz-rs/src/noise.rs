@@ -0,0 +1,282 @@
+//! Transcription noise injection and robustness testing.
+//!
+//! Real wordlists mix transcription conventions: segments get transposed,
+//! and diacritics (aspiration, length, nasalization, ...) are often left
+//! out. `perturb_ipa` injects exactly this kind of noise at controlled
+//! rates, and `robustness_curve` reports how far threshold clustering of a
+//! wordlist drifts from its clean result as the noise grows, so users can
+//! judge whether their pipeline settings are stable on their own data.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::cluster::UnionFind;
+use crate::phonetic::{is_ipa_modifier, phonetic_distance, segment_ipa};
+
+/// Noise rates, each applied independently per segment
+#[derive(Debug, Clone)]
+pub struct NoiseParams {
+    /// Probability of swapping a segment with its right neighbor
+    pub swap_rate: f64,
+    /// Probability of dropping a segment's diacritics and modifier letters
+    pub diacritic_loss_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        Self {
+            swap_rate: 0.05,
+            diacritic_loss_rate: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+impl NoiseParams {
+    /// Same noise with both rates multiplied by `scale` (capped at 1)
+    pub fn scaled(&self, scale: f64) -> Self {
+        Self {
+            swap_rate: (self.swap_rate * scale).clamp(0.0, 1.0),
+            diacritic_loss_rate: (self.diacritic_loss_rate * scale).clamp(0.0, 1.0),
+            seed: self.seed,
+        }
+    }
+}
+
+/// Perturb one IPA string. The result is the concatenated segments, so
+/// stress and syllable marks are dropped even when no noise hits.
+pub fn perturb_ipa(ipa: &str, params: &NoiseParams, rng: &mut StdRng) -> String {
+    let mut segments = segment_ipa(ipa);
+
+    for segment in segments.iter_mut() {
+        if rng.gen::<f64>() < params.diacritic_loss_rate {
+            // Decompose so precomposed letters (ã, é) lose their marks too
+            let stripped: String = segment
+                .nfd()
+                .filter(|&c| !is_ipa_modifier(c))
+                .nfc()
+                .collect();
+            if !stripped.is_empty() {
+                *segment = stripped;
+            }
+        }
+    }
+
+    let mut i = 0;
+    while i + 1 < segments.len() {
+        if rng.gen::<f64>() < params.swap_rate {
+            segments.swap(i, i + 1);
+            // A swapped segment is not swapped again
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+
+    segments.concat()
+}
+
+/// Perturb every form with one generator seeded from `params.seed`
+pub fn perturb_forms(forms: &[String], params: &NoiseParams) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    forms
+        .iter()
+        .map(|form| perturb_ipa(form, params, &mut rng))
+        .collect()
+}
+
+/// Cluster label per form: forms of the same concept are linked when their
+/// phonetic similarity reaches `threshold`
+fn cluster_labels(concepts: &[String], forms: &[String], threshold: f64) -> Vec<usize> {
+    let mut by_concept: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, concept) in concepts.iter().enumerate() {
+        by_concept.entry(concept.as_str()).or_default().push(idx);
+    }
+
+    let links: Vec<(usize, usize)> = by_concept
+        .into_par_iter()
+        .flat_map_iter(|(_, members)| {
+            let mut pairs = Vec::new();
+            for (k, &i) in members.iter().enumerate() {
+                for &j in &members[k + 1..] {
+                    if phonetic_distance(&forms[i], &forms[j]) >= threshold {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+            pairs
+        })
+        .collect();
+
+    let mut uf = UnionFind::new(forms.len());
+    for (i, j) in links {
+        uf.union(i, j);
+    }
+    (0..forms.len()).map(|i| uf.find(i)).collect()
+}
+
+/// Pair precision and recall of `predicted` against `reference` labels
+fn pair_scores(reference: &[usize], predicted: &[usize]) -> (f64, f64) {
+    fn pairs<K: std::hash::Hash + Eq>(labels: impl Iterator<Item = K>) -> usize {
+        let mut counts: HashMap<K, usize> = HashMap::new();
+        for label in labels {
+            *counts.entry(label).or_default() += 1;
+        }
+        counts.values().map(|&c| c * (c - 1) / 2).sum()
+    }
+
+    let shared = pairs(reference.iter().zip(predicted));
+    let reference_pairs = pairs(reference.iter());
+    let predicted_pairs = pairs(predicted.iter());
+    let ratio = |total: usize| {
+        if total == 0 {
+            1.0
+        } else {
+            shared as f64 / total as f64
+        }
+    };
+    (ratio(predicted_pairs), ratio(reference_pairs))
+}
+
+/// Clustering agreement at one noise scale, averaged over trials
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobustnessPoint {
+    pub scale: f64,
+    /// Fraction of forms the noise changed
+    pub changed_forms: f64,
+    /// Pair precision, recall and F1 against the clean clustering
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    /// Mean number of clusters (the clean clustering's count at scale 0)
+    pub num_clusters: f64,
+}
+
+/// Cluster (id, concept, ipa) entries with and without noise at each scale
+/// of `params`, comparing the noisy clusterings to the clean one. Trial `t`
+/// uses seed `params.seed + t`.
+pub fn robustness_curve(
+    entries: &[(String, String, String)],
+    threshold: f64,
+    params: &NoiseParams,
+    scales: &[f64],
+    trials: usize,
+) -> Vec<RobustnessPoint> {
+    let concepts: Vec<String> = entries.iter().map(|e| e.1.clone()).collect();
+    let forms: Vec<String> = entries.iter().map(|e| e.2.clone()).collect();
+    let clean = cluster_labels(&concepts, &forms, threshold);
+    let trials = trials.max(1);
+
+    scales
+        .iter()
+        .map(|&scale| {
+            let mut totals = [0.0; 5];
+            for trial in 0..trials {
+                let mut noise = params.scaled(scale);
+                noise.seed = params.seed.wrapping_add(trial as u64);
+                let noisy_forms = perturb_forms(&forms, &noise);
+                let noisy = cluster_labels(&concepts, &noisy_forms, threshold);
+
+                let changed = noisy_forms
+                    .iter()
+                    .zip(&forms)
+                    .filter(|(noisy, clean)| segment_ipa(clean).concat() != **noisy)
+                    .count();
+                let (precision, recall) = pair_scores(&clean, &noisy);
+                let f1 = if precision + recall > 0.0 {
+                    2.0 * precision * recall / (precision + recall)
+                } else {
+                    0.0
+                };
+                let mut roots = noisy.clone();
+                roots.sort_unstable();
+                roots.dedup();
+
+                let n = forms.len().max(1) as f64;
+                for (total, value) in totals.iter_mut().zip([
+                    changed as f64 / n,
+                    precision,
+                    recall,
+                    f1,
+                    roots.len() as f64,
+                ]) {
+                    *total += value / trials as f64;
+                }
+            }
+            RobustnessPoint {
+                scale,
+                changed_forms: totals[0],
+                precision: totals[1],
+                recall: totals[2],
+                f1: totals[3],
+                num_clusters: totals[4],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_perturb_ipa() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let none = NoiseParams {
+            swap_rate: 0.0,
+            diacritic_loss_rate: 0.0,
+            seed: 0,
+        };
+        assert_eq!(perturb_ipa("ˈtʰaː.ko", &none, &mut rng), "tʰaːko");
+
+        let strip = NoiseParams {
+            diacritic_loss_rate: 1.0,
+            ..none.clone()
+        };
+        assert_eq!(perturb_ipa("tʰãːt͡ʃ", &strip, &mut rng), "tat͡ʃ");
+
+        let swap = NoiseParams {
+            swap_rate: 1.0,
+            ..none
+        };
+        assert_eq!(perturb_ipa("pʰata", &swap, &mut rng), "apʰat");
+
+        let forms = vec!["tʰata".to_string(); 20];
+        let params = NoiseParams::default();
+        assert_eq!(
+            perturb_forms(&forms, &params),
+            perturb_forms(&forms, &params)
+        );
+    }
+
+    #[test]
+    fn test_robustness_curve() {
+        let entries: Vec<(String, String, String)> = [
+            ("a1", "water", "wɔːtʰər"),
+            ("a2", "water", "wɔtər"),
+            ("a3", "water", "vasːər"),
+            ("b1", "fire", "faɪər"),
+            ("b2", "fire", "fɔʏər"),
+            ("b3", "fire", "pʰuːr"),
+        ]
+        .iter()
+        .map(|(id, concept, ipa)| (id.to_string(), concept.to_string(), ipa.to_string()))
+        .collect();
+
+        let params = NoiseParams::default();
+        let curve = robustness_curve(&entries, 0.5, &params, &[0.0, 10.0], 3);
+        assert_eq!(curve.len(), 2);
+
+        let clean = &curve[0];
+        assert_eq!(clean.changed_forms, 0.0);
+        assert_eq!((clean.precision, clean.recall, clean.f1), (1.0, 1.0, 1.0));
+
+        let noisy = &curve[1];
+        assert!(noisy.changed_forms > 0.5);
+        assert!(noisy.f1 <= 1.0);
+    }
+}
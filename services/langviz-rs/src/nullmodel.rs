@@ -0,0 +1,288 @@
+//! Random-graph null models for significance testing.
+//!
+//! Whether a cognate network's communities or triangles mean anything
+//! depends on what random graphs with the same degrees look like. Two null
+//! models keep every node's degree:
+//!
+//! - degree-preserving rewiring: repeated double-edge swaps
+//!   (a-b, c-d → a-d, c-b) that create no self-loops or parallel edges; each
+//!   edge keeps its weight.
+//! - weighted configuration model: edge endpoints ("stubs") are paired at
+//!   random and the observed weights shuffled over the new edges. Self-loops
+//!   are dropped and parallel edges merged (weights summed), so degrees hold
+//!   only in expectation.
+//!
+//! `null_model_stats` compares Louvain modularity and the average
+//! clustering coefficient of the graph with their distribution over many
+//! samples; sample `i` uses seed `seed + i`, so runs are reproducible
+//! whatever the thread count.
+
+use ahash::AHashMap;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::str::FromStr;
+
+use crate::determinism::par_sum;
+use crate::graph::CognateGraph;
+
+/// Double-edge swaps attempted per edge when rewiring
+const SWAPS_PER_EDGE: usize = 10;
+
+/// Random graph family to compare against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullModel {
+    Rewire,
+    Configuration,
+}
+
+impl FromStr for NullModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rewire" => Ok(NullModel::Rewire),
+            "configuration" => Ok(NullModel::Configuration),
+            other => Err(format!(
+                "unknown null model '{}' (expected rewire or configuration)",
+                other
+            )),
+        }
+    }
+}
+
+/// Observed statistics and their null distributions, one value per sample
+#[derive(Debug, Clone, PartialEq)]
+pub struct NullModelStats {
+    pub observed_modularity: f64,
+    pub observed_clustering: f64,
+    pub modularity: Vec<f64>,
+    pub clustering: Vec<f64>,
+}
+
+impl NullModelStats {
+    pub fn modularity_p_value(&self) -> f64 {
+        empirical_p_value(self.observed_modularity, &self.modularity)
+    }
+
+    pub fn clustering_p_value(&self) -> f64 {
+        empirical_p_value(self.observed_clustering, &self.clustering)
+    }
+}
+
+/// One-sided empirical p-value of `observed` being this large: (1 + samples
+/// at least as large) / (1 + samples)
+pub fn empirical_p_value(observed: f64, samples: &[f64]) -> f64 {
+    let extreme = samples.iter().filter(|&&s| s >= observed).count();
+    (1 + extreme) as f64 / (1 + samples.len()) as f64
+}
+
+/// Sorted pair key of an undirected edge
+fn pair(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn rewire(edges: &mut [(usize, usize, f64)], rng: &mut StdRng) {
+    let m = edges.len();
+    if m < 2 {
+        return;
+    }
+    let mut counts: AHashMap<(usize, usize), usize> = AHashMap::new();
+    for &(a, b, _) in edges.iter() {
+        *counts.entry(pair(a, b)).or_insert(0) += 1;
+    }
+    for _ in 0..SWAPS_PER_EDGE * m {
+        let (i, j) = (rng.gen_range(0..m), rng.gen_range(0..m));
+        let (a, b, _) = edges[i];
+        let (mut c, mut d, _) = edges[j];
+        if rng.gen_bool(0.5) {
+            std::mem::swap(&mut c, &mut d);
+        }
+        let (new_i, new_j) = (pair(a, d), pair(c, b));
+        let taken = |p| counts.get(&p).copied().unwrap_or(0) > 0;
+        if i == j || a == d || c == b || new_i == new_j || taken(new_i) || taken(new_j) {
+            continue;
+        }
+        for old in [pair(a, b), pair(c, d)] {
+            *counts.get_mut(&old).unwrap() -= 1;
+        }
+        *counts.entry(new_i).or_insert(0) += 1;
+        *counts.entry(new_j).or_insert(0) += 1;
+        (edges[i].0, edges[i].1) = (a, d);
+        (edges[j].0, edges[j].1) = (c, b);
+    }
+}
+
+fn configuration(edges: &[(usize, usize, f64)], rng: &mut StdRng) -> Vec<(usize, usize, f64)> {
+    let mut stubs: Vec<usize> = edges.iter().flat_map(|&(a, b, _)| [a, b]).collect();
+    let mut weights: Vec<f64> = edges.iter().map(|e| e.2).collect();
+    stubs.shuffle(rng);
+    weights.shuffle(rng);
+
+    let mut merged: AHashMap<(usize, usize), f64> = AHashMap::new();
+    for (ends, weight) in stubs.chunks_exact(2).zip(weights) {
+        if ends[0] != ends[1] {
+            *merged.entry(pair(ends[0], ends[1])).or_insert(0.0) += weight;
+        }
+    }
+    let mut edges: Vec<(usize, usize, f64)> =
+        merged.into_iter().map(|((a, b), w)| (a, b, w)).collect();
+    edges.sort_unstable_by_key(|&(a, b, _)| (a, b));
+    edges
+}
+
+impl CognateGraph {
+    /// Edges as (source index, target index, weight)
+    fn indexed_edges(&self) -> Vec<(usize, usize, f64)> {
+        self.graph
+            .edge_indices()
+            .map(|edge| {
+                let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+                (a.index(), b.index(), self.graph[edge])
+            })
+            .collect()
+    }
+
+    /// Graph on the same nodes with the given indexed edges
+    fn with_indexed_edges(&self, edges: &[(usize, usize, f64)]) -> CognateGraph {
+        let ids: Vec<&String> = self.graph.node_weights().collect();
+        let mut graph = CognateGraph::new();
+        for id in &ids {
+            graph.get_or_create_node((*id).clone());
+        }
+        for &(a, b, weight) in edges {
+            graph.add_edge(ids[a].clone(), ids[b].clone(), weight);
+        }
+        graph
+    }
+
+    /// Random graph on the same nodes drawn from `model`
+    pub fn null_model(&self, model: NullModel, seed: u64) -> CognateGraph {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edges = self.indexed_edges();
+        match model {
+            NullModel::Rewire => rewire(&mut edges, &mut rng),
+            NullModel::Configuration => edges = configuration(&edges, &mut rng),
+        }
+        self.with_indexed_edges(&edges)
+    }
+
+    /// Mean local clustering coefficient (fraction of a node's neighbor
+    /// pairs that are linked), ignoring weights, parallel edges and
+    /// self-loops; nodes with fewer than two neighbors count as 0
+    pub fn average_clustering(&self) -> f64 {
+        let n = self.graph.node_count();
+        if n == 0 {
+            return 0.0;
+        }
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (a, b, _) in self.indexed_edges() {
+            if a != b {
+                neighbors[a].push(b);
+                neighbors[b].push(a);
+            }
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+        let total = par_sum(neighbors.par_iter().map(|list| {
+            let k = list.len();
+            if k < 2 {
+                return 0.0;
+            }
+            let links: usize = list
+                .iter()
+                .enumerate()
+                .map(|(i, &u)| {
+                    list[i + 1..]
+                        .iter()
+                        .filter(|v| neighbors[u].binary_search(v).is_ok())
+                        .count()
+                })
+                .sum();
+            2.0 * links as f64 / (k * (k - 1)) as f64
+        }));
+        total / n as f64
+    }
+
+    /// Modularity of the Louvain partition at `resolution`
+    fn louvain_modularity(&self, resolution: f64) -> f64 {
+        self.louvain_hierarchy(resolution)
+            .last()
+            .map_or(0.0, |level| level.modularity)
+    }
+
+    /// Modularity and average clustering of this graph and of `n_samples`
+    /// random graphs from `model`, computed in parallel
+    pub fn null_model_stats(
+        &self,
+        model: NullModel,
+        n_samples: usize,
+        resolution: f64,
+        seed: u64,
+    ) -> NullModelStats {
+        let (modularity, clustering) = (0..n_samples as u64)
+            .into_par_iter()
+            .map(|i| {
+                let sample = self.null_model(model, seed.wrapping_add(i));
+                (
+                    sample.louvain_modularity(resolution),
+                    sample.average_clustering(),
+                )
+            })
+            .unzip();
+        NullModelStats {
+            observed_modularity: self.louvain_modularity(resolution),
+            observed_clustering: self.average_clustering(),
+            modularity,
+            clustering,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn degrees(graph: &CognateGraph) -> Vec<usize> {
+        graph
+            .graph
+            .node_indices()
+            .map(|node| graph.graph.neighbors(node).count())
+            .collect()
+    }
+
+    #[test]
+    fn test_null_models() {
+        // Two 4-cliques joined by one bridge
+        let mut edges = Vec::new();
+        for group in [["a", "b", "c", "d"], ["e", "f", "g", "h"]] {
+            for (i, a) in group.iter().enumerate() {
+                for b in &group[i + 1..] {
+                    edges.push(SimilarityEdge::new(a.to_string(), b.to_string(), 0.9));
+                }
+            }
+        }
+        edges.push(SimilarityEdge::new("d".to_string(), "e".to_string(), 0.5));
+        let graph = CognateGraph::from_edges(edges, 0.0);
+        assert!((graph.average_clustering() - 7.0 / 8.0).abs() < 1e-12);
+
+        let rewired = graph.null_model(NullModel::Rewire, 7);
+        assert_eq!(degrees(&rewired), degrees(&graph));
+        assert_eq!(rewired.graph.edge_count(), graph.graph.edge_count());
+        assert_ne!(rewired.edge_list(), graph.edge_list());
+        let configuration = graph.null_model(NullModel::Configuration, 7);
+        assert_eq!(configuration.graph.node_count(), 8);
+
+        let stats = graph.null_model_stats(NullModel::Rewire, 20, 1.0, 0);
+        assert_eq!(stats.modularity.len(), 20);
+        assert!(stats.observed_modularity > 0.3);
+        assert!(stats.modularity_p_value() < 0.2);
+        assert_eq!(stats, graph.null_model_stats(NullModel::Rewire, 20, 1.0, 0));
+        assert_eq!(empirical_p_value(1.0, &[0.5, 1.0, 2.0]), 0.75);
+        assert!("erdos".parse::<NullModel>().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Orthographic fallback for datasets without IPA transcriptions.
+//!
+//! Forms are normalized (optional lowercasing and diacritic stripping),
+//! segmented into graphemes or words, and compared with the same normalized
+//! edit similarity used for IPA strings.
+
+use rayon::prelude::*;
+use std::str::FromStr;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::phonetic::levenshtein;
+
+/// Segmentation unit for orthographic forms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrthoUnit {
+    /// Extended grapheme clusters (whitespace dropped)
+    Grapheme,
+    /// Unicode words (punctuation and whitespace dropped)
+    Word,
+}
+
+impl FromStr for OrthoUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grapheme" => Ok(OrthoUnit::Grapheme),
+            "word" => Ok(OrthoUnit::Word),
+            other => Err(format!(
+                "unknown orthographic unit '{}' (expected grapheme or word)",
+                other
+            )),
+        }
+    }
+}
+
+/// Normalization and segmentation settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrthographyOptions {
+    pub casefold: bool,
+    pub strip_diacritics: bool,
+    pub unit: OrthoUnit,
+}
+
+impl Default for OrthographyOptions {
+    fn default() -> Self {
+        Self {
+            casefold: true,
+            strip_diacritics: false,
+            unit: OrthoUnit::Grapheme,
+        }
+    }
+}
+
+/// Normalize to NFC, optionally dropping combining marks and lowercasing
+pub fn normalize_orthography(text: &str, options: &OrthographyOptions) -> String {
+    let normalized: String = if options.strip_diacritics {
+        text.nfd()
+            .filter(|&c| !is_combining_mark(c))
+            .nfc()
+            .collect()
+    } else {
+        text.nfc().collect()
+    };
+
+    if options.casefold {
+        normalized.to_lowercase()
+    } else {
+        normalized
+    }
+}
+
+/// Normalize and split a form into comparison units
+pub fn segment_orthography(text: &str, options: &OrthographyOptions) -> Vec<String> {
+    let normalized = normalize_orthography(text, options);
+    match options.unit {
+        OrthoUnit::Grapheme => normalized
+            .graphemes(true)
+            .filter(|g| !g.chars().all(char::is_whitespace))
+            .map(str::to_string)
+            .collect(),
+        OrthoUnit::Word => normalized.unicode_words().map(str::to_string).collect(),
+    }
+}
+
+/// Normalized edit similarity (1 - distance / max length) of two forms
+pub fn orthographic_similarity(a: &str, b: &str, options: &OrthographyOptions) -> f64 {
+    let segments_a = segment_orthography(a, options);
+    let segments_b = segment_orthography(b, options);
+    let refs_a: Vec<&str> = segments_a.iter().map(String::as_str).collect();
+    let refs_b: Vec<&str> = segments_b.iter().map(String::as_str).collect();
+
+    let max_len = refs_a.len().max(refs_b.len()) as f64;
+    if max_len == 0.0 {
+        1.0
+    } else {
+        1.0 - levenshtein(&refs_a, &refs_b) as f64 / max_len
+    }
+}
+
+/// Batch orthographic similarity for multiple pairs (parallelized)
+pub fn batch_orthographic_similarity(
+    pairs: &[(String, String)],
+    options: &OrthographyOptions,
+) -> Vec<f64> {
+    pairs
+        .par_iter()
+        .map(|(a, b)| orthographic_similarity(a, b, options))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_options() {
+        let options = OrthographyOptions {
+            strip_diacritics: true,
+            ..Default::default()
+        };
+        assert_eq!(normalize_orthography("Ça Fête", &options), "ca fete");
+        assert_eq!(
+            normalize_orthography("Ça", &OrthographyOptions::default()),
+            "ça"
+        );
+
+        // Decomposed input segments the same as precomposed
+        let decomposed = "e\u{301}te\u{301}";
+        assert_eq!(
+            segment_orthography(decomposed, &OrthographyOptions::default()),
+            vec!["é", "t", "é"]
+        );
+    }
+
+    #[test]
+    fn test_orthographic_similarity() {
+        let options = OrthographyOptions {
+            strip_diacritics: true,
+            ..Default::default()
+        };
+        assert_eq!(orthographic_similarity("Vater", "väter", &options), 1.0);
+
+        let words = OrthographyOptions {
+            unit: OrthoUnit::Word,
+            ..Default::default()
+        };
+        let sim = orthographic_similarity("the old man", "the young man", &words);
+        assert!((sim - 2.0 / 3.0).abs() < 1e-12);
+    }
+}
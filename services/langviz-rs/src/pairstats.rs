@@ -0,0 +1,197 @@
+//! Similarity distribution of a random sample of word pairs.
+//!
+//! Before an O(n²) similarity job it pays to know what the scores look
+//! like: which threshold keeps the top 1% of pairs, and how many edges that
+//! would make. `sample_pair_stats` draws a fixed number of distinct pairs
+//! uniformly with reservoir sampling (Li's Algorithm L) over the implicit
+//! stream of all n(n-1)/2 pair indices, jumping straight between
+//! replacements so only O(k log(N/k)) random numbers are drawn and no pair
+//! list is built, then scores just those pairs.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::phonetic::SimilarityMetric;
+
+/// Quantiles reported by `sample_pair_stats`
+pub const PAIR_QUANTILES: [f64; 7] = [0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairSampleStats {
+    /// Pairs among all the words
+    pub total_pairs: u64,
+    /// Sampled similarities, ascending
+    pub similarities: Vec<f64>,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// (q, nearest-rank quantile) for each of `PAIR_QUANTILES`
+    pub quantiles: Vec<(f64, f64)>,
+}
+
+impl PairSampleStats {
+    /// Share of sampled pairs scoring at or above `threshold`
+    pub fn fraction_above(&self, threshold: f64) -> f64 {
+        if self.similarities.is_empty() {
+            return 0.0;
+        }
+        let below = self.similarities.partition_point(|&s| s < threshold);
+        (self.similarities.len() - below) as f64 / self.similarities.len() as f64
+    }
+
+    /// Estimated number of pairs, over all words, at or above `threshold`
+    pub fn estimated_edges(&self, threshold: f64) -> f64 {
+        self.fraction_above(threshold) * self.total_pairs as f64
+    }
+}
+
+/// Score `sample_size` distinct random pairs of `ipa_strings` (every pair
+/// when there are fewer) and summarize their similarities
+pub fn sample_pair_stats(
+    ipa_strings: &[String],
+    sample_size: usize,
+    metric: SimilarityMetric,
+    seed: u64,
+) -> Result<PairSampleStats, String> {
+    if sample_size == 0 {
+        return Err("sample_size must be positive".to_string());
+    }
+    let n = ipa_strings.len() as u64;
+    let total_pairs = n * n.saturating_sub(1) / 2;
+    if total_pairs == 0 {
+        return Err(format!("need at least 2 words, got {}", n));
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let sampled = reservoir_sample(total_pairs, sample_size as u64, &mut rng);
+
+    let mut similarities: Vec<f64> = sampled
+        .par_iter()
+        .map(|&p| {
+            let (i, j) = pair_at(n, p);
+            metric.similarity(&ipa_strings[i as usize], &ipa_strings[j as usize])
+        })
+        .collect();
+    similarities.sort_by(f64::total_cmp);
+
+    let count = similarities.len() as f64;
+    let mean = similarities.iter().sum::<f64>() / count;
+    let variance = similarities.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+    let quantiles = PAIR_QUANTILES
+        .iter()
+        .map(|&q| {
+            let rank = (q * count).ceil() as usize;
+            (q, similarities[rank.clamp(1, similarities.len()) - 1])
+        })
+        .collect();
+    Ok(PairSampleStats {
+        total_pairs,
+        mean,
+        std_dev: variance.sqrt(),
+        min: similarities[0],
+        max: similarities[similarities.len() - 1],
+        quantiles,
+        similarities,
+    })
+}
+
+/// `k` distinct indices drawn uniformly from `0..total` (all of them when
+/// `k >= total`) by Algorithm L
+fn reservoir_sample(total: u64, k: u64, rng: &mut StdRng) -> Vec<u64> {
+    if k >= total {
+        return (0..total).collect();
+    }
+    let mut reservoir: Vec<u64> = (0..k).collect();
+    if k == 0 {
+        return reservoir;
+    }
+    // Open interval (0, 1), so the logarithms stay finite
+    let mut uniform = || 1.0 - rng.gen::<f64>();
+    let mut w = (uniform().ln() / k as f64).exp();
+    let mut next = k - 1;
+    loop {
+        let skip = (uniform().ln() / (1.0 - w).ln()).floor();
+        if !skip.is_finite() || skip >= (total - next) as f64 {
+            break;
+        }
+        next += skip as u64 + 1;
+        if next >= total {
+            break;
+        }
+        let slot = (uniform() * k as f64) as usize;
+        reservoir[slot.min(k as usize - 1)] = next;
+        w *= (uniform().ln() / k as f64).exp();
+    }
+    reservoir
+}
+
+/// The `p`-th pair (i < j) of `n` items in row-major order
+fn pair_at(n: u64, p: u64) -> (u64, u64) {
+    // Pairs before row i: i * (2n - i - 1) / 2
+    let before = |i: u64| i * (2 * n - i - 1) / 2;
+    let m = (2 * n - 1) as f64;
+    let mut i = ((m - (m * m - 8.0 * p as f64).max(0.0).sqrt()) / 2.0) as u64;
+    i = i.min(n - 2);
+    while i > 0 && before(i) > p {
+        i -= 1;
+    }
+    while i + 2 < n && before(i + 1) <= p {
+        i += 1;
+    }
+    (i, i + 1 + p - before(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_indexing_and_reservoir() {
+        let n = 7;
+        let mut expected = Vec::new();
+        for i in 0..n {
+            for j in i + 1..n {
+                expected.push((i, j));
+            }
+        }
+        let decoded: Vec<(u64, u64)> = (0..expected.len() as u64).map(|p| pair_at(n, p)).collect();
+        assert_eq!(decoded, expected);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut sample = reservoir_sample(1_000_000, 500, &mut rng);
+        sample.sort_unstable();
+        sample.dedup();
+        assert_eq!(sample.len(), 500);
+        assert!(sample.iter().all(|&p| p < 1_000_000));
+        // Not stuck on the initial fill
+        assert!(sample[499] > 10_000);
+        assert_eq!(reservoir_sample(5, 10, &mut rng), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_sample_pair_stats() {
+        let words: Vec<String> = ["pater", "fater", "mater", "brater", "duo", "tres"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        // Fewer pairs than the sample size: every pair is scored
+        let all = sample_pair_stats(&words, 100, SimilarityMetric::Levenshtein, 0).unwrap();
+        assert_eq!(all.total_pairs, 15);
+        assert_eq!(all.similarities.len(), 15);
+        assert_eq!(all.max, 0.8);
+        assert_eq!(all.quantiles.len(), PAIR_QUANTILES.len());
+        assert!(all.quantiles.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(all.estimated_edges(2.0), 0.0);
+        assert_eq!(all.fraction_above(0.0), 1.0);
+
+        let some = sample_pair_stats(&words, 5, SimilarityMetric::Levenshtein, 1).unwrap();
+        assert_eq!(some.similarities.len(), 5);
+        assert_eq!(
+            some,
+            sample_pair_stats(&words, 5, SimilarityMetric::Levenshtein, 1).unwrap()
+        );
+        assert!(sample_pair_stats(&words[..1], 5, SimilarityMetric::Levenshtein, 0).is_err());
+        assert!(sample_pair_stats(&words, 0, SimilarityMetric::Levenshtein, 0).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! Partial cognate detection: cognacy computed between morphemes rather
+//! than whole words, so compounds that share only some of their parts
+//! (common in Southeast Asian and compound-heavy data) are still linked.
+//!
+//! Words are split into morphemes either at user-provided boundary markers
+//! (`+`, `-`, `_`, `◦` or whitespace) or automatically at syllable
+//! boundaries (`.` and the end of a tone mark). Within each concept,
+//! morphemes of different words whose phonetic similarity reaches the
+//! threshold are linked, and the connected groups become partial cognate
+//! sets.
+
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use crate::cluster::UnionFind;
+use crate::phonetic::phonetic_distance;
+use crate::types::WeightedEdge;
+
+/// Characters that separate user-annotated morphemes
+const MORPHEME_MARKERS: [char; 4] = ['+', '-', '_', '◦'];
+
+/// How words are split into morphemes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MorphemeSegmentation {
+    /// Only at explicit boundary markers and whitespace
+    #[default]
+    Markers,
+    /// Markers plus syllable boundaries (`.` and after tone marks)
+    Syllables,
+}
+
+impl FromStr for MorphemeSegmentation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markers" => Ok(MorphemeSegmentation::Markers),
+            "syllables" => Ok(MorphemeSegmentation::Syllables),
+            other => Err(format!(
+                "unknown morpheme segmentation '{}' (expected markers or syllables)",
+                other
+            )),
+        }
+    }
+}
+
+/// Tone letters and tone numbers (superscript or ASCII) close a syllable
+fn is_tone_mark(c: char) -> bool {
+    matches!(c, '\u{02E5}'..='\u{02E9}' | '\u{A700}'..='\u{A71F}' | '⁰' | '¹' | '²' | '³')
+        || matches!(c, '\u{2074}'..='\u{2079}')
+        || c.is_ascii_digit()
+}
+
+/// Split a form into non-empty morphemes
+pub fn split_morphemes(form: &str, segmentation: MorphemeSegmentation) -> Vec<String> {
+    let mut morphemes = Vec::new();
+    let mut current = String::new();
+    let mut after_tone = false;
+
+    for c in form.chars() {
+        let boundary = MORPHEME_MARKERS.contains(&c)
+            || c.is_whitespace()
+            || (segmentation == MorphemeSegmentation::Syllables && c == '.');
+        if boundary {
+            morphemes.push(std::mem::take(&mut current));
+            after_tone = false;
+            continue;
+        }
+
+        let tone = is_tone_mark(c);
+        if segmentation == MorphemeSegmentation::Syllables && after_tone && !tone {
+            morphemes.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+        after_tone = tone;
+    }
+    morphemes.push(current);
+
+    morphemes.retain(|m| !m.is_empty());
+    morphemes
+}
+
+/// Partial cognate settings
+#[derive(Debug, Clone, Copy)]
+pub struct PartialCognateParams {
+    /// Minimum morpheme similarity (normalized edit similarity) to link
+    pub threshold: f64,
+    pub segmentation: MorphemeSegmentation,
+}
+
+impl Default for PartialCognateParams {
+    fn default() -> Self {
+        Self {
+            threshold: 0.6,
+            segmentation: MorphemeSegmentation::Markers,
+        }
+    }
+}
+
+/// Partial cognate annotation for one word
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialCognates {
+    pub id: String,
+    pub language: String,
+    pub concept: String,
+    pub morphemes: Vec<String>,
+    /// Partial cognate set of each morpheme (parallel to `morphemes`)
+    pub cognate_ids: Vec<usize>,
+}
+
+/// Morpheme-level cognate sets for (id, language, concept, form) entries,
+/// returned in input order. Set IDs are numbered by concept (sorted), then
+/// by first appearance.
+pub fn detect_partial_cognates(
+    entries: &[(String, String, String, String)],
+    params: &PartialCognateParams,
+) -> Vec<PartialCognates> {
+    let morphemes: Vec<Vec<String>> = entries
+        .iter()
+        .map(|(_, _, _, form)| split_morphemes(form, params.segmentation))
+        .collect();
+
+    let mut by_concept: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, (_, _, concept, _)) in entries.iter().enumerate() {
+        by_concept.entry(concept.as_str()).or_default().push(i);
+    }
+
+    // Cluster each concept's morphemes independently: (word, morpheme, local set)
+    let clustered: Vec<Vec<(usize, usize, usize)>> = by_concept
+        .values()
+        .collect::<Vec<_>>()
+        .par_iter()
+        .map(|words| {
+            let slots: Vec<(usize, usize)> = words
+                .iter()
+                .flat_map(|&w| (0..morphemes[w].len()).map(move |m| (w, m)))
+                .collect();
+
+            let mut uf = UnionFind::new(slots.len());
+            for a in 0..slots.len() {
+                for b in (a + 1)..slots.len() {
+                    let ((word_a, m_a), (word_b, m_b)) = (slots[a], slots[b]);
+                    if word_a == word_b {
+                        continue;
+                    }
+                    let similarity =
+                        phonetic_distance(&morphemes[word_a][m_a], &morphemes[word_b][m_b]);
+                    if similarity >= params.threshold {
+                        uf.union(a, b);
+                    }
+                }
+            }
+
+            let mut local_ids: BTreeMap<usize, usize> = BTreeMap::new();
+            slots
+                .iter()
+                .enumerate()
+                .map(|(slot, &(word, morpheme))| {
+                    let root = uf.find(slot);
+                    let next = local_ids.len();
+                    (word, morpheme, *local_ids.entry(root).or_insert(next))
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut cognate_ids: Vec<Vec<usize>> = morphemes.iter().map(|m| vec![0; m.len()]).collect();
+    let mut offset = 0;
+    for assignments in clustered {
+        let mut sets = 0;
+        for (word, morpheme, local) in assignments {
+            cognate_ids[word][morpheme] = offset + local;
+            sets = sets.max(local + 1);
+        }
+        offset += sets;
+    }
+
+    entries
+        .iter()
+        .zip(morphemes)
+        .zip(cognate_ids)
+        .map(
+            |(((id, language, concept, _), morphemes), cognate_ids)| PartialCognates {
+                id: id.clone(),
+                language: language.clone(),
+                concept: concept.clone(),
+                morphemes,
+                cognate_ids,
+            },
+        )
+        .collect()
+}
+
+/// Word-level edges between words of the same concept that share at least
+/// one partial cognate set, weighted by the Jaccard overlap of their sets
+pub fn partial_cognate_edges(words: &[PartialCognates]) -> Vec<WeightedEdge> {
+    let mut by_concept: BTreeMap<&str, Vec<&PartialCognates>> = BTreeMap::new();
+    for word in words {
+        by_concept
+            .entry(word.concept.as_str())
+            .or_default()
+            .push(word);
+    }
+
+    let mut edges = Vec::new();
+    for group in by_concept.values() {
+        for (i, a) in group.iter().enumerate() {
+            for b in &group[i + 1..] {
+                let sets_a: BTreeSet<usize> = a.cognate_ids.iter().copied().collect();
+                let sets_b: BTreeSet<usize> = b.cognate_ids.iter().copied().collect();
+                let shared = sets_a.intersection(&sets_b).count();
+                if shared > 0 {
+                    let union = sets_a.union(&sets_b).count();
+                    edges.push((a.id.clone(), b.id.clone(), shared as f64 / union as f64));
+                }
+            }
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        id: &str,
+        language: &str,
+        concept: &str,
+        form: &str,
+    ) -> (String, String, String, String) {
+        (id.into(), language.into(), concept.into(), form.into())
+    }
+
+    #[test]
+    fn test_split_morphemes() {
+        let markers = MorphemeSegmentation::Markers;
+        assert_eq!(split_morphemes("nam+ta", markers), vec!["nam", "ta"]);
+        assert_eq!(split_morphemes("ta-ma ho", markers), vec!["ta", "ma", "ho"]);
+        assert_eq!(split_morphemes("nam˧˥ta˨", markers), vec!["nam˧˥ta˨"]);
+
+        let syllables = MorphemeSegmentation::Syllables;
+        assert_eq!(split_morphemes("nam˧˥ta˨", syllables), vec!["nam˧˥", "ta˨"]);
+        assert_eq!(
+            split_morphemes("ma⁵⁵.tsʰi²¹", syllables),
+            vec!["ma⁵⁵", "tsʰi²¹"]
+        );
+        assert!(split_morphemes("+", syllables).is_empty());
+    }
+
+    #[test]
+    fn test_detect_partial_cognates() {
+        // "sun" as "eye of day" compounds sharing only the second morpheme
+        let entries = vec![
+            entry("w1", "A", "sun", "mata+hari"),
+            entry("w2", "B", "sun", "mata+ari"),
+            entry("w3", "C", "sun", "lo+hari"),
+            entry("w4", "C", "water", "wai"),
+            entry("w5", "D", "water", "wai"),
+        ];
+        let words = detect_partial_cognates(&entries, &PartialCognateParams::default());
+
+        assert_eq!(words[0].morphemes, vec!["mata", "hari"]);
+        assert_eq!(words[0].cognate_ids, words[1].cognate_ids);
+        assert_eq!(words[2].cognate_ids[1], words[0].cognate_ids[1]);
+        assert_ne!(words[2].cognate_ids[0], words[0].cognate_ids[0]);
+        assert_eq!(words[3].cognate_ids, words[4].cognate_ids);
+        assert_ne!(words[3].cognate_ids[0], words[0].cognate_ids[0]);
+
+        let edges = partial_cognate_edges(&words);
+        let weight = |a: &str, b: &str| edges.iter().find(|e| e.0 == a && e.1 == b).map(|e| e.2);
+        assert_eq!(weight("w1", "w2"), Some(1.0));
+        assert_eq!(weight("w1", "w3"), Some(1.0 / 3.0));
+        assert_eq!(weight("w4", "w5"), Some(1.0));
+        assert_eq!(edges.len(), 4);
+    }
+}
@@ -0,0 +1,148 @@
+//! Shortest-path statistics (diameter, mean path length) in hops.
+//!
+//! Exact all-pairs BFS is quadratic, so large graphs are estimated from BFS
+//! runs out of a seeded sample of source nodes.
+
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+use crate::graph::CognateGraph;
+
+/// Diameter and mean shortest-path length over connected node pairs
+#[derive(Debug, Clone)]
+pub struct PathStats {
+    /// Longest shortest path (a lower bound when estimated)
+    pub diameter: usize,
+    /// Mean shortest-path length over reachable ordered pairs
+    pub avg_path_length: f64,
+    /// Whether every node was used as a BFS source
+    pub exact: bool,
+    /// Number of BFS sources used
+    pub num_sources: usize,
+}
+
+/// Hop distances from `source`; unreachable nodes are `usize::MAX`
+pub(crate) fn bfs_distances(adjacency: &[Vec<usize>], source: usize) -> Vec<usize> {
+    let mut dist = vec![usize::MAX; adjacency.len()];
+    dist[source] = 0;
+    let mut queue = VecDeque::from([source]);
+
+    while let Some(u) = queue.pop_front() {
+        for &v in &adjacency[u] {
+            if dist[v] == usize::MAX {
+                dist[v] = dist[u] + 1;
+                queue.push_back(v);
+            }
+        }
+    }
+
+    dist
+}
+
+/// Farthest reachable node and its distance
+fn farthest(dist: &[usize]) -> (usize, usize) {
+    dist.iter()
+        .enumerate()
+        .filter(|(_, &d)| d != usize::MAX)
+        .max_by_key(|(_, &d)| d)
+        .map(|(i, &d)| (i, d))
+        .unwrap_or((0, 0))
+}
+
+impl CognateGraph {
+    /// Diameter and average shortest-path length in hops. Graphs with at
+    /// most `max_sources` nodes are computed exactly; larger graphs use
+    /// `max_sources` sampled BFS sources plus a double sweep from the
+    /// farthest node found, which tightens the diameter bound.
+    pub fn path_stats(&self, max_sources: usize, seed: u64) -> PathStats {
+        let n = self.graph.node_count();
+        if n == 0 {
+            return PathStats {
+                diameter: 0,
+                avg_path_length: 0.0,
+                exact: true,
+                num_sources: 0,
+            };
+        }
+
+        let adjacency = self.adjacency_lists();
+        let exact = n <= max_sources;
+        let sources: Vec<usize> = if exact {
+            (0..n).collect()
+        } else {
+            let mut rng = StdRng::seed_from_u64(seed);
+            index::sample(&mut rng, n, max_sources.max(1)).into_vec()
+        };
+
+        let (diameter, total, pairs, far_node) = sources
+            .par_iter()
+            .map(|&s| {
+                let dist = bfs_distances(&adjacency, s);
+                let (far, ecc) = farthest(&dist);
+                let (total, pairs) = dist
+                    .iter()
+                    .filter(|&&d| d != usize::MAX && d > 0)
+                    .fold((0usize, 0usize), |(t, p), &d| (t + d, p + 1));
+                (ecc, total, pairs, far)
+            })
+            .reduce(
+                || (0, 0, 0, 0),
+                |a, b| {
+                    let (ecc, far) = if b.0 > a.0 { (b.0, b.3) } else { (a.0, a.3) };
+                    (ecc, a.1 + b.1, a.2 + b.2, far)
+                },
+            );
+
+        let diameter = if exact {
+            diameter
+        } else {
+            // Double sweep: the farthest node is a good eccentricity candidate
+            let (_, ecc) = farthest(&bfs_distances(&adjacency, far_node));
+            diameter.max(ecc)
+        };
+
+        PathStats {
+            diameter,
+            avg_path_length: if pairs > 0 {
+                total as f64 / pairs as f64
+            } else {
+                0.0
+            },
+            exact,
+            num_sources: sources.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn path(n: usize) -> CognateGraph {
+        let edges = (0..n - 1)
+            .map(|i| SimilarityEdge::new(format!("w{}", i), format!("w{}", i + 1), 0.9))
+            .collect();
+        CognateGraph::from_edges(edges, 0.5)
+    }
+
+    #[test]
+    fn test_exact_path_stats() {
+        let stats = path(4).path_stats(100, 0);
+        assert!(stats.exact);
+        assert_eq!(stats.diameter, 3);
+        // Pair distances on a 4-path: 1,1,1,2,2,3 -> mean 10/6
+        assert!((stats.avg_path_length - 10.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sampled_diameter_uses_double_sweep() {
+        let stats = path(500).path_stats(5, 3);
+        assert!(!stats.exact);
+        assert_eq!(stats.num_sources, 5);
+        assert_eq!(stats.diameter, 499);
+    }
+}
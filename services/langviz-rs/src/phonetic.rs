@@ -2,71 +2,89 @@
 
 use ndarray::{Array2, Axis};
 use rayon::prelude::*;
-use unicode_segmentation::UnicodeSegmentation;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::types::{Alignment, EditOp, IPASegment};
+// Segmentation and the unit-cost kernels live in the shared
+// `langviz_phonetic_core` crate, which the `langviz_phonetic` shim wraps too
+pub(crate) use langviz_phonetic_core::{is_ipa_modifier, levenshtein};
+pub use langviz_phonetic_core::{lcs_ratio, phonetic_distance, segment_ipa};
 
-/// Compute normalized Levenshtein distance between IPA strings
-pub fn phonetic_distance(ipa_a: &str, ipa_b: &str) -> f64 {
-    let segments_a: Vec<&str> = ipa_a.graphemes(true).collect();
-    let segments_b: Vec<&str> = ipa_b.graphemes(true).collect();
+use crate::features::{feature_cost, feature_index, segment_features, FeatureDistanceTable};
+use crate::soundclass::SoundClassCosts;
+use crate::types::{Alignment, EditOp, IPASegment};
 
-    let distance = levenshtein(&segments_a, &segments_b);
+/// `phonetic_distance` with weighted edits from a `CostModel`: one minus
+/// the cheapest edit cost over the longer segment count, floored at 0
+pub fn phonetic_distance_with_costs(ipa_a: &str, ipa_b: &str, costs: &CostModel) -> f64 {
+    let segments_a = segment_ipa(ipa_a);
+    let segments_b = segment_ipa(ipa_b);
     let max_len = segments_a.len().max(segments_b.len()) as f64;
-
     if max_len == 0.0 {
-        1.0 // Both empty = perfect match
-    } else {
-        1.0 - (distance as f64 / max_len)
-    }
-}
-
-/// Standard Levenshtein distance using dynamic programming
-fn levenshtein(a: &[&str], b: &[&str]) -> usize {
-    let len_a = a.len();
-    let len_b = b.len();
-
-    if len_a == 0 {
-        return len_b;
-    }
-    if len_b == 0 {
-        return len_a;
+        return 1.0;
     }
 
-    let mut prev_row: Vec<usize> = (0..=len_b).collect();
-    let mut curr_row = vec![0; len_b + 1];
-
-    for (i, seg_a) in a.iter().enumerate() {
-        curr_row[0] = i + 1;
-
-        for (j, seg_b) in b.iter().enumerate() {
-            let cost = if seg_a == seg_b { 0 } else { 1 };
+    let mut prev_row: Vec<f64> = std::iter::once(0.0)
+        .chain(segments_b.iter().scan(0.0, |total, seg| {
+            *total += costs.indel_cost(seg);
+            Some(*total)
+        }))
+        .collect();
+    let mut curr_row = vec![0.0; segments_b.len() + 1];
+    let substitution = costs.substitution.cost_grid(&segments_a, &segments_b);
 
-            curr_row[j + 1] = std::cmp::min(
-                std::cmp::min(curr_row[j] + 1, prev_row[j + 1] + 1),
-                prev_row[j] + cost,
+    for (i, seg_a) in segments_a.iter().enumerate() {
+        let delete = costs.indel_cost(seg_a);
+        curr_row[0] = prev_row[0] + delete;
+        for (j, seg_b) in segments_b.iter().enumerate() {
+            curr_row[j + 1] = f64::min(
+                f64::min(curr_row[j] + costs.indel_cost(seg_b), prev_row[j + 1] + delete),
+                prev_row[j] + substitution[[i, j]],
             );
         }
-
         std::mem::swap(&mut prev_row, &mut curr_row);
     }
 
-    prev_row[len_b]
+    (1.0 - prev_row[segments_b.len()] / max_len).max(0.0)
 }
 
 /// Batch compute phonetic distances for multiple pairs (parallelized)
 pub fn batch_phonetic_distance(pairs: Vec<(String, String)>) -> Vec<f64> {
+    langviz_phonetic_core::batch_phonetic_distance(&pairs)
+}
+
+/// `batch_phonetic_distance` with a `CostModel`
+pub fn batch_phonetic_distance_with_costs(
+    pairs: &[(String, String)],
+    costs: &CostModel,
+) -> Vec<f64> {
     pairs
         .par_iter()
-        .map(|(a, b)| phonetic_distance(a, b))
+        .map(|(a, b)| phonetic_distance_with_costs(a, b, costs))
         .collect()
 }
 
 /// Feature-weighted phonetic distance using 24D feature vectors
 pub fn feature_weighted_distance(segments_a: &[IPASegment], segments_b: &[IPASegment]) -> f64 {
-    let len_a = segments_a.len();
-    let len_b = segments_b.len();
+    unit_indel_distance(segments_a.len(), segments_b.len(), |i, j| {
+        let (seg_a, seg_b) = (&segments_a[i], &segments_b[j]);
+        // Substitution cost is feature distance
+        if seg_a.grapheme == seg_b.grapheme {
+            0.0
+        } else {
+            seg_a.feature_distance(seg_b)
+        }
+    })
+}
 
+/// Edit distance with unit insertions/deletions and `substitution(i, j)`
+/// costs between segment i of a and j of b, over the longer length
+pub(crate) fn unit_indel_distance(
+    len_a: usize,
+    len_b: usize,
+    substitution: impl Fn(usize, usize) -> f64,
+) -> f64 {
     if len_a == 0 && len_b == 0 {
         return 0.0;
     }
@@ -85,18 +103,10 @@ pub fn feature_weighted_distance(segments_a: &[IPASegment], segments_b: &[IPASeg
         dp[[0, j]] = j as f64;
     }
 
-    // Fill DP table with feature-weighted costs
+    // Fill DP table with substitution costs
     for i in 1..=len_a {
         for j in 1..=len_b {
-            let seg_a = &segments_a[i - 1];
-            let seg_b = &segments_b[j - 1];
-
-            // Substitution cost is feature distance
-            let subst_cost = if seg_a.grapheme == seg_b.grapheme {
-                0.0
-            } else {
-                seg_a.feature_distance(seg_b)
-            };
+            let subst_cost = substitution(i - 1, j - 1);
 
             dp[[i, j]] = f64::min(
                 f64::min(
@@ -116,28 +126,32 @@ pub fn feature_weighted_distance(segments_a: &[IPASegment], segments_b: &[IPASeg
 
 /// Dynamic Time Warping alignment for phonetic sequences
 pub fn dtw_align(ipa_a: &str, ipa_b: &str) -> Alignment {
-    let segments_a: Vec<String> = ipa_a.graphemes(true).map(|s| s.to_string()).collect();
-    let segments_b: Vec<String> = ipa_b.graphemes(true).map(|s| s.to_string()).collect();
-
-    let len_a = segments_a.len();
-    let len_b = segments_b.len();
+    dtw_align_with_costs(ipa_a, ipa_b, &CostModel::default())
+}
 
-    if len_a == 0 || len_b == 0 {
-        return Alignment::new(segments_a, segments_b, vec![], 0.0);
-    }
+/// DTW alignment with per-cell match costs from a `CostModel`'s
+/// substitution table (warping steps carry no separate indel cost)
+pub fn dtw_align_with_costs(ipa_a: &str, ipa_b: &str, costs: &CostModel) -> Alignment {
+    dtw_align_banded(ipa_a, ipa_b, costs, None)
+}
 
-    // DTW cost matrix
+/// Cumulative DTW cost matrix over a match-cost grid. With a Sakoe-Chiba
+/// `band`, only cells within that many segments of the diagonal are filled
+/// (the rest stay infinite); the band is widened to the length difference
+/// so the final cell stays reachable.
+fn dtw_matrix(substitution: &Array2<f64>, band: Option<usize>) -> Array2<f64> {
+    let (len_a, len_b) = substitution.dim();
+    let band = band.map(|band| band.max(len_a.abs_diff(len_b)));
     let mut cost = Array2::<f64>::from_elem((len_a + 1, len_b + 1), f64::INFINITY);
     cost[[0, 0]] = 0.0;
 
-    // Fill cost matrix
     for i in 1..=len_a {
-        for j in 1..=len_b {
-            let match_cost = if segments_a[i - 1] == segments_b[j - 1] {
-                0.0
-            } else {
-                1.0
-            };
+        let (first, last) = match band {
+            Some(band) => (i.saturating_sub(band).max(1), (i + band).min(len_b)),
+            None => (1, len_b),
+        };
+        for j in first..=last {
+            let match_cost = substitution[[i - 1, j - 1]];
 
             cost[[i, j]] = match_cost
                 + f64::min(
@@ -146,11 +160,49 @@ pub fn dtw_align(ipa_a: &str, ipa_b: &str) -> Alignment {
                 );
         }
     }
+    cost
+}
+
+/// Cost of the (optionally banded) DTW alignment, without reconstructing
+/// its path
+pub fn dtw_cost(ipa_a: &str, ipa_b: &str, costs: &CostModel, band: Option<usize>) -> f64 {
+    let segments_a = segment_ipa(ipa_a);
+    let segments_b = segment_ipa(ipa_b);
+    if segments_a.is_empty() || segments_b.is_empty() {
+        return 0.0;
+    }
+    let substitution = costs.substitution.cost_grid(&segments_a, &segments_b);
+    dtw_matrix(&substitution, band)[[segments_a.len(), segments_b.len()]]
+}
+
+/// `dtw_align_with_costs` restricted to a Sakoe-Chiba `band` (see
+/// `dtw_matrix`), bounding the work on long sequences; None searches all
+/// cells
+pub fn dtw_align_banded(
+    ipa_a: &str,
+    ipa_b: &str,
+    costs: &CostModel,
+    band: Option<usize>,
+) -> Alignment {
+    let segments_a = segment_ipa(ipa_a);
+    let segments_b = segment_ipa(ipa_b);
+
+    let len_a = segments_a.len();
+    let len_b = segments_b.len();
+
+    if len_a == 0 || len_b == 0 {
+        return Alignment::new(segments_a, segments_b, vec![], vec![], 0.0);
+    }
+
+    // DTW cost matrix
+    let substitution = costs.substitution.cost_grid(&segments_a, &segments_b);
+    let cost = dtw_matrix(&substitution, band);
 
     // Backtrack to find alignment path
     let mut i = len_a;
     let mut j = len_b;
     let mut operations = Vec::new();
+    let mut op_costs = Vec::new();
     let mut aligned_a = Vec::new();
     let mut aligned_b = Vec::new();
 
@@ -158,16 +210,21 @@ pub fn dtw_align(ipa_a: &str, ipa_b: &str) -> Alignment {
         if i == 0 {
             // Only insertions left
             operations.push(EditOp::Insert);
+            op_costs.push(0.0);
             aligned_a.push("-".to_string());
             aligned_b.push(segments_b[j - 1].clone());
             j -= 1;
         } else if j == 0 {
             // Only deletions left
             operations.push(EditOp::Delete);
+            op_costs.push(0.0);
             aligned_a.push(segments_a[i - 1].clone());
             aligned_b.push("-".to_string());
             i -= 1;
         } else {
+            // Every step pays the match cost of the cell it leaves
+            op_costs.push(substitution[[i - 1, j - 1]]);
+
             // Find minimum cost predecessor
             let diag = cost[[i - 1, j - 1]];
             let up = cost[[i - 1, j]];
@@ -202,45 +259,557 @@ pub fn dtw_align(ipa_a: &str, ipa_b: &str) -> Alignment {
 
     // Reverse since we backtracked
     operations.reverse();
+    op_costs.reverse();
     aligned_a.reverse();
     aligned_b.reverse();
 
-    Alignment::new(aligned_a, aligned_b, operations, cost[[len_a, len_b]])
+    Alignment::new(aligned_a, aligned_b, operations, op_costs, cost[[len_a, len_b]])
 }
 
-/// Longest Common Subsequence ratio
-pub fn lcs_ratio(ipa_a: &str, ipa_b: &str) -> f64 {
-    let segments_a: Vec<&str> = ipa_a.graphemes(true).collect();
-    let segments_b: Vec<&str> = ipa_b.graphemes(true).collect();
+/// Cost and substituted segment pairs of one DTW alignment
+#[derive(Debug, Clone, PartialEq)]
+pub struct DtwSummary {
+    pub cost: f64,
+    /// (segment_a, segment_b) of each substitution on the path, in order;
+    /// empty when the path was not reconstructed
+    pub correspondences: Vec<(String, String)>,
+}
 
-    let lcs_len = lcs_length(&segments_a, &segments_b);
-    let max_len = segments_a.len().max(segments_b.len()) as f64;
+/// DTW-align many pairs in parallel. Without `with_correspondences` only
+/// costs are computed, skipping path reconstruction.
+pub fn batch_dtw_align(
+    pairs: &[(String, String)],
+    costs: &CostModel,
+    band: Option<usize>,
+    with_correspondences: bool,
+) -> Vec<DtwSummary> {
+    pairs
+        .par_iter()
+        .map(|(a, b)| {
+            if with_correspondences {
+                let alignment = dtw_align_banded(a, b, costs, band);
+                DtwSummary {
+                    cost: alignment.cost,
+                    correspondences: alignment.extract_correspondences(),
+                }
+            } else {
+                DtwSummary {
+                    cost: dtw_cost(a, b, costs, band),
+                    correspondences: Vec::new(),
+                }
+            }
+        })
+        .collect()
+}
 
-    if max_len == 0.0 {
-        1.0
-    } else {
-        lcs_len as f64 / max_len
+/// Substitution cost between two segments, in [0, 1]
+#[derive(Debug, Clone, Default)]
+pub enum SubstitutionCost {
+    /// 0 for identical segments, 1 otherwise
+    #[default]
+    Uniform,
+    /// Share of differing articulatory features (built-in feature table)
+    FeatureWeighted,
+    /// `FeatureWeighted` costs precomputed over a segment inventory;
+    /// segments outside it are compared on the fly
+    FeatureTable(Arc<FeatureDistanceTable>),
+    /// User-supplied symmetric costs; unlisted pairs fall back to uniform
+    Matrix(HashMap<(String, String), f64>),
+    /// Cheaper substitutions within a sound class than across classes
+    SoundClasses(Arc<SoundClassCosts>),
+}
+
+impl SubstitutionCost {
+    /// Build a `Matrix` cost from (segment_a, segment_b, cost) triples
+    pub fn from_triples(costs: &[(String, String, f64)]) -> Self {
+        let mut matrix = HashMap::new();
+        for (a, b, cost) in costs {
+            matrix.insert((a.clone(), b.clone()), *cost);
+            matrix.insert((b.clone(), a.clone()), *cost);
+        }
+        SubstitutionCost::Matrix(matrix)
+    }
+
+    pub fn cost(&self, a: &str, b: &str) -> f64 {
+        if a == b {
+            return 0.0;
+        }
+        match self {
+            SubstitutionCost::Uniform => 1.0,
+            SubstitutionCost::FeatureWeighted => feature_cost(a, b),
+            SubstitutionCost::FeatureTable(table) => table.cost(a, b),
+            SubstitutionCost::Matrix(matrix) => matrix
+                .get(&(a.to_string(), b.to_string()))
+                .copied()
+                .unwrap_or(1.0),
+            SubstitutionCost::SoundClasses(costs) => costs.cost(a, b),
+        }
+    }
+
+    /// `FeatureWeighted` over the segment inventory of `forms` becomes a
+    /// `FeatureTable`, for batch jobs over those forms; other costs are
+    /// returned unchanged
+    pub fn precomputed<S: AsRef<str> + Sync>(self, forms: &[S]) -> Self {
+        match self {
+            SubstitutionCost::FeatureWeighted => {
+                SubstitutionCost::FeatureTable(Arc::new(FeatureDistanceTable::from_forms(forms)))
+            }
+            other => other,
+        }
+    }
+
+    /// Cost of every (segment of a, segment of b) cell, so aligners look
+    /// each pair up once; a `FeatureTable` resolves each segment once
+    pub(crate) fn cost_grid(&self, a: &[String], b: &[String]) -> Array2<f64> {
+        match self {
+            SubstitutionCost::FeatureTable(table) => {
+                let rows: Vec<Option<usize>> = a.iter().map(|s| table.index_of(s)).collect();
+                let cols: Vec<Option<usize>> = b.iter().map(|s| table.index_of(s)).collect();
+                Array2::from_shape_fn((a.len(), b.len()), |(i, j)| match (rows[i], cols[j]) {
+                    (Some(row), Some(col)) => table.cost_at(row, col),
+                    _ => table.cost(&a[i], &b[j]),
+                })
+            }
+            _ => Array2::from_shape_fn((a.len(), b.len()), |(i, j)| self.cost(&a[i], &b[j])),
+        }
     }
 }
 
-/// Compute length of longest common subsequence
-fn lcs_length(a: &[&str], b: &[&str]) -> usize {
-    let len_a = a.len();
-    let len_b = b.len();
+impl FromStr for SubstitutionCost {
+    type Err = String;
 
-    let mut dp = vec![vec![0; len_b + 1]; len_a + 1];
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(SubstitutionCost::Uniform),
+            "feature" | "feature_weighted" => Ok(SubstitutionCost::FeatureWeighted),
+            "sound_class" => Ok(SubstitutionCost::SoundClasses(Arc::default())),
+            other => Err(format!(
+                "unknown substitution cost '{}' (expected uniform, feature or sound_class)",
+                other
+            )),
+        }
+    }
+}
+
+/// Edit costs for the `*_with_costs` distance and alignment variants: a
+/// substitution cost per segment pair (e.g. an empirical IPA confusion
+/// matrix) and an insertion/deletion cost per segment
+#[derive(Debug, Clone)]
+pub struct CostModel {
+    pub substitution: SubstitutionCost,
+    /// Per-segment insertion/deletion costs
+    pub indel: HashMap<String, f64>,
+    /// Insertion/deletion cost of segments missing from `indel`
+    pub default_indel: f64,
+}
 
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            substitution: SubstitutionCost::Uniform,
+            indel: HashMap::new(),
+            default_indel: 1.0,
+        }
+    }
+}
+
+impl CostModel {
+    /// Build from a segment-pair cost table. Pairs are symmetric and
+    /// unlisted pairs cost 1; a pair with the gap symbol "-" on one side
+    /// sets that segment's insertion/deletion cost.
+    pub fn from_table(
+        table: &HashMap<(String, String), f64>,
+        default_indel: f64,
+    ) -> Result<Self, String> {
+        if !(default_indel.is_finite() && default_indel >= 0.0) {
+            return Err(format!("indel cost must be non-negative, got {}", default_indel));
+        }
+        let mut triples = Vec::new();
+        let mut indel = HashMap::new();
+        for ((a, b), &cost) in table {
+            if !(cost.is_finite() && cost >= 0.0) {
+                return Err(format!("cost for ({}, {}) must be non-negative, got {}", a, b, cost));
+            }
+            match (a.as_str(), b.as_str()) {
+                ("-", "-") => return Err("cost table pair (-, -) is not an edit".to_string()),
+                ("-", segment) | (segment, "-") => {
+                    indel.insert(segment.to_string(), cost);
+                }
+                _ => triples.push((a.clone(), b.clone(), cost)),
+            }
+        }
+        Ok(Self {
+            substitution: SubstitutionCost::from_triples(&triples),
+            indel,
+            default_indel,
+        })
+    }
+
+    pub fn indel_cost(&self, segment: &str) -> f64 {
+        self.indel.get(segment).copied().unwrap_or(self.default_indel)
+    }
+}
+
+/// Affine gap penalties and substitution costs for NW / SW alignment.
+///
+/// Aligned segments score `1 - 2 * cost` (identical +1, maximally different
+/// -1); a gap of length k costs `gap_open + (k - 1) * gap_extend`.
+#[derive(Debug, Clone)]
+pub struct AlignParams {
+    pub gap_open: f64,
+    pub gap_extend: f64,
+    pub substitution: SubstitutionCost,
+}
+
+impl Default for AlignParams {
+    fn default() -> Self {
+        Self {
+            gap_open: 1.0,
+            gap_extend: 0.5,
+            substitution: SubstitutionCost::Uniform,
+        }
+    }
+}
+
+// Traceback states for affine alignment
+const STATE_MATCH: u8 = 0;
+const STATE_DELETE: u8 = 1;
+const STATE_INSERT: u8 = 2;
+const STATE_START: u8 = 3;
+
+fn best_state(candidates: [(f64, u8); 3]) -> (f64, u8) {
+    candidates
+        .into_iter()
+        .fold((f64::NEG_INFINITY, STATE_START), |best, c| {
+            if c.0 > best.0 {
+                c
+            } else {
+                best
+            }
+        })
+}
+
+/// Gotoh affine-gap alignment; `local` selects Smith–Waterman
+fn affine_align(ipa_a: &str, ipa_b: &str, params: &AlignParams, local: bool) -> Alignment {
+    let segments_a = segment_ipa(ipa_a);
+    let segments_b = segment_ipa(ipa_b);
+    let (len_a, len_b) = (segments_a.len(), segments_b.len());
+    let (open, extend) = (params.gap_open, params.gap_extend);
+
+    // Score matrices ending in a match, a deletion (gap in b) or an insertion
+    let shape = (len_a + 1, len_b + 1);
+    let mut m = Array2::<f64>::from_elem(shape, f64::NEG_INFINITY);
+    let mut x = Array2::<f64>::from_elem(shape, f64::NEG_INFINITY);
+    let mut y = Array2::<f64>::from_elem(shape, f64::NEG_INFINITY);
+    let mut trace_m = Array2::<u8>::from_elem(shape, STATE_START);
+    let mut trace_x = Array2::<u8>::from_elem(shape, STATE_START);
+    let mut trace_y = Array2::<u8>::from_elem(shape, STATE_START);
+
+    m[[0, 0]] = 0.0;
+    if !local {
+        for i in 1..=len_a {
+            x[[i, 0]] = -(open + (i - 1) as f64 * extend);
+            trace_x[[i, 0]] = if i == 1 { STATE_MATCH } else { STATE_DELETE };
+        }
+        for j in 1..=len_b {
+            y[[0, j]] = -(open + (j - 1) as f64 * extend);
+            trace_y[[0, j]] = if j == 1 { STATE_MATCH } else { STATE_INSERT };
+        }
+    }
+
+    let substitution = params.substitution.cost_grid(&segments_a, &segments_b);
+    let mut best = (0.0, 0, 0);
     for i in 1..=len_a {
         for j in 1..=len_b {
-            if a[i - 1] == b[j - 1] {
-                dp[i][j] = dp[i - 1][j - 1] + 1;
+            let cost = substitution[[i - 1, j - 1]];
+            let (prev, state) = best_state([
+                (m[[i - 1, j - 1]], STATE_MATCH),
+                (x[[i - 1, j - 1]], STATE_DELETE),
+                (y[[i - 1, j - 1]], STATE_INSERT),
+            ]);
+            let (prev, state) = if local && prev < 0.0 {
+                (0.0, STATE_START)
             } else {
-                dp[i][j] = dp[i - 1][j].max(dp[i][j - 1]);
+                (prev, state)
+            };
+            m[[i, j]] = prev + 1.0 - 2.0 * cost;
+            trace_m[[i, j]] = state;
+
+            let (score, state) = best_state([
+                (m[[i - 1, j]] - open, STATE_MATCH),
+                (x[[i - 1, j]] - extend, STATE_DELETE),
+                (y[[i - 1, j]] - open, STATE_INSERT),
+            ]);
+            x[[i, j]] = score;
+            trace_x[[i, j]] = state;
+
+            let (score, state) = best_state([
+                (m[[i, j - 1]] - open, STATE_MATCH),
+                (x[[i, j - 1]] - open, STATE_DELETE),
+                (y[[i, j - 1]] - extend, STATE_INSERT),
+            ]);
+            y[[i, j]] = score;
+            trace_y[[i, j]] = state;
+
+            if local && m[[i, j]] > best.0 {
+                best = (m[[i, j]], i, j);
+            }
+        }
+    }
+
+    // Traceback start: best local cell, or the best state in the corner
+    let (mut i, mut j, mut state) = if local {
+        (best.1, best.2, if best.0 > 0.0 { STATE_MATCH } else { STATE_START })
+    } else {
+        let (_, state) = best_state([
+            (m[[len_a, len_b]], STATE_MATCH),
+            (x[[len_a, len_b]], STATE_DELETE),
+            (y[[len_a, len_b]], STATE_INSERT),
+        ]);
+        (len_a, len_b, state)
+    };
+
+    let mut operations = Vec::new();
+    let mut op_costs = Vec::new();
+    let mut aligned_a = Vec::new();
+    let mut aligned_b = Vec::new();
+
+    while state != STATE_START && (i > 0 || j > 0) {
+        match state {
+            STATE_MATCH => {
+                let (seg_a, seg_b) = (&segments_a[i - 1], &segments_b[j - 1]);
+                op_costs.push(substitution[[i - 1, j - 1]]);
+                operations.push(if seg_a == seg_b {
+                    EditOp::Match
+                } else {
+                    EditOp::Substitute
+                });
+                aligned_a.push(seg_a.clone());
+                aligned_b.push(seg_b.clone());
+                state = trace_m[[i, j]];
+                i -= 1;
+                j -= 1;
+            }
+            STATE_DELETE => {
+                operations.push(EditOp::Delete);
+                aligned_a.push(segments_a[i - 1].clone());
+                aligned_b.push("-".to_string());
+                state = trace_x[[i, j]];
+                i -= 1;
+            }
+            _ => {
+                operations.push(EditOp::Insert);
+                aligned_a.push("-".to_string());
+                aligned_b.push(segments_b[j - 1].clone());
+                state = trace_y[[i, j]];
+                j -= 1;
+            }
+        }
+
+        let gap = match operations.last() {
+            Some(EditOp::Delete) => Some(STATE_DELETE),
+            Some(EditOp::Insert) => Some(STATE_INSERT),
+            _ => None,
+        };
+        if let Some(gap) = gap {
+            // Walking backwards, a gap is "opened" at its first position
+            op_costs.push(if state == gap { extend } else { open });
+        }
+    }
+
+    operations.reverse();
+    op_costs.reverse();
+    aligned_a.reverse();
+    aligned_b.reverse();
+
+    let cost = op_costs.iter().sum();
+    Alignment::new(aligned_a, aligned_b, operations, op_costs, cost)
+}
+
+/// Global (Needleman–Wunsch) alignment with affine gap penalties. The
+/// returned cost sums substitution costs and gap penalties.
+pub fn nw_align(ipa_a: &str, ipa_b: &str, params: &AlignParams) -> Alignment {
+    affine_align(ipa_a, ipa_b, params, false)
+}
+
+/// Local (Smith–Waterman) alignment with affine gap penalties: only the
+/// best-scoring aligned region is returned
+pub fn sw_align(ipa_a: &str, ipa_b: &str, params: &AlignParams) -> Alignment {
+    affine_align(ipa_a, ipa_b, params, true)
+}
+
+/// Boundary marker padding n-grams (never produced by `segment_ipa`)
+const NGRAM_BOUNDARY: &str = "#";
+
+/// Multiset of segment n-grams of a word padded with one boundary marker on
+/// each side, so words shorter than `n` still have n-grams
+fn segment_ngrams(segments: &[String], n: usize) -> HashMap<Vec<&str>, usize> {
+    let padded: Vec<&str> = std::iter::once(NGRAM_BOUNDARY)
+        .chain(segments.iter().map(String::as_str))
+        .chain(std::iter::once(NGRAM_BOUNDARY))
+        .collect();
+    let mut counts = HashMap::new();
+    for gram in padded.windows(n.clamp(1, padded.len())) {
+        *counts.entry(gram.to_vec()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// (shared, total in a, total in b) n-gram counts, shared counting each
+/// n-gram min(count_a, count_b) times
+fn ngram_overlap(ipa_a: &str, ipa_b: &str, n: usize) -> (usize, usize, usize) {
+    let (segments_a, segments_b) = (segment_ipa(ipa_a), segment_ipa(ipa_b));
+    let grams_a = segment_ngrams(&segments_a, n);
+    let grams_b = segment_ngrams(&segments_b, n);
+    let shared = grams_a
+        .iter()
+        .map(|(gram, &count)| count.min(grams_b.get(gram).copied().unwrap_or(0)))
+        .sum();
+    (shared, grams_a.values().sum(), grams_b.values().sum())
+}
+
+/// Weighted (multiset) Jaccard similarity of segment n-grams:
+/// sum of min counts over sum of max counts
+pub fn ngram_jaccard(ipa_a: &str, ipa_b: &str, n: usize) -> f64 {
+    let (shared, total_a, total_b) = ngram_overlap(ipa_a, ipa_b, n);
+    shared as f64 / (total_a + total_b - shared) as f64
+}
+
+/// Dice coefficient of segment n-gram multisets: twice the shared count over
+/// the total count
+pub fn ngram_dice(ipa_a: &str, ipa_b: &str, n: usize) -> f64 {
+    let (shared, total_a, total_b) = ngram_overlap(ipa_a, ipa_b, n);
+    2.0 * shared as f64 / (total_a + total_b) as f64
+}
+
+/// Jaro-Winkler similarity over segments: Jaro similarity boosted by 0.1 per
+/// shared leading segment (up to four)
+pub fn jaro_winkler_similarity(ipa_a: &str, ipa_b: &str) -> f64 {
+    let a = segment_ipa(ipa_a);
+    let b = segment_ipa(ipa_b);
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // Segments match when equal and no further apart than the window
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut matched_b = vec![false; b.len()];
+    let mut matches_a = Vec::new();
+    for (i, seg_a) in a.iter().enumerate() {
+        let range = i.saturating_sub(window)..(i + window + 1).min(b.len());
+        if let Some(j) = range.into_iter().find(|&j| !matched_b[j] && b[j] == *seg_a) {
+            matched_b[j] = true;
+            matches_a.push(seg_a);
+        }
+    }
+    if matches_a.is_empty() {
+        return 0.0;
+    }
+    let matches_b = b.iter().zip(&matched_b).filter(|(_, &m)| m).map(|(s, _)| s);
+    let transpositions = matches_a.iter().zip(matches_b).filter(|(x, y)| **x != *y).count() / 2;
+
+    let m = matches_a.len() as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Word similarity measure for the batch and matrix functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimilarityMetric {
+    /// Normalized segment edit distance (`phonetic_distance`)
+    #[default]
+    Levenshtein,
+    /// Longest common subsequence ratio (`lcs_ratio`)
+    Lcs,
+    /// Weighted Jaccard of segment n-grams of the given size
+    Jaccard(usize),
+    /// Dice coefficient of segment n-grams of the given size
+    Dice(usize),
+    /// Segment Jaro-Winkler
+    JaroWinkler,
+}
+
+impl FromStr for SimilarityMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "levenshtein" => Ok(SimilarityMetric::Levenshtein),
+            "lcs" => Ok(SimilarityMetric::Lcs),
+            "jaccard" | "jaccard2" => Ok(SimilarityMetric::Jaccard(2)),
+            "jaccard3" => Ok(SimilarityMetric::Jaccard(3)),
+            "dice" | "dice2" => Ok(SimilarityMetric::Dice(2)),
+            "dice3" => Ok(SimilarityMetric::Dice(3)),
+            "jaro_winkler" | "jaro-winkler" => Ok(SimilarityMetric::JaroWinkler),
+            other => Err(format!(
+                "unknown metric '{}' (expected levenshtein, lcs, jaccard, jaccard3, dice, \
+                 dice3 or jaro_winkler)",
+                other
+            )),
+        }
+    }
+}
+
+impl SimilarityMetric {
+    /// Similarity in [0, 1] of two IPA strings (1 for identical words)
+    pub fn similarity(self, ipa_a: &str, ipa_b: &str) -> f64 {
+        match self {
+            SimilarityMetric::Levenshtein => phonetic_distance(ipa_a, ipa_b),
+            SimilarityMetric::Lcs => lcs_ratio(ipa_a, ipa_b),
+            SimilarityMetric::Jaccard(n) => ngram_jaccard(ipa_a, ipa_b, n),
+            SimilarityMetric::Dice(n) => ngram_dice(ipa_a, ipa_b, n),
+            SimilarityMetric::JaroWinkler => jaro_winkler_similarity(ipa_a, ipa_b),
+        }
+    }
+
+    /// Upper bound on `similarity` for words of `len_a` and `len_b` segments,
+    /// used to skip pairs whose lengths alone keep them below a threshold.
+    /// Uses the metric's own arithmetic, so a pair reaching the bound is
+    /// never rejected.
+    pub fn length_bound(self, len_a: usize, len_b: usize) -> f64 {
+        let (short, long) = (len_a.min(len_b), len_a.max(len_b));
+        if long == 0 {
+            return 1.0;
+        }
+        // Padded words of `len` segments have max(len + 3 - n, 1) n-grams
+        let grams = |len: usize, n: usize| (len + 3).saturating_sub(n.max(1)).max(1);
+        match self {
+            SimilarityMetric::Levenshtein => 1.0 - (long - short) as f64 / long as f64,
+            SimilarityMetric::Lcs => short as f64 / long as f64,
+            SimilarityMetric::Jaccard(n) => grams(short, n) as f64 / grams(long, n) as f64,
+            SimilarityMetric::Dice(n) => {
+                let (a, b) = (grams(short, n), grams(long, n));
+                2.0 * a as f64 / (a + b) as f64
+            }
+            SimilarityMetric::JaroWinkler if short == 0 => 0.0,
+            SimilarityMetric::JaroWinkler => {
+                // Every segment of the shorter word matched, four-segment prefix
+                let m = short as f64;
+                let jaro = (m / len_a as f64 + m / len_b as f64 + 1.0) / 3.0;
+                jaro + 4.0 * 0.1 * (1.0 - jaro)
             }
         }
     }
+}
+
+/// Segment count of every IPA string, for `SimilarityMetric::length_bound`
+pub fn segment_lengths(ipa_strings: &[String]) -> Vec<usize> {
+    ipa_strings
+        .par_iter()
+        .map(|ipa| segment_ipa(ipa).len())
+        .collect()
+}
 
-    dp[len_a][len_b]
+/// `batch_phonetic_distance` under any `SimilarityMetric`
+pub fn batch_metric_similarity(pairs: &[(String, String)], metric: SimilarityMetric) -> Vec<f64> {
+    pairs
+        .par_iter()
+        .map(|(a, b)| metric.similarity(a, b))
+        .collect()
 }
 
 /// Extract sound correspondence patterns from multiple alignments
@@ -260,14 +829,205 @@ pub fn extract_sound_correspondences(alignments: &[Alignment]) -> Vec<(String, S
         .map(|((a, b), count)| (a, b, count))
         .collect();
 
-    // Sort by frequency
-    correspondences.sort_by(|a, b| b.2.cmp(&a.2));
+    // Sort by frequency, then by segment pair
+    correspondences.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| (&a.0, &a.1).cmp(&(&b.0, &b.1))));
 
     correspondences
 }
 
+/// Word position of an aligned column, judged on the first sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordPosition {
+    Initial,
+    Medial,
+    Final,
+}
+
+/// One segment correspondence with its contexts and significance
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundCorrespondence {
+    pub segment_a: String,
+    pub segment_b: String,
+    pub count: usize,
+    /// Count expected if the two segments were independent
+    pub expected: f64,
+    /// Pointwise mutual information, `ln(count / expected)`
+    pub pmi: f64,
+    /// Pearson chi-square of the 2×2 table (segment_a vs rest, segment_b vs rest)
+    pub chi_square: f64,
+    /// Occurrences per word position: initial, medial, final
+    pub positions: [usize; 3],
+    /// (preceding, following) segment of `segment_a`, `#` at word
+    /// boundaries, with counts; most frequent first
+    pub contexts: Vec<(String, String, usize)>,
+}
+
+/// Contextual correspondence statistics over a set of alignments
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundCorrespondences {
+    /// Non-identical correspondences, most significant first
+    pub correspondences: Vec<SoundCorrespondence>,
+    /// Aligned columns counted, identical ones included
+    pub columns: usize,
+}
+
+impl SoundCorrespondences {
+    /// Correspondences seen at least `min_count` times whose PMI and
+    /// chi-square reach the given minimums
+    pub fn filter(
+        &self,
+        min_count: usize,
+        min_pmi: Option<f64>,
+        min_chi_square: Option<f64>,
+    ) -> Vec<&SoundCorrespondence> {
+        self.correspondences
+            .iter()
+            .filter(|c| {
+                c.count >= min_count
+                    && min_pmi.is_none_or(|m| c.pmi >= m)
+                    && min_chi_square.is_none_or(|m| c.chi_square >= m)
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct CorrespondenceCounts {
+    count: usize,
+    positions: [usize; 3],
+    contexts: HashMap<(String, String), usize>,
+}
+
+/// Sound correspondences with phonetic context and significance.
+///
+/// Every aligned column, identical ones included, feeds the segment
+/// unigram distributions of both sides; a correspondence's expected count
+/// is `count(a) · count(b) / columns`. Context is read off the first
+/// sequence, skipping gaps. Only non-identical columns (substitutions and
+/// gaps) are reported, so regular sound laws rank above sporadic noise.
+pub fn contextual_sound_correspondences(alignments: &[Alignment]) -> SoundCorrespondences {
+    let mut pairs: HashMap<(String, String), CorrespondenceCounts> = HashMap::new();
+    let mut unigrams_a: HashMap<String, usize> = HashMap::new();
+    let mut unigrams_b: HashMap<String, usize> = HashMap::new();
+    let mut columns = 0;
+
+    for alignment in alignments {
+        let len = alignment.sequence_a.len().min(alignment.sequence_b.len());
+        let segments: Vec<usize> = (0..len)
+            .filter(|&i| alignment.sequence_a[i] != "-")
+            .collect();
+
+        for i in 0..len {
+            let (a, b) = (&alignment.sequence_a[i], &alignment.sequence_b[i]);
+            columns += 1;
+            *unigrams_a.entry(a.clone()).or_insert(0) += 1;
+            *unigrams_b.entry(b.clone()).or_insert(0) += 1;
+            if a == b {
+                continue;
+            }
+
+            let before = segments.partition_point(|&j| j < i);
+            let after = segments.partition_point(|&j| j <= i);
+            let preceding = before
+                .checked_sub(1)
+                .map_or("#", |k| alignment.sequence_a[segments[k]].as_str());
+            let following = segments
+                .get(after)
+                .map_or("#", |&j| alignment.sequence_a[j].as_str());
+            let position = if before == 0 {
+                WordPosition::Initial
+            } else if after == segments.len() {
+                WordPosition::Final
+            } else {
+                WordPosition::Medial
+            };
+
+            let entry = pairs.entry((a.clone(), b.clone())).or_default();
+            entry.count += 1;
+            entry.positions[position as usize] += 1;
+            *entry
+                .contexts
+                .entry((preceding.to_string(), following.to_string()))
+                .or_insert(0) += 1;
+        }
+    }
+
+    let n = columns as f64;
+    let mut correspondences: Vec<SoundCorrespondence> = pairs
+        .into_iter()
+        .map(|((segment_a, segment_b), counts)| {
+            let row = unigrams_a[&segment_a] as f64;
+            let column = unigrams_b[&segment_b] as f64;
+            let observed = counts.count as f64;
+            let expected = row * column / n;
+
+            // 2×2 table: [[observed, row - observed], [column - observed, rest]]
+            let rest = n - row - column + observed;
+            let denominator = row * column * (n - row) * (n - column);
+            let chi_square = if denominator > 0.0 {
+                n * (observed * rest - (row - observed) * (column - observed)).powi(2)
+                    / denominator
+            } else {
+                0.0
+            };
+
+            let mut contexts: Vec<(String, String, usize)> = counts
+                .contexts
+                .into_iter()
+                .map(|((preceding, following), count)| (preceding, following, count))
+                .collect();
+            contexts.sort_by(|x, y| y.2.cmp(&x.2).then_with(|| (&x.0, &x.1).cmp(&(&y.0, &y.1))));
+
+            SoundCorrespondence {
+                segment_a,
+                segment_b,
+                count: counts.count,
+                expected,
+                pmi: (observed / expected).ln(),
+                chi_square,
+                positions: counts.positions,
+                contexts,
+            }
+        })
+        .collect();
+    correspondences.sort_by(|x, y| {
+        y.chi_square
+            .total_cmp(&x.chi_square)
+            .then(y.count.cmp(&x.count))
+            .then_with(|| (&x.segment_a, &x.segment_b).cmp(&(&y.segment_a, &y.segment_b)))
+    });
+
+    SoundCorrespondences {
+        correspondences,
+        columns,
+    }
+}
+
 /// Compute phonetic similarity matrix for batch of IPA strings
 pub fn compute_similarity_matrix(ipa_strings: &[String]) -> Array2<f64> {
+    similarity_matrix_by(ipa_strings, phonetic_distance)
+}
+
+/// `compute_similarity_matrix` under any `SimilarityMetric`
+pub fn compute_similarity_matrix_with_metric(
+    ipa_strings: &[String],
+    metric: SimilarityMetric,
+) -> Array2<f64> {
+    similarity_matrix_by(ipa_strings, |a, b| metric.similarity(a, b))
+}
+
+/// `compute_similarity_matrix` with a `CostModel`
+pub fn compute_similarity_matrix_with_costs(
+    ipa_strings: &[String],
+    costs: &CostModel,
+) -> Array2<f64> {
+    similarity_matrix_by(ipa_strings, |a, b| phonetic_distance_with_costs(a, b, costs))
+}
+
+fn similarity_matrix_by<F>(ipa_strings: &[String], similarity: F) -> Array2<f64>
+where
+    F: Fn(&str, &str) -> f64 + Sync,
+{
     let n = ipa_strings.len();
     let mut matrix = Array2::<f64>::zeros((n, n));
 
@@ -283,7 +1043,7 @@ pub fn compute_similarity_matrix(ipa_strings: &[String]) -> Array2<f64> {
 
     let similarities: Vec<_> = pairs
         .par_iter()
-        .map(|&(i, j)| phonetic_distance(&ipa_strings[i], &ipa_strings[j]))
+        .map(|&(i, j)| similarity(&ipa_strings[i], &ipa_strings[j]))
         .collect();
 
     // Fill matrix (symmetric)
@@ -296,6 +1056,376 @@ pub fn compute_similarity_matrix(ipa_strings: &[String]) -> Array2<f64> {
     matrix
 }
 
+/// One block of a chunked similarity computation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityBlock {
+    /// Rows covered by this block
+    pub rows: std::ops::Range<usize>,
+    /// Upper-triangle (i, j, similarity) pairs with `i` in `rows`, `i < j`
+    pub pairs: Vec<(usize, usize, f64)>,
+    /// Pairs computed so far (this block included) and in total
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Block-by-block similarity computation over the upper triangle, so
+/// callers can report progress, cancel between blocks, or keep only edges
+/// above a threshold instead of materializing the dense matrix. Each block
+/// is computed in parallel. With a positive threshold, pairs whose segment
+/// lengths already rule it out (`SimilarityMetric::length_bound`) are
+/// skipped without being scored.
+pub struct SimilarityBlocks<'a> {
+    ipa_strings: &'a [String],
+    block_size: usize,
+    threshold: f64,
+    metric: SimilarityMetric,
+    length_filter: bool,
+    /// Segment counts, computed with the first block when filtering
+    lengths: Option<Vec<usize>>,
+    next_row: usize,
+    done: usize,
+    total: usize,
+}
+
+/// Iterate over the similarity matrix in blocks of `block_size` rows,
+/// keeping pairs with similarity at or above `threshold`
+/// (`f64::NEG_INFINITY` keeps every pair)
+pub fn similarity_blocks(
+    ipa_strings: &[String],
+    block_size: usize,
+    threshold: f64,
+) -> SimilarityBlocks<'_> {
+    let n = ipa_strings.len();
+    SimilarityBlocks {
+        ipa_strings,
+        block_size: block_size.max(1),
+        threshold,
+        metric: SimilarityMetric::Levenshtein,
+        length_filter: true,
+        lengths: None,
+        next_row: 0,
+        done: 0,
+        total: n * n.saturating_sub(1) / 2,
+    }
+}
+
+impl SimilarityBlocks<'_> {
+    /// Score pairs with `metric` instead of Levenshtein
+    pub fn with_metric(mut self, metric: SimilarityMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Turn the length-difference quick reject on (the default) or off
+    pub fn with_length_filter(mut self, enabled: bool) -> Self {
+        self.length_filter = enabled;
+        self
+    }
+
+    /// Skip the rows before `row`, e.g. to resume an interrupted run; their
+    /// pairs count as done
+    pub fn starting_at(mut self, row: usize) -> Self {
+        let n = self.ipa_strings.len();
+        self.next_row = row.min(n);
+        self.done = (0..self.next_row).map(|i| n - i - 1).sum();
+        self
+    }
+}
+
+impl Iterator for SimilarityBlocks<'_> {
+    type Item = SimilarityBlock;
+
+    fn next(&mut self) -> Option<SimilarityBlock> {
+        let n = self.ipa_strings.len();
+        if self.next_row >= n {
+            return None;
+        }
+        let rows = self.next_row..(self.next_row + self.block_size).min(n);
+        self.next_row = rows.end;
+
+        let strings = self.ipa_strings;
+        let (threshold, metric) = (self.threshold, self.metric);
+        // Bounds are never negative, so only a positive threshold rejects
+        if self.length_filter && threshold > 0.0 && self.lengths.is_none() {
+            self.lengths = Some(segment_lengths(strings));
+        }
+        let lengths = self.lengths.as_deref().filter(|_| self.length_filter);
+        let pairs: Vec<(usize, usize, f64)> = rows
+            .clone()
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                (i + 1..n).filter_map(move |j| {
+                    if lengths.is_some_and(|l| metric.length_bound(l[i], l[j]) < threshold) {
+                        return None;
+                    }
+                    let sim = metric.similarity(&strings[i], &strings[j]);
+                    (sim >= threshold).then_some((i, j, sim))
+                })
+            })
+            .collect();
+        self.done += rows.clone().map(|i| n - i - 1).sum::<usize>();
+
+        Some(SimilarityBlock {
+            rows,
+            pairs,
+            done: self.done,
+            total: self.total,
+        })
+    }
+}
+
+/// Node of the BK-tree: an item and children keyed by their distance to it
+#[derive(Debug, Clone)]
+struct BkNode {
+    item: usize,
+    children: Vec<(usize, usize)>,
+}
+
+/// BK-tree over segment edit distance (Levenshtein on `segment_ipa`
+/// segments), answering "forms within distance d of X" without comparing
+/// against every form. Segments are interned so comparisons run on integers.
+#[derive(Debug, Clone)]
+pub struct PhoneticIndex {
+    ipa_strings: Vec<String>,
+    /// Interned segments per indexed form
+    segments: Vec<Vec<u32>>,
+    symbols: HashMap<String, u32>,
+    nodes: Vec<BkNode>,
+}
+
+impl PhoneticIndex {
+    /// Index `ipa_strings`; results refer to forms by their position
+    pub fn build(ipa_strings: Vec<String>) -> Self {
+        let mut symbols: HashMap<String, u32> = HashMap::new();
+        let segments: Vec<Vec<u32>> = ipa_strings
+            .iter()
+            .map(|ipa| {
+                segment_ipa(ipa)
+                    .into_iter()
+                    .map(|segment| {
+                        let next = symbols.len() as u32;
+                        *symbols.entry(segment).or_insert(next)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut index = Self {
+            ipa_strings,
+            segments,
+            symbols,
+            nodes: Vec::new(),
+        };
+        for item in 0..index.segments.len() {
+            index.insert(item);
+        }
+        index
+    }
+
+    fn insert(&mut self, item: usize) {
+        if self.nodes.is_empty() {
+            self.nodes.push(BkNode {
+                item,
+                children: Vec::new(),
+            });
+            return;
+        }
+        let mut node = 0;
+        loop {
+            let distance = levenshtein(&self.segments[item], &self.segments[self.nodes[node].item]);
+            match self.nodes[node].children.iter().find(|c| c.0 == distance) {
+                Some(&(_, child)) => node = child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(BkNode {
+                        item,
+                        children: Vec::new(),
+                    });
+                    self.nodes[node].children.push((distance, child));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Segments of a query, with unseen segments mapped past every symbol
+    fn encode(&self, ipa: &str) -> Vec<u32> {
+        let unseen = self.symbols.len() as u32;
+        segment_ipa(ipa)
+            .iter()
+            .map(|segment| self.symbols.get(segment).copied().unwrap_or(unseen))
+            .collect()
+    }
+
+    fn search(&self, query: &[u32], max_dist: usize) -> Vec<(usize, usize)> {
+        let mut results = Vec::new();
+        let mut stack: Vec<usize> = if self.nodes.is_empty() { vec![] } else { vec![0] };
+        while let Some(node) = stack.pop() {
+            let current = &self.nodes[node];
+            let distance = levenshtein(query, &self.segments[current.item]);
+            if distance <= max_dist {
+                results.push((current.item, distance));
+            }
+            // Triangle inequality: only children within max_dist can match
+            let range = distance.saturating_sub(max_dist)..=distance + max_dist;
+            stack.extend(
+                current
+                    .children
+                    .iter()
+                    .filter(|c| range.contains(&c.0))
+                    .map(|c| c.1),
+            );
+        }
+        results.sort_unstable_by_key(|&(item, distance)| (distance, item));
+        results
+    }
+
+    /// Indexed forms within `max_dist` segment edits of `ipa`, as
+    /// (index, distance) closest first
+    pub fn query(&self, ipa: &str, max_dist: usize) -> Vec<(usize, usize)> {
+        self.search(&self.encode(ipa), max_dist)
+    }
+
+    /// Every pair (i, j, distance), `i < j`, within `max_dist` edits, sorted
+    /// by (i, j)
+    pub fn all_pairs_within(&self, max_dist: usize) -> Vec<(usize, usize, usize)> {
+        (0..self.segments.len())
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let mut matches: Vec<(usize, usize, usize)> = self
+                    .search(&self.segments[i], max_dist)
+                    .into_iter()
+                    .filter(|&(j, _)| j > i)
+                    .map(|(j, distance)| (i, j, distance))
+                    .collect();
+                matches.sort_unstable();
+                matches
+            })
+            .collect()
+    }
+
+    pub fn ipa_strings(&self) -> &[String] {
+        &self.ipa_strings
+    }
+
+    pub fn len(&self) -> usize {
+        self.ipa_strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ipa_strings.is_empty()
+    }
+}
+
+/// Sound-class scheme for `phonetic_key`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyScheme {
+    /// Consonant classes only, vowels dropped
+    Consonants,
+    /// Consonant classes with every vowel as `V`
+    Full,
+    /// First two consonant classes (Dolgopolsky's cognacy criterion)
+    Dolgopolsky,
+}
+
+impl FromStr for KeyScheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "consonants" => Ok(KeyScheme::Consonants),
+            "full" | "vowels" => Ok(KeyScheme::Full),
+            "dolgopolsky" => Ok(KeyScheme::Dolgopolsky),
+            other => Err(format!(
+                "unknown key scheme '{}' (expected consonants, full or dolgopolsky)",
+                other
+            )),
+        }
+    }
+}
+
+/// Dolgopolsky-style sound class of a segment from its features: P labial,
+/// T dental, S sibilant / affricate, K dorsal, H laryngeal obstruents; M / N
+/// nasals, R liquids, W / J glides, V vowels. Segments without features
+/// keep their first character.
+pub(crate) fn sound_class(segment: &str) -> char {
+    let Some(features) = segment_features(segment) else {
+        return segment.chars().next().unwrap_or('?');
+    };
+    let has = |name: &str| features[feature_index(name)] > 0;
+    if has("syl") {
+        'V'
+    } else if has("nas") {
+        if has("lab") {
+            'M'
+        } else {
+            'N'
+        }
+    } else if has("son") && !has("cons") {
+        if has("lab") {
+            'W'
+        } else {
+            'J'
+        }
+    } else if has("son") || has("lat") {
+        'R'
+    } else if has("lab") {
+        'P'
+    } else if has("cor") {
+        if has("strid") {
+            'S'
+        } else {
+            'T'
+        }
+    } else if has("hi") || has("back") {
+        'K'
+    } else {
+        'H'
+    }
+}
+
+/// Coarse phonetic key of an IPA string for blocking: segments mapped to
+/// sound classes with repeats collapsed, so "pater" and "fater" both give
+/// "PTR". Words sharing a key are the candidates worth comparing.
+pub fn phonetic_key(ipa: &str, scheme: KeyScheme) -> String {
+    let mut key = String::new();
+    let mut consonants = 0;
+    for segment in segment_ipa(ipa) {
+        let class = sound_class(&segment);
+        if class == 'V' && scheme != KeyScheme::Full {
+            continue;
+        }
+        if key.ends_with(class) {
+            continue;
+        }
+        if scheme == KeyScheme::Dolgopolsky && consonants == 2 {
+            break;
+        }
+        consonants += usize::from(class != 'V');
+        key.push(class);
+    }
+    key
+}
+
+/// Group indices of `ipa_strings` by phonetic key, keeping groups of two or
+/// more (ordered by first member); only pairs within a group need comparing
+pub fn block_by_key(ipa_strings: &[String], scheme: KeyScheme) -> Vec<Vec<usize>> {
+    let keys: Vec<String> = ipa_strings
+        .par_iter()
+        .map(|ipa| phonetic_key(ipa, scheme))
+        .collect();
+    let mut groups: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        groups.entry(key).or_default().push(i);
+    }
+    let mut blocks: Vec<Vec<usize>> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect();
+    blocks.sort_unstable_by_key(|group| group[0]);
+    blocks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,10 +1449,350 @@ mod tests {
         assert!(!alignment.operations.is_empty());
     }
 
+    #[test]
+    fn test_batch_dtw_align_banded() {
+        let costs = CostModel::default();
+        let pairs = vec![
+            ("pater".to_string(), "fater".to_string()),
+            ("pater".to_string(), "patɛr".to_string()),
+            ("akwa".to_string(), "aːkʷa".to_string()),
+            ("".to_string(), "pa".to_string()),
+        ];
+        let summaries = batch_dtw_align(&pairs, &costs, None, true);
+        for (summary, (a, b)) in summaries.iter().zip(&pairs) {
+            let alignment = dtw_align(a, b);
+            assert_eq!(summary.cost, alignment.cost);
+            assert_eq!(summary.correspondences, alignment.extract_correspondences());
+        }
+        assert_eq!(summaries[0].correspondences, vec![("p".into(), "f".into())]);
+        let costs_only = batch_dtw_align(&pairs, &costs, None, false);
+        assert!(costs_only.iter().all(|s| s.correspondences.is_empty()));
+        assert_eq!(costs_only[2].cost, summaries[2].cost);
+
+        // A band never lowers the cost and is widened to the length gap
+        let (a, b) = ("abcdefgh", "bcdefgha");
+        assert!(dtw_cost(a, b, &costs, Some(0)) >= dtw_cost(a, b, &costs, None));
+        assert!(dtw_cost("pa", "patata", &costs, Some(0)).is_finite());
+        let (banded, full) = (dtw_align_banded(a, b, &costs, Some(8)), dtw_align(a, b));
+        assert_eq!((banded.cost, banded.operations), (full.cost, full.operations));
+    }
+
+    #[test]
+    fn test_segment_ipa() {
+        assert_eq!(segment_ipa("t͡ʃaːpʰ"), vec!["t͡ʃ", "aː", "pʰ"]);
+        assert_eq!(segment_ipa("ˈma˥˩.ma˧"), vec!["m", "a", "˥˩", "m", "a", "˧"]);
+        // Decomposed and precomposed diacritics yield the same segment
+        assert_eq!(segment_ipa("e\u{301}"), segment_ipa("\u{e9}"));
+        assert_eq!(segment_ipa("ã̰"), vec!["ã̰"]);
+        assert!(segment_ipa("").is_empty());
+    }
+
+    #[test]
+    fn test_multichar_segments_in_distance() {
+        // One substituted segment out of two, not two out of four characters
+        assert_eq!(phonetic_distance("t͡ʃa", "t͡sa"), 0.5);
+        assert_eq!(phonetic_distance("ˈpaːter", "paːter"), 1.0);
+        let alignment = dtw_align("pʰa", "pa");
+        assert_eq!(alignment.sequence_a, vec!["pʰ", "a"]);
+        assert_eq!(alignment.operations, vec![EditOp::Substitute, EditOp::Match]);
+    }
+
+    #[test]
+    fn test_parallel_batches_match_serial() {
+        let forms = ["pater", "fater", "t͡ʃaːpʰ", "mater", "", "vasɐ"];
+        let pairs: Vec<(String, String)> = forms
+            .iter()
+            .flat_map(|a| forms.iter().map(move |b| (a.to_string(), b.to_string())))
+            .collect();
+
+        let serial: Vec<f64> = pairs.iter().map(|(a, b)| phonetic_distance(a, b)).collect();
+        assert_eq!(batch_phonetic_distance(pairs), serial);
+
+        let strings: Vec<String> = forms.iter().map(|f| f.to_string()).collect();
+        let matrix = compute_similarity_matrix(&strings);
+        for i in 0..strings.len() {
+            for j in (i + 1)..strings.len() {
+                assert_eq!(matrix[[i, j]], phonetic_distance(&strings[i], &strings[j]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_similarity_blocks() {
+        let strings: Vec<String> = ["pater", "fadar", "vater", "padre", "otac"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        let matrix = compute_similarity_matrix(&strings);
+
+        let blocks: Vec<SimilarityBlock> =
+            similarity_blocks(&strings, 2, f64::NEG_INFINITY).collect();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].rows, 0..2);
+        assert_eq!((blocks[0].done, blocks[0].total), (7, 10));
+        assert_eq!(blocks[2].done, 10);
+
+        let pairs: Vec<(usize, usize, f64)> = blocks.into_iter().flat_map(|b| b.pairs).collect();
+        assert_eq!(pairs.len(), 10);
+        assert!(pairs.iter().all(|&(i, j, sim)| i < j && matrix[[i, j]] == sim));
+
+        let above: usize = similarity_blocks(&strings, 3, 0.5).map(|b| b.pairs.len()).sum();
+        let expected = pairs.iter().filter(|p| p.2 >= 0.5).count();
+        assert_eq!(above, expected);
+        assert_eq!(similarity_blocks(&[], 4, 0.0).count(), 0);
+    }
+
+    #[test]
+    fn test_length_bound() {
+        let strings: Vec<String> = ["", "a", "pa", "pater", "t͡ʃiŋ", "fadar", "kapitulum"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        let lengths = segment_lengths(&strings);
+        let metrics = [
+            SimilarityMetric::Levenshtein,
+            SimilarityMetric::Lcs,
+            SimilarityMetric::Jaccard(2),
+            SimilarityMetric::Dice(3),
+            SimilarityMetric::JaroWinkler,
+        ];
+        for metric in metrics {
+            for (i, a) in strings.iter().enumerate() {
+                for (j, b) in strings.iter().enumerate() {
+                    let bound = metric.length_bound(lengths[i], lengths[j]);
+                    assert!(metric.similarity(a, b) <= bound, "{:?} {} {}", metric, a, b);
+                }
+            }
+            // Equal lengths rule nothing out
+            assert_eq!(metric.length_bound(5, 5), 1.0);
+
+            let filtered: Vec<_> = similarity_blocks(&strings, 3, 0.4)
+                .with_metric(metric)
+                .flat_map(|b| b.pairs)
+                .collect();
+            let unfiltered: Vec<_> = similarity_blocks(&strings, 3, 0.4)
+                .with_metric(metric)
+                .with_length_filter(false)
+                .flat_map(|b| b.pairs)
+                .collect();
+            assert_eq!(filtered, unfiltered);
+        }
+        assert_eq!(SimilarityMetric::Levenshtein.length_bound(2, 8), 0.25);
+    }
+
+    #[test]
+    fn test_phonetic_index() {
+        let forms: Vec<String> = ["pater", "pader", "fater", "mater", "padre", "pater", "otac"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        let index = PhoneticIndex::build(forms.clone());
+        assert_eq!(index.len(), 7);
+
+        // Brute force agrees with the tree
+        let brute = |query: &str, max_dist: usize| -> Vec<(usize, usize)> {
+            let q = segment_ipa(query);
+            let q: Vec<&str> = q.iter().map(String::as_str).collect();
+            let mut hits: Vec<(usize, usize)> = forms
+                .iter()
+                .enumerate()
+                .map(|(i, f)| {
+                    let s = segment_ipa(f);
+                    let s: Vec<&str> = s.iter().map(String::as_str).collect();
+                    (i, levenshtein(&q, &s))
+                })
+                .filter(|&(_, d)| d <= max_dist)
+                .collect();
+            hits.sort_unstable_by_key(|&(i, d)| (d, i));
+            hits
+        };
+        for (query, max_dist) in [("pater", 1), ("pater", 2), ("tʃater", 1), ("otac", 0)] {
+            assert_eq!(index.query(query, max_dist), brute(query, max_dist));
+        }
+        assert_eq!(index.query("pater", 0), vec![(0, 0), (5, 0)]);
+
+        let pairs = index.all_pairs_within(1);
+        assert!(pairs.contains(&(0, 5, 0)));
+        assert!(pairs.contains(&(0, 1, 1)));
+        assert!(!pairs.iter().any(|&(i, j, _)| i == 6 || j == 6));
+        assert!(PhoneticIndex::build(Vec::new()).query("a", 3).is_empty());
+    }
+
+    #[test]
+    fn test_nw_align_affine_gaps() {
+        let params = AlignParams::default();
+        let alignment = nw_align("pater", "pater", &params);
+        assert_eq!(alignment.cost, 0.0);
+        assert!(alignment.operations.iter().all(|&op| op == EditOp::Match));
+
+        // One gap of two segments: open + extend
+        let alignment = nw_align("patera", "pata", &params);
+        assert_eq!(alignment.sequence_a.len(), 6);
+        assert_eq!(alignment.cost, 1.5);
+        let gaps: Vec<usize> = alignment
+            .operations
+            .iter()
+            .enumerate()
+            .filter(|(_, &op)| op == EditOp::Delete)
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[1], gaps[0] + 1);
+
+        let alignment = nw_align("", "pa", &params);
+        assert_eq!(alignment.operations, vec![EditOp::Insert, EditOp::Insert]);
+        assert_eq!(alignment.cost, 1.5);
+    }
+
+    #[test]
+    fn test_alignment_payload() {
+        let params = AlignParams::default();
+        let alignment = nw_align("patera", "fata", &params);
+        assert_eq!(alignment.op_costs.len(), alignment.operations.len());
+        assert_eq!(alignment.op_costs.iter().sum::<f64>(), alignment.cost);
+        assert_eq!(alignment.cigar(), "1S2M2D1M");
+        // The gap is opened at its first position and extended after
+        assert_eq!(alignment.op_costs[3..5], [1.0, 0.5]);
+
+        let json = alignment.to_json();
+        assert_eq!(json["cigar"], "1S2M2D1M");
+        assert_eq!(json["operations"][0]["op"], "substitute");
+        assert_eq!(json["operations"][4]["b"], "-");
+        assert_eq!(json["operations"].as_array().unwrap().len(), 6);
+
+        let alignment = dtw_align("pʰa", "pa");
+        assert_eq!(alignment.op_costs.iter().sum::<f64>(), alignment.cost);
+        assert_eq!(alignment.cigar(), "1S1M");
+        assert_eq!(dtw_align("", "pa").cigar(), "");
+    }
+
+    #[test]
+    fn test_sw_align_local() {
+        let alignment = sw_align("xxpaterxx", "pater", &AlignParams::default());
+        assert_eq!(alignment.sequence_a, vec!["p", "a", "t", "e", "r"]);
+        assert_eq!(alignment.cost, 0.0);
+        assert!(sw_align("", "pater", &AlignParams::default()).operations.is_empty());
+    }
+
+    #[test]
+    fn test_substitution_costs() {
+        let feature = SubstitutionCost::FeatureWeighted;
+        assert!(feature.cost("p", "b") < feature.cost("p", "a"));
+        assert_eq!(feature.cost("p", "p"), 0.0);
+
+        let matrix = SubstitutionCost::from_triples(&[("p".into(), "f".into(), 0.2)]);
+        assert_eq!(matrix.cost("f", "p"), 0.2);
+        assert_eq!(matrix.cost("p", "k"), 1.0);
+
+        let params = AlignParams {
+            substitution: matrix,
+            ..AlignParams::default()
+        };
+        assert!((nw_align("pater", "fater", &params).cost - 0.2).abs() < 1e-12);
+        assert!("matrix".parse::<SubstitutionCost>().is_err());
+    }
+
+    #[test]
+    fn test_cost_model() {
+        let uniform = CostModel::default();
+        for (a, b) in [("pater", "fater"), ("mater", "mutter"), ("", "pa")] {
+            let weighted = phonetic_distance_with_costs(a, b, &uniform);
+            assert!((weighted - phonetic_distance(a, b)).abs() < 1e-12);
+        }
+
+        let table = HashMap::from([
+            (("p".to_string(), "b".to_string()), 0.2),
+            (("h".to_string(), "-".to_string()), 0.1),
+        ]);
+        let costs = CostModel::from_table(&table, 1.0).unwrap();
+        assert!((phonetic_distance_with_costs("ba", "pa", &costs) - 0.9).abs() < 1e-12);
+        assert!((phonetic_distance_with_costs("ha", "a", &costs) - 0.95).abs() < 1e-12);
+        assert!((dtw_align_with_costs("pata", "bata", &costs).cost - 0.2).abs() < 1e-12);
+
+        let matrix = compute_similarity_matrix_with_costs(&["ba".into(), "pa".into()], &costs);
+        assert!((matrix[[0, 1]] - 0.9).abs() < 1e-12);
+
+        let negative = HashMap::from([(("p".to_string(), "b".to_string()), -1.0)]);
+        assert!(CostModel::from_table(&negative, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_contextual_sound_correspondences() {
+        let pairs = [
+            ("pata", "fata"),
+            ("pak", "fak"),
+            ("apa", "afa"),
+            ("tapa", "tafa"),
+            ("kata", "kada"),
+        ];
+        let alignments: Vec<Alignment> = pairs.iter().map(|(a, b)| dtw_align(a, b)).collect();
+        let result = contextual_sound_correspondences(&alignments);
+        assert_eq!(result.columns, 18);
+        assert_eq!(result.correspondences.len(), 2);
+
+        let regular = &result.correspondences[0];
+        assert_eq!((regular.segment_a.as_str(), regular.segment_b.as_str()), ("p", "f"));
+        assert_eq!(regular.count, 4);
+        assert_eq!(regular.positions, [2, 2, 0]);
+        assert_eq!(regular.contexts[0], ("#".to_string(), "a".to_string(), 2));
+        assert!(regular.pmi > 0.0 && regular.count as f64 > regular.expected);
+
+        let sporadic = &result.correspondences[1];
+        assert_eq!((sporadic.segment_a.as_str(), sporadic.segment_b.as_str()), ("t", "d"));
+        assert!(regular.chi_square > sporadic.chi_square);
+
+        assert_eq!(result.filter(2, None, None).len(), 1);
+        assert!(result.filter(1, None, Some(f64::INFINITY)).is_empty());
+    }
+
     #[test]
     fn test_lcs() {
         let ratio = lcs_ratio("abcd", "acd");
         assert!(ratio > 0.7);
     }
-}
 
+    #[test]
+    fn test_similarity_metrics() {
+        // Padded bigrams: #p pa at te er r# vs #f fa at te er r#
+        assert!((ngram_jaccard("pater", "fater", 2) - 4.0 / 8.0).abs() < 1e-12);
+        assert!((ngram_dice("pater", "fater", 2) - 8.0 / 12.0).abs() < 1e-12);
+        assert!((jaro_winkler_similarity("martha", "marhta") - 0.961).abs() < 1e-3);
+        assert_eq!(jaro_winkler_similarity("", "abc"), 0.0);
+        for metric in ["levenshtein", "lcs", "jaccard3", "dice", "jaro_winkler"] {
+            let metric: SimilarityMetric = metric.parse().unwrap();
+            assert_eq!(metric.similarity("t͡ʃiŋ", "t͡ʃiŋ"), 1.0);
+            assert_eq!(metric.similarity("", ""), 1.0);
+        }
+        assert!("cosine".parse::<SimilarityMetric>().is_err());
+
+        let strings = vec!["pater".to_string(), "fater".to_string(), "mater".to_string()];
+        let matrix = compute_similarity_matrix_with_metric(&strings, SimilarityMetric::Dice(2));
+        let blocks: Vec<_> = similarity_blocks(&strings, 2, f64::NEG_INFINITY)
+            .with_metric(SimilarityMetric::Dice(2))
+            .flat_map(|block| block.pairs)
+            .collect();
+        for (i, j, sim) in blocks {
+            assert_eq!(matrix[[i, j]], sim);
+        }
+    }
+
+    #[test]
+    fn test_phonetic_key_and_blocking() {
+        assert_eq!(phonetic_key("pater", KeyScheme::Consonants), "PTR");
+        assert_eq!(phonetic_key("fater", KeyScheme::Consonants), "PTR");
+        assert_eq!(phonetic_key("ˈmʊtːɐ", KeyScheme::Full), "MVTV");
+        assert_eq!(phonetic_key("t͡ʃiŋ", KeyScheme::Consonants), "SN");
+        assert_eq!(phonetic_key("tres", KeyScheme::Dolgopolsky), "TR");
+        assert_eq!(phonetic_key("θriː", KeyScheme::Dolgopolsky), "TR");
+        assert!("soundex".parse::<KeyScheme>().is_err());
+
+        let words: Vec<String> = ["pater", "fater", "mus", "tres", "θriː", "hand"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            block_by_key(&words, KeyScheme::Dolgopolsky),
+            vec![vec![0, 1], vec![3, 4]]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Proto-form reconstruction from the members of one cognate set.
+//!
+//! The reflexes are star-aligned onto the most central member (as for
+//! correspondence patterns), and each alignment column is reconstructed on
+//! its own: every attested segment votes for each candidate segment with
+//! weight 1 minus their feature-weighted substitution cost, so near misses
+//! (p for b) still support a candidate. A gap (`-`) is a candidate too and
+//! only matches other gaps; columns reconstructed as gaps are left out of
+//! the proto-form. A column's confidence is the winner's support over the
+//! number of reflexes, 1 when they all agree.
+//!
+//! Segments with no counterpart in the central member are not reconstructed.
+
+use rayon::prelude::*;
+
+use crate::correspondence::star_align;
+use crate::error::LangVizError;
+use crate::phonetic::SubstitutionCost;
+
+/// Gap symbol in alignment columns
+const GAP: &str = "-";
+
+/// Reconstructed proto-form of one cognate set
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtoForm {
+    pub form: String,
+    pub segments: Vec<String>,
+    /// Confidence per segment, parallel to `segments`
+    pub confidence: Vec<f64>,
+}
+
+/// Feature-weighted vote for the segment of one alignment column, with its
+/// confidence
+fn reconstruct_column<'a>(column: &'a [String], costs: &SubstitutionCost) -> (&'a str, f64) {
+    let similarity = |a: &str, b: &str| match (a == GAP, b == GAP) {
+        (true, true) => 1.0,
+        (false, false) => 1.0 - costs.cost(a, b),
+        _ => 0.0,
+    };
+
+    let mut candidates: Vec<&str> = column.iter().map(String::as_str).collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    let scored = candidates.into_iter().map(|candidate| {
+        let support: f64 = column.iter().map(|s| similarity(candidate, s)).sum();
+        let count = column.iter().filter(|s| *s == candidate).count();
+        (candidate, support, count)
+    });
+    // Highest support, then most exact matches, then alphabetical
+    let (segment, support, _) = scored
+        .reduce(|best, next| {
+            let better = next.1.total_cmp(&best.1).then(next.2.cmp(&best.2));
+            if better.is_gt() {
+                next
+            } else {
+                best
+            }
+        })
+        .unwrap();
+    (segment, support / column.len() as f64)
+}
+
+/// Reconstruct the proto-form of a cognate set from its IPA reflexes
+pub fn reconstruct_proto(forms: &[String]) -> Result<ProtoForm, LangVizError> {
+    if forms.is_empty() {
+        return Err(LangVizError::EmptyInput("cognate set".to_string()));
+    }
+    let forms: Vec<&str> = forms.iter().map(String::as_str).collect();
+    // Every column votes over the same inventory
+    let costs = SubstitutionCost::FeatureWeighted.precomputed(&forms);
+    let (segments, confidence): (Vec<String>, Vec<f64>) = star_align(&forms)
+        .iter()
+        .map(|column| reconstruct_column(column, &costs))
+        .filter(|&(segment, _)| segment != GAP)
+        .map(|(segment, confidence)| (segment.to_string(), confidence))
+        .unzip();
+    Ok(ProtoForm {
+        form: segments.concat(),
+        segments,
+        confidence,
+    })
+}
+
+/// `reconstruct_proto` for many cognate sets in parallel
+pub fn reconstruct_protos(cognate_sets: &[Vec<String>]) -> Result<Vec<ProtoForm>, LangVizError> {
+    cognate_sets
+        .par_iter()
+        .map(|forms| reconstruct_proto(forms))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(ipa: &[&str]) -> Vec<String> {
+        ipa.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_reconstruct_proto() {
+        let proto = reconstruct_proto(&forms(&["pater", "pater", "fater", "padre"])).unwrap();
+        assert_eq!(proto.form, "pater");
+        assert_eq!(proto.segments.len(), proto.confidence.len());
+        assert!(proto.confidence[1] > proto.confidence[0]);
+        assert!(proto.confidence.iter().all(|&c| c > 0.0 && c <= 1.0));
+
+        // Unanimous columns are certain
+        let proto = reconstruct_proto(&forms(&["kant", "kant", "kanta"])).unwrap();
+        assert_eq!(proto.form, "kant");
+        assert_eq!(proto.confidence, vec![1.0; 4]);
+        // A segment most reflexes lost reconstructs as a gap
+        let column = forms(&["-", "a", "-"]);
+        let (segment, confidence) = reconstruct_column(&column, &SubstitutionCost::FeatureWeighted);
+        assert_eq!(segment, GAP);
+        assert!((confidence - 2.0 / 3.0).abs() < 1e-12);
+
+        let single = reconstruct_proto(&forms(&["t͡ʃiŋ"])).unwrap();
+        assert_eq!(single.segments, ["t͡ʃ", "i", "ŋ"]);
+        assert!(reconstruct_proto(&[]).is_err());
+        let sets = [forms(&["a"]), forms(&["b"])];
+        assert_eq!(reconstruct_protos(&sets).unwrap().len(), 2);
+    }
+}
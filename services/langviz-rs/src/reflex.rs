@@ -0,0 +1,292 @@
+//! Reflex prediction: the expected form of a missing entry in a target
+//! language, with a confidence score.
+//!
+//! Each alignment site of the cognate set is predicted on its own. A
+//! correspondence pattern that agrees with the site and attests the target
+//! language decides first; otherwise the sound laws learned from all sites
+//! (how each attested segment of every other language surfaces in the
+//! target) vote. Site confidence is `best / (evidence + 1)`, so one
+//! supporting site gives 0.5 and more support approaches 1. The word's
+//! confidence is the geometric mean over its sites. Each prediction carries
+//! a derivation with one step per site: the attested segments, the evidence
+//! that decided and the predicted segment.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::correspondence::{align_sites, compatible, PatternAnalysis, Site, UNKNOWN};
+use crate::soundchange::{Derivation, DerivationStep};
+
+/// Predicted reflex for one cognate set
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReflexPrediction {
+    /// Predicted form (gaps omitted, `?` where nothing is known)
+    pub form: String,
+    pub confidence: f64,
+    /// (segment, confidence) per alignment site; `-` predicts a gap
+    pub segments: Vec<(String, f64)>,
+    /// From the attested members to `form`, one step per site
+    pub derivation: Derivation,
+}
+
+/// Target-language segment counts per (language, segment)
+type SoundLaws<'a> = HashMap<(usize, &'a str), BTreeMap<&'a str, u64>>;
+
+struct SitePredictor<'a> {
+    analysis: &'a PatternAnalysis,
+    column: usize,
+    /// Sites per pattern attesting the target language
+    support: Vec<u64>,
+    laws: SoundLaws<'a>,
+}
+
+impl<'a> SitePredictor<'a> {
+    fn new(analysis: &'a PatternAnalysis, column: usize) -> Self {
+        let mut support = vec![0; analysis.patterns.len()];
+        let mut laws: SoundLaws = HashMap::new();
+        for (set, sites) in analysis.sites.iter().enumerate() {
+            for (index, site) in sites.iter().enumerate() {
+                let Some(target) = site[column].as_deref() else {
+                    continue;
+                };
+                support[analysis.site_patterns[set][index]] += 1;
+                for (language, value) in site.iter().enumerate() {
+                    if let (true, Some(segment)) = (language != column, value.as_deref()) {
+                        let counts = laws.entry((language, segment)).or_default();
+                        *counts.entry(target).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        Self {
+            analysis,
+            column,
+            support,
+            laws,
+        }
+    }
+
+    /// Predicted segment, its confidence and the evidence used
+    fn predict(&self, site: &Site) -> (String, f64, String) {
+        // Best-supported agreeing pattern that attests the target
+        let pattern = self
+            .analysis
+            .patterns
+            .iter()
+            .enumerate()
+            .filter(|(p, pattern)| self.support[*p] > 0 && compatible(&pattern.reflexes, site))
+            .max_by(|a, b| {
+                self.support[a.0]
+                    .cmp(&self.support[b.0])
+                    .then(b.0.cmp(&a.0))
+            });
+        if let Some((p, pattern)) = pattern {
+            let support = self.support[p] as f64;
+            let reflex = pattern.reflexes[self.column].clone().unwrap();
+            let evidence = format!("pattern {} ({} sites)", p, self.support[p]);
+            return (reflex, support / (support + 1.0), evidence);
+        }
+
+        // Otherwise let every attested segment vote through its sound laws
+        let mut votes: BTreeMap<&str, u64> = BTreeMap::new();
+        for (language, value) in site.iter().enumerate() {
+            let Some(segment) = value.as_deref() else {
+                continue;
+            };
+            if let Some(counts) = self.laws.get(&(language, segment)) {
+                for (&target, &count) in counts {
+                    *votes.entry(target).or_insert(0) += count;
+                }
+            }
+        }
+        let total: u64 = votes.values().sum();
+        match votes
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+        {
+            Some((target, best)) => (
+                target.to_string(),
+                best as f64 / (total as f64 + 1.0),
+                format!("sound laws ({} of {} votes)", best, total),
+            ),
+            None => (UNKNOWN.to_string(), 0.0, "no evidence".to_string()),
+        }
+    }
+
+    fn predict_sites(&self, sites: &[Site]) -> Option<ReflexPrediction> {
+        if sites.is_empty() {
+            return None;
+        }
+        let predictions: Vec<(String, f64, String)> =
+            sites.iter().map(|site| self.predict(site)).collect();
+        let form: String = predictions
+            .iter()
+            .filter(|(segment, _, _)| segment != "-")
+            .map(|(segment, _, _)| segment.as_str())
+            .collect();
+        let log_sum: f64 = predictions.iter().map(|(_, c, _)| c.ln()).sum();
+        let confidence = (log_sum / predictions.len() as f64).exp();
+
+        let steps = sites
+            .iter()
+            .zip(&predictions)
+            .map(|(site, (segment, _, evidence))| DerivationStep {
+                rule: evidence.clone(),
+                before: self.describe(site.iter().map(|v| v.as_deref().unwrap_or(""))),
+                after: segment.clone(),
+            })
+            .collect();
+        let members: Vec<String> = (0..self.analysis.languages.len())
+            .map(|language| {
+                sites
+                    .iter()
+                    .filter_map(|site| site[language].as_deref())
+                    .filter(|segment| *segment != "-")
+                    .collect()
+            })
+            .collect();
+        let derivation = Derivation {
+            input: self.describe(members.iter().map(String::as_str)),
+            output: form.clone(),
+            steps,
+        };
+        let segments = predictions
+            .into_iter()
+            .map(|(segment, confidence, _)| (segment, confidence))
+            .collect();
+
+        Some(ReflexPrediction {
+            form,
+            confidence,
+            segments,
+            derivation,
+        })
+    }
+
+    /// "lat p, eng f" from one value per language; the target language
+    /// and empty values are left out
+    fn describe<'s>(&self, values: impl Iterator<Item = &'s str>) -> String {
+        values
+            .enumerate()
+            .filter(|&(language, value)| language != self.column && !value.is_empty())
+            .map(|(language, value)| format!("{} {}", self.analysis.languages[language], value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl PatternAnalysis {
+    fn language_column(&self, language: &str) -> Option<usize> {
+        self.languages
+            .binary_search_by(|l| l.as_str().cmp(language))
+            .ok()
+    }
+
+    /// Predict the `language` reflex of a cognate set of (language, ipa)
+    /// members; members in languages the analysis has not seen are ignored
+    pub fn predict_reflex(
+        &self,
+        members: &[(String, String)],
+        language: &str,
+    ) -> Option<ReflexPrediction> {
+        let column = self.language_column(language)?;
+        let known: Vec<(String, String)> = members
+            .iter()
+            .filter(|(l, _)| l != language)
+            .cloned()
+            .collect();
+        let sites = align_sites(&known, &self.languages);
+        SitePredictor::new(self, column).predict_sites(&sites)
+    }
+
+    /// Predictions for every analyzed cognate set lacking `language`, as
+    /// (set index, prediction)
+    pub fn predict_missing(&self, language: &str) -> Vec<(usize, ReflexPrediction)> {
+        let Some(column) = self.language_column(language) else {
+            return Vec::new();
+        };
+        let predictor = SitePredictor::new(self, column);
+
+        self.sites
+            .iter()
+            .enumerate()
+            .filter(|(_, sites)| sites.iter().all(|s| s[column].is_none()))
+            .filter_map(|(set, sites)| Some((set, predictor.predict_sites(sites)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::correspondence::correspondence_patterns;
+
+    fn member(language: &str, ipa: &str) -> (String, String) {
+        (language.into(), ipa.into())
+    }
+
+    fn sets() -> Vec<Vec<(String, String)>> {
+        vec![
+            vec![
+                member("lat", "pater"),
+                member("eng", "fater"),
+                member("deu", "vater"),
+            ],
+            vec![
+                member("lat", "pes"),
+                member("eng", "fes"),
+                member("deu", "ves"),
+            ],
+            vec![
+                member("lat", "pisk"),
+                member("eng", "fisk"),
+                member("deu", "visk"),
+            ],
+            vec![
+                member("lat", "tres"),
+                member("eng", "θres"),
+                member("deu", "dres"),
+            ],
+            vec![member("lat", "pit"), member("eng", "fit")],
+        ]
+    }
+
+    #[test]
+    fn test_predict_missing() {
+        let analysis = correspondence_patterns(&sets());
+        let predictions = analysis.predict_missing("deu");
+        assert_eq!(predictions.len(), 1);
+
+        let (set, prediction) = &predictions[0];
+        assert_eq!(*set, 4);
+        assert_eq!(prediction.form, "vit");
+        assert!(prediction.confidence > 0.5 && prediction.confidence < 1.0);
+        assert_eq!(prediction.segments.len(), 3);
+        assert!(analysis.predict_missing("got").is_empty());
+    }
+
+    #[test]
+    fn test_predict_reflex_for_new_set() {
+        let analysis = correspondence_patterns(&sets());
+        let prediction = analysis
+            .predict_reflex(&[member("lat", "ter"), member("eng", "θer")], "deu")
+            .unwrap();
+        assert_eq!(prediction.form, "der");
+        let derivation = &prediction.derivation;
+        assert_eq!(derivation.input, "eng θer, lat ter");
+        assert_eq!(derivation.output, "der");
+        assert_eq!(derivation.steps.len(), prediction.segments.len());
+        assert_eq!(derivation.steps[0].before, "eng θ, lat t");
+        assert_eq!(derivation.steps[0].after, "d");
+        assert!(derivation.steps[0].rule.starts_with("pattern "));
+
+        // Unseen segments cannot be predicted
+        let unknown = analysis
+            .predict_reflex(&[member("lat", "qo")], "deu")
+            .unwrap();
+        assert!(unknown.form.starts_with('?'));
+        assert_eq!(unknown.confidence, 0.0);
+        assert!(analysis
+            .predict_reflex(&[member("xyz", "pa")], "deu")
+            .is_none());
+    }
+}
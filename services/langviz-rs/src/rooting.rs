@@ -0,0 +1,233 @@
+//! Rerooting of inferred trees by an outgroup or at the midpoint.
+//!
+//! Neighbor-Joining roots its output wherever the last join happened, which
+//! says nothing about ancestry. Both functions read the tree as unrooted and
+//! place a new root on one of its branches: the branch separating the named
+//! outgroup from everything else (at its midpoint), or the point halfway
+//! along the longest leaf-to-leaf path. Nodes left with a single child are
+//! removed, their branch lengths added to the child's.
+
+use std::collections::HashSet;
+
+use crate::tree::{Tree, TreeNode};
+
+/// Tree rooted on the branch between the `outgroup` leaves and the rest
+pub fn root_with_outgroup(tree: &Tree, outgroup: &[String]) -> Result<Tree, String> {
+    let leaves = tree.leaves();
+    let wanted: HashSet<&str> = outgroup.iter().map(String::as_str).collect();
+    let known: HashSet<String> = leaves.iter().map(|&leaf| tree.node_name(leaf)).collect();
+    if let Some(name) = wanted.iter().find(|name| !known.contains(**name)) {
+        return Err(format!("outgroup leaf '{}' is not in the tree", name));
+    }
+    if wanted.is_empty() || wanted.len() == leaves.len() {
+        return Err("the outgroup must hold some but not all leaves".to_string());
+    }
+
+    // Outgroup leaves below each node
+    let mut below = vec![(0, 0); tree.nodes.len()];
+    for &leaf in &leaves {
+        below[leaf] = (
+            1,
+            usize::from(wanted.contains(tree.node_name(leaf).as_str())),
+        );
+    }
+    let mut branch = None;
+    for &node in tree.preorder().iter().rev() {
+        let (size, inside) = below[node];
+        if let Some(parent) = tree.nodes[node].parent {
+            below[parent].0 += size;
+            below[parent].1 += inside;
+            let outgroup_side = inside == size && inside == wanted.len();
+            let ingroup_side = inside == 0 && size == leaves.len() - wanted.len();
+            if branch.is_none() && (outgroup_side || ingroup_side) {
+                branch = Some(node);
+            }
+        }
+    }
+    let node = branch.ok_or("the outgroup is not a clade of the unrooted tree")?;
+
+    // Below a root with two children the branch runs on through the root
+    let mut half = tree.nodes[node].length / 2.0;
+    if let [first, second] = tree.nodes[tree.root].children[..] {
+        if node == first || node == second {
+            let sibling = if node == first { second } else { first };
+            half = (tree.nodes[first].length + tree.nodes[second].length) / 2.0;
+            if half > tree.nodes[node].length {
+                return Ok(reroot(tree, sibling, half));
+            }
+        }
+    }
+    Ok(reroot(tree, node, half))
+}
+
+/// Tree rooted halfway between the two leaves farthest apart
+pub fn midpoint_root(tree: &Tree) -> Result<Tree, String> {
+    let leaves = tree.leaves();
+    if leaves.len() < 2 {
+        return Err("midpoint rooting needs at least two leaves".to_string());
+    }
+    // The leaf farthest from any leaf ends a longest path
+    let (start, _) = farthest(tree, leaves[0]);
+    let (end, previous) = farthest(tree, start);
+    let mut remaining = previous[end].1 / 2.0;
+
+    // Walk back from `end` until the midpoint's branch
+    let mut node = end;
+    loop {
+        let (next, _) = previous[node];
+        let length = edge_length(tree, node, next);
+        if length >= remaining {
+            return Ok(if tree.nodes[node].parent == Some(next) {
+                reroot(tree, node, remaining)
+            } else {
+                reroot(tree, next, length - remaining)
+            });
+        }
+        remaining -= length;
+        node = next;
+    }
+}
+
+fn edge_length(tree: &Tree, a: usize, b: usize) -> f64 {
+    if tree.nodes[a].parent == Some(b) {
+        tree.nodes[a].length
+    } else {
+        tree.nodes[b].length
+    }
+}
+
+fn neighbors(tree: &Tree, node: usize) -> impl Iterator<Item = usize> + '_ {
+    let current = &tree.nodes[node];
+    current.children.iter().chain(&current.parent).copied()
+}
+
+/// Leaf farthest from `start` by branch length, with each node's
+/// predecessor towards `start` and distance from it
+fn farthest(tree: &Tree, start: usize) -> (usize, Vec<(usize, f64)>) {
+    let mut previous = vec![(usize::MAX, 0.0); tree.nodes.len()];
+    previous[start] = (start, 0.0);
+    let mut stack = vec![start];
+    let mut best = (start, 0.0);
+    while let Some(node) = stack.pop() {
+        let distance = previous[node].1;
+        if tree.nodes[node].children.is_empty() && distance > best.1 {
+            best = (node, distance);
+        }
+        for next in neighbors(tree, node) {
+            if previous[next].0 == usize::MAX {
+                previous[next] = (node, distance + edge_length(tree, node, next));
+                stack.push(next);
+            }
+        }
+    }
+    (best.0, previous)
+}
+
+/// Copy of `tree` rooted on the branch above `node`, `offset` from `node`
+fn reroot(tree: &Tree, node: usize, offset: f64) -> Tree {
+    let parent = tree.nodes[node]
+        .parent
+        .expect("the root has no branch above it");
+    let length = tree.nodes[node].length;
+    let mut nodes = vec![TreeNode {
+        name: None,
+        length: 0.0,
+        parent: None,
+        children: Vec::new(),
+    }];
+    for (start, from, length) in [(node, parent, offset), (parent, node, length - offset)] {
+        let child = copy_away(tree, start, from, length.max(0.0), 0, &mut nodes);
+        nodes[0].children.push(child);
+    }
+    Tree { nodes, root: 0 }
+}
+
+/// Copy the side of `tree` reached from `from` through `node` below `parent`
+/// in `nodes`, skipping nodes that would keep a single child
+fn copy_away(
+    tree: &Tree,
+    mut node: usize,
+    mut from: usize,
+    mut length: f64,
+    parent: usize,
+    nodes: &mut Vec<TreeNode>,
+) -> usize {
+    let mut away: Vec<usize> = neighbors(tree, node).filter(|&n| n != from).collect();
+    while let [only] = away[..] {
+        length += edge_length(tree, node, only);
+        from = node;
+        node = only;
+        away = neighbors(tree, node).filter(|&n| n != from).collect();
+    }
+    let index = nodes.len();
+    nodes.push(TreeNode {
+        name: tree.nodes[node].name.clone(),
+        length,
+        parent: Some(parent),
+        children: Vec::new(),
+    });
+    for next in away {
+        let child = copy_away(
+            tree,
+            next,
+            node,
+            edge_length(tree, node, next),
+            index,
+            nodes,
+        );
+        nodes[index].children.push(child);
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_root_with_outgroup() {
+        let tree = Tree::from_newick("((a:1,b:1):2,c:3,(d:1,e:1):4);").unwrap();
+        let rooted = root_with_outgroup(&tree, &names(&["d", "e"])).unwrap();
+        assert_eq!(rooted.to_newick(), "((d:1,e:1):2,((a:1,b:1):2,c:3):2);");
+
+        // The complement of a clade is a clade of the unrooted tree too
+        let complement = root_with_outgroup(&tree, &names(&["a", "b", "c"])).unwrap();
+        assert_eq!(complement, rooted);
+
+        let rooted = root_with_outgroup(&tree, &names(&["c"])).unwrap();
+        assert_eq!(rooted.to_newick(), "(c:1.5,((a:1,b:1):2,(d:1,e:1):4):1.5);");
+
+        // Rerooting a two-child root splits the branch through it evenly
+        let lopsided = Tree::from_newick("(a:1,(b:1,(c:1,d:1):1):3);").unwrap();
+        let rooted = root_with_outgroup(&lopsided, &names(&["a"])).unwrap();
+        assert_eq!(rooted.to_newick(), "((b:1,(c:1,d:1):1):2,a:2);");
+
+        assert!(root_with_outgroup(&tree, &names(&["a", "c"])).is_err());
+        assert!(root_with_outgroup(&tree, &names(&["x"])).is_err());
+        assert!(root_with_outgroup(&tree, &[]).is_err());
+    }
+
+    #[test]
+    fn test_midpoint_root() {
+        // Longest path a..e is 1 + 2 + 4 + 1 = 8, its midpoint 4 from a
+        let tree = Tree::from_newick("((a:1,b:1):2,c:3,(d:1,e:1):4);").unwrap();
+        let rooted = midpoint_root(&tree).unwrap();
+        assert_eq!(rooted.to_newick(), "((d:1,e:1):3,((a:1,b:1):2,c:3):1);");
+        assert_eq!(rooted.leaves().len(), 5);
+
+        // An already midpoint-rooted tree keeps its shape
+        let balanced = Tree::from_newick("((a:1,b:1):1,(c:1,d:1):1);").unwrap();
+        let rooted = midpoint_root(&balanced).unwrap();
+        let lengths: Vec<f64> = rooted.nodes[rooted.root]
+            .children
+            .iter()
+            .map(|&child| rooted.nodes[child].length)
+            .collect();
+        assert_eq!(lengths, vec![1.0, 1.0]);
+        assert!(midpoint_root(&Tree::from_newick("a;").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Interactions and ordering evidence within a sound change rule list.
+//!
+//! Rule A feeds rule B on a word when, in the word's derivation, B finds a
+//! match it would not have found had A been left out, and bleeds B when
+//! leaving A out would have given B a match. For A listed after B the same
+//! question is asked with A moved to just before B: that is what the given
+//! order forgoes (counterfeeding and counterbleeding). Whether an
+//! interaction matters is decided by the attested
+//! reflexes: for every interacting pair the derivation is rerun with the
+//! two rules swapped, and words derived correctly in only one of the two
+//! orders vote for it. A pair with votes both ways is an ordering conflict
+//! (no single order derives every word); otherwise the votes are precedence
+//! constraints, and a rule order satisfying all of them is returned when one
+//! exists, staying as close to the given order as the constraints allow.
+
+use rayon::prelude::*;
+
+use crate::phonetic::segment_ipa;
+use crate::soundchange::SoundChangeRule;
+
+/// How often rule `earlier` feeds or bleeds rule `later`; with `earlier`
+/// listed after `later`, how often it would if moved just before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleInteraction {
+    pub earlier: usize,
+    pub later: usize,
+    /// Words where `earlier` creates a match for `later`
+    pub feeding: usize,
+    /// Words where `earlier` removes a match for `later`
+    pub bleeding: usize,
+}
+
+/// Words whose reflex only one order of two rules derives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderEvidence {
+    /// The earlier of the two rules in the given order
+    pub first: usize,
+    pub second: usize,
+    /// Words derived correctly with `first` before `second` only
+    pub supporting: usize,
+    /// Words derived correctly with `second` before `first` only
+    pub contradicting: usize,
+}
+
+impl OrderEvidence {
+    pub fn conflicting(&self) -> bool {
+        self.supporting > 0 && self.contradicting > 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderingAnalysis {
+    /// Interacting ordered rule pairs, by earlier then later rule
+    pub interactions: Vec<RuleInteraction>,
+    /// Swap evidence per interacting rule pair, by first then second rule
+    pub evidence: Vec<OrderEvidence>,
+    /// A rule order (indices) meeting every non-conflicting precedence, or
+    /// `None` when the precedences form a cycle
+    pub consistent_order: Option<Vec<usize>>,
+    /// Whether the given order meets every non-conflicting precedence
+    pub given_order_consistent: bool,
+}
+
+impl OrderingAnalysis {
+    /// Rule pairs (first, second) with evidence for both orders
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        self.evidence
+            .iter()
+            .filter(|evidence| evidence.conflicting())
+            .map(|evidence| (evidence.first, evidence.second))
+            .collect()
+    }
+}
+
+/// Feeding / bleeding interactions of `rules` over `proto_forms`, and which
+/// orders of the interacting rules the `attested` reflexes support
+pub fn analyze_rule_ordering(
+    proto_forms: &[String],
+    attested: &[String],
+    rules: &[SoundChangeRule],
+) -> OrderingAnalysis {
+    let n = rules.len();
+    let pairs: Vec<(usize, usize)> = (0..n)
+        .flat_map(|a| (0..n).filter(move |&b| b != a).map(move |b| (a, b)))
+        .collect();
+
+    // Per word: the form before each rule of the given derivation
+    let states: Vec<Vec<Vec<String>>> = proto_forms
+        .par_iter()
+        .map(|word| {
+            let mut segments = segment_ipa(word);
+            let mut states = Vec::with_capacity(n);
+            for rule in rules {
+                let next = rule.apply(&segments);
+                states.push(std::mem::replace(&mut segments, next));
+            }
+            states
+        })
+        .collect();
+
+    let interactions: Vec<RuleInteraction> = pairs
+        .par_iter()
+        .map(|&(earlier, later)| {
+            let (mut feeding, mut bleeding) = (0, 0);
+            for word in &states {
+                // The input `later` sees with and without `earlier`
+                let (with, without) = if earlier < later {
+                    let skipped = derive(&word[earlier], rules, earlier + 1..later);
+                    (word[later].clone(), skipped)
+                } else {
+                    (rules[earlier].apply(&word[later]), word[later].clone())
+                };
+                let applies = |form: &[String]| rules[later].apply(form) != form;
+                match (applies(&with), applies(&without)) {
+                    (true, false) => feeding += 1,
+                    (false, true) => bleeding += 1,
+                    _ => {}
+                }
+            }
+            RuleInteraction {
+                earlier,
+                later,
+                feeding,
+                bleeding,
+            }
+        })
+        .filter(|interaction| interaction.feeding + interaction.bleeding > 0)
+        .collect();
+
+    let mut interacting: Vec<(usize, usize)> = interactions
+        .iter()
+        .map(|i| (i.earlier.min(i.later), i.earlier.max(i.later)))
+        .collect();
+    interacting.sort_unstable();
+    interacting.dedup();
+
+    let attested: Vec<Vec<String>> = attested.iter().map(|form| segment_ipa(form)).collect();
+    let given: Vec<bool> = states
+        .iter()
+        .zip(&attested)
+        .map(|(word, attested)| match word.first() {
+            Some(input) => derive(input, rules, 0..n) == *attested,
+            None => true,
+        })
+        .collect();
+    let evidence: Vec<OrderEvidence> = interacting
+        .par_iter()
+        .map(|&(first, second)| {
+            let mut order: Vec<usize> = (0..n).collect();
+            order.swap(first, second);
+            let (mut supporting, mut contradicting) = (0, 0);
+            for ((word, attested), &given) in states.iter().zip(&attested).zip(&given) {
+                let swapped = derive(&word[0], rules, order.iter().copied()) == *attested;
+                match (given, swapped) {
+                    (true, false) => supporting += 1,
+                    (false, true) => contradicting += 1,
+                    _ => {}
+                }
+            }
+            OrderEvidence {
+                first,
+                second,
+                supporting,
+                contradicting,
+            }
+        })
+        .collect();
+
+    // Precedences from one-sided evidence
+    let mut before: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for evidence in evidence.iter().filter(|evidence| !evidence.conflicting()) {
+        if evidence.supporting > 0 {
+            before[evidence.second].push(evidence.first);
+        } else if evidence.contradicting > 0 {
+            before[evidence.first].push(evidence.second);
+        }
+    }
+    let given_order_consistent = before
+        .iter()
+        .enumerate()
+        .all(|(rule, earlier)| earlier.iter().all(|&other| other < rule));
+
+    OrderingAnalysis {
+        interactions,
+        evidence,
+        consistent_order: topological_order(&before),
+        given_order_consistent,
+    }
+}
+
+fn derive(
+    input: &[String],
+    rules: &[SoundChangeRule],
+    order: impl Iterator<Item = usize>,
+) -> Vec<String> {
+    order.fold(input.to_vec(), |segments, rule| {
+        rules[rule].apply(&segments)
+    })
+}
+
+/// Order placing every rule after those listed in `before[rule]`, taking the
+/// lowest-numbered ready rule each step; `None` on a cycle
+fn topological_order(before: &[Vec<usize>]) -> Option<Vec<usize>> {
+    let n = before.len();
+    let mut placed = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    while order.len() < n {
+        let next = (0..n)
+            .find(|&rule| !placed[rule] && before[rule].iter().all(|&other| placed[other]))?;
+        placed[next] = true;
+        order.push(next);
+    }
+    Some(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soundchange::parse_rules;
+
+    fn words(forms: &[&str]) -> Vec<String> {
+        forms.iter().map(|form| form.to_string()).collect()
+    }
+
+    #[test]
+    fn test_feeding_and_bleeding() {
+        // Voicing between vowels feeds spirantization of voiced stops;
+        // apocope bleeds final devoicing
+        let rules =
+            parse_rules(&["t > d / V_V", "d > ð / V_V", "e > ∅ / _#", "d > t / _#"]).unwrap();
+        let proto = words(&["ata", "ade"]);
+        let attested = words(&["aða", "að"]);
+        let analysis = analyze_rule_ordering(&proto, &attested, &rules);
+
+        let find = |earlier: usize, later: usize| {
+            analysis
+                .interactions
+                .iter()
+                .find(|i| i.earlier == earlier && i.later == later)
+                .cloned()
+        };
+        assert_eq!(find(0, 1).map(|i| (i.feeding, i.bleeding)), Some((1, 0)));
+        // Without spirantization apocope would expose d to final devoicing
+        assert_eq!(find(1, 3).map(|i| i.bleeding), Some(1));
+        assert_eq!(find(2, 3), None);
+        // Devoicing first would keep the t of ata from voicing
+        assert_eq!(find(3, 0), None);
+
+        let feeding = analysis
+            .evidence
+            .iter()
+            .find(|e| (e.first, e.second) == (0, 1))
+            .unwrap();
+        assert_eq!((feeding.supporting, feeding.contradicting), (1, 0));
+        assert!(analysis.given_order_consistent);
+        assert_eq!(analysis.consistent_order, Some(vec![0, 1, 2, 3]));
+        assert!(analysis.conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_order_conflicts() {
+        let rules = parse_rules(&["a > e", "e > i"]).unwrap();
+        let proto = words(&["pa", "pa", "ta"]);
+        let analysis = analyze_rule_ordering(&proto, &words(&["pi", "pi", "ti"]), &rules);
+        assert_eq!(analysis.conflicts(), []);
+        assert!(analysis.given_order_consistent);
+
+        let analysis = analyze_rule_ordering(&proto, &words(&["pe", "pe", "te"]), &rules);
+        assert!(!analysis.given_order_consistent);
+        assert_eq!(analysis.consistent_order, Some(vec![1, 0]));
+
+        // One word needs the rules in the given order, the other swapped
+        let analysis = analyze_rule_ordering(&proto, &words(&["pi", "pi", "te"]), &rules);
+        assert_eq!(analysis.conflicts(), [(0, 1)]);
+    }
+}
@@ -0,0 +1,233 @@
+//! Subgraph sampling for previewing very large cognate networks.
+//!
+//! All samplers are seeded so the same preview can be regenerated.
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use rand::rngs::StdRng;
+use rand::seq::{index, SliceRandom};
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use crate::graph::CognateGraph;
+
+/// Strategy used to pick the preview subgraph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplingMethod {
+    /// Uniform node sample, induced subgraph
+    Node,
+    /// Uniform edge sample plus endpoints
+    Edge,
+    /// Forest fire burning with the given forward-burning probability
+    ForestFire { forward_prob: f64 },
+    /// Random walk with restarts at the given probability
+    RandomWalk { restart_prob: f64 },
+}
+
+impl FromStr for SamplingMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node" => Ok(SamplingMethod::Node),
+            "edge" => Ok(SamplingMethod::Edge),
+            "forest_fire" => Ok(SamplingMethod::ForestFire { forward_prob: 0.7 }),
+            "random_walk" => Ok(SamplingMethod::RandomWalk { restart_prob: 0.15 }),
+            other => Err(format!(
+                "unknown sampling method '{}' (expected node, edge, forest_fire or random_walk)",
+                other
+            )),
+        }
+    }
+}
+
+impl CognateGraph {
+    /// Sample a representative subgraph of roughly `target_size` nodes
+    /// (edges for `SamplingMethod::Edge`)
+    pub fn sample(&self, method: SamplingMethod, target_size: usize, seed: u64) -> CognateGraph {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let n = self.graph.node_count();
+
+        if target_size >= n && method != SamplingMethod::Edge {
+            return self.subgraph(&vec![true; n], |_| true);
+        }
+
+        match method {
+            SamplingMethod::Node => {
+                let mut keep = vec![false; n];
+                for i in index::sample(&mut rng, n, target_size) {
+                    keep[i] = true;
+                }
+                self.subgraph(&keep, |_| true)
+            }
+            SamplingMethod::Edge => self.sample_edges(target_size, &mut rng),
+            SamplingMethod::ForestFire { forward_prob } => {
+                let keep = self.forest_fire(target_size, forward_prob, &mut rng);
+                self.subgraph(&keep, |_| true)
+            }
+            SamplingMethod::RandomWalk { restart_prob } => {
+                let keep = self.random_walk(target_size, restart_prob, &mut rng);
+                self.subgraph(&keep, |_| true)
+            }
+        }
+    }
+
+    fn sample_edges(&self, target_edges: usize, rng: &mut StdRng) -> CognateGraph {
+        let m = self.graph.edge_count();
+        let chosen: Vec<usize> = index::sample(rng, m, target_edges.min(m)).into_vec();
+
+        let mut keep_nodes = vec![false; self.graph.node_count()];
+        let mut keep_edges = vec![false; m];
+        for i in chosen {
+            keep_edges[i] = true;
+            if let Some((a, b)) = self
+                .graph
+                .edge_endpoints(petgraph::graph::EdgeIndex::new(i))
+            {
+                keep_nodes[a.index()] = true;
+                keep_nodes[b.index()] = true;
+            }
+        }
+
+        self.subgraph(&keep_nodes, |edge| keep_edges[edge.id().index()])
+    }
+
+    /// Forest fire sampling (Leskovec & Faloutsos): burn a geometric number
+    /// of unvisited neighbors from each burning node, reigniting elsewhere
+    /// when the fire dies out
+    fn forest_fire(&self, target: usize, forward_prob: f64, rng: &mut StdRng) -> Vec<bool> {
+        let n = self.graph.node_count();
+        let forward_prob = forward_prob.clamp(0.0, 0.99);
+        let mut visited = vec![false; n];
+        let mut count = 0;
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.shuffle(rng);
+
+        for start in order {
+            if count >= target {
+                break;
+            }
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+            count += 1;
+            let mut queue = VecDeque::from([NodeIndex::new(start)]);
+
+            while let Some(node) = queue.pop_front() {
+                if count >= target {
+                    break;
+                }
+
+                // Geometric burn count with mean p / (1 - p)
+                let mut burn = 0;
+                while rng.gen::<f64>() < forward_prob {
+                    burn += 1;
+                }
+
+                let mut candidates: Vec<NodeIndex> = self
+                    .graph
+                    .neighbors(node)
+                    .filter(|nb| !visited[nb.index()])
+                    .collect();
+                candidates.sort_unstable();
+                candidates.dedup();
+                candidates.shuffle(rng);
+
+                for nb in candidates.into_iter().take(burn) {
+                    if count >= target {
+                        break;
+                    }
+                    visited[nb.index()] = true;
+                    count += 1;
+                    queue.push_back(nb);
+                }
+            }
+        }
+
+        visited
+    }
+
+    /// Random walk with restart; jumps to a fresh start node if the walk
+    /// stops discovering new nodes
+    fn random_walk(&self, target: usize, restart_prob: f64, rng: &mut StdRng) -> Vec<bool> {
+        let n = self.graph.node_count();
+        let mut visited = vec![false; n];
+        let mut count = 0;
+
+        let stall_limit = 100 * target.max(1);
+
+        while count < target {
+            let start = NodeIndex::new(rng.gen_range(0..n));
+            if !visited[start.index()] {
+                visited[start.index()] = true;
+                count += 1;
+            }
+
+            let mut current = start;
+            let mut stalled = 0;
+            while count < target && stalled < stall_limit {
+                let neighbors: Vec<NodeIndex> = self.graph.neighbors(current).collect();
+                if neighbors.is_empty() || rng.gen::<f64>() < restart_prob {
+                    current = start;
+                    if neighbors.is_empty() {
+                        break;
+                    }
+                    continue;
+                }
+
+                current = neighbors[rng.gen_range(0..neighbors.len())];
+                if visited[current.index()] {
+                    stalled += 1;
+                } else {
+                    visited[current.index()] = true;
+                    count += 1;
+                    stalled = 0;
+                }
+            }
+        }
+
+        visited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn ring(n: usize) -> CognateGraph {
+        let edges = (0..n)
+            .map(|i| SimilarityEdge::new(format!("w{}", i), format!("w{}", (i + 1) % n), 0.9))
+            .collect();
+        CognateGraph::from_edges(edges, 0.5)
+    }
+
+    #[test]
+    fn test_sample_sizes() {
+        let graph = ring(200);
+
+        for method in ["node", "forest_fire", "random_walk"] {
+            let method: SamplingMethod = method.parse().unwrap();
+            let sample = graph.sample(method, 30, 7);
+            assert_eq!(sample.stats().num_nodes, 30);
+        }
+
+        let edges = graph.sample(SamplingMethod::Edge, 25, 7);
+        assert_eq!(edges.stats().num_edges, 25);
+    }
+
+    #[test]
+    fn test_sampling_is_seeded() {
+        let graph = ring(100);
+        let method = SamplingMethod::ForestFire { forward_prob: 0.7 };
+        let mut a = graph.sample(method, 20, 42).edge_list();
+        let mut b = graph.sample(method, 20, 42).edge_list();
+        a.sort_by(|x, y| x.0.cmp(&y.0).then(x.1.cmp(&y.1)));
+        b.sort_by(|x, y| x.0.cmp(&y.0).then(x.1.cmp(&y.1)));
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,100 @@
+//! Per-pair feature vectors (all distance metrics plus alignment summary
+//! statistics) for training custom models downstream.
+
+use ndarray::Array2;
+use rayon::prelude::*;
+
+use crate::features::feature_distance;
+use crate::phonetic::{dtw_align, lcs_ratio, phonetic_distance, segment_ipa};
+use crate::types::EditOp;
+
+/// Number of scores per pair
+pub const NUM_PAIR_SCORES: usize = 11;
+
+/// Score names in column order
+pub const PAIR_SCORE_NAMES: [&str; NUM_PAIR_SCORES] = [
+    "edit_similarity",
+    "lcs_ratio",
+    "feature_distance",
+    "length_difference",
+    "dtw_cost",
+    "matches",
+    "substitutions",
+    "insertions",
+    "deletions",
+    "alignment_length",
+    "match_ratio",
+];
+
+/// Segment-count difference relative to the longer form (0 when both empty)
+pub(crate) fn length_difference(ipa_a: &str, ipa_b: &str) -> f64 {
+    let len_a = segment_ipa(ipa_a).len() as f64;
+    let len_b = segment_ipa(ipa_b).len() as f64;
+    let longest = len_a.max(len_b);
+    if longest > 0.0 {
+        (len_a - len_b).abs() / longest
+    } else {
+        0.0
+    }
+}
+
+/// All scores for one pair, in `PAIR_SCORE_NAMES` order
+pub fn pair_scores(ipa_a: &str, ipa_b: &str) -> [f64; NUM_PAIR_SCORES] {
+    let alignment = dtw_align(ipa_a, ipa_b);
+    let count = |op: EditOp| alignment.operations.iter().filter(|&&o| o == op).count() as f64;
+    let matches = count(EditOp::Match);
+    let length = alignment.operations.len() as f64;
+
+    [
+        phonetic_distance(ipa_a, ipa_b),
+        lcs_ratio(ipa_a, ipa_b),
+        feature_distance(ipa_a, ipa_b),
+        length_difference(ipa_a, ipa_b),
+        alignment.cost,
+        matches,
+        count(EditOp::Substitute),
+        count(EditOp::Insert),
+        count(EditOp::Delete),
+        length,
+        if length > 0.0 { matches / length } else { 1.0 },
+    ]
+}
+
+/// Pairs × scores matrix (rows computed in parallel)
+pub fn batch_pair_scores(pairs: &[(String, String)]) -> Array2<f64> {
+    let rows: Vec<[f64; NUM_PAIR_SCORES]> = pairs
+        .par_iter()
+        .map(|(a, b)| pair_scores(a, b))
+        .collect();
+
+    let mut matrix = Array2::<f64>::zeros((rows.len(), NUM_PAIR_SCORES));
+    for (i, row) in rows.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            matrix[[i, j]] = value;
+        }
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_scores() {
+        let scores = pair_scores("pater", "pater");
+        let column = |name: &str| PAIR_SCORE_NAMES.iter().position(|&n| n == name).unwrap();
+        assert_eq!(scores[column("edit_similarity")], 1.0);
+        assert_eq!(scores[column("matches")], 5.0);
+        assert_eq!(scores[column("match_ratio")], 1.0);
+
+        let matrix = batch_pair_scores(&[
+            ("pater".into(), "pater".into()),
+            ("pʰa".into(), "pa".into()),
+        ]);
+        assert_eq!(matrix.shape(), &[2, NUM_PAIR_SCORES]);
+        assert_eq!(matrix.row(0).to_vec(), scores.to_vec());
+        assert_eq!(matrix[[1, column("substitutions")]], 1.0);
+        assert_eq!(matrix[[1, column("length_difference")]], 0.0);
+    }
+}
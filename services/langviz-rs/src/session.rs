@@ -0,0 +1,641 @@
+//! Analysis sessions: one versioned file bundling a graph, its sparse
+//! similarity matrix, named clusterings, learned cost matrices and the
+//! configuration that produced them.
+//!
+//! Sessions are stored as JSON so they can be diffed and shared.
+//!
+//! Curators can merge and split the cognate sets of a stored clustering.
+//! A set's ID is its index in the clustering. Each edit also updates the
+//! stored graph, so re-clustering it reproduces the decision: a merge links
+//! the first member of each merged set with a weight-1 "manual" edge, and a
+//! split removes the edges between its parts. Curators can also delete
+//! graph edges directly. Every edit goes into the session's audit log with
+//! a timestamp, an optional annotator ID and note, the sets before and after
+//! and the edges it touched; `audit_log_json` exports the log for review.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::format::{JsonFormat, Migration};
+use crate::graph::CognateGraph;
+use crate::sparse::SparseSimilarityMatrix;
+use crate::types::{AttrValue, ProvenanceEdge};
+
+/// Current session file format version (2: numeric node attributes,
+/// 3: curation edits, 4: audited edits and edge deletions)
+pub const SESSION_FORMAT_VERSION: u32 = 4;
+
+/// Provenance of the edges a merge adds
+pub const MANUAL_PROVENANCE: &str = "manual";
+
+/// Version 1 node attribute values were all strings, which read unchanged
+/// as text attributes
+fn migrate_v1_attributes(_session: &mut serde_json::Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// Version 2 sessions have no curation edits, which default to none
+fn migrate_v2_edits(_session: &mut serde_json::Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// Version 3 edits have no timestamp or annotator, which read as 0 and
+/// none
+fn migrate_v3_audit(_session: &mut serde_json::Value) -> Result<(), String> {
+    Ok(())
+}
+
+const SESSION_MIGRATIONS: &[Migration] =
+    &[migrate_v1_attributes, migrate_v2_edits, migrate_v3_audit];
+
+pub const SESSION_JSON: JsonFormat = JsonFormat {
+    name: "session",
+    version_key: "version",
+    current: SESSION_FORMAT_VERSION,
+    migrations: SESSION_MIGRATIONS,
+};
+
+/// Serializable form of a `CognateGraph`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    /// Node IDs in index order (isolated nodes included)
+    pub nodes: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub node_attributes: BTreeMap<String, BTreeMap<String, AttrValue>>,
+    pub edges: Vec<ProvenanceEdge>,
+}
+
+/// Serializable form of a `SparseSimilarityMatrix` (COO triplets)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SparseSnapshot {
+    pub row_ids: Vec<String>,
+    pub col_ids: Vec<String>,
+    pub triplets: Vec<(usize, usize, f64)>,
+}
+
+/// Kind of curation edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditKind {
+    Merge,
+    Split,
+    DeleteEdges,
+}
+
+impl EditKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            EditKind::Merge => "merge",
+            EditKind::Split => "split",
+            EditKind::DeleteEdges => "delete_edges",
+        }
+    }
+}
+
+/// One entry of a session's audit log: a manual merge or split of a
+/// clustering's cognate sets, or a deletion of graph edges
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurationEdit {
+    /// Milliseconds since the Unix epoch
+    #[serde(default)]
+    pub timestamp_ms: u64,
+    #[serde(default)]
+    pub annotator: Option<String>,
+    /// Clustering a merge or split edited (None for edge deletions)
+    #[serde(default)]
+    pub clustering: Option<String>,
+    pub kind: EditKind,
+    /// Set IDs the edit read: the merged sets, or the one split set
+    #[serde(default)]
+    pub sets: Vec<usize>,
+    /// Their members before the edit
+    #[serde(default)]
+    pub before: Vec<Vec<String>>,
+    /// Resulting set IDs and members
+    #[serde(default)]
+    pub result: Vec<(usize, Vec<String>)>,
+    #[serde(default)]
+    pub added_edges: Vec<ProvenanceEdge>,
+    #[serde(default)]
+    pub removed_edges: Vec<ProvenanceEdge>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Everything needed to reopen an analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    #[serde(default)]
+    pub graph: Option<GraphSnapshot>,
+    #[serde(default)]
+    pub sparse: Option<SparseSnapshot>,
+    /// Named clusterings (lists of member IDs)
+    #[serde(default)]
+    pub clusterings: BTreeMap<String, Vec<Vec<String>>>,
+    /// Named substitution cost matrices as (segment_a, segment_b, cost)
+    #[serde(default)]
+    pub cost_matrices: BTreeMap<String, Vec<(String, String, f64)>>,
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+    /// Audit log of curation edits, oldest first
+    #[serde(default)]
+    pub edits: Vec<CurationEdit>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            version: SESSION_FORMAT_VERSION,
+            graph: None,
+            sparse: None,
+            clusterings: BTreeMap::new(),
+            cost_matrices: BTreeMap::new(),
+            config: BTreeMap::new(),
+            edits: Vec::new(),
+        }
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the session as JSON
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|e| format!("cannot create session file {}: {}", path.display(), e))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|e| format!("cannot write session: {}", e))
+    }
+
+    /// Read a session, upgrading older format versions and rejecting files
+    /// written by a newer one
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("cannot open session file {}: {}", path.display(), e))?;
+        let mut document: serde_json::Value = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("invalid session file: {}", e))?;
+        SESSION_JSON.upgrade(&mut document)?;
+        serde_json::from_value(document).map_err(|e| format!("invalid session file: {}", e))
+    }
+
+    /// Merge sets of `clustering` into the one with the lowest ID; the
+    /// others are removed, shifting later IDs down. Returns the merged ID.
+    pub fn merge_sets(
+        &mut self,
+        clustering: &str,
+        set_ids: &[usize],
+        annotator: Option<String>,
+        note: Option<String>,
+    ) -> Result<usize, String> {
+        let sets = self.clustering_mut(clustering)?;
+        let mut ids = set_ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.len() < 2 {
+            return Err("merge needs at least two distinct set IDs".to_string());
+        }
+        check_set_ids(&ids, sets.len())?;
+
+        let before: Vec<Vec<String>> = ids.iter().map(|&id| sets[id].clone()).collect();
+        let target = ids[0];
+        for &id in ids[1..].iter().rev() {
+            let members = sets.remove(id);
+            sets[target].extend(members);
+        }
+        let merged = sets[target].clone();
+
+        let mut added_edges = Vec::new();
+        if let Some(graph) = self.graph.as_mut() {
+            let anchors: Vec<&String> = before.iter().filter_map(|set| set.first()).collect();
+            for pair in anchors.windows(2) {
+                let edge = (
+                    pair[0].clone(),
+                    pair[1].clone(),
+                    1.0,
+                    Some(MANUAL_PROVENANCE.to_string()),
+                );
+                graph.add_edge(edge.clone());
+                added_edges.push(edge);
+            }
+        }
+        self.edits.push(CurationEdit {
+            timestamp_ms: now_ms(),
+            annotator,
+            clustering: Some(clustering.to_string()),
+            kind: EditKind::Merge,
+            sets: ids,
+            before,
+            result: vec![(target, merged)],
+            added_edges,
+            removed_edges: Vec::new(),
+            note,
+        });
+        Ok(target)
+    }
+
+    /// Split set `set_id` of `clustering` into `partition`, which must
+    /// cover its members exactly, in at least two non-empty parts. The
+    /// first part keeps the ID and the rest are appended; returns the IDs
+    /// of the parts.
+    pub fn split_set(
+        &mut self,
+        clustering: &str,
+        set_id: usize,
+        partition: Vec<Vec<String>>,
+        annotator: Option<String>,
+        note: Option<String>,
+    ) -> Result<Vec<usize>, String> {
+        let sets = self.clustering_mut(clustering)?;
+        check_set_ids(&[set_id], sets.len())?;
+        if partition.len() < 2 || partition.iter().any(Vec::is_empty) {
+            return Err("split needs at least two non-empty parts".to_string());
+        }
+        let mut part_of: HashMap<&str, usize> = HashMap::new();
+        for (part, members) in partition.iter().enumerate() {
+            for member in members {
+                if part_of.insert(member, part).is_some() {
+                    return Err(format!("'{}' is in more than one part", member));
+                }
+            }
+        }
+        let members = &sets[set_id];
+        if let Some(missing) = members.iter().find(|m| !part_of.contains_key(m.as_str())) {
+            return Err(format!("'{}' of set {} is in no part", missing, set_id));
+        }
+        if part_of.len() != members.len() {
+            let stray = partition
+                .iter()
+                .flatten()
+                .find(|m| !members.contains(m))
+                .unwrap();
+            return Err(format!("'{}' is not in set {}", stray, set_id));
+        }
+
+        let before = vec![members.clone()];
+        let mut ids = vec![set_id];
+        let mut parts = partition.clone().into_iter();
+        sets[set_id] = parts.next().unwrap();
+        for part in parts {
+            ids.push(sets.len());
+            sets.push(part);
+        }
+
+        let mut removed_edges = Vec::new();
+        if let Some(graph) = self.graph.as_mut() {
+            graph.edges.retain(|edge| {
+                let crossing = match (part_of.get(edge.0.as_str()), part_of.get(edge.1.as_str())) {
+                    (Some(a), Some(b)) => a != b,
+                    _ => false,
+                };
+                if crossing {
+                    removed_edges.push(edge.clone());
+                }
+                !crossing
+            });
+        }
+        self.edits.push(CurationEdit {
+            timestamp_ms: now_ms(),
+            annotator,
+            clustering: Some(clustering.to_string()),
+            kind: EditKind::Split,
+            sets: vec![set_id],
+            before,
+            result: ids.iter().copied().zip(partition).collect(),
+            added_edges: Vec::new(),
+            removed_edges,
+            note,
+        });
+        Ok(ids)
+    }
+
+    /// Delete the graph edges between each (source, target) pair, in either
+    /// direction; every pair must have at least one. Returns the number of
+    /// edges deleted.
+    pub fn delete_edges(
+        &mut self,
+        pairs: &[(String, String)],
+        annotator: Option<String>,
+        note: Option<String>,
+    ) -> Result<usize, String> {
+        let graph = self
+            .graph
+            .as_mut()
+            .ok_or_else(|| "session has no graph".to_string())?;
+        let key = |a: &str, b: &str| {
+            if a <= b {
+                (a.to_string(), b.to_string())
+            } else {
+                (b.to_string(), a.to_string())
+            }
+        };
+        let wanted: HashSet<(String, String)> = pairs.iter().map(|(a, b)| key(a, b)).collect();
+        let present: HashSet<(String, String)> = graph
+            .edges
+            .iter()
+            .map(|edge| key(&edge.0, &edge.1))
+            .collect();
+        if let Some((a, b)) = pairs.iter().find(|(a, b)| !present.contains(&key(a, b))) {
+            return Err(format!("no edge between '{}' and '{}'", a, b));
+        }
+
+        let mut removed_edges = Vec::new();
+        graph.edges.retain(|edge| {
+            let deleted = wanted.contains(&key(&edge.0, &edge.1));
+            if deleted {
+                removed_edges.push(edge.clone());
+            }
+            !deleted
+        });
+        let deleted = removed_edges.len();
+        self.edits.push(CurationEdit {
+            timestamp_ms: now_ms(),
+            annotator,
+            clustering: None,
+            kind: EditKind::DeleteEdges,
+            sets: Vec::new(),
+            before: Vec::new(),
+            result: Vec::new(),
+            added_edges: Vec::new(),
+            removed_edges,
+            note,
+        });
+        Ok(deleted)
+    }
+
+    /// The audit log as pretty-printed JSON, oldest edit first
+    pub fn audit_log_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.edits)
+            .map_err(|e| format!("cannot serialize audit log: {}", e))
+    }
+
+    fn clustering_mut(&mut self, name: &str) -> Result<&mut Vec<Vec<String>>, String> {
+        self.clusterings
+            .get_mut(name)
+            .ok_or_else(|| format!("no clustering named '{}'", name))
+    }
+}
+
+/// Milliseconds since the Unix epoch (0 if the clock is before it)
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Reject set IDs past the end of a clustering of `len` sets
+fn check_set_ids(ids: &[usize], len: usize) -> Result<(), String> {
+    match ids.iter().find(|&&id| id >= len) {
+        Some(id) => Err(format!("set ID {} out of range ({} sets)", id, len)),
+        None => Ok(()),
+    }
+}
+
+impl GraphSnapshot {
+    /// Append an edge, adding unseen endpoints as nodes
+    fn add_edge(&mut self, edge: ProvenanceEdge) {
+        for id in [&edge.0, &edge.1] {
+            if !self.nodes.contains(id) {
+                self.nodes.push(id.clone());
+            }
+        }
+        self.edges.push(edge);
+    }
+}
+
+impl CognateGraph {
+    /// Capture nodes, attributes, edges and provenance
+    pub fn snapshot(&self) -> GraphSnapshot {
+        let node_attributes = self
+            .node_attrs
+            .iter()
+            .filter(|(_, attrs)| !attrs.is_empty())
+            .map(|(&idx, attrs)| {
+                let attrs = attrs.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                (self.graph[idx].clone(), attrs)
+            })
+            .collect();
+
+        GraphSnapshot {
+            nodes: self.graph.node_weights().cloned().collect(),
+            node_attributes,
+            edges: self.provenance_edge_list(),
+        }
+    }
+
+    /// Rebuild a graph from a snapshot (no threshold is applied)
+    pub fn from_snapshot(snapshot: GraphSnapshot) -> Self {
+        let mut graph = CognateGraph::new();
+        for id in snapshot.nodes {
+            graph.get_or_create_node(id);
+        }
+        for (source, target, weight, provenance) in snapshot.edges {
+            graph.add_edge_with_provenance(source, target, weight, provenance.as_deref());
+        }
+
+        let attributes: HashMap<String, HashMap<String, AttrValue>> = snapshot
+            .node_attributes
+            .into_iter()
+            .map(|(id, attrs)| (id, attrs.into_iter().collect()))
+            .collect();
+        graph.set_node_attributes(attributes);
+        graph
+    }
+}
+
+impl SparseSimilarityMatrix {
+    pub fn snapshot(&self) -> SparseSnapshot {
+        SparseSnapshot {
+            row_ids: self.entry_ids().to_vec(),
+            col_ids: self.col_ids().to_vec(),
+            triplets: self.triplets(),
+        }
+    }
+
+    pub fn from_snapshot(snapshot: SparseSnapshot) -> Result<Self, String> {
+        Self::from_triplets(snapshot.row_ids, snapshot.col_ids, &snapshot.triplets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    #[test]
+    fn test_session_round_trip() {
+        let mut graph = CognateGraph::from_edges(
+            vec![
+                SimilarityEdge::with_provenance("a".into(), "b".into(), 0.9, Some("lev".into())),
+                SimilarityEdge::new("b".into(), "c".into(), 0.8),
+            ],
+            0.5,
+        );
+        graph.get_or_create_node("lonely".into());
+        graph.set_node_attributes(HashMap::from([(
+            "a".to_string(),
+            HashMap::from([("language".to_string(), "lat".to_string())]),
+        )]));
+        graph.set_node_attributes(HashMap::from([(
+            "a".to_string(),
+            HashMap::from([("frequency".to_string(), 3.0)]),
+        )]));
+
+        let sparse = SparseSimilarityMatrix::from_edges(
+            vec![("a".into(), "b".into(), 0.9), ("b".into(), "c".into(), 0.8)],
+            0.5,
+        );
+
+        let mut session = Session::new();
+        session.graph = Some(graph.snapshot());
+        session.sparse = Some(sparse.snapshot());
+        session
+            .clusterings
+            .insert("threshold".into(), vec![vec!["a".into(), "b".into()]]);
+        session
+            .cost_matrices
+            .insert("learned".into(), vec![("p".into(), "b".into(), 0.3)]);
+        session.config.insert("threshold".into(), "0.5".into());
+
+        let path =
+            std::env::temp_dir().join(format!("langviz_session_{}.json", std::process::id()));
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let restored = CognateGraph::from_snapshot(loaded.graph.unwrap());
+        assert_eq!(restored.stats().num_nodes, 4);
+        assert_eq!(
+            restored.provenance_edge_list(),
+            graph.provenance_edge_list()
+        );
+        assert_eq!(
+            restored.node_attribute("a", "language"),
+            Some(&AttrValue::from("lat"))
+        );
+        assert_eq!(
+            restored.node_attribute("a", "frequency"),
+            Some(&AttrValue::Number(3.0))
+        );
+
+        let matrix = SparseSimilarityMatrix::from_snapshot(loaded.sparse.unwrap()).unwrap();
+        assert_eq!(matrix.nnz(), sparse.nnz());
+        assert_eq!(matrix.knn("a", 1), sparse.knn("a", 1));
+
+        assert_eq!(loaded.clusterings["threshold"].len(), 1);
+        assert_eq!(loaded.cost_matrices["learned"][0].2, 0.3);
+        assert_eq!(loaded.config["threshold"], "0.5");
+    }
+
+    #[test]
+    fn test_curation_edits() {
+        let graph = CognateGraph::from_edges(
+            vec![
+                SimilarityEdge::new("a".into(), "b".into(), 0.9),
+                SimilarityEdge::new("b".into(), "c".into(), 0.8),
+                SimilarityEdge::new("d".into(), "e".into(), 0.7),
+            ],
+            0.5,
+        );
+        let mut session = Session::new();
+        session.graph = Some(graph.snapshot());
+        let strings = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        session.clusterings.insert(
+            "auto".into(),
+            vec![
+                strings(&["a", "b", "c"]),
+                strings(&["d", "e"]),
+                strings(&["f"]),
+            ],
+        );
+
+        let parts = vec![strings(&["a", "b"]), strings(&["c"])];
+        let annotator = Some("mk".to_string());
+        let ids = session.split_set("auto", 0, parts, annotator, None);
+        assert_eq!(ids.unwrap(), [0, 3]);
+        assert_eq!(session.clusterings["auto"][3], ["c"]);
+        let edit = &session.edits[0];
+        assert_eq!(edit.kind, EditKind::Split);
+        assert_eq!(edit.annotator.as_deref(), Some("mk"));
+        assert!(edit.timestamp_ms > 0);
+        assert_eq!(edit.removed_edges.len(), 1);
+        assert_eq!(
+            (
+                edit.removed_edges[0].0.as_str(),
+                edit.removed_edges[0].1.as_str()
+            ),
+            ("b", "c")
+        );
+
+        let note = Some("same root".to_string());
+        assert_eq!(session.merge_sets("auto", &[3, 1], None, note).unwrap(), 1);
+        assert_eq!(
+            session.clusterings["auto"],
+            [
+                strings(&["a", "b"]),
+                strings(&["d", "e", "c"]),
+                strings(&["f"])
+            ]
+        );
+        let graph = session.graph.as_ref().unwrap();
+        let manual = graph.edges.last().unwrap();
+        assert_eq!((manual.0.as_str(), manual.1.as_str()), ("d", "c"));
+        assert_eq!(manual.3.as_deref(), Some(MANUAL_PROVENANCE));
+        assert_eq!(session.edits[1].note.as_deref(), Some("same root"));
+
+        // Partitions must cover the set exactly
+        assert!(session
+            .split_set("auto", 0, vec![strings(&["a"])], None, None)
+            .is_err());
+        let stray = vec![strings(&["a"]), strings(&["z"])];
+        assert!(session.split_set("auto", 0, stray, None, None).is_err());
+        assert!(session.merge_sets("auto", &[0, 7], None, None).is_err());
+        assert!(session.merge_sets("other", &[0, 1], None, None).is_err());
+        assert_eq!(session.edits.len(), 2);
+
+        // Either direction matches; an absent edge rejects the whole call
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        let missing = [pair("b", "a"), pair("a", "f")];
+        assert!(session.delete_edges(&missing, None, None).is_err());
+        assert_eq!(session.delete_edges(&[pair("b", "a")], None, None), Ok(1));
+        assert_eq!(session.graph.as_ref().unwrap().edges.len(), 2);
+
+        let log: Vec<CurationEdit> =
+            serde_json::from_str(&session.audit_log_json().unwrap()).unwrap();
+        assert_eq!(log, session.edits);
+        assert_eq!(log[2].kind, EditKind::DeleteEdges);
+        assert_eq!(log[2].clustering, None);
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let path = std::env::temp_dir().join(format!("langviz_future_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"version": 999}"#).unwrap();
+        let result = Session::load(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_upgrades_version_1() {
+        let path = std::env::temp_dir().join(format!("langviz_v1_{}.json", std::process::id()));
+        let v1 = r#"{"version": 1, "graph": {"nodes": ["a", "b"],
+            "node_attributes": {"a": {"language": "lat"}}, "edges": [["a", "b", 0.9, null]]}}"#;
+        std::fs::write(&path, v1).unwrap();
+        let session = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(session.version, SESSION_FORMAT_VERSION);
+        let graph = CognateGraph::from_snapshot(session.graph.unwrap());
+        assert_eq!(
+            graph.node_attribute("a", "language"),
+            Some(&AttrValue::from("lat"))
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Set operations between cognate graphs keyed by node ID.
+//!
+//! Lets graphs built from different metrics or datasets be merged or
+//! compared structurally. Edges are matched as unordered node-ID pairs.
+
+use ahash::{AHashMap, AHashSet};
+use petgraph::visit::EdgeRef;
+use std::str::FromStr;
+
+use crate::graph::CognateGraph;
+use crate::multigraph::EdgeAggregation;
+
+/// Structural set operation between two graphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl FromStr for SetOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "union" => Ok(SetOperation::Union),
+            "intersection" => Ok(SetOperation::Intersection),
+            "difference" => Ok(SetOperation::Difference),
+            other => Err(format!(
+                "unknown set operation '{}' (expected union, intersection or difference)",
+                other
+            )),
+        }
+    }
+}
+
+impl CognateGraph {
+    /// Edge weights keyed by sorted ID pair (parallel edges keep the max)
+    pub(crate) fn pair_weights(&self) -> AHashMap<(&str, &str), f64> {
+        let mut weights: AHashMap<(&str, &str), f64> = AHashMap::new();
+        for edge in self.graph.edge_references() {
+            let a = self.graph[edge.source()].as_str();
+            let b = self.graph[edge.target()].as_str();
+            let key = if a <= b { (a, b) } else { (b, a) };
+            let w = weights.entry(key).or_insert(f64::NEG_INFINITY);
+            *w = w.max(*edge.weight());
+        }
+        weights
+    }
+
+    /// Apply a set operation; shared edges are combined with `policy`
+    pub fn set_operation(
+        &self,
+        other: &CognateGraph,
+        operation: SetOperation,
+        policy: &EdgeAggregation,
+    ) -> CognateGraph {
+        match operation {
+            SetOperation::Union => self.union(other, policy),
+            SetOperation::Intersection => self.intersection(other, policy),
+            SetOperation::Difference => self.difference(other),
+        }
+    }
+
+    /// All nodes and edges of both graphs
+    pub fn union(&self, other: &CognateGraph, policy: &EdgeAggregation) -> CognateGraph {
+        let mine = self.pair_weights();
+        let theirs = other.pair_weights();
+        let mut result = CognateGraph::new();
+
+        for g in [self, other] {
+            for idx in g.graph.node_indices() {
+                let new_idx = result.get_or_create_node(g.graph[idx].clone());
+                if let Some(attrs) = g.node_attrs.get(&idx) {
+                    let merged = result.node_attrs.entry(new_idx).or_default();
+                    for (k, v) in attrs {
+                        merged.entry(k.clone()).or_insert_with(|| v.clone());
+                    }
+                }
+            }
+        }
+
+        // Walk edges in graph order so the output is deterministic
+        let mut added = AHashSet::new();
+        for key in self.pair_keys().chain(other.pair_keys()) {
+            if !added.insert(key) {
+                continue;
+            }
+            let weight = match (mine.get(&key), theirs.get(&key)) {
+                (Some(&w), Some(&w2)) => policy.combine(&[(None, w), (None, w2)]),
+                (Some(&w), None) | (None, Some(&w)) => w,
+                (None, None) => continue,
+            };
+            result.add_edge(key.0.to_string(), key.1.to_string(), weight);
+        }
+
+        result
+    }
+
+    /// Sorted ID pair of every edge, in edge order
+    fn pair_keys(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.graph.edge_references().map(move |edge| {
+            let a = self.graph[edge.source()].as_str();
+            let b = self.graph[edge.target()].as_str();
+            if a <= b {
+                (a, b)
+            } else {
+                (b, a)
+            }
+        })
+    }
+
+    /// Nodes present in both graphs and edges present in both
+    pub fn intersection(&self, other: &CognateGraph, policy: &EdgeAggregation) -> CognateGraph {
+        let keep: Vec<bool> = self
+            .graph
+            .node_indices()
+            .map(|idx| other.node_map.contains_key(&self.graph[idx]))
+            .collect();
+        let mut result = self.subgraph(&keep, |_| false);
+
+        let mine = self.pair_weights();
+        let theirs = other.pair_weights();
+        let mut added = AHashSet::new();
+        for key in self.pair_keys() {
+            if let (Some(&w), Some(&w2)) = (mine.get(&key), theirs.get(&key)) {
+                if added.insert(key) {
+                    let weight = policy.combine(&[(None, w), (None, w2)]);
+                    result.add_edge(key.0.to_string(), key.1.to_string(), weight);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// All nodes of this graph with the edges absent from `other`
+    pub fn difference(&self, other: &CognateGraph) -> CognateGraph {
+        let theirs = other.pair_weights();
+        let keep = vec![true; self.graph.node_count()];
+
+        self.subgraph(&keep, |edge| {
+            let a = self.graph[edge.source()].as_str();
+            let b = self.graph[edge.target()].as_str();
+            !theirs.contains_key(&if a <= b { (a, b) } else { (b, a) })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn graph(edges: &[(&str, &str, f64)]) -> CognateGraph {
+        CognateGraph::from_edges(
+            edges
+                .iter()
+                .map(|(a, b, w)| SimilarityEdge::new(a.to_string(), b.to_string(), *w))
+                .collect(),
+            0.0,
+        )
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = graph(&[("x", "y", 0.9), ("y", "z", 0.6)]);
+        let b = graph(&[("y", "x", 0.7), ("z", "w", 0.8)]);
+
+        let union = a.union(&b, &EdgeAggregation::Mean);
+        assert_eq!(union.stats().num_nodes, 4);
+        assert_eq!(union.stats().num_edges, 3);
+        let xy = union.pair_weights()[&("x", "y")];
+        assert!((xy - 0.8).abs() < 1e-12);
+
+        let inter = a.intersection(&b, &EdgeAggregation::Min);
+        assert_eq!(inter.stats().num_nodes, 3);
+        assert_eq!(
+            inter.edge_list(),
+            vec![("x".to_string(), "y".to_string(), 0.7)]
+        );
+
+        let diff = a.difference(&b);
+        assert_eq!(diff.stats().num_nodes, 3);
+        assert_eq!(diff.stats().num_edges, 1);
+    }
+}
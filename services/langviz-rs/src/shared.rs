@@ -0,0 +1,86 @@
+//! Thread-safe state behind the graph handles exposed to Python.
+//!
+//! Handles are queried from many Python threads at once (the web service
+//! releases the GIL inside every kernel call). Each query takes a snapshot:
+//! an `Arc` cloned under a read lock held only for the clone, so queries
+//! never block one another and never hold the lock while waiting for the
+//! GIL. Updates take the write lock and mutate in place, copying the state
+//! first only if a query still holds the previous snapshot; running queries
+//! keep seeing the state they started with.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Read-mostly value shared between threads, updated copy-on-write
+#[derive(Debug, Default)]
+pub struct Shared<T> {
+    value: RwLock<Arc<T>>,
+}
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value: RwLock::new(Arc::new(value)),
+        }
+    }
+
+    /// Current state; later updates don't affect the returned snapshot
+    pub fn get(&self) -> Arc<T> {
+        // A panicking update leaves either the old or the new state behind
+        Arc::clone(&self.value.read().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    /// Mutate in place under the write lock, waiting for other updates
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut guard = self.value.write().unwrap_or_else(PoisonError::into_inner);
+        f(Arc::make_mut(&mut guard))
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    /// Independent handle starting from the current state (copied on its
+    /// first update)
+    fn clone(&self) -> Self {
+        Self {
+            value: RwLock::new(self.get()),
+        }
+    }
+}
+
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshots_and_updates() {
+        let shared = Shared::new(vec![1, 2, 3]);
+        let snapshot = shared.get();
+        shared.update(|v| v.push(4));
+        assert_eq!(*snapshot, [1, 2, 3]);
+        assert_eq!(*shared.get(), [1, 2, 3, 4]);
+
+        let copy = shared.clone();
+        copy.update(|v| v.clear());
+        assert_eq!(shared.get().len(), 4);
+
+        // Concurrent readers and writers
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        shared.update(|v| v.push(0));
+                        assert!(shared.get().len() >= 4);
+                    }
+                });
+            }
+        });
+        assert_eq!(shared.get().len(), 404);
+    }
+}
@@ -0,0 +1,241 @@
+//! Bootstrap confidence for sound correspondences.
+//!
+//! The aligned columns (sites) of each language pair, as counted by
+//! `correspondence_matrices`, are resampled with replacement. For every
+//! observed correspondence a → b the replicates give a percentile interval
+//! for its frequency (the share of `a`'s sites that align with `b`), and
+//! the share of replicates in which `b` is `a`'s commonest partner supports
+//! the sound law a → b. A regular change keeps both high across replicates;
+//! a correspondence resting on a couple of sites swings widely. Each
+//! language pair draws from its own seeded generator, so results do not
+//! depend on the thread count.
+
+use ahash::AHashMap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::correspondence::aligned_pairs;
+
+/// One correspondence with its bootstrap interval
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrespondenceConfidence {
+    pub language_a: String,
+    pub language_b: String,
+    pub segment_a: String,
+    pub segment_b: String,
+    pub count: u64,
+    /// Share of `segment_a`'s sites aligned with `segment_b`
+    pub frequency: f64,
+    /// Percentile interval of `frequency` over the replicates
+    pub low: f64,
+    pub high: f64,
+    /// Share of replicates in which `segment_b` is `segment_a`'s commonest
+    /// partner (ties to the first segment in sort order)
+    pub rule_support: f64,
+}
+
+/// Bootstrap every correspondence seen at least `min_count` times over
+/// `replicates` resamplings of each language pair's sites, with intervals
+/// at `level` (e.g. 0.95). Sorted by language pair, then segments.
+pub fn bootstrap_correspondences(
+    cognate_sets: &[Vec<(String, String)>],
+    replicates: usize,
+    level: f64,
+    min_count: u64,
+    seed: u64,
+) -> Result<Vec<CorrespondenceConfidence>, String> {
+    if !(level > 0.0 && level < 1.0) {
+        return Err(format!("level must be in (0, 1), got {}", level));
+    }
+    if replicates == 0 {
+        return Err("at least one replicate is needed".to_string());
+    }
+
+    let mut sites: BTreeMap<(&str, &str), Vec<(String, String)>> = BTreeMap::new();
+    for (language_a, language_b, columns) in aligned_pairs(cognate_sets) {
+        sites
+            .entry((language_a, language_b))
+            .or_default()
+            .extend(columns);
+    }
+
+    let pairs: Vec<_> = sites.into_iter().collect();
+    let results: Vec<Vec<CorrespondenceConfidence>> = pairs
+        .par_iter()
+        .enumerate()
+        .map(|(index, ((language_a, language_b), columns))| {
+            let mut rng =
+                StdRng::seed_from_u64(seed ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            pair_confidence(columns, replicates, level, min_count, &mut rng)
+                .into_iter()
+                .map(|mut confidence| {
+                    confidence.language_a = language_a.to_string();
+                    confidence.language_b = language_b.to_string();
+                    confidence
+                })
+                .collect()
+        })
+        .collect();
+    Ok(results.into_iter().flatten().collect())
+}
+
+fn pair_confidence(
+    columns: &[(String, String)],
+    replicates: usize,
+    level: f64,
+    min_count: u64,
+    rng: &mut StdRng,
+) -> Vec<CorrespondenceConfidence> {
+    // Distinct cells in sort order, and each site's cell
+    let mut cells: Vec<&(String, String)> = columns.iter().collect();
+    cells.sort_unstable();
+    cells.dedup();
+    let cell_index: AHashMap<&(String, String), usize> = cells
+        .iter()
+        .enumerate()
+        .map(|(i, &cell)| (cell, i))
+        .collect();
+    let site_cells: Vec<usize> = columns.iter().map(|column| cell_index[column]).collect();
+    // Cells of one source segment are contiguous: row[c] is its first cell
+    let row: Vec<usize> = cells
+        .iter()
+        .map(|cell| cells.partition_point(|other| other.0 < cell.0))
+        .collect();
+
+    let observed = counts(site_cells.iter().copied(), cells.len());
+    let kept: Vec<usize> = (0..cells.len())
+        .filter(|&c| observed[c] >= min_count)
+        .collect();
+    let mut frequencies = vec![Vec::with_capacity(replicates); kept.len()];
+    let mut dominant = vec![0usize; kept.len()];
+    for _ in 0..replicates {
+        let sample = (0..site_cells.len()).map(|_| site_cells[rng.gen_range(0..site_cells.len())]);
+        let counts = counts(sample, cells.len());
+        let best = best_partners(&counts, &row);
+        for (k, &c) in kept.iter().enumerate() {
+            frequencies[k].push(frequency(&counts, &row, c));
+            if best[row[c]] == c {
+                dominant[k] += 1;
+            }
+        }
+    }
+
+    kept.iter()
+        .zip(frequencies)
+        .zip(dominant)
+        .map(|((&c, mut replicate), dominant)| {
+            replicate.sort_by(f64::total_cmp);
+            let tail = (1.0 - level) / 2.0;
+            CorrespondenceConfidence {
+                language_a: String::new(),
+                language_b: String::new(),
+                segment_a: cells[c].0.clone(),
+                segment_b: cells[c].1.clone(),
+                count: observed[c],
+                frequency: frequency(&observed, &row, c),
+                low: percentile(&replicate, tail),
+                high: percentile(&replicate, 1.0 - tail),
+                rule_support: dominant as f64 / replicates as f64,
+            }
+        })
+        .collect()
+}
+
+fn counts(sites: impl Iterator<Item = usize>, cells: usize) -> Vec<u64> {
+    let mut counts = vec![0; cells];
+    for cell in sites {
+        counts[cell] += 1;
+    }
+    counts
+}
+
+/// Share of cell `c` in its source segment's row, 0 when the row is empty
+fn frequency(counts: &[u64], row: &[usize], c: usize) -> f64 {
+    let total: u64 = (row[c]..counts.len())
+        .take_while(|&other| row[other] == row[c])
+        .map(|other| counts[other])
+        .sum();
+    if total == 0 {
+        0.0
+    } else {
+        counts[c] as f64 / total as f64
+    }
+}
+
+/// Commonest cell per row start, ties to the first; rows with no sites
+/// point at no cell
+fn best_partners(counts: &[u64], row: &[usize]) -> Vec<usize> {
+    let mut best = vec![usize::MAX; counts.len()];
+    for c in 0..counts.len() {
+        let start = row[c];
+        if counts[c] > 0 && (best[start] == usize::MAX || counts[c] > counts[best[start]]) {
+            best[start] = c;
+        }
+    }
+    best
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sets() -> Vec<Vec<(String, String)>> {
+        // f ~ p everywhere, t ~ t but once d ~ t
+        [
+            [("lat", "pater"), ("eng", "fater")],
+            [("lat", "pes"), ("eng", "fes")],
+            [("lat", "piskis"), ("eng", "fiskis")],
+            [("lat", "tres"), ("eng", "tres")],
+            [("lat", "tu"), ("eng", "tu")],
+            [("lat", "ten"), ("eng", "den")],
+        ]
+        .iter()
+        .map(|set| {
+            set.iter()
+                .map(|(l, f)| (l.to_string(), f.to_string()))
+                .collect()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_bootstrap_correspondences() {
+        let confidences = bootstrap_correspondences(&sets(), 200, 0.9, 1, 3).unwrap();
+        assert!(confidences
+            .iter()
+            .all(|c| c.language_a == "eng" && c.language_b == "lat"));
+        let find = |a: &str, b: &str| {
+            confidences
+                .iter()
+                .find(|c| c.segment_a == a && c.segment_b == b)
+                .unwrap()
+        };
+
+        let f_p = find("f", "p");
+        assert_eq!((f_p.count, f_p.frequency), (3, 1.0));
+        assert_eq!((f_p.low, f_p.high), (1.0, 1.0));
+        // Only replicates that miss every f site fail to support f → p
+        assert!(f_p.rule_support > 0.95);
+
+        // d ~ t rests on a single site, which a third of the replicates
+        // leave out
+        let d_t = find("d", "t");
+        assert_eq!((d_t.count, d_t.frequency, d_t.low), (1, 1.0, 0.0));
+        assert!(d_t.rule_support > 0.4 && d_t.rule_support < 0.9);
+        assert!(confidences.iter().all(|c| c.low <= c.high));
+
+        let again = bootstrap_correspondences(&sets(), 200, 0.9, 1, 3).unwrap();
+        assert_eq!(again, confidences);
+        let common = bootstrap_correspondences(&sets(), 10, 0.9, 3, 3).unwrap();
+        assert!(common.iter().all(|c| c.count >= 3));
+        assert!(bootstrap_correspondences(&sets(), 10, 1.0, 1, 0).is_err());
+    }
+}
@@ -0,0 +1,534 @@
+//! Sound change rules: apply hypothesized sound laws to IPA forms.
+//!
+//! Rules use the usual `A > B / L_R` notation over `segment_ipa` segments:
+//!
+//! - `p > f / #_`: word-initial p becomes f
+//! - `e > i / _r`: e before r becomes i
+//! - `{p,t,k} > {f,θ,x}`: sets map member by member
+//! - `h > ∅ / V_V`: deletion (`∅` or `0`) between vowels
+//!
+//! In environments `#` is the word boundary, `C` / `V` match any consonant /
+//! vowel (vowels are the `syl` segments of the feature table) and `{a,b}`
+//! any listed segment; the environment may be omitted. A rule applies
+//! simultaneously to every match, scanning left to right with contexts read
+//! from its input, and rules apply in order, each to the previous output.
+
+use rayon::prelude::*;
+use std::fmt::Write;
+
+use crate::features::{feature_index, segment_features};
+use crate::phonetic::{phonetic_distance, segment_ipa};
+
+/// One position of a rule's target or environment
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Segment(String),
+    Set(Vec<String>),
+    Consonant,
+    Vowel,
+    Boundary,
+}
+
+fn is_vowel(segment: &str) -> bool {
+    segment_features(segment).is_some_and(|features| features[feature_index("syl")] > 0)
+}
+
+impl Element {
+    fn matches(&self, segment: &str) -> bool {
+        match self {
+            Element::Segment(s) => s == segment,
+            Element::Set(members) => members.iter().any(|m| m == segment),
+            Element::Consonant => !is_vowel(segment),
+            Element::Vowel => is_vowel(segment),
+            Element::Boundary => false,
+        }
+    }
+}
+
+/// Split rule text into elements; `_` marks the focus position
+fn parse_elements(text: &str) -> Result<Vec<Option<Element>>, String> {
+    let mut elements = Vec::new();
+    let mut run = String::new();
+    let flush = |run: &mut String, elements: &mut Vec<Option<Element>>| {
+        let segments = segment_ipa(&std::mem::take(run));
+        elements.extend(segments.into_iter().map(|s| Some(Element::Segment(s))));
+    };
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        let element = match c {
+            '{' => {
+                let body: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let members: Vec<String> = body
+                    .split(',')
+                    .map(|m| m.trim().to_string())
+                    .filter(|m| !m.is_empty())
+                    .collect();
+                if members.is_empty() {
+                    return Err(format!("empty set in '{}'", text));
+                }
+                Some(Element::Set(members))
+            }
+            '#' => Some(Element::Boundary),
+            'C' => Some(Element::Consonant),
+            'V' => Some(Element::Vowel),
+            '_' => None,
+            c if c.is_whitespace() => {
+                flush(&mut run, &mut elements);
+                continue;
+            }
+            c => {
+                run.push(c);
+                continue;
+            }
+        };
+        flush(&mut run, &mut elements);
+        elements.push(element);
+    }
+    flush(&mut run, &mut elements);
+    Ok(elements)
+}
+
+/// A parsed `A > B / L_R` rewrite rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundChangeRule {
+    /// Rule text as written
+    pub text: String,
+    target: Vec<Element>,
+    /// Replacement segments; one list per set member for set-to-set rules
+    replacement: Vec<Vec<String>>,
+    left: Vec<Element>,
+    right: Vec<Element>,
+}
+
+impl SoundChangeRule {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("invalid rule '{}': {}", text, reason);
+        let (change, environment) = match text.split_once('/') {
+            Some((change, environment)) => (change, Some(environment)),
+            None => (text, None),
+        };
+        let (target, replacement) = change
+            .split_once('>')
+            .ok_or_else(|| invalid("missing '>'"))?;
+
+        let target: Vec<Element> = parse_elements(target)
+            .map_err(|e| invalid(&e))?
+            .into_iter()
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid("'_' outside the environment"))?;
+        if target.is_empty() {
+            return Err(invalid("empty target (insertions are not supported)"));
+        }
+        if target.contains(&Element::Boundary) {
+            return Err(invalid("'#' in the target"));
+        }
+
+        let replacement = replacement.trim();
+        let replacement = if matches!(replacement, "∅" | "0" | "Ø" | "") {
+            vec![Vec::new()]
+        } else {
+            match parse_elements(replacement)
+                .map_err(|e| invalid(&e))?
+                .as_slice()
+            {
+                [Some(Element::Set(members))] => {
+                    let sources = match target.as_slice() {
+                        [Element::Set(sources)] => sources.len(),
+                        _ => return Err(invalid("a replacement set needs a target set")),
+                    };
+                    if members.len() != sources {
+                        return Err(invalid("target and replacement sets differ in size"));
+                    }
+                    members.iter().map(|m| vec![m.clone()]).collect()
+                }
+                elements => vec![elements
+                    .iter()
+                    .map(|element| match element {
+                        Some(Element::Segment(s)) => Ok(s.clone()),
+                        _ => Err(invalid("the replacement must be segments")),
+                    })
+                    .collect::<Result<_, _>>()?],
+            }
+        };
+
+        let (left, right) = match environment {
+            None => (Vec::new(), Vec::new()),
+            Some(environment) => {
+                let elements = parse_elements(environment).map_err(|e| invalid(&e))?;
+                let focus = elements.iter().filter(|e| e.is_none()).count();
+                if focus != 1 {
+                    return Err(invalid("the environment needs exactly one '_'"));
+                }
+                let split = elements.iter().position(Option::is_none).unwrap();
+                let left: Vec<Element> = elements[..split].iter().flatten().cloned().collect();
+                let right: Vec<Element> = elements[split + 1..].iter().flatten().cloned().collect();
+                let inner_boundary = |side: &[Element], edge: usize| {
+                    side.iter()
+                        .enumerate()
+                        .any(|(i, e)| *e == Element::Boundary && i != edge)
+                };
+                if inner_boundary(&left, 0) || inner_boundary(&right, right.len().wrapping_sub(1)) {
+                    return Err(invalid("'#' must be at the edge of the environment"));
+                }
+                (left, right)
+            }
+        };
+
+        Ok(Self {
+            text: text.to_string(),
+            target,
+            replacement,
+            left,
+            right,
+        })
+    }
+
+    /// Whether the environment holds around `segments[start..end]`
+    fn context_matches(&self, segments: &[String], start: usize, end: usize) -> bool {
+        let mut pos = start;
+        for element in self.left.iter().rev() {
+            match element {
+                Element::Boundary if pos == 0 => {}
+                _ if pos > 0 && element.matches(&segments[pos - 1]) => pos -= 1,
+                _ => return false,
+            }
+        }
+        let mut pos = end;
+        for element in &self.right {
+            match element {
+                Element::Boundary if pos == segments.len() => {}
+                _ if pos < segments.len() && element.matches(&segments[pos]) => pos += 1,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Replacement for the target matched at `start`, if any
+    fn replacement_at(&self, segments: &[String], start: usize) -> Option<&[String]> {
+        let end = start + self.target.len();
+        if end > segments.len()
+            || !self
+                .target
+                .iter()
+                .zip(&segments[start..end])
+                .all(|(e, s)| e.matches(s))
+            || !self.context_matches(segments, start, end)
+        {
+            return None;
+        }
+        let member = match &self.target[..] {
+            [Element::Set(members)] if self.replacement.len() > 1 => {
+                members.iter().position(|m| *m == segments[start])?
+            }
+            _ => 0,
+        };
+        Some(&self.replacement[member])
+    }
+
+    /// Apply the rule to a segmented form
+    pub fn apply(&self, segments: &[String]) -> Vec<String> {
+        let mut output = Vec::with_capacity(segments.len());
+        let mut i = 0;
+        while i < segments.len() {
+            match self.replacement_at(segments, i) {
+                Some(replacement) => {
+                    output.extend(replacement.iter().cloned());
+                    i += self.target.len();
+                }
+                None => {
+                    output.push(segments[i].clone());
+                    i += 1;
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Parse an ordered rule list, naming the first invalid rule
+pub fn parse_rules<S: AsRef<str>>(rules: &[S]) -> Result<Vec<SoundChangeRule>, String> {
+    rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            SoundChangeRule::parse(rule.as_ref()).map_err(|e| format!("rule {}: {}", i, e))
+        })
+        .collect()
+}
+
+/// Segments of `word` after every rule, and which rules changed it
+fn derive(word: &str, rules: &[SoundChangeRule]) -> (Vec<String>, Vec<bool>) {
+    let mut segments = segment_ipa(word);
+    let changed = rules
+        .iter()
+        .map(|rule| {
+            let next = rule.apply(&segments);
+            let changed = next != segments;
+            segments = next;
+            changed
+        })
+        .collect();
+    (segments, changed)
+}
+
+/// One step of a derivation: the rule (or correspondence) applied and the
+/// form before and after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivationStep {
+    pub rule: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Step-by-step derivation of one form, for display
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derivation {
+    pub input: String,
+    pub output: String,
+    /// Steps that changed something, in order
+    pub steps: Vec<DerivationStep>,
+}
+
+/// Derivation of `word` through `rules`, listing the rules that changed it
+pub fn derivation(word: &str, rules: &[SoundChangeRule]) -> Derivation {
+    let mut segments = segment_ipa(word);
+    let mut steps = Vec::new();
+    for rule in rules {
+        let next = rule.apply(&segments);
+        if next != segments {
+            steps.push(DerivationStep {
+                rule: rule.text.clone(),
+                before: segments.concat(),
+                after: next.concat(),
+            });
+        }
+        segments = next;
+    }
+    Derivation {
+        input: word.to_string(),
+        output: segments.concat(),
+        steps,
+    }
+}
+
+/// `derivation` of every word (in parallel)
+pub fn derive_sound_changes(words: &[String], rules: &[SoundChangeRule]) -> Vec<Derivation> {
+    words
+        .par_iter()
+        .map(|word| derivation(word, rules))
+        .collect()
+}
+
+/// Apply `rules` in order to every word (in parallel)
+pub fn apply_sound_changes(words: &[String], rules: &[SoundChangeRule]) -> Vec<String> {
+    words
+        .par_iter()
+        .map(|word| derive(word, rules).0.concat())
+        .collect()
+}
+
+/// How well a rule list derives attested forms from proto-forms
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleEvaluation {
+    /// Predicted reflex per proto-form
+    pub predicted: Vec<String>,
+    /// Whether each prediction equals the attested form (segment by segment)
+    pub matches: Vec<bool>,
+    pub exact_matches: usize,
+    /// Share of exact matches (0 for no words)
+    pub accuracy: f64,
+    /// Mean `phonetic_distance` similarity of prediction and attested form
+    pub mean_similarity: f64,
+    /// Words each rule changed
+    pub rule_applications: Vec<usize>,
+    /// Mispredicted words, most deviant first
+    pub exceptions: Vec<RuleException>,
+}
+
+/// A proto-form whose derived reflex differs from the attested one
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleException {
+    /// Position of the word in the input
+    pub index: usize,
+    pub proto: String,
+    pub expected: String,
+    pub attested: String,
+    /// One minus the `phonetic_distance` similarity of expected and attested
+    pub deviation: f64,
+    /// Rules that changed the word on its way to `expected`, in order
+    pub applied: Vec<String>,
+}
+
+impl RuleEvaluation {
+    /// Exceptions as a TSV review table, one row per mispredicted word
+    pub fn exceptions_table(&self) -> String {
+        let mut out = String::from("index\tproto\texpected\tattested\tdeviation\tapplied\n");
+        for exception in &self.exceptions {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{:.4}\t{}",
+                exception.index,
+                exception.proto,
+                exception.expected,
+                exception.attested,
+                exception.deviation,
+                exception.applied.join("; ")
+            );
+        }
+        out
+    }
+}
+
+/// Derive every proto-form and compare it with its attested reflex
+pub fn evaluate_rules(
+    proto_forms: &[String],
+    attested: &[String],
+    rules: &[SoundChangeRule],
+) -> RuleEvaluation {
+    let results: Vec<(String, bool, f64, Vec<bool>)> = proto_forms
+        .par_iter()
+        .zip(attested)
+        .map(|(proto, attested)| {
+            let (segments, changed) = derive(proto, rules);
+            let matched = segments == segment_ipa(attested);
+            let predicted = segments.concat();
+            let similarity = phonetic_distance(&predicted, attested);
+            (predicted, matched, similarity, changed)
+        })
+        .collect();
+
+    let mut rule_applications = vec![0; rules.len()];
+    for (_, _, _, changed) in &results {
+        for (count, &changed) in rule_applications.iter_mut().zip(changed) {
+            *count += changed as usize;
+        }
+    }
+    let n = results.len().max(1) as f64;
+    let exact_matches = results.iter().filter(|r| r.1).count();
+    let mean_similarity = results.iter().map(|r| r.2).sum::<f64>() / n;
+    let mut exceptions: Vec<RuleException> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| !r.1)
+        .map(
+            |(index, (expected, _, similarity, changed))| RuleException {
+                index,
+                proto: proto_forms[index].clone(),
+                expected: expected.clone(),
+                attested: attested[index].clone(),
+                deviation: 1.0 - similarity,
+                applied: rules
+                    .iter()
+                    .zip(changed)
+                    .filter(|(_, &changed)| changed)
+                    .map(|(rule, _)| rule.text.clone())
+                    .collect(),
+            },
+        )
+        .collect();
+    exceptions.sort_by(|a, b| {
+        b.deviation
+            .total_cmp(&a.deviation)
+            .then(a.index.cmp(&b.index))
+    });
+    let (predicted, matches) = results.into_iter().map(|r| (r.0, r.1)).unzip();
+    RuleEvaluation {
+        predicted,
+        matches,
+        exact_matches,
+        accuracy: exact_matches as f64 / n,
+        mean_similarity,
+        rule_applications,
+        exceptions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(rules: &[&str], word: &str) -> String {
+        let rules = parse_rules(rules).unwrap();
+        apply_sound_changes(&[word.to_string()], &rules).remove(0)
+    }
+
+    #[test]
+    fn test_rule_application() {
+        assert_eq!(run(&["p > f / #_"], "papa"), "fapa");
+        assert_eq!(run(&["e > i / _r"], "pater"), "patir");
+        assert_eq!(run(&["{p,t,k} > {f,θ,x}"], "pektu"), "fexθu");
+        assert_eq!(run(&["h > ∅ / V_V"], "ahah"), "aah");
+        assert_eq!(run(&["a > e / _#"], "mata"), "mate");
+        // Contexts are read from the rule's input, so every o after an o
+        // changes, and later rules see earlier rules' output
+        assert_eq!(run(&["o > u / o_"], "ooo"), "ouu");
+        assert_eq!(run(&["t > d / V_V", "a > o / d_"], "atata"), "adodo");
+        // Grimm's law then Verner's-style voicing, in order
+        assert_eq!(run(&["p > f", "f > v / V_V"], "apa"), "ava");
+        assert_eq!(run(&["t͡ʃ > ʃ"], "t͡ʃat͡ʃa"), "ʃaʃa");
+
+        for bad in [
+            "p f",
+            "p > f / _ _",
+            "{p,t} > {f}",
+            "> f",
+            "p > C",
+            "p > f / a#_",
+        ] {
+            assert!(SoundChangeRule::parse(bad).is_err(), "{}", bad);
+        }
+        assert!(parse_rules(&["p > f", "x"])
+            .unwrap_err()
+            .starts_with("rule 1:"));
+    }
+
+    #[test]
+    fn test_evaluate_rules() {
+        let proto = vec!["pater".to_string(), "ped".to_string(), "tres".to_string()];
+        let attested = vec!["fater".to_string(), "fet".to_string(), "θres".to_string()];
+        let rules = parse_rules(&["p > f / #_", "d > t / _#"]).unwrap();
+        let evaluation = evaluate_rules(&proto, &attested, &rules);
+        assert_eq!(evaluation.predicted, ["fater", "fet", "tres"]);
+        assert_eq!(evaluation.matches, [true, true, false]);
+        assert_eq!(evaluation.exact_matches, 2);
+        assert!((evaluation.accuracy - 2.0 / 3.0).abs() < 1e-12);
+        assert!((evaluation.mean_similarity - (1.0 + 1.0 + 0.75) / 3.0).abs() < 1e-12);
+        assert_eq!(evaluation.rule_applications, [2, 1]);
+
+        let [exception] = &evaluation.exceptions[..] else {
+            panic!("expected one exception");
+        };
+        assert_eq!((exception.index, exception.expected.as_str()), (2, "tres"));
+        assert!((exception.deviation - 0.25).abs() < 1e-12);
+        assert!(exception.applied.is_empty());
+        assert_eq!(
+            evaluation.exceptions_table().lines().nth(1),
+            Some("2\ttres\ttres\tθres\t0.2500\t")
+        );
+    }
+
+    #[test]
+    fn test_derivation() {
+        let rules = parse_rules(&["p > f / #_", "e > i / _r", "k > x"]).unwrap();
+        let derived = derivation("pater", &rules);
+        assert_eq!(derived.output, "fatir");
+        // The k rule changed nothing, so it's not a step
+        let steps: Vec<(&str, &str, &str)> = derived
+            .steps
+            .iter()
+            .map(|s| (s.rule.as_str(), s.before.as_str(), s.after.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("p > f / #_", "pater", "fater"),
+                ("e > i / _r", "fater", "fatir")
+            ]
+        );
+        let words = ["pater".to_string(), "mus".to_string()];
+        let all = derive_sound_changes(&words, &rules);
+        assert!(all[1].steps.is_empty() && all[1].output == "mus");
+    }
+}
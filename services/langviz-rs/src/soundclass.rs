@@ -0,0 +1,151 @@
+//! Sound-class substitution costs for weighted edit distance.
+//!
+//! A middle ground between unit costs and full feature vectors: segments
+//! are grouped into sound classes, and substituting within a class (t ~ d,
+//! p ~ f) costs less than across classes (t ~ k). Classes default to the
+//! Dolgopolsky-style ones of `phonetic_key` (P labial, T dental, S
+//! sibilant, K dorsal, H laryngeal, M / N nasals, R liquids, W / J glides,
+//! V vowels), derived from the feature table; any segment can be assigned
+//! to a class of the caller's own, and costs can be set per class and per
+//! class pair. Plugged in as `SubstitutionCost::SoundClasses`, the costs
+//! work with every `CostModel` distance and with NW / SW alignment.
+
+use std::collections::HashMap;
+
+use crate::phonetic::sound_class;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundClassCosts {
+    /// Class of each listed segment; others take their built-in class
+    pub classes: HashMap<String, String>,
+    /// Cost of substituting two different segments of one class
+    pub within: f64,
+    /// Cost of substituting segments of different classes
+    pub across: f64,
+    /// `within` overrides per class
+    pub class_within: HashMap<String, f64>,
+    /// `across` overrides per class pair, symmetric
+    pub class_pairs: HashMap<(String, String), f64>,
+}
+
+impl Default for SoundClassCosts {
+    fn default() -> Self {
+        Self {
+            classes: HashMap::new(),
+            within: 0.5,
+            across: 1.0,
+            class_within: HashMap::new(),
+            class_pairs: HashMap::new(),
+        }
+    }
+}
+
+impl SoundClassCosts {
+    /// Costs with every listed cost checked to lie in [0, 1]; `class_pairs`
+    /// are stored both ways round
+    pub fn new(
+        classes: HashMap<String, String>,
+        within: f64,
+        across: f64,
+        class_within: HashMap<String, f64>,
+        class_pairs: &[(String, String, f64)],
+    ) -> Result<Self, String> {
+        let check = |what: &str, cost: f64| {
+            if (0.0..=1.0).contains(&cost) {
+                Ok(())
+            } else {
+                Err(format!("{} cost must be in [0, 1], got {}", what, cost))
+            }
+        };
+        check("within-class", within)?;
+        check("across-class", across)?;
+        for (class, &cost) in &class_within {
+            check(&format!("class {}", class), cost)?;
+        }
+        let mut pairs = HashMap::new();
+        for (a, b, cost) in class_pairs {
+            check(&format!("class pair ({}, {})", a, b), *cost)?;
+            pairs.insert((a.clone(), b.clone()), *cost);
+            pairs.insert((b.clone(), a.clone()), *cost);
+        }
+        Ok(Self {
+            classes,
+            within,
+            across,
+            class_within,
+            class_pairs: pairs,
+        })
+    }
+
+    /// Class of `segment`: its assigned class, else the built-in one
+    pub fn class_of(&self, segment: &str) -> String {
+        match self.classes.get(segment) {
+            Some(class) => class.clone(),
+            None => sound_class(segment).to_string(),
+        }
+    }
+
+    /// Substitution cost of `a` for `b`: 0 when identical
+    pub fn cost(&self, a: &str, b: &str) -> f64 {
+        if a == b {
+            return 0.0;
+        }
+        let (class_a, class_b) = (self.class_of(a), self.class_of(b));
+        if class_a == class_b {
+            self.class_within
+                .get(&class_a)
+                .copied()
+                .unwrap_or(self.within)
+        } else {
+            self.class_pairs
+                .get(&(class_a, class_b))
+                .copied()
+                .unwrap_or(self.across)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phonetic::{phonetic_distance_with_costs, CostModel, SubstitutionCost};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sound_class_costs() {
+        let costs = SoundClassCosts::default();
+        assert_eq!(costs.cost("t", "t"), 0.0);
+        assert_eq!(costs.cost("t", "d"), 0.5);
+        assert_eq!(costs.cost("p", "f"), 0.5);
+        assert_eq!(costs.cost("t", "k"), 1.0);
+
+        // A custom class for the laryngeals, cheap dental changes and a
+        // cheaper T ~ S boundary
+        let costs = SoundClassCosts::new(
+            HashMap::from([("h".to_string(), "LAR".to_string())]),
+            0.5,
+            1.0,
+            HashMap::from([("T".to_string(), 0.2)]),
+            &[("S".to_string(), "T".to_string(), 0.6)],
+        )
+        .unwrap();
+        assert_eq!(costs.class_of("h"), "LAR");
+        assert_eq!(costs.cost("t", "d"), 0.2);
+        assert_eq!(costs.cost("t", "s"), 0.6);
+        assert_eq!(costs.cost("s", "t"), 0.6);
+        assert!(SoundClassCosts::new(HashMap::new(), 1.5, 1.0, HashMap::new(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_sound_class_distance() {
+        let model = CostModel {
+            substitution: SubstitutionCost::SoundClasses(Arc::new(SoundClassCosts::default())),
+            ..CostModel::default()
+        };
+        // One within-class change over five segments, then one across
+        let within = phonetic_distance_with_costs("pater", "fater", &model);
+        let across = phonetic_distance_with_costs("pater", "kater", &model);
+        assert!((within - 0.9).abs() < 1e-12);
+        assert!((across - 0.8).abs() < 1e-12);
+    }
+}
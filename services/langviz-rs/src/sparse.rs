@@ -1,12 +1,27 @@
 //! Sparse matrix operations for efficient similarity computation.
 
-use ndarray::{Array1, Array2};
+use ndarray::{Array1, Array2, ArrayView2};
 use ordered_float::OrderedFloat;
 use rayon::prelude::*;
 use sprs::{CsMat, TriMat};
+use std::cmp::Reverse;
 use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::format::check_version;
+use crate::mapped::write_mapped;
+use crate::phonetic::{block_by_key, segment_lengths, KeyScheme, SimilarityMetric};
+
+/// Leading bytes of a saved matrix file
+const SPARSE_MAGIC: &[u8; 8] = b"LVSPARSE";
+
+/// Current binary format version of saved matrices
+pub const SPARSE_FORMAT_VERSION: u32 = 1;
 
 /// Sparse similarity matrix optimized for memory efficiency
+#[derive(Clone)]
 pub struct SparseSimilarityMatrix {
     /// Sparse matrix in CSR format
     matrix: CsMat<f64>,
@@ -17,49 +32,39 @@ pub struct SparseSimilarityMatrix {
 }
 
 impl SparseSimilarityMatrix {
-    /// Build sparse matrix from similarity edges with threshold
+    /// Build sparse matrix from similarity edges with threshold. IDs are
+    /// collected, indexed and turned into triplets in parallel, and the CSR
+    /// arrays assembled by a parallel sort rather than entry by entry.
     pub fn from_edges(
         edges: Vec<(String, String, f64)>,
         threshold: f64,
     ) -> Self {
-        // Create ID mappings
-        let mut id_set: std::collections::HashSet<String> = std::collections::HashSet::new();
-        for (a, b, _) in &edges {
-            id_set.insert(a.clone());
-            id_set.insert(b.clone());
-        }
-
-        let mut ids: Vec<String> = id_set.into_iter().collect();
-        ids.sort();
-
-        let id_to_idx: std::collections::HashMap<&str, usize> = ids
-            .iter()
-            .enumerate()
-            .map(|(idx, id)| (id.as_str(), idx))
+        // Sorted, deduplicated IDs; an ID's index is its sorted position
+        let mut ids: Vec<&str> = edges
+            .par_iter()
+            .flat_map_iter(|(a, b, _)| [a.as_str(), b.as_str()])
             .collect();
-
+        ids.par_sort_unstable();
+        ids.dedup();
+        let index = |id: &str| ids.binary_search(&id).unwrap();
         let n = ids.len();
-        let mut triplets = TriMat::new((n, n));
-
-        // Add edges above threshold
-        for (a, b, weight) in edges {
-            if weight >= threshold {
-                let i = id_to_idx[a.as_str()];
-                let j = id_to_idx[b.as_str()];
-                triplets.add_triplet(i, j, weight);
-                if i != j {
-                    triplets.add_triplet(j, i, weight); // Symmetric
-                }
-            }
-        }
-
-        // Add diagonal (self-similarity = 1.0)
-        for i in 0..n {
-            triplets.add_triplet(i, i, 1.0);
-        }
-
-        let matrix = triplets.to_csr();
 
+        // Diagonal (self-similarity = 1.0), then edges above threshold
+        let mut triplets: Vec<(usize, usize, f64)> =
+            (0..n).into_par_iter().map(|i| (i, i, 1.0)).collect();
+        triplets.par_extend(
+            edges
+                .par_iter()
+                .filter(|(_, _, weight)| *weight >= threshold)
+                .flat_map_iter(|(a, b, weight)| {
+                    let (i, j) = (index(a), index(b));
+                    let mirror = (i != j).then_some((j, i, *weight)); // Symmetric
+                    std::iter::once((i, j, *weight)).chain(mirror)
+                }),
+        );
+
+        let matrix = assemble_csr((n, n), triplets);
+        let ids: Vec<String> = ids.into_iter().map(str::to_string).collect();
         Self {
             matrix,
             row_ids: ids.clone(),
@@ -77,27 +82,15 @@ impl SparseSimilarityMatrix {
         // Get row from sparse matrix
         let row = self.matrix.outer_view(idx).unwrap();
 
-        // Use max-heap to find top-k
-        let mut heap: BinaryHeap<(OrderedFloat<f64>, usize)> = BinaryHeap::new();
-
-        for (col_idx, &value) in row.iter() {
-            if col_idx != idx {
-                // Skip self
-                heap.push((OrderedFloat(value), col_idx));
-            }
-        }
-
-        // Extract top k
-        let mut results = Vec::new();
-        for _ in 0..k {
-            if let Some((score, col_idx)) = heap.pop() {
-                results.push((self.col_ids[col_idx].clone(), score.0));
-            } else {
-                break;
-            }
-        }
+        top_k(row.iter().map(|(col_idx, &value)| (col_idx, value)), idx, k)
+            .into_iter()
+            .map(|(col_idx, value)| (self.col_ids[col_idx].clone(), value))
+            .collect()
+    }
 
-        results
+    /// Stored value at (row, col), None for an absent entry
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        self.matrix.get(row, col).copied()
     }
 
     /// Get all neighbors above threshold
@@ -152,6 +145,52 @@ impl SparseSimilarityMatrix {
         result
     }
 
+    /// Symmetric normalized Laplacian `I - D^-1/2 W D^-1/2` of the
+    /// similarity graph, with `W` symmetrized by averaging and
+    /// self-similarities left out. Entries without neighbors get an all-zero
+    /// row, making each its own zero-eigenvalue component.
+    pub fn normalized_laplacian(&self) -> Result<Self, String> {
+        let (n, cols) = self.shape();
+        if n != cols || self.row_ids != self.col_ids {
+            return Err(
+                "Laplacian needs a square matrix with matching row and column IDs".to_string(),
+            );
+        }
+
+        let off_diagonal: Vec<(usize, usize, f64)> = self
+            .triplets()
+            .into_iter()
+            .filter(|&(i, j, _)| i != j)
+            .collect();
+        let mut degrees = vec![0.0; n];
+        for &(i, j, weight) in &off_diagonal {
+            degrees[i] += weight / 2.0;
+            degrees[j] += weight / 2.0;
+        }
+        let scale: Vec<f64> = degrees
+            .iter()
+            .map(|&d| if d > 0.0 { d.sqrt().recip() } else { 0.0 })
+            .collect();
+
+        let mut triplets = TriMat::new((n, n));
+        for (i, &degree) in degrees.iter().enumerate() {
+            if degree > 0.0 {
+                triplets.add_triplet(i, i, 1.0);
+            }
+        }
+        for (i, j, weight) in off_diagonal {
+            let value = -weight / 2.0 * scale[i] * scale[j];
+            triplets.add_triplet(i, j, value);
+            triplets.add_triplet(j, i, value);
+        }
+
+        Ok(Self {
+            matrix: triplets.to_csr(),
+            row_ids: self.row_ids.clone(),
+            col_ids: self.col_ids.clone(),
+        })
+    }
+
     /// Get matrix dimensions
     pub fn shape(&self) -> (usize, usize) {
         (self.matrix.rows(), self.matrix.cols())
@@ -176,6 +215,414 @@ impl SparseSimilarityMatrix {
     pub fn entry_ids(&self) -> &[String] {
         &self.row_ids
     }
+
+    /// Get column IDs
+    pub fn col_ids(&self) -> &[String] {
+        &self.col_ids
+    }
+
+    /// Non-zero entries as (row, col, value) triplets
+    pub fn triplets(&self) -> Vec<(usize, usize, f64)> {
+        self.matrix
+            .outer_iterator()
+            .enumerate()
+            .flat_map(|(row, vec)| {
+                vec.iter()
+                    .map(move |(col, &value)| (row, col, value))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Rebuild from IDs and (row, col, value) triplets
+    pub fn from_triplets(
+        row_ids: Vec<String>,
+        col_ids: Vec<String>,
+        triplets: &[(usize, usize, f64)],
+    ) -> Result<Self, String> {
+        let shape = (row_ids.len(), col_ids.len());
+        let outside = triplets
+            .par_iter()
+            .find_first(|&&(i, j, _)| i >= shape.0 || j >= shape.1);
+        if let Some(&(i, j, _)) = outside {
+            return Err(format!(
+                "triplet ({}, {}) out of bounds for {}x{} matrix",
+                i, j, shape.0, shape.1
+            ));
+        }
+
+        Ok(Self {
+            matrix: assemble_csr(shape, triplets.to_vec()),
+            row_ids,
+            col_ids,
+        })
+    }
+
+    /// Symmetric matrix over `ids` from upper-triangle (i, j, similarity)
+    /// pairs such as those of `similarity_blocks` (diagonal fixed to 1.0 as
+    /// in `from_edges`)
+    pub fn from_similarity_pairs(
+        ids: Vec<String>,
+        pairs: &[(usize, usize, f64)],
+    ) -> Result<Self, String> {
+        let mut triplets: Vec<(usize, usize, f64)> = (0..ids.len()).map(|i| (i, i, 1.0)).collect();
+        for &(i, j, value) in pairs {
+            if i != j {
+                triplets.push((i, j, value));
+                triplets.push((j, i, value));
+            }
+        }
+        Self::from_triplets(ids.clone(), ids, &triplets)
+    }
+
+    /// Top-k similarity matrix computed straight from IPA strings: row `i`
+    /// keeps the `k` entries most similar to `ipa_strings[i]` at or above
+    /// `min_sim` (ties go to the lower index). Rows are filled in parallel,
+    /// each holding at most `k` candidates, so no dense matrix is built. With
+    /// `blocking`, only entries sharing a phonetic key are compared; pairs
+    /// whose segment lengths cannot beat `min_sim` or a full row's weakest
+    /// neighbor are skipped unscored. Rows are top-k independently, so the
+    /// result need not be symmetric (diagonal fixed to 1.0 as in
+    /// `from_edges`)
+    pub fn from_ipa_topk(
+        ids: Vec<String>,
+        ipa_strings: &[String],
+        k: usize,
+        min_sim: f64,
+        metric: SimilarityMetric,
+        blocking: Option<KeyScheme>,
+    ) -> Result<Self, String> {
+        let n = ids.len();
+        if n != ipa_strings.len() {
+            return Err(format!("{} ids for {} IPA strings", n, ipa_strings.len()));
+        }
+        let lengths = segment_lengths(ipa_strings);
+        let everyone: Vec<usize> = (0..n).collect();
+        let blocks = blocking.map(|scheme| block_by_key(ipa_strings, scheme));
+        let mut block_of = vec![None; n];
+        for (b, members) in blocks.iter().flatten().enumerate() {
+            for &i in members {
+                block_of[i] = Some(b);
+            }
+        }
+
+        let rows: Vec<Vec<(usize, usize, f64)>> = (0..n)
+            .into_par_iter()
+            .map(|i| {
+                let candidates: &[usize] = match (&blocks, block_of[i]) {
+                    (None, _) => &everyone,
+                    (Some(blocks), Some(b)) => &blocks[b],
+                    (Some(_), None) => &[],
+                };
+                // Min-heap whose root is the weakest neighbor kept so far
+                let mut heap: BinaryHeap<Reverse<(OrderedFloat<f64>, Reverse<usize>)>> =
+                    BinaryHeap::with_capacity(k + 1);
+                for &j in candidates {
+                    if j == i {
+                        continue;
+                    }
+                    // Candidates come in index order, so a tie with a full
+                    // row's weakest neighbor loses too
+                    let bound = metric.length_bound(lengths[i], lengths[j]);
+                    let weakest = heap.peek().filter(|_| heap.len() == k);
+                    if bound < min_sim || weakest.is_some_and(|Reverse((sim, _))| bound <= sim.0) {
+                        continue;
+                    }
+                    let sim = metric.similarity(&ipa_strings[i], &ipa_strings[j]);
+                    if sim >= min_sim {
+                        heap.push(Reverse((OrderedFloat(sim), Reverse(j))));
+                        if heap.len() > k {
+                            heap.pop();
+                        }
+                    }
+                }
+                heap.into_iter()
+                    .map(|Reverse((sim, Reverse(j)))| (i, j, sim.0))
+                    .collect()
+            })
+            .collect();
+
+        let mut triplets: Vec<(usize, usize, f64)> = (0..n).map(|i| (i, i, 1.0)).collect();
+        triplets.extend(rows.into_iter().flatten());
+        Self::from_triplets(ids.clone(), ids, &triplets)
+    }
+
+    /// Sparsify a dense square similarity matrix, keeping entries at or above
+    /// `threshold` (diagonal fixed to 1.0 as in `from_edges`)
+    pub fn from_dense(
+        ids: Vec<String>,
+        dense: ArrayView2<f64>,
+        threshold: f64,
+    ) -> Result<Self, String> {
+        let n = ids.len();
+        if dense.shape() != [n, n] {
+            return Err(format!(
+                "dense matrix shape {:?} does not match {} ids",
+                dense.shape(),
+                n
+            ));
+        }
+
+        let mut triplets: Vec<(usize, usize, f64)> = (0..n).map(|i| (i, i, 1.0)).collect();
+        for ((i, j), &value) in dense.indexed_iter() {
+            if i != j && value >= threshold {
+                triplets.push((i, j, value));
+            }
+        }
+        Self::from_triplets(ids.clone(), ids, &triplets)
+    }
+
+    /// CSR arrays as (indptr, indices, data)
+    pub fn csr_parts(&self) -> (Vec<usize>, Vec<usize>, Vec<f64>) {
+        (
+            self.matrix.proper_indptr().into_owned(),
+            self.matrix.indices().to_vec(),
+            self.matrix.data().to_vec(),
+        )
+    }
+
+    /// Rebuild from CSR arrays. Columns within a row may come in any order
+    /// and duplicate entries are summed, as scipy allows both.
+    pub fn from_csr(
+        row_ids: Vec<String>,
+        col_ids: Vec<String>,
+        indptr: &[usize],
+        indices: &[usize],
+        data: &[f64],
+    ) -> Result<Self, String> {
+        let (rows, cols) = (row_ids.len(), col_ids.len());
+        if indptr.len() != rows + 1 {
+            return Err(format!(
+                "indptr has {} entries, expected {} for {} rows",
+                indptr.len(),
+                rows + 1,
+                rows
+            ));
+        }
+        if indices.len() != data.len() {
+            return Err(format!(
+                "indices ({}) and data ({}) differ in length",
+                indices.len(),
+                data.len()
+            ));
+        }
+        if indptr[0] != 0
+            || indptr[rows] != indices.len()
+            || indptr.windows(2).any(|w| w[0] > w[1])
+        {
+            return Err("indptr must rise from 0 to the number of stored entries".to_string());
+        }
+        if let Some(&col) = indices.iter().find(|&&col| col >= cols) {
+            return Err(format!("column index {} out of bounds for {} columns", col, cols));
+        }
+
+        let mut sorted_indptr = Vec::with_capacity(rows + 1);
+        let mut sorted_indices = Vec::with_capacity(indices.len());
+        let mut sorted_data = Vec::with_capacity(data.len());
+        sorted_indptr.push(0);
+        for row in 0..rows {
+            let mut entries: Vec<(usize, f64)> = (indptr[row]..indptr[row + 1])
+                .map(|k| (indices[k], data[k]))
+                .collect();
+            entries.sort_by_key(|e| e.0);
+            for (col, value) in entries {
+                let row_started = sorted_indices.len() > sorted_indptr[row];
+                if row_started && sorted_indices.last() == Some(&col) {
+                    *sorted_data.last_mut().unwrap() += value;
+                } else {
+                    sorted_indices.push(col);
+                    sorted_data.push(value);
+                }
+            }
+            sorted_indptr.push(sorted_indices.len());
+        }
+
+        let matrix = CsMat::try_new((rows, cols), sorted_indptr, sorted_indices, sorted_data)
+            .map_err(|(_, _, _, e)| format!("invalid CSR structure: {}", e))?;
+        Ok(Self {
+            matrix,
+            row_ids,
+            col_ids,
+        })
+    }
+
+    /// Save as a compact little-endian CSR dump: magic, format version,
+    /// shape and nnz, row and column ID vocabularies, then indptr, indices
+    /// and values
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|e| format!("cannot create matrix file {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        self.write_csr(&mut writer)
+            .and_then(|_| writer.flush())
+            .map_err(|e| format!("cannot write matrix: {}", e))
+    }
+
+    /// Load a matrix written by `save`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("cannot open matrix file {}: {}", path.display(), e))?;
+        Self::read_csr(&mut BufReader::new(file))
+    }
+
+    /// Save in the memory-mappable layout of `MappedSimilarityMatrix`
+    /// (`path` plus an `.ids` sidecar)
+    pub fn save_mapped(&self, path: &Path) -> Result<(), String> {
+        write_mapped(path, &self.row_ids, &self.col_ids, self.nnz(), |writer| {
+            self.write_arrays(writer)
+        })
+    }
+
+    fn write_csr(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(SPARSE_MAGIC)?;
+        writer.write_all(&SPARSE_FORMAT_VERSION.to_le_bytes())?;
+        for dim in [self.matrix.rows(), self.matrix.cols(), self.matrix.nnz()] {
+            write_u64(writer, dim as u64)?;
+        }
+        for id in self.row_ids.iter().chain(&self.col_ids) {
+            write_u64(writer, id.len() as u64)?;
+            writer.write_all(id.as_bytes())?;
+        }
+        self.write_arrays(writer)
+    }
+
+    /// indptr and indices as u64, then values as f64, all little-endian
+    fn write_arrays(&self, writer: &mut impl Write) -> io::Result<()> {
+        for &offset in self.matrix.proper_indptr().iter() {
+            write_u64(writer, offset as u64)?;
+        }
+        for &index in self.matrix.indices() {
+            write_u64(writer, index as u64)?;
+        }
+        for &value in self.matrix.data() {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_csr(reader: &mut impl Read) -> Result<Self, String> {
+        let invalid = |e: io::Error| format!("invalid matrix file: {}", e);
+        let SavedHeader {
+            row_ids,
+            col_ids,
+            nnz,
+        } = SavedHeader::read(reader)?;
+        let indptr = read_usizes(reader, row_ids.len() + 1).map_err(invalid)?;
+        let indices = read_usizes(reader, nnz).map_err(invalid)?;
+        let mut data = Vec::new();
+        for _ in 0..nnz {
+            let mut bytes = [0u8; 8];
+            reader.read_exact(&mut bytes).map_err(invalid)?;
+            data.push(f64::from_le_bytes(bytes));
+        }
+
+        Self::from_csr(row_ids, col_ids, &indptr, &indices, &data)
+            .map_err(|e| format!("invalid matrix file: {}", e))
+    }
+}
+
+/// Everything in a saved matrix file before its CSR arrays
+pub(crate) struct SavedHeader {
+    pub row_ids: Vec<String>,
+    pub col_ids: Vec<String>,
+    pub nnz: usize,
+}
+
+impl SavedHeader {
+    /// Read up to the arrays, checking magic and format version
+    pub(crate) fn read(reader: &mut impl Read) -> Result<Self, String> {
+        let invalid = |e: io::Error| format!("invalid matrix file: {}", e);
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic).map_err(invalid)?;
+        if &magic != SPARSE_MAGIC {
+            return Err("invalid matrix file: not a saved sparse matrix".to_string());
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version).map_err(invalid)?;
+        check_version("matrix", u32::from_le_bytes(version), SPARSE_FORMAT_VERSION)?;
+
+        let rows = read_usize(reader).map_err(invalid)?;
+        let cols = read_usize(reader).map_err(invalid)?;
+        let nnz = read_usize(reader).map_err(invalid)?;
+        Ok(Self {
+            row_ids: read_ids(reader, rows).map_err(invalid)?,
+            col_ids: read_ids(reader, cols).map_err(invalid)?,
+            nnz,
+        })
+    }
+}
+
+/// The `k` largest (column, value) entries of a row, skipping column
+/// `skip` (the entry itself); ties go to the higher column
+pub(crate) fn top_k(
+    row: impl Iterator<Item = (usize, f64)>,
+    skip: usize,
+    k: usize,
+) -> Vec<(usize, f64)> {
+    // Use max-heap to find top-k
+    let mut heap: BinaryHeap<(OrderedFloat<f64>, usize)> = row
+        .filter(|&(col, _)| col != skip)
+        .map(|(col, value)| (OrderedFloat(value), col))
+        .collect();
+    std::iter::from_fn(|| heap.pop())
+        .take(k)
+        .map(|(value, col)| (col, value.0))
+        .collect()
+}
+
+pub(crate) fn write_u64(writer: &mut impl Write, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_usize(reader: &mut impl Read) -> io::Result<usize> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    usize::try_from(u64::from_le_bytes(bytes))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "length overflows usize"))
+}
+
+fn read_usizes(reader: &mut impl Read, count: usize) -> io::Result<Vec<usize>> {
+    (0..count).map(|_| read_usize(reader)).collect()
+}
+
+fn read_ids(reader: &mut impl Read, count: usize) -> io::Result<Vec<String>> {
+    (0..count)
+        .map(|_| {
+            let len = read_usize(reader)?;
+            let mut bytes = Vec::new();
+            reader.take(len as u64).read_to_end(&mut bytes)?;
+            if bytes.len() != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+/// CSR matrix of `shape` from in-bounds (row, col, value) triplets: a
+/// parallel stable sort by position, then one pass summing duplicates in
+/// input order
+fn assemble_csr(shape: (usize, usize), mut triplets: Vec<(usize, usize, f64)>) -> CsMat<f64> {
+    triplets.par_sort_by_key(|&(i, j, _)| (i, j));
+    let mut indptr = vec![0; shape.0 + 1];
+    let mut indices = Vec::with_capacity(triplets.len());
+    let mut data: Vec<f64> = Vec::with_capacity(triplets.len());
+    let mut last = None;
+    for (i, j, value) in triplets {
+        if last == Some((i, j)) {
+            *data.last_mut().unwrap() += value;
+        } else {
+            indices.push(j);
+            data.push(value);
+            indptr[i + 1] += 1;
+            last = Some((i, j));
+        }
+    }
+    for row in 0..shape.0 {
+        indptr[row + 1] += indptr[row];
+    }
+    CsMat::new(shape, indptr, indices, data)
 }
 
 /// Batch compute top-k similar entries for multiple queries
@@ -198,6 +645,26 @@ pub fn threshold_filter(edges: Vec<(String, String, f64)>, threshold: f64) -> Ve
         .collect()
 }
 
+/// Soft alternative to `threshold_filter`: rescale each weight through the
+/// sigmoid `1 / (1 + exp(-steepness * (w - threshold)))`, so an edge at the
+/// cutoff gets 0.5 and near-threshold edges keep a graded weight for
+/// PageRank or diffusion. Edges rescaled below `min_weight` are dropped.
+pub fn sigmoid_weights(
+    edges: Vec<(String, String, f64)>,
+    threshold: f64,
+    steepness: f64,
+    min_weight: f64,
+) -> Vec<(String, String, f64)> {
+    edges
+        .into_par_iter()
+        .map(|(source, target, weight)| {
+            let soft = 1.0 / (1.0 + (-steepness * (weight - threshold)).exp());
+            (source, target, soft)
+        })
+        .filter(|(_, _, weight)| *weight >= min_weight)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,6 +682,42 @@ mod tests {
         assert!(matrix.nnz() > 0);
     }
 
+    #[test]
+    fn test_from_ipa_topk() {
+        let ids: Vec<String> = ["a", "b", "c", "d"].iter().map(|s| s.to_string()).collect();
+        let ipa: Vec<String> = ["pater", "fater", "pater", "kanta"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let topk = |k, min_sim, blocking| {
+            let metric = SimilarityMetric::Levenshtein;
+            SparseSimilarityMatrix::from_ipa_topk(ids.clone(), &ipa, k, min_sim, metric, blocking)
+                .unwrap()
+        };
+
+        let matrix = topk(1, 0.5, None);
+        assert_eq!(matrix.knn("a", 5), vec![("c".to_string(), 1.0)]);
+        // b is equally close to a and c; the lower index wins
+        assert_eq!(matrix.knn("b", 5)[0].0, "a");
+        assert!(matrix.knn("d", 5).is_empty());
+        assert_eq!(matrix.nnz(), 4 + 3);
+
+        assert_eq!(topk(2, 0.0, None).nnz(), 4 + 4 * 2);
+        // kanta shares no key with the others and is never compared
+        let blocked = topk(2, 0.0, Some(KeyScheme::Dolgopolsky));
+        assert_eq!(blocked.nnz(), 4 + 3 * 2);
+        assert!(blocked.knn("d", 5).is_empty());
+        let short = SparseSimilarityMatrix::from_ipa_topk(
+            ids[..2].to_vec(),
+            &ipa,
+            1,
+            0.0,
+            SimilarityMetric::Levenshtein,
+            None,
+        );
+        assert!(short.is_err());
+    }
+
     #[test]
     fn test_knn() {
         let edges = vec![
@@ -229,6 +732,74 @@ mod tests {
         assert_eq!(neighbors[0].0, "b"); // Highest similarity
     }
 
+    #[test]
+    fn test_batch_knn_matches_knn() {
+        let edges = vec![
+            ("a".to_string(), "b".to_string(), 0.9),
+            ("a".to_string(), "c".to_string(), 0.7),
+            ("b".to_string(), "c".to_string(), 0.6),
+        ];
+
+        let matrix = SparseSimilarityMatrix::from_edges(edges, 0.4);
+        let ids: Vec<String> = vec!["a".into(), "b".into(), "missing".into()];
+        let batched = batch_knn(&matrix, &ids, 2);
+        for (id, neighbors) in ids.iter().zip(&batched) {
+            assert_eq!(neighbors, &matrix.knn(id, 2));
+        }
+    }
+
+    #[test]
+    fn test_from_dense() {
+        let dense = ndarray::arr2(&[[1.0, 0.9, 0.2], [0.9, 1.0, 0.6], [0.2, 0.6, 1.0]]);
+        let ids: Vec<String> = vec!["a".into(), "b".into(), "c".into()];
+        let matrix = SparseSimilarityMatrix::from_dense(ids.clone(), dense.view(), 0.5).unwrap();
+        assert_eq!(matrix.nnz(), 7);
+        assert_eq!(matrix.knn("a", 1), vec![("b".to_string(), 0.9)]);
+
+        let wrong = ndarray::Array2::<f64>::zeros((2, 3));
+        assert!(SparseSimilarityMatrix::from_dense(ids, wrong.view(), 0.5).is_err());
+    }
+
+    #[test]
+    fn test_from_edges_matches_serial_assembly() {
+        let names: Vec<String> = (0..40).map(|i| format!("w{}", i)).collect();
+        let mut edges: Vec<(String, String, f64)> = (0..400)
+            .map(|k| {
+                let (a, b) = ((k * 7) % 40, (k * 13 + 5) % 40);
+                (names[a].clone(), names[b].clone(), (k % 10) as f64 / 10.0)
+            })
+            .collect();
+        // Repeated edges and self-loops are summed as before
+        edges.push(("w1".to_string(), "w2".to_string(), 0.8));
+        edges.push(("w3".to_string(), "w3".to_string(), 0.5));
+
+        let mut ids = names.clone();
+        ids.sort();
+        let index = |id: &str| ids.iter().position(|x| x == id).unwrap();
+        let mut tri = TriMat::new((ids.len(), ids.len()));
+        for (a, b, weight) in &edges {
+            if *weight >= 0.3 {
+                tri.add_triplet(index(a), index(b), *weight);
+                if a != b {
+                    tri.add_triplet(index(b), index(a), *weight);
+                }
+            }
+        }
+        for i in 0..ids.len() {
+            tri.add_triplet(i, i, 1.0);
+        }
+        let serial: CsMat<f64> = tri.to_csr();
+
+        let matrix = SparseSimilarityMatrix::from_edges(edges, 0.3);
+        assert_eq!(matrix.entry_ids(), &ids[..]);
+        let (indptr, indices, data) = matrix.csr_parts();
+        assert_eq!(indptr, serial.proper_indptr().into_owned());
+        assert_eq!(indices, serial.indices());
+        let close = data.iter().zip(serial.data()).all(|(x, y)| (x - y).abs() < 1e-12);
+        assert!(close && data.len() == serial.nnz());
+        assert_eq!(matrix.get(index("w3"), index("w3")), Some(1.5));
+    }
+
     #[test]
     fn test_sparsity() {
         let edges = vec![
@@ -239,5 +810,96 @@ mod tests {
         let sparsity = matrix.sparsity();
         assert!(sparsity > 0.0 && sparsity < 1.0);
     }
-}
 
+    #[test]
+    fn test_csr_round_trip() {
+        let edges = vec![
+            ("a".to_string(), "b".to_string(), 0.9),
+            ("b".to_string(), "c".to_string(), 0.7),
+        ];
+        let matrix = SparseSimilarityMatrix::from_edges(edges, 0.5);
+        let (indptr, indices, data) = matrix.csr_parts();
+        let ids = matrix.entry_ids().to_vec();
+
+        let rebuilt =
+            SparseSimilarityMatrix::from_csr(ids.clone(), ids.clone(), &indptr, &indices, &data)
+                .unwrap();
+        assert_eq!(rebuilt.triplets(), matrix.triplets());
+
+        // Unsorted columns and duplicates, as scipy may produce
+        let messy = SparseSimilarityMatrix::from_csr(
+            vec!["x".into(), "y".into()],
+            vec!["x".into(), "y".into()],
+            &[0, 3, 3],
+            &[1, 0, 1],
+            &[0.25, 1.0, 0.5],
+        )
+        .unwrap();
+        assert_eq!(messy.triplets(), vec![(0, 0, 1.0), (0, 1, 0.75)]);
+
+        let short = SparseSimilarityMatrix::from_csr(ids.clone(), ids, &[0, 1], &[0], &[1.0]);
+        assert!(short.is_err());
+        assert!(SparseSimilarityMatrix::from_csr(
+            vec!["x".into()],
+            vec!["x".into()],
+            &[0, 1],
+            &[3],
+            &[1.0]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let edges = vec![
+            ("a".to_string(), "b".to_string(), 0.9),
+            ("b".to_string(), "c".to_string(), 0.6),
+            ("c".to_string(), "δ".to_string(), 0.8),
+        ];
+        let matrix = SparseSimilarityMatrix::from_edges(edges, 0.5);
+
+        let path =
+            std::env::temp_dir().join(format!("langviz_sparse_{}.bin", std::process::id()));
+        matrix.save(&path).unwrap();
+        let loaded = SparseSimilarityMatrix::load(&path).unwrap();
+
+        assert_eq!(loaded.shape(), matrix.shape());
+        assert_eq!(loaded.entry_ids(), matrix.entry_ids());
+        assert_eq!(loaded.triplets(), matrix.triplets());
+        assert_eq!(loaded.knn("b", 2), matrix.knn("b", 2));
+
+        // Truncated and foreign files are rejected
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+        assert!(SparseSimilarityMatrix::load(&path).is_err());
+        std::fs::write(&path, b"not a matrix").unwrap();
+        assert!(SparseSimilarityMatrix::load(&path).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_from_similarity_pairs() {
+        let ids: Vec<String> = vec!["a".into(), "b".into(), "c".into()];
+        let matrix =
+            SparseSimilarityMatrix::from_similarity_pairs(ids.clone(), &[(0, 2, 0.7)]).unwrap();
+        assert_eq!(matrix.nnz(), 5);
+        assert_eq!(matrix.knn("c", 1), vec![("a".to_string(), 0.7)]);
+        assert!(SparseSimilarityMatrix::from_similarity_pairs(ids, &[(0, 3, 0.5)]).is_err());
+    }
+
+    #[test]
+    fn test_sigmoid_weights() {
+        let edges = vec![
+            ("a".to_string(), "b".to_string(), 0.9),
+            ("b".to_string(), "c".to_string(), 0.7),
+            ("c".to_string(), "d".to_string(), 0.65),
+            ("d".to_string(), "e".to_string(), 0.1),
+        ];
+        let soft = sigmoid_weights(edges, 0.7, 20.0, 0.05);
+        assert_eq!(soft.len(), 3);
+        assert!(soft[0].2 > 0.95);
+        assert_eq!(soft[1].2, 0.5);
+        // Just below the cutoff: kept, down-weighted rather than dropped
+        assert!(soft[2].2 > 0.2 && soft[2].2 < 0.5);
+    }
+}
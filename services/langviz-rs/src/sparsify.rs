@@ -0,0 +1,222 @@
+//! Threshold-free sparsification of the similarity graph.
+//!
+//! A single global threshold assumes similarity scores are comparable
+//! across language pairs, which they rarely are. These methods instead rank
+//! each node's own edges and keep the strongest ones locally; an edge
+//! survives if either endpoint keeps it. Components and communities are
+//! then computed on the sparsified graph as usual.
+//!
+//! Mutual kNN reweighting uses the same per-node rankings without dropping
+//! anything: edges both endpoints rank in their top k are boosted, one-sided
+//! ones penalized.
+
+use petgraph::visit::EdgeRef;
+use rayon::prelude::*;
+use std::str::FromStr;
+
+use crate::graph::CognateGraph;
+
+/// How many of its edges each node keeps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SparsificationMethod {
+    /// The `k` strongest edges
+    TopK { k: usize },
+    /// The `ceil(degree^exponent)` strongest edges (local degree
+    /// sparsification): hubs keep more edges, but sublinearly
+    LocalDegree { exponent: f64 },
+    /// Edges at least `ratio` times as strong as the node's best edge
+    Relative { ratio: f64 },
+}
+
+impl FromStr for SparsificationMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top_k" => Ok(SparsificationMethod::TopK { k: 5 }),
+            "local_degree" => Ok(SparsificationMethod::LocalDegree { exponent: 0.5 }),
+            "relative" => Ok(SparsificationMethod::Relative { ratio: 0.9 }),
+            other => Err(format!(
+                "unknown sparsification method '{}' (expected top_k, local_degree or relative)",
+                other
+            )),
+        }
+    }
+}
+
+impl SparsificationMethod {
+    /// Replace the method's parameter (k, exponent or ratio)
+    pub fn with_parameter(self, value: f64) -> Result<Self, String> {
+        match self {
+            SparsificationMethod::TopK { .. } if value >= 1.0 && value.fract() == 0.0 => {
+                Ok(SparsificationMethod::TopK { k: value as usize })
+            }
+            SparsificationMethod::LocalDegree { .. } if (0.0..=1.0).contains(&value) => {
+                Ok(SparsificationMethod::LocalDegree { exponent: value })
+            }
+            SparsificationMethod::Relative { .. } if (0.0..=1.0).contains(&value) => {
+                Ok(SparsificationMethod::Relative { ratio: value })
+            }
+            SparsificationMethod::TopK { .. } => {
+                Err(format!("top_k needs a positive integer k, got {}", value))
+            }
+            _ => Err(format!("parameter must lie in [0, 1], got {}", value)),
+        }
+    }
+
+    /// Number of edges to keep out of `weights`, sorted strongest first
+    fn quota(&self, weights: &[f64]) -> usize {
+        match *self {
+            SparsificationMethod::TopK { k } => k,
+            SparsificationMethod::LocalDegree { exponent } => {
+                (weights.len() as f64).powf(exponent).ceil() as usize
+            }
+            SparsificationMethod::Relative { ratio } => match weights.first() {
+                Some(&best) => weights.iter().take_while(|&&w| w >= ratio * best).count(),
+                None => 0,
+            },
+        }
+    }
+}
+
+impl CognateGraph {
+    /// Per edge, how many of its endpoints keep it among their strongest
+    /// `quota` incident edges (ties broken by insertion order)
+    fn local_votes<F>(&self, quota: F) -> Vec<u8>
+    where
+        F: Fn(&[f64]) -> usize + Sync,
+    {
+        let kept: Vec<Vec<usize>> = self
+            .graph
+            .node_indices()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|idx| {
+                let mut incident: Vec<(f64, usize)> = self
+                    .graph
+                    .edges(idx)
+                    .map(|edge| (*edge.weight(), edge.id().index()))
+                    .collect();
+                incident.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+                let weights: Vec<f64> = incident.iter().map(|e| e.0).collect();
+                let quota = quota(&weights);
+                incident.into_iter().take(quota).map(|e| e.1).collect()
+            })
+            .collect();
+
+        let mut votes = vec![0u8; self.graph.edge_count()];
+        for edge in kept.into_iter().flatten() {
+            votes[edge] += 1;
+        }
+        votes
+    }
+
+    /// Graph keeping, for every node, only its locally strongest edges
+    /// (ties broken by insertion order); all nodes are kept
+    pub fn sparsify(&self, method: SparsificationMethod) -> CognateGraph {
+        let votes = self.local_votes(|weights| method.quota(weights));
+        let keep = vec![true; self.graph.node_count()];
+        self.subgraph(&keep, |edge| votes[edge.id().index()] > 0)
+    }
+
+    /// Mutual kNN reweighting: an edge both endpoints rank among their `k`
+    /// strongest is multiplied by `boost`, a one-sided edge by `penalty`, and
+    /// an edge neither endpoint ranks that high by `penalty` squared
+    pub fn mutual_knn_reweight(&self, k: usize, boost: f64, penalty: f64) -> CognateGraph {
+        let votes = self.local_votes(|_| k);
+        let mut reweighted = self.clone();
+        for edge in reweighted.graph.edge_indices() {
+            reweighted.graph[edge] *= match votes[edge.index()] {
+                2 => boost,
+                1 => penalty,
+                _ => penalty * penalty,
+            };
+        }
+        reweighted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn edge(a: &str, b: &str, w: f64) -> SimilarityEdge {
+        SimilarityEdge::new(a.to_string(), b.to_string(), w)
+    }
+
+    /// Two language pairs on different similarity scales, each with one
+    /// weak spurious link
+    fn mixed_scales() -> CognateGraph {
+        CognateGraph::from_edges(
+            vec![
+                // High-scoring pair: true links ~0.9, noise 0.6
+                edge("eng_a", "deu_a", 0.9),
+                edge("eng_b", "deu_b", 0.92),
+                edge("eng_a", "deu_b", 0.6),
+                // Low-scoring pair: true links ~0.4, noise 0.1
+                edge("lat_a", "gre_a", 0.4),
+                edge("lat_b", "gre_b", 0.42),
+                edge("lat_a", "gre_b", 0.1),
+            ],
+            0.0,
+        )
+    }
+
+    #[test]
+    fn test_top_k_handles_mixed_scales() {
+        let graph = mixed_scales();
+        let sparse = graph.sparsify(SparsificationMethod::TopK { k: 1 });
+        assert_eq!(sparse.graph.node_count(), 8);
+        assert_eq!(sparse.graph.edge_count(), 4);
+
+        let sets = sparse.find_cognate_sets();
+        assert_eq!(sets.len(), 4);
+        assert!(sets.iter().all(|set| set.members.len() == 2));
+    }
+
+    #[test]
+    fn test_relative_and_local_degree() {
+        let graph = mixed_scales();
+        let relative = graph.sparsify(SparsificationMethod::Relative { ratio: 0.9 });
+        assert_eq!(relative.graph.edge_count(), 4);
+
+        // Every node has degree <= 2, so ceil(d^0.5) keeps at most 2 edges
+        let local = graph.sparsify(SparsificationMethod::LocalDegree { exponent: 0.5 });
+        assert_eq!(local.graph.edge_count(), 6);
+        let strict = graph.sparsify(SparsificationMethod::LocalDegree { exponent: 0.0 });
+        assert_eq!(strict.graph.edge_count(), 4);
+    }
+
+    #[test]
+    fn test_method_parsing() {
+        let method: SparsificationMethod = "top_k".parse().unwrap();
+        assert_eq!(
+            method.with_parameter(3.0),
+            Ok(SparsificationMethod::TopK { k: 3 })
+        );
+        assert!(method.with_parameter(2.5).is_err());
+        assert!("relative"
+            .parse::<SparsificationMethod>()
+            .unwrap()
+            .with_parameter(1.5)
+            .is_err());
+        assert!("global".parse::<SparsificationMethod>().is_err());
+    }
+
+    #[test]
+    fn test_mutual_knn_reweight() {
+        let graph = mixed_scales();
+        let reweighted = graph.mutual_knn_reweight(1, 1.5, 0.5);
+        let factors: Vec<f64> = reweighted
+            .edge_list()
+            .iter()
+            .zip(graph.edge_list())
+            .map(|(new, old)| (new.2 / old.2 * 100.0).round() / 100.0)
+            .collect();
+        // True links are each endpoint's best edge; the noise links are
+        // nobody's best
+        assert_eq!(factors, vec![1.5, 1.5, 0.25, 1.5, 1.5, 0.25]);
+        assert_eq!(reweighted.graph.node_count(), graph.graph.node_count());
+    }
+}
@@ -0,0 +1,344 @@
+//! Grouped train/test and k-fold splits of wordlists.
+//!
+//! Pairs from the same concept, or from closely related languages, share
+//! most of their signal, so a random split of entries lets a classifier or a
+//! calibration see its test data through their neighbours. These splits keep
+//! every concept (or every language family) whole on one side and balance
+//! the entry counts across folds. A pair of entries then belongs to a fold
+//! only when both of its entries do; pairs straddling two folds are dropped
+//! rather than leaked.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use crate::error::LangVizError;
+
+/// What keeps entries together in a split
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    Concept,
+    /// Language family; languages without one count as isolates
+    Family,
+}
+
+impl FromStr for SplitBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "concept" => Ok(SplitBy::Concept),
+            "family" => Ok(SplitBy::Family),
+            other => Err(format!(
+                "unknown split grouping '{}' (expected concept or family)",
+                other
+            )),
+        }
+    }
+}
+
+/// Fold of each wordlist entry, with whole groups per fold
+#[derive(Debug, Clone)]
+pub struct WordlistSplit {
+    pub(crate) ids: Vec<String>,
+    /// Fold of each entry, in input order
+    pub folds: Vec<usize>,
+    /// Fold of each group (concept or family)
+    pub groups: BTreeMap<String, usize>,
+    pub k: usize,
+    index: HashMap<String, usize>,
+}
+
+/// Pair indices on each side of a split
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PairSplit {
+    pub train: Vec<usize>,
+    pub test: Vec<usize>,
+    /// Pairs with one entry in the test fold and one outside it
+    pub dropped: usize,
+}
+
+impl WordlistSplit {
+    /// Two folds over (id, language, concept, form) entries: fold 1 holds
+    /// whole groups adding up to about `test_fraction` of the entries,
+    /// fold 0 the rest. `families` maps languages to families.
+    pub fn train_test(
+        entries: &[(String, String, String, String)],
+        by: SplitBy,
+        families: Option<&HashMap<String, String>>,
+        test_fraction: f64,
+        seed: u64,
+    ) -> Result<Self, LangVizError> {
+        if !(test_fraction > 0.0 && test_fraction < 1.0) {
+            return Err(LangVizError::InvalidParameter {
+                name: "test_fraction".to_string(),
+                value: test_fraction,
+                expected: "a value in (0, 1)".to_string(),
+            });
+        }
+        let (keys, groups) = group_entries(entries, by, families, 2, seed)?;
+        let target = test_fraction * entries.len() as f64;
+        let mut test = 0usize;
+        let mut assignment = vec![0; groups.len()];
+        for (g, (_, size)) in groups.iter().enumerate() {
+            // Take the group when it brings the test side closer to target
+            let with = (test + size) as f64 - target;
+            if with.abs() < (test as f64 - target).abs() {
+                assignment[g] = 1;
+                test += size;
+            }
+        }
+        // Neither side may end up empty
+        let smallest = |side: usize, assignment: &[usize]| {
+            (0..groups.len())
+                .filter(|&g| assignment[g] == side)
+                .min_by_key(|&g| groups[g].1)
+        };
+        for side in [0, 1] {
+            if !assignment.contains(&(1 - side)) {
+                if let Some(g) = smallest(side, &assignment) {
+                    assignment[g] = 1 - side;
+                }
+            }
+        }
+        Ok(Self::new(entries, keys, &groups, &assignment, 2))
+    }
+
+    /// `k` folds over (id, language, concept, form) entries with whole
+    /// groups per fold: largest groups first, each into the fold with the
+    /// fewest entries so far
+    pub fn k_fold(
+        entries: &[(String, String, String, String)],
+        by: SplitBy,
+        families: Option<&HashMap<String, String>>,
+        k: usize,
+        seed: u64,
+    ) -> Result<Self, LangVizError> {
+        if k < 2 {
+            return Err(LangVizError::InvalidParameter {
+                name: "k".to_string(),
+                value: k as f64,
+                expected: "at least 2".to_string(),
+            });
+        }
+        let (keys, groups) = group_entries(entries, by, families, k, seed)?;
+        let mut order: Vec<usize> = (0..groups.len()).collect();
+        // Stable, so equal sizes keep their shuffled order
+        order.sort_by_key(|&g| std::cmp::Reverse(groups[g].1));
+        let mut sizes = vec![0usize; k];
+        let mut assignment = vec![0; groups.len()];
+        for g in order {
+            let fold = (0..k).min_by_key(|&f| sizes[f]).unwrap();
+            assignment[g] = fold;
+            sizes[fold] += groups[g].1;
+        }
+        Ok(Self::new(entries, keys, &groups, &assignment, k))
+    }
+
+    fn new(
+        entries: &[(String, String, String, String)],
+        keys: Vec<usize>,
+        groups: &[(String, usize)],
+        assignment: &[usize],
+        k: usize,
+    ) -> Self {
+        let ids: Vec<String> = entries.iter().map(|e| e.0.clone()).collect();
+        let mut index = HashMap::with_capacity(ids.len());
+        for (i, id) in ids.iter().enumerate() {
+            index.entry(id.clone()).or_insert(i);
+        }
+        Self {
+            folds: keys.iter().map(|&g| assignment[g]).collect(),
+            groups: groups
+                .iter()
+                .zip(assignment)
+                .map(|((group, _), &fold)| (group.clone(), fold))
+                .collect(),
+            ids,
+            k,
+            index,
+        }
+    }
+
+    /// Fold of an entry ID
+    pub fn fold_of(&self, id: &str) -> Option<usize> {
+        self.index.get(id).map(|&i| self.folds[i])
+    }
+
+    /// Entry IDs in `fold`, in input order
+    pub fn fold_ids(&self, fold: usize) -> Vec<&str> {
+        self.ids
+            .iter()
+            .zip(&self.folds)
+            .filter(|&(_, &f)| f == fold)
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    /// Indices of the (id, id) pairs with both entries in `test_fold`
+    /// (test) or both outside it (train)
+    pub fn split_pairs(
+        &self,
+        pairs: &[(String, String)],
+        test_fold: usize,
+    ) -> Result<PairSplit, LangVizError> {
+        let mut split = PairSplit::default();
+        for (i, (a, b)) in pairs.iter().enumerate() {
+            let fold = |id: &String| {
+                self.fold_of(id)
+                    .ok_or_else(|| LangVizError::UnknownEntryId(id.clone()))
+            };
+            match (fold(a)? == test_fold, fold(b)? == test_fold) {
+                (true, true) => split.test.push(i),
+                (false, false) => split.train.push(i),
+                _ => split.dropped += 1,
+            }
+        }
+        Ok(split)
+    }
+}
+
+/// Group index of each entry, and (group, entry count) in shuffled order
+type Grouping = (Vec<usize>, Vec<(String, usize)>);
+
+fn group_entries(
+    entries: &[(String, String, String, String)],
+    by: SplitBy,
+    families: Option<&HashMap<String, String>>,
+    min_groups: usize,
+    seed: u64,
+) -> Result<Grouping, LangVizError> {
+    if entries.is_empty() {
+        return Err(LangVizError::EmptyInput("entries".to_string()));
+    }
+    if by == SplitBy::Family && families.is_none() {
+        return Err(LangVizError::EmptyInput("families".to_string()));
+    }
+    let group_of = |(_, language, concept, _): &(String, String, String, String)| match by {
+        SplitBy::Concept => concept.clone(),
+        SplitBy::Family => families
+            .and_then(|f| f.get(language))
+            .unwrap_or(language)
+            .clone(),
+    };
+
+    // Sorted first so the shuffle depends only on the seed
+    let mut sizes: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in entries {
+        *sizes.entry(group_of(entry)).or_insert(0) += 1;
+    }
+    if sizes.len() < min_groups {
+        return Err(LangVizError::InvalidParameter {
+            name: "groups".to_string(),
+            value: sizes.len() as f64,
+            expected: format!("at least {} distinct groups to split", min_groups),
+        });
+    }
+    let mut groups: Vec<(String, usize)> = sizes.into_iter().collect();
+    groups.shuffle(&mut StdRng::seed_from_u64(seed));
+
+    let position: HashMap<&str, usize> = groups
+        .iter()
+        .enumerate()
+        .map(|(g, (group, _))| (group.as_str(), g))
+        .collect();
+    let keys = entries
+        .iter()
+        .map(|entry| position[group_of(entry).as_str()])
+        .collect();
+    Ok((keys, groups))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wordlist() -> Vec<(String, String, String, String)> {
+        let languages = ["latin", "italian", "german", "dutch", "greek"];
+        let concepts = ["father", "mother", "water", "fire", "sun", "moon"];
+        languages
+            .iter()
+            .flat_map(|l| {
+                concepts.iter().map(move |c| {
+                    let id = format!("{}_{}", l, c);
+                    (id, l.to_string(), c.to_string(), String::new())
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_splits_keep_groups_whole() {
+        let entries = wordlist();
+        let split = WordlistSplit::train_test(&entries, SplitBy::Concept, None, 0.3, 7).unwrap();
+        let test = split.fold_ids(1);
+        // 30 entries, 5 per concept: the closest whole-concept split is 10
+        assert_eq!(test.len(), 10);
+        for (entry, &fold) in entries.iter().zip(&split.folds) {
+            assert_eq!(split.groups[&entry.2], fold);
+        }
+        let again = WordlistSplit::train_test(&entries, SplitBy::Concept, None, 0.3, 7).unwrap();
+        assert_eq!(again.folds, split.folds);
+
+        let families: HashMap<String, String> = [
+            ("latin", "italic"),
+            ("italian", "italic"),
+            ("german", "germanic"),
+            ("dutch", "germanic"),
+        ]
+        .iter()
+        .map(|(l, f)| (l.to_string(), f.to_string()))
+        .collect();
+        let folds = WordlistSplit::k_fold(&entries, SplitBy::Family, Some(&families), 3, 1);
+        let folds = folds.unwrap();
+        // italic, germanic and the greek isolate, one per fold
+        assert_eq!(folds.groups.len(), 3);
+        assert_eq!(folds.fold_of("latin_sun"), folds.fold_of("italian_moon"));
+        assert_ne!(folds.fold_of("latin_sun"), folds.fold_of("greek_sun"));
+
+        let error = WordlistSplit::k_fold(&entries, SplitBy::Family, Some(&families), 4, 1);
+        assert!(matches!(error, Err(LangVizError::InvalidParameter { .. })));
+        let error = WordlistSplit::train_test(&entries, SplitBy::Family, None, 0.2, 0);
+        assert_eq!(
+            error.unwrap_err(),
+            LangVizError::EmptyInput("families".into())
+        );
+    }
+
+    #[test]
+    fn test_split_pairs() {
+        let entries = wordlist();
+        let split = WordlistSplit::train_test(&entries, SplitBy::Concept, None, 0.5, 3).unwrap();
+        let test_concept = split.groups.iter().find(|g| *g.1 == 1).unwrap().0;
+        let train_concept = split.groups.iter().find(|g| *g.1 == 0).unwrap().0;
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        let pairs = [
+            pair(
+                &format!("latin_{}", test_concept),
+                &format!("greek_{}", test_concept),
+            ),
+            pair(
+                &format!("latin_{}", train_concept),
+                &format!("dutch_{}", train_concept),
+            ),
+            pair(
+                &format!("latin_{}", test_concept),
+                &format!("dutch_{}", train_concept),
+            ),
+        ];
+        let pairs = split.split_pairs(&pairs, 1).unwrap();
+        assert_eq!(
+            pairs,
+            PairSplit {
+                train: vec![1],
+                test: vec![0],
+                dropped: 1
+            }
+        );
+
+        let unknown = split.split_pairs(&[pair("latin_sun", "zz")], 1);
+        assert_eq!(unknown, Err(LangVizError::UnknownEntryId("zz".to_string())));
+    }
+}
@@ -0,0 +1,289 @@
+//! Empirical concept stability: replacement rates per concept from a tree
+//! and cognate judgments.
+//!
+//! A concept's cognate classes form one multistate character over the
+//! tree's leaves; its replacements are the changes of the most
+//! parsimonious (Fitch) history, with synonyms giving a leaf several
+//! states and languages without data fitting any. Dividing by the branch
+//! length spanned by the languages attesting the concept (branch counts
+//! when the tree has no lengths) makes sparsely attested concepts
+//! comparable. Ranking concepts by rate, most stable first, gives an
+//! empirical retentiveness list, which `stability_correlation` compares
+//! with a published one such as Leipzig–Jakarta by Spearman's rho.
+
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::concepts::ConceptList;
+use crate::tree::Tree;
+
+/// Replacement rate of one concept
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConceptRate {
+    pub concept: String,
+    /// Languages with at least one cognate class for the concept
+    pub languages: usize,
+    pub cognate_classes: usize,
+    /// Parsimony changes of cognate class on the tree
+    pub replacements: usize,
+    /// Branch length spanned by the attesting languages
+    pub tree_length: f64,
+    /// replacements / tree_length (0 when the span is empty)
+    pub rate: f64,
+    /// 1 = most stable
+    pub rank: usize,
+}
+
+/// Agreement of the empirical ranking with a reference one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StabilityCorrelation {
+    /// Spearman's rho over the shared concepts (NaN when undefined)
+    pub spearman: f64,
+    pub shared: usize,
+}
+
+/// Replacement rates of every concept in (language, concept, cognate_class)
+/// judgments, most stable first (ties by concept)
+pub fn concept_replacement_rates(
+    tree: &Tree,
+    judgments: &[(String, String, String)],
+) -> Result<Vec<ConceptRate>, String> {
+    let leaf: HashMap<String, usize> = tree
+        .leaves()
+        .into_iter()
+        .map(|node| (tree.node_name(node), node))
+        .collect();
+    // concept -> leaf -> classes
+    let mut characters: BTreeMap<&str, BTreeMap<usize, BTreeSet<&str>>> = BTreeMap::new();
+    for (language, concept, class) in judgments {
+        let &node = leaf
+            .get(language)
+            .ok_or_else(|| format!("language '{}' is not a leaf of the tree", language))?;
+        characters
+            .entry(concept)
+            .or_default()
+            .entry(node)
+            .or_default()
+            .insert(class);
+    }
+
+    let order = tree.preorder();
+    let has_lengths = tree.nodes.iter().any(|node| node.length > 0.0);
+    let characters: Vec<_> = characters.into_iter().collect();
+    let mut rates: Vec<ConceptRate> = characters
+        .par_iter()
+        .map(|(concept, states)| {
+            let replacements = fitch_changes(tree, &order, states);
+            let tree_length = spanned_length(tree, &order, states, has_lengths);
+            let classes: BTreeSet<&str> = states.values().flatten().copied().collect();
+            ConceptRate {
+                concept: concept.to_string(),
+                languages: states.len(),
+                cognate_classes: classes.len(),
+                replacements,
+                tree_length,
+                rate: if tree_length > 0.0 {
+                    replacements as f64 / tree_length
+                } else {
+                    0.0
+                },
+                rank: 0,
+            }
+        })
+        .collect();
+    // Stable, so ties stay in concept order
+    rates.sort_by(|a, b| a.rate.total_cmp(&b.rate));
+    for (i, rate) in rates.iter_mut().enumerate() {
+        rate.rank = i + 1;
+    }
+    Ok(rates)
+}
+
+/// Fitch changes of a character with state sets at some leaves; other
+/// leaves fit any state
+fn fitch_changes(tree: &Tree, order: &[usize], states: &BTreeMap<usize, BTreeSet<&str>>) -> usize {
+    // None = any state
+    let mut sets: Vec<Option<BTreeSet<&str>>> = vec![None; tree.nodes.len()];
+    let mut changes = 0;
+    for &node in order.iter().rev() {
+        let children = &tree.nodes[node].children;
+        if children.is_empty() {
+            sets[node] = states.get(&node).cloned();
+            continue;
+        }
+        let mut set: Option<BTreeSet<&str>> = None;
+        for &child in children {
+            set = match (set, sets[child].take()) {
+                (None, other) | (other, None) => other,
+                (Some(a), Some(b)) => {
+                    let common: BTreeSet<&str> = a.intersection(&b).copied().collect();
+                    if common.is_empty() {
+                        changes += 1;
+                        Some(a.union(&b).copied().collect())
+                    } else {
+                        Some(common)
+                    }
+                }
+            };
+        }
+        sets[node] = set;
+    }
+    changes
+}
+
+/// Length of the branches connecting the leaves in `states`
+fn spanned_length(
+    tree: &Tree,
+    order: &[usize],
+    states: &BTreeMap<usize, BTreeSet<&str>>,
+    has_lengths: bool,
+) -> f64 {
+    let mut below = vec![0; tree.nodes.len()];
+    let mut length = 0.0;
+    for &node in order.iter().rev() {
+        if states.contains_key(&node) {
+            below[node] += 1;
+        }
+        let Some(parent) = tree.nodes[node].parent else {
+            continue;
+        };
+        if below[node] > 0 && below[node] < states.len() {
+            length += if has_lengths {
+                tree.nodes[node].length
+            } else {
+                1.0
+            };
+        }
+        below[parent] += below[node];
+    }
+    length
+}
+
+/// Reference ranks of `concepts` on a bundled list, for concepts matching
+/// one of its glosses
+pub fn list_ranks<'a>(
+    list: ConceptList,
+    concepts: impl IntoIterator<Item = &'a str>,
+) -> HashMap<String, usize> {
+    let ranks = list.ranks();
+    concepts
+        .into_iter()
+        .filter_map(|concept| {
+            let canonical = list.canonical(concept)?;
+            Some((concept.to_string(), ranks[canonical]))
+        })
+        .collect()
+}
+
+/// Spearman's rho between the empirical rates and `reference` ranks (1 =
+/// most stable) over the concepts in both
+pub fn stability_correlation(
+    rates: &[ConceptRate],
+    reference: &HashMap<String, usize>,
+) -> StabilityCorrelation {
+    let (empirical, published): (Vec<f64>, Vec<f64>) = rates
+        .iter()
+        .filter_map(|rate| Some((rate.rate, *reference.get(&rate.concept)? as f64)))
+        .unzip();
+    StabilityCorrelation {
+        spearman: pearson(&average_ranks(&empirical), &average_ranks(&published)),
+        shared: empirical.len(),
+    }
+}
+
+/// 1-based ranks, ties sharing their average rank
+fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let (mean_x, mean_y) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+    let (mut xy, mut xx, mut yy) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mean_x, b - mean_y);
+        xy += dx * dy;
+        xx += dx * dx;
+        yy += dy * dy;
+    }
+    xy / (xx * yy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn judgments(rows: &[(&str, &str, &str)]) -> Vec<(String, String, String)> {
+        rows.iter()
+            .map(|(l, c, k)| (l.to_string(), c.to_string(), k.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_replacement_rates() {
+        let tree = Tree::from_newick("(((a,b)ab,c)abc,(d,e)de)root;").unwrap();
+        let data = judgments(&[
+            ("a", "hand", "1"),
+            ("b", "hand", "1"),
+            ("d", "hand", "1"),
+            ("a", "dog", "1"),
+            ("b", "dog", "1"),
+            ("c", "dog", "2"),
+            ("d", "dog", "3"),
+            ("e", "dog", "3"),
+            ("a", "bird", "1"),
+            ("b", "bird", "2"),
+            ("d", "bird", "1"),
+            ("e", "bird", "1"),
+            ("e", "bird", "4"),
+        ]);
+        let rates = concept_replacement_rates(&tree, &data).unwrap();
+        let summary: Vec<(&str, usize, f64)> = rates
+            .iter()
+            .map(|r| (r.concept.as_str(), r.replacements, r.tree_length))
+            .collect();
+        // c has no "bird", so its branch is outside the span
+        assert_eq!(
+            summary,
+            [("hand", 0, 6.0), ("bird", 1, 7.0), ("dog", 2, 8.0)]
+        );
+        assert_eq!(rates[2].rank, 3);
+        assert_eq!(rates[1].cognate_classes, 3);
+        assert_eq!(rates[2].rate, 0.25);
+
+        let reference = HashMap::from([
+            ("hand".to_string(), 1),
+            ("dog".to_string(), 2),
+            ("bird".to_string(), 3),
+        ]);
+        let correlation = stability_correlation(&rates, &reference);
+        assert_eq!(correlation.shared, 3);
+        assert!((correlation.spearman - 0.5).abs() < 1e-12);
+
+        let unknown = judgments(&[("x", "hand", "1")]);
+        assert!(concept_replacement_rates(&tree, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_list_ranks() {
+        let ranks = list_ranks(ConceptList::LeipzigJakarta, ["Fire", "to go", "spaceship"]);
+        assert_eq!(ranks.len(), 2);
+        assert_eq!(ranks["Fire"], 1);
+        assert_eq!(average_ranks(&[0.3, 0.1, 0.3]), [2.5, 1.0, 2.5]);
+    }
+}
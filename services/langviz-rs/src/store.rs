@@ -0,0 +1,342 @@
+//! SQLite result store (cargo feature `sqlite`).
+//!
+//! Writes edges, cognate sets, alignments and sound correspondences into a
+//! SQLite database so a web backend can query results with SQL instead of
+//! loading them into memory. The schema is `SCHEMA` below; its version is
+//! kept in `PRAGMA user_version`. Every table has a nullable `run` column
+//! labelling the computation that produced the rows (a language pair, a
+//! threshold sweep step, ...). Each write is one transaction.
+
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use crate::phonetic::SoundCorrespondence;
+use crate::types::{Alignment, CognateSet, ProvenanceEdge};
+
+/// Current store schema version
+pub const STORE_SCHEMA_VERSION: i64 = 1;
+
+/// Store schema. Aligned segments are space-separated with `-` for gaps;
+/// operations are one letter per column (M match, S substitute, I insert,
+/// D delete).
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS edges (
+    id          INTEGER PRIMARY KEY,
+    run         TEXT,
+    source      TEXT NOT NULL,
+    target      TEXT NOT NULL,
+    weight      REAL NOT NULL,
+    provenance  TEXT
+);
+CREATE INDEX IF NOT EXISTS edges_source ON edges (source);
+CREATE INDEX IF NOT EXISTS edges_target ON edges (target);
+
+CREATE TABLE IF NOT EXISTS cognate_sets (
+    id          INTEGER PRIMARY KEY,
+    run         TEXT,
+    set_index   INTEGER NOT NULL,
+    size        INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS cognate_set_members (
+    set_id      INTEGER NOT NULL REFERENCES cognate_sets (id) ON DELETE CASCADE,
+    member      TEXT NOT NULL,
+    PRIMARY KEY (set_id, member)
+);
+CREATE INDEX IF NOT EXISTS cognate_set_members_member ON cognate_set_members (member);
+
+CREATE TABLE IF NOT EXISTS alignments (
+    id          INTEGER PRIMARY KEY,
+    run         TEXT,
+    word_a      TEXT NOT NULL,
+    word_b      TEXT NOT NULL,
+    sequence_a  TEXT NOT NULL,
+    sequence_b  TEXT NOT NULL,
+    operations  TEXT NOT NULL,
+    cost        REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS alignments_words ON alignments (word_a, word_b);
+
+CREATE TABLE IF NOT EXISTS correspondences (
+    id          INTEGER PRIMARY KEY,
+    run         TEXT,
+    segment_a   TEXT NOT NULL,
+    segment_b   TEXT NOT NULL,
+    count       INTEGER NOT NULL,
+    expected    REAL NOT NULL,
+    pmi         REAL NOT NULL,
+    chi_square  REAL NOT NULL,
+    initial     INTEGER NOT NULL,
+    medial      INTEGER NOT NULL,
+    final       INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS correspondences_segments ON correspondences (segment_a, segment_b);
+";
+
+/// Tables written by the store, in schema order
+pub const TABLES: [&str; 5] = [
+    "edges",
+    "cognate_sets",
+    "cognate_set_members",
+    "alignments",
+    "correspondences",
+];
+
+/// Handle on a result database
+pub struct ResultStore {
+    connection: Connection,
+}
+
+fn sql_error(error: rusqlite::Error) -> String {
+    format!("SQLite error: {}", error)
+}
+
+impl ResultStore {
+    /// Open (creating if needed) the database at `path`
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Self::init(Connection::open(path).map_err(sql_error)?)
+    }
+
+    /// Store in a private in-memory database
+    pub fn in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(sql_error)?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, String> {
+        let version: i64 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(sql_error)?;
+        if version > STORE_SCHEMA_VERSION {
+            return Err(format!(
+                "result store schema version {} is newer than supported ({})",
+                version, STORE_SCHEMA_VERSION
+            ));
+        }
+        connection
+            .execute_batch(SCHEMA)
+            .and_then(|_| connection.pragma_update(None, "user_version", STORE_SCHEMA_VERSION))
+            .and_then(|_| connection.pragma_update(None, "foreign_keys", true))
+            .map_err(sql_error)?;
+        Ok(Self { connection })
+    }
+
+    /// Insert (source, target, weight, provenance) edges
+    pub fn write_edges(
+        &mut self,
+        edges: &[ProvenanceEdge],
+        run: Option<&str>,
+    ) -> Result<usize, String> {
+        let tx = self.connection.transaction().map_err(sql_error)?;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO edges (run, source, target, weight, provenance) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(sql_error)?;
+            for (source, target, weight, provenance) in edges {
+                insert
+                    .execute(params![run, source, target, weight, provenance])
+                    .map_err(sql_error)?;
+            }
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(edges.len())
+    }
+
+    /// Insert cognate sets and their members; `set_index` is the set's ID
+    /// within the run
+    pub fn write_cognate_sets(
+        &mut self,
+        sets: &[CognateSet],
+        run: Option<&str>,
+    ) -> Result<usize, String> {
+        let tx = self.connection.transaction().map_err(sql_error)?;
+        {
+            let mut insert_set = tx
+                .prepare("INSERT INTO cognate_sets (run, set_index, size) VALUES (?1, ?2, ?3)")
+                .map_err(sql_error)?;
+            let mut insert_member = tx
+                .prepare(
+                    "INSERT OR IGNORE INTO cognate_set_members (set_id, member) VALUES (?1, ?2)",
+                )
+                .map_err(sql_error)?;
+            for set in sets {
+                insert_set
+                    .execute(params![run, set.id as i64, set.size as i64])
+                    .map_err(sql_error)?;
+                let set_id = tx.last_insert_rowid();
+                for member in &set.members {
+                    insert_member
+                        .execute(params![set_id, member])
+                        .map_err(sql_error)?;
+                }
+            }
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(sets.len())
+    }
+
+    /// Insert (word_a, word_b, alignment) rows
+    pub fn write_alignments(
+        &mut self,
+        alignments: &[(String, String, Alignment)],
+        run: Option<&str>,
+    ) -> Result<usize, String> {
+        let tx = self.connection.transaction().map_err(sql_error)?;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO alignments \
+                     (run, word_a, word_b, sequence_a, sequence_b, operations, cost) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )
+                .map_err(sql_error)?;
+            for (word_a, word_b, alignment) in alignments {
+                let operations: String = alignment.operations.iter().map(|op| op.code()).collect();
+                insert
+                    .execute(params![
+                        run,
+                        word_a,
+                        word_b,
+                        alignment.sequence_a.join(" "),
+                        alignment.sequence_b.join(" "),
+                        operations,
+                        alignment.cost,
+                    ])
+                    .map_err(sql_error)?;
+            }
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(alignments.len())
+    }
+
+    /// Insert sound correspondences with their statistics
+    pub fn write_correspondences(
+        &mut self,
+        correspondences: &[SoundCorrespondence],
+        run: Option<&str>,
+    ) -> Result<usize, String> {
+        let tx = self.connection.transaction().map_err(sql_error)?;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO correspondences (run, segment_a, segment_b, count, expected, \
+                     pmi, chi_square, initial, medial, final) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                )
+                .map_err(sql_error)?;
+            for c in correspondences {
+                insert
+                    .execute(params![
+                        run,
+                        c.segment_a,
+                        c.segment_b,
+                        c.count as i64,
+                        c.expected,
+                        c.pmi,
+                        c.chi_square,
+                        c.positions[0] as i64,
+                        c.positions[1] as i64,
+                        c.positions[2] as i64,
+                    ])
+                    .map_err(sql_error)?;
+            }
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(correspondences.len())
+    }
+
+    /// Delete the rows of `run` (or of every run) from every table
+    pub fn clear(&mut self, run: Option<&str>) -> Result<(), String> {
+        let tx = self.connection.transaction().map_err(sql_error)?;
+        for table in TABLES.iter().filter(|&&t| t != "cognate_set_members") {
+            match run {
+                Some(run) => tx.execute(&format!("DELETE FROM {} WHERE run = ?1", table), [run]),
+                None => tx.execute(&format!("DELETE FROM {}", table), []),
+            }
+            .map_err(sql_error)?;
+        }
+        tx.commit().map_err(sql_error)
+    }
+
+    /// Row count per table, in `TABLES` order
+    pub fn counts(&self) -> Result<Vec<(&'static str, usize)>, String> {
+        TABLES
+            .iter()
+            .map(|&table| {
+                self.connection
+                    .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                    .map(|count| (table, count as usize))
+                    .map_err(sql_error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phonetic::{contextual_sound_correspondences, dtw_align};
+
+    #[test]
+    fn test_write_and_query() {
+        let mut store = ResultStore::in_memory().unwrap();
+        let edges = vec![
+            (
+                "a".to_string(),
+                "b".to_string(),
+                0.9,
+                Some("manual".to_string()),
+            ),
+            ("b".to_string(), "c".to_string(), 0.7, None),
+        ];
+        store.write_edges(&edges, Some("lat-eng")).unwrap();
+        let sets = vec![
+            CognateSet::new(0, vec!["a".into(), "b".into(), "c".into()]),
+            CognateSet::new(1, vec!["d".into()]),
+        ];
+        store.write_cognate_sets(&sets, Some("lat-eng")).unwrap();
+        let alignment = dtw_align("pater", "fater");
+        store
+            .write_alignments(&[("a".into(), "b".into(), alignment.clone())], None)
+            .unwrap();
+        let correspondences = contextual_sound_correspondences(&[alignment]);
+        store
+            .write_correspondences(&correspondences.correspondences, None)
+            .unwrap();
+
+        let counts = store.counts().unwrap();
+        assert_eq!(counts[0], ("edges", 2));
+        assert_eq!(counts[2], ("cognate_set_members", 4));
+        assert_eq!(counts[4], ("correspondences", 1));
+
+        let (sequence, operations): (String, String) = store
+            .connection
+            .query_row("SELECT sequence_b, operations FROM alignments", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(
+            (sequence.as_str(), operations.as_str()),
+            ("f a t e r", "SMMMM")
+        );
+        let members: i64 = store
+            .connection
+            .query_row(
+                "SELECT COUNT(*) FROM cognate_set_members m JOIN cognate_sets s \
+                 ON m.set_id = s.id WHERE s.set_index = 0 AND s.run = 'lat-eng'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(members, 3);
+
+        store.clear(Some("lat-eng")).unwrap();
+        let counts = store.counts().unwrap();
+        assert_eq!(counts[0], ("edges", 0));
+        assert_eq!(counts[2], ("cognate_set_members", 0));
+        assert_eq!(counts[3], ("alignments", 1));
+    }
+}
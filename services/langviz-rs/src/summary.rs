@@ -0,0 +1,184 @@
+//! Cluster-level summary graphs: relationships between cognate sets.
+//!
+//! `cluster_graph` contracts every cluster of a clustering into one node
+//! and sums the weight of member edges between each pair of clusters (the
+//! inter-cluster similarity mass) into one edge. The summary graph is an
+//! ordinary `CognateGraph`, so it can be laid out, exported or clustered
+//! again; `expand_clusters` maps groups of its nodes back to the member IDs
+//! they stand for.
+
+use ahash::AHashMap;
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+
+use crate::error::LangVizError;
+use crate::graph::CognateGraph;
+use crate::types::AttrValue;
+
+/// `cluster_0`, `cluster_1`, ... for clusterings without names
+pub fn default_cluster_names(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("cluster_{}", i)).collect()
+}
+
+fn check_names(clusters: &[Vec<String>], names: &[String]) -> Result<(), LangVizError> {
+    if clusters.len() == names.len() {
+        Ok(())
+    } else {
+        Err(LangVizError::DimensionMismatch {
+            what: "cluster names".to_string(),
+            expected: format!("{} names", clusters.len()),
+            actual: format!("{} names", names.len()),
+        })
+    }
+}
+
+impl CognateGraph {
+    /// Summary graph with one node per cluster, named by `names`, and edges
+    /// weighted by the summed weight of member edges between two clusters.
+    /// Cluster nodes carry `size` and `internal_weight` attributes. Nodes in
+    /// no cluster are left out; a node in several clusters counts for the
+    /// first, and clusters sharing a name are merged.
+    pub fn cluster_graph(
+        &self,
+        clusters: &[Vec<String>],
+        names: &[String],
+    ) -> Result<CognateGraph, LangVizError> {
+        check_names(clusters, names)?;
+        let mut cluster_of: AHashMap<usize, usize> = AHashMap::new();
+        for (c, members) in clusters.iter().enumerate() {
+            for id in members {
+                let idx = self
+                    .node_map
+                    .get(id)
+                    .ok_or_else(|| LangVizError::UnknownEntryId(id.clone()))?;
+                cluster_of.entry(idx.index()).or_insert(c);
+            }
+        }
+
+        let mut internal = vec![0.0; clusters.len()];
+        let mut mass: AHashMap<(usize, usize), f64> = AHashMap::new();
+        for edge in self.graph.edge_references() {
+            let (Some(&a), Some(&b)) = (
+                cluster_of.get(&edge.source().index()),
+                cluster_of.get(&edge.target().index()),
+            ) else {
+                continue;
+            };
+            if a == b {
+                internal[a] += *edge.weight();
+            } else {
+                *mass.entry((a.min(b), a.max(b))).or_insert(0.0) += *edge.weight();
+            }
+        }
+
+        let mut summary = CognateGraph::new();
+        let mut attributes: HashMap<String, HashMap<String, AttrValue>> = HashMap::new();
+        for (c, name) in names.iter().enumerate() {
+            summary.get_or_create_node(name.clone());
+            let attrs = attributes.entry(name.clone()).or_default();
+            let mut add = |key: &str, value: f64| {
+                let total = attrs.get(key).and_then(AttrValue::as_number).unwrap_or(0.0);
+                attrs.insert(key.to_string(), AttrValue::Number(total + value));
+            };
+            add("size", clusters[c].len() as f64);
+            add("internal_weight", internal[c]);
+        }
+        let mut pairs: Vec<((usize, usize), f64)> = mass.into_iter().collect();
+        pairs.sort_by_key(|&(pair, _)| pair);
+        for ((a, b), weight) in pairs {
+            summary.add_edge(names[a].clone(), names[b].clone(), weight);
+        }
+        summary.set_node_attributes(attributes);
+        Ok(summary)
+    }
+}
+
+/// Member IDs of each group of summary-graph nodes (cluster names), e.g.
+/// communities detected on the summary graph
+pub fn expand_clusters(
+    groups: &[Vec<String>],
+    clusters: &[Vec<String>],
+    names: &[String],
+) -> Result<Vec<Vec<String>>, LangVizError> {
+    check_names(clusters, names)?;
+    let mut members: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (name, cluster) in names.iter().zip(clusters) {
+        members
+            .entry(name.as_str())
+            .or_default()
+            .extend(cluster.iter().map(String::as_str));
+    }
+    groups
+        .iter()
+        .map(|group| {
+            let mut expanded = Vec::new();
+            for name in group {
+                let cluster = members
+                    .get(name.as_str())
+                    .ok_or_else(|| LangVizError::UnknownEntryId(name.clone()))?;
+                expanded.extend(cluster.iter().map(|id| id.to_string()));
+            }
+            Ok(expanded)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn cluster(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cluster_graph_round_trip() {
+        let edges = [
+            ("a", "b", 0.9),
+            ("c", "d", 0.8),
+            ("a", "c", 0.3),
+            ("b", "d", 0.2),
+            ("d", "e", 0.5),
+            ("e", "x", 0.7),
+        ];
+        let graph = CognateGraph::from_edges(
+            edges
+                .iter()
+                .map(|&(s, t, w)| SimilarityEdge::new(s.to_string(), t.to_string(), w))
+                .collect(),
+            0.0,
+        );
+        let clusters = [cluster(&["a", "b"]), cluster(&["c", "d"]), cluster(&["e"])];
+        let names = default_cluster_names(clusters.len());
+        let summary = graph.cluster_graph(&clusters, &names).unwrap();
+
+        let mut edges = summary.edge_list();
+        edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        assert_eq!(edges.len(), 2);
+        assert_eq!(
+            (edges[0].0.as_str(), edges[0].1.as_str()),
+            ("cluster_0", "cluster_1")
+        );
+        assert!((edges[0].2 - 0.5).abs() < 1e-12);
+        assert_eq!(edges[1].2, 0.5);
+        let size = summary.node_attribute("cluster_1", "size");
+        assert_eq!(size, Some(&AttrValue::Number(2.0)));
+        let internal = summary.node_attribute("cluster_0", "internal_weight");
+        assert_eq!(internal, Some(&AttrValue::Number(0.9)));
+
+        let groups = [
+            cluster(&["cluster_0", "cluster_2"]),
+            cluster(&["cluster_1"]),
+        ];
+        let expanded = expand_clusters(&groups, &clusters, &names).unwrap();
+        assert_eq!(expanded, [cluster(&["a", "b", "e"]), cluster(&["c", "d"])]);
+
+        let unknown = graph.cluster_graph(&[cluster(&["zz"])], &names[..1]);
+        assert_eq!(
+            unknown.err(),
+            Some(LangVizError::UnknownEntryId("zz".to_string()))
+        );
+        assert!(graph.cluster_graph(&clusters, &names[..2]).is_err());
+    }
+}
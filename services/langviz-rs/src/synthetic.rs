@@ -0,0 +1,478 @@
+//! Synthetic wordlists with known cognate classes for benchmarking.
+//!
+//! Random proto-forms are evolved into each daughter language by a set of
+//! regular sound changes drawn per language. Along the way a concept can be
+//! replaced by an unrelated new root (a new cognate class) or borrowed from
+//! another language, which copies the donor's surface form and class. The
+//! true cognate class of every entry is kept as ground truth, and
+//! `similarity_edges` turns the wordlist into the weighted graph the
+//! detection pipeline consumes.
+//!
+//! `simulate_tree` instead evolves the vocabulary down a given language
+//! tree, with replacements and sound changes accumulating along each branch
+//! in proportion to its length, so related languages share history.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::phonetic::phonetic_distance;
+use crate::tree::Tree;
+
+const CONSONANTS: &[&str] = &["p", "t", "k", "b", "d", "g", "m", "n", "s", "l", "r", "w"];
+const VOWELS: &[&str] = &["a", "e", "i", "o", "u"];
+
+/// Default pool of (segment, replacement) sound changes
+pub const DEFAULT_SOUND_CHANGES: &[(&str, &str)] = &[
+    ("p", "f"),
+    ("t", "θ"),
+    ("k", "h"),
+    ("b", "v"),
+    ("d", "ð"),
+    ("g", "ɣ"),
+    ("s", "h"),
+    ("w", "v"),
+    ("a", "o"),
+    ("e", "i"),
+    ("o", "u"),
+    ("u", "y"),
+];
+
+/// Generator settings
+#[derive(Debug, Clone)]
+pub struct SyntheticParams {
+    pub num_languages: usize,
+    pub num_concepts: usize,
+    /// Syllables per proto-form (CV), inclusive range
+    pub min_syllables: usize,
+    pub max_syllables: usize,
+    /// Pool of (segment, replacement) changes each language draws from
+    pub sound_changes: Vec<(String, String)>,
+    /// Changes applied per language, in the drawn order
+    pub changes_per_language: usize,
+    /// Probability that a language replaces a concept with a new root
+    pub replacement_rate: f64,
+    /// Probability that a language borrows a concept from another language
+    pub borrowing_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for SyntheticParams {
+    fn default() -> Self {
+        Self {
+            num_languages: 5,
+            num_concepts: 100,
+            min_syllables: 2,
+            max_syllables: 3,
+            sound_changes: DEFAULT_SOUND_CHANGES
+                .iter()
+                .map(|&(a, b)| (a.to_string(), b.to_string()))
+                .collect(),
+            changes_per_language: 3,
+            replacement_rate: 0.1,
+            borrowing_rate: 0.05,
+            seed: 0,
+        }
+    }
+}
+
+/// One generated word with its ground truth
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticEntry {
+    /// `{language}_{concept}`
+    pub id: String,
+    pub language: String,
+    pub concept: String,
+    pub ipa: String,
+    /// True cognate class (`{concept}:{root}`)
+    pub cognate_class: String,
+    /// Copied from another language rather than inherited
+    pub borrowed: bool,
+}
+
+/// Generated wordlist plus the proto-forms it descends from
+#[derive(Debug, Clone)]
+pub struct SyntheticDataset {
+    /// Proto-form per concept
+    pub proto_forms: Vec<String>,
+    /// Sound changes applied in each language, in order
+    pub language_changes: Vec<(String, Vec<(String, String)>)>,
+    /// Entries grouped by language, then concept
+    pub entries: Vec<SyntheticEntry>,
+}
+
+fn random_root(rng: &mut StdRng, min_syllables: usize, max_syllables: usize) -> Vec<String> {
+    let syllables = rng.gen_range(min_syllables..=max_syllables.max(min_syllables));
+    let mut segments = Vec::with_capacity(2 * syllables);
+    for _ in 0..syllables {
+        segments.push(CONSONANTS.choose(rng).unwrap().to_string());
+        segments.push(VOWELS.choose(rng).unwrap().to_string());
+    }
+    segments
+}
+
+/// Apply regular sound changes in order (every occurrence of a segment)
+fn evolve(segments: &[String], changes: &[(String, String)]) -> String {
+    segments
+        .iter()
+        .map(|segment| {
+            changes
+                .iter()
+                .fold(segment.as_str(), |current, (from, to)| {
+                    if current == from {
+                        to.as_str()
+                    } else {
+                        current
+                    }
+                })
+        })
+        .collect()
+}
+
+/// Generate a wordlist with known cognate classes
+pub fn generate_wordlist(params: &SyntheticParams) -> SyntheticDataset {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let languages: Vec<String> = (1..=params.num_languages)
+        .map(|i| format!("L{:02}", i))
+        .collect();
+    let concepts: Vec<String> = (1..=params.num_concepts)
+        .map(|i| format!("C{:03}", i))
+        .collect();
+
+    let roots: Vec<Vec<String>> = (0..params.num_concepts)
+        .map(|_| random_root(&mut rng, params.min_syllables, params.max_syllables))
+        .collect();
+    let language_changes: Vec<Vec<(String, String)>> = languages
+        .iter()
+        .map(|_| {
+            params
+                .sound_changes
+                .choose_multiple(&mut rng, params.changes_per_language)
+                .cloned()
+                .collect()
+        })
+        .collect();
+
+    // Inheritance with occasional lexical replacement; replacement roots are
+    // numbered after the proto-root (root 0)
+    let mut forms: Vec<Vec<(String, String)>> = Vec::with_capacity(languages.len());
+    let mut next_root = vec![1usize; params.num_concepts];
+    for changes in &language_changes {
+        let row = (0..params.num_concepts)
+            .map(|c| {
+                if rng.gen_bool(params.replacement_rate.clamp(0.0, 1.0)) {
+                    let root = random_root(&mut rng, params.min_syllables, params.max_syllables);
+                    let class = format!("{}:{}", concepts[c], next_root[c]);
+                    next_root[c] += 1;
+                    (evolve(&root, changes), class)
+                } else {
+                    (evolve(&roots[c], changes), format!("{}:0", concepts[c]))
+                }
+            })
+            .collect();
+        forms.push(row);
+    }
+
+    // Borrowing copies the donor's inherited form and class
+    let mut entries = Vec::with_capacity(languages.len() * params.num_concepts);
+    for (l, language) in languages.iter().enumerate() {
+        for (c, concept) in concepts.iter().enumerate() {
+            let mut borrowed = false;
+            let (mut ipa, mut class) = forms[l][c].clone();
+            if languages.len() > 1 && rng.gen_bool(params.borrowing_rate.clamp(0.0, 1.0)) {
+                let donor = (l + rng.gen_range(1..languages.len())) % languages.len();
+                (ipa, class) = forms[donor][c].clone();
+                borrowed = true;
+            }
+            entries.push(SyntheticEntry {
+                id: format!("{}_{}", language, concept),
+                language: language.clone(),
+                concept: concept.clone(),
+                ipa,
+                cognate_class: class,
+                borrowed,
+            });
+        }
+    }
+
+    SyntheticDataset {
+        proto_forms: roots.iter().map(|root| root.concat()).collect(),
+        language_changes: languages.into_iter().zip(language_changes).collect(),
+        entries,
+    }
+}
+
+/// Settings for evolving a vocabulary down a language tree
+#[derive(Debug, Clone)]
+pub struct TreeSimulationParams {
+    pub num_concepts: usize,
+    /// Syllables per root (CV), inclusive range
+    pub min_syllables: usize,
+    pub max_syllables: usize,
+    /// Pool of (segment, replacement) changes drawn along branches
+    pub sound_changes: Vec<(String, String)>,
+    /// Expected sound changes per unit of branch length
+    pub sound_change_rate: f64,
+    /// Expected replacements of a concept per unit of branch length
+    pub replacement_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for TreeSimulationParams {
+    fn default() -> Self {
+        Self {
+            num_concepts: 100,
+            min_syllables: 2,
+            max_syllables: 3,
+            sound_changes: SyntheticParams::default().sound_changes,
+            sound_change_rate: 1.0,
+            replacement_rate: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+/// Vocabulary of one node: segments and root number per concept, plus the
+/// sound changes accumulated since the root
+#[derive(Clone)]
+struct Lineage {
+    words: Vec<(Vec<String>, usize)>,
+    changes: Vec<(String, String)>,
+}
+
+/// Poisson sample (Knuth's method; rates here are small)
+fn poisson(rng: &mut StdRng, lambda: f64) -> usize {
+    let limit = (-lambda.max(0.0)).exp();
+    let mut product: f64 = rng.gen();
+    let mut count = 0;
+    while product > limit {
+        product *= rng.gen::<f64>();
+        count += 1;
+    }
+    count
+}
+
+/// Evolve random proto-forms from the root of `tree` to its leaves. Each
+/// branch of length `t` replaces a concept with probability
+/// `1 - exp(-replacement_rate * t)` and draws a Poisson number of sound
+/// changes with mean `sound_change_rate * t`, applied to every word below.
+/// Leaves become the languages (unnamed ones are called `leaf{index}`).
+pub fn simulate_tree(tree: &Tree, params: &TreeSimulationParams) -> SyntheticDataset {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let concepts: Vec<String> = (1..=params.num_concepts)
+        .map(|i| format!("C{:03}", i))
+        .collect();
+    let roots: Vec<Vec<String>> = (0..params.num_concepts)
+        .map(|_| random_root(&mut rng, params.min_syllables, params.max_syllables))
+        .collect();
+
+    let mut lineages: Vec<Option<Lineage>> = vec![None; tree.nodes.len()];
+    lineages[tree.root] = Some(Lineage {
+        words: roots.iter().map(|root| (root.clone(), 0)).collect(),
+        changes: Vec::new(),
+    });
+    let mut next_root = vec![1usize; params.num_concepts];
+
+    for node in tree.preorder() {
+        let Some(parent) = tree.nodes[node].parent else {
+            continue;
+        };
+        let mut lineage = lineages[parent].clone().unwrap();
+        let length = tree.nodes[node].length.max(0.0);
+
+        let replace = 1.0 - (-params.replacement_rate.max(0.0) * length).exp();
+        for (c, word) in lineage.words.iter_mut().enumerate() {
+            if rng.gen_bool(replace) {
+                let root = random_root(&mut rng, params.min_syllables, params.max_syllables);
+                *word = (root, next_root[c]);
+                next_root[c] += 1;
+            }
+        }
+
+        let count = poisson(&mut rng, params.sound_change_rate * length);
+        for change in params.sound_changes.choose_multiple(&mut rng, count) {
+            for (segments, _) in lineage.words.iter_mut() {
+                for segment in segments.iter_mut().filter(|s| **s == change.0) {
+                    *segment = change.1.clone();
+                }
+            }
+            lineage.changes.push(change.clone());
+        }
+        lineages[node] = Some(lineage);
+    }
+
+    let mut entries = Vec::new();
+    let mut language_changes = Vec::new();
+    for leaf in tree.leaves() {
+        let language = tree.node_name(leaf);
+        let lineage = lineages[leaf].take().unwrap();
+        for (c, (segments, root)) in lineage.words.into_iter().enumerate() {
+            entries.push(SyntheticEntry {
+                id: format!("{}_{}", language, concepts[c]),
+                language: language.clone(),
+                concept: concepts[c].clone(),
+                ipa: segments.concat(),
+                cognate_class: format!("{}:{}", concepts[c], root),
+                borrowed: false,
+            });
+        }
+        language_changes.push((language, lineage.changes));
+    }
+
+    SyntheticDataset {
+        proto_forms: roots.iter().map(|root| root.concat()).collect(),
+        language_changes,
+        entries,
+    }
+}
+
+impl SyntheticDataset {
+    /// Phonetic similarity (as in `compute_similarity_matrix`) between every
+    /// pair of entries sharing a concept, as (id, id, weight) edges
+    pub fn similarity_edges(&self) -> Vec<(String, String, f64)> {
+        let pairs: Vec<(&SyntheticEntry, &SyntheticEntry)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| {
+                self.entries[i + 1..]
+                    .iter()
+                    .filter(move |b| b.concept == a.concept)
+                    .map(move |b| (a, b))
+            })
+            .collect();
+
+        pairs
+            .into_par_iter()
+            .map(|(a, b)| {
+                (
+                    a.id.clone(),
+                    b.id.clone(),
+                    phonetic_distance(&a.ipa, &b.ipa),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phonetic::segment_ipa;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_generate_wordlist_shape() {
+        let params = SyntheticParams {
+            num_languages: 4,
+            num_concepts: 20,
+            seed: 7,
+            ..Default::default()
+        };
+        let dataset = generate_wordlist(&params);
+        assert_eq!(dataset.entries.len(), 80);
+        assert_eq!(dataset.proto_forms.len(), 20);
+        assert!(dataset
+            .proto_forms
+            .iter()
+            .all(|form| (4..=6).contains(&segment_ipa(form).len())));
+        assert!(dataset.language_changes.iter().all(|(_, c)| c.len() == 3));
+
+        // Deterministic for a seed
+        let again = generate_wordlist(&params);
+        assert_eq!(again.entries, dataset.entries);
+    }
+
+    #[test]
+    fn test_regular_changes_without_noise() {
+        let params = SyntheticParams {
+            num_languages: 3,
+            num_concepts: 10,
+            sound_changes: vec![("p".into(), "f".into())],
+            changes_per_language: 1,
+            replacement_rate: 0.0,
+            borrowing_rate: 0.0,
+            ..Default::default()
+        };
+        let dataset = generate_wordlist(&params);
+        for entry in &dataset.entries {
+            let concept: usize = entry.concept[1..].parse().unwrap();
+            let proto = &dataset.proto_forms[concept - 1];
+            assert_eq!(entry.ipa, proto.replace('p', "f"));
+            assert!(entry.cognate_class.ends_with(":0"));
+            assert!(!entry.borrowed);
+        }
+
+        // One edge per pair of languages per concept
+        let edges = dataset.similarity_edges();
+        assert_eq!(edges.len(), 30);
+        assert!(edges.iter().all(|e| e.2 == 1.0));
+    }
+
+    #[test]
+    fn test_simulate_tree() {
+        let tree = Tree::from_newick("((a:0.1,b:0.1):0.1,c:10);").unwrap();
+        let params = TreeSimulationParams {
+            num_concepts: 200,
+            seed: 11,
+            ..Default::default()
+        };
+        let dataset = simulate_tree(&tree, &params);
+        assert_eq!(dataset.entries.len(), 600);
+        let languages: Vec<&str> = dataset
+            .language_changes
+            .iter()
+            .map(|(l, _)| l.as_str())
+            .collect();
+        assert_eq!(languages, vec!["a", "b", "c"]);
+
+        // Close sisters share far more cognates than the distant leaf
+        let shared = |x: &str, y: &str| {
+            let class = |l: &str, c: usize| {
+                dataset
+                    .entries
+                    .iter()
+                    .find(|e| e.language == l && e.concept == format!("C{:03}", c))
+                    .unwrap()
+                    .cognate_class
+                    .clone()
+            };
+            (1..=200).filter(|&c| class(x, c) == class(y, c)).count()
+        };
+        assert!(shared("a", "b") > 150);
+        assert!(shared("a", "b") > shared("a", "c") + 50);
+
+        // Zero rates reproduce the proto-forms exactly
+        let frozen = simulate_tree(
+            &tree,
+            &TreeSimulationParams {
+                sound_change_rate: 0.0,
+                replacement_rate: 0.0,
+                ..params
+            },
+        );
+        for (i, entry) in frozen.entries.iter().enumerate() {
+            assert_eq!(entry.ipa, frozen.proto_forms[i % 200]);
+        }
+    }
+
+    #[test]
+    fn test_replacement_and_borrowing() {
+        let params = SyntheticParams {
+            num_concepts: 50,
+            replacement_rate: 0.5,
+            borrowing_rate: 0.3,
+            seed: 3,
+            ..Default::default()
+        };
+        let dataset = generate_wordlist(&params);
+        let classes: HashSet<&str> = dataset
+            .entries
+            .iter()
+            .map(|e| e.cognate_class.as_str())
+            .collect();
+        assert!(classes.len() > 50);
+        assert!(dataset.entries.iter().any(|e| e.borrowed));
+    }
+}
@@ -0,0 +1,236 @@
+//! Alignment-guided detection of likely transcription errors.
+//!
+//! Every cross-language member pair of every cognate set is aligned (DTW,
+//! as for the correspondence matrices) and the aligned columns are counted
+//! per ordered language pair. A member's alignment against a partner is
+//! scored by the mean surprisal of its segments given the partner's,
+//! `-ln P(segment | partner segment)`, estimated leave-one-out (the
+//! column's own count is removed) with additive smoothing. A form is only
+//! suspicious if it aligns improbably against *all* of its partners, so
+//! its score is the minimum over partners; a genuinely irregular partner
+//! does not incriminate the forms aligned against it.
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::phonetic::dtw_align;
+
+/// Additive smoothing for correspondence probabilities
+const SMOOTHING: f64 = 0.5;
+
+/// (score, partner, worst column) of a member's best alignment so far
+type BestAlignment = (f64, usize, (String, String));
+
+/// A member whose best alignment is still improbable
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptionFlag {
+    /// Cognate set and member index in the input
+    pub set: usize,
+    pub member: usize,
+    pub language: String,
+    pub ipa: String,
+    /// Mean surprisal (nats) of the best-scoring alignment
+    pub score: f64,
+    /// Member the best alignment is against
+    pub partner: usize,
+    /// Most improbable column of that alignment: (segment, partner segment)
+    pub worst_column: (String, String),
+}
+
+/// Aligned columns of members `a` and `b` of one set, `a`'s segment first
+struct AlignedPair {
+    set: usize,
+    a: usize,
+    b: usize,
+    columns: Vec<(String, String)>,
+}
+
+/// Column counts per ordered (given language, scored language) pair
+#[derive(Default)]
+struct Correspondences<'a> {
+    columns: HashMap<(&'a str, &'a str, &'a str, &'a str), u64>,
+    rows: HashMap<(&'a str, &'a str, &'a str), u64>,
+    /// Distinct scored-language segments (gap included) per language pair
+    vocabulary: HashMap<(&'a str, &'a str), HashSet<&'a str>>,
+}
+
+impl<'a> Correspondences<'a> {
+    fn add(&mut self, given_lang: &'a str, lang: &'a str, given: &'a str, segment: &'a str) {
+        *self
+            .columns
+            .entry((given_lang, lang, given, segment))
+            .or_insert(0) += 1;
+        *self.rows.entry((given_lang, lang, given)).or_insert(0) += 1;
+        self.vocabulary
+            .entry((given_lang, lang))
+            .or_default()
+            .insert(segment);
+    }
+
+    /// Leave-one-out surprisal of `segment` given the partner's `given`
+    fn surprisal(&self, given_lang: &str, lang: &str, given: &str, segment: &str) -> f64 {
+        let count = self.columns[&(given_lang, lang, given, segment)] - 1;
+        let row = self.rows[&(given_lang, lang, given)] - 1;
+        let vocabulary = self.vocabulary[&(given_lang, lang)].len() as f64;
+        let probability = (count as f64 + SMOOTHING) / (row as f64 + SMOOTHING * vocabulary);
+        -probability.ln()
+    }
+}
+
+/// Flag members of (language, ipa) cognate sets whose best alignment
+/// against another-language member scores at least `min_score` nats per
+/// column. Sorted by descending score.
+pub fn detect_transcription_errors(
+    cognate_sets: &[Vec<(String, String)>],
+    min_score: f64,
+) -> Vec<TranscriptionFlag> {
+    let pairs: Vec<(usize, usize, usize)> = cognate_sets
+        .iter()
+        .enumerate()
+        .flat_map(|(set, members)| {
+            (0..members.len()).flat_map(move |a| {
+                (a + 1..members.len())
+                    .filter(move |&b| members[a].0 != members[b].0)
+                    .map(move |b| (set, a, b))
+            })
+        })
+        .collect();
+
+    let aligned: Vec<AlignedPair> = pairs
+        .par_iter()
+        .map(|&(set, a, b)| {
+            let alignment = dtw_align(&cognate_sets[set][a].1, &cognate_sets[set][b].1);
+            AlignedPair {
+                set,
+                a,
+                b,
+                columns: alignment
+                    .sequence_a
+                    .into_iter()
+                    .zip(alignment.sequence_b)
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let mut counts = Correspondences::default();
+    for pair in &aligned {
+        let lang_a = cognate_sets[pair.set][pair.a].0.as_str();
+        let lang_b = cognate_sets[pair.set][pair.b].0.as_str();
+        for (seg_a, seg_b) in &pair.columns {
+            counts.add(lang_b, lang_a, seg_b, seg_a);
+            counts.add(lang_a, lang_b, seg_a, seg_b);
+        }
+    }
+
+    // Best (lowest) score per member
+    let mut best: HashMap<(usize, usize), BestAlignment> = HashMap::new();
+    for pair in &aligned {
+        if pair.columns.is_empty() {
+            continue;
+        }
+        let members = &cognate_sets[pair.set];
+        for (member, partner, flipped) in [(pair.a, pair.b, false), (pair.b, pair.a, true)] {
+            let lang = members[member].0.as_str();
+            let partner_lang = members[partner].0.as_str();
+            let scored: Vec<(f64, (&str, &str))> = pair
+                .columns
+                .iter()
+                .map(|(seg_a, seg_b)| {
+                    let (segment, given) = if flipped {
+                        (seg_b, seg_a)
+                    } else {
+                        (seg_a, seg_b)
+                    };
+                    let surprisal = counts.surprisal(partner_lang, lang, given, segment);
+                    (surprisal, (segment.as_str(), given.as_str()))
+                })
+                .collect();
+            let score = scored.iter().map(|s| s.0).sum::<f64>() / scored.len() as f64;
+
+            let current = best.get(&(pair.set, member)).map_or(f64::INFINITY, |b| b.0);
+            if score < current {
+                let worst = scored
+                    .iter()
+                    .max_by(|x, y| x.0.total_cmp(&y.0))
+                    .map(|s| (s.1 .0.to_string(), s.1 .1.to_string()))
+                    .unwrap();
+                best.insert((pair.set, member), (score, partner, worst));
+            }
+        }
+    }
+
+    let mut flags: Vec<TranscriptionFlag> = best
+        .into_iter()
+        .filter(|(_, (score, _, _))| *score >= min_score)
+        .map(
+            |((set, member), (score, partner, worst_column))| TranscriptionFlag {
+                set,
+                member,
+                language: cognate_sets[set][member].0.clone(),
+                ipa: cognate_sets[set][member].1.clone(),
+                score,
+                partner,
+                worst_column,
+            },
+        )
+        .collect();
+    flags.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.set.cmp(&b.set))
+            .then(a.member.cmp(&b.member))
+    });
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Regular p : f : b correspondences, with one miscopied form
+    fn cognate_sets() -> Vec<Vec<(String, String)>> {
+        let words = ["pata", "paka", "pat", "tapa", "kapa", "pak", "apa", "papa"];
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let fra = if i == 3 {
+                    "taka".to_string()
+                } else {
+                    word.replace('p', "f")
+                };
+                vec![
+                    ("ara".to_string(), word.to_string()),
+                    ("fra".to_string(), fra),
+                    ("bra".to_string(), word.replace('p', "b")),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_flags_miscopied_form() {
+        let sets = cognate_sets();
+        let all = detect_transcription_errors(&sets, f64::NEG_INFINITY);
+        assert_eq!(all.len(), 24);
+
+        let top = &all[0];
+        assert_eq!((top.set, top.member), (3, 1));
+        assert_eq!(top.ipa, "taka");
+        assert_eq!(top.worst_column.0, "k");
+        assert!(top.score > all[1].score + 0.5);
+
+        let flagged = detect_transcription_errors(&sets, top.score - 1e-9);
+        assert_eq!(flagged.len(), 1);
+    }
+
+    #[test]
+    fn test_single_language_sets_are_skipped() {
+        let sets = vec![vec![
+            ("eng".to_string(), "hand".to_string()),
+            ("eng".to_string(), "hant".to_string()),
+        ]];
+        assert!(detect_transcription_errors(&sets, 0.0).is_empty());
+    }
+}
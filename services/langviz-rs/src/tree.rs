@@ -0,0 +1,685 @@
+//! Rooted language trees with branch lengths, read from and written to
+//! Newick.
+//!
+//! Nodes live in one arena indexed by `usize`; the root is node `root` and
+//! every other node records its parent. Labels are kept verbatim and may be
+//! quoted with single quotes (`''` escapes a quote); missing branch lengths
+//! read as 0.
+//!
+//! Trees can also be built from a distance matrix by UPGMA (ultrametric,
+//! rooted) or Neighbor-Joining (additive, rooted at the last join), and
+//! exported as nested JSON for the frontend. Either method can be held to a
+//! constraint tree of known subgroups: a join is only made when the merged
+//! cluster splits none of the constraint's clades, so every clade comes out
+//! as a node of the result.
+
+use ndarray::ArrayView2;
+use std::collections::HashMap;
+
+/// One node of a `Tree`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub name: Option<String>,
+    /// Length of the branch to the parent
+    pub length: f64,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+/// Rooted tree over an arena of nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree {
+    pub nodes: Vec<TreeNode>,
+    pub root: usize,
+}
+
+struct NewickParser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    nodes: Vec<TreeNode>,
+}
+
+impl NewickParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((i, c)) => Err(format!("expected '{}' at {}, found '{}'", expected, i, c)),
+            None => Err(format!("expected '{}', found end of input", expected)),
+        }
+    }
+
+    fn label(&mut self) -> Result<Option<String>, String> {
+        if self.peek() == Some('\'') {
+            self.chars.next();
+            let mut label = String::new();
+            loop {
+                match self.chars.next() {
+                    Some((_, '\'')) if self.chars.next_if(|&(_, c)| c == '\'').is_some() => {
+                        label.push('\'')
+                    }
+                    Some((_, '\'')) => return Ok(Some(label)),
+                    Some((_, c)) => label.push(c),
+                    None => return Err("unterminated quoted label".to_string()),
+                }
+            }
+        }
+
+        let mut label = String::new();
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|&(_, c)| !"(),:;".contains(c) && !c.is_whitespace())
+        {
+            label.push(c);
+        }
+        Ok((!label.is_empty()).then_some(label))
+    }
+
+    fn length(&mut self) -> Result<f64, String> {
+        if self.peek() != Some(':') {
+            return Ok(0.0);
+        }
+        self.chars.next();
+        self.skip_whitespace();
+        let mut number = String::new();
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|&(_, c)| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            number.push(c);
+        }
+        number
+            .parse()
+            .map_err(|_| format!("invalid branch length '{}'", number))
+    }
+
+    fn subtree(&mut self, parent: Option<usize>) -> Result<usize, String> {
+        let index = self.nodes.len();
+        self.nodes.push(TreeNode {
+            name: None,
+            length: 0.0,
+            parent,
+            children: Vec::new(),
+        });
+
+        if self.peek() == Some('(') {
+            self.chars.next();
+            loop {
+                let child = self.subtree(Some(index))?;
+                self.nodes[index].children.push(child);
+                match self.peek() {
+                    Some(',') => {
+                        self.chars.next();
+                    }
+                    _ => break,
+                }
+            }
+            self.skip_whitespace();
+            self.expect(')')?;
+        }
+
+        self.nodes[index].name = self.label()?;
+        self.nodes[index].length = self.length()?;
+        Ok(index)
+    }
+}
+
+/// Label as written in Newick, quoted when it holds special characters
+pub(crate) fn newick_label(name: &str) -> String {
+    if name
+        .chars()
+        .any(|c| "(),:;'".contains(c) || c.is_whitespace())
+    {
+        format!("'{}'", name.replace('\'', "''"))
+    } else {
+        name.to_string()
+    }
+}
+
+impl Tree {
+    /// Parse a Newick string such as `((A:1,B:1)AB:0.5,C:1.5);`
+    pub fn from_newick(newick: &str) -> Result<Self, String> {
+        let mut parser = NewickParser {
+            chars: newick.char_indices().peekable(),
+            nodes: Vec::new(),
+        };
+        let root = parser.subtree(None)?;
+        if parser.peek() == Some(';') {
+            parser.chars.next();
+        }
+        if let Some(c) = parser.peek() {
+            return Err(format!("unexpected '{}' after tree", c));
+        }
+        Ok(Self {
+            nodes: parser.nodes,
+            root,
+        })
+    }
+
+    /// Newick string with branch lengths on every non-root node
+    pub fn to_newick(&self) -> String {
+        let mut out = String::new();
+        self.write_newick(self.root, &mut out);
+        out.push(';');
+        out
+    }
+
+    fn write_newick(&self, node: usize, out: &mut String) {
+        let current = &self.nodes[node];
+        if !current.children.is_empty() {
+            out.push('(');
+            for (i, &child) in current.children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                self.write_newick(child, out);
+            }
+            out.push(')');
+        }
+        if let Some(name) = &current.name {
+            out.push_str(&newick_label(name));
+        }
+        if current.parent.is_some() {
+            out.push_str(&format!(":{}", current.length));
+        }
+    }
+
+    /// Nodes with every parent before its children
+    pub fn preorder(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![self.root];
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            stack.extend(self.nodes[node].children.iter().rev());
+        }
+        order
+    }
+
+    /// Leaf nodes, left to right
+    pub fn leaves(&self) -> Vec<usize> {
+        self.preorder()
+            .into_iter()
+            .filter(|&node| self.nodes[node].children.is_empty())
+            .collect()
+    }
+
+    /// Leaf names, left to right; unnamed leaves are called `leaf{index}`
+    pub fn leaf_names(&self) -> Vec<String> {
+        self.leaves()
+            .into_iter()
+            .map(|leaf| self.node_name(leaf))
+            .collect()
+    }
+
+    /// Name of a node, or `leaf{index}` / `node{index}` when unnamed
+    pub fn node_name(&self, node: usize) -> String {
+        let current = &self.nodes[node];
+        match &current.name {
+            Some(name) => name.clone(),
+            None if current.children.is_empty() => format!("leaf{}", node),
+            None => format!("node{}", node),
+        }
+    }
+}
+
+/// Leaf per ID, ready for distance-based joining
+fn leaf_arena(ids: &[String]) -> Vec<TreeNode> {
+    ids.iter()
+        .map(|id| TreeNode {
+            name: Some(id.clone()),
+            length: 0.0,
+            parent: None,
+            children: Vec::new(),
+        })
+        .collect()
+}
+
+/// New unnamed node over `children`, each with its branch length
+fn join(nodes: &mut Vec<TreeNode>, children: &[(usize, f64)]) -> usize {
+    let index = nodes.len();
+    for &(child, length) in children {
+        nodes[child].parent = Some(index);
+        nodes[child].length = length;
+    }
+    nodes.push(TreeNode {
+        name: None,
+        length: 0.0,
+        parent: None,
+        children: children.iter().map(|&(child, _)| child).collect(),
+    });
+    index
+}
+
+/// Symmetrized copy of an n × n distance matrix for `ids`, rejecting bad
+/// shapes and non-finite or negative entries
+fn distance_rows(ids: &[String], distances: ArrayView2<f64>) -> Result<Vec<Vec<f64>>, String> {
+    let n = ids.len();
+    if n == 0 {
+        return Err("cannot build a tree over no ids".to_string());
+    }
+    if distances.shape() != [n, n] {
+        return Err(format!(
+            "distance matrix shape {:?} does not match {} ids",
+            distances.shape(),
+            n
+        ));
+    }
+    if let Some(((i, j), d)) = distances
+        .indexed_iter()
+        .find(|(_, d)| !d.is_finite() || **d < 0.0)
+    {
+        return Err(format!(
+            "distance between {} and {} is {}: distances must be finite and >= 0",
+            ids[i], ids[j], d
+        ));
+    }
+    Ok((0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| (distances[[i, j]] + distances[[j, i]]) / 2.0)
+                .collect()
+        })
+        .collect())
+}
+
+/// Clades of a constraint tree as seen from the rows of a distance matrix
+struct Constraints {
+    clade_sizes: Vec<usize>,
+    /// Per row: ids in the row's cluster
+    sizes: Vec<usize>,
+    /// Per row: ids of the row's cluster in each clade
+    inside: Vec<Vec<usize>>,
+}
+
+impl Constraints {
+    /// Clades of `constraint` with at least two ids (its root and leaves
+    /// constrain nothing); constraint leaves must be distinct ids
+    fn new(ids: &[String], constraint: Option<&Tree>) -> Result<Self, String> {
+        let mut clades: Vec<Vec<usize>> = Vec::new();
+        if let Some(constraint) = constraint {
+            let row_of: HashMap<&str, usize> = ids
+                .iter()
+                .enumerate()
+                .map(|(row, id)| (id.as_str(), row))
+                .collect();
+            let mut members: Vec<Vec<usize>> = vec![Vec::new(); constraint.nodes.len()];
+            for &node in constraint.preorder().iter().rev() {
+                if constraint.nodes[node].children.is_empty() {
+                    let name = constraint.node_name(node);
+                    let row = *row_of.get(name.as_str()).ok_or_else(|| {
+                        format!("constraint leaf '{}' is not among the ids", name)
+                    })?;
+                    members[node].push(row);
+                }
+                if let Some(parent) = constraint.nodes[node].parent {
+                    let below = members[node].clone();
+                    members[parent].extend(below);
+                    if members[node].len() >= 2 {
+                        clades.push(std::mem::take(&mut members[node]));
+                    }
+                }
+            }
+            let mut seen = vec![false; ids.len()];
+            if let Some(&row) = members[constraint.root]
+                .iter()
+                .find(|&&row| std::mem::replace(&mut seen[row], true))
+            {
+                return Err(format!(
+                    "constraint leaf '{}' appears more than once",
+                    ids[row]
+                ));
+            }
+        }
+
+        let mut inside = vec![vec![0; clades.len()]; ids.len()];
+        for (c, clade) in clades.iter().enumerate() {
+            for &row in clade {
+                inside[row][c] = 1;
+            }
+        }
+        Ok(Self {
+            clade_sizes: clades.iter().map(Vec::len).collect(),
+            sizes: vec![1; ids.len()],
+            inside,
+        })
+    }
+
+    /// Ids of the cluster merging rows `i` and `j`, and how many of them
+    /// fall in each clade, paired with the clade size
+    fn merged(&self, i: usize, j: usize) -> (usize, impl Iterator<Item = (usize, usize)> + '_) {
+        let shared = self.inside[i]
+            .iter()
+            .zip(&self.inside[j])
+            .map(|(a, b)| a + b)
+            .zip(self.clade_sizes.iter().copied());
+        (self.sizes[i] + self.sizes[j], shared)
+    }
+
+    /// Whether every clade is disjoint from, holds or lies within the
+    /// cluster merging rows `i` and `j`
+    fn allows(&self, i: usize, j: usize) -> bool {
+        let (size, mut shared) = self.merged(i, j);
+        shared.all(|(shared, clade)| shared == 0 || shared == size || shared == clade)
+    }
+
+    /// Whether merging rows `i` and `j` yields exactly some clade
+    fn completes(&self, i: usize, j: usize) -> bool {
+        let (size, mut shared) = self.merged(i, j);
+        shared.any(|(shared, clade)| shared == size && clade == size)
+    }
+
+    /// Fold row `j` into row `i`
+    fn merge(&mut self, i: usize, j: usize) {
+        self.sizes[i] += self.sizes[j];
+        let moved = std::mem::take(&mut self.inside[j]);
+        for (a, b) in self.inside[i].iter_mut().zip(moved) {
+            *a += b;
+        }
+    }
+}
+
+/// Closest pair (i < j) among the active rows under `score` that the
+/// constraints allow to merge
+fn closest_pair(
+    active: &[usize],
+    constraints: &Constraints,
+    score: impl Fn(usize, usize) -> f64,
+) -> (usize, usize) {
+    let mut best = (0, 1, f64::INFINITY);
+    for (a, &i) in active.iter().enumerate() {
+        for &j in &active[a + 1..] {
+            if !constraints.allows(i, j) {
+                continue;
+            }
+            let value = score(i, j);
+            if value < best.2 {
+                best = (i, j, value);
+            }
+        }
+    }
+    (best.0, best.1)
+}
+
+impl Tree {
+    /// UPGMA (average linkage) tree: an ultrametric tree whose root-to-leaf
+    /// paths all have the same length; `constraint` clades are kept whole
+    pub fn upgma(
+        ids: &[String],
+        distances: ArrayView2<f64>,
+        constraint: Option<&Tree>,
+    ) -> Result<Self, String> {
+        let mut dist = distance_rows(ids, distances)?;
+        let mut constraints = Constraints::new(ids, constraint)?;
+        let mut nodes = leaf_arena(ids);
+        // Per matrix row: tree node, leaf count and height above the leaves
+        let mut clusters: Vec<(usize, usize, f64)> = (0..ids.len()).map(|i| (i, 1, 0.0)).collect();
+        let mut active: Vec<usize> = (0..ids.len()).collect();
+
+        while active.len() > 1 {
+            let (i, j) = closest_pair(&active, &constraints, |i, j| dist[i][j]);
+            let height = dist[i][j] / 2.0;
+            let (node_i, size_i, height_i) = clusters[i];
+            let (node_j, size_j, height_j) = clusters[j];
+            let node = join(
+                &mut nodes,
+                &[
+                    (node_i, (height - height_i).max(0.0)),
+                    (node_j, (height - height_j).max(0.0)),
+                ],
+            );
+
+            active.retain(|&k| k != j);
+            for &k in &active {
+                if k != i {
+                    let d = (dist[i][k] * size_i as f64 + dist[j][k] * size_j as f64)
+                        / (size_i + size_j) as f64;
+                    dist[i][k] = d;
+                    dist[k][i] = d;
+                }
+            }
+            constraints.merge(i, j);
+            clusters[i] = (node, size_i + size_j, height);
+        }
+
+        Ok(Self {
+            root: clusters[active[0]].0,
+            nodes,
+        })
+    }
+
+    /// Neighbor-Joining tree, rooted at the node joining the last three
+    /// clusters (or the last two, when two of the three make up a
+    /// `constraint` clade); negative branch length estimates are clamped
+    /// to 0
+    pub fn neighbor_joining(
+        ids: &[String],
+        distances: ArrayView2<f64>,
+        constraint: Option<&Tree>,
+    ) -> Result<Self, String> {
+        let mut dist = distance_rows(ids, distances)?;
+        let mut constraints = Constraints::new(ids, constraint)?;
+        let mut nodes = leaf_arena(ids);
+        let mut row_node: Vec<usize> = (0..ids.len()).collect();
+        let mut active: Vec<usize> = (0..ids.len()).collect();
+
+        while active.len() > 3
+            || active.len() == 3 && {
+                let (i, j, k) = (active[0], active[1], active[2]);
+                constraints.completes(i, j)
+                    || constraints.completes(i, k)
+                    || constraints.completes(j, k)
+            }
+        {
+            let r = active.len() as f64;
+            let totals: Vec<f64> = (0..dist.len())
+                .map(|i| active.iter().map(|&k| dist[i][k]).sum())
+                .collect();
+            let (i, j) = closest_pair(&active, &constraints, |i, j| {
+                (r - 2.0) * dist[i][j] - totals[i] - totals[j]
+            });
+            let length_i = dist[i][j] / 2.0 + (totals[i] - totals[j]) / (2.0 * (r - 2.0));
+            let length_j = dist[i][j] - length_i;
+            let node = join(
+                &mut nodes,
+                &[
+                    (row_node[i], length_i.max(0.0)),
+                    (row_node[j], length_j.max(0.0)),
+                ],
+            );
+
+            active.retain(|&k| k != j);
+            for &k in &active {
+                if k != i {
+                    let d = (dist[i][k] + dist[j][k] - dist[i][j]) / 2.0;
+                    dist[i][k] = d;
+                    dist[k][i] = d;
+                }
+            }
+            constraints.merge(i, j);
+            row_node[i] = node;
+        }
+
+        let root = match active[..] {
+            [only] => row_node[only],
+            [i, j] => join(
+                &mut nodes,
+                &[
+                    (row_node[i], dist[i][j] / 2.0),
+                    (row_node[j], dist[i][j] / 2.0),
+                ],
+            ),
+            [i, j, k] => {
+                let length = |a: usize, b: usize, c: usize| {
+                    ((dist[a][b] + dist[a][c] - dist[b][c]) / 2.0).max(0.0)
+                };
+                join(
+                    &mut nodes,
+                    &[
+                        (row_node[i], length(i, j, k)),
+                        (row_node[j], length(j, i, k)),
+                        (row_node[k], length(k, i, j)),
+                    ],
+                )
+            }
+            _ => unreachable!("joining stops at three clusters"),
+        };
+        Ok(Self { nodes, root })
+    }
+
+    /// Nested JSON (`{"name", "length", "children"}` per node, `name` null
+    /// on unnamed nodes, no `children` on leaves) for tree layouts such as
+    /// d3.hierarchy
+    pub fn to_json(&self) -> String {
+        self.json_value(self.root).to_string()
+    }
+
+    fn json_value(&self, node: usize) -> serde_json::Value {
+        let current = &self.nodes[node];
+        let mut value = serde_json::json!({
+            "name": current.name,
+            "length": current.length,
+        });
+        if !current.children.is_empty() {
+            value["children"] = current
+                .children
+                .iter()
+                .map(|&child| self.json_value(child))
+                .collect();
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newick_round_trip() {
+        let tree = Tree::from_newick("((eng:1.5, deu:1)gem:0.5,'it''s':2.25);").unwrap();
+        assert_eq!(tree.nodes.len(), 5);
+        assert_eq!(tree.leaf_names(), vec!["eng", "deu", "it's"]);
+
+        let gem = tree.nodes[tree.root].children[0];
+        assert_eq!(tree.nodes[gem].name.as_deref(), Some("gem"));
+        assert_eq!(tree.nodes[gem].length, 0.5);
+        assert_eq!(tree.nodes[tree.nodes[gem].children[0]].length, 1.5);
+
+        let newick = tree.to_newick();
+        assert_eq!(newick, "((eng:1.5,deu:1)gem:0.5,'it''s':2.25);");
+        assert_eq!(Tree::from_newick(&newick).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_unnamed_nodes_and_errors() {
+        let tree = Tree::from_newick("((,b),c)").unwrap();
+        assert_eq!(tree.leaf_names(), vec!["leaf2", "b", "c"]);
+        assert_eq!(tree.preorder(), vec![0, 1, 2, 3, 4]);
+
+        assert!(Tree::from_newick("((a,b);").is_err());
+        assert!(Tree::from_newick("(a:x,b);").is_err());
+        assert!(Tree::from_newick("(a,b);c").is_err());
+    }
+
+    #[test]
+    fn test_upgma_and_neighbor_joining() {
+        let ids: Vec<String> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        // Additive distances of the tree ((a:2,b:3):3,c:4,(d:2,e:1):2)
+        let distances = ndarray::array![
+            [0.0, 5.0, 9.0, 9.0, 8.0],
+            [5.0, 0.0, 10.0, 10.0, 9.0],
+            [9.0, 10.0, 0.0, 8.0, 7.0],
+            [9.0, 10.0, 8.0, 0.0, 3.0],
+            [8.0, 9.0, 7.0, 3.0, 0.0],
+        ];
+
+        let nj = Tree::neighbor_joining(&ids, distances.view(), None).unwrap();
+        let leaf_lengths: Vec<f64> = (0..5).map(|leaf| nj.nodes[leaf].length).collect();
+        assert_eq!(leaf_lengths, vec![2.0, 3.0, 4.0, 2.0, 1.0]);
+        assert_eq!(nj.nodes[nj.root].children.len(), 3);
+
+        let upgma = Tree::upgma(&ids, distances.view(), None).unwrap();
+        assert!(upgma.to_newick().starts_with("((a:2.5,b:2.5):"));
+        let depths: Vec<f64> = upgma
+            .leaves()
+            .into_iter()
+            .map(|mut node| {
+                let mut depth = 0.0;
+                while let Some(parent) = upgma.nodes[node].parent {
+                    depth += upgma.nodes[node].length;
+                    node = parent;
+                }
+                depth
+            })
+            .collect();
+        assert!(depths.iter().all(|d| (d - 55.0 / 12.0).abs() < 1e-9));
+        let json: serde_json::Value = serde_json::from_str(&upgma.to_json()).unwrap();
+        assert_eq!(json["children"][0]["children"][1]["name"], "b");
+        assert_eq!(json["children"][1]["children"][1]["length"], 2.25);
+
+        assert!(Tree::upgma(&ids[..2], distances.view(), None).is_err());
+        assert!(Tree::neighbor_joining(&[], distances.slice(ndarray::s![..0, ..0]), None).is_err());
+    }
+
+    #[test]
+    fn test_constraint_tree() {
+        let ids: Vec<String> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let distances = ndarray::array![
+            [0.0, 5.0, 9.0, 9.0, 8.0],
+            [5.0, 0.0, 10.0, 10.0, 9.0],
+            [9.0, 10.0, 0.0, 8.0, 7.0],
+            [9.0, 10.0, 8.0, 0.0, 3.0],
+            [8.0, 9.0, 7.0, 3.0, 0.0],
+        ];
+        let clade_of = |tree: &Tree, names: &[&str]| {
+            tree.preorder().into_iter().any(|node| {
+                let mut below: Vec<String> = Tree {
+                    nodes: tree.nodes.clone(),
+                    root: node,
+                }
+                .leaf_names();
+                below.sort();
+                below == names
+            })
+        };
+
+        // Known subgroups {a, c} and {c, a, e} against the distances
+        let constraint = Tree::from_newick("(((a,c),e),b);").unwrap();
+        for tree in [
+            Tree::upgma(&ids, distances.view(), Some(&constraint)).unwrap(),
+            Tree::neighbor_joining(&ids, distances.view(), Some(&constraint)).unwrap(),
+        ] {
+            assert!(clade_of(&tree, &["a", "c"]));
+            assert!(clade_of(&tree, &["a", "c", "e"]));
+            assert_eq!(tree.leaves().len(), 5);
+        }
+
+        // NJ roots between the last two clusters when they form a clade
+        let nj = Tree::neighbor_joining(
+            &ids,
+            distances.view(),
+            Some(&Tree::from_newick("((d,e),a,b);").unwrap()),
+        )
+        .unwrap();
+        assert_eq!(nj.nodes[nj.root].children.len(), 2);
+        assert!(clade_of(&nj, &["d", "e"]));
+
+        let unknown = Tree::from_newick("((a,x),b);").unwrap();
+        assert!(Tree::upgma(&ids, distances.view(), Some(&unknown)).is_err());
+        let repeated = Tree::from_newick("((a,b),a);").unwrap();
+        assert!(Tree::neighbor_joining(&ids, distances.view(), Some(&repeated)).is_err());
+    }
+}
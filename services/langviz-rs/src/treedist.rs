@@ -0,0 +1,236 @@
+//! Topological distances between two trees over their shared leaves.
+//!
+//! Both trees are compared unrooted and restricted to the leaf names they
+//! have in common. The Robinson–Foulds distance counts the non-trivial
+//! splits (bipartitions of the shared leaves) found in only one tree,
+//! normalized by the splits of both. The quartet distance counts the
+//! four-leaf subsets whose topology differs, a quartet unresolved in one
+//! tree and resolved in the other counting as different, normalized by
+//! all quartets. Quartet topologies come from the four-point condition on
+//! branch counts, so the cost grows with the fourth power of the shared
+//! leaves (a few hundred languages remain quick).
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::tree::Tree;
+
+/// Distances between two trees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeComparison {
+    pub shared_leaves: usize,
+    /// Non-trivial splits of each tree
+    pub splits: (usize, usize),
+    pub robinson_foulds: usize,
+    /// `robinson_foulds` / total splits of both trees (0 when neither has
+    /// any)
+    pub robinson_foulds_normalized: f64,
+    pub quartets: u64,
+    pub quartet_distance: u64,
+    /// `quartet_distance` / `quartets`
+    pub quartet_distance_normalized: f64,
+}
+
+/// Compare two trees over the leaf names they share (at least 4)
+pub fn compare_trees(a: &Tree, b: &Tree) -> Result<TreeComparison, String> {
+    let names_a = leaf_index(a)?;
+    let names_b = leaf_index(b)?;
+    let mut shared: Vec<&str> = names_a
+        .keys()
+        .filter(|name| names_b.contains_key(*name))
+        .copied()
+        .collect();
+    shared.sort_unstable();
+    if shared.len() < 4 {
+        return Err(format!(
+            "trees share {} leaves, at least 4 are needed",
+            shared.len()
+        ));
+    }
+    let leaves_a: Vec<usize> = shared.iter().map(|name| names_a[name]).collect();
+    let leaves_b: Vec<usize> = shared.iter().map(|name| names_b[name]).collect();
+
+    let splits_a = splits(a, &leaves_a);
+    let splits_b = splits(b, &leaves_b);
+    let robinson_foulds = splits_a.symmetric_difference(&splits_b).count();
+    let total_splits = splits_a.len() + splits_b.len();
+
+    let distances_a = leaf_distances(a, &leaves_a);
+    let distances_b = leaf_distances(b, &leaves_b);
+    let n = shared.len();
+    let quartet_distance: u64 = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let mut differing = 0;
+            for j in i + 1..n {
+                for k in j + 1..n {
+                    for l in k + 1..n {
+                        let quartet = [i, j, k, l];
+                        if topology(&distances_a, quartet) != topology(&distances_b, quartet) {
+                            differing += 1;
+                        }
+                    }
+                }
+            }
+            differing
+        })
+        .sum();
+    let n = n as u64;
+    let quartets = n * (n - 1) * (n - 2) * (n - 3) / 24;
+
+    Ok(TreeComparison {
+        shared_leaves: shared.len(),
+        splits: (splits_a.len(), splits_b.len()),
+        robinson_foulds,
+        robinson_foulds_normalized: if total_splits == 0 {
+            0.0
+        } else {
+            robinson_foulds as f64 / total_splits as f64
+        },
+        quartets,
+        quartet_distance,
+        quartet_distance_normalized: quartet_distance as f64 / quartets as f64,
+    })
+}
+
+/// Leaf node per name; leaves must be named and unique
+fn leaf_index(tree: &Tree) -> Result<HashMap<&str, usize>, String> {
+    let mut index = HashMap::new();
+    for leaf in tree.leaves() {
+        let name = tree.nodes[leaf]
+            .name
+            .as_deref()
+            .ok_or("every leaf must be named to compare trees")?;
+        if index.insert(name, leaf).is_some() {
+            return Err(format!("leaf name '{}' appears more than once", name));
+        }
+    }
+    Ok(index)
+}
+
+/// Non-trivial splits over `leaves` as bitsets of the side without
+/// `leaves[0]`
+fn splits(tree: &Tree, leaves: &[usize]) -> HashSet<Vec<u64>> {
+    let n = leaves.len();
+    let words = n.div_ceil(64);
+    let mut below = vec![vec![0u64; words]; tree.nodes.len()];
+    let mut count = vec![0; tree.nodes.len()];
+    for (i, &leaf) in leaves.iter().enumerate() {
+        below[leaf][i / 64] |= 1 << (i % 64);
+        count[leaf] = 1;
+    }
+    let mut splits = HashSet::new();
+    for &node in tree.preorder().iter().rev() {
+        if let Some(parent) = tree.nodes[node].parent {
+            let (side, size) = (below[node].clone(), count[node]);
+            for (word, bits) in below[parent].iter_mut().zip(&side) {
+                *word |= bits;
+            }
+            count[parent] += size;
+            if size < 2 || size > n - 2 {
+                continue;
+            }
+            let side = if side[0] & 1 == 1 {
+                complement(&side, n)
+            } else {
+                side
+            };
+            splits.insert(side);
+        }
+    }
+    splits
+}
+
+fn complement(bits: &[u64], n: usize) -> Vec<u64> {
+    let mut out: Vec<u64> = bits.iter().map(|word| !word).collect();
+    if !n.is_multiple_of(64) {
+        *out.last_mut().unwrap() &= (1 << (n % 64)) - 1;
+    }
+    out
+}
+
+/// Branch counts between every pair of `leaves`
+fn leaf_distances(tree: &Tree, leaves: &[usize]) -> Vec<Vec<u32>> {
+    let slot: HashMap<usize, usize> = leaves.iter().enumerate().map(|(i, &l)| (l, i)).collect();
+    leaves
+        .par_iter()
+        .map(|&start| {
+            let mut row = vec![0; leaves.len()];
+            let mut seen = vec![false; tree.nodes.len()];
+            let mut queue = VecDeque::from([(start, 0)]);
+            seen[start] = true;
+            while let Some((node, depth)) = queue.pop_front() {
+                if let Some(&i) = slot.get(&node) {
+                    row[i] = depth;
+                }
+                let current = &tree.nodes[node];
+                for &next in current.children.iter().chain(&current.parent) {
+                    if !seen[next] {
+                        seen[next] = true;
+                        queue.push_back((next, depth + 1));
+                    }
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+/// Pairing of a quartet by the four-point condition: 0 = ij|kl, 1 = ik|jl,
+/// 2 = il|jk, 3 = unresolved
+fn topology(distances: &[Vec<u32>], [i, j, k, l]: [usize; 4]) -> u8 {
+    let sums = [
+        distances[i][j] + distances[k][l],
+        distances[i][k] + distances[j][l],
+        distances[i][l] + distances[j][k],
+    ];
+    let min = *sums.iter().min().unwrap();
+    let mut smallest = sums.iter().enumerate().filter(|&(_, &sum)| sum == min);
+    match (smallest.next(), smallest.next()) {
+        (Some((pairing, _)), None) => pairing as u8,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(newick: &str) -> Tree {
+        Tree::from_newick(newick).unwrap()
+    }
+
+    #[test]
+    fn test_compare_trees() {
+        let reference = tree("(((a,b),c),((d,e),f));");
+        let same = tree("((f,(e,d)),(c,(b,a)));");
+        let identical = compare_trees(&reference, &same).unwrap();
+        assert_eq!(identical.splits, (3, 3));
+        assert_eq!(identical.robinson_foulds, 0);
+        assert_eq!(identical.quartet_distance, 0);
+        assert_eq!(identical.quartets, 15);
+
+        // Swapping b and c only replaces the split ab by ac, and changes
+        // only the quartets holding a, b and c
+        let swapped = tree("(((a,c),b),((d,e),f));");
+        let comparison = compare_trees(&reference, &swapped).unwrap();
+        assert_eq!(comparison.robinson_foulds, 2);
+        assert!((comparison.robinson_foulds_normalized - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(comparison.quartet_distance, 3);
+
+        // A star tree resolves nothing
+        let star = tree("(a,b,c,d,e,f,g);");
+        let comparison = compare_trees(&reference, &star).unwrap();
+        assert_eq!((comparison.shared_leaves, comparison.splits), (6, (3, 0)));
+        assert_eq!(comparison.robinson_foulds, 3);
+        assert_eq!(comparison.quartet_distance, 15);
+        assert_eq!(comparison.quartet_distance_normalized, 1.0);
+    }
+
+    #[test]
+    fn test_rejects_bad_trees() {
+        assert!(compare_trees(&tree("((a,b),c);"), &tree("((a,b),c);")).is_err());
+        let duplicated = tree("((a,a),(c,d));");
+        assert!(compare_trees(&duplicated, &tree("((a,b),(c,d));")).is_err());
+    }
+}
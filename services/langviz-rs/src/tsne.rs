@@ -0,0 +1,440 @@
+//! t-SNE neighbor embedding of words from the sparse similarity matrix.
+//!
+//! Input affinities come from each word's stored neighbors only (as in
+//! Barnes–Hut t-SNE with a kNN graph): distances `1 - similarity` feed a
+//! Gaussian whose bandwidth is calibrated per row to the requested
+//! perplexity, and the conditional affinities are symmetrized. Repulsion is
+//! computed exactly for small inputs and with a Barnes–Hut quadtree above
+//! `exact_limit` points. Optimization follows van der Maaten: early
+//! exaggeration, momentum and per-coordinate gains.
+
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::embedding::NodeEmbedding;
+use crate::sparse::SparseSimilarityMatrix;
+
+/// Iterations run with early exaggeration and low momentum
+const EXAGGERATION_ITERATIONS: usize = 250;
+/// Quadtree depth at which coincident points share a leaf
+const MAX_DEPTH: usize = 48;
+
+/// t-SNE parameters
+#[derive(Debug, Clone)]
+pub struct TsneParams {
+    pub perplexity: f64,
+    pub iterations: usize,
+    /// Step size; `None` picks `max(n / early_exaggeration / 4, 50)`
+    pub learning_rate: Option<f64>,
+    pub early_exaggeration: f64,
+    /// Barnes–Hut accuracy (0 = exact traversal)
+    pub theta: f64,
+    /// Largest input whose repulsion is computed exactly
+    pub exact_limit: usize,
+    pub seed: u64,
+}
+
+impl Default for TsneParams {
+    fn default() -> Self {
+        Self {
+            perplexity: 30.0,
+            iterations: 1000,
+            learning_rate: None,
+            early_exaggeration: 12.0,
+            theta: 0.5,
+            exact_limit: 1000,
+            seed: 0,
+        }
+    }
+}
+
+/// Symmetric input affinities as per-row (column, p) lists summing to 1
+fn input_affinities(matrix: &SparseSimilarityMatrix, perplexity: f64) -> Vec<Vec<(usize, f64)>> {
+    let n = matrix.shape().0;
+    let (indptr, indices, data) = matrix.csr_parts();
+
+    let conditional: Vec<Vec<(usize, f64)>> = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let neighbors: Vec<(usize, f64)> = (indptr[i]..indptr[i + 1])
+                .filter(|&k| indices[k] != i && data[k] > 0.0)
+                .map(|k| (indices[k], (1.0 - data[k]).max(0.0).powi(2)))
+                .collect();
+            calibrate_row(&neighbors, perplexity)
+        })
+        .collect();
+
+    let mut joint: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+    for (i, row) in conditional.iter().enumerate() {
+        for &(j, p) in row {
+            let p = p / (2.0 * n as f64);
+            joint[i].push((j, p));
+            joint[j].push((i, p));
+        }
+    }
+    for row in &mut joint {
+        row.sort_by_key(|&(j, _)| j);
+        row.dedup_by(|next, kept| {
+            let same = next.0 == kept.0;
+            if same {
+                kept.1 += next.1;
+            }
+            same
+        });
+    }
+    joint
+}
+
+/// Conditional affinities of one row, with the Gaussian precision found by
+/// bisection so the row's entropy matches `ln(perplexity)`
+fn calibrate_row(neighbors: &[(usize, f64)], perplexity: f64) -> Vec<(usize, f64)> {
+    if neighbors.is_empty() {
+        return Vec::new();
+    }
+    let target = perplexity.min(neighbors.len() as f64).ln();
+    let min_distance = neighbors.iter().map(|n| n.1).fold(f64::INFINITY, f64::min);
+
+    let (mut beta, mut low, mut high) = (1.0, 0.0, f64::INFINITY);
+    let mut weights = vec![0.0; neighbors.len()];
+    for _ in 0..64 {
+        // Shifted by the nearest distance for numerical stability
+        for (w, &(_, d)) in weights.iter_mut().zip(neighbors) {
+            *w = (-beta * (d - min_distance)).exp();
+        }
+        let sum: f64 = weights.iter().sum();
+        let entropy = beta
+            * neighbors
+                .iter()
+                .zip(&weights)
+                .map(|(&(_, d), w)| (d - min_distance) * w)
+                .sum::<f64>()
+            / sum
+            + sum.ln();
+
+        if (entropy - target).abs() < 1e-5 {
+            break;
+        }
+        if entropy > target {
+            low = beta;
+            beta = if high.is_finite() {
+                (beta + high) / 2.0
+            } else {
+                beta * 2.0
+            };
+        } else {
+            high = beta;
+            beta = (beta + low) / 2.0;
+        }
+    }
+
+    let sum: f64 = weights.iter().sum();
+    neighbors
+        .iter()
+        .zip(weights)
+        .map(|(&(j, _), w)| (j, w / sum))
+        .collect()
+}
+
+/// Barnes–Hut quadtree cell
+struct Cell {
+    center: [f64; 2],
+    half_width: f64,
+    mass_center: [f64; 2],
+    count: usize,
+    /// Child cell indices (0 = none); empty for leaves
+    children: [usize; 4],
+    leaf: bool,
+}
+
+struct QuadTree {
+    cells: Vec<Cell>,
+}
+
+impl QuadTree {
+    fn build(points: &Array2<f64>) -> Self {
+        let (mut min, mut max) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+        for point in points.rows() {
+            for d in 0..2 {
+                min[d] = min[d].min(point[d]);
+                max[d] = max[d].max(point[d]);
+            }
+        }
+        let half_width = ((max[0] - min[0]).max(max[1] - min[1]) / 2.0).max(1e-9) * 1.0001;
+        let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+
+        let mut tree = Self {
+            cells: vec![Cell::new(center, half_width)],
+        };
+        for point in points.rows() {
+            tree.insert(0, [point[0], point[1]], 0);
+        }
+        tree
+    }
+
+    fn insert(&mut self, cell: usize, point: [f64; 2], depth: usize) {
+        let c = &mut self.cells[cell];
+        // A leaf's single occupant sits at its mass center
+        let occupant = (c.leaf && c.count == 1).then_some(c.mass_center);
+        let count = c.count as f64;
+        for (center, &x) in c.mass_center.iter_mut().zip(&point) {
+            *center = (*center * count + x) / (count + 1.0);
+        }
+        c.count += 1;
+
+        if c.leaf {
+            if c.count == 1 || depth >= MAX_DEPTH {
+                return;
+            }
+            c.leaf = false;
+            if let Some(occupant) = occupant {
+                let child = self.child_for(cell, occupant);
+                self.insert(child, occupant, depth + 1);
+            }
+        }
+        let child = self.child_for(cell, point);
+        self.insert(child, point, depth + 1);
+    }
+
+    /// Child quadrant containing `point`, created on demand
+    fn child_for(&mut self, cell: usize, point: [f64; 2]) -> usize {
+        let c = &self.cells[cell];
+        let quadrant =
+            usize::from(point[0] > c.center[0]) + 2 * usize::from(point[1] > c.center[1]);
+        if c.children[quadrant] != 0 {
+            return c.children[quadrant];
+        }
+        let half_width = c.half_width / 2.0;
+        let center = [
+            c.center[0]
+                + if quadrant & 1 == 1 {
+                    half_width
+                } else {
+                    -half_width
+                },
+            c.center[1]
+                + if quadrant & 2 == 2 {
+                    half_width
+                } else {
+                    -half_width
+                },
+        ];
+        self.cells.push(Cell::new(center, half_width));
+        let child = self.cells.len() - 1;
+        self.cells[cell].children[quadrant] = child;
+        child
+    }
+
+    /// Unnormalized repulsive force on `point` and its contribution to Z
+    fn repulsion(&self, point: [f64; 2], theta: f64) -> ([f64; 2], f64) {
+        let mut force = [0.0; 2];
+        let mut z = 0.0;
+        let mut stack = vec![0];
+        while let Some(cell) = stack.pop() {
+            let c = &self.cells[cell];
+            if c.count == 0 {
+                continue;
+            }
+            let diff = [point[0] - c.mass_center[0], point[1] - c.mass_center[1]];
+            let distance_sq = diff[0] * diff[0] + diff[1] * diff[1];
+            if c.leaf || (2.0 * c.half_width) < theta * distance_sq.sqrt() {
+                // The point itself sits in its own leaf at distance zero
+                if distance_sq == 0.0 && c.leaf {
+                    let others = c.count as f64 - 1.0;
+                    z += others;
+                    continue;
+                }
+                let q = 1.0 / (1.0 + distance_sq);
+                let mass = c.count as f64;
+                z += mass * q;
+                force[0] += mass * q * q * diff[0];
+                force[1] += mass * q * q * diff[1];
+            } else {
+                stack.extend(c.children.iter().copied().filter(|&child| child != 0));
+            }
+        }
+        (force, z)
+    }
+}
+
+impl Cell {
+    fn new(center: [f64; 2], half_width: f64) -> Self {
+        Self {
+            center,
+            half_width,
+            mass_center: [0.0; 2],
+            count: 0,
+            children: [0; 4],
+            leaf: true,
+        }
+    }
+}
+
+/// Unnormalized repulsion and Z contribution for every point, exactly
+fn exact_repulsion(y: &Array2<f64>) -> Vec<([f64; 2], f64)> {
+    let n = y.nrows();
+    (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let mut force = [0.0; 2];
+            let mut z = 0.0;
+            for j in (0..n).filter(|&j| j != i) {
+                let diff = [y[[i, 0]] - y[[j, 0]], y[[i, 1]] - y[[j, 1]]];
+                let q = 1.0 / (1.0 + diff[0] * diff[0] + diff[1] * diff[1]);
+                z += q;
+                force[0] += q * q * diff[0];
+                force[1] += q * q * diff[1];
+            }
+            (force, z)
+        })
+        .collect()
+}
+
+/// 2-D t-SNE coordinates for every row of a square similarity matrix
+pub fn tsne_embedding(
+    matrix: &SparseSimilarityMatrix,
+    params: &TsneParams,
+) -> Result<NodeEmbedding, String> {
+    let (n, cols) = matrix.shape();
+    if n != cols || matrix.entry_ids() != matrix.col_ids() {
+        return Err("t-SNE needs a square matrix with matching row and column IDs".to_string());
+    }
+    let ids = matrix.entry_ids().to_vec();
+    if n < 2 {
+        return Ok(NodeEmbedding {
+            ids,
+            coords: Array2::zeros((n, 2)),
+        });
+    }
+
+    let affinities = input_affinities(matrix, params.perplexity);
+    let learning_rate = params
+        .learning_rate
+        .unwrap_or_else(|| (n as f64 / params.early_exaggeration / 4.0).max(50.0));
+
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut y = Array2::from_shape_fn((n, 2), |_| (rng.gen::<f64>() - 0.5) * 1e-4);
+    let mut update = Array2::<f64>::zeros((n, 2));
+    let mut gains = Array2::<f64>::ones((n, 2));
+
+    for iteration in 0..params.iterations {
+        let early = iteration < EXAGGERATION_ITERATIONS;
+        let exaggeration = if early {
+            params.early_exaggeration
+        } else {
+            1.0
+        };
+        let momentum = if early { 0.5 } else { 0.8 };
+
+        let repulsion = if n <= params.exact_limit {
+            exact_repulsion(&y)
+        } else {
+            let tree = QuadTree::build(&y);
+            (0..n)
+                .into_par_iter()
+                .map(|i| tree.repulsion([y[[i, 0]], y[[i, 1]]], params.theta))
+                .collect()
+        };
+        let z: f64 = repulsion.iter().map(|r| r.1).sum::<f64>().max(1e-12);
+
+        let gradient: Vec<[f64; 2]> = (0..n)
+            .into_par_iter()
+            .map(|i| {
+                let mut attraction = [0.0; 2];
+                for &(j, p) in &affinities[i] {
+                    let diff = [y[[i, 0]] - y[[j, 0]], y[[i, 1]] - y[[j, 1]]];
+                    let q = 1.0 / (1.0 + diff[0] * diff[0] + diff[1] * diff[1]);
+                    attraction[0] += p * q * diff[0];
+                    attraction[1] += p * q * diff[1];
+                }
+                let force = repulsion[i].0;
+                [
+                    4.0 * (exaggeration * attraction[0] - force[0] / z),
+                    4.0 * (exaggeration * attraction[1] - force[1] / z),
+                ]
+            })
+            .collect();
+
+        for (i, grad) in gradient.iter().enumerate() {
+            for d in 0..2 {
+                let gain = &mut gains[[i, d]];
+                *gain = if (grad[d] > 0.0) != (update[[i, d]] > 0.0) {
+                    *gain + 0.2
+                } else {
+                    (*gain * 0.8).max(0.01)
+                };
+                update[[i, d]] = momentum * update[[i, d]] - learning_rate * *gain * grad[d];
+                y[[i, d]] += update[[i, d]];
+            }
+        }
+
+        // Keep the embedding centered
+        for d in 0..2 {
+            let mean = y.column(d).sum() / n as f64;
+            y.column_mut(d).mapv_inplace(|v| v - mean);
+        }
+    }
+
+    Ok(NodeEmbedding { ids, coords: y })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two groups of 15 words, similar within and unrelated across
+    fn two_groups() -> SparseSimilarityMatrix {
+        let mut edges = Vec::new();
+        for group in ["a", "b"] {
+            for i in 0..15 {
+                for j in (i + 1)..15 {
+                    let weight = 0.9 - 0.01 * (i + j) as f64;
+                    edges.push((format!("{group}{i:02}"), format!("{group}{j:02}"), weight));
+                }
+            }
+        }
+        SparseSimilarityMatrix::from_edges(edges, 0.0)
+    }
+
+    /// Mean distance between the two groups over mean distance within them
+    fn separation(embedding: &NodeEmbedding) -> f64 {
+        let y = &embedding.coords;
+        let (mut within, mut between) = ((0.0, 0), (0.0, 0));
+        for i in 0..y.nrows() {
+            for j in (i + 1)..y.nrows() {
+                let d = ((y[[i, 0]] - y[[j, 0]]).powi(2) + (y[[i, 1]] - y[[j, 1]]).powi(2)).sqrt();
+                let same = embedding.ids[i][..1] == embedding.ids[j][..1];
+                let slot = if same { &mut within } else { &mut between };
+                slot.0 += d;
+                slot.1 += 1;
+            }
+        }
+        (between.0 / between.1 as f64) / (within.0 / within.1 as f64)
+    }
+
+    #[test]
+    fn test_exact_separates_groups() {
+        let params = TsneParams {
+            perplexity: 5.0,
+            iterations: 1000,
+            ..TsneParams::default()
+        };
+        let embedding = tsne_embedding(&two_groups(), &params).unwrap();
+        assert_eq!(embedding.coords.shape(), &[30, 2]);
+        assert!(embedding.coords.iter().all(|v| v.is_finite()));
+        assert!(separation(&embedding) > 4.0);
+    }
+
+    #[test]
+    fn test_barnes_hut_separates_groups() {
+        let params = TsneParams {
+            perplexity: 5.0,
+            iterations: 1000,
+            exact_limit: 0,
+            ..TsneParams::default()
+        };
+        let embedding = tsne_embedding(&two_groups(), &params).unwrap();
+        assert!(separation(&embedding) > 4.0);
+    }
+}
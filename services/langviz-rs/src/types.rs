@@ -2,6 +2,64 @@
 
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Plain (source, target, weight) edge tuple as exchanged with Python
+pub type WeightedEdge = (String, String, f64);
+
+/// Edge tuple carrying the data source / computation run that produced it
+pub type ProvenanceEdge = (String, String, f64, Option<String>);
+
+/// Node attribute value: text (language, gloss) or number (frequency)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttrValue {
+    Number(f64),
+    Text(String),
+}
+
+impl AttrValue {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            AttrValue::Text(text) => Some(text),
+            AttrValue::Number(_) => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            AttrValue::Number(number) => Some(*number),
+            AttrValue::Text(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttrValue::Text(text) => f.write_str(text),
+            AttrValue::Number(number) => write!(f, "{}", number),
+        }
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(text: String) -> Self {
+        AttrValue::Text(text)
+    }
+}
+
+impl From<&str> for AttrValue {
+    fn from(text: &str) -> Self {
+        AttrValue::Text(text.to_string())
+    }
+}
+
+impl From<f64> for AttrValue {
+    fn from(number: f64) -> Self {
+        AttrValue::Number(number)
+    }
+}
 
 /// Edge in similarity/cognate graph
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +67,9 @@ pub struct SimilarityEdge {
     pub source: String,
     pub target: String,
     pub weight: OrderedFloat<f64>,
+    /// Data source or computation run that produced the edge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<String>,
 }
 
 impl SimilarityEdge {
@@ -17,6 +78,20 @@ impl SimilarityEdge {
             source,
             target,
             weight: OrderedFloat(weight),
+            provenance: None,
+        }
+    }
+
+    /// Create edge tagged with its data source, if any
+    pub fn with_provenance(
+        source: String,
+        target: String,
+        weight: f64,
+        provenance: Option<String>,
+    ) -> Self {
+        Self {
+            provenance,
+            ..Self::new(source, target, weight)
         }
     }
 }
@@ -54,12 +129,36 @@ pub enum EditOp {
     Delete,
 }
 
+impl EditOp {
+    /// One-letter code as used in CIGAR strings: M, S, I or D
+    pub fn code(self) -> char {
+        match self {
+            EditOp::Match => 'M',
+            EditOp::Substitute => 'S',
+            EditOp::Insert => 'I',
+            EditOp::Delete => 'D',
+        }
+    }
+
+    /// Lowercase operation name
+    pub fn name(self) -> &'static str {
+        match self {
+            EditOp::Match => "match",
+            EditOp::Substitute => "substitute",
+            EditOp::Insert => "insert",
+            EditOp::Delete => "delete",
+        }
+    }
+}
+
 /// Result of phonetic alignment
 #[derive(Debug, Clone)]
 pub struct Alignment {
     pub sequence_a: Vec<String>,
     pub sequence_b: Vec<String>,
     pub operations: Vec<EditOp>,
+    /// Cost of each operation, parallel to `operations`; they sum to `cost`
+    pub op_costs: Vec<f64>,
     pub cost: f64,
 }
 
@@ -68,16 +167,58 @@ impl Alignment {
         sequence_a: Vec<String>,
         sequence_b: Vec<String>,
         operations: Vec<EditOp>,
+        op_costs: Vec<f64>,
         cost: f64,
     ) -> Self {
         Self {
             sequence_a,
             sequence_b,
             operations,
+            op_costs,
             cost,
         }
     }
 
+    /// Run-length operation string, e.g. `3M1S2M1I`
+    pub fn cigar(&self) -> String {
+        let mut cigar = String::new();
+        let mut ops = self.operations.iter().peekable();
+        while let Some(&op) = ops.next() {
+            let mut run = 1;
+            while ops.next_if_eq(&&op).is_some() {
+                run += 1;
+            }
+            cigar.push_str(&run.to_string());
+            cigar.push(op.code());
+        }
+        cigar
+    }
+
+    /// Visualization payload: both gapped sequences, total cost, CIGAR
+    /// string and one `{op, a, b, cost}` object per aligned position
+    pub fn to_json(&self) -> serde_json::Value {
+        let operations: Vec<serde_json::Value> = self
+            .operations
+            .iter()
+            .enumerate()
+            .map(|(i, op)| {
+                serde_json::json!({
+                    "op": op.name(),
+                    "a": self.sequence_a.get(i),
+                    "b": self.sequence_b.get(i),
+                    "cost": self.op_costs.get(i),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "sequence_a": self.sequence_a,
+            "sequence_b": self.sequence_b,
+            "cost": self.cost,
+            "cigar": self.cigar(),
+            "operations": operations,
+        })
+    }
+
     /// Extract sound correspondence rules from alignment
     pub fn extract_correspondences(&self) -> Vec<(String, String)> {
         let mut rules = Vec::new();
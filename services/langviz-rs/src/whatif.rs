@@ -0,0 +1,498 @@
+//! What-if impact of removing or adding a single edge or node.
+//!
+//! A change only reaches the connected components it touches: components
+//! elsewhere keep their members, and Louvain never puts nodes of different
+//! components in one community. The touched components are collected by a
+//! breadth-first search from the changed nodes, rebuilt as a small graph
+//! with and without the change (nodes and edges in the whole graph's
+//! order), and re-examined on their own. Louvain runs on them with the
+//! whole graph's total weight in the modularity null model, so their
+//! communities are those of Louvain on the whole graph restricted to the
+//! touched components; the communities elsewhere are left out, though the
+//! change of total weight could in principle shift them too. The cached
+//! statistics of the whole graph are
+//! then patched by swapping those components' sizes in the size
+//! distribution and their nodes' strengths in the sorted strength list, so
+//! a query costs time in the touched components, not the whole graph.
+
+use ahash::AHashSet;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::graph::{CognateGraph, GraphStats, StrengthSummary};
+
+/// A hypothetical edit of a graph
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphChange {
+    /// Remove every edge between two nodes
+    RemoveEdge { source: String, target: String },
+    /// Add an edge, creating unseen endpoints
+    AddEdge {
+        source: String,
+        target: String,
+        weight: f64,
+    },
+    /// Remove a node and its edges
+    RemoveNode(String),
+    /// Add a node with (neighbor, weight) edges to existing nodes
+    AddNode {
+        id: String,
+        edges: Vec<(String, f64)>,
+    },
+}
+
+/// How a change would alter the graph; component and community lists
+/// cover only the touched components, each sorted
+#[derive(Debug, Clone)]
+pub struct WhatIf {
+    pub before: GraphStats,
+    pub after: GraphStats,
+    pub components_before: Vec<Vec<String>>,
+    pub components_after: Vec<Vec<String>>,
+    /// Louvain communities of the touched components
+    pub communities_before: Vec<Vec<String>>,
+    pub communities_after: Vec<Vec<String>>,
+}
+
+impl CognateGraph {
+    /// Impact of `change` on components, Louvain communities at
+    /// `resolution` and the graph statistics, without applying it
+    pub fn what_if(&self, change: &GraphChange, resolution: f64) -> Result<WhatIf, String> {
+        let node = |id: &str| {
+            self.node_map
+                .get(id)
+                .copied()
+                .ok_or_else(|| format!("unknown node '{}'", id))
+        };
+        let seeds: Vec<NodeIndex> = match change {
+            GraphChange::RemoveEdge { source, target } => {
+                let (a, b) = (node(source)?, node(target)?);
+                if self.graph.find_edge(a, b).is_none() {
+                    return Err(format!("no edge between '{}' and '{}'", source, target));
+                }
+                vec![a]
+            }
+            GraphChange::AddEdge {
+                source,
+                target,
+                weight,
+            } => {
+                if !weight.is_finite() || *weight < 0.0 {
+                    return Err(format!("edge weight {} must be finite and >= 0", weight));
+                }
+                [source, target]
+                    .iter()
+                    .filter_map(|id| self.node_map.get(id.as_str()).copied())
+                    .collect()
+            }
+            GraphChange::RemoveNode(id) => vec![node(id)?],
+            GraphChange::AddNode { id, edges } => {
+                if self.node_map.contains_key(id) {
+                    return Err(format!("node '{}' already exists", id));
+                }
+                if let Some((_, weight)) = edges.iter().find(|e| !e.1.is_finite() || e.1 < 0.0) {
+                    return Err(format!("edge weight {} must be finite and >= 0", weight));
+                }
+                edges
+                    .iter()
+                    .map(|(neighbor, _)| node(neighbor))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        let mut region = self.components_around(&seeds);
+        region.sort_unstable();
+        let before_graph = self.region_graph(&region, None);
+        let after_graph = self.region_graph(&region, Some(change));
+
+        let cache = self.stats_cache();
+        let before = cache.stats.clone();
+        let mut distribution: BTreeMap<usize, usize> =
+            before.component_size_distribution.iter().copied().collect();
+        let components_before = sorted_groups(components(&before_graph));
+        let components_after = sorted_groups(components(&after_graph));
+        for component in &components_before {
+            let count = distribution.get_mut(&component.len()).ok_or_else(|| {
+                format!(
+                    "graph statistics out of date: no component of size {}",
+                    component.len()
+                )
+            })?;
+            *count -= 1;
+            if *count == 0 {
+                distribution.remove(&component.len());
+            }
+        }
+        for component in &components_after {
+            *distribution.entry(component.len()).or_insert(0) += 1;
+        }
+        let num_nodes =
+            before.num_nodes + after_graph.graph.node_count() - before_graph.graph.node_count();
+        let num_edges =
+            before.num_edges + after_graph.graph.edge_count() - before_graph.graph.edge_count();
+
+        // Swap the touched nodes' strengths; the rest keep their cached ones
+        let mut removed: Vec<usize> = region.iter().map(|u| cache.rank[u.index()]).collect();
+        removed.sort_unstable();
+        let mut added = after_graph.node_strengths();
+        added.sort_by(f64::total_cmp);
+        let total = 2.0 * before.total_weight
+            - region
+                .iter()
+                .map(|u| cache.strengths[u.index()])
+                .sum::<f64>()
+            + added.iter().sum::<f64>();
+        let strength = StrengthSummary::from_sorted(num_nodes, total, |k| {
+            kth_patched(&cache.sorted, &removed, &added, k)
+        });
+
+        // Normalized by the whole graph's total weight (2m), before and after
+        let communities_before =
+            before_graph.detect_communities_within(resolution, 2.0 * before.total_weight);
+        let communities_after = after_graph.detect_communities_within(resolution, total);
+
+        Ok(WhatIf {
+            after: GraphStats::from_parts(num_nodes, num_edges, distribution, strength),
+            before,
+            components_before,
+            components_after,
+            communities_before: sorted_groups(communities_before),
+            communities_after: sorted_groups(communities_after),
+        })
+    }
+
+    /// Nodes of the components containing `seeds`, in search order
+    fn components_around(&self, seeds: &[NodeIndex]) -> Vec<NodeIndex> {
+        let mut seen: AHashSet<NodeIndex> = AHashSet::new();
+        let mut order = Vec::new();
+        for &seed in seeds {
+            if !seen.insert(seed) {
+                continue;
+            }
+            let mut queue = VecDeque::from([seed]);
+            while let Some(u) = queue.pop_front() {
+                order.push(u);
+                for v in self.graph.neighbors(u) {
+                    if seen.insert(v) {
+                        queue.push_back(v);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// The graph over `region` (whole components, so every incident edge
+    /// stays inside; ascending, to keep the whole graph's node order) with
+    /// `change` applied if given
+    fn region_graph(&self, region: &[NodeIndex], change: Option<&GraphChange>) -> CognateGraph {
+        let removed_node = match change {
+            Some(GraphChange::RemoveNode(id)) => Some(self.node_map[id]),
+            _ => None,
+        };
+        let removed_pair = match change {
+            Some(GraphChange::RemoveEdge { source, target }) => {
+                Some((self.node_map[source], self.node_map[target]))
+            }
+            _ => None,
+        };
+
+        let mut subgraph = CognateGraph::new();
+        for &u in region.iter().filter(|&&u| Some(u) != removed_node) {
+            subgraph.get_or_create_node(self.graph[u].clone());
+        }
+        // Incident edges in edge order, each once
+        let mut edges: Vec<EdgeIndex> = region
+            .iter()
+            .flat_map(|&u| self.graph.edges(u).map(|edge| edge.id()))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        for edge in edges {
+            let endpoints = self.graph.edge_endpoints(edge).unwrap();
+            let skip = Some(endpoints.0) == removed_node
+                || Some(endpoints.1) == removed_node
+                || removed_pair.is_some_and(|(a, b)| endpoints == (a, b) || endpoints == (b, a));
+            if !skip {
+                subgraph.add_edge_with_provenance(
+                    self.graph[endpoints.0].clone(),
+                    self.graph[endpoints.1].clone(),
+                    self.graph[edge],
+                    self.edge_provenance(edge),
+                );
+            }
+        }
+
+        match change {
+            Some(GraphChange::AddEdge {
+                source,
+                target,
+                weight,
+            }) => subgraph.add_edge(source.clone(), target.clone(), *weight),
+            Some(GraphChange::AddNode { id, edges }) => {
+                subgraph.get_or_create_node(id.clone());
+                for (neighbor, weight) in edges {
+                    subgraph.add_edge(id.clone(), neighbor.clone(), *weight);
+                }
+            }
+            _ => {}
+        }
+        subgraph
+    }
+}
+
+/// The `k`-th smallest (from 0) of `sorted` without the entries at the
+/// ascending positions `removed`, merged with the ascending `added`
+fn kth_patched(sorted: &[f64], removed: &[usize], added: &[f64], k: usize) -> f64 {
+    let kept_len = sorted.len() - removed.len();
+    // The i-th entry of `sorted` left after the removals
+    let kept = |i: usize| {
+        let mut position = i;
+        for &r in removed {
+            if r > position {
+                break;
+            }
+            position += 1;
+        }
+        sorted[position]
+    };
+    // Find how many of the k + 1 smallest come from `added`
+    let (mut lo, mut hi) = ((k + 1).saturating_sub(kept_len), added.len().min(k + 1));
+    while lo < hi {
+        let taken = (lo + hi) / 2;
+        let from_kept = k + 1 - taken;
+        if from_kept > 0 && kept(from_kept - 1) > added[taken] {
+            lo = taken + 1;
+        } else {
+            hi = taken;
+        }
+    }
+    let from_kept = k + 1 - lo;
+    let last_added = lo.checked_sub(1).map(|t| added[t]);
+    let last_kept = from_kept.checked_sub(1).map(kept);
+    match (last_added, last_kept) {
+        (Some(a), Some(b)) => a.max(b),
+        (Some(a), None) => a,
+        (None, Some(b)) => b,
+        (None, None) => unreachable!("k is below the merged length"),
+    }
+}
+
+/// Member IDs of each component of `graph`
+fn components(graph: &CognateGraph) -> Vec<Vec<String>> {
+    let (labels, count) = graph.component_labels();
+    let mut groups = vec![Vec::new(); count];
+    for (node, label) in labels.into_iter().enumerate() {
+        groups[label].push(graph.graph[NodeIndex::new(node)].clone());
+    }
+    groups
+}
+
+/// Groups with sorted members, ordered by their first member
+fn sorted_groups(mut groups: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    for group in &mut groups {
+        group.sort();
+    }
+    groups.sort();
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SimilarityEdge;
+
+    fn graph(edges: &[(&str, &str, f64)]) -> CognateGraph {
+        CognateGraph::from_edges(
+            edges
+                .iter()
+                .map(|(a, b, w)| SimilarityEdge::new(a.to_string(), b.to_string(), *w))
+                .collect(),
+            0.0,
+        )
+    }
+
+    fn triangles() -> Vec<(&'static str, &'static str, f64)> {
+        // Two triangles joined by the bridge c-d, and a separate pair
+        vec![
+            ("a", "b", 0.9),
+            ("b", "c", 0.9),
+            ("a", "c", 0.9),
+            ("d", "e", 0.9),
+            ("e", "f", 0.9),
+            ("d", "f", 0.9),
+            ("c", "d", 0.2),
+            ("x", "y", 0.8),
+        ]
+    }
+
+    #[test]
+    fn test_what_if_matches_recomputation() {
+        let full = graph(&triangles());
+        let change = GraphChange::RemoveEdge {
+            source: "d".to_string(),
+            target: "c".to_string(),
+        };
+        let impact = full.what_if(&change, 1.0).unwrap();
+        assert_eq!(impact.components_before.len(), 1);
+        assert_eq!(impact.components_after, [["a", "b", "c"], ["d", "e", "f"]]);
+        assert_eq!(impact.communities_after.len(), 2);
+
+        let edges: Vec<_> = triangles().into_iter().filter(|e| e.0 != "c").collect();
+        let expected = graph(&edges).stats();
+        assert_eq!(impact.after.num_edges, expected.num_edges);
+        assert_eq!(impact.after.num_components, expected.num_components);
+        assert_eq!(
+            impact.after.component_size_distribution,
+            expected.component_size_distribution
+        );
+        assert!((impact.after.total_weight - expected.total_weight).abs() < 1e-12);
+        assert_eq!(impact.after.median_strength, expected.median_strength);
+        assert_eq!(impact.before.num_components, 2);
+
+        // Joining the pair to the triangles touches both components
+        let change = GraphChange::AddNode {
+            id: "z".to_string(),
+            edges: vec![("y".to_string(), 0.5), ("a".to_string(), 0.5)],
+        };
+        let impact = full.what_if(&change, 1.0).unwrap();
+        assert_eq!(impact.components_before.len(), 2);
+        assert_eq!(impact.components_after.len(), 1);
+        assert_eq!(
+            (impact.after.num_nodes, impact.after.num_components),
+            (9, 1)
+        );
+        assert_eq!(impact.after.largest_component_size, 9);
+    }
+
+    #[test]
+    fn test_what_if_node_removal() {
+        let full = graph(&triangles());
+        let impact = full
+            .what_if(&GraphChange::RemoveNode("c".to_string()), 1.0)
+            .unwrap();
+        assert_eq!(
+            impact.components_after,
+            vec![vec!["a", "b"], vec!["d", "e", "f"]]
+        );
+        assert_eq!((impact.after.num_nodes, impact.after.num_edges), (7, 5));
+
+        assert!(full
+            .what_if(&GraphChange::RemoveNode("q".to_string()), 1.0)
+            .is_err());
+        let missing = GraphChange::RemoveEdge {
+            source: "a".to_string(),
+            target: "x".to_string(),
+        };
+        assert!(full.what_if(&missing, 1.0).is_err());
+    }
+
+    /// The triangles next to a long chain that no change below reaches
+    fn triangles_and_chain(chain: &[String]) -> Vec<(&str, &str, f64)> {
+        let mut edges = triangles();
+        edges.extend(
+            chain
+                .windows(2)
+                .enumerate()
+                .map(|(i, w)| (w[0].as_str(), w[1].as_str(), 0.1 + (i % 7) as f64 / 10.0)),
+        );
+        edges
+    }
+
+    #[test]
+    fn test_what_if_skips_untouched_components() {
+        let chain: Vec<String> = (0..5000).map(|i| format!("w{}", i)).collect();
+        let edges = triangles_and_chain(&chain);
+        let full = graph(&edges);
+        full.stats();
+
+        let region = full.components_around(&[full.node_map["c"]]);
+        assert_eq!(region.len(), 6);
+        let change = GraphChange::RemoveNode("c".to_string());
+        let impact = full.what_if(&change, 1.0).unwrap();
+
+        let kept: Vec<_> = edges
+            .into_iter()
+            .filter(|e| e.0 != "c" && e.1 != "c")
+            .collect();
+        let expected = graph(&kept).stats();
+        assert_eq!(impact.after.num_nodes, expected.num_nodes);
+        assert_eq!(impact.after.num_edges, expected.num_edges);
+        assert_eq!(
+            impact.after.component_size_distribution,
+            expected.component_size_distribution
+        );
+        assert!((impact.after.total_weight - expected.total_weight).abs() < 1e-9);
+        assert!((impact.after.mean_strength - expected.mean_strength).abs() < 1e-12);
+        assert_eq!(impact.after.median_strength, expected.median_strength);
+        assert_eq!(impact.after.max_strength, expected.max_strength);
+    }
+
+    #[test]
+    fn test_kth_patched() {
+        let sorted = [0.1, 0.2, 0.2, 0.5, 0.7, 0.9];
+        let removed = [1, 4];
+        let added = [0.0, 0.3, 0.95];
+        let mut expected = [0.1, 0.2, 0.5, 0.9, 0.0, 0.3, 0.95];
+        expected.sort_by(f64::total_cmp);
+        for (k, &value) in expected.iter().enumerate() {
+            assert_eq!(kth_patched(&sorted, &removed, &added, k), value);
+        }
+        assert_eq!(kth_patched(&sorted, &[], &[], 5), 0.9);
+        assert_eq!(kth_patched(&[], &[], &added, 1), 0.3);
+    }
+
+    #[test]
+    fn test_what_if_communities_match_full_louvain() {
+        let chain: Vec<String> = (0..2000).map(|i| format!("w{}", i)).collect();
+        let mut edges = triangles_and_chain(&chain);
+        let full = graph(&edges);
+        let change = GraphChange::AddEdge {
+            source: "b".to_string(),
+            target: "e".to_string(),
+            weight: 0.2,
+        };
+        let impact = full.what_if(&change, 1.0).unwrap();
+
+        // Full-graph Louvain, restricted to the touched components
+        let touched = |graph: &CognateGraph| {
+            sorted_groups(
+                graph
+                    .detect_communities(1.0)
+                    .into_iter()
+                    .filter(|c| ["a", "b", "c", "d", "e", "f"].contains(&c[0].as_str()))
+                    .collect(),
+            )
+        };
+        assert_eq!(impact.communities_before, touched(&full));
+        edges.push(("b", "e", 0.2));
+        assert_eq!(impact.communities_after, touched(&graph(&edges)));
+        // The chain's weight makes merging the triangles pay off, which the
+        // touched components alone would not
+        assert_eq!(impact.communities_after.len(), 1);
+        assert_eq!(graph(&triangles()[..7]).detect_communities(1.0).len(), 2);
+    }
+
+    #[test]
+    fn test_what_if_after_adding_edges() {
+        let mut full = graph(&[("a", "b", 0.9), ("c", "d", 0.9)]);
+        assert_eq!(full.stats().num_components, 2);
+        full.add_edges(vec![SimilarityEdge::new(
+            "b".to_string(),
+            "c".to_string(),
+            0.5,
+        )]);
+        let stats = full.stats();
+        assert_eq!((stats.num_edges, stats.num_components), (3, 1));
+        assert!((stats.total_weight - 2.3).abs() < 1e-12);
+
+        let change = GraphChange::RemoveEdge {
+            source: "a".to_string(),
+            target: "b".to_string(),
+        };
+        let impact = full.what_if(&change, 1.0).unwrap();
+        assert_eq!(impact.before.num_components, 1);
+        assert_eq!(impact.after.component_size_distribution, [(1, 1), (3, 1)]);
+    }
+}
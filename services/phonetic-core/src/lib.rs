@@ -0,0 +1,221 @@
+//! IPA segmentation and edit-distance kernels shared by the LangViz Python
+//! extensions.
+//!
+//! `langviz_core` re-exports these from its `phonetic` module and the
+//! deprecated `langviz_phonetic` shim wraps them directly, so both modules
+//! give identical results by construction. The crate has no Python
+//! dependency; the `parallel` feature (on by default) adds the rayon batch
+//! functions.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+const TIE_BARS: [char; 2] = ['\u{0361}', '\u{035C}'];
+
+/// How a character participates in IPA segmentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IpaChar {
+    /// Starts a new segment
+    Base,
+    /// Attaches to the preceding segment (diacritics, length, aspiration)
+    Modifier,
+    /// Attaches and also pulls the next base symbol into the segment
+    Tie,
+    /// Tone letter; consecutive tone letters form one contour segment
+    Tone,
+    /// Suprasegmental or boundary mark, dropped
+    Skip,
+}
+
+fn classify_ipa_char(c: char) -> IpaChar {
+    match c {
+        c if TIE_BARS.contains(&c) => IpaChar::Tie,
+        c if c.is_whitespace() => IpaChar::Skip,
+        // Stress, syllable and prosodic boundary marks
+        'ˈ' | 'ˌ' | '.' | '|' | '‖' | '‿' => IpaChar::Skip,
+        '\u{02E5}'..='\u{02E9}' | '\u{A712}'..='\u{A716}' => IpaChar::Tone,
+        c if is_combining_mark(c) => IpaChar::Modifier,
+        // Spacing modifier letters (ʰ ʷ ʲ ː ˑ ʼ ˤ ˞ ...)
+        '\u{02B0}'..='\u{02FF}' => IpaChar::Modifier,
+        // Superscript letters (ᵝ ᶣ ⁿ ...)
+        '\u{1D2C}'..='\u{1D6A}' | '\u{1D9B}'..='\u{1DBF}' | '\u{207F}' => IpaChar::Modifier,
+        _ => IpaChar::Base,
+    }
+}
+
+/// Whether `c` is a diacritic or modifier letter that attaches to the
+/// preceding base symbol (tie bars excluded)
+pub fn is_ipa_modifier(c: char) -> bool {
+    classify_ipa_char(c) == IpaChar::Modifier
+}
+
+/// Split an IPA string into phonetic segments.
+///
+/// A segment is a base symbol plus its combining diacritics and modifier
+/// letters; tie bars join two symbols into one segment (t͡ʃ), runs of tone
+/// letters form one contour, and stress and syllable marks are dropped.
+pub fn segment_ipa(ipa: &str) -> Vec<String> {
+    let mut segments: Vec<String> = Vec::new();
+    let mut tied = false;
+
+    for c in ipa.nfc() {
+        let kind = classify_ipa_char(c);
+        let follows_tone = || {
+            segments
+                .last()
+                .and_then(|s| s.chars().last())
+                .is_some_and(|last| classify_ipa_char(last) == IpaChar::Tone)
+        };
+
+        match kind {
+            IpaChar::Skip => tied = false,
+            IpaChar::Base if tied => {
+                if let Some(last) = segments.last_mut() {
+                    last.push(c);
+                }
+                tied = false;
+            }
+            IpaChar::Tone if follows_tone() => {
+                if let Some(last) = segments.last_mut() {
+                    last.push(c);
+                }
+            }
+            IpaChar::Base | IpaChar::Tone => segments.push(c.to_string()),
+            IpaChar::Modifier | IpaChar::Tie => {
+                match segments.last_mut() {
+                    Some(last) => last.push(c),
+                    None => segments.push(c.to_string()),
+                }
+                tied = kind == IpaChar::Tie;
+            }
+        }
+    }
+
+    segments
+}
+
+/// Compute normalized Levenshtein distance between IPA strings
+pub fn phonetic_distance(ipa_a: &str, ipa_b: &str) -> f64 {
+    let segments_a = segment_ipa(ipa_a);
+    let segments_b = segment_ipa(ipa_b);
+    let segments_a: Vec<&str> = segments_a.iter().map(String::as_str).collect();
+    let segments_b: Vec<&str> = segments_b.iter().map(String::as_str).collect();
+
+    let distance = levenshtein(&segments_a, &segments_b);
+    let max_len = segments_a.len().max(segments_b.len()) as f64;
+
+    if max_len == 0.0 {
+        1.0 // Both empty = perfect match
+    } else {
+        1.0 - (distance as f64 / max_len)
+    }
+}
+
+/// Standard Levenshtein distance using dynamic programming
+pub fn levenshtein<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let len_a = a.len();
+    let len_b = b.len();
+
+    if len_a == 0 {
+        return len_b;
+    }
+    if len_b == 0 {
+        return len_a;
+    }
+
+    let mut prev_row: Vec<usize> = (0..=len_b).collect();
+    let mut curr_row = vec![0; len_b + 1];
+
+    for (i, seg_a) in a.iter().enumerate() {
+        curr_row[0] = i + 1;
+
+        for (j, seg_b) in b.iter().enumerate() {
+            let cost = if seg_a == seg_b { 0 } else { 1 };
+
+            curr_row[j + 1] = std::cmp::min(
+                std::cmp::min(curr_row[j] + 1, prev_row[j + 1] + 1),
+                prev_row[j] + cost,
+            );
+        }
+
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+
+    prev_row[len_b]
+}
+
+/// Longest Common Subsequence ratio
+pub fn lcs_ratio(ipa_a: &str, ipa_b: &str) -> f64 {
+    let segments_a = segment_ipa(ipa_a);
+    let segments_b = segment_ipa(ipa_b);
+    let segments_a: Vec<&str> = segments_a.iter().map(String::as_str).collect();
+    let segments_b: Vec<&str> = segments_b.iter().map(String::as_str).collect();
+
+    let lcs_len = lcs_length(&segments_a, &segments_b);
+    let max_len = segments_a.len().max(segments_b.len()) as f64;
+
+    if max_len == 0.0 {
+        1.0
+    } else {
+        lcs_len as f64 / max_len
+    }
+}
+
+/// Compute length of longest common subsequence
+pub fn lcs_length(a: &[&str], b: &[&str]) -> usize {
+    let len_a = a.len();
+    let len_b = b.len();
+
+    let mut dp = vec![vec![0; len_b + 1]; len_a + 1];
+
+    for i in 1..=len_a {
+        for j in 1..=len_b {
+            if a[i - 1] == b[j - 1] {
+                dp[i][j] = dp[i - 1][j - 1] + 1;
+            } else {
+                dp[i][j] = dp[i - 1][j].max(dp[i][j - 1]);
+            }
+        }
+    }
+
+    dp[len_a][len_b]
+}
+
+/// Batch compute phonetic distances for multiple pairs (parallelized)
+#[cfg(feature = "parallel")]
+pub fn batch_phonetic_distance(pairs: &[(String, String)]) -> Vec<f64> {
+    pairs
+        .par_iter()
+        .map(|(a, b)| phonetic_distance(a, b))
+        .collect()
+}
+
+/// Batch compute LCS ratios for multiple pairs (parallelized)
+#[cfg(feature = "parallel")]
+pub fn batch_lcs_ratio(pairs: &[(String, String)]) -> Vec<f64> {
+    pairs.par_iter().map(|(a, b)| lcs_ratio(a, b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_ipa() {
+        assert_eq!(segment_ipa("t͡ʃaːpʰ"), ["t͡ʃ", "aː", "pʰ"]);
+        assert_eq!(segment_ipa("ˈma˥˩.ma"), ["m", "a", "˥˩", "m", "a"]);
+        assert!(is_ipa_modifier('ʰ') && !is_ipa_modifier('\u{0361}'));
+    }
+
+    #[test]
+    fn test_empty_strings() {
+        // Two empty forms are identical under every kernel
+        assert_eq!(phonetic_distance("", ""), 1.0);
+        assert_eq!(lcs_ratio("", ""), 1.0);
+        assert_eq!(phonetic_distance("", "pater"), 0.0);
+        assert_eq!(lcs_ratio("pater", ""), 0.0);
+        assert_eq!(levenshtein::<&str>(&[], &["a", "b"]), 2);
+    }
+}